@@ -11,19 +11,40 @@
 use crate::{
     auth::AuthProvider,
     config::SmcpAgentConfig,
+    dedup::RequestDeduplicator,
     error::{Result, SmcpAgentError},
-    events::AsyncAgentEventHandler,
-    transport::{NotificationMessage, SocketIoTransport},
+    events::{AsyncAgentEventHandler, ErrorContext},
+    roster::OfficeRoster,
+    transport::{AgentConnState, NotificationMessage, SocketIoTransport},
 };
+use futures_util::future::FutureExt;
+use futures_util::stream::{BoxStream, StreamExt};
 use smcp::{
-    events::*, AgentCallData, EnterOfficeReq, GetDesktopReq, GetToolsReq, LeaveOfficeReq,
-    ListRoomReq, ReqId, Role, SMCPTool, SessionInfo, ToolCallReq, SMCP_NAMESPACE,
+    events::*, AgentCallData, DisconnectReason, EnterOfficeReq, GetDesktopReq, GetPromptReq,
+    GetPromptRet, GetPromptsReq, GetResourceTemplatesReq, GetToolsReq, LeaveOfficeReq, ListRoomReq,
+    ReadResourceReq, ReadResourceRet, ReconnectState, ReqId, Role, SMCPPrompt,
+    SMCPResourceTemplate, SMCPTool, ServerCapabilities, SessionInfo, ToolCallReq, ToolCallRet,
+    PROTOCOL_VERSION, SMCP_NAMESPACE,
 };
 use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, watch, RwLock};
 use tracing::{debug, error, info, warn};
 
+/// 通知广播通道容量，订阅者落后超过该数量时丢弃最旧的通知
+const NOTIFICATION_CHANNEL_CAPACITY: usize = 256;
+
+/// 流式工具调用的输出项
+#[derive(Debug, Clone)]
+pub enum ToolCallStreamItem {
+    /// 工具随进度通知上报的部分结果（MCP content 块）
+    Content(Vec<serde_json::Value>),
+    /// 工具调用的最终结果，之后流结束
+    Done(serde_json::Value),
+}
+
 /// 异步SMCP Agent
 pub struct AsyncSmcpAgent {
     transport: Arc<RwLock<Option<SocketIoTransport>>>,
@@ -31,19 +52,81 @@ pub struct AsyncSmcpAgent {
     event_handler: Option<Arc<dyn AsyncAgentEventHandler>>,
     config: SmcpAgentConfig,
     tools_cache: Arc<RwLock<HashMap<String, Vec<SMCPTool>>>>,
+    roster: Arc<RwLock<OfficeRoster>>,
+    last_disconnect: Arc<RwLock<Option<DisconnectReason>>>,
+    reconnect_state: Arc<RwLock<ReconnectState>>,
+    conn_state: Arc<watch::Sender<AgentConnState>>,
+    server_capabilities: Arc<RwLock<Option<ServerCapabilities>>>,
     notification_task: Option<tokio::task::JoinHandle<()>>,
+    notifications: broadcast::Sender<NotificationMessage>,
+    in_office: Arc<AtomicBool>,
+    request_dedup: Arc<RequestDeduplicator>,
+    /// 仅为其 `Drop` 行为而持有
+    _auto_leave: Option<AutoLeaveGuard>,
+}
+
+/// 丢弃时尽力离开办公室的守卫，只由原始实例持有
+///
+/// `Drop` 无法等待，因此只能在当前 tokio 运行时中派生任务发送离开事件，
+/// 运行时已关闭或进程随即退出时离开事件可能不会送达。
+struct AutoLeaveGuard {
+    transport: Arc<RwLock<Option<SocketIoTransport>>>,
+    office_id: String,
+    in_office: Arc<AtomicBool>,
+}
+
+impl Drop for AutoLeaveGuard {
+    fn drop(&mut self) {
+        if !self.in_office.swap(false, Ordering::SeqCst) {
+            return;
+        }
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            warn!(
+                "Agent dropped outside a tokio runtime, cannot leave office {}",
+                self.office_id
+            );
+            return;
+        };
+        let transport = self.transport.clone();
+        let office_id = self.office_id.clone();
+        runtime.spawn(async move {
+            match AsyncSmcpAgent::emit_leave(&transport, &office_id).await {
+                Ok(()) => info!("Left office {} on drop", office_id),
+                Err(e) => warn!("Failed to leave office {} on drop: {}", office_id, e),
+            }
+        });
+    }
 }
 
 impl AsyncSmcpAgent {
     /// 创建新的Agent实例
     pub fn new(auth_provider: impl AuthProvider + 'static, config: SmcpAgentConfig) -> Self {
+        let office_id = auth_provider.get_agent_config().office_id.clone();
+        let roster = OfficeRoster::new(office_id.clone());
+        let transport = Arc::new(RwLock::new(None));
+        let in_office = Arc::new(AtomicBool::new(false));
+        let auto_leave = config.auto_leave_on_drop.then(|| AutoLeaveGuard {
+            transport: Arc::clone(&transport),
+            office_id,
+            in_office: in_office.clone(),
+        });
+        let request_dedup = Arc::new(RequestDeduplicator::new(config.req_id_cache_size));
         Self {
-            transport: Arc::new(RwLock::new(None)),
+            transport,
             auth_provider: Arc::new(auth_provider),
             event_handler: None,
             config,
             tools_cache: Arc::new(RwLock::new(HashMap::new())),
+            roster: Arc::new(RwLock::new(roster)),
+            last_disconnect: Arc::new(RwLock::new(None)),
+            reconnect_state: Arc::new(RwLock::new(ReconnectState::default())),
+            conn_state: Arc::new(watch::channel(AgentConnState::default()).0),
+            server_capabilities: Arc::new(RwLock::new(None)),
             notification_task: None,
+            notifications: broadcast::channel(NOTIFICATION_CHANNEL_CAPACITY).0,
+            in_office,
+            request_dedup,
+            _auto_leave: auto_leave,
         }
     }
 
@@ -59,80 +142,303 @@ impl AsyncSmcpAgent {
         let headers = self.auth_provider.get_connection_headers();
 
         // 创建transport并获取通知接收器
-        let (transport, mut notification_rx) =
-            SocketIoTransport::connect_with_handlers(url, SMCP_NAMESPACE, auth, headers).await?;
+        self.conn_state.send_replace(AgentConnState::Connecting);
+        let (transport, mut notification_rx) = match SocketIoTransport::connect_with_handlers(
+            url,
+            SMCP_NAMESPACE,
+            auth,
+            headers,
+            self.config.max_retries,
+            self.config.reconnect_backoff(),
+        )
+        .await
+        {
+            Ok(connected) => connected,
+            Err(e) => {
+                self.conn_state.send_replace(AgentConnState::Disconnected);
+                return Err(e);
+            }
+        };
+        self.set_conn_state(AgentConnState::Connected);
 
         // 启动通知处理任务
-        let event_handler = self.event_handler.clone();
         let agent_clone = self.clone();
-
         let notification_task = tokio::spawn(async move {
             while let Some(notification) = notification_rx.recv().await {
-                match notification {
-                    NotificationMessage::EnterOffice(data) => {
-                        // Python 的自动行为：收到 enter_office 后自动触发 get_tools
-                        if let Some(ref computer) = data.computer {
-                            if let Ok(tools) = agent_clone.get_tools(computer).await {
-                                if let Some(ref handler) = event_handler {
-                                    let _ = handler
-                                        .on_tools_received(computer, tools, &agent_clone)
-                                        .await;
-                                }
-                            }
-                        }
+                agent_clone.handle_notification(notification).await;
+            }
+        });
 
-                        if let Some(ref handler) = event_handler {
-                            let _ = handler.on_computer_enter_office(data, &agent_clone).await;
-                        }
-                    }
-                    NotificationMessage::LeaveOffice(data) => {
-                        if let Some(ref handler) = event_handler {
-                            let _ = handler.on_computer_leave_office(data, &agent_clone).await;
-                        }
+        self.notification_task = Some(notification_task);
+        *self.transport.write().await = Some(transport);
+
+        info!("Connected to SMCP server at {}", url);
+        Ok(())
+    }
+
+    /// 订阅连接状态
+    ///
+    /// 接收器总是持有最新状态，可用于展示连接状态，或通过
+    /// `wait_for(|s| *s == AgentConnState::Connected)` 等待连接建立后再发起调用。
+    pub fn connection_state(&self) -> watch::Receiver<AgentConnState> {
+        self.conn_state.subscribe()
+    }
+
+    /// 更新连接状态；`Failed` 为终止状态，只能由重新 `connect` 清除
+    fn set_conn_state(&self, state: AgentConnState) {
+        self.conn_state.send_if_modified(|current| {
+            if *current == state || *current == AgentConnState::Failed {
+                false
+            } else {
+                *current = state;
+                true
+            }
+        });
+    }
+
+    /// 订阅服务器通知流，作为实现事件处理器之外的拉取式用法
+    ///
+    /// 只包含订阅之后收到的通知，且在本地处理（成员缓存、自动获取工具等）之前发布。
+    /// 消费过慢而落后超过通道容量时，被覆盖的通知会被跳过并记录警告。
+    pub fn notifications(&self) -> BoxStream<'static, NotificationMessage> {
+        futures_util::stream::unfold(self.notifications.subscribe(), |mut rx| async move {
+            loop {
+                match rx.recv().await {
+                    Ok(notification) => return Some((notification, rx)),
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!(
+                            "Notification stream lagged, skipped {} notifications",
+                            skipped
+                        );
                     }
-                    NotificationMessage::UpdateConfig(data) => {
-                        // Python 的自动行为：收到 update_config 后自动触发 get_tools
-                        if let Ok(tools) = agent_clone.get_tools(&data.computer).await {
-                            if let Some(ref handler) = event_handler {
-                                let _ = handler
-                                    .on_tools_received(&data.computer, tools, &agent_clone)
-                                    .await;
-                            }
-                        }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        })
+        .boxed()
+    }
 
-                        if let Some(ref handler) = event_handler {
-                            let _ = handler.on_computer_update_config(data, &agent_clone).await;
+    /// 处理一条传输层通知：更新本地状态、执行自动行为并分发给事件处理器
+    ///
+    /// 通常由 `connect` 启动的通知任务调用。处理器或自动请求返回的错误会交给 `on_error`。
+    pub async fn handle_notification(&self, notification: NotificationMessage) {
+        // 没有订阅者时发送失败，忽略即可
+        let _ = self.notifications.send(notification.clone());
+
+        match notification {
+            NotificationMessage::EnterOffice(data) => {
+                self.roster.write().await.apply_enter(&data);
+
+                // Python 的自动行为：收到 enter_office 后自动触发 get_tools
+                if let Some(ref computer) = data.computer {
+                    self.auto_fetch_tools(computer).await;
+                }
+
+                if let Some(ref handler) = self.event_handler {
+                    let result = handler.on_computer_enter_office(data, self).await;
+                    self.report_handler_result("on_computer_enter_office", result)
+                        .await;
+                }
+            }
+            NotificationMessage::LeaveOffice(data) => {
+                self.roster.write().await.apply_leave(&data);
+
+                if let Some(ref handler) = self.event_handler {
+                    let result = handler.on_computer_leave_office(data, self).await;
+                    self.report_handler_result("on_computer_leave_office", result)
+                        .await;
+                }
+            }
+            NotificationMessage::UpdateConfig(data) => {
+                // Python 的自动行为：收到 update_config 后自动触发 get_tools
+                self.auto_fetch_tools(&data.computer).await;
+
+                if let Some(ref handler) = self.event_handler {
+                    let result = handler.on_computer_update_config(data, self).await;
+                    self.report_handler_result("on_computer_update_config", result)
+                        .await;
+                }
+            }
+            NotificationMessage::UpdateToolList(data) => {
+                // Python 的自动行为：收到 update_tool_list 后自动触发 get_tools
+                self.auto_fetch_tools(&data.computer).await;
+            }
+            NotificationMessage::UpdateDesktop(computer) => {
+                // Python 的自动行为：收到 update_desktop 后自动触发 get_desktop
+                match self.get_desktop(&computer, None, None).await {
+                    Ok(desktops) => {
+                        if let Some(ref handler) = self.event_handler {
+                            let result =
+                                handler.on_desktop_updated(&computer, desktops, self).await;
+                            self.report_handler_result("on_desktop_updated", result)
+                                .await;
                         }
                     }
-                    NotificationMessage::UpdateToolList(data) => {
-                        // Python 的自动行为：收到 update_tool_list 后自动触发 get_tools
-                        if let Ok(tools) = agent_clone.get_tools(&data.computer).await {
-                            if let Some(ref handler) = event_handler {
-                                let _ = handler
-                                    .on_tools_received(&data.computer, tools, &agent_clone)
-                                    .await;
-                            }
-                        }
+                    Err(e) => {
+                        self.report_error(
+                            &e,
+                            ErrorContext::AutoRequest {
+                                event: CLIENT_GET_DESKTOP,
+                                computer: Some(computer),
+                            },
+                        )
+                        .await;
                     }
-                    NotificationMessage::UpdateDesktop(computer) => {
-                        // Python 的自动行为：收到 update_desktop 后自动触发 get_desktop
-                        if let Ok(desktops) = agent_clone.get_desktop(&computer, None, None).await {
-                            if let Some(ref handler) = event_handler {
-                                let _ = handler
-                                    .on_desktop_updated(&computer, desktops, &agent_clone)
-                                    .await;
-                            }
-                        }
+                }
+            }
+            NotificationMessage::ToolCallResult {
+                computer,
+                req_id,
+                result,
+            } => {
+                if !self.request_dedup.first_response(&req_id) {
+                    debug!("Ignoring duplicate tool call result {}", req_id.as_str());
+                    return;
+                }
+                if let Some(ref handler) = self.event_handler {
+                    let result = handler
+                        .on_tool_call_result(&computer, &req_id, result, self)
+                        .await;
+                    self.report_handler_result("on_tool_call_result", result)
+                        .await;
+                }
+            }
+            NotificationMessage::ToolProgress(data) => {
+                if let Some(ref handler) = self.event_handler {
+                    let result = handler.on_tool_progress(data, self).await;
+                    self.report_handler_result("on_tool_progress", result).await;
+                }
+            }
+            NotificationMessage::ServerHello(capabilities) => {
+                *self.server_capabilities.write().await = Some(capabilities);
+            }
+            NotificationMessage::Connected => {
+                self.set_conn_state(AgentConnState::Connected);
+                {
+                    let mut state = self.reconnect_state.write().await;
+                    if *state != ReconnectState::Failed {
+                        *state = ReconnectState::Connected;
+                    }
+                }
+
+                // 重连后重新初始化成员缓存，弥补断线期间错过的通知
+                let office_id = {
+                    let roster = self.roster.read().await;
+                    roster.is_seeded().then(|| roster.office_id().to_string())
+                };
+                if let Some(office_id) = office_id {
+                    if let Err(e) = self.list_room(&office_id).await {
+                        self.report_error(
+                            &e,
+                            ErrorContext::AutoRequest {
+                                event: SERVER_LIST_ROOM,
+                                computer: None,
+                            },
+                        )
+                        .await;
                     }
                 }
             }
-        });
+            NotificationMessage::Disconnected(reason) => {
+                self.set_conn_state(AgentConnState::Disconnected);
+                *self.last_disconnect.write().await = Some(reason.clone());
+                if let Some(ref handler) = self.event_handler {
+                    let result = handler.on_disconnected(reason, self).await;
+                    self.report_handler_result("on_disconnected", result).await;
+                }
+            }
+            NotificationMessage::TransportError(message) => {
+                self.report_error(
+                    &SmcpAgentError::connection(message),
+                    ErrorContext::Transport,
+                )
+                .await;
+            }
+            NotificationMessage::Reconnecting(attempt) => {
+                self.set_conn_state(AgentConnState::Reconnecting);
+                let mut state = self.reconnect_state.write().await;
+                if *state != ReconnectState::Failed {
+                    *state = ReconnectState::Reconnecting { attempt };
+                }
+            }
+            NotificationMessage::ReconnectExhausted(attempts) => {
+                self.set_conn_state(AgentConnState::Failed);
+                *self.reconnect_state.write().await = ReconnectState::Failed;
+                error!("Gave up reconnecting after {} attempts", attempts);
+                if let Some(ref handler) = self.event_handler {
+                    let result = handler.on_reconnect_exhausted(attempts, self).await;
+                    self.report_handler_result("on_reconnect_exhausted", result)
+                        .await;
+                }
+            }
+        }
+    }
 
-        self.notification_task = Some(notification_task);
-        *self.transport.write().await = Some(transport);
+    /// 自动获取工具列表并通知事件处理器
+    async fn auto_fetch_tools(&self, computer: &str) {
+        match self.get_tools(computer).await {
+            Ok(tools) => {
+                if let Some(ref handler) = self.event_handler {
+                    let result = handler.on_tools_received(computer, tools, self).await;
+                    self.report_handler_result("on_tools_received", result)
+                        .await;
+                }
+            }
+            Err(e) => {
+                self.report_error(
+                    &e,
+                    ErrorContext::AutoRequest {
+                        event: CLIENT_GET_TOOLS,
+                        computer: Some(computer.to_string()),
+                    },
+                )
+                .await;
+            }
+        }
+    }
 
-        info!("Connected to SMCP server at {}", url);
-        Ok(())
+    /// 将处理器返回的错误交给 `on_error`
+    async fn report_handler_result(&self, method: &'static str, result: Result<()>) {
+        if let Err(e) = result {
+            self.report_error(&e, ErrorContext::Handler { method })
+                .await;
+        }
+    }
+
+    async fn report_error(&self, error: &SmcpAgentError, context: ErrorContext) {
+        warn!("Agent error ({:?}): {}", context, error);
+        if let Some(ref handler) = self.event_handler {
+            handler.on_error(error, context).await;
+        }
+    }
+
+    /// 获取重连状态，重连次数耗尽后为 `ReconnectState::Failed` 且不再改变
+    pub async fn reconnect_state(&self) -> ReconnectState {
+        *self.reconnect_state.read().await
+    }
+
+    /// 获取服务器连接时下发的能力，尚未收到时为 `None`
+    pub async fn server_capabilities(&self) -> Option<ServerCapabilities> {
+        self.server_capabilities.read().await.clone()
+    }
+
+    /// 工具调用超时秒数：配置值，且不超过服务器公布的上限
+    async fn tool_call_timeout_secs(&self, requested: u64) -> u64 {
+        let max_timeout = self
+            .server_capabilities
+            .read()
+            .await
+            .as_ref()
+            .and_then(|capabilities| capabilities.max_timeout_secs);
+        match max_timeout {
+            Some(max) => requested.min(max),
+            None => requested,
+        }
+    }
+
+    /// 获取最近一次连接断开的原因（含服务器关闭码）
+    pub async fn last_disconnect_reason(&self) -> Option<DisconnectReason> {
+        self.last_disconnect.read().await.clone()
     }
 
     /// 加入办公室
@@ -142,6 +448,8 @@ impl AsyncSmcpAgent {
             role: Role::Agent,
             name: agent_name.to_string(),
             office_id: office_id.clone(),
+            meta: None,
+            a2c_version: Some(PROTOCOL_VERSION.to_string()),
         };
 
         let transport = self.transport.read().await;
@@ -150,6 +458,7 @@ impl AsyncSmcpAgent {
             .ok_or_else(|| SmcpAgentError::connection("Not connected".to_string()))?;
         let data = serde_json::to_value(req)?;
         transport.emit(SERVER_JOIN_OFFICE, data).await?;
+        self.in_office.store(true, Ordering::SeqCst);
 
         info!("Joined office: {}", office_id);
         Ok(())
@@ -158,19 +467,47 @@ impl AsyncSmcpAgent {
     /// 离开办公室
     pub async fn leave_office(&self) -> Result<()> {
         let office_id = &self.auth_provider.get_agent_config().office_id;
+        Self::emit_leave(&self.transport, office_id).await?;
+        self.in_office.store(false, Ordering::SeqCst);
+
+        info!("Left office: {}", office_id);
+        Ok(())
+    }
+
+    /// 关闭Agent：如已加入办公室则先离开，然后断开连接
+    ///
+    /// 这是结束会话的推荐方式；`auto_leave_on_drop` 仅作尽力而为的兜底。
+    pub async fn close(&mut self) -> Result<()> {
+        if self.in_office.load(Ordering::SeqCst) {
+            self.leave_office().await?;
+        }
+        if let Some(task) = self.notification_task.take() {
+            task.abort();
+        }
+        if let Some(transport) = self.transport.write().await.take() {
+            transport.disconnect().await?;
+        }
+        self.conn_state.send_replace(AgentConnState::Disconnected);
+        info!("Agent closed");
+        Ok(())
+    }
+
+    /// 发送离开办公室事件
+    async fn emit_leave(
+        transport: &RwLock<Option<SocketIoTransport>>,
+        office_id: &str,
+    ) -> Result<()> {
         let req = LeaveOfficeReq {
-            office_id: office_id.clone(),
+            office_id: office_id.to_string(),
+            reason: None,
         };
 
-        let transport = self.transport.read().await;
+        let transport = transport.read().await;
         let transport = transport
             .as_ref()
             .ok_or_else(|| SmcpAgentError::connection("Not connected".to_string()))?;
         let data = serde_json::to_value(req)?;
-        transport.emit(SERVER_LEAVE_OFFICE, data).await?;
-
-        info!("Left office: {}", office_id);
-        Ok(())
+        transport.emit(SERVER_LEAVE_OFFICE, data).await
     }
 
     /// 获取指定Computer的工具列表
@@ -285,6 +622,197 @@ impl AsyncSmcpAgent {
         Ok(desktops)
     }
 
+    /// 获取指定Computer的提示词列表
+    pub async fn get_prompts(&self, computer: &str) -> Result<Vec<SMCPPrompt>> {
+        let agent_config = self.auth_provider.get_agent_config();
+        let req_id = ReqId::new();
+        let req = GetPromptsReq {
+            base: AgentCallData {
+                agent: agent_config.agent.clone(),
+                req_id: req_id.clone(),
+            },
+            computer: computer.to_string(),
+        };
+
+        debug!("Getting prompts from computer: {}", computer);
+
+        let transport = self.transport.read().await;
+        let transport = transport
+            .as_ref()
+            .ok_or_else(|| SmcpAgentError::connection("Not connected".to_string()))?;
+        let data = serde_json::to_value(req)?;
+        let response = transport
+            .call(CLIENT_GET_PROMPTS, data, self.config.default_timeout)
+            .await?;
+
+        // 验证req_id
+        let response_req_id: String = response
+            .get("req_id")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| SmcpAgentError::internal("Missing req_id in response"))?
+            .to_string();
+
+        if response_req_id != req_id.as_str() {
+            return Err(SmcpAgentError::ReqIdMismatch {
+                expected: req_id.as_str().to_string(),
+                actual: response_req_id,
+            });
+        }
+
+        let prompts: Vec<SMCPPrompt> =
+            serde_json::from_value(response.get("prompts").cloned().unwrap_or_default())?;
+
+        info!(
+            "Received {} prompts from computer: {}",
+            prompts.len(),
+            computer
+        );
+        Ok(prompts)
+    }
+
+    /// 获取指定Computer的资源模板列表，用于构造资源URI
+    pub async fn get_resource_templates(
+        &self,
+        computer: &str,
+    ) -> Result<Vec<SMCPResourceTemplate>> {
+        let agent_config = self.auth_provider.get_agent_config();
+        let req_id = ReqId::new();
+        let req = GetResourceTemplatesReq {
+            base: AgentCallData {
+                agent: agent_config.agent.clone(),
+                req_id: req_id.clone(),
+            },
+            computer: computer.to_string(),
+        };
+
+        debug!("Getting resource templates from computer: {}", computer);
+
+        let transport = self.transport.read().await;
+        let transport = transport
+            .as_ref()
+            .ok_or_else(|| SmcpAgentError::connection("Not connected".to_string()))?;
+        let data = serde_json::to_value(req)?;
+        let response = transport
+            .call(
+                CLIENT_GET_RESOURCE_TEMPLATES,
+                data,
+                self.config.default_timeout,
+            )
+            .await?;
+
+        // 验证req_id
+        let response_req_id: String = response
+            .get("req_id")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| SmcpAgentError::internal("Missing req_id in response"))?
+            .to_string();
+
+        if response_req_id != req_id.as_str() {
+            return Err(SmcpAgentError::ReqIdMismatch {
+                expected: req_id.as_str().to_string(),
+                actual: response_req_id,
+            });
+        }
+
+        let templates: Vec<SMCPResourceTemplate> = serde_json::from_value(
+            response
+                .get("resource_templates")
+                .cloned()
+                .unwrap_or_default(),
+        )?;
+
+        info!(
+            "Received {} resource templates from computer: {}",
+            templates.len(),
+            computer
+        );
+        Ok(templates)
+    }
+
+    /// 获取指定Computer上的提示词内容
+    pub async fn get_prompt(
+        &self,
+        computer: &str,
+        name: &str,
+        arguments: Option<HashMap<String, String>>,
+    ) -> Result<GetPromptRet> {
+        let agent_config = self.auth_provider.get_agent_config();
+        let req_id = ReqId::new();
+        let req = GetPromptReq {
+            base: AgentCallData {
+                agent: agent_config.agent.clone(),
+                req_id: req_id.clone(),
+            },
+            computer: computer.to_string(),
+            name: name.to_string(),
+            arguments,
+        };
+
+        debug!("Getting prompt {} from computer: {}", name, computer);
+
+        let transport = self.transport.read().await;
+        let transport = transport
+            .as_ref()
+            .ok_or_else(|| SmcpAgentError::connection("Not connected".to_string()))?;
+        let data = serde_json::to_value(req)?;
+        let response = transport
+            .call(CLIENT_GET_PROMPT, data, self.config.default_timeout)
+            .await?;
+
+        let ret: GetPromptRet = serde_json::from_value(response)?;
+
+        // 验证req_id
+        if ret.req_id != req_id {
+            return Err(SmcpAgentError::ReqIdMismatch {
+                expected: req_id.as_str().to_string(),
+                actual: ret.req_id.as_str().to_string(),
+            });
+        }
+
+        info!("Received prompt {} from computer: {}", name, computer);
+        Ok(ret)
+    }
+
+    /// 读取指定Computer上的资源
+    ///
+    /// 用于取回大结果被替换成的资源链接（`smcp-blob://results/...`），返回内容中的文本为完整结果的 JSON。
+    pub async fn read_resource(&self, computer: &str, uri: &str) -> Result<ReadResourceRet> {
+        let agent_config = self.auth_provider.get_agent_config();
+        let req_id = ReqId::new();
+        let req = ReadResourceReq {
+            base: AgentCallData {
+                agent: agent_config.agent.clone(),
+                req_id: req_id.clone(),
+            },
+            computer: computer.to_string(),
+            uri: uri.to_string(),
+        };
+
+        debug!("Reading resource {} from computer: {}", uri, computer);
+
+        let transport = self.transport.read().await;
+        let transport = transport
+            .as_ref()
+            .ok_or_else(|| SmcpAgentError::connection("Not connected".to_string()))?;
+        let data = serde_json::to_value(req)?;
+        let response = transport
+            .call(CLIENT_READ_RESOURCE, data, self.config.default_timeout)
+            .await?;
+
+        let ret: ReadResourceRet = serde_json::from_value(response)?;
+
+        // 验证req_id
+        if ret.req_id != req_id {
+            return Err(SmcpAgentError::ReqIdMismatch {
+                expected: req_id.as_str().to_string(),
+                actual: ret.req_id.as_str().to_string(),
+            });
+        }
+
+        info!("Received resource {} from computer: {}", uri, computer);
+        Ok(ret)
+    }
+
     /// 调用工具
     pub async fn tool_call(
         &self,
@@ -292,9 +820,148 @@ impl AsyncSmcpAgent {
         tool_name: &str,
         params: serde_json::Value,
     ) -> Result<serde_json::Value> {
-        let agent_config = self.auth_provider.get_agent_config();
+        self.send_tool_call(computer, tool_name, params, None, None, None)
+            .await
+    }
+
+    /// 使用指定 req_id 调用工具
+    ///
+    /// 重传时使用相同的 `req_id`：若该 req_id 仍在最近缓存中，将复用首个调用的结果，
+    /// 不会再次发送请求。
+    pub async fn tool_call_with_req_id(
+        &self,
+        computer: &str,
+        tool_name: &str,
+        params: serde_json::Value,
+        req_id: ReqId,
+    ) -> Result<serde_json::Value> {
+        self.send_tool_call(computer, tool_name, params, None, None, Some(req_id))
+            .await
+    }
+
+    /// 携带幂等键调用工具
+    ///
+    /// 重试同一逻辑操作时使用相同的 `idempotency_key`，即使每次的 req_id 不同，
+    /// Computer 也只执行一次并返回缓存结果。
+    pub async fn tool_call_with_idempotency_key(
+        &self,
+        computer: &str,
+        tool_name: &str,
+        params: serde_json::Value,
+        idempotency_key: &str,
+    ) -> Result<serde_json::Value> {
+        self.send_tool_call(
+            computer,
+            tool_name,
+            params,
+            Some(idempotency_key),
+            None,
+            None,
+        )
+        .await
+    }
+
+    /// 流式调用工具，在最终结果之前逐段产出工具上报的部分结果
+    ///
+    /// 部分结果来自该调用的 `notify:tool_progress` 通知，按到达顺序以
+    /// [`ToolCallStreamItem::Content`] 产出，最后以 [`ToolCallStreamItem::Done`] 产出最终结果并结束。
+    /// 最终结果之后才到达的部分结果会被丢弃，其内容已包含在最终结果中。
+    pub fn tool_call_stream<'a>(
+        &'a self,
+        computer: &'a str,
+        tool_name: &'a str,
+        params: serde_json::Value,
+    ) -> BoxStream<'a, Result<ToolCallStreamItem>> {
         let req_id = ReqId::new();
+        let chunk_req_id = req_id.clone();
+        // 先订阅再发送请求，避免漏掉早到的进度通知
+        let chunks = self.notifications().filter_map(move |notification| {
+            let content = match notification {
+                NotificationMessage::ToolProgress(progress)
+                    if progress.req_id == chunk_req_id && !progress.content.is_empty() =>
+                {
+                    Some(progress.content)
+                }
+                _ => None,
+            };
+            futures_util::future::ready(content)
+        });
+        let call = self
+            .send_tool_call(computer, tool_name, params, None, None, Some(req_id))
+            .boxed();
+
+        futures_util::stream::unfold(Some((chunks, call)), |state| async move {
+            let (mut chunks, mut call) = state?;
+            tokio::select! {
+                biased;
+                Some(content) = chunks.next() => {
+                    Some((Ok(ToolCallStreamItem::Content(content)), Some((chunks, call))))
+                }
+                result = &mut call => Some((result.map(ToolCallStreamItem::Done), None)),
+            }
+        })
+        .boxed()
+    }
+
+    /// 批量调用同一 Computer 上的工具
+    ///
+    /// `calls` 中每项为 `(工具名, 参数, 超时秒数)`。调用并发发出，同时进行中的数量受
+    /// `max_concurrent_tool_calls` 限制；结果按输入顺序返回，单个调用失败不影响其他调用。
+    pub async fn tool_call_batch(
+        &self,
+        computer: &str,
+        calls: Vec<(String, serde_json::Value, i32)>,
+    ) -> Vec<Result<ToolCallRet>> {
+        let tasks = calls
+            .into_iter()
+            .map(|(tool_name, params, timeout)| async move {
+                let timeout_secs = u64::try_from(timeout).unwrap_or(0);
+                let response = self
+                    .send_tool_call(computer, &tool_name, params, None, Some(timeout_secs), None)
+                    .await?;
+                Ok(serde_json::from_value::<ToolCallRet>(response)?)
+            });
+        run_bounded(tasks, self.config.max_concurrent_tool_calls).await
+    }
+
+    async fn send_tool_call(
+        &self,
+        computer: &str,
+        tool_name: &str,
+        params: serde_json::Value,
+        idempotency_key: Option<&str>,
+        timeout_secs: Option<u64>,
+        req_id: Option<ReqId>,
+    ) -> Result<serde_json::Value> {
+        let req_id = req_id.unwrap_or_default();
+        self.request_dedup
+            .run_once(&req_id.clone(), || {
+                self.emit_tool_call(
+                    computer,
+                    tool_name,
+                    params,
+                    idempotency_key,
+                    timeout_secs,
+                    req_id,
+                )
+            })
+            .await
+    }
+
+    async fn emit_tool_call(
+        &self,
+        computer: &str,
+        tool_name: &str,
+        params: serde_json::Value,
+        idempotency_key: Option<&str>,
+        timeout_secs: Option<u64>,
+        req_id: ReqId,
+    ) -> Result<serde_json::Value> {
+        let agent_config = self.auth_provider.get_agent_config();
         let req_id_for_cancel = req_id.clone();
+        let timeout_secs = self
+            .tool_call_timeout_secs(timeout_secs.unwrap_or(self.config.tool_call_timeout))
+            .await;
         let req = ToolCallReq {
             base: AgentCallData {
                 agent: agent_config.agent.clone(),
@@ -303,23 +970,37 @@ impl AsyncSmcpAgent {
             computer: computer.to_string(),
             tool_name: tool_name.to_string(),
             params,
-            timeout: self.config.tool_call_timeout as i32,
+            timeout: i32::try_from(timeout_secs).unwrap_or(i32::MAX),
+            idempotency_key: idempotency_key.map(|key| key.to_string()),
         };
 
         debug!("Calling tool {} on computer: {}", tool_name, computer);
 
-        let transport = self.transport.read().await;
-        let transport = transport
+        let guard = self.transport.read().await;
+        let transport = guard
             .as_ref()
             .ok_or_else(|| SmcpAgentError::connection("Not connected".to_string()))?;
         let data = serde_json::to_value(req.clone())?;
 
-        match transport
-            .call(CLIENT_TOOL_CALL, data, self.config.tool_call_timeout)
-            .await
-        {
+        match transport.call(CLIENT_TOOL_CALL, data, timeout_secs).await {
             Ok(response) => {
                 info!("Tool call successful: {} on {}", tool_name, computer);
+                // 处理器可能再次调用 Agent，先释放传输层锁
+                drop(guard);
+                match serde_json::from_value::<ToolCallRet>(response.clone()) {
+                    Ok(result) => {
+                        self.handle_notification(NotificationMessage::ToolCallResult {
+                            computer: computer.to_string(),
+                            req_id: req_id_for_cancel,
+                            result,
+                        })
+                        .await;
+                    }
+                    Err(e) => warn!(
+                        "Tool call response from {} is not a ToolCallRet: {}",
+                        computer, e
+                    ),
+                }
                 Ok(response)
             }
             Err(SmcpAgentError::Timeout) => {
@@ -366,6 +1047,9 @@ impl AsyncSmcpAgent {
                 req_id: req_id.clone(),
             },
             office_id: office_id.to_string(),
+            limit: None,
+            offset: None,
+            role: None,
         };
 
         debug!("Listing sessions in office: {}", office_id);
@@ -396,6 +1080,11 @@ impl AsyncSmcpAgent {
         let sessions: Vec<SessionInfo> =
             serde_json::from_value(response.get("sessions").cloned().unwrap_or_default())?;
 
+        // 查询自己所在办公室时顺带初始化成员缓存
+        if office_id == self.auth_provider.get_agent_config().office_id {
+            self.roster.write().await.seed(sessions.clone());
+        }
+
         info!(
             "Listed {} sessions in office: {}",
             sessions.len(),
@@ -403,6 +1092,20 @@ impl AsyncSmcpAgent {
         );
         Ok(sessions)
     }
+
+    /// 获取缓存的办公室成员列表
+    ///
+    /// 缓存由进入/离开通知更新，对本办公室调用 `list_room` 时以其结果重置，
+    /// 已初始化的缓存在重连后会自动重新初始化。
+    pub async fn roster(&self) -> Vec<SessionInfo> {
+        self.roster.read().await.sessions()
+    }
+
+    /// 通过 `list_room` 重新初始化办公室成员缓存
+    pub async fn refresh_roster(&self) -> Result<Vec<SessionInfo>> {
+        let office_id = self.auth_provider.get_agent_config().office_id.clone();
+        self.list_room(&office_id).await
+    }
 }
 
 // 实现Clone以便在事件处理器中使用
@@ -414,7 +1117,72 @@ impl Clone for AsyncSmcpAgent {
             event_handler: self.event_handler.clone(),
             config: self.config.clone(),
             tools_cache: self.tools_cache.clone(),
+            roster: self.roster.clone(),
+            last_disconnect: self.last_disconnect.clone(),
+            reconnect_state: self.reconnect_state.clone(),
+            conn_state: self.conn_state.clone(),
+            server_capabilities: self.server_capabilities.clone(),
             notification_task: None, // Note: 任务句柄不克隆，因为它是特定于实例的
+            notifications: self.notifications.clone(),
+            in_office: self.in_office.clone(),
+            request_dedup: self.request_dedup.clone(),
+            _auto_leave: None, // 克隆被丢弃时不应离开办公室
+        }
+    }
+}
+
+/// 以有限并发执行任务，并按输入顺序收集结果
+async fn run_bounded<I, F, T>(tasks: I, limit: usize) -> Vec<T>
+where
+    I: IntoIterator<Item = F>,
+    F: Future<Output = T>,
+{
+    futures_util::stream::iter(tasks)
+        .buffered(limit.max(1))
+        .collect()
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_run_bounded_preserves_order_and_limit() {
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let tasks = (0..6u64).map(|i| {
+            let in_flight = in_flight.clone();
+            let peak = peak.clone();
+            async move {
+                let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(current, Ordering::SeqCst);
+                // 先发出的任务更晚完成
+                tokio::time::sleep(Duration::from_millis(30 - i * 5)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                if i == 2 {
+                    Err(SmcpAgentError::Timeout)
+                } else {
+                    Ok(i)
+                }
+            }
+        });
+
+        let results = run_bounded(tasks, 2).await;
+
+        assert_eq!(results.len(), 6);
+        for (i, result) in results.iter().enumerate() {
+            match result {
+                Ok(value) => assert_eq!(*value, i as u64),
+                Err(e) => {
+                    assert_eq!(i, 2);
+                    assert!(matches!(e, SmcpAgentError::Timeout));
+                }
+            }
         }
+        assert!(peak.load(Ordering::SeqCst) <= 2);
     }
 }
@@ -8,6 +8,10 @@
 * 描述: SMCP Agent配置 / SMCP Agent configuration
 */
 
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
 /// SMCP Agent配置
 #[derive(Debug, Clone)]
 pub struct SmcpAgentConfig {
@@ -17,10 +21,22 @@ pub struct SmcpAgentConfig {
     pub tool_call_timeout: u64,
     /// 是否在收到桌面更新通知时自动拉取桌面
     pub auto_fetch_desktop: bool,
-    /// 连接重试次数
+    /// 断线后的最大重连次数，耗尽后进入 `ReconnectState::Failed` 并触发 `on_reconnect_exhausted`
     pub max_retries: u32,
-    /// 重连间隔（毫秒）
+    /// 首次重连间隔（毫秒），之后按 `reconnect_backoff_multiplier` 指数增长
     pub reconnect_interval: u64,
+    /// 重连间隔上限（毫秒）
+    pub reconnect_backoff_max: u64,
+    /// 每次重连后间隔的增长倍数
+    pub reconnect_backoff_multiplier: f64,
+    /// 重连间隔的随机抖动比例，间隔在 ±该比例内浮动，0 表示不抖动
+    pub reconnect_jitter: f64,
+    /// Agent 被丢弃时是否尽力离开已加入的办公室，推荐显式调用 `close().await`
+    pub auto_leave_on_drop: bool,
+    /// 批量工具调用时同时进行中的最大调用数
+    pub max_concurrent_tool_calls: usize,
+    /// 用于去重的最近 req_id 缓存容量，为 0 时不去重
+    pub req_id_cache_size: usize,
 }
 
 impl Default for SmcpAgentConfig {
@@ -31,6 +47,12 @@ impl Default for SmcpAgentConfig {
             auto_fetch_desktop: true,
             max_retries: 3,
             reconnect_interval: 1000,
+            reconnect_backoff_max: 30_000,
+            reconnect_backoff_multiplier: 2.0,
+            reconnect_jitter: 0.1,
+            auto_leave_on_drop: false,
+            max_concurrent_tool_calls: 8,
+            req_id_cache_size: 256,
         }
     }
 }
@@ -64,6 +86,88 @@ impl SmcpAgentConfig {
         self.reconnect_interval = interval;
         self
     }
+
+    /// 设置指数退避：首次间隔 `base` 毫秒，每次乘以 `multiplier`，不超过 `max` 毫秒
+    pub fn with_backoff(mut self, base: u64, max: u64, multiplier: f64) -> Self {
+        self.reconnect_interval = base;
+        self.reconnect_backoff_max = max;
+        self.reconnect_backoff_multiplier = multiplier;
+        self
+    }
+
+    /// 设置重连间隔的抖动比例，取值范围 [0, 1]
+    pub fn with_jitter(mut self, fraction: f64) -> Self {
+        self.reconnect_jitter = fraction;
+        self
+    }
+
+    /// 当前配置对应的重连退避策略
+    pub fn reconnect_backoff(&self) -> ReconnectBackoff {
+        ReconnectBackoff {
+            base: self.reconnect_interval,
+            max: self.reconnect_backoff_max,
+            multiplier: self.reconnect_backoff_multiplier,
+            jitter: self.reconnect_jitter,
+        }
+    }
+
+    pub fn with_auto_leave_on_drop(mut self, auto_leave: bool) -> Self {
+        self.auto_leave_on_drop = auto_leave;
+        self
+    }
+
+    pub fn with_max_concurrent_tool_calls(mut self, max: usize) -> Self {
+        self.max_concurrent_tool_calls = max;
+        self
+    }
+
+    pub fn with_req_id_cache_size(mut self, size: usize) -> Self {
+        self.req_id_cache_size = size;
+        self
+    }
+}
+
+/// 带抖动的指数退避重连间隔
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReconnectBackoff {
+    /// 首次重连间隔（毫秒）
+    pub base: u64,
+    /// 间隔上限（毫秒）
+    pub max: u64,
+    /// 每次重连后间隔的增长倍数
+    pub multiplier: f64,
+    /// 抖动比例，0 表示不抖动
+    pub jitter: f64,
+}
+
+impl ReconnectBackoff {
+    /// 第 `attempt` 次重连（从 1 开始）前的等待时间
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.delay_with_sample(attempt, random_unit())
+    }
+
+    /// 使用给定的 [0, 1) 随机数计算第 `attempt` 次重连前的等待时间
+    ///
+    /// 抖动后的间隔同样不超过上限。
+    pub fn delay_with_sample(&self, attempt: u32, sample: f64) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let max = self.max as f64;
+        let raw = (self.base as f64 * self.multiplier.max(1.0).powi(exponent)).min(max);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        let millis = (raw * (1.0 + jitter * (2.0 * sample - 1.0))).clamp(0.0, max);
+        Duration::from_millis(millis.round() as u64)
+    }
+
+    /// 从第 1 次重连开始的等待时间序列
+    pub fn delays(&self) -> impl Iterator<Item = Duration> + '_ {
+        (1..).map(|attempt| self.delay(attempt))
+    }
+}
+
+/// [0, 1) 范围内的随机数，每次调用使用新的随机种子
+fn random_unit() -> f64 {
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
 }
 
 #[cfg(test)]
@@ -77,12 +181,74 @@ mod tests {
             .with_tool_call_timeout(30)
             .with_auto_fetch_desktop(false)
             .with_max_retries(5)
-            .with_reconnect_interval(2000);
+            .with_reconnect_interval(2000)
+            .with_auto_leave_on_drop(true)
+            .with_max_concurrent_tool_calls(4)
+            .with_req_id_cache_size(32);
 
         assert_eq!(config.default_timeout, 10);
         assert_eq!(config.tool_call_timeout, 30);
         assert!(!config.auto_fetch_desktop);
         assert_eq!(config.max_retries, 5);
         assert_eq!(config.reconnect_interval, 2000);
+        assert!(config.auto_leave_on_drop);
+        assert_eq!(config.max_concurrent_tool_calls, 4);
+        assert_eq!(config.req_id_cache_size, 32);
+    }
+
+    #[test]
+    fn test_backoff_sequence_without_jitter() {
+        let backoff = SmcpAgentConfig::new()
+            .with_backoff(100, 1000, 2.0)
+            .with_jitter(0.0)
+            .reconnect_backoff();
+
+        let delays: Vec<u64> = backoff
+            .delays()
+            .take(6)
+            .map(|d| d.as_millis() as u64)
+            .collect();
+        assert_eq!(delays, vec![100, 200, 400, 800, 1000, 1000]);
+    }
+
+    #[test]
+    fn test_backoff_fixed_interval() {
+        let backoff = SmcpAgentConfig::new()
+            .with_reconnect_interval(500)
+            .with_backoff(500, 500, 1.0)
+            .with_jitter(0.0)
+            .reconnect_backoff();
+
+        assert!(backoff
+            .delays()
+            .take(4)
+            .all(|d| d == Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn test_backoff_jitter_bounds() {
+        let backoff = SmcpAgentConfig::new()
+            .with_backoff(1000, 10_000, 2.0)
+            .with_jitter(0.5)
+            .reconnect_backoff();
+
+        assert_eq!(
+            backoff.delay_with_sample(1, 0.0),
+            Duration::from_millis(500)
+        );
+        assert_eq!(
+            backoff.delay_with_sample(1, 0.5),
+            Duration::from_millis(1000)
+        );
+        // 抖动后仍不超过上限
+        assert_eq!(
+            backoff.delay_with_sample(10, 0.99),
+            Duration::from_millis(10_000)
+        );
+        for attempt in 1..5 {
+            let delay = backoff.delay(attempt).as_millis() as u64;
+            let raw = 1000u64 << (attempt - 1);
+            assert!(delay >= raw / 2 && delay <= raw * 3 / 2);
+        }
     }
 }
@@ -0,0 +1,225 @@
+/*!
+* 文件名: dedup
+* 作者: JQQ
+* 创建日期: 2025/12/15
+* 最后修改日期: 2025/12/15
+* 版权: 2023 JQQ. All rights reserved.
+* 依赖: tokio
+* 描述: Agent侧按 req_id 的请求去重 / Agent-side request deduplication by req_id
+*/
+
+use crate::error::{Result, SmcpAgentError};
+use serde_json::Value;
+use smcp::ReqId;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::Mutex;
+use tokio::sync::watch;
+
+/// 调用结果的可共享形式，错误只保留描述
+type SharedOutcome = std::result::Result<Value, String>;
+
+#[derive(Default)]
+struct Entry {
+    /// 进行中或已完成调用的结果
+    outcome: Option<watch::Receiver<Option<SharedOutcome>>>,
+    /// 是否已收到过该 req_id 的响应
+    responded: bool,
+}
+
+#[derive(Default)]
+struct Lru {
+    order: VecDeque<String>,
+    entries: HashMap<String, Entry>,
+}
+
+impl Lru {
+    /// 获取或插入条目，并将其标记为最近使用
+    fn touch(&mut self, key: &str, capacity: usize) -> &mut Entry {
+        if self.entries.contains_key(key) {
+            self.order.retain(|k| k != key);
+        } else {
+            while self.order.len() >= capacity {
+                match self.order.pop_front() {
+                    Some(oldest) => {
+                        self.entries.remove(&oldest);
+                    }
+                    None => break,
+                }
+            }
+        }
+        self.order.push_back(key.to_string());
+        self.entries.entry(key.to_string()).or_default()
+    }
+}
+
+/// 最近 req_id 的 LRU 缓存
+///
+/// 同一 req_id 的重复发出调用共享首个调用的结果，不会再次发送到网络；
+/// 同一 req_id 的重复响应只处理一次。容量为 0 时不去重。
+pub struct RequestDeduplicator {
+    capacity: usize,
+    inner: Mutex<Lru>,
+}
+
+impl RequestDeduplicator {
+    /// 创建指定容量的缓存
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            inner: Mutex::new(Lru::default()),
+        }
+    }
+
+    /// 以 req_id 去重执行调用
+    ///
+    /// 首次出现的 req_id 执行 `call`；重复的 req_id 等待并返回首个调用的结果。
+    /// 首个调用失败时，重复调用得到内部错误。
+    pub async fn run_once<F, Fut>(&self, req_id: &ReqId, call: F) -> Result<Value>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Value>>,
+    {
+        if self.capacity == 0 {
+            return call().await;
+        }
+
+        let existing = {
+            let mut lru = self.inner.lock().unwrap();
+            let entry = lru.touch(req_id.as_str(), self.capacity);
+            match &entry.outcome {
+                Some(rx) => Err(rx.clone()),
+                None => {
+                    let (tx, rx) = watch::channel(None);
+                    entry.outcome = Some(rx);
+                    Ok(tx)
+                }
+            }
+        };
+
+        match existing {
+            Ok(tx) => {
+                let result = call().await;
+                let shared = match &result {
+                    Ok(value) => Ok(value.clone()),
+                    Err(e) => Err(e.to_string()),
+                };
+                let _ = tx.send(Some(shared));
+                result
+            }
+            Err(mut rx) => {
+                let outcome = rx
+                    .wait_for(Option::is_some)
+                    .await
+                    .map_err(|_| {
+                        SmcpAgentError::internal(format!(
+                            "Original request {} was abandoned",
+                            req_id.as_str()
+                        ))
+                    })?
+                    .clone();
+                match outcome {
+                    Some(Ok(value)) => Ok(value),
+                    Some(Err(e)) => Err(SmcpAgentError::internal(e)),
+                    None => unreachable!("wait_for only returns once the outcome is set"),
+                }
+            }
+        }
+    }
+
+    /// 记录一次响应，该 req_id 首次响应时返回 `true`，重复响应返回 `false`
+    pub fn first_response(&self, req_id: &ReqId) -> bool {
+        if self.capacity == 0 {
+            return true;
+        }
+        let mut lru = self.inner.lock().unwrap();
+        let entry = lru.touch(req_id.as_str(), self.capacity);
+        !std::mem::replace(&mut entry.responded, true)
+    }
+}
+
+impl std::fmt::Debug for RequestDeduplicator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RequestDeduplicator")
+            .field("capacity", &self.capacity)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_duplicate_req_id_emits_once() {
+        let dedup = RequestDeduplicator::new(16);
+        let emissions = Arc::new(AtomicUsize::new(0));
+        let req_id = ReqId::from_string("req-dup".to_string());
+
+        let emit = || {
+            let emissions = emissions.clone();
+            move || async move {
+                emissions.fetch_add(1, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                Ok(serde_json::json!({"isError": false}))
+            }
+        };
+
+        let (first, second) = tokio::join!(
+            dedup.run_once(&req_id, emit()),
+            dedup.run_once(&req_id, emit())
+        );
+
+        assert_eq!(emissions.load(Ordering::SeqCst), 1);
+        assert_eq!(first.unwrap(), second.unwrap());
+
+        // 其他 req_id 正常发出
+        dedup
+            .run_once(&ReqId::from_string("req-other".to_string()), emit())
+            .await
+            .unwrap();
+        assert_eq!(emissions.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_shares_failure() {
+        let dedup = RequestDeduplicator::new(16);
+        let req_id = ReqId::from_string("req-fail".to_string());
+
+        let first = dedup
+            .run_once(&req_id, || async { Err(SmcpAgentError::Timeout) })
+            .await;
+        assert!(matches!(first, Err(SmcpAgentError::Timeout)));
+
+        let second = dedup.run_once(&req_id, || async { Ok(Value::Null) }).await;
+        assert!(matches!(second, Err(SmcpAgentError::Internal(_))));
+    }
+
+    #[test]
+    fn test_first_response_and_eviction() {
+        let dedup = RequestDeduplicator::new(2);
+        let a = ReqId::from_string("a".to_string());
+        let b = ReqId::from_string("b".to_string());
+        let c = ReqId::from_string("c".to_string());
+
+        assert!(dedup.first_response(&a));
+        assert!(!dedup.first_response(&a));
+        assert!(dedup.first_response(&b));
+        assert!(dedup.first_response(&c));
+
+        // a 已被淘汰，再次出现视为首次响应
+        assert!(dedup.first_response(&a));
+        assert!(!dedup.first_response(&c));
+    }
+
+    #[test]
+    fn test_zero_capacity_disables_dedup() {
+        let dedup = RequestDeduplicator::new(0);
+        let a = ReqId::from_string("a".to_string());
+        assert!(dedup.first_response(&a));
+        assert!(dedup.first_response(&a));
+    }
+}
@@ -10,9 +10,25 @@
 
 use async_trait::async_trait;
 use smcp::{
-    EnterOfficeNotification, LeaveOfficeNotification, SMCPTool, UpdateMCPConfigNotification,
+    DisconnectReason, EnterOfficeNotification, LeaveOfficeNotification, ReqId, SMCPTool,
+    ToolCallRet, ToolProgressNotification, UpdateMCPConfigNotification,
 };
 
+/// 错误发生时的上下文
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorContext {
+    /// 事件处理器方法返回错误，`method` 为方法名
+    Handler { method: &'static str },
+    /// 收到通知后自动发起的请求失败，`event` 为请求事件名，`computer` 为请求针对的 Computer，
+    /// 办公室级请求（如重连后刷新成员列表）为 `None`
+    AutoRequest {
+        event: &'static str,
+        computer: Option<String>,
+    },
+    /// 传输层的可恢复错误
+    Transport,
+}
+
 /// 异步事件处理器trait
 #[async_trait]
 pub trait AsyncAgentEventHandler: Send + Sync {
@@ -71,6 +87,50 @@ pub trait AsyncAgentEventHandler: Send + Sync {
         );
         Ok(())
     }
+
+    /// 当工具调用往返完成时触发，`req_id` 为发起调用时生成的请求 ID
+    async fn on_tool_call_result(
+        &self,
+        _computer: &str,
+        _req_id: &ReqId,
+        _result: ToolCallRet,
+        _agent: &AsyncSmcpAgent,
+    ) -> Result<(), crate::error::SmcpAgentError> {
+        Ok(())
+    }
+
+    /// 当工具调用上报进度时触发，`data.req_id` 对应发起调用的请求
+    async fn on_tool_progress(
+        &self,
+        data: ToolProgressNotification,
+        _agent: &AsyncSmcpAgent,
+    ) -> Result<(), crate::error::SmcpAgentError> {
+        tracing::debug!("Tool call progress: {:?}", data);
+        Ok(())
+    }
+
+    /// 当连接断开时触发，携带服务器的关闭码
+    async fn on_disconnected(
+        &self,
+        reason: DisconnectReason,
+        _agent: &AsyncSmcpAgent,
+    ) -> Result<(), crate::error::SmcpAgentError> {
+        tracing::info!("Disconnected from server: {:?}", reason);
+        Ok(())
+    }
+
+    /// 当重连次数耗尽、连接进入 `Failed` 状态时触发，`attempts` 为已尝试的重连次数
+    async fn on_reconnect_exhausted(
+        &self,
+        attempts: u32,
+        _agent: &AsyncSmcpAgent,
+    ) -> Result<(), crate::error::SmcpAgentError> {
+        tracing::error!("Reconnect exhausted after {} attempts", attempts);
+        Ok(())
+    }
+
+    /// 当事件处理器返回错误或传输层出现可恢复错误时触发
+    async fn on_error(&self, _error: &crate::error::SmcpAgentError, _context: ErrorContext) {}
 }
 
 /// 同步事件处理器trait
@@ -130,6 +190,50 @@ pub trait AgentEventHandler: Send + Sync {
         );
         Ok(())
     }
+
+    /// 当工具调用往返完成时触发，`req_id` 为发起调用时生成的请求 ID
+    fn on_tool_call_result(
+        &self,
+        _computer: &str,
+        _req_id: &ReqId,
+        _result: ToolCallRet,
+        _agent: &SyncSmcpAgent,
+    ) -> Result<(), crate::error::SmcpAgentError> {
+        Ok(())
+    }
+
+    /// 当工具调用上报进度时触发，`data.req_id` 对应发起调用的请求
+    fn on_tool_progress(
+        &self,
+        data: ToolProgressNotification,
+        _agent: &SyncSmcpAgent,
+    ) -> Result<(), crate::error::SmcpAgentError> {
+        tracing::debug!("Tool call progress: {:?}", data);
+        Ok(())
+    }
+
+    /// 当连接断开时触发，携带服务器的关闭码
+    fn on_disconnected(
+        &self,
+        reason: DisconnectReason,
+        _agent: &SyncSmcpAgent,
+    ) -> Result<(), crate::error::SmcpAgentError> {
+        tracing::info!("Disconnected from server: {:?}", reason);
+        Ok(())
+    }
+
+    /// 当重连次数耗尽、连接进入 `Failed` 状态时触发，`attempts` 为已尝试的重连次数
+    fn on_reconnect_exhausted(
+        &self,
+        attempts: u32,
+        _agent: &SyncSmcpAgent,
+    ) -> Result<(), crate::error::SmcpAgentError> {
+        tracing::error!("Reconnect exhausted after {} attempts", attempts);
+        Ok(())
+    }
+
+    /// 当事件处理器返回错误或传输层出现可恢复错误时触发
+    fn on_error(&self, _error: &crate::error::SmcpAgentError, _context: ErrorContext) {}
 }
 
 // 前向声明，避免循环依赖
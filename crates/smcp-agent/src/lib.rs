@@ -11,15 +11,20 @@
 pub mod async_agent;
 pub mod auth;
 pub mod config;
+pub mod dedup;
 pub mod error;
 pub mod events;
+pub mod roster;
 pub mod sync_agent;
 pub mod transport;
 
 // 重新导出主要类型
-pub use async_agent::AsyncSmcpAgent;
+pub use async_agent::{AsyncSmcpAgent, ToolCallStreamItem};
 pub use auth::{AuthProvider, DefaultAuthProvider};
-pub use config::SmcpAgentConfig;
+pub use config::{ReconnectBackoff, SmcpAgentConfig};
 pub use error::{Result, SmcpAgentError};
-pub use events::{AgentEventHandler, AsyncAgentEventHandler};
+pub use events::{AgentEventHandler, AsyncAgentEventHandler, ErrorContext};
+pub use roster::OfficeRoster;
+pub use smcp::ReconnectState;
 pub use sync_agent::SyncSmcpAgent;
+pub use transport::AgentConnState;
@@ -0,0 +1,180 @@
+/*!
+* 文件名: roster
+* 作者: JQQ
+* 创建日期: 2025/12/15
+* 最后修改日期: 2025/12/15
+* 版权: 2023 JQQ. All rights reserved.
+* 依赖: None
+* 描述: Agent侧办公室成员缓存 / Agent-side office roster cache
+*/
+
+use smcp::{EnterOfficeNotification, LeaveOfficeNotification, Role, SessionInfo};
+
+/// 办公室成员缓存
+///
+/// 始终跟踪本办公室的进入/离开通知，可由 `list_room` 的结果初始化。
+/// 通过通知加入的成员无法得知 sid，其 sid 为空字符串。
+#[derive(Debug, Clone, Default)]
+pub struct OfficeRoster {
+    office_id: String,
+    sessions: Vec<SessionInfo>,
+    seeded: bool,
+}
+
+impl OfficeRoster {
+    /// 创建指定办公室的空缓存
+    pub fn new(office_id: impl Into<String>) -> Self {
+        Self {
+            office_id: office_id.into(),
+            ..Default::default()
+        }
+    }
+
+    /// 使用 `list_room` 的结果重置缓存
+    pub fn seed(&mut self, sessions: Vec<SessionInfo>) {
+        self.sessions = sessions;
+        self.seeded = true;
+    }
+
+    /// 是否已由 `list_room` 初始化
+    pub fn is_seeded(&self) -> bool {
+        self.seeded
+    }
+
+    /// 缓存对应的办公室ID
+    pub fn office_id(&self) -> &str {
+        &self.office_id
+    }
+
+    /// 当前成员列表
+    pub fn sessions(&self) -> Vec<SessionInfo> {
+        self.sessions.clone()
+    }
+
+    /// 应用进入办公室通知
+    pub fn apply_enter(&mut self, data: &EnterOfficeNotification) {
+        if !self.tracks(&data.office_id) {
+            return;
+        }
+        for (role, name) in Self::members(&data.computer, &data.agent) {
+            if !self.contains(&role, name) {
+                self.sessions.push(SessionInfo {
+                    sid: String::new(),
+                    name: name.clone(),
+                    role,
+                    office_id: data.office_id.clone(),
+                });
+            }
+        }
+    }
+
+    /// 应用离开办公室通知
+    pub fn apply_leave(&mut self, data: &LeaveOfficeNotification) {
+        if !self.tracks(&data.office_id) {
+            return;
+        }
+        for (role, name) in Self::members(&data.computer, &data.agent) {
+            self.sessions
+                .retain(|session| !(session.role == role && &session.name == name));
+        }
+    }
+
+    fn tracks(&self, office_id: &str) -> bool {
+        self.office_id == office_id
+    }
+
+    fn contains(&self, role: &Role, name: &str) -> bool {
+        self.sessions
+            .iter()
+            .any(|session| &session.role == role && session.name == name)
+    }
+
+    fn members<'a>(
+        computer: &'a Option<String>,
+        agent: &'a Option<String>,
+    ) -> impl Iterator<Item = (Role, &'a String)> {
+        computer
+            .iter()
+            .map(|name| (Role::Computer, name))
+            .chain(agent.iter().map(|name| (Role::Agent, name)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(name: &str, role: Role) -> SessionInfo {
+        SessionInfo {
+            sid: format!("sid-{}", name),
+            name: name.to_string(),
+            role,
+            office_id: "office1".to_string(),
+        }
+    }
+
+    fn seeded_roster() -> OfficeRoster {
+        let mut roster = OfficeRoster::new("office1");
+        roster.seed(vec![session("agent1", Role::Agent)]);
+        roster
+    }
+
+    #[test]
+    fn test_roster_tracks_enter_and_leave() {
+        let mut roster = seeded_roster();
+
+        roster.apply_enter(&EnterOfficeNotification {
+            office_id: "office1".to_string(),
+            computer: Some("computer1".to_string()),
+            agent: None,
+        });
+        let sessions = roster.sessions();
+        assert_eq!(sessions.len(), 2);
+        assert!(sessions
+            .iter()
+            .any(|s| s.name == "computer1" && s.role == Role::Computer));
+
+        // 重复通知不应产生重复成员
+        roster.apply_enter(&EnterOfficeNotification {
+            office_id: "office1".to_string(),
+            computer: Some("computer1".to_string()),
+            agent: None,
+        });
+        assert_eq!(roster.sessions().len(), 2);
+
+        roster.apply_leave(&LeaveOfficeNotification {
+            office_id: "office1".to_string(),
+            computer: Some("computer1".to_string()),
+            agent: None,
+            reason: None,
+        });
+        let sessions = roster.sessions();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].name, "agent1");
+    }
+
+    #[test]
+    fn test_unseeded_roster_follows_notifications() {
+        let mut roster = OfficeRoster::new("office1");
+        roster.apply_enter(&EnterOfficeNotification {
+            office_id: "office1".to_string(),
+            computer: Some("computer1".to_string()),
+            agent: None,
+        });
+        assert!(!roster.is_seeded());
+        assert_eq!(roster.sessions().len(), 1);
+        assert_eq!(roster.sessions()[0].role, Role::Computer);
+        assert!(roster.sessions()[0].sid.is_empty());
+    }
+
+    #[test]
+    fn test_roster_ignores_other_offices() {
+        let mut roster = seeded_roster();
+        roster.apply_enter(&EnterOfficeNotification {
+            office_id: "office2".to_string(),
+            computer: Some("computer2".to_string()),
+            agent: None,
+        });
+        assert_eq!(roster.sessions().len(), 1);
+    }
+}
@@ -12,11 +12,25 @@ use crate::{
     auth::AuthProvider,
     config::SmcpAgentConfig,
     error::{Result, SmcpAgentError},
+    events::{AgentEventHandler, AsyncAgentEventHandler, ErrorContext},
+    transport::NotificationMessage,
     AsyncSmcpAgent,
 };
-use smcp::{SMCPTool, SessionInfo};
+use async_trait::async_trait;
+use smcp::{ReconnectState, SMCPTool, SessionInfo};
+use std::sync::Arc;
 use tokio::runtime::Runtime;
 
+/// 将异步Agent报告的错误转交给同步处理器的 `on_error`
+struct SyncErrorBridge(Arc<dyn AgentEventHandler>);
+
+#[async_trait]
+impl AsyncAgentEventHandler for SyncErrorBridge {
+    async fn on_error(&self, error: &SmcpAgentError, context: ErrorContext) {
+        self.0.on_error(error, context);
+    }
+}
+
 /// 同步SMCP Agent
 pub struct SyncSmcpAgent {
     runtime: Runtime,
@@ -40,6 +54,18 @@ impl SyncSmcpAgent {
         })
     }
 
+    /// 设置同步事件处理器，需在 `connect` 之前调用
+    ///
+    /// 事件处理器返回的错误、自动请求失败与传输层错误会交给处理器的 `on_error`。
+    /// 其余回调需要在运行时中访问同步Agent，目前不会被调用。
+    pub fn with_event_handler(self, handler: impl AgentEventHandler + 'static) -> Self {
+        let bridge = SyncErrorBridge(Arc::new(handler));
+        Self {
+            runtime: self.runtime,
+            async_agent: self.async_agent.with_event_handler(bridge),
+        }
+    }
+
     /// 连接到服务器
     pub fn connect(&mut self, url: &str) -> Result<()> {
         self.runtime.block_on(self.async_agent.connect(url))
@@ -56,6 +82,13 @@ impl SyncSmcpAgent {
         self.runtime.block_on(self.async_agent.leave_office())
     }
 
+    /// 关闭Agent：如已加入办公室则先离开，然后断开连接
+    ///
+    /// 同步Agent被丢弃时运行时已不可用，`auto_leave_on_drop` 不会生效，请显式调用此方法。
+    pub fn close(&mut self) -> Result<()> {
+        self.runtime.block_on(self.async_agent.close())
+    }
+
     /// 获取指定Computer的工具列表
     pub fn get_tools(&self, computer: &str) -> Result<Vec<SMCPTool>> {
         self.runtime.block_on(self.async_agent.get_tools(computer))
@@ -83,8 +116,46 @@ impl SyncSmcpAgent {
             .block_on(self.async_agent.tool_call(computer, tool_name, params))
     }
 
+    /// 携带幂等键调用工具
+    pub fn tool_call_with_idempotency_key(
+        &self,
+        computer: &str,
+        tool_name: &str,
+        params: serde_json::Value,
+        idempotency_key: &str,
+    ) -> Result<serde_json::Value> {
+        self.runtime
+            .block_on(self.async_agent.tool_call_with_idempotency_key(
+                computer,
+                tool_name,
+                params,
+                idempotency_key,
+            ))
+    }
+
     /// 列出房间内的所有会话
     pub fn list_room(&self, office_id: &str) -> Result<Vec<SessionInfo>> {
         self.runtime.block_on(self.async_agent.list_room(office_id))
     }
+
+    /// 处理一条传输层通知，参见 [`AsyncSmcpAgent::handle_notification`]
+    pub fn handle_notification(&self, notification: NotificationMessage) {
+        self.runtime
+            .block_on(self.async_agent.handle_notification(notification))
+    }
+
+    /// 获取重连状态
+    pub fn reconnect_state(&self) -> ReconnectState {
+        self.runtime.block_on(self.async_agent.reconnect_state())
+    }
+
+    /// 获取缓存的办公室成员列表
+    pub fn roster(&self) -> Vec<SessionInfo> {
+        self.runtime.block_on(self.async_agent.roster())
+    }
+
+    /// 重新初始化办公室成员缓存
+    pub fn refresh_roster(&self) -> Result<Vec<SessionInfo>> {
+        self.runtime.block_on(self.async_agent.refresh_roster())
+    }
 }
@@ -8,23 +8,42 @@
 * 描述: SMCP Agent传输层实现 / SMCP Agent transport layer implementation
 */
 
+use crate::config::ReconnectBackoff;
 use crate::error::{Result, SmcpAgentError};
 use futures_util::FutureExt;
 use rust_socketio::{
-    asynchronous::{Client, ClientBuilder},
+    asynchronous::{Client, ClientBuilder, ReconnectSettings},
     Event, Payload,
 };
 use serde_json::Value;
 use smcp::events::*;
+pub use smcp::handshake::disconnect_reason_from_payload;
+use smcp::{ReconnectState, ReconnectTracker};
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::{mpsc, oneshot, Mutex};
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 /// 事件处理器类型
 pub type EventHandler = Box<dyn FnMut(Payload, Client) + Send + Sync>;
 
+/// Agent 连接状态
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AgentConnState {
+    /// 未连接或已主动关闭
+    #[default]
+    Disconnected,
+    /// 正在建立连接
+    Connecting,
+    /// 已连接
+    Connected,
+    /// 断线后正在重连
+    Reconnecting,
+    /// 重连次数耗尽，不再重连
+    Failed,
+}
+
 /// 通知事件消息
 #[derive(Debug, Clone)]
 pub enum NotificationMessage {
@@ -33,6 +52,35 @@ pub enum NotificationMessage {
     UpdateConfig(smcp::UpdateMCPConfigNotification),
     UpdateToolList(smcp::UpdateToolListNotification),
     UpdateDesktop(String), // computer name
+    ToolProgress(smcp::ToolProgressNotification),
+    // 工具调用往返完成，由 Agent 在收到 ack 后产生
+    ToolCallResult {
+        computer: String,
+        req_id: smcp::ReqId,
+        result: smcp::ToolCallRet,
+    },
+    ServerHello(smcp::ServerCapabilities),
+    Connected, // 连接或重连成功
+    Disconnected(smcp::DisconnectReason),
+    TransportError(String),  // 可恢复的传输层错误
+    Reconnecting(u32),       // 第 n 次重连尝试
+    ReconnectExhausted(u32), // 重连次数耗尽，携带已尝试次数
+}
+
+/// 构建携带协议版本的握手地址，命名空间由 Socket.IO 连接包单独指定
+fn handshake_url(url: &str) -> String {
+    smcp::build_handshake_url(url, "", smcp::PROTOCOL_VERSION, None)
+}
+
+/// 将连接失败转为错误，服务器因协议版本不兼容（4008）拒绝握手时给出明确原因
+fn connect_error(error: rust_socketio::Error) -> SmcpAgentError {
+    match smcp::extract_4008_from_error(&error.to_string()) {
+        Some(payload) => SmcpAgentError::connection(format!(
+            "Protocol version mismatch ({}): {}",
+            payload.code, payload.message
+        )),
+        None => SmcpAgentError::connection(format!("Failed to connect: {}", error)),
+    }
 }
 
 /// Socket.IO传输层
@@ -49,7 +97,7 @@ impl SocketIoTransport {
         auth: Option<Value>,
         headers: HashMap<String, String>,
     ) -> Result<(Self, mpsc::UnboundedReceiver<NotificationMessage>)> {
-        let mut builder = ClientBuilder::new(url);
+        let mut builder = ClientBuilder::new(handshake_url(url));
 
         // 设置命名空间
         if !namespace.is_empty() {
@@ -69,10 +117,7 @@ impl SocketIoTransport {
         let (_tx, rx) = mpsc::unbounded_channel();
 
         // 连接服务器
-        let client = builder
-            .connect()
-            .await
-            .map_err(|e| SmcpAgentError::connection(format!("Failed to connect: {}", e)))?;
+        let client = builder.connect().await.map_err(connect_error)?;
 
         info!(
             "Connected to SMCP server at {} with namespace {}",
@@ -89,18 +134,117 @@ impl SocketIoTransport {
     }
 
     /// 创建新的传输层实例并注册事件处理器
+    ///
+    /// 断线后最多重连 `max_reconnect_attempts` 次，每次重连前按 `backoff` 等待，
+    /// 耗尽后停止重连并发送 `ReconnectExhausted`。
     pub async fn connect_with_handlers(
         url: &str,
         namespace: &str,
         auth: Option<Value>,
         headers: HashMap<String, String>,
+        max_reconnect_attempts: u32,
+        backoff: ReconnectBackoff,
     ) -> Result<(Self, mpsc::UnboundedReceiver<NotificationMessage>)> {
-        let mut builder = ClientBuilder::new(url);
+        let mut builder = ClientBuilder::new(handshake_url(url));
 
         // 注册on_any处理器来捕获所有事件
         let (tx, rx) = mpsc::unbounded_channel();
         let tx = Arc::new(tx);
 
+        let tracker = Arc::new(std::sync::Mutex::new(ReconnectTracker::new(Some(
+            max_reconnect_attempts,
+        ))));
+        let client_slot: Arc<std::sync::Mutex<Option<Client>>> =
+            Arc::new(std::sync::Mutex::new(None));
+
+        // 连接或重连成功时通知上层
+        let connect_tx = tx.clone();
+        let connect_tracker = tracker.clone();
+        builder = builder.on(Event::Connect, move |_payload, _client| {
+            connect_tracker.lock().unwrap().on_connected();
+            let _ = connect_tx.send(NotificationMessage::Connected);
+            Box::pin(async {})
+        });
+
+        // 每次重连前计数，超过上限后停止重连。底层上限多留一次，
+        // 以便在第 max+1 次回调时确认前 max 次均已失败。底层不再额外等待，
+        // 重连间隔由回调按退避策略控制
+        let reconnect_tx = tx.clone();
+        let reconnect_slot = client_slot.clone();
+        builder = builder
+            .reconnect(true)
+            .reconnect_delay(0, 0)
+            .max_reconnect_attempts(
+                max_reconnect_attempts.saturating_add(1).min(u8::MAX as u32) as u8
+            )
+            .on_reconnect(move || {
+                let mut tracker = tracker.lock().unwrap();
+                let already_failed = tracker.state() == ReconnectState::Failed;
+                let mut delay = Duration::ZERO;
+                match tracker.on_attempt() {
+                    ReconnectState::Reconnecting { attempt } => {
+                        delay = backoff.delay(attempt);
+                        warn!(
+                            "Reconnecting (attempt {}/{}) in {:?}",
+                            attempt, max_reconnect_attempts, delay
+                        );
+                        let _ = reconnect_tx.send(NotificationMessage::Reconnecting(attempt));
+                    }
+                    ReconnectState::Failed if !already_failed => {
+                        let _ = reconnect_tx.send(NotificationMessage::ReconnectExhausted(
+                            tracker.failed_attempts(),
+                        ));
+                        if let Some(client) = reconnect_slot.lock().unwrap().clone() {
+                            tokio::spawn(async move {
+                                if let Err(e) = client.disconnect().await {
+                                    error!("Failed to stop reconnecting: {}", e);
+                                }
+                            });
+                        }
+                    }
+                    _ => {}
+                }
+                Box::pin(async move {
+                    tokio::time::sleep(delay).await;
+                    ReconnectSettings::new()
+                })
+            });
+
+        // 连接断开时上报关闭码，并按策略停止自动重连
+        let close_tx = tx.clone();
+        builder = builder.on(Event::Close, move |payload, client| {
+            let reason = disconnect_reason_from_payload(payload);
+            let should_reconnect = reason.should_reconnect();
+            let _ = close_tx.send(NotificationMessage::Disconnected(reason));
+            Box::pin(async move {
+                if !should_reconnect {
+                    if let Err(e) = client.disconnect().await {
+                        error!("Failed to stop reconnecting: {}", e);
+                    }
+                }
+            })
+        });
+
+        // 传输层错误不会中断连接，交给上层观察
+        let error_tx = tx.clone();
+        builder = builder.on(Event::Error, move |payload, _client| {
+            let message = match payload {
+                Payload::Text(values, _) => values
+                    .first()
+                    .map(|v| {
+                        v.as_str()
+                            .map(str::to_string)
+                            .unwrap_or_else(|| v.to_string())
+                    })
+                    .unwrap_or_default(),
+                #[allow(deprecated)]
+                Payload::String(text, _) => text,
+                Payload::Binary(..) => "binary error payload".to_string(),
+            };
+            let _ = error_tx.send(NotificationMessage::TransportError(message));
+            Box::pin(async {})
+        });
+
         builder = builder.on_any(move |event, payload, _client| {
             let event_str = match event {
                 Event::Custom(s) => s,
@@ -168,6 +312,31 @@ impl SocketIoTransport {
                             }
                         }
                     }
+                    NOTIFY_TOOL_PROGRESS => {
+                        if let Payload::Text(values, _) = payload {
+                            if let Some(value) = values.into_iter().next() {
+                                if let Ok(notification) =
+                                    serde_json::from_value::<smcp::ToolProgressNotification>(value)
+                                {
+                                    debug!("Tool call progress: {:?}", notification);
+                                    let _ =
+                                        tx.send(NotificationMessage::ToolProgress(notification));
+                                }
+                            }
+                        }
+                    }
+                    NOTIFY_SERVER_HELLO => {
+                        if let Payload::Text(values, _) = payload {
+                            if let Some(value) = values.into_iter().next() {
+                                if let Ok(capabilities) =
+                                    serde_json::from_value::<smcp::ServerCapabilities>(value)
+                                {
+                                    info!("Received server capabilities: {:?}", capabilities);
+                                    let _ = tx.send(NotificationMessage::ServerHello(capabilities));
+                                }
+                            }
+                        }
+                    }
                     NOTIFY_UPDATE_DESKTOP => {
                         if let Payload::Text(values, _) = payload {
                             if let Some(value) = values.into_iter().next() {
@@ -210,10 +379,8 @@ impl SocketIoTransport {
         }
 
         // 连接服务器
-        let client = builder
-            .connect()
-            .await
-            .map_err(|e| SmcpAgentError::connection(format!("Failed to connect: {}", e)))?;
+        let client = builder.connect().await.map_err(connect_error)?;
+        *client_slot.lock().unwrap() = Some(client.clone());
 
         info!(
             "Connected to SMCP server at {} with namespace {} and handlers",
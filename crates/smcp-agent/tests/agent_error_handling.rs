@@ -8,7 +8,11 @@
 * 描述: SMCP Agent错误处理和重连测试 / SMCP Agent error handling and reconnection tests
 */
 
-use smcp_agent::{AsyncSmcpAgent, DefaultAuthProvider, SmcpAgentConfig, SmcpAgentError};
+use smcp_agent::{
+    transport::NotificationMessage, AsyncAgentEventHandler, AsyncSmcpAgent, DefaultAuthProvider,
+    ErrorContext, ReconnectState, SmcpAgentConfig, SmcpAgentError,
+};
+use std::sync::{Arc, Mutex};
 mod common;
 use common::*;
 
@@ -130,3 +134,151 @@ async fn test_agent_concurrent_error_handling() {
     // Agent应该仍然可用
     // Agent应该仍然可用
 }
+
+/// 离开办公室处理器总是失败，并记录 on_error 的调用
+struct FailingLeaveHandler {
+    errors: Arc<Mutex<Vec<(String, ErrorContext)>>>,
+}
+
+#[async_trait::async_trait]
+impl AsyncAgentEventHandler for FailingLeaveHandler {
+    async fn on_computer_leave_office(
+        &self,
+        _data: smcp::LeaveOfficeNotification,
+        _agent: &AsyncSmcpAgent,
+    ) -> Result<(), SmcpAgentError> {
+        Err(SmcpAgentError::internal("leave handler failed"))
+    }
+
+    async fn on_error(&self, error: &SmcpAgentError, context: ErrorContext) {
+        self.errors
+            .lock()
+            .unwrap()
+            .push((error.to_string(), context));
+    }
+}
+
+#[tokio::test]
+async fn test_on_error_receives_handler_errors() {
+    // 中文：处理器返回错误时 on_error 收到错误及上下文
+    // English: on_error receives handler errors with their context
+
+    let errors = Arc::new(Mutex::new(Vec::new()));
+    let agent = create_test_agent("test-agent-on-error", "office1").with_event_handler(
+        FailingLeaveHandler {
+            errors: errors.clone(),
+        },
+    );
+
+    agent
+        .handle_notification(NotificationMessage::LeaveOffice(
+            smcp::LeaveOfficeNotification {
+                office_id: "office1".to_string(),
+                computer: Some("computer1".to_string()),
+                agent: None,
+                reason: None,
+            },
+        ))
+        .await;
+
+    {
+        let errors = errors.lock().unwrap();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].0.contains("leave handler failed"));
+        assert_eq!(
+            errors[0].1,
+            ErrorContext::Handler {
+                method: "on_computer_leave_office"
+            }
+        );
+    }
+
+    // 传输层错误同样交给 on_error
+    agent
+        .handle_notification(NotificationMessage::TransportError(
+            "packet parse failed".to_string(),
+        ))
+        .await;
+    let errors = errors.lock().unwrap();
+    assert_eq!(errors.len(), 2);
+    assert!(errors[1].0.contains("packet parse failed"));
+    assert_eq!(errors[1].1, ErrorContext::Transport);
+}
+
+/// 记录重连耗尽回调的处理器
+struct ReconnectExhaustedHandler {
+    exhausted: Arc<Mutex<Vec<u32>>>,
+}
+
+#[async_trait::async_trait]
+impl AsyncAgentEventHandler for ReconnectExhaustedHandler {
+    async fn on_reconnect_exhausted(
+        &self,
+        attempts: u32,
+        _agent: &AsyncSmcpAgent,
+    ) -> Result<(), SmcpAgentError> {
+        self.exhausted.lock().unwrap().push(attempts);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_reconnect_exhausted_enters_failed_state() {
+    // 中文：重连次数耗尽后进入 Failed 并触发 on_reconnect_exhausted
+    // English: exhausting reconnect attempts enters Failed and fires on_reconnect_exhausted
+
+    let exhausted = Arc::new(Mutex::new(Vec::new()));
+    let agent = create_test_agent("test-agent-reconnect", "office1").with_event_handler(
+        ReconnectExhaustedHandler {
+            exhausted: exhausted.clone(),
+        },
+    );
+    assert_eq!(agent.reconnect_state().await, ReconnectState::Connected);
+
+    for attempt in 1..=2 {
+        agent
+            .handle_notification(NotificationMessage::Reconnecting(attempt))
+            .await;
+        assert_eq!(
+            agent.reconnect_state().await,
+            ReconnectState::Reconnecting { attempt }
+        );
+    }
+
+    agent
+        .handle_notification(NotificationMessage::ReconnectExhausted(2))
+        .await;
+    assert_eq!(agent.reconnect_state().await, ReconnectState::Failed);
+    assert_eq!(*exhausted.lock().unwrap(), vec![2]);
+
+    // Failed 是终止状态
+    agent
+        .handle_notification(NotificationMessage::Connected)
+        .await;
+    agent
+        .handle_notification(NotificationMessage::Reconnecting(3))
+        .await;
+    assert_eq!(agent.reconnect_state().await, ReconnectState::Failed);
+}
+
+#[tokio::test]
+async fn test_agent_tool_call_batch_when_disconnected() {
+    // 中文：测试断开连接时批量调用为每个调用分别返回错误
+    // English: Test batch tool calls return one error per call when disconnected
+
+    let agent = create_test_agent("test-agent-batch", "test-office-batch");
+
+    let results = agent
+        .tool_call_batch(
+            "test-computer",
+            vec![
+                ("echo".to_string(), serde_json::json!({"text": "a"}), 5),
+                ("echo".to_string(), serde_json::json!({"text": "b"}), 5),
+                ("sleep".to_string(), serde_json::json!({}), 1),
+            ],
+        )
+        .await;
+
+    assert_eq!(results.len(), 3);
+    assert!(results.iter().all(|result| result.is_err()));
+}
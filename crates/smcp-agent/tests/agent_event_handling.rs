@@ -8,6 +8,7 @@
 * 描述: SMCP Agent事件处理测试 / SMCP Agent event handling tests
 */
 
+use smcp_agent::transport::NotificationMessage;
 use std::time::Duration;
 // use smcp_agent::{DefaultAuthProvider, SmcpAgentConfig, AsyncSmcpAgent}; // Unused imports
 mod common;
@@ -120,3 +121,91 @@ async fn test_agent_event_handler_concurrent_access() {
     assert!(handler.computer_enter_events.lock().await.is_empty());
     assert!(handler.tools_received.lock().await.is_empty());
 }
+
+#[tokio::test]
+async fn test_agent_dispatches_tool_progress() {
+    // 中文：测试工具调用进度按到达顺序分发给事件处理器
+    // English: Test tool call progress is dispatched to the handler in arrival order
+
+    let handler = TestEventHandler::new();
+    let agent = create_test_agent_with_handler(
+        "test-agent-progress",
+        "test-office-progress",
+        handler.clone(),
+    );
+
+    for (progress, message) in [(1.0, "compiling"), (2.0, "linking")] {
+        agent
+            .handle_notification(NotificationMessage::ToolProgress(
+                smcp::ToolProgressNotification {
+                    computer: "computer1".to_string(),
+                    req_id: smcp::ReqId::from_string("req1".to_string()),
+                    progress,
+                    total: Some(2.0),
+                    message: Some(message.to_string()),
+                    content: vec![],
+                },
+            ))
+            .await;
+    }
+
+    let events = handler.tool_progress_events.lock().await;
+    assert_eq!(events.len(), 2);
+    assert!(events.iter().all(|e| e.req_id.as_str() == "req1"));
+    assert_eq!(events[0].message.as_deref(), Some("compiling"));
+    assert_eq!(events[1].progress, 2.0);
+}
+
+#[tokio::test]
+async fn test_agent_dispatches_tool_call_result() {
+    // 中文：测试工具调用结果携带发起请求的 req_id 分发给事件处理器
+    // English: Test tool call results reach the handler with the originating req_id
+
+    let handler = TestEventHandler::new();
+    let agent =
+        create_test_agent_with_handler("test-agent-result", "test-office-result", handler.clone());
+
+    let req_id = smcp::ReqId::new();
+    agent
+        .handle_notification(NotificationMessage::ToolCallResult {
+            computer: "computer1".to_string(),
+            req_id: req_id.clone(),
+            result: serde_json::from_value(serde_json::json!({
+                "content": [{"type": "text", "text": "done"}],
+                "isError": false
+            }))
+            .unwrap(),
+        })
+        .await;
+
+    let results = handler.tool_call_results.lock().await;
+    assert_eq!(results.len(), 1);
+    let (computer, received_req_id, result) = &results[0];
+    assert_eq!(computer, "computer1");
+    assert_eq!(received_req_id, &req_id);
+    assert_eq!(result.is_error, Some(false));
+    assert_eq!(result.content.as_ref().unwrap()[0]["text"], "done");
+}
+
+#[tokio::test]
+async fn test_agent_ignores_duplicate_tool_call_result() {
+    // 中文：测试同一 req_id 的重复工具调用结果只分发一次
+    // English: Test a duplicate tool call result for the same req_id is dispatched once
+
+    let handler = TestEventHandler::new();
+    let agent =
+        create_test_agent_with_handler("test-agent-dup", "test-office-dup", handler.clone());
+
+    let req_id = smcp::ReqId::new();
+    for _ in 0..2 {
+        agent
+            .handle_notification(NotificationMessage::ToolCallResult {
+                computer: "computer1".to_string(),
+                req_id: req_id.clone(),
+                result: serde_json::from_value(serde_json::json!({"isError": false})).unwrap(),
+            })
+            .await;
+    }
+
+    assert_eq!(handler.tool_call_results.lock().await.len(), 1);
+}
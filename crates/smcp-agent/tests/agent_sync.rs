@@ -8,7 +8,11 @@
 * 描述: SMCP SyncAgent集成测试 / SMCP SyncAgent integration tests
 */
 
-use smcp_agent::{DefaultAuthProvider, SmcpAgentConfig, SyncSmcpAgent};
+use smcp_agent::{
+    transport::NotificationMessage, AgentEventHandler, DefaultAuthProvider, ErrorContext,
+    SmcpAgentConfig, SmcpAgentError, SyncSmcpAgent,
+};
+use std::sync::{Arc, Mutex};
 mod common;
 use common::*;
 
@@ -128,6 +132,50 @@ fn test_sync_agent_config_validation() {
     let _agent = SyncSmcpAgent::new(auth, custom_config).expect("Failed to create sync agent");
 }
 
+/// 记录 on_error 调用的同步处理器
+#[derive(Default)]
+struct RecordingSyncHandler {
+    errors: Arc<Mutex<Vec<ErrorContext>>>,
+}
+
+impl AgentEventHandler for RecordingSyncHandler {
+    fn on_error(&self, _error: &SmcpAgentError, context: ErrorContext) {
+        self.errors.lock().unwrap().push(context);
+    }
+}
+
+#[test]
+fn test_sync_agent_on_error_receives_errors() {
+    // 中文：同步处理器的 on_error 收到传输层错误与自动请求失败，并带有 Computer 名称
+    // English: The sync handler's on_error receives transport errors and failed auto requests with the computer name
+
+    let handler = RecordingSyncHandler::default();
+    let errors = handler.errors.clone();
+    let auth = DefaultAuthProvider::new(
+        "test-sync-on-error".to_string(),
+        "test-sync-office".to_string(),
+    );
+    let agent = SyncSmcpAgent::new(auth, SmcpAgentConfig::new())
+        .expect("Failed to create sync agent")
+        .with_event_handler(handler);
+
+    agent.handle_notification(NotificationMessage::TransportError("boom".to_string()));
+    // 未连接时自动获取桌面失败 / Auto-fetching the desktop fails while disconnected
+    agent.handle_notification(NotificationMessage::UpdateDesktop("computer-1".to_string()));
+
+    let errors = errors.lock().unwrap();
+    assert_eq!(
+        *errors,
+        vec![
+            ErrorContext::Transport,
+            ErrorContext::AutoRequest {
+                event: smcp::events::CLIENT_GET_DESKTOP,
+                computer: Some("computer-1".to_string()),
+            },
+        ]
+    );
+}
+
 /// 创建测试用的同步Agent实例
 pub fn create_sync_agent(agent_id: &str, office_id: &str) -> SyncSmcpAgent {
     let auth = DefaultAuthProvider::new(agent_id.to_string(), office_id.to_string());
@@ -110,6 +110,7 @@ async fn test_notification_message_creation() {
         office_id: "office123".to_string(),
         computer: Some("computer1".to_string()),
         agent: Some("agent1".to_string()),
+        reason: None,
     });
 
     let update_config = NotificationMessage::UpdateConfig(smcp::UpdateMCPConfigNotification {
@@ -234,6 +235,7 @@ async fn test_notification_payload_validation() {
                 office_id: "office1".to_string(),
                 computer: Some("comp1".to_string()),
                 agent: Some("agent1".to_string()),
+                reason: None,
             }),
             "LeaveOffice with both",
         ),
@@ -253,6 +255,52 @@ async fn test_notification_payload_validation() {
             NotificationMessage::UpdateDesktop("comp1".to_string()),
             "UpdateDesktop",
         ),
+        (NotificationMessage::Connected, "Connected"),
+        (
+            NotificationMessage::Disconnected(smcp::DisconnectReason::default()),
+            "Disconnected",
+        ),
+        (
+            NotificationMessage::TransportError("transport error".to_string()),
+            "TransportError",
+        ),
+        (NotificationMessage::Reconnecting(1), "Reconnecting"),
+        (
+            NotificationMessage::ReconnectExhausted(3),
+            "ReconnectExhausted",
+        ),
+        (
+            NotificationMessage::ServerHello(smcp::ServerCapabilities {
+                protocol_version: smcp::PROTOCOL_VERSION.to_string(),
+                public_url: None,
+                max_timeout_secs: Some(60),
+            }),
+            "ServerHello",
+        ),
+        (
+            NotificationMessage::ToolProgress(smcp::ToolProgressNotification {
+                computer: "computer1".to_string(),
+                req_id: smcp::ReqId::from_string("req1".to_string()),
+                progress: 1.0,
+                total: None,
+                message: None,
+                content: vec![],
+            }),
+            "ToolProgress",
+        ),
+        (
+            NotificationMessage::ToolCallResult {
+                computer: "computer1".to_string(),
+                req_id: smcp::ReqId::from_string("req1".to_string()),
+                result: smcp::ToolCallRet {
+                    content: None,
+                    is_error: Some(false),
+                    req_id: None,
+                    call_info: None,
+                },
+            },
+            "ToolCallResult",
+        ),
     ];
 
     for (notification, description) in test_cases {
@@ -273,6 +321,30 @@ async fn test_notification_payload_validation() {
             NotificationMessage::UpdateDesktop(_) => {
                 assert!(description.contains("UpdateDesktop"));
             }
+            NotificationMessage::Connected => {
+                assert!(description.contains("Connected"));
+            }
+            NotificationMessage::Disconnected(_) => {
+                assert!(description.contains("Disconnected"));
+            }
+            NotificationMessage::TransportError(_) => {
+                assert!(description.contains("TransportError"));
+            }
+            NotificationMessage::Reconnecting(_) => {
+                assert!(description.contains("Reconnecting"));
+            }
+            NotificationMessage::ReconnectExhausted(_) => {
+                assert!(description.contains("ReconnectExhausted"));
+            }
+            NotificationMessage::ServerHello(_) => {
+                assert!(description.contains("ServerHello"));
+            }
+            NotificationMessage::ToolProgress(_) => {
+                assert!(description.contains("ToolProgress"));
+            }
+            NotificationMessage::ToolCallResult { .. } => {
+                assert!(description.contains("ToolCallResult"));
+            }
         }
     }
 }
@@ -329,6 +401,17 @@ async fn test_python_compatibility_behavior() {
                 assert_eq!(computer, "computer1");
                 assert_eq!(i, 3); // 第四个通知
             }
+            NotificationMessage::Connected
+            | NotificationMessage::Disconnected(_)
+            | NotificationMessage::TransportError(_)
+            | NotificationMessage::Reconnecting(_)
+            | NotificationMessage::ReconnectExhausted(_)
+            | NotificationMessage::ServerHello(_) => {
+                panic!("Unexpected connection notification");
+            }
+            NotificationMessage::ToolProgress(_) | NotificationMessage::ToolCallResult { .. } => {
+                panic!("Unexpected tool call notification");
+            }
         }
     }
 }
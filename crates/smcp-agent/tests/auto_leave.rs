@@ -0,0 +1,157 @@
+/*!
+* 文件名: auto_leave
+* 作者: JQQ
+* 创建日期: 2025/12/15
+* 最后修改日期: 2025/12/15
+* 版权: 2023 JQQ. All rights reserved.
+* 依赖: smcp-server-core, hyper
+* 描述: Agent 关闭与丢弃时离开办公室测试 / Agent leaves office on close and drop tests
+*/
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures_util::FutureExt;
+use http_body_util::Full;
+use hyper_util::rt::TokioIo;
+use rust_socketio::asynchronous::{Client, ClientBuilder};
+use rust_socketio::{Payload, TransportType};
+use serde_json::json;
+use smcp::{events::NOTIFY_LEAVE_OFFICE, LeaveOfficeNotification, SMCP_NAMESPACE};
+use smcp_agent::{AsyncSmcpAgent, DefaultAuthProvider, SmcpAgentConfig};
+use smcp_server_core::{DefaultAuthenticationProvider, SmcpServerBuilder};
+use tokio::net::TcpListener;
+use tokio::time::sleep;
+use tower::{Layer, Service};
+
+const OFFICE_ID: &str = "auto-leave-office";
+const API_KEY: &str = "test_secret";
+
+/// 启动测试服务器，返回 URL
+async fn start_server() -> String {
+    let layer = SmcpServerBuilder::new()
+        .with_auth_provider(Arc::new(DefaultAuthenticationProvider::new(
+            Some(API_KEY.to_string()),
+            None,
+        )))
+        .build_layer()
+        .expect("failed to build SMCP server layer");
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let layer = layer.clone();
+            tokio::spawn(async move {
+                let svc = tower::service_fn(move |req| {
+                    let layer = layer.clone();
+                    async move {
+                        let inner = tower::service_fn(|_req| async {
+                            Ok::<_, std::convert::Infallible>(hyper::Response::new(Full::new(
+                                hyper::body::Bytes::new(),
+                            )))
+                        });
+                        layer.layer.layer(inner).call(req).await
+                    }
+                });
+                let _ = hyper::server::conn::http1::Builder::new()
+                    .serve_connection(
+                        TokioIo::new(stream),
+                        hyper_util::service::TowerToHyperService::new(svc),
+                    )
+                    .with_upgrades()
+                    .await;
+            });
+        }
+    });
+
+    format!("http://{}", addr)
+}
+
+/// 以 Computer 身份加入办公室并记录收到的离开通知
+async fn start_observer(url: &str) -> (Client, Arc<Mutex<Vec<LeaveOfficeNotification>>>) {
+    let leaves = Arc::new(Mutex::new(Vec::new()));
+    let leaves_clone = leaves.clone();
+    let client = ClientBuilder::new(url)
+        .transport_type(TransportType::Websocket)
+        .namespace(SMCP_NAMESPACE)
+        .opening_header("x-api-key", API_KEY)
+        .on(NOTIFY_LEAVE_OFFICE, move |payload, _client| {
+            let leaves = leaves_clone.clone();
+            async move {
+                if let Payload::Text(values, _) = payload {
+                    if let Some(data) = values
+                        .into_iter()
+                        .next()
+                        .and_then(|v| serde_json::from_value(v).ok())
+                    {
+                        leaves.lock().unwrap().push(data);
+                    }
+                }
+            }
+            .boxed()
+        })
+        .connect()
+        .await
+        .expect("observer failed to connect");
+
+    client
+        .emit(
+            "server:join_office",
+            json!({"role": "computer", "office_id": OFFICE_ID, "name": "observer"}),
+        )
+        .await
+        .unwrap();
+    sleep(Duration::from_millis(200)).await;
+    (client, leaves)
+}
+
+async fn joined_agent(url: &str, name: &str, config: SmcpAgentConfig) -> AsyncSmcpAgent {
+    let auth = DefaultAuthProvider::new(name.to_string(), OFFICE_ID.to_string())
+        .with_api_key(API_KEY.to_string());
+    let mut agent = AsyncSmcpAgent::new(auth, config);
+    agent.connect(url).await.expect("agent failed to connect");
+    agent.join_office(name).await.unwrap();
+    sleep(Duration::from_millis(200)).await;
+    agent
+}
+
+fn agent_left(leaves: &Mutex<Vec<LeaveOfficeNotification>>, name: &str) -> bool {
+    leaves
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|leave| leave.office_id == OFFICE_ID && leave.agent.as_deref() == Some(name))
+}
+
+#[tokio::test]
+async fn test_close_emits_leave() {
+    let url = start_server().await;
+    let (observer, leaves) = start_observer(&url).await;
+
+    let mut agent = joined_agent(&url, "closing-agent", SmcpAgentConfig::new()).await;
+    agent.close().await.unwrap();
+    sleep(Duration::from_millis(300)).await;
+
+    assert!(agent_left(&leaves, "closing-agent"));
+    let _ = observer.disconnect().await;
+}
+
+#[tokio::test]
+async fn test_drop_with_auto_leave_emits_leave() {
+    let url = start_server().await;
+    let (observer, leaves) = start_observer(&url).await;
+
+    let config = SmcpAgentConfig::new().with_auto_leave_on_drop(true);
+    let agent = joined_agent(&url, "dropped-agent", config).await;
+    // 克隆被丢弃不应触发离开 / Dropping a clone must not leave
+    drop(agent.clone());
+    sleep(Duration::from_millis(300)).await;
+    assert!(!agent_left(&leaves, "dropped-agent"));
+
+    drop(agent);
+    sleep(Duration::from_millis(300)).await;
+
+    assert!(agent_left(&leaves, "dropped-agent"));
+    let _ = observer.disconnect().await;
+}
@@ -13,7 +13,8 @@ use std::time::Duration;
 
 use serde_json::json;
 use smcp::{
-    EnterOfficeNotification, LeaveOfficeNotification, SMCPTool, UpdateMCPConfigNotification,
+    EnterOfficeNotification, LeaveOfficeNotification, ReqId, SMCPTool, ToolCallRet,
+    ToolProgressNotification, UpdateMCPConfigNotification,
 };
 use tokio::sync::Mutex;
 use tracing::debug;
@@ -33,6 +34,11 @@ pub struct TestEventHandler {
     /// 收到的工具列表
     #[allow(clippy::type_complexity)]
     pub tools_received: Arc<Mutex<Vec<(String, Vec<SMCPTool>)>>>,
+    /// 收到的工具调用进度
+    pub tool_progress_events: Arc<Mutex<Vec<ToolProgressNotification>>>,
+    /// 收到的工具调用结果
+    #[allow(clippy::type_complexity)]
+    pub tool_call_results: Arc<Mutex<Vec<(String, ReqId, ToolCallRet)>>>,
 }
 
 impl TestEventHandler {
@@ -49,6 +55,8 @@ impl TestEventHandler {
         self.computer_leave_events.lock().await.clear();
         self.computer_update_events.lock().await.clear();
         self.tools_received.lock().await.clear();
+        self.tool_progress_events.lock().await.clear();
+        self.tool_call_results.lock().await.clear();
     }
 
     /// 等待事件到达
@@ -64,7 +72,9 @@ impl TestEventHandler {
             let total_events = self.computer_enter_events.lock().await.len()
                 + self.computer_leave_events.lock().await.len()
                 + self.computer_update_events.lock().await.len()
-                + self.tools_received.lock().await.len();
+                + self.tools_received.lock().await.len()
+                + self.tool_progress_events.lock().await.len()
+                + self.tool_call_results.lock().await.len();
             if total_events >= count {
                 return true;
             }
@@ -129,6 +139,35 @@ impl smcp_agent::AsyncAgentEventHandler for TestEventHandler {
         debug!("Received desktop from {}: {:?}", computer, desktops);
         Ok(())
     }
+
+    async fn on_tool_call_result(
+        &self,
+        computer: &str,
+        req_id: &ReqId,
+        result: ToolCallRet,
+        _agent: &AsyncSmcpAgent,
+    ) -> Result<(), smcp_agent::SmcpAgentError> {
+        debug!(
+            "Received tool call result {} from {}",
+            req_id.as_str(),
+            computer
+        );
+        self.tool_call_results
+            .lock()
+            .await
+            .push((computer.to_string(), req_id.clone(), result));
+        Ok(())
+    }
+
+    async fn on_tool_progress(
+        &self,
+        data: ToolProgressNotification,
+        _agent: &AsyncSmcpAgent,
+    ) -> Result<(), smcp_agent::SmcpAgentError> {
+        debug!("Received tool progress: {:?}", data);
+        self.tool_progress_events.lock().await.push(data);
+        Ok(())
+    }
 }
 
 /// 创建测试用的默认工具列表
@@ -78,6 +78,8 @@ mod e2e_tests {
             role: Role::Agent,
             name: "Test".to_string(),
             office_id: "Office1".to_string(),
+            meta: None,
+            a2c_version: None,
         };
         let json = serde_json::to_string(&req).unwrap();
         let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
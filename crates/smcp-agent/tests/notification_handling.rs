@@ -9,8 +9,11 @@
 */
 
 use smcp_agent::{
-    auth::DefaultAuthProvider, config::SmcpAgentConfig, events::AsyncAgentEventHandler,
-    transport::NotificationMessage, AsyncSmcpAgent,
+    auth::DefaultAuthProvider,
+    config::SmcpAgentConfig,
+    events::AsyncAgentEventHandler,
+    transport::{self, NotificationMessage},
+    AgentConnState, AsyncSmcpAgent,
 };
 use std::sync::Arc;
 use tokio::sync::{mpsc, RwLock};
@@ -127,6 +130,7 @@ async fn test_notification_channel_flow() {
             office_id: "office1".to_string(),
             computer: Some("comp1".to_string()),
             agent: None,
+            reason: None,
         },
     ))
     .unwrap();
@@ -198,6 +202,7 @@ async fn test_all_notification_types() {
             office_id: "office1".to_string(),
             computer: Some("comp1".to_string()),
             agent: Some("agent1".to_string()),
+            reason: None,
         }),
         NotificationMessage::UpdateConfig(smcp::UpdateMCPConfigNotification {
             computer: "comp1".to_string(),
@@ -229,3 +234,81 @@ async fn test_all_notification_types() {
         }
     }
 }
+
+#[tokio::test]
+async fn test_simulated_4900_close_reports_code_and_suppresses_reconnect() {
+    // 模拟服务器以 4900 关闭连接
+    let payload = rust_socketio::Payload::Text(
+        vec![serde_json::json!({"code": 4900, "reason": "switch to polling"})],
+        None,
+    );
+    let notification =
+        NotificationMessage::Disconnected(transport::disconnect_reason_from_payload(payload));
+
+    match notification {
+        NotificationMessage::Disconnected(reason) => {
+            assert_eq!(reason.code, Some(4900));
+            assert_eq!(reason.message.as_deref(), Some("switch to polling"));
+            assert!(!reason.should_reconnect());
+        }
+        _ => panic!("Expected Disconnected notification"),
+    }
+
+    // 普通断开仍允许重连
+    let reason = transport::disconnect_reason_from_payload(rust_socketio::Payload::Text(
+        vec![serde_json::json!("transport close")],
+        None,
+    ));
+    assert_eq!(reason.code, None);
+    assert!(reason.should_reconnect());
+
+    // 尚未断开过的 Agent 没有断开原因
+    let auth_provider = DefaultAuthProvider::new("test_agent".to_string(), "office1".to_string());
+    let agent = AsyncSmcpAgent::new(auth_provider, SmcpAgentConfig::default());
+    assert!(agent.last_disconnect_reason().await.is_none());
+}
+
+#[tokio::test]
+async fn test_connection_state_transitions() {
+    let auth_provider = DefaultAuthProvider::new("test_agent".to_string(), "office1".to_string());
+    let agent = AsyncSmcpAgent::new(auth_provider, SmcpAgentConfig::default());
+    let mut state = agent.connection_state();
+    assert_eq!(*state.borrow_and_update(), AgentConnState::Disconnected);
+
+    // 按传输层的通知顺序驱动：连接、断线、重连、恢复、再断线直至放弃
+    let steps = vec![
+        (NotificationMessage::Connected, AgentConnState::Connected),
+        (
+            NotificationMessage::Disconnected(smcp::DisconnectReason::default()),
+            AgentConnState::Disconnected,
+        ),
+        (
+            NotificationMessage::Reconnecting(1),
+            AgentConnState::Reconnecting,
+        ),
+        (NotificationMessage::Connected, AgentConnState::Connected),
+        (
+            NotificationMessage::Reconnecting(1),
+            AgentConnState::Reconnecting,
+        ),
+        (
+            NotificationMessage::ReconnectExhausted(1),
+            AgentConnState::Failed,
+        ),
+    ];
+    for (notification, expected) in steps {
+        agent.handle_notification(notification).await;
+        timeout(Duration::from_secs(1), state.changed())
+            .await
+            .expect("state change not observed")
+            .unwrap();
+        assert_eq!(*state.borrow_and_update(), expected);
+    }
+
+    // 失败后不再因后续通知改变状态
+    agent
+        .handle_notification(NotificationMessage::Reconnecting(2))
+        .await;
+    assert!(!state.has_changed().unwrap());
+    assert_eq!(*state.borrow(), AgentConnState::Failed);
+}
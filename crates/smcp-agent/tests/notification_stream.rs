@@ -0,0 +1,227 @@
+/*!
+* 文件名: notification_stream
+* 作者: JQQ
+* 创建日期: 2025/12/15
+* 最后修改日期: 2025/12/15
+* 版权: 2023 JQQ. All rights reserved.
+* 依赖: smcp-server-core, hyper
+* 描述: Agent 通知流测试 / Agent notification stream tests
+*/
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_util::{FutureExt, StreamExt};
+use http_body_util::Full;
+use hyper_util::rt::TokioIo;
+use rust_socketio::asynchronous::ClientBuilder;
+use rust_socketio::{Payload, TransportType};
+use serde_json::{json, Value};
+use smcp::events::{CLIENT_TOOL_CALL, SERVER_TOOL_PROGRESS};
+use smcp::SMCP_NAMESPACE;
+use smcp_agent::{
+    transport::NotificationMessage, AsyncSmcpAgent, DefaultAuthProvider, SmcpAgentConfig,
+    ToolCallStreamItem,
+};
+use smcp_server_core::{DefaultAuthenticationProvider, SmcpServerBuilder};
+use tokio::net::TcpListener;
+use tokio::time::{sleep, timeout};
+use tower::{Layer, Service};
+
+const OFFICE_ID: &str = "stream-office";
+const API_KEY: &str = "test_secret";
+
+/// 启动测试服务器，返回 URL
+async fn start_server() -> String {
+    let layer = SmcpServerBuilder::new()
+        .with_auth_provider(Arc::new(DefaultAuthenticationProvider::new(
+            Some(API_KEY.to_string()),
+            None,
+        )))
+        .build_layer()
+        .expect("failed to build SMCP server layer");
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let layer = layer.clone();
+            tokio::spawn(async move {
+                let svc = tower::service_fn(move |req| {
+                    let layer = layer.clone();
+                    async move {
+                        let inner = tower::service_fn(|_req| async {
+                            Ok::<_, std::convert::Infallible>(hyper::Response::new(Full::new(
+                                hyper::body::Bytes::new(),
+                            )))
+                        });
+                        layer.layer.layer(inner).call(req).await
+                    }
+                });
+                let _ = hyper::server::conn::http1::Builder::new()
+                    .serve_connection(
+                        TokioIo::new(stream),
+                        hyper_util::service::TowerToHyperService::new(svc),
+                    )
+                    .with_upgrades()
+                    .await;
+            });
+        }
+    });
+
+    format!("http://{}", addr)
+}
+
+#[tokio::test]
+async fn test_stream_receives_enter_office() {
+    let url = start_server().await;
+
+    let auth = DefaultAuthProvider::new("stream-agent".to_string(), OFFICE_ID.to_string())
+        .with_api_key(API_KEY.to_string());
+    let mut agent = AsyncSmcpAgent::new(auth, SmcpAgentConfig::new());
+    let mut notifications = agent.notifications();
+    agent.connect(&url).await.expect("agent failed to connect");
+    agent.join_office("stream-agent").await.unwrap();
+    sleep(Duration::from_millis(200)).await;
+
+    let computer = ClientBuilder::new(url.as_str())
+        .transport_type(TransportType::Websocket)
+        .namespace(SMCP_NAMESPACE)
+        .opening_header("x-api-key", API_KEY)
+        .connect()
+        .await
+        .expect("computer failed to connect");
+    computer
+        .emit(
+            "server:join_office",
+            json!({"role": "computer", "office_id": OFFICE_ID, "name": "stream-computer"}),
+        )
+        .await
+        .unwrap();
+
+    // 跳过连接等其他通知，直到收到 Computer 加入 / Skip other notifications until the computer enters
+    let enter = timeout(Duration::from_secs(3), async {
+        while let Some(notification) = notifications.next().await {
+            if let NotificationMessage::EnterOffice(data) = notification {
+                return Some(data);
+            }
+        }
+        None
+    })
+    .await
+    .expect("timed out waiting for enter_office")
+    .expect("notification stream ended");
+
+    assert_eq!(enter.office_id, OFFICE_ID);
+    assert_eq!(enter.computer.as_deref(), Some("stream-computer"));
+
+    let _ = computer.disconnect().await;
+    agent.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_stream_only_sees_notifications_after_subscribe() {
+    let auth = DefaultAuthProvider::new("stream-agent".to_string(), OFFICE_ID.to_string());
+    let agent = AsyncSmcpAgent::new(auth, SmcpAgentConfig::new());
+    let leave = |computer: &str| {
+        NotificationMessage::LeaveOffice(smcp::LeaveOfficeNotification {
+            office_id: OFFICE_ID.to_string(),
+            computer: Some(computer.to_string()),
+            agent: None,
+            reason: None,
+        })
+    };
+
+    agent.handle_notification(leave("before")).await;
+    let mut notifications = agent.notifications();
+    agent.handle_notification(leave("after")).await;
+
+    match timeout(Duration::from_secs(1), notifications.next()).await {
+        Ok(Some(NotificationMessage::LeaveOffice(data))) => {
+            assert_eq!(data.computer.as_deref(), Some("after"));
+        }
+        other => panic!("expected leave_office notification, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_tool_call_stream_yields_partial_content() {
+    let url = start_server().await;
+
+    // 模拟的 Computer：先逐段上报两块文本，再应答最终结果
+    // Mock computer: reports two text chunks, then acks the final result
+    let computer = ClientBuilder::new(url.as_str())
+        .transport_type(TransportType::Websocket)
+        .namespace(SMCP_NAMESPACE)
+        .opening_header("x-api-key", API_KEY)
+        .on(CLIENT_TOOL_CALL, |payload, client| {
+            async move {
+                let Payload::Text(values, Some(ack_id)) = payload else {
+                    return;
+                };
+                let req_id = values[0]["req_id"].clone();
+                for (progress, text) in [(1.0, "Hello, "), (2.0, "world")] {
+                    let chunk = json!({
+                        "computer": "stream-computer",
+                        "req_id": req_id,
+                        "progress": progress,
+                        "content": [{"type": "text", "text": text}],
+                    });
+                    client.emit(SERVER_TOOL_PROGRESS, chunk).await.unwrap();
+                    sleep(Duration::from_millis(100)).await;
+                }
+                let result = json!({
+                    "content": [{"type": "text", "text": "Hello, world"}],
+                    "isError": false,
+                });
+                client.ack_with_id(ack_id, result).await.unwrap();
+            }
+            .boxed()
+        })
+        .connect()
+        .await
+        .expect("computer failed to connect");
+    computer
+        .emit(
+            "server:join_office",
+            json!({"role": "computer", "office_id": OFFICE_ID, "name": "stream-computer"}),
+        )
+        .await
+        .unwrap();
+
+    let auth = DefaultAuthProvider::new("stream-agent".to_string(), OFFICE_ID.to_string())
+        .with_api_key(API_KEY.to_string());
+    let mut agent = AsyncSmcpAgent::new(auth, SmcpAgentConfig::new());
+    agent.connect(&url).await.expect("agent failed to connect");
+    agent.join_office("stream-agent").await.unwrap();
+    sleep(Duration::from_millis(200)).await;
+
+    let items: Vec<ToolCallStreamItem> = timeout(
+        Duration::from_secs(5),
+        agent
+            .tool_call_stream("stream-computer", "generate", json!({}))
+            .map(|item| item.unwrap())
+            .collect(),
+    )
+    .await
+    .expect("timed out waiting for the tool call stream");
+
+    assert_eq!(items.len(), 3, "unexpected stream items: {:?}", items);
+    let texts: Vec<&Value> = items[..2]
+        .iter()
+        .map(|item| match item {
+            ToolCallStreamItem::Content(content) => &content[0]["text"],
+            other => panic!("expected partial content, got {:?}", other),
+        })
+        .collect();
+    assert_eq!(texts, vec![&json!("Hello, "), &json!("world")]);
+    match &items[2] {
+        ToolCallStreamItem::Done(result) => {
+            assert_eq!(result["content"][0]["text"], json!("Hello, world"))
+        }
+        other => panic!("expected final result, got {:?}", other),
+    }
+
+    let _ = computer.disconnect().await;
+    agent.close().await.unwrap();
+}
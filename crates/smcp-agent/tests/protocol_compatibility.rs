@@ -46,6 +46,8 @@ fn test_protocol_serialization_compatibility() {
         role: Role::Agent,
         name: "Test Agent".to_string(),
         office_id: "test-office".to_string(),
+        meta: None,
+        a2c_version: None,
     };
     let enter_req_json = serde_json::to_string(&enter_req).unwrap();
     let parsed: serde_json::Value = serde_json::from_str(&enter_req_json).unwrap();
@@ -89,6 +91,7 @@ fn test_protocol_serialization_compatibility() {
         tool_name: "echo".to_string(),
         params: serde_json::json!({"text": "hello"}),
         timeout: 30,
+        idempotency_key: None,
     };
     let tool_call_json = serde_json::to_string(&tool_call).unwrap();
     let parsed: serde_json::Value = serde_json::from_str(&tool_call_json).unwrap();
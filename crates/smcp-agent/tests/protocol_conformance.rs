@@ -28,6 +28,7 @@ async fn test_tool_call_ret_python_compatibility() {
         })]),
         is_error: Some(false),
         req_id: Some(ReqId::from_string("req-123".to_string())),
+        call_info: None,
     };
 
     let json = serde_json::to_string(&success_ret).unwrap();
@@ -60,6 +61,7 @@ async fn test_tool_call_ret_error_python_compatibility() {
         })]),
         is_error: Some(true),
         req_id: Some(ReqId::from_string("req-456".to_string())),
+        call_info: None,
     };
 
     let json = serde_json::to_string(&error_ret).unwrap();
@@ -89,6 +91,7 @@ async fn test_notification_message_json_format() {
                 office_id: "office-001".to_string(),
                 computer: Some("computer-001".to_string()),
                 agent: Some("agent-001".to_string()),
+                reason: None,
             }),
             "LeaveOffice notification",
         ),
@@ -108,6 +111,17 @@ async fn test_notification_message_json_format() {
             NotificationMessage::UpdateDesktop("computer-001".to_string()),
             "UpdateDesktop notification",
         ),
+        (
+            NotificationMessage::ToolProgress(smcp::ToolProgressNotification {
+                computer: "computer-001".to_string(),
+                req_id: smcp::ReqId::from_string("req-001".to_string()),
+                progress: 1.0,
+                total: Some(2.0),
+                message: None,
+                content: vec![],
+            }),
+            "ToolProgress notification",
+        ),
     ];
 
     for (notification, _description) in test_cases {
@@ -132,6 +146,19 @@ async fn test_notification_message_json_format() {
             NotificationMessage::UpdateDesktop(computer) => {
                 assert_eq!(computer, "computer-001");
             }
+            NotificationMessage::ToolProgress(data) => {
+                assert_eq!(data.computer, "computer-001");
+                assert_eq!(data.req_id.as_str(), "req-001");
+            }
+            NotificationMessage::ToolCallResult { computer, .. } => {
+                assert_eq!(computer, "computer-001");
+            }
+            NotificationMessage::Connected
+            | NotificationMessage::Disconnected(_)
+            | NotificationMessage::TransportError(_)
+            | NotificationMessage::Reconnecting(_)
+            | NotificationMessage::ReconnectExhausted(_)
+            | NotificationMessage::ServerHello(_) => {}
         }
     }
 }
@@ -228,6 +255,7 @@ async fn test_protocol_field_names() {
         content: None,
         is_error: Some(true),
         req_id: None,
+        call_info: None,
     };
 
     let json = serde_json::to_string(&tool_ret).unwrap();
@@ -257,6 +285,7 @@ async fn test_empty_and_optional_fields() {
         content: None,
         is_error: None,
         req_id: None,
+        call_info: None,
     };
 
     let json = serde_json::to_string(&empty_ret).unwrap();
@@ -267,6 +296,7 @@ async fn test_empty_and_optional_fields() {
         content: Some(vec![]),
         is_error: Some(false),
         req_id: None,
+        call_info: None,
     };
 
     let json = serde_json::to_string(&partial_ret).unwrap();
@@ -287,6 +317,7 @@ async fn test_python_compatibility_checklist() {
         content: Some(vec![serde_json::json!({"type": "text", "text": "test"})]),
         is_error: Some(false),
         req_id: Some(ReqId::new()),
+        call_info: None,
     };
 
     // 2. Role 序列化为小写
@@ -307,6 +338,7 @@ async fn test_python_compatibility_checklist() {
         office_id: "test".to_string(),
         computer: None,
         agent: None,
+        reason: None,
     };
 
     let _ = UpdateMCPConfigNotification {
@@ -10,11 +10,11 @@
 
 use crate::computer::{Computer, SilentSession};
 use crate::errors::ComputerError;
-use crate::mcp_clients::model::{MCPServerConfig, MCPServerInput};
-use crate::socketio_client::SmcpComputerClient;
+use crate::mcp_clients::model::{MCPServerConfig, MCPServerInput, Tool};
+use crate::socketio_client::{ConnectionInfo, SmcpComputerClient};
 use serde_json::{json, Value};
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use thiserror::Error;
 
@@ -41,9 +41,152 @@ pub enum CommandError {
     ComputerError(#[from] ComputerError),
 }
 
+/// 命令输出格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// 便于阅读的文本
+    #[default]
+    Text,
+    /// JSON
+    Json,
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = CommandError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(CommandError::InvalidCommand(format!(
+                "未知的输出格式 / Unknown format: {}（可选 text|json）",
+                s
+            ))),
+        }
+    }
+}
+
+/// 工具的参数与返回值 schema，字段与 Agent 获取到的 `SMCPTool` 一致
+fn tool_schema(tool: Tool) -> Value {
+    let tool = smcp::SMCPTool::from(tool);
+    json!({
+        "name": tool.name,
+        "params_schema": tool.params_schema,
+        "return_schema": tool.return_schema,
+    })
+}
+
+/// 读取配置文件并展开 `$include` 指令
+///
+/// `$include` 为相对于当前文件的路径数组，被包含文件的 `servers`/`inputs` 先于当前文件合并。
+/// 服务器按 `name`、输入按 `id` 检测重复，出现重复或循环包含时返回错误。
+pub fn read_config_with_includes(path: &Path) -> Result<Value, CommandError> {
+    let mut merged = MergedConfig::default();
+    let mut stack = Vec::new();
+    merged.include(path, &mut stack)?;
+    Ok(json!({
+        "servers": merged.servers,
+        "inputs": merged.inputs,
+    }))
+}
+
+/// `$include` 展开过程中的合并结果
+#[derive(Default)]
+struct MergedConfig {
+    servers: Vec<Value>,
+    inputs: Vec<Value>,
+    /// 已合并条目的来源文件，用于重复提示
+    origins: HashMap<(&'static str, String), PathBuf>,
+}
+
+impl MergedConfig {
+    fn include(&mut self, path: &Path, stack: &mut Vec<PathBuf>) -> Result<(), CommandError> {
+        let canonical = path.canonicalize()?;
+        if stack.contains(&canonical) {
+            let chain: Vec<String> = stack
+                .iter()
+                .chain(std::iter::once(&canonical))
+                .map(|p| p.display().to_string())
+                .collect();
+            return Err(ComputerError::InvalidConfiguration(format!(
+                "Cyclic $include: {}",
+                chain.join(" -> ")
+            ))
+            .into());
+        }
+
+        let content = std::fs::read_to_string(&canonical)?;
+        let config: Value = serde_json::from_str(&content)?;
+
+        stack.push(canonical.clone());
+        if let Some(includes) = config.get("$include") {
+            let includes = includes.as_array().ok_or_else(|| {
+                ComputerError::InvalidConfiguration(format!(
+                    "$include in {} must be an array of paths",
+                    canonical.display()
+                ))
+            })?;
+            let base_dir = canonical.parent().unwrap_or_else(|| Path::new("."));
+            for include in includes {
+                let include = include.as_str().ok_or_else(|| {
+                    ComputerError::InvalidConfiguration(format!(
+                        "$include in {} must be an array of paths",
+                        canonical.display()
+                    ))
+                })?;
+                self.include(&base_dir.join(include), stack)?;
+            }
+        }
+        stack.pop();
+
+        self.merge(&config, "servers", "name", &canonical)?;
+        self.merge(&config, "inputs", "id", &canonical)?;
+        Ok(())
+    }
+
+    fn merge(
+        &mut self,
+        config: &Value,
+        section: &'static str,
+        key: &str,
+        source: &Path,
+    ) -> Result<(), CommandError> {
+        let Some(entries) = config.get(section).and_then(|v| v.as_array()) else {
+            return Ok(());
+        };
+        let target = match section {
+            "servers" => &mut self.servers,
+            _ => &mut self.inputs,
+        };
+        for entry in entries {
+            if let Some(id) = entry.get(key).and_then(|v| v.as_str()) {
+                if let Some(previous) = self
+                    .origins
+                    .insert((section, id.to_string()), source.to_path_buf())
+                {
+                    return Err(ComputerError::InvalidConfiguration(format!(
+                        "Duplicate {} entry '{}' in {} (already defined in {})",
+                        section,
+                        id,
+                        source.display(),
+                        previous.display()
+                    ))
+                    .into());
+                }
+            }
+            target.push(entry.clone());
+        }
+        Ok(())
+    }
+}
+
 pub struct CommandHandler {
     pub computer: Computer<SilentSession>,
     pub cli_config: CliConfig,
+    /// 当前 Socket.IO 连接，持有以维持连接
+    socketio_connection: Option<Arc<SmcpComputerClient>>,
+    /// `status`/`tools`/`mcp`/`history` 的默认输出格式
+    format: OutputFormat,
 }
 
 impl CommandHandler {
@@ -51,24 +194,43 @@ impl CommandHandler {
         Self {
             computer,
             cli_config,
+            socketio_connection: None,
+            format: OutputFormat::Text,
         }
     }
 
+    /// 设置默认输出格式
+    pub fn with_format(mut self, format: OutputFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// 默认输出格式
+    pub fn format(&self) -> OutputFormat {
+        self.format
+    }
+
     /// 显示帮助信息
     pub fn show_help(&self) {
         println!("可用命令 / Commands:");
         println!();
         println!("  status                    查看服务器状态 / show server status");
         println!("  tools                     列出可用工具 / list tools");
+        println!("  tools --schema [name]     显示工具参数/返回 schema / show tool schemas");
+        println!("    [--format text|json]    输出格式 / output format");
         println!("  mcp                       显示当前 MCP 配置 / show current MCP config");
         println!("  server add <json|@file>   添加或更新 MCP 配置 / add or update config");
         println!("  server rm <name>          移除 MCP 配置 / remove config");
+        println!("  server list               列出服务器及运行状态 / list servers and state");
+        println!(
+            "  server show <name> [--rendered]  显示服务器配置（可含渲染结果）/ show server config"
+        );
         println!("  start <name>|all          启动客户端 / start client(s)");
         println!("  stop <name>|all           停止客户端 / stop client(s)");
         println!("  inputs load <@file>       从文件加载 inputs 定义 / load inputs");
         println!("  inputs add <json|@file>   添加 input 定义 / add input definition");
         println!("  inputs update <json|@file> 更新 input 定义 / update input definition");
-        println!("  inputs rm <id>            移除 input 定义 / remove input definition");
+        println!("  inputs rm <id...>|all     移除 input 定义 / remove input definition(s)");
         println!("  inputs get <id>           获取 input 定义 / get input definition");
         println!("  inputs list               查看当前inputs的定义 / show inputs");
         println!("  inputs value list         列出当前 inputs 的缓存值 / list current cached input values");
@@ -84,60 +246,135 @@ impl CommandHandler {
         println!("  socket leave              离开房间 / leave office");
         println!("  notify update             触发配置更新通知 / emit config updated");
         println!("  render <json|@file>       测试渲染（占位符解析）");
+        println!("  validate                  校验当前配置（不连接）/ validate config without connecting");
         println!("  quit | exit               退出 / quit");
     }
 
-    /// 显示服务器状态
-    pub async fn show_status(&self) -> Result<(), CommandError> {
-        println!("服务器状态 / Server Status:");
-
+    /// 显示服务器状态，返回结构化结果
+    pub async fn show_status(&self) -> Result<Value, CommandError> {
         // 获取 Socket.IO 状态
         let socketio_client = self.computer.get_socketio_client();
-        let socketio_ref = socketio_client.read().await;
-        if let Some(ref weak_client) = *socketio_ref {
-            if let Some(client) = weak_client.upgrade() as Option<Arc<SmcpComputerClient>> {
+        let socketio = match socketio_client
+            .read()
+            .await
+            .as_ref()
+            .map(|weak| weak.upgrade())
+        {
+            Some(Some(client)) => {
+                let info = client.connection_info();
+                json!({
+                    "status": "connected",
+                    "url": client.get_url(),
+                    "namespace": info.namespace,
+                    "transport": info.transport,
+                    "connected_at": info.connected_at.to_rfc3339(),
+                    "connect_count": info.connect_count,
+                    "office_id": client.get_office_id().await,
+                    "computer_name": self.computer.name(),
+                })
+            }
+            Some(None) => json!({ "status": "disconnected" }),
+            None => json!({ "status": "not_connected" }),
+        };
+
+        // 获取 MCP Manager 状态
+        let initialized = self.computer.is_mcp_manager_initialized().await;
+        let servers: Vec<Value> = self
+            .computer
+            .get_server_status()
+            .await
+            .into_iter()
+            .map(|(name, active, state, last_error)| {
+                json!({
+                    "name": name,
+                    "active": active,
+                    "state": state,
+                    "last_error": last_error,
+                })
+            })
+            .collect();
+        let tools = if initialized {
+            self.computer
+                .get_available_tools()
+                .await
+                .ok()
+                .map(|tools| tools.into_iter().map(|tool| tool.name).collect::<Vec<_>>())
+        } else {
+            Some(Vec::new())
+        };
+
+        let status = json!({
+            "socketio": socketio,
+            "mcp_manager_initialized": initialized,
+            "servers": servers,
+            "tools": tools,
+        });
+
+        if self.format == OutputFormat::Json {
+            println!("{}", serde_json::to_string(&status)?);
+            return Ok(status);
+        }
+
+        println!("服务器状态 / Server Status:");
+        match status["socketio"]["status"].as_str() {
+            Some("connected") => {
+                let socketio = &status["socketio"];
                 println!("  Socket.IO: 已连接 / Connected");
-                println!("    URL: {}", client.get_url());
-                println!("    Namespace: {}", client.get_namespace());
-                if let Some(office_id) = client.get_office_id().await {
+                println!("    URL: {}", socketio["url"].as_str().unwrap_or_default());
+                println!(
+                    "    Namespace: {}",
+                    socketio["namespace"].as_str().unwrap_or_default()
+                );
+                println!(
+                    "    Transport: {}",
+                    socketio["transport"].as_str().unwrap_or_default()
+                );
+                println!(
+                    "    Connected at: {} (connects: {})",
+                    socketio["connected_at"].as_str().unwrap_or_default(),
+                    socketio["connect_count"]
+                );
+                if let Some(office_id) = socketio["office_id"].as_str() {
                     println!("    Office ID: {}", office_id);
                     println!("    Computer Name: {}", self.computer.name());
                 } else {
                     println!("    Office: 未加入 / Not joined");
                 }
-            } else {
-                println!("  Socket.IO: 已断开 / Disconnected");
             }
-        } else {
-            println!("  Socket.IO: 未连接 / Not connected");
+            Some("disconnected") => println!("  Socket.IO: 已断开 / Disconnected"),
+            _ => println!("  Socket.IO: 未连接 / Not connected"),
         }
 
-        // 获取 MCP Manager 状态
-        if self.computer.is_mcp_manager_initialized().await {
-            // 获取服务器状态列表
-            let server_status = self.computer.get_server_status().await;
-            let active_count = server_status
+        if initialized {
+            let active_count = servers
                 .iter()
-                .filter(|(_, active, _)| *active)
+                .filter(|server| server["active"] == true)
                 .count();
 
             println!("  MCP Manager: 已初始化 / Initialized");
             println!("  Active Servers: {}", active_count);
 
             // 显示每个服务器的状态
-            for (name, active, state) in server_status {
-                let status = if active {
+            for server in &servers {
+                let status = if server["active"] == true {
                     "运行中 / Running"
                 } else {
                     "已停止 / Stopped"
                 };
-                println!("    - {}: {} ({})", name, status, state);
+                println!(
+                    "    - {}: {} ({})",
+                    server["name"].as_str().unwrap_or_default(),
+                    status,
+                    server["state"].as_str().unwrap_or_default()
+                );
+                if let Some(err) = server["last_error"].as_str() {
+                    println!("      最近错误 / Last error: {}", err);
+                }
             }
 
-            // 获取可用工具数量
-            match self.computer.get_available_tools().await {
-                Ok(tools) => println!("  Available Tools: {}", tools.len()),
-                Err(_) => println!("  Available Tools: 获取失败 / Failed to get"),
+            match &status["tools"] {
+                Value::Array(tools) => println!("  Available Tools: {}", tools.len()),
+                _ => println!("  Available Tools: 获取失败 / Failed to get"),
             }
         } else {
             println!("  MCP Manager: 未初始化 / Not initialized");
@@ -145,37 +382,90 @@ impl CommandHandler {
             println!("  Available Tools: 0");
         }
 
-        Ok(())
+        Ok(status)
     }
 
-    /// 列出可用工具
-    pub async fn list_tools(&self) -> Result<(), CommandError> {
+    /// 列出可用工具，返回工具名数组
+    pub async fn list_tools(&self, format: OutputFormat) -> Result<Value, CommandError> {
         if !self.computer.is_mcp_manager_initialized().await {
+            if format == OutputFormat::Json {
+                println!("[]");
+                return Ok(json!([]));
+            }
             println!("MCP 管理器未初始化 / MCP manager not initialized");
             println!("请先添加并启动 MCP server，然后再执行 tools / Please add and start an MCP server before running 'tools'");
             println!();
             println!("示例 / Example:");
             println!("  server add @./config.json");
             println!("  start all");
-            return Ok(());
+            return Ok(json!([]));
         }
 
-        match self.computer.get_available_tools().await {
-            Ok(tools) => {
-                println!("可用工具 / Available Tools:");
-                for tool in tools {
-                    println!("  - {}", tool.name);
-                }
+        let names: Vec<String> = self
+            .computer
+            .get_available_tools()
+            .await?
+            .into_iter()
+            .map(|tool| tool.name)
+            .collect();
+        if format == OutputFormat::Json {
+            println!("{}", serde_json::to_string(&names)?);
+        } else {
+            println!("可用工具 / Available Tools:");
+            for name in &names {
+                println!("  - {}", name);
             }
-            Err(e) => {
-                return Err(CommandError::ComputerError(e));
+        }
+        Ok(json!(names))
+    }
+
+    /// 显示工具的参数与返回值 schema，`name` 为空时显示全部工具
+    ///
+    /// 指定 `name` 时返回单个对象，否则返回数组。
+    pub async fn show_tool_schemas(
+        &self,
+        name: Option<&str>,
+        format: OutputFormat,
+    ) -> Result<Value, CommandError> {
+        let tools = self.computer.get_available_tools().await?;
+        let view = match name {
+            Some(name) => {
+                let tool = tools
+                    .into_iter()
+                    .find(|tool| tool.name == name)
+                    .ok_or_else(|| {
+                        CommandError::InvalidCommand(format!("未知的工具 / Unknown tool: {}", name))
+                    })?;
+                tool_schema(tool)
+            }
+            None => Value::Array(tools.into_iter().map(tool_schema).collect()),
+        };
+
+        match format {
+            OutputFormat::Json => println!("{}", serde_json::to_string(&view)?),
+            OutputFormat::Text => {
+                let entries = match &view {
+                    Value::Array(entries) => entries.clone(),
+                    entry => vec![entry.clone()],
+                };
+                for entry in entries {
+                    println!("{}:", entry["name"].as_str().unwrap_or_default());
+                    println!(
+                        "  params_schema: {}",
+                        serde_json::to_string_pretty(&entry["params_schema"])?
+                    );
+                    println!(
+                        "  return_schema: {}",
+                        serde_json::to_string_pretty(&entry["return_schema"])?
+                    );
+                }
             }
         }
-        Ok(())
+        Ok(view)
     }
 
-    /// 显示 MCP 配置
-    pub async fn show_mcp_config(&self) -> Result<(), CommandError> {
+    /// 显示 MCP 配置，返回 `{"servers":[...],"inputs":[...]}`
+    pub async fn show_mcp_config(&self) -> Result<Value, CommandError> {
         // 获取服务器配置
         let servers = self.computer.list_mcp_servers().await;
 
@@ -187,12 +477,75 @@ impl CommandHandler {
             "inputs": inputs
         });
 
-        println!("当前 MCP 配置 / Current MCP Config:");
-        println!("{}", serde_json::to_string_pretty(&config)?);
+        if self.format == OutputFormat::Json {
+            println!("{}", serde_json::to_string(&config)?);
+        } else {
+            println!("当前 MCP 配置 / Current MCP Config:");
+            println!("{}", serde_json::to_string_pretty(&config)?);
+        }
+
+        Ok(config)
+    }
 
+    /// 列出服务器名称及运行状态
+    pub async fn list_servers(&self) -> Result<(), CommandError> {
+        let mut names: Vec<String> = self
+            .computer
+            .list_mcp_servers()
+            .await
+            .iter()
+            .map(|server| server.name().to_string())
+            .collect();
+        if names.is_empty() {
+            println!("没有服务器配置 / No servers configured");
+            return Ok(());
+        }
+        names.sort();
+
+        let status: HashMap<String, bool> = self
+            .computer
+            .get_server_status()
+            .await
+            .into_iter()
+            .map(|(name, active, _, _)| (name, active))
+            .collect();
+
+        println!("服务器 / Servers:");
+        for name in names {
+            let state = if status.get(&name).copied().unwrap_or(false) {
+                "运行中 / Running"
+            } else {
+                "已停止 / Stopped"
+            };
+            println!("  - {}: {}", name, state);
+        }
         Ok(())
     }
 
+    /// 显示单个服务器配置，`rendered` 为 true 时同时显示渲染结果
+    pub async fn show_server(&self, name: &str, rendered: bool) -> Result<Value, CommandError> {
+        let raw = self.computer.get_server_config(name).await.ok_or_else(|| {
+            CommandError::InvalidCommand(format!("未知的服务器 / Unknown server: {}", name))
+        })?;
+
+        let view = if rendered {
+            let rendered = self
+                .computer
+                .get_rendered_server_config(name)
+                .await?
+                .unwrap_or_else(|| raw.clone());
+            json!({
+                "raw": raw,
+                "rendered": rendered
+            })
+        } else {
+            serde_json::to_value(&raw)?
+        };
+
+        println!("{}", serde_json::to_string_pretty(&view)?);
+        Ok(view)
+    }
+
     /// 添加或更新服务器配置
     pub async fn add_server(&mut self, config_str: &str) -> Result<(), CommandError> {
         let config: Value = if let Some(path) = config_str.strip_prefix('@') {
@@ -328,25 +681,35 @@ impl CommandHandler {
         namespace: &str,
         auth: &Option<String>,
         headers: &Option<String>,
-    ) -> Result<(), CommandError> {
-        self.computer
+    ) -> Result<ConnectionInfo, CommandError> {
+        let client = self
+            .computer
             .connect_socketio(url, namespace, auth, headers)
             .await?;
-        println!("✅ 已连接到 Socket.IO: {} / Connected to Socket.IO", url);
-        Ok(())
+        let info = client.connection_info();
+        self.socketio_connection = Some(client);
+        println!(
+            "✅ 已连接到 Socket.IO: {} / Connected to Socket.IO",
+            info.url
+        );
+        println!(
+            "    Namespace: {}  Transport: {}",
+            info.namespace, info.transport
+        );
+        Ok(info)
     }
 
     /// 断开 SocketIO 连接 / Disconnect SocketIO
     pub async fn disconnect_socketio(&mut self) -> Result<(), CommandError> {
+        self.socketio_connection = None;
         self.computer.disconnect_socketio().await?;
         println!("✅ 已断开 Socket.IO 连接 / Disconnected from Socket.IO");
         Ok(())
     }
 
-    /// 加载服务器配置
+    /// 加载服务器配置，支持 `$include` 组合多个文件
     pub async fn load_config(&mut self, path: &Path) -> Result<(), CommandError> {
-        let content = std::fs::read_to_string(path)?;
-        let config: Value = serde_json::from_str(&content)?;
+        let config = read_config_with_includes(path)?;
 
         // 解析服务器配置数组
         if let Some(servers_array) = config.get("servers").and_then(|v| v.as_array()) {
@@ -377,9 +740,15 @@ impl CommandHandler {
         size: Option<u32>,
         uri: Option<&str>,
     ) -> Result<(), CommandError> {
-        // TODO: 实现获取桌面信息 - 需要等待 desktop 模块实现
+        let windows = self
+            .computer
+            .get_desktop(
+                size.map(|size| size.min(i32::MAX as u32) as i32),
+                uri.map(str::to_string),
+            )
+            .await?;
         let desktop = json!({
-            "windows": [],
+            "windows": windows,
             "size": size,
             "uri": uri
         });
@@ -388,19 +757,24 @@ impl CommandHandler {
         Ok(())
     }
 
-    /// 显示历史记录
-    pub async fn show_history(&self, n: Option<usize>) -> Result<(), CommandError> {
+    /// 显示最近 `n` 条历史记录（默认 10），返回 `{"history":[...]}`
+    pub async fn show_history(&self, n: Option<usize>) -> Result<Value, CommandError> {
         let history = self.computer.get_tool_history().await?;
+        let limit = n.unwrap_or(10).min(history.len());
+        let recent = &history[history.len() - limit..];
+        let view = json!({ "history": recent });
+
+        if self.format == OutputFormat::Json {
+            println!("{}", serde_json::to_string(&view)?);
+            return Ok(view);
+        }
 
         println!("最近工具调用历史 / Recent Tool Call History:");
 
         if history.is_empty() {
             println!("  (暂无记录 / No records yet)");
         } else {
-            let limit = n.unwrap_or(10).min(history.len());
-            let start_idx = history.len().saturating_sub(limit);
-
-            for (i, record) in history.iter().skip(start_idx).enumerate() {
+            for (i, record) in recent.iter().enumerate() {
                 println!(
                     "  {}. [{}] {}::{} - {}{}",
                     i + 1,
@@ -421,7 +795,7 @@ impl CommandHandler {
             }
         }
 
-        Ok(())
+        Ok(view)
     }
 
     /// 获取输入定义 / Get input definition
@@ -499,6 +873,29 @@ impl CommandHandler {
         Ok(())
     }
 
+    /// 校验当前配置 / Validate current config
+    pub async fn validate_config(&self) -> Result<bool, CommandError> {
+        let report = self.computer.validate_config().await?;
+        if report.servers.is_empty() {
+            println!("没有服务器配置 / No servers configured");
+        }
+        for server in &report.servers {
+            let mark = if server.errors.is_empty() {
+                "✅"
+            } else {
+                "❌"
+            };
+            println!("{} {}", mark, server.name);
+            for error in &server.errors {
+                println!("    error: {}", error);
+            }
+            for warning in &server.warnings {
+                println!("    warning: {}", warning);
+            }
+        }
+        Ok(report.is_valid())
+    }
+
     /// 工具调用调试 / Tool call debug
     pub async fn debug_tool_call(&self, tool_call_str: &str) -> Result<(), CommandError> {
         // 解析工具调用请求
@@ -643,6 +1040,43 @@ impl CommandHandler {
         Ok(removed)
     }
 
+    /// 批量移除输入定义 / Remove input definitions in bulk
+    ///
+    /// `all` 移除全部定义，返回已移除数量与不存在的 id。
+    /// `all` removes every definition; returns the removed count and the ids not found.
+    pub async fn remove_input_defs(
+        &mut self,
+        ids: &[&str],
+    ) -> Result<(usize, Vec<String>), CommandError> {
+        let targets: Vec<String> = if ids == ["all"] {
+            self.computer
+                .list_inputs()
+                .await?
+                .into_iter()
+                .map(|input| input.id().to_string())
+                .collect()
+        } else {
+            ids.iter().map(|id| id.to_string()).collect()
+        };
+
+        let mut removed = 0;
+        let mut not_found = Vec::new();
+        for id in targets {
+            // 逐个移除以触发缓存清理 / Remove one by one so the cache is cleared for each
+            if self.computer.remove_input(&id).await? {
+                removed += 1;
+            } else if !not_found.contains(&id) {
+                not_found.push(id);
+            }
+        }
+
+        println!("已移除 {} 个 / Removed {}", removed, removed);
+        if !not_found.is_empty() {
+            println!("不存在的 id / Not found: {}", not_found.join(", "));
+        }
+        Ok((removed, not_found))
+    }
+
     /// 获取输入定义 / Get input definition
     pub async fn get_input_def(&self, id: &str) -> Result<(), CommandError> {
         match self.computer.get_input(id).await? {
@@ -662,6 +1096,7 @@ impl CommandHandler {
 mod tests {
     use super::*;
     use crate::computer::SilentSession;
+    use crate::mcp_clients::testing::sh_reply;
     use std::io::Write;
     use tempfile::NamedTempFile;
 
@@ -697,6 +1132,63 @@ mod tests {
         handler.show_help();
     }
 
+    #[tokio::test]
+    async fn test_show_tool_schemas() {
+        let init = r#"{"jsonrpc":"2.0","id":1,"result":{"capabilities":{}}}"#;
+        let tools = r#"{"jsonrpc":"2.0","id":3,"result":{"tools":[{"name":"echo","description":"Echo text","inputSchema":{"type":"object","properties":{"text":{"type":"string"}},"required":["text"]}}]}}"#;
+        let script = format!(
+            "read l; {}; read l; while read l; do {}; done",
+            sh_reply(init),
+            sh_reply(tools)
+        );
+        let server: MCPServerConfig = serde_json::from_value(json!({
+            "type": "Stdio",
+            "name": "echo_server",
+            "disabled": false,
+            "forbidden_tools": [],
+            "tool_meta": {},
+            "server_parameters": {
+                "command": "sh",
+                "args": ["-c", script],
+                "env": {},
+                "cwd": null
+            }
+        }))
+        .unwrap();
+        let computer = Computer::new(
+            "test_computer",
+            SilentSession::new("test_session"),
+            None,
+            Some(HashMap::from([("echo_server".to_string(), server)])),
+            true,
+            false,
+        );
+        computer.boot_up().await.unwrap();
+        let handler = create_test_handler(computer);
+
+        let view = handler
+            .show_tool_schemas(Some("echo"), OutputFormat::Json)
+            .await
+            .unwrap();
+        assert_eq!(view["name"], "echo");
+        assert_eq!(view["params_schema"]["required"], json!(["text"]));
+        assert!(view["return_schema"].is_null());
+
+        let all = handler
+            .show_tool_schemas(None, OutputFormat::Text)
+            .await
+            .unwrap();
+        assert_eq!(all.as_array().unwrap().len(), 1);
+
+        assert!(handler
+            .show_tool_schemas(Some("missing"), OutputFormat::Json)
+            .await
+            .is_err());
+        assert!("yaml".parse::<OutputFormat>().is_err());
+
+        handler.computer.shutdown().await.unwrap();
+    }
+
     #[tokio::test]
     async fn test_show_status_uninitialized() {
         let computer = create_test_computer().await;
@@ -845,6 +1337,149 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    /// 添加若干 PromptString 输入定义并设置缓存值 / Add PromptString inputs with cached values
+    async fn seed_inputs(handler: &mut CommandHandler, ids: &[&str]) {
+        for id in ids {
+            handler
+                .add_input(&format!(
+                    r#"{{"type": "PromptString", "id": "{}", "description": "d"}}"#,
+                    id
+                ))
+                .await
+                .unwrap();
+            handler
+                .computer
+                .set_input_value(id, json!(format!("value-{}", id)))
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_remove_multiple_input_defs() {
+        let computer = create_test_computer().await;
+        let mut handler = create_test_handler(computer);
+        seed_inputs(&mut handler, &["a", "b", "c"]).await;
+
+        let (removed, not_found) = handler
+            .remove_input_defs(&["a", "missing", "c"])
+            .await
+            .unwrap();
+        assert_eq!(removed, 2);
+        assert_eq!(not_found, vec!["missing".to_string()]);
+
+        let remaining = handler.computer.list_inputs().await.unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].id(), "b");
+
+        // 被移除的输入其缓存值也应被清除 / Cached values of removed inputs are cleared
+        let values = handler.computer.list_input_values().await.unwrap();
+        assert!(!values.contains_key("a"));
+        assert!(!values.contains_key("c"));
+        assert!(values.contains_key("b"));
+    }
+
+    #[tokio::test]
+    async fn test_remove_all_input_defs() {
+        let computer = create_test_computer().await;
+        let mut handler = create_test_handler(computer);
+        seed_inputs(&mut handler, &["a", "b", "c"]).await;
+
+        let (removed, not_found) = handler.remove_input_defs(&["all"]).await.unwrap();
+        assert_eq!(removed, 3);
+        assert!(not_found.is_empty());
+        assert!(handler.computer.list_inputs().await.unwrap().is_empty());
+        assert!(handler
+            .computer
+            .list_input_values()
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_validate_config_reports_bad_server() {
+        let mut servers = HashMap::new();
+        let server: MCPServerConfig = serde_json::from_value(json!({
+            "type": "Stdio",
+            "name": "broken",
+            "disabled": false,
+            "forbidden_tools": [],
+            "tool_meta": {},
+            "server_parameters": {
+                "command": "echo",
+                "args": ["${input:undefined}"],
+                "env": {}
+            }
+        }))
+        .unwrap();
+        servers.insert("broken".to_string(), server);
+        let computer = Computer::new(
+            "test_computer",
+            SilentSession::new("test_session"),
+            None,
+            Some(servers),
+            false,
+            false,
+        );
+        let handler = create_test_handler(computer);
+
+        assert!(!handler.validate_config().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_show_server_raw_and_rendered() {
+        let computer = create_test_computer().await;
+        computer
+            .add_or_update_input(MCPServerInput::PromptString(
+                crate::mcp_clients::model::PromptStringInput {
+                    id: "token".to_string(),
+                    description: "API token".to_string(),
+                    default: Some("secret".to_string()),
+                    password: Some(true),
+                },
+            ))
+            .await
+            .unwrap();
+        let server: MCPServerConfig = serde_json::from_value(json!({
+            "type": "Stdio",
+            "name": "api",
+            "disabled": false,
+            "forbidden_tools": [],
+            "tool_meta": {},
+            "server_parameters": {
+                "command": "echo",
+                "args": [],
+                "env": {"API_TOKEN": "${input:token}"}
+            }
+        }))
+        .unwrap();
+        computer.add_or_update_server(server).await.unwrap();
+        let handler = create_test_handler(computer);
+
+        let raw = handler.show_server("api", false).await.unwrap();
+        assert_eq!(
+            raw["server_parameters"]["env"]["API_TOKEN"],
+            "${input:token}"
+        );
+
+        let view = handler.show_server("api", true).await.unwrap();
+        assert_eq!(
+            view["raw"]["server_parameters"]["env"]["API_TOKEN"],
+            "${input:token}"
+        );
+        assert_eq!(
+            view["rendered"]["server_parameters"]["env"]["API_TOKEN"],
+            "secret"
+        );
+
+        assert!(handler.list_servers().await.is_ok());
+        assert!(matches!(
+            handler.show_server("missing", false).await,
+            Err(CommandError::InvalidCommand(_))
+        ));
+    }
+
     #[tokio::test]
     async fn test_show_history_empty() {
         let computer = create_test_computer().await;
@@ -855,6 +1490,40 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_json_format_outputs() {
+        let computer = create_test_computer().await;
+        let mut handler = create_test_handler(computer).with_format(OutputFormat::Json);
+        handler
+            .add_server(
+                r#"{"type": "Stdio", "name": "files", "disabled": true, "forbidden_tools": [],
+                    "tool_meta": {}, "server_parameters": {"command": "echo", "args": [], "env": {}}}"#,
+            )
+            .await
+            .unwrap();
+
+        // 每个结果序列化后都应能重新解析 / Every result must round-trip through JSON text
+        let reparse = |view: &Value| -> Value {
+            serde_json::from_str(&serde_json::to_string(view).unwrap()).unwrap()
+        };
+
+        let status = reparse(&handler.show_status().await.unwrap());
+        assert_eq!(status["socketio"]["status"], "not_connected");
+        assert_eq!(status["servers"][0]["name"], "files");
+        assert_eq!(status["servers"][0]["active"], false);
+        assert_eq!(status["tools"], json!([]));
+
+        let tools = reparse(&handler.list_tools(handler.format()).await.unwrap());
+        assert_eq!(tools, json!([]));
+
+        let mcp = reparse(&handler.show_mcp_config().await.unwrap());
+        assert_eq!(mcp["servers"][0]["name"], "files");
+        assert_eq!(mcp["inputs"], json!([]));
+
+        let history = reparse(&handler.show_history(None).await.unwrap());
+        assert_eq!(history, json!({ "history": [] }));
+    }
+
     #[tokio::test]
     async fn test_get_desktop() {
         let computer = create_test_computer().await;
@@ -912,6 +1581,109 @@ mod tests {
         Ok(())
     }
 
+    fn stdio_server_json(name: &str) -> Value {
+        json!({
+            "type": "Stdio",
+            "name": name,
+            "server_parameters": {"command": "echo", "args": [], "env": {}, "cwd": null}
+        })
+    }
+
+    #[test]
+    fn test_read_config_with_includes_merges_fragments() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("servers")).unwrap();
+        std::fs::write(
+            dir.path().join("servers/a.json"),
+            json!({"servers": [stdio_server_json("server_a")]}).to_string(),
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("servers/b.json"),
+            json!({
+                "servers": [stdio_server_json("server_b")],
+                "inputs": [{"type": "PromptString", "id": "token", "description": "Token"}]
+            })
+            .to_string(),
+        )
+        .unwrap();
+        let base = dir.path().join("base.json");
+        std::fs::write(
+            &base,
+            json!({
+                "$include": ["servers/a.json", "servers/b.json"],
+                "servers": [stdio_server_json("server_base")]
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        let config = read_config_with_includes(&base).unwrap();
+        let names: Vec<&str> = config["servers"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| s["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, vec!["server_a", "server_b", "server_base"]);
+        assert_eq!(config["inputs"][0]["id"], "token");
+
+        // 重复的服务器名称会被拒绝 / Duplicate server names are rejected
+        std::fs::write(
+            &base,
+            json!({
+                "$include": ["servers/a.json"],
+                "servers": [stdio_server_json("server_a")]
+            })
+            .to_string(),
+        )
+        .unwrap();
+        let err = read_config_with_includes(&base).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Duplicate servers entry 'server_a'"));
+    }
+
+    #[test]
+    fn test_read_config_with_includes_detects_cycle() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("a.json"),
+            json!({"$include": ["b.json"]}).to_string(),
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("b.json"),
+            json!({"$include": ["a.json"]}).to_string(),
+        )
+        .unwrap();
+
+        let err = read_config_with_includes(&dir.path().join("a.json")).unwrap_err();
+        assert!(err.to_string().contains("Cyclic $include"));
+    }
+
+    #[tokio::test]
+    async fn test_load_config_with_includes() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("fragment.json"),
+            json!({"servers": [stdio_server_json("included_server")]}).to_string(),
+        )
+        .unwrap();
+        let base = dir.path().join("base.json");
+        std::fs::write(&base, json!({"$include": ["fragment.json"]}).to_string()).unwrap();
+
+        let computer = create_test_computer().await;
+        let mut handler = create_test_handler(computer);
+        handler.load_config(&base).await.unwrap();
+
+        assert!(handler
+            .computer
+            .get_server_config("included_server")
+            .await
+            .is_some());
+    }
+
     // 表驱动测试示例 / Table-driven test example
     #[tokio::test]
     async fn test_add_server_validation() {
@@ -71,7 +71,33 @@ async fn handle_command(handler: &mut CommandHandler, line: &str) -> Result<(),
             handler.show_status().await?;
         }
         "tools" => {
-            handler.list_tools().await?;
+            let mut schema = false;
+            let mut name = None;
+            let mut format = handler.format();
+            let mut args = parts[1..].iter();
+            while let Some(arg) = args.next() {
+                match *arg {
+                    "--schema" => schema = true,
+                    "--format" => {
+                        let value = args.next().ok_or_else(|| {
+                            CommandError::InvalidCommand("--format 缺少参数".to_string())
+                        })?;
+                        format = value.parse()?;
+                    }
+                    _ if schema && name.is_none() && !arg.starts_with("--") => name = Some(*arg),
+                    _ => {
+                        return Err(CommandError::InvalidCommand(format!(
+                            "未知的 tools 参数: {}",
+                            arg
+                        )));
+                    }
+                }
+            }
+            if schema {
+                handler.show_tool_schemas(name, format).await?;
+            } else {
+                handler.list_tools(format).await?;
+            }
         }
         "mcp" => {
             handler.show_mcp_config().await?;
@@ -96,6 +122,16 @@ async fn handle_command(handler: &mut CommandHandler, line: &str) -> Result<(),
                     }
                     handler.remove_server(parts[2]).await?;
                 }
+                "list" | "ls" => {
+                    handler.list_servers().await?;
+                }
+                "show" => {
+                    if parts.len() < 3 {
+                        return Err(CommandError::InvalidCommand("缺少服务器名称".to_string()));
+                    }
+                    let rendered = parts[3..].contains(&"--rendered");
+                    handler.show_server(parts[2], rendered).await?;
+                }
                 _ => {
                     return Err(CommandError::InvalidCommand(format!(
                         "未知的 server 子命令: {}",
@@ -149,10 +185,11 @@ async fn handle_command(handler: &mut CommandHandler, line: &str) -> Result<(),
                 "rm" | "remove" => {
                     if parts.len() < 3 {
                         return Err(CommandError::InvalidCommand(
-                            "用法: inputs rm <id> / Usage: inputs rm <id>".to_string(),
+                            "用法: inputs rm <id...>|all / Usage: inputs rm <id...>|all"
+                                .to_string(),
                         ));
                     }
-                    handler.remove_input_def(parts[2]).await?;
+                    handler.remove_input_defs(&parts[2..]).await?;
                 }
                 "get" => {
                     if parts.len() < 3 {
@@ -268,6 +305,9 @@ async fn handle_command(handler: &mut CommandHandler, line: &str) -> Result<(),
             let config_str = line.splitn(3, ' ').nth(2).unwrap();
             handler.render_config(config_str).await?;
         }
+        "validate" => {
+            handler.validate_config().await?;
+        }
         "tc" => {
             if parts.len() < 2 {
                 return Err(CommandError::InvalidCommand(
@@ -16,7 +16,7 @@ pub mod commands;
 mod interactive;
 mod utils;
 
-use commands::CommandHandler;
+use commands::{CommandHandler, OutputFormat};
 
 /// A2C-SMCP Computer CLI - 计算机客户端命令行工具
 #[derive(Parser, Debug)]
@@ -52,6 +52,10 @@ pub struct Args {
     #[arg(long)]
     pub no_color: bool,
 
+    /// status/tools/mcp/history 的输出格式（默认: text）
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+
     #[command(subcommand)]
     pub command: Option<Commands>,
 }
@@ -88,6 +92,7 @@ pub fn main() {
         let namespace = args.namespace.clone();
         let auth = args.auth.clone();
         let headers = args.headers.clone();
+        let format = args.format;
 
         if let Some(ref command) = args.command {
             match command {
@@ -99,6 +104,7 @@ pub fn main() {
                         namespace,
                         auth,
                         headers,
+                        format,
                         config: config.clone(),
                         inputs: inputs.clone(),
                     };
@@ -114,6 +120,7 @@ pub fn main() {
                 namespace,
                 auth,
                 headers,
+                format,
                 config: None,
                 inputs: None,
             };
@@ -130,6 +137,7 @@ struct CliConfig {
     namespace: String,
     auth: Option<String>,
     headers: Option<String>,
+    format: OutputFormat,
     config: Option<PathBuf>,
     inputs: Option<PathBuf>,
 }
@@ -153,7 +161,8 @@ async fn run_command(config: CliConfig) {
         auth: config.auth.clone(),
         headers: config.headers.clone(),
     };
-    let mut handler = CommandHandler::new(computer, cli_config_for_handler);
+    let mut handler =
+        CommandHandler::new(computer, cli_config_for_handler).with_format(config.format);
 
     // 加载配置
     if let Some(inputs_path) = config.inputs {
@@ -0,0 +1,139 @@
+/*!
+* 文件名: blocking
+* 作者: JQQ
+* 创建日期: 2025/12/16
+* 最后修改日期: 2025/12/16
+* 版权: 2023 JQQ. All rights reserved.
+* 依赖: tokio
+* 描述: Computer 的同步封装，供非异步调用方使用 / Synchronous Computer wrapper for non-async callers
+*/
+
+use tokio::runtime::Handle;
+
+use super::{Computer, Session, ToolCallRecord};
+use crate::errors::ComputerResult;
+use crate::mcp_clients::model::{CallToolResult, MCPServerConfig, Tool};
+
+/// `Computer` 的阻塞式封装，在给定运行时上同步执行各项操作
+/// Blocking wrapper around `Computer` that runs each operation synchronously on the given runtime
+///
+/// 供 GUI 事件循环等非 tokio 环境使用。各方法内部调用 [`Handle::block_on`]，
+/// 因此不能在异步上下文（如 tokio 任务内部）中调用，否则会 panic。
+/// Meant for non-tokio contexts such as a GUI event loop. Each method calls [`Handle::block_on`]
+/// internally, so it must not be called from within an async context (e.g. inside a tokio task),
+/// where it panics.
+pub struct BlockingComputer<S: Session> {
+    computer: Computer<S>,
+    handle: Handle,
+}
+
+impl<S: Session> BlockingComputer<S> {
+    /// 用运行时句柄封装 Computer / Wrap a Computer with a runtime handle
+    pub fn new(computer: Computer<S>, handle: Handle) -> Self {
+        Self { computer, handle }
+    }
+
+    /// 获取被封装的 Computer / Get the wrapped Computer
+    pub fn inner(&self) -> &Computer<S> {
+        &self.computer
+    }
+
+    /// 取回被封装的 Computer / Take back the wrapped Computer
+    pub fn into_inner(self) -> Computer<S> {
+        self.computer
+    }
+
+    /// 见 [`Computer::add_or_update_server`] / See [`Computer::add_or_update_server`]
+    pub fn add_or_update_server(&self, server: MCPServerConfig) -> ComputerResult<()> {
+        self.handle
+            .block_on(self.computer.add_or_update_server(server))
+    }
+
+    /// 见 [`Computer::remove_server`] / See [`Computer::remove_server`]
+    pub fn remove_server(&self, server_name: &str) -> ComputerResult<()> {
+        self.handle
+            .block_on(self.computer.remove_server(server_name))
+    }
+
+    /// 见 [`Computer::execute_tool`] / See [`Computer::execute_tool`]
+    pub fn execute_tool(
+        &self,
+        req_id: &str,
+        tool_name: &str,
+        parameters: serde_json::Value,
+        timeout: Option<f64>,
+    ) -> ComputerResult<CallToolResult> {
+        self.handle.block_on(
+            self.computer
+                .execute_tool(req_id, tool_name, parameters, timeout),
+        )
+    }
+
+    /// 见 [`Computer::get_available_tools`] / See [`Computer::get_available_tools`]
+    pub fn get_available_tools(&self) -> ComputerResult<Vec<Tool>> {
+        self.handle.block_on(self.computer.get_available_tools())
+    }
+
+    /// 见 [`Computer::get_tool_history`] / See [`Computer::get_tool_history`]
+    pub fn get_tool_history(&self) -> ComputerResult<Vec<ToolCallRecord>> {
+        self.handle.block_on(self.computer.get_tool_history())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::computer::SilentSession;
+    use crate::mcp_clients::model::{StdioServerConfig, StdioServerParameters};
+    use std::collections::HashMap;
+
+    fn disabled_server(name: &str) -> MCPServerConfig {
+        MCPServerConfig::Stdio(StdioServerConfig {
+            name: name.to_string(),
+            disabled: true,
+            forbidden_tools: vec![],
+            tool_meta: HashMap::new(),
+            default_tool_meta: None,
+            vrl: None,
+            server_parameters: StdioServerParameters {
+                command: "echo".to_string(),
+                args: vec![],
+                env: HashMap::new(),
+                cwd: None,
+            },
+        })
+    }
+
+    #[test]
+    fn test_blocking_server_management() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let computer = Computer::new("c", SilentSession::new("test"), None, None, false, false);
+        let blocking = BlockingComputer::new(computer, runtime.handle().clone());
+
+        blocking
+            .add_or_update_server(disabled_server("files"))
+            .unwrap();
+        assert_eq!(
+            runtime.block_on(blocking.inner().list_mcp_servers()).len(),
+            1
+        );
+        assert!(blocking.get_available_tools().unwrap().is_empty());
+
+        blocking.remove_server("files").unwrap();
+        assert!(runtime
+            .block_on(blocking.inner().list_mcp_servers())
+            .is_empty());
+    }
+
+    #[test]
+    fn test_blocking_execute_unknown_tool() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let computer = Computer::new("c", SilentSession::new("test"), None, None, false, false);
+        let blocking = BlockingComputer::new(computer, runtime.handle().clone());
+
+        assert!(blocking
+            .execute_tool("req-1", "missing", serde_json::json!({}), None)
+            .is_err());
+        assert!(blocking.get_tool_history().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,243 @@
+/*!
+* 文件名: pipeline
+* 作者: JQQ
+* 创建日期: 2025/12/16
+* 最后修改日期: 2025/12/16
+* 版权: 2023 JQQ. All rights reserved.
+* 依赖: tokio, tracing, uuid
+* 描述: 工具结果后处理，Computer 与 Socket.IO 调用路径共用 / Tool result post-processing shared by the Computer and the Socket.IO call path
+*/
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+use tracing::{debug, error, warn};
+
+use super::{
+    CallContext, ErrorVerbosity, ResultTransform, MAX_RESULT_BLOBS, RESULT_BLOB_URI_PREFIX,
+};
+use crate::errors::{ComputerError, ComputerResult};
+use crate::mcp_clients::model::{
+    CallToolResult, Content, ReadResourceResult, TextResourceContents,
+};
+
+/// 工具结果后处理管线 / Tool result post-processing pipeline
+///
+/// 由 [`Computer`](super::Computer) 持有，并在连接时交给 Socket.IO 客户端，
+/// 使本地调用与 Agent 经服务器发起的调用得到相同的处理。
+/// Owned by the [`Computer`](super::Computer) and handed to the Socket.IO client on connect, so
+/// local calls and calls an Agent makes through the server are processed alike.
+#[derive(Clone, Default)]
+pub struct ResultPipeline {
+    /// 工具调用错误详细程度 / Tool call error verbosity
+    error_verbosity: ErrorVerbosity,
+    /// 全局结果转换 / Global result transforms
+    transforms: Vec<Arc<dyn ResultTransform>>,
+    /// 按工具名注册的结果转换 / Result transforms registered per tool name
+    tool_transforms: HashMap<String, Vec<Arc<dyn ResultTransform>>>,
+    /// 超过该字节数的结果以资源链接返回 / Results larger than this many bytes are returned as resource links
+    link_threshold: Option<usize>,
+    /// 已转为资源链接的结果 (uri, json)，最旧的在前 / Results replaced by resource links (uri, json), oldest first
+    blobs: Arc<Mutex<VecDeque<(String, String)>>>,
+}
+
+impl ResultPipeline {
+    /// 创建不做任何处理的管线 / Create a pipeline that leaves results untouched
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置工具调用错误详细程度 / Set tool call error verbosity
+    pub fn with_error_verbosity(mut self, verbosity: ErrorVerbosity) -> Self {
+        self.error_verbosity = verbosity;
+        self
+    }
+
+    /// 注册对所有工具生效的结果转换 / Register a result transform applied to every tool
+    pub fn with_transform(mut self, transform: Arc<dyn ResultTransform>) -> Self {
+        self.transforms.push(transform);
+        self
+    }
+
+    /// 注册对指定工具生效的结果转换，先于全局转换执行
+    /// Register a result transform for one tool, applied before the global transforms
+    pub fn with_tool_transform(
+        mut self,
+        tool_name: impl Into<String>,
+        transform: Arc<dyn ResultTransform>,
+    ) -> Self {
+        self.tool_transforms
+            .entry(tool_name.into())
+            .or_default()
+            .push(transform);
+        self
+    }
+
+    /// 设置结果转为资源链接的字节阈值 / Set the byte threshold above which results become resource links
+    pub fn with_link_threshold(mut self, bytes: usize) -> Self {
+        self.link_threshold = Some(bytes);
+        self
+    }
+
+    /// 是否注册了任何结果转换 / Whether any result transform is registered
+    pub fn has_transforms(&self) -> bool {
+        !self.transforms.is_empty() || self.tool_transforms.values().any(|t| !t.is_empty())
+    }
+
+    /// 获取工具调用错误详细程度 / Get tool call error verbosity
+    pub fn error_verbosity(&self) -> ErrorVerbosity {
+        self.error_verbosity
+    }
+
+    /// 依次执行工具转换与全局转换 / Apply the per-tool transforms, then the global ones
+    ///
+    /// 转换失败时结果被替换为错误结果。
+    /// A failing transform replaces the result with an error result.
+    pub async fn transform(&self, ctx: &CallContext, mut result: CallToolResult) -> CallToolResult {
+        let transforms = self
+            .tool_transforms
+            .get(&ctx.requested_tool)
+            .into_iter()
+            .flatten()
+            .chain(self.transforms.iter());
+        for transform in transforms {
+            result = match transform.transform(ctx, result).await {
+                Ok(result) => result,
+                Err(e) => {
+                    warn!(
+                        "Result transform failed for {}/{} (req_id: {}): {}",
+                        ctx.server, ctx.tool, ctx.req_id, e
+                    );
+                    return CallToolResult {
+                        content: vec![Content::Text {
+                            text: format!("Result transform failed: {}", e),
+                        }],
+                        is_error: true,
+                        structured_content: None,
+                        meta: None,
+                    };
+                }
+            };
+        }
+        result
+    }
+
+    /// 调用完成后的处理：按错误详细程度处理错误结果，并将超过阈值的结果替换为资源链接
+    /// Post-call processing: apply the error verbosity to error results and replace
+    /// over-threshold results with resource links
+    pub async fn finish(
+        &self,
+        req_id: &str,
+        result: CallToolResult,
+    ) -> ComputerResult<CallToolResult> {
+        if result.is_error && self.error_verbosity == ErrorVerbosity::Sanitized {
+            return Ok(Self::sanitize_error_result(req_id, result));
+        }
+        match self.link_threshold {
+            Some(threshold) => self.link_large_result(req_id, result, threshold).await,
+            None => Ok(result),
+        }
+    }
+
+    /// 读取由大结果生成的资源链接 / Read a resource link minted for a large result
+    ///
+    /// 返回的文本为完整 `CallToolResult` 的 JSON。
+    /// The returned text is the JSON of the full `CallToolResult`.
+    pub async fn read_resource(&self, uri: &str) -> ComputerResult<ReadResourceResult> {
+        let blobs = self.blobs.lock().await;
+        let (_, json) = blobs
+            .iter()
+            .find(|(blob_uri, _)| blob_uri == uri)
+            .ok_or_else(|| ComputerError::ValidationError(format!("Unknown resource: {}", uri)))?;
+
+        Ok(ReadResourceResult {
+            contents: vec![TextResourceContents {
+                uri: uri.to_string(),
+                text: json.clone(),
+                mime_type: Some("application/json".to_string()),
+            }],
+        })
+    }
+
+    /// 将超过阈值的结果保存并替换为资源链接 / Store an over-threshold result and replace it with a resource link
+    async fn link_large_result(
+        &self,
+        req_id: &str,
+        result: CallToolResult,
+        threshold: usize,
+    ) -> ComputerResult<CallToolResult> {
+        let json = serde_json::to_string(&result)?;
+        if json.len() <= threshold {
+            return Ok(result);
+        }
+
+        let uri = format!("{}{}", RESULT_BLOB_URI_PREFIX, uuid::Uuid::new_v4());
+        debug!(
+            "Tool result of {} bytes exceeds {} bytes, returning link {}",
+            json.len(),
+            threshold,
+            uri
+        );
+        let size = json.len() as u64;
+        {
+            let mut blobs = self.blobs.lock().await;
+            blobs.push_back((uri.clone(), json));
+            // 只保留最近的结果 / Keep only the most recent results
+            while blobs.len() > MAX_RESULT_BLOBS {
+                blobs.pop_front();
+            }
+        }
+
+        Ok(CallToolResult {
+            content: vec![Content::ResourceLink {
+                uri,
+                name: format!("{}-result", req_id),
+                mime_type: Some("application/json".to_string()),
+                size: Some(size),
+            }],
+            is_error: result.is_error,
+            structured_content: None,
+            meta: result.meta,
+        })
+    }
+
+    /// 隐藏错误详情，完整内容仅记录到日志 / Hide error detail, full content is only logged
+    fn sanitize_error_result(req_id: &str, result: CallToolResult) -> CallToolResult {
+        let correlation_id = uuid::Uuid::new_v4().to_string();
+        error!(
+            "Tool call failed (req_id: {}, correlation_id: {}): {:?}",
+            req_id, correlation_id, result.content
+        );
+
+        CallToolResult {
+            content: vec![Content::Text {
+                text: Self::sanitized_message(&correlation_id),
+            }],
+            is_error: true,
+            structured_content: None,
+            meta: result.meta,
+        }
+    }
+
+    /// 按错误详细程度生成返回给 Agent 的错误信息 / Build the error message returned to the Agent per the error verbosity
+    pub fn error_message(&self, error: &ComputerError) -> String {
+        if self.error_verbosity == ErrorVerbosity::Full {
+            return error.to_string();
+        }
+
+        let correlation_id = uuid::Uuid::new_v4().to_string();
+        error!(
+            "Tool call failed (correlation_id: {}): {}",
+            correlation_id, error
+        );
+        Self::sanitized_message(&correlation_id)
+    }
+
+    fn sanitized_message(correlation_id: &str) -> String {
+        format!(
+            "Tool call failed. Reference correlation id: {}",
+            correlation_id
+        )
+    }
+}
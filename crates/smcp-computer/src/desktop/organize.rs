@@ -142,6 +142,62 @@ pub fn organize_desktop(
     result
 }
 
+/// 合并多个服务器的窗口 / Merge windows from multiple servers
+///
+/// 规则 / Rules:
+///   1) 仅保留合法的 `window://` 资源。
+///   2) 去重键为 MCP ID（host）加解码后的窗口路径，查询参数（priority、fullscreen、title 等）
+///      属于展示提示，不参与去重；重复时保留 priority 更高者，相同则保留先出现者。
+///   3) 按 priority 降序、再按资源名称升序排序。
+///   4) 按 size 截断（None 表示不限；size<=0 则返回空）。
+///
+///   1) Only valid `window://` resources are kept.
+///   2) The dedup key is the MCP ID (host) plus the decoded window path; query parameters
+///      (priority, fullscreen, title, ...) are presentation hints and are ignored. Among
+///      duplicates the higher priority wins, ties keep the first seen.
+///   3) Sorted by priority descending, then by resource name ascending.
+///   4) Truncated to `size` (None means unlimited; 0 returns nothing).
+pub fn organize_windows(
+    resources: Vec<(ServerName, Resource)>,
+    size: Option<usize>,
+) -> Vec<Desktop> {
+    let mut merged: Vec<(String, i32, Resource)> = Vec::new();
+    let mut index_by_key: HashMap<String, usize> = HashMap::new();
+
+    for (_server, resource) in resources {
+        let Ok(uri) = WindowURI::new(&resource.uri) else {
+            continue;
+        };
+        let priority = uri.priority().unwrap_or(0);
+        let key = window_dedup_key(&uri);
+
+        match index_by_key.get(&key) {
+            Some(&idx) => {
+                if priority > merged[idx].1 {
+                    merged[idx] = (key, priority, resource);
+                }
+            }
+            None => {
+                index_by_key.insert(key.clone(), merged.len());
+                merged.push((key, priority, resource));
+            }
+        }
+    }
+
+    merged.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.2.name.cmp(&b.2.name)));
+
+    merged
+        .into_iter()
+        .take(size.unwrap_or(usize::MAX))
+        .map(|(_, _, resource)| resource.uri)
+        .collect()
+}
+
+/// 窗口去重键：MCP ID 加窗口路径 / Window dedup key: MCP ID plus window path
+fn window_dedup_key(uri: &WindowURI) -> String {
+    format!("{}/{}", uri.mcp_id(), uri.windows().join("/"))
+}
+
 /// 窗口项（内部使用） / Window item (internal use)
 struct WindowItem {
     resource: Resource,
@@ -641,4 +697,59 @@ mod tests {
         assert!(result[0].contains("window://serverB.mcp.com/b"));
         assert!(result[1].contains("window://serverA.mcp.com/a"));
     }
+
+    fn window_resource(server: &str, uri: &str, name: &str) -> (ServerName, Resource) {
+        (
+            server.to_string(),
+            Resource {
+                uri: uri.to_string(),
+                name: name.to_string(),
+                description: None,
+                mime_type: None,
+            },
+        )
+    }
+
+    #[test]
+    fn test_organize_windows_merges_overlapping_servers() {
+        let resources = vec![
+            window_resource("s1", "window://shared.mcp/board?priority=10", "board"),
+            window_resource("s1", "window://s1.mcp/logs", "logs"),
+            window_resource(
+                "s2",
+                "window://shared.mcp/board?priority=60&title=Board",
+                "board",
+            ),
+            window_resource("s2", "window://s2.mcp/alpha?priority=60", "alpha"),
+            window_resource("s2", "file:///not-a-window.txt", "file"),
+        ];
+
+        let result = organize_windows(resources, None);
+
+        assert_eq!(
+            result,
+            vec![
+                "window://s2.mcp/alpha?priority=60".to_string(),
+                "window://shared.mcp/board?priority=60&title=Board".to_string(),
+                "window://s1.mcp/logs".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_organize_windows_keeps_first_on_tie_and_caps_size() {
+        let resources = vec![
+            window_resource("s1", "window://shared.mcp/board?title=First", "board"),
+            window_resource("s2", "window://shared.mcp/board?title=Second", "board"),
+            window_resource("s2", "window://s2.mcp/extra", "extra"),
+        ];
+
+        let result = organize_windows(resources.clone(), Some(1));
+        assert_eq!(
+            result,
+            vec!["window://shared.mcp/board?title=First".to_string()]
+        );
+
+        assert!(organize_windows(resources, Some(0)).is_empty());
+    }
 }
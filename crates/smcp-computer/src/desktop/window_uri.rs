@@ -13,7 +13,7 @@ use url::Url;
 
 /// Window URI 解析器 / Window URI parser
 /// 对应 Python 侧的 WindowURI 类
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct WindowURI {
     /// 原始 URL / Original URL
     url: Url,
@@ -64,14 +64,19 @@ impl WindowURI {
         };
 
         // 验证 priority / Validate priority
-        if let Some(priority) = uri.priority() {
+        if let Some(raw) = uri.params.get("priority") {
+            let priority: i32 = raw
+                .parse()
+                .map_err(|_| WindowURIError::MalformedPriority(raw.clone()))?;
             if !(0..=100).contains(&priority) {
                 return Err(WindowURIError::InvalidPriority(priority));
             }
         }
 
         // 验证 fullscreen / Validate fullscreen
-        let _ = uri.fullscreen(); // Will error if invalid
+        if uri.params.contains_key("fullscreen") && uri.fullscreen().is_none() {
+            return Err(WindowURIError::InvalidFullscreen);
+        }
 
         Ok(uri)
     }
@@ -86,6 +91,16 @@ impl WindowURI {
         &self.windows
     }
 
+    /// 获取全部查询参数 / Get all query parameters
+    pub fn params(&self) -> &HashMap<String, String> {
+        &self.params
+    }
+
+    /// 获取单个查询参数，如 `title`、`app` / Get a single query parameter such as `title` or `app`
+    pub fn param(&self, key: &str) -> Option<&str> {
+        self.params.get(key).map(String::as_str)
+    }
+
     /// 获取优先级 / Get priority (0-100)
     pub fn priority(&self) -> Option<i32> {
         self.params.get("priority").and_then(|s| s.parse().ok())
@@ -180,6 +195,9 @@ pub enum WindowURIError {
     #[error("Invalid priority: {0}, must be between 0 and 100")]
     InvalidPriority(i32),
 
+    #[error("Malformed priority: {0:?}, expected an integer")]
+    MalformedPriority(String),
+
     #[error("Invalid fullscreen value")]
     InvalidFullscreen,
 }
@@ -218,6 +236,53 @@ mod tests {
         assert_eq!(uri.fullscreen(), Some(true));
     }
 
+    #[test]
+    fn test_parse_arbitrary_params() {
+        let uri =
+            WindowURI::new("window://com.example.mcp/page?title=My%20Board&app=editor&priority=5")
+                .unwrap();
+        assert_eq!(uri.param("title"), Some("My Board"));
+        assert_eq!(uri.param("app"), Some("editor"));
+        assert_eq!(uri.param("missing"), None);
+        assert_eq!(uri.params().len(), 3);
+        assert_eq!(uri.priority(), Some(5));
+    }
+
+    #[test]
+    fn test_to_string_round_trip() {
+        for raw in [
+            "window://com.example.mcp",
+            "window://com.example.mcp/dashboard/main",
+            "window://com.example.mcp/page?title=My%20Board&app=editor&fullscreen=true",
+        ] {
+            let uri = WindowURI::new(raw).unwrap();
+            let reparsed = WindowURI::new(&uri.to_string()).unwrap();
+            assert_eq!(reparsed, uri);
+            assert_eq!(reparsed.params(), uri.params());
+        }
+    }
+
+    #[test]
+    fn test_malformed_uri_rejected() {
+        assert!(matches!(
+            WindowURI::new("window://x?priority=high"),
+            Err(WindowURIError::MalformedPriority(raw)) if raw == "high"
+        ));
+        assert!(matches!(
+            WindowURI::new("window://x?fullscreen=maybe"),
+            Err(WindowURIError::InvalidFullscreen)
+        ));
+        assert!(matches!(
+            WindowURI::new("http://x"),
+            Err(WindowURIError::InvalidScheme(scheme)) if scheme == "http"
+        ));
+        assert!(matches!(
+            WindowURI::new("not a uri"),
+            Err(WindowURIError::InvalidURI(_))
+        ));
+        assert!(!is_window_uri("window://x?priority=high"));
+    }
+
     #[test]
     fn test_priority_bounds() {
         assert!(WindowURI::new("window://x?priority=0").is_ok());
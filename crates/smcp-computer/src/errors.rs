@@ -8,12 +8,31 @@
 * 描述: Computer模块的错误定义 / Error definitions for Computer module
 */
 
+use crate::mcp_clients::model::MCPClientError;
 use thiserror::Error;
 
 /// Computer模块的Result类型别名 / Result type alias for Computer module
 pub type ComputerResult<T> = Result<T, ComputerError>;
 
+/// 错误分类，供重连与重试逻辑判断 / Error classification for reconnect and retry logic
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// 暂时性错误，重试可能成功 / Transient error, a retry may succeed
+    Transient,
+    /// 致命错误，重试不会成功 / Fatal error, retrying will not help
+    Fatal,
+    /// 配置错误，需要修改配置后再试 / Configuration error, fix the configuration before retrying
+    Config,
+}
+
 /// Computer模块的错误类型 / Error type for Computer module
+///
+/// 连接类错误的约定 / Conventions for connection-related errors:
+/// - `ConnectionError`：无法建立、维持或使用连接（连接、发送、断开失败）
+///   / the connection could not be established, kept or used (connect, send, disconnect failures)
+/// - `TimeoutError`：等待对端响应超时 / timed out waiting for the peer
+/// - `ProtocolError`：对端响应格式不符合协议 / the peer's response does not follow the protocol
+/// - `PermissionError`：对端明确拒绝了请求 / the peer explicitly rejected the request
 #[derive(Debug, Error)]
 pub enum ComputerError {
     #[error("Tool name duplicated: {tool_name} in servers: {servers:?}")]
@@ -80,7 +99,8 @@ pub enum ComputerError {
     ProtocolError(String),
 
     #[error("Socket.IO error: {0}")]
-    /// Socket.IO错误 / Socket.IO error
+    /// Socket.IO错误，仅用于无法归入上述类别的底层错误
+    /// Socket.IO error, only for low-level errors that fit none of the categories above
     SocketIoError(String),
 
     #[error("Validation error: {0}")]
@@ -90,6 +110,55 @@ pub enum ComputerError {
     #[error("Invalid state: {0}")]
     /// 无效状态 / Invalid state
     InvalidState(String),
+
+    #[error("Operation cancelled: {0}")]
+    /// 操作被调用方取消 / Operation cancelled by the caller
+    Cancelled(String),
+}
+
+impl ComputerError {
+    /// 对错误进行分类 / Classify the error
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            ComputerError::ConnectionError(_)
+            | ComputerError::TimeoutError(_)
+            | ComputerError::ToolExecutionTimeout { .. }
+            | ComputerError::TransportError(_)
+            | ComputerError::SocketIoError(_)
+            | ComputerError::IoError(_) => ErrorKind::Transient,
+            ComputerError::InvalidConfiguration(_)
+            | ComputerError::InputNotFound { .. }
+            | ComputerError::VrlSyntaxError { .. }
+            | ComputerError::ToolNameDuplicated { .. }
+            | ComputerError::ServerNotActive { .. } => ErrorKind::Config,
+            ComputerError::McpClientError(err) => err.kind(),
+            ComputerError::SerializationError(_)
+            | ComputerError::RuntimeError(_)
+            | ComputerError::PermissionError(_)
+            | ComputerError::ProtocolError(_)
+            | ComputerError::ValidationError(_)
+            | ComputerError::InvalidState(_)
+            | ComputerError::Cancelled(_) => ErrorKind::Fatal,
+        }
+    }
+
+    /// 是否为暂时性错误 / Whether the error is transient
+    pub fn is_transient(&self) -> bool {
+        self.kind() == ErrorKind::Transient
+    }
+}
+
+impl From<MCPClientError> for ComputerError {
+    fn from(err: MCPClientError) -> Self {
+        match err {
+            MCPClientError::ConnectionError(msg) => ComputerError::ConnectionError(msg),
+            MCPClientError::ProtocolError(msg) => ComputerError::ProtocolError(msg),
+            MCPClientError::IoError(e) => ComputerError::IoError(e),
+            MCPClientError::JsonError(e) => ComputerError::SerializationError(e),
+            MCPClientError::TimeoutError(msg) => ComputerError::TimeoutError(msg),
+            MCPClientError::Other(msg) => ComputerError::RuntimeError(msg),
+        }
+    }
 }
 
 impl From<Box<dyn std::error::Error + Send + Sync>> for ComputerError {
@@ -145,3 +214,80 @@ pub enum McpClientError {
     /// 内部错误 / Internal error
     InternalError(String),
 }
+
+impl McpClientError {
+    /// 对错误进行分类 / Classify the error
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            McpClientError::NotConnected
+            | McpClientError::ConnectionFailed(_)
+            | McpClientError::ConnectionError(_)
+            | McpClientError::ProcessError(_)
+            | McpClientError::TimeoutError(_) => ErrorKind::Transient,
+            McpClientError::ConfigError(_) => ErrorKind::Config,
+            McpClientError::ToolCallFailed(_)
+            | McpClientError::InvalidState(_)
+            | McpClientError::ProtocolError(_)
+            | McpClientError::ToolError(_)
+            | McpClientError::InternalError(_) => ErrorKind::Fatal,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_kind_classification() {
+        // 连接与超时类错误可重试 / Connection and timeout errors are retryable
+        assert_eq!(
+            ComputerError::ConnectionError("refused".to_string()).kind(),
+            ErrorKind::Transient
+        );
+        assert_eq!(
+            ComputerError::TimeoutError("no ack".to_string()).kind(),
+            ErrorKind::Transient
+        );
+        assert!(
+            ComputerError::from(std::io::Error::from(std::io::ErrorKind::BrokenPipe))
+                .is_transient()
+        );
+
+        // 协议与拒绝类错误不可重试 / Protocol and rejection errors are fatal
+        assert_eq!(
+            ComputerError::ProtocolError("bad ack".to_string()).kind(),
+            ErrorKind::Fatal
+        );
+        assert_eq!(
+            ComputerError::PermissionError("office rejected".to_string()).kind(),
+            ErrorKind::Fatal
+        );
+
+        // 配置类错误 / Configuration errors
+        assert_eq!(
+            ComputerError::InputNotFound {
+                input_id: "token".to_string()
+            }
+            .kind(),
+            ErrorKind::Config
+        );
+        assert_eq!(
+            ComputerError::from(McpClientError::ConfigError("missing command".to_string())).kind(),
+            ErrorKind::Config
+        );
+    }
+
+    #[test]
+    fn test_mcp_client_error_conversion_keeps_kind() {
+        let err = ComputerError::from(MCPClientError::ConnectionError("closed".to_string()));
+        assert!(matches!(err, ComputerError::ConnectionError(_)));
+        assert_eq!(err.kind(), ErrorKind::Transient);
+
+        let err = ComputerError::from(MCPClientError::TimeoutError("30s".to_string()));
+        assert_eq!(err.kind(), ErrorKind::Transient);
+
+        let err = ComputerError::from(MCPClientError::ProtocolError("bad json".to_string()));
+        assert_eq!(err.kind(), ErrorKind::Fatal);
+    }
+}
@@ -11,19 +11,43 @@ use super::model::*;
 use super::providers::{
     CliInputProvider, CompositeInputProvider, EnvironmentInputProvider, InputProvider,
 };
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use tracing::{debug, error, info};
 
+/// 缓存条目 / Cache entry
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    value: InputValue,
+    /// 过期时间，None 表示永不过期 / Expiry time, None means never expires
+    expires_at: Option<Instant>,
+}
+
+impl CacheEntry {
+    fn new(value: InputValue, ttl: Option<Duration>) -> Self {
+        Self {
+            value,
+            expires_at: ttl.map(|ttl| Instant::now() + ttl),
+        }
+    }
+
+    fn is_expired(&self, now: Instant) -> bool {
+        self.expires_at.is_some_and(|at| at <= now)
+    }
+}
+
 /// 输入处理器 / Input handler
 pub struct InputHandler {
     /// 输入提供者 / Input provider
     provider: Arc<dyn InputProvider>,
     /// 缓存的输入值 / Cached input values
-    cache: Arc<RwLock<HashMap<String, InputValue>>>,
+    cache: Arc<RwLock<BTreeMap<CacheKey, CacheEntry>>>,
     /// 是否启用缓存 / Whether to enable cache
     enable_cache: bool,
+    /// 缓存条目的默认存活时间 / Default time-to-live of cache entries
+    cache_ttl: Option<Duration>,
 }
 
 impl InputHandler {
@@ -39,8 +63,9 @@ impl InputHandler {
 
         Self {
             provider: Arc::from(provider),
-            cache: Arc::new(RwLock::new(HashMap::new())),
+            cache: Arc::new(RwLock::new(BTreeMap::new())),
             enable_cache: true,
+            cache_ttl: None,
         }
     }
 
@@ -51,8 +76,9 @@ impl InputHandler {
     {
         Self {
             provider: Arc::new(provider),
-            cache: Arc::new(RwLock::new(HashMap::new())),
+            cache: Arc::new(RwLock::new(BTreeMap::new())),
             enable_cache: true,
+            cache_ttl: None,
         }
     }
 
@@ -62,6 +88,12 @@ impl InputHandler {
         self
     }
 
+    /// 设置缓存条目的默认存活时间 / Set default time-to-live of cache entries
+    pub fn with_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = Some(ttl);
+        self
+    }
+
     /// 获取单个输入 / Get single input
     pub async fn get_input(
         &self,
@@ -72,7 +104,7 @@ impl InputHandler {
 
         // 检查缓存 / Check cache
         if self.enable_cache {
-            let cache_key = self.build_cache_key(&request.id, &context);
+            let cache_key = CacheKey::from_context(&request.id, &context);
             if let Some(value) = self.get_cached_value(&cache_key).await {
                 debug!("Using cached value for: {}", request.id);
                 return Ok(InputResponse {
@@ -101,8 +133,8 @@ impl InputHandler {
         if self.enable_cache {
             if let Ok(ref resp) = response {
                 if !resp.cancelled {
-                    let cache_key = self.build_cache_key(&request.id, &context);
-                    self.cache_value(cache_key, resp.value.clone()).await;
+                    let cache_key = CacheKey::from_context(&request.id, &context);
+                    self.set_cached_value(cache_key, resp.value.clone()).await;
                 }
             }
         }
@@ -139,64 +171,104 @@ impl InputHandler {
 
     /// 清除特定缓存 / Clear specific cache
     pub async fn clear_cache_for(&self, id: &str, context: &InputContext) {
-        let cache_key = self.build_cache_key(id, context);
-        let mut cache = self.cache.write().await;
-        cache.remove(&cache_key);
-        debug!("Cleared cache for: {}", id);
+        let cache_key = CacheKey::from_context(id, context);
+        self.cache.write().await.remove(&cache_key);
+        debug!("Cleared cache for: {}", cache_key);
     }
 
-    /// 构建缓存键 / Build cache key
-    fn build_cache_key(&self, id: &str, context: &InputContext) -> String {
-        let mut key = id.to_string();
-
-        if let Some(server) = &context.server_name {
-            key = format!("{}:{}", key, server);
-        }
-
-        if let Some(tool) = &context.tool_name {
-            key = format!("{}:{}", key, tool);
-        }
-
-        // 添加其他元数据 / Add other metadata
-        if !context.metadata.is_empty() {
-            let mut metadata_pairs: Vec<_> = context.metadata.iter().collect();
-            metadata_pairs.sort_by(|(k1, _), (k2, _)| k1.cmp(k2)); // 确保顺序一致 / Ensure consistent order
-
-            for (k, v) in metadata_pairs {
-                key = format!("{}:{}={}", key, k, v);
+    /// 获取缓存值，过期条目在读取时清除 / Get cached value, expired entries are evicted on read
+    pub async fn get_cached_value(&self, key: &CacheKey) -> Option<InputValue> {
+        let now = Instant::now();
+        {
+            let cache = self.cache.read().await;
+            match cache.get(key) {
+                None => return None,
+                Some(entry) if !entry.is_expired(now) => return Some(entry.value.clone()),
+                Some(_) => {}
             }
         }
 
-        key
+        let mut cache = self.cache.write().await;
+        if cache.get(key).is_some_and(|entry| entry.is_expired(now)) {
+            cache.remove(key);
+            debug!("Evicted expired cache entry: {}", key);
+        }
+        None
     }
 
-    /// 获取缓存值 / Get cached value
-    async fn get_cached_value(&self, key: &str) -> Option<InputValue> {
-        let cache = self.cache.read().await;
-        cache.get(key).cloned()
+    /// 获取某个输入的所有有效缓存值，可按服务器过滤
+    /// Get all live cached values of an input, optionally filtered by server
+    ///
+    /// 结果按键排序，不带上下文的条目在前
+    /// Results are ordered by key, with the context-free entry first
+    pub async fn cached_values_for(
+        &self,
+        input_id: &str,
+        server: Option<&str>,
+    ) -> Vec<(CacheKey, InputValue)> {
+        let now = Instant::now();
+        let mut cache = self.cache.write().await;
+        Self::evict_expired_for(&mut cache, input_id, now);
+
+        cache
+            .range(CacheKey::new(input_id)..)
+            .take_while(|(key, _)| key.input_id == input_id)
+            .filter(|(key, _)| server.is_none() || key.server.as_deref() == server)
+            .map(|(key, entry)| (key.clone(), entry.value.clone()))
+            .collect()
     }
 
-    /// 缓存值 / Cache value
-    async fn cache_value(&self, key: String, value: InputValue) {
+    /// 获取所有有效缓存值 / Get all live cached values
+    pub async fn get_all_cached_values(&self) -> HashMap<CacheKey, InputValue> {
+        let now = Instant::now();
         let mut cache = self.cache.write().await;
-        cache.insert(key, value);
+        cache.retain(|_, entry| !entry.is_expired(now));
+        cache
+            .iter()
+            .map(|(key, entry)| (key.clone(), entry.value.clone()))
+            .collect()
     }
 
-    /// 获取所有缓存值 / Get all cached values
-    pub async fn get_all_cached_values(&self) -> HashMap<String, InputValue> {
-        let cache = self.cache.read().await;
-        cache.clone()
+    /// 使用默认存活时间设置缓存值 / Set cached value with the default time-to-live
+    pub async fn set_cached_value(&self, key: CacheKey, value: InputValue) {
+        self.set_cached_value_with_ttl(key, value, self.cache_ttl)
+            .await;
     }
 
-    /// 设置缓存值 / Set cached value
-    pub async fn set_cached_value(&self, key: String, value: InputValue) {
-        self.cache_value(key, value).await;
+    /// 使用指定存活时间设置缓存值 / Set cached value with the given time-to-live
+    pub async fn set_cached_value_with_ttl(
+        &self,
+        key: CacheKey,
+        value: InputValue,
+        ttl: Option<Duration>,
+    ) {
+        let mut cache = self.cache.write().await;
+        cache.insert(key, CacheEntry::new(value, ttl));
     }
 
     /// 删除缓存值 / Remove cached value
-    pub async fn remove_cached_value(&self, key: &str) -> Option<InputValue> {
+    pub async fn remove_cached_value(&self, key: &CacheKey) -> Option<InputValue> {
+        let now = Instant::now();
         let mut cache = self.cache.write().await;
-        cache.remove(key)
+        cache
+            .remove(key)
+            .filter(|entry| !entry.is_expired(now))
+            .map(|entry| entry.value)
+    }
+
+    /// 删除某个输入的所有缓存值，返回删除数量
+    /// Remove all cached values of an input, returning how many were removed
+    pub async fn remove_cached_values_for(&self, input_id: &str) -> usize {
+        let mut cache = self.cache.write().await;
+        let keys: Vec<CacheKey> = cache
+            .range(CacheKey::new(input_id)..)
+            .take_while(|(key, _)| key.input_id == input_id)
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in &keys {
+            cache.remove(key);
+        }
+        keys.len()
     }
 
     /// 清空所有缓存 / Clear all cache
@@ -204,6 +276,19 @@ impl InputHandler {
         self.cache.write().await.clear();
     }
 
+    /// 清除某个输入的过期条目 / Evict expired entries of an input
+    fn evict_expired_for(cache: &mut BTreeMap<CacheKey, CacheEntry>, input_id: &str, now: Instant) {
+        let expired: Vec<CacheKey> = cache
+            .range(CacheKey::new(input_id)..)
+            .take_while(|(key, _)| key.input_id == input_id)
+            .filter(|(_, entry)| entry.is_expired(now))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in expired {
+            cache.remove(&key);
+        }
+    }
+
     /// 从MCP服务器输入配置创建请求 / Create request from MCP server input configuration
     pub fn create_request_from_mcp_input(
         &self,
@@ -236,6 +321,19 @@ impl InputHandler {
                 required: true,
                 validation: None,
             },
+            crate::mcp_clients::model::MCPServerInput::PromptNumber(input) => InputRequest {
+                id: input.id.clone(),
+                input_type: InputType::Numeric {
+                    min: input.min,
+                    max: input.max,
+                    integer: input.integer,
+                },
+                title: input.description.clone(),
+                description: input.description.clone(),
+                default,
+                required: true,
+                validation: None,
+            },
             crate::mcp_clients::model::MCPServerInput::Command(input) => InputRequest {
                 id: input.id.clone(),
                 input_type: InputType::Command {
@@ -303,15 +401,20 @@ mod tests {
         assert!(handler.enable_cache);
     }
 
-    #[tokio::test]
-    async fn test_cache_key_generation() {
-        let handler = InputHandler::new();
+    #[test]
+    fn test_cache_key_generation() {
         let context = InputContext::new()
             .with_server_name("test_server".to_string())
             .with_tool_name("test_tool".to_string());
 
-        let key = handler.build_cache_key("test_input", &context);
-        assert_eq!(key, "test_input:test_server:test_tool");
+        let key = CacheKey::from_context("test_input", &context);
+        assert_eq!(
+            key,
+            CacheKey::new("test_input")
+                .with_server("test_server")
+                .with_tool("test_tool")
+        );
+        assert_eq!(key.to_string(), "test_input:test_server:test_tool");
     }
 
     #[tokio::test]
@@ -320,15 +423,102 @@ mod tests {
         let _context = InputContext::new();
 
         // 测试缓存设置和获取 / Test cache set and get
-        let key = "test_key";
+        let key = CacheKey::new("test_key");
         let value = InputValue::String("test_value".to_string());
 
-        handler.cache_value(key.to_string(), value.clone()).await;
-        let cached = handler.get_cached_value(key).await;
+        handler.set_cached_value(key.clone(), value.clone()).await;
+        let cached = handler.get_cached_value(&key).await;
 
         assert_eq!(cached, Some(value));
     }
 
+    #[tokio::test]
+    async fn test_cache_ttl_expiry() {
+        let handler = InputHandler::new().with_cache_ttl(Duration::from_millis(20));
+        let key = CacheKey::new("token");
+
+        handler
+            .set_cached_value(key.clone(), InputValue::String("short".to_string()))
+            .await;
+        handler
+            .set_cached_value_with_ttl(
+                CacheKey::new("token").with_server("files"),
+                InputValue::String("long".to_string()),
+                None,
+            )
+            .await;
+        assert!(handler.get_cached_value(&key).await.is_some());
+
+        tokio::time::sleep(Duration::from_millis(40)).await;
+
+        assert_eq!(handler.get_cached_value(&key).await, None);
+        let remaining = handler.cached_values_for("token", None).await;
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].1, InputValue::String("long".to_string()));
+        assert_eq!(handler.get_all_cached_values().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_context_scoped_retrieval() {
+        let handler = InputHandler::new();
+        let files = InputContext::new().with_server_name("files".to_string());
+        let git = InputContext::new()
+            .with_server_name("git".to_string())
+            .with_tool_name("commit".to_string());
+
+        handler
+            .set_cached_value(
+                CacheKey::new("token"),
+                InputValue::String("plain".to_string()),
+            )
+            .await;
+        handler
+            .set_cached_value(
+                CacheKey::from_context("token", &files),
+                InputValue::String("files".to_string()),
+            )
+            .await;
+        handler
+            .set_cached_value(
+                CacheKey::from_context("token", &git),
+                InputValue::String("git".to_string()),
+            )
+            .await;
+        // 前缀相同但 input_id 不同的条目不应被扫描到
+        // Entries whose input_id merely shares a prefix must not be scanned
+        handler
+            .set_cached_value(CacheKey::new("token_2"), InputValue::Number(2))
+            .await;
+
+        let all = handler.cached_values_for("token", None).await;
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0].0, CacheKey::new("token"));
+
+        let scoped = handler.cached_values_for("token", Some("git")).await;
+        assert_eq!(scoped.len(), 1);
+        assert_eq!(
+            scoped[0].0,
+            CacheKey::new("token")
+                .with_server("git")
+                .with_tool("commit")
+        );
+
+        handler.clear_cache_for("token", &files).await;
+        assert_eq!(
+            handler
+                .cached_values_for("token", Some("files"))
+                .await
+                .len(),
+            0
+        );
+
+        assert_eq!(handler.remove_cached_values_for("token").await, 2);
+        assert_eq!(
+            handler.get_cached_value(&CacheKey::new("token_2")).await,
+            Some(InputValue::Number(2))
+        );
+    }
+
     #[tokio::test]
     async fn test_create_request_from_mcp_input() {
         let handler = InputHandler::new();
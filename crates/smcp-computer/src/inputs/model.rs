@@ -121,6 +121,18 @@ pub enum InputType {
         #[serde(skip_serializing_if = "Option::is_none")]
         max: Option<i64>,
     },
+    /// 带浮点上下限的数字输入 / Number input with floating-point bounds
+    Numeric {
+        /// 最小值（含） / Minimum value (inclusive)
+        #[serde(skip_serializing_if = "Option::is_none")]
+        min: Option<f64>,
+        /// 最大值（含） / Maximum value (inclusive)
+        #[serde(skip_serializing_if = "Option::is_none")]
+        max: Option<f64>,
+        /// 是否只接受整数 / Whether only integers are accepted
+        #[serde(default)]
+        integer: bool,
+    },
     /// 布尔输入 / Boolean input
     Bool {
         /// 真值标签 / True label
@@ -255,6 +267,65 @@ impl Default for InputContext {
     }
 }
 
+/// 输入值缓存键 / Input value cache key
+///
+/// 按 `input_id`、服务器、工具排序，同一输入的所有条目相邻，便于前缀扫描
+/// Ordered by `input_id`, server, then tool, so all entries of one input are adjacent for prefix scans
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CacheKey {
+    /// 输入ID / Input ID
+    pub input_id: String,
+    /// 服务器名称 / Server name
+    pub server: Option<String>,
+    /// 工具名称 / Tool name
+    pub tool: Option<String>,
+}
+
+impl CacheKey {
+    /// 创建不带上下文的缓存键 / Create cache key without context
+    pub fn new(input_id: impl Into<String>) -> Self {
+        Self {
+            input_id: input_id.into(),
+            server: None,
+            tool: None,
+        }
+    }
+
+    /// 从输入上下文创建缓存键 / Create cache key from input context
+    pub fn from_context(input_id: impl Into<String>, context: &InputContext) -> Self {
+        Self {
+            input_id: input_id.into(),
+            server: context.server_name.clone(),
+            tool: context.tool_name.clone(),
+        }
+    }
+
+    /// 设置服务器名称 / Set server name
+    pub fn with_server(mut self, server: impl Into<String>) -> Self {
+        self.server = Some(server.into());
+        self
+    }
+
+    /// 设置工具名称 / Set tool name
+    pub fn with_tool(mut self, tool: impl Into<String>) -> Self {
+        self.tool = Some(tool.into());
+        self
+    }
+}
+
+impl std::fmt::Display for CacheKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.input_id)?;
+        if let Some(server) = &self.server {
+            write!(f, ":{}", server)?;
+        }
+        if let Some(tool) = &self.tool {
+            write!(f, ":{}", tool)?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
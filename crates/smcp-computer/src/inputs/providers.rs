@@ -27,6 +27,43 @@ pub trait InputProvider: Send + Sync {
     ) -> InputResult<InputResponse>;
 }
 
+/// 校验数字是否满足整数与上下限约束 / Validate a number against integer and bound constraints
+fn check_numeric(
+    value: f64,
+    min: Option<f64>,
+    max: Option<f64>,
+    integer: bool,
+) -> InputResult<InputValue> {
+    if integer && value.fract() != 0.0 {
+        return Err(InputError::ValidationFailed(format!(
+            "{} 不是整数 ({} is not an integer)",
+            value, value
+        )));
+    }
+    if let Some(min_val) = min {
+        if value < min_val {
+            return Err(InputError::ValidationFailed(format!(
+                "数值不能小于{} (Minimum value is {})",
+                min_val, min_val
+            )));
+        }
+    }
+    if let Some(max_val) = max {
+        if value > max_val {
+            return Err(InputError::ValidationFailed(format!(
+                "数值不能大于{} (Maximum value is {})",
+                max_val, max_val
+            )));
+        }
+    }
+
+    Ok(if integer {
+        InputValue::Number(value as i64)
+    } else {
+        InputValue::Float(value)
+    })
+}
+
 /// CLI输入提供者 / CLI input provider
 pub struct CliInputProvider {
     /// 超时时间 / Timeout duration
@@ -112,6 +149,20 @@ impl CliInputProvider {
         }
     }
 
+    /// 从标准输入读取浮点数 / Read floating-point number from stdin
+    async fn read_float(&self, prompt: &str) -> InputResult<f64> {
+        loop {
+            let input = self.read_string(prompt, false).await?;
+
+            match input.parse::<f64>() {
+                Ok(n) if n.is_finite() => return Ok(n),
+                _ => {
+                    println!("无效数字，请重新输入 (Invalid number, please try again)");
+                }
+            }
+        }
+    }
+
     /// 从标准输入读取布尔值 / Read boolean from stdin
     async fn read_bool(
         &self,
@@ -234,6 +285,10 @@ impl InputProvider for CliInputProvider {
 
                 InputValue::Number(num)
             }
+            InputType::Numeric { min, max, integer } => {
+                let num = self.read_float(&prompt).await?;
+                check_numeric(num, *min, *max, *integer)?
+            }
             InputType::Bool {
                 true_label,
                 false_label,
@@ -382,6 +437,15 @@ impl InputProvider for EnvironmentInputProvider {
                             ))
                         })?
                     }
+                    InputType::Numeric { min, max, integer } => {
+                        let num = value.parse::<f64>().map_err(|_| {
+                            InputError::ValidationFailed(format!(
+                                "Invalid number in environment variable: {}",
+                                env_name
+                            ))
+                        })?;
+                        check_numeric(num, *min, *max, *integer)?
+                    }
                     InputType::Bool { .. } => {
                         let lower = value.to_lowercase();
                         if lower == "true" || lower == "1" || lower == "yes" || lower == "是" {
@@ -512,4 +576,50 @@ mod tests {
         let provider = EnvironmentInputProvider::new().with_prefix("CUSTOM_".to_string());
         assert_eq!(provider.prefix, "CUSTOM_");
     }
+
+    #[test]
+    fn test_check_numeric_bounds() {
+        assert_eq!(
+            check_numeric(8080.0, Some(1.0), Some(65535.0), true).unwrap(),
+            InputValue::Number(8080)
+        );
+        assert_eq!(
+            check_numeric(0.5, Some(0.0), Some(1.0), false).unwrap(),
+            InputValue::Float(0.5)
+        );
+        assert!(check_numeric(0.0, Some(1.0), None, true).is_err());
+        assert!(check_numeric(70000.0, None, Some(65535.0), true).is_err());
+        assert!(check_numeric(1.5, None, None, true).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_environment_provider_rejects_out_of_bounds_number() {
+        let provider =
+            EnvironmentInputProvider::new().with_prefix("SMCP_NUMERIC_TEST_".to_string());
+        let request = InputRequest {
+            id: "port".to_string(),
+            input_type: InputType::Numeric {
+                min: Some(1.0),
+                max: Some(65535.0),
+                integer: true,
+            },
+            title: "Port".to_string(),
+            description: "Port".to_string(),
+            default: None,
+            required: true,
+            validation: None,
+        };
+
+        env::set_var("SMCP_NUMERIC_TEST_PORT", "70000");
+        let result = provider.get_input(&request, &InputContext::new()).await;
+        assert!(matches!(result, Err(InputError::ValidationFailed(_))));
+
+        env::set_var("SMCP_NUMERIC_TEST_PORT", "443");
+        let response = provider
+            .get_input(&request, &InputContext::new())
+            .await
+            .unwrap();
+        env::remove_var("SMCP_NUMERIC_TEST_PORT");
+        assert_eq!(response.value, InputValue::Number(443));
+    }
 }
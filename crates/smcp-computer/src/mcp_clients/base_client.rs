@@ -10,6 +10,7 @@
 use super::model::*;
 use crate::errors::ComputerError;
 use async_trait::async_trait;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use tokio::sync::{watch, Mutex, RwLock};
 use tokio::task::JoinHandle;
@@ -30,6 +31,51 @@ pub struct BaseMCPClient<P> {
     shutdown_tx: Arc<Mutex<Option<watch::Sender<bool>>>>,
     /// 状态变化回调 / State change callback
     state_change_callback: Option<Box<dyn Fn(ClientState, ClientState) + Send + Sync>>,
+    /// 下一个 JSON-RPC 请求ID / Next JSON-RPC request ID
+    next_request_id: Arc<AtomicU64>,
+}
+
+/// 构造 MCP `notifications/cancelled` 通知 / Build an MCP `notifications/cancelled` notification
+pub fn cancelled_notification(request_id: u64, reason: &str) -> serde_json::Value {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "method": "notifications/cancelled",
+        "params": {
+            "requestId": request_id,
+            "reason": reason
+        }
+    })
+}
+
+/// 请求未完成即被丢弃时执行取消动作的守卫
+/// Guard that runs a cancel action when a request is dropped before completing
+pub struct CancelOnDrop {
+    on_cancel: Option<Box<dyn FnOnce() + Send>>,
+}
+
+impl CancelOnDrop {
+    /// 创建守卫 / Create the guard
+    pub fn new<F>(on_cancel: F) -> Self
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        Self {
+            on_cancel: Some(Box::new(on_cancel)),
+        }
+    }
+
+    /// 请求已完成，不再取消 / The request completed, do not cancel
+    pub fn disarm(mut self) {
+        self.on_cancel = None;
+    }
+}
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        if let Some(on_cancel) = self.on_cancel.take() {
+            on_cancel();
+        }
+    }
 }
 
 impl<P> BaseMCPClient<P>
@@ -49,9 +95,15 @@ where
             keep_alive_handle: Arc::new(Mutex::new(None)),
             shutdown_tx: Arc::new(Mutex::new(Some(shutdown_tx))),
             state_change_callback: None,
+            next_request_id: Arc::new(AtomicU64::new(1)),
         }
     }
 
+    /// 分配新的 JSON-RPC 请求ID / Allocate a new JSON-RPC request ID
+    pub fn next_request_id(&self) -> u64 {
+        self.next_request_id.fetch_add(1, Ordering::Relaxed)
+    }
+
     /// 设置状态变化回调 / Set state change callback
     pub fn set_state_change_callback<F>(&mut self, callback: F)
     where
@@ -76,8 +128,8 @@ where
         let old_state = *state;
         *state = new_state;
 
-        // 通知状态变化 / Notify state change
-        let _ = self.state_notifier.send(new_state);
+        // 通知状态变化，无订阅者时也保留最新值 / Notify state change, keeping the latest value even without subscribers
+        self.state_notifier.send_replace(new_state);
 
         // 调用回调 / Call callback
         if let Some(ref callback) = self.state_change_callback {
@@ -7,16 +7,22 @@
 * 依赖: tokio, reqwest, serde_json
 * 描述: HTTP类型的MCP客户端实现
 */
-use super::base_client::BaseMCPClient;
+use super::base_client::{cancelled_notification, BaseMCPClient, CancelOnDrop};
 use super::model::*;
+use super::sse_client::SseParser;
 use super::{ResourceCache, SubscriptionManager};
 use crate::desktop::window_uri::{is_window_uri, WindowURI};
 use async_trait::async_trait;
+use futures::StreamExt;
 use reqwest::Client;
 use serde_json;
+use std::collections::HashMap;
 use std::time::Duration;
 use tracing::{debug, info, warn};
 
+/// Streamable HTTP 会话ID头 / Streamable HTTP session id header
+const MCP_SESSION_ID_HEADER: &str = "Mcp-Session-Id";
+
 /// HTTP MCP客户端 / HTTP MCP client
 pub struct HttpMCPClient {
     /// 基础客户端 / Base client
@@ -25,6 +31,8 @@ pub struct HttpMCPClient {
     http_client: Client,
     /// 会话ID / Session ID
     session_id: std::sync::Arc<tokio::sync::Mutex<Option<String>>>,
+    /// 服务器信息 / Server info
+    server_info: std::sync::Arc<tokio::sync::Mutex<Option<ServerInfo>>>,
     /// 订阅管理器 / Subscription manager
     subscription_manager: SubscriptionManager,
     /// 资源缓存 / Resource cache
@@ -53,6 +61,7 @@ impl HttpMCPClient {
             base: BaseMCPClient::new(params),
             http_client,
             session_id: std::sync::Arc::new(tokio::sync::Mutex::new(None)),
+            server_info: std::sync::Arc::new(tokio::sync::Mutex::new(None)),
             subscription_manager: SubscriptionManager::new(),
             resource_cache: ResourceCache::new(Duration::from_secs(60)), // 默认 60 秒 TTL
         }
@@ -63,11 +72,23 @@ impl HttpMCPClient {
         &self,
         method: &str,
         params: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, MCPClientError> {
+        let request_id = self.base.next_request_id();
+        self.send_request_with_id(request_id, method, params).await
+    }
+
+    /// 使用指定ID发送JSON-RPC请求 / Send JSON-RPC request with the given ID
+    async fn send_request_with_id(
+        &self,
+        request_id: u64,
+        method: &str,
+        params: Option<serde_json::Value>,
     ) -> Result<serde_json::Value, MCPClientError> {
         let url = &self.base.params.url;
 
         let mut request_body = serde_json::json!({
             "jsonrpc": "2.0",
+            "id": request_id,
             "method": method,
         });
 
@@ -75,30 +96,64 @@ impl HttpMCPClient {
             request_body["params"] = p;
         }
 
-        // 添加请求ID / Add request ID
-        request_body["id"] = serde_json::Value::Number(serde_json::Number::from(
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs() as i64,
-        ));
-
         debug!("Sending HTTP request to {}: {}", url, request_body);
 
-        let mut request = self.http_client.post(url);
+        let response = self.post_message(&request_body).await?;
+
+        // 服务器可以直接返回 JSON，也可以返回 SSE 流并在其中给出最终结果
+        // The server may answer with plain JSON or with an SSE stream carrying the final result
+        let is_event_stream = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("text/event-stream"));
+        let response_body = if is_event_stream {
+            Self::read_event_stream(response, request_id).await?
+        } else {
+            response.json().await.map_err(|e| {
+                MCPClientError::ProtocolError(format!("Failed to parse response: {}", e))
+            })?
+        };
 
-        // 添加headers / Add headers
-        for (key, value) in &self.base.params.headers {
-            request = request.header(key, value);
+        debug!("Received HTTP response: {}", response_body);
+
+        Ok(response_body)
+    }
+
+    /// 发送通知（不需要响应） / Send notification (no response expected)
+    async fn send_notification(
+        &self,
+        method: &str,
+        params: Option<serde_json::Value>,
+    ) -> Result<(), MCPClientError> {
+        let mut notification = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": method,
+        });
+
+        if let Some(p) = params {
+            notification["params"] = p;
         }
 
-        // 添加content-type / Add content-type
-        request = request.header("Content-Type", "application/json");
+        self.post_message(&notification).await?;
+        Ok(())
+    }
 
-        let response =
-            request.json(&request_body).send().await.map_err(|e| {
-                MCPClientError::ConnectionError(format!("HTTP request failed: {}", e))
-            })?;
+    /// 携带会话ID POST 消息，并记录服务器分配的会话ID
+    /// POST a message with the session id and record the session id assigned by the server
+    async fn post_message(
+        &self,
+        body: &serde_json::Value,
+    ) -> Result<reqwest::Response, MCPClientError> {
+        let session_id = self.session_id.lock().await.clone();
+        let response = Self::post_json(
+            &self.http_client,
+            &self.base.params.url,
+            &self.base.params.headers,
+            session_id.as_deref(),
+            body,
+        )
+        .await?;
 
         if !response.status().is_success() {
             return Err(MCPClientError::ConnectionError(format!(
@@ -107,13 +162,114 @@ impl HttpMCPClient {
             )));
         }
 
-        let response_body: serde_json::Value = response.json().await.map_err(|e| {
-            MCPClientError::ProtocolError(format!("Failed to parse response: {}", e))
-        })?;
+        if let Some(session_id) = response
+            .headers()
+            .get(MCP_SESSION_ID_HEADER)
+            .and_then(|v| v.to_str().ok())
+        {
+            *self.session_id.lock().await = Some(session_id.to_string());
+        }
 
-        debug!("Received HTTP response: {}", response_body);
+        Ok(response)
+    }
 
-        Ok(response_body)
+    /// 从 SSE 响应中读取与请求 `id` 对应的结果消息
+    /// Read the result message matching the request `id` from an SSE response
+    async fn read_event_stream(
+        response: reqwest::Response,
+        request_id: u64,
+    ) -> Result<serde_json::Value, MCPClientError> {
+        let mut parser = SseParser::default();
+        let mut stream = response.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(|e| {
+                MCPClientError::ConnectionError(format!("Failed to read SSE response: {}", e))
+            })?;
+            for event in parser.feed(&chunk) {
+                if !matches!(event.event.as_deref(), None | Some("message")) {
+                    continue;
+                }
+                let message: serde_json::Value = match serde_json::from_str(&event.data) {
+                    Ok(message) => message,
+                    Err(e) => {
+                        warn!("Ignoring non JSON-RPC SSE message: {} ({})", event.data, e);
+                        continue;
+                    }
+                };
+                if message.get("id").and_then(|id| id.as_u64()) == Some(request_id) {
+                    return Ok(message);
+                }
+                // 结果之前的服务器通知与请求 / Server notifications and requests preceding the result
+                debug!("Received server message: {}", message);
+            }
+        }
+        Err(MCPClientError::ProtocolError(format!(
+            "SSE response ended without a result for request {}",
+            request_id
+        )))
+    }
+
+    /// POST 一条 JSON-RPC 消息 / POST a JSON-RPC message
+    async fn post_json(
+        http_client: &Client,
+        url: &str,
+        headers: &HashMap<String, String>,
+        session_id: Option<&str>,
+        body: &serde_json::Value,
+    ) -> Result<reqwest::Response, MCPClientError> {
+        let mut request = http_client.post(url);
+
+        // 添加headers / Add headers
+        for (key, value) in headers {
+            request = request.header(key, value);
+        }
+
+        // 添加会话ID / Add session id
+        if let Some(session_id) = session_id {
+            request = request.header(MCP_SESSION_ID_HEADER, session_id);
+        }
+
+        // 添加content-type与accept / Add content-type and accept
+        request = request
+            .header("Content-Type", "application/json")
+            .header("Accept", "application/json, text/event-stream");
+
+        request
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| MCPClientError::ConnectionError(format!("HTTP request failed: {}", e)))
+    }
+
+    /// 请求被丢弃时通知服务器取消 / Tell the server to cancel a request when it is dropped
+    fn cancel_on_drop(&self, request_id: u64) -> CancelOnDrop {
+        let http_client = self.http_client.clone();
+        let url = self.base.params.url.clone();
+        let headers = self.base.params.headers.clone();
+        let session_id = self.session_id.clone();
+        CancelOnDrop::new(move || {
+            let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+                return;
+            };
+            runtime.spawn(async move {
+                let notification = cancelled_notification(request_id, "Tool call cancelled");
+                let session_id = session_id.lock().await.clone();
+                if let Err(e) = Self::post_json(
+                    &http_client,
+                    &url,
+                    &headers,
+                    session_id.as_deref(),
+                    &notification,
+                )
+                .await
+                {
+                    warn!(
+                        "Failed to send cancellation for request {}: {}",
+                        request_id, e
+                    );
+                }
+            });
+        })
     }
 
     /// 初始化会话 / Initialize session
@@ -144,10 +300,12 @@ impl HttpMCPClient {
             if let Some(session_id) = result.get("sessionId").and_then(|v| v.as_str()) {
                 *self.session_id.lock().await = Some(session_id.to_string());
             }
+            *self.server_info.lock().await = Some(ServerInfo::from_initialize_result(result));
         }
 
         // 发送initialized通知 / Send initialized notification
-        self.send_request("notifications/initialized", None).await?;
+        self.send_notification("notifications/initialized", None)
+            .await?;
 
         info!("HTTP session initialized successfully");
         Ok(())
@@ -209,6 +367,10 @@ impl MCPClientProtocol for HttpMCPClient {
         self.base.state()
     }
 
+    fn subscribe_state(&self) -> Option<tokio::sync::watch::Receiver<ClientState>> {
+        Some(self.base.get_state_notifier())
+    }
+
     async fn connect(&self) -> Result<(), MCPClientError> {
         // 检查是否可以连接 / Check if can connect
         if !self.base.can_connect().await {
@@ -243,12 +405,13 @@ impl MCPClientProtocol for HttpMCPClient {
         }
 
         // 发送exit通知 / Send exit notification
-        if let Err(e) = self.send_request("exit", None).await {
+        if let Err(e) = self.send_notification("exit", None).await {
             warn!("Failed to send exit notification: {}", e);
         }
 
         // 清理会话ID / Clear session ID
         *self.session_id.lock().await = None;
+        *self.server_info.lock().await = None;
 
         // 更新状态 / Update state
         self.base.update_state(ClientState::Disconnected).await;
@@ -300,7 +463,15 @@ impl MCPClientProtocol for HttpMCPClient {
             "arguments": params
         });
 
-        let response = self.send_request("tools/call", Some(call_params)).await?;
+        // 调用被中止时向服务器发送 notifications/cancelled
+        // Send notifications/cancelled to the server if the call is aborted
+        let request_id = self.base.next_request_id();
+        let cancel_guard = self.cancel_on_drop(request_id);
+        let response = self
+            .send_request_with_id(request_id, "tools/call", Some(call_params))
+            .await;
+        cancel_guard.disarm();
+        let response = response?;
 
         if let Some(error) = response.get("error") {
             return Err(MCPClientError::ProtocolError(format!(
@@ -500,13 +671,128 @@ impl MCPClientProtocol for HttpMCPClient {
 
         Ok(())
     }
+
+    async fn server_info(&self) -> Option<ServerInfo> {
+        self.server_info.lock().await.clone()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use http_body_util::{BodyExt, Full};
+    use hyper::body::{Bytes, Incoming};
+    use hyper::server::conn::http1;
+    use hyper::service::service_fn;
+    use hyper::{Request, Response, StatusCode};
+    use hyper_util::rt::TokioIo;
     use serde_json::json;
     use std::collections::HashMap;
+    use std::convert::Infallible;
+    use std::sync::Arc;
+    use tokio::net::TcpListener;
+
+    /// 模拟服务器收到的请求：(method, Mcp-Session-Id, X-Api-Key)
+    /// Requests seen by the mock server: (method, Mcp-Session-Id, X-Api-Key)
+    type SeenRequests = Arc<std::sync::Mutex<Vec<(String, Option<String>, Option<String>)>>>;
+
+    /// 启动模拟 Streamable HTTP 服务器 / Start a mock Streamable HTTP server
+    async fn start_mock_server() -> (String, SeenRequests) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/mcp", listener.local_addr().unwrap());
+        let seen = SeenRequests::default();
+        let server_seen = seen.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let seen = server_seen.clone();
+                tokio::spawn(async move {
+                    let service = service_fn(move |req| handle_mock_request(seen.clone(), req));
+                    let _ = http1::Builder::new()
+                        .serve_connection(TokioIo::new(stream), service)
+                        .await;
+                });
+            }
+        });
+        (url, seen)
+    }
+
+    async fn handle_mock_request(
+        seen: SeenRequests,
+        req: Request<Incoming>,
+    ) -> Result<Response<Full<Bytes>>, Infallible> {
+        let header = |name: &str| {
+            req.headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        let session_id = header("mcp-session-id");
+        let api_key = header("x-api-key");
+        let body = req.into_body().collect().await.unwrap().to_bytes();
+        let message: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let method = message["method"].as_str().unwrap_or_default().to_string();
+        seen.lock()
+            .unwrap()
+            .push((method.clone(), session_id, api_key));
+
+        let Some(id) = message.get("id") else {
+            return Ok(Response::builder()
+                .status(StatusCode::ACCEPTED)
+                .body(Full::new(Bytes::new()))
+                .unwrap());
+        };
+        let response = match method.as_str() {
+            "initialize" => Response::builder()
+                .header("Content-Type", "application/json")
+                .header("Mcp-Session-Id", "session-abc")
+                .body(Full::new(Bytes::from(
+                    json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": {
+                            "protocolVersion": "2024-11-05",
+                            "capabilities": {"tools": {}},
+                            "serverInfo": {"name": "mock-http", "version": "0.1.0"}
+                        }
+                    })
+                    .to_string(),
+                ))),
+            "tools/list" => Response::builder()
+                .header("Content-Type", "application/json")
+                .body(Full::new(Bytes::from(
+                    json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": {"tools": [{
+                            "name": "echo",
+                            "description": "Echo the input text",
+                            "inputSchema": {"type": "object"}
+                        }]}
+                    })
+                    .to_string(),
+                ))),
+            // 以 SSE 流返回：先推送一条通知，再给出最终结果
+            // Answer with an SSE stream: a notification first, then the final result
+            _ => {
+                let notification = json!({"jsonrpc": "2.0", "method": "notifications/progress"});
+                let result = json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "result": {
+                        "content": [{"type": "text", "text": message["params"]["arguments"]["text"]}],
+                        "isError": false
+                    }
+                });
+                Response::builder()
+                    .header("Content-Type", "text/event-stream")
+                    .body(Full::new(Bytes::from(format!(
+                        "event: message\ndata: {}\n\nevent: message\ndata: {}\n\n",
+                        notification, result
+                    ))))
+            }
+        };
+        Ok(response.unwrap())
+    }
 
     #[tokio::test]
     async fn test_http_client_creation() {
@@ -790,4 +1076,51 @@ mod tests {
         let debug_str = format!("{:?}", client);
         assert!(debug_str.contains("HttpMCPClient"));
     }
+
+    #[tokio::test]
+    async fn test_session_id_header_propagated() {
+        let (url, seen) = start_mock_server().await;
+        let mut headers = HashMap::new();
+        headers.insert("X-Api-Key".to_string(), "secret".to_string());
+        let client = HttpMCPClient::new(HttpServerParameters { url, headers });
+
+        client.connect().await.unwrap();
+        let tools = client.list_tools().await.unwrap();
+        assert_eq!(tools[0].name, "echo");
+
+        // initialize 不带会话ID，之后的每个请求都回传服务器分配的会话ID并保留静态头
+        // initialize carries no session id; every later request echoes it alongside the static headers
+        let seen = seen.lock().unwrap().clone();
+        let methods: Vec<&str> = seen.iter().map(|(m, _, _)| m.as_str()).collect();
+        assert_eq!(
+            methods,
+            vec!["initialize", "notifications/initialized", "tools/list"]
+        );
+        assert_eq!(seen[0].1, None);
+        for (_, session_id, api_key) in &seen[1..] {
+            assert_eq!(session_id.as_deref(), Some("session-abc"));
+            assert_eq!(api_key.as_deref(), Some("secret"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_call_tool_with_event_stream_response() {
+        let (url, _seen) = start_mock_server().await;
+        let client = HttpMCPClient::new(HttpServerParameters {
+            url,
+            headers: HashMap::new(),
+        });
+        client.connect().await.unwrap();
+
+        let result = client
+            .call_tool("echo", json!({"text": "hello"}))
+            .await
+            .unwrap();
+        assert_eq!(
+            result.content,
+            vec![Content::Text {
+                text: "hello".to_string()
+            }]
+        );
+    }
 }
@@ -8,14 +8,20 @@
 * 描述: MCP服务器管理器，负责管理多个MCP服务器连接和工具调用路由
 */
 use super::model::*;
+use super::object_mapper::RetObjectMapper;
 use super::utils::client_factory;
-use super::vrl_runtime::VrlRuntime;
+use super::vrl_runtime::CompiledVrl;
+use crate::computer::{ManagerChangeHandler, ManagerChangeMessage};
+use crate::desktop::{is_window_uri, WindowInfo};
 use crate::errors::ComputerError;
 use serde_json::Value;
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::{BuildHasher, Hasher};
 use std::sync::Arc;
 use std::sync::Arc as StdArc;
-use tokio::sync::{watch, RwLock};
+use std::time::Duration;
+use tokio::sync::{broadcast, watch, RwLock};
+use tokio::task::JoinHandle;
 use tracing::{debug, error, info, warn};
 
 /// 工具名称重复错误 / Tool name duplication error
@@ -26,10 +32,204 @@ pub struct ToolNameDuplicatedError {
     pub servers: Vec<String>,
 }
 
+/// 启动策略 / Boot policy
+///
+/// 决定启动多个服务器时，单个服务器失败应如何处理。
+/// Decides how a single server failure is handled when booting multiple servers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BootPolicy {
+    /// 任一服务器失败则整体失败，并停止已启动的服务器 / Abort if any server fails and stop the started ones
+    AllOrNothing,
+    /// 启动所有能成功的服务器，失败记录在状态中 / Start every server that succeeds, record failures in status
+    #[default]
+    BestEffort,
+}
+
+/// 服务器工具列表变更通知 / Server notification announcing a tool list change
+const TOOLS_LIST_CHANGED: &str = "notifications/tools/list_changed";
+
+/// 服务器工具调用进度通知 / Server notification reporting tool call progress
+const PROGRESS: &str = "notifications/progress";
+
+/// 为某个服务器构造新客户端的工厂 / Factory building a fresh client for one server
+type ClientFactory = StdArc<dyn Fn() -> StdArc<dyn MCPClientProtocol> + Send + Sync>;
+
+/// 连接重试策略 / Connect retry policy
+///
+/// 默认不重试，与单次连接的行为一致。
+/// No retries by default, matching a single connect attempt.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReconnectPolicy {
+    /// 首次失败后的最大重试次数 / Maximum retries after the first failure
+    pub max_retries: u32,
+    /// 首次重试前的等待时间，之后每次翻倍 / Wait before the first retry, doubled for each later one
+    pub base_delay: Duration,
+    /// 单次等待的上限 / Upper bound for a single wait
+    pub max_delay: Duration,
+    /// 随机附加的等待比例（0.0 - 1.0）/ Fraction of the wait added at random (0.0 - 1.0)
+    pub jitter: f64,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 0,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(10),
+            jitter: 0.2,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// 第 `retry` 次重试（从 0 开始）前的等待时间 / Wait before retry number `retry` (0-based)
+    pub fn delay(&self, retry: u32) -> Duration {
+        let backoff = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_delay);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        if jitter == 0.0 {
+            return backoff;
+        }
+        // 无需密码学强度，借用 RandomState 的随机种子 / No cryptographic strength needed, borrow RandomState's random seed
+        let random = std::collections::hash_map::RandomState::new()
+            .build_hasher()
+            .finish() as f64
+            / u64::MAX as f64;
+        backoff.mul_f64(1.0 + jitter * random)
+    }
+}
+
+/// 校验 STDIO 服务器的工作目录是否为已存在的目录 / Check that a STDIO server's cwd is an existing directory
+pub fn validate_server_cwd(config: &MCPServerConfig) -> Result<(), ComputerError> {
+    if let MCPServerConfig::Stdio(stdio) = config {
+        if let Some(cwd) = &stdio.server_parameters.cwd {
+            if !std::path::Path::new(cwd).is_dir() {
+                return Err(ComputerError::InvalidConfiguration(format!(
+                    "Server {}: cwd '{}' does not exist or is not a directory",
+                    stdio.name, cwd
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 幂等缓存保留的调用数量 / Number of calls kept in the idempotency cache
+const MAX_COMPLETED_CALLS: usize = 64;
+
+/// 幂等调用的可共享结果，错误只保留描述 / Shareable outcome of an idempotent call, errors keep only their message
+type SharedOutcome = Result<CallToolResult, String>;
+
+/// 幂等缓存中的一次调用（进行中或已完成）/ A call in the idempotency cache, in flight or completed
+struct IdempotentCall {
+    /// 调用时请求的工具名 / Tool name as requested
+    tool_name: ToolName,
+    /// 参数指纹 / Parameters fingerprint
+    params_hash: u64,
+    /// 执行调用的服务器 / Server executing the call
+    server: ServerName,
+    /// 调用结果，完成前为 `None` / Call outcome, `None` until the call completes
+    outcome: watch::Receiver<Option<SharedOutcome>>,
+}
+
+impl IdempotentCall {
+    /// 首个调用已放弃且没有留下结果 / The first call was abandoned without an outcome
+    fn is_abandoned(&self) -> bool {
+        self.outcome.borrow().is_none() && self.outcome.has_changed().is_err()
+    }
+}
+
+/// 认领幂等键的结果 / Result of claiming an idempotency key
+enum Claim {
+    /// 由当前调用执行并发布结果 / The current call executes and publishes the outcome
+    Owner(watch::Sender<Option<SharedOutcome>>),
+    /// 等待首个调用的结果 / Wait for the first call's outcome
+    Waiter(watch::Receiver<Option<SharedOutcome>>),
+}
+
+/// 按幂等键登记调用的缓存，超出容量时淘汰最早的键
+/// Cache registering calls by idempotency key, evicting the oldest keys beyond capacity
+#[derive(Default)]
+struct IdempotencyCache {
+    order: VecDeque<String>,
+    entries: HashMap<String, IdempotentCall>,
+}
+
+impl IdempotencyCache {
+    /// 认领幂等键：首次出现时登记为进行中，重复的键等待首个调用
+    /// Claim an idempotency key: a new key is registered as in flight, a repeated key waits for the first call
+    ///
+    /// 键被用于不同的工具或参数时返回 [`ComputerError::ValidationError`]；工具已改由其他服务器
+    /// 提供或首个调用已放弃时重新执行。
+    /// Reusing a key for a different tool or parameters yields [`ComputerError::ValidationError`];
+    /// the call runs again when the tool is now served by another server or the first call was abandoned.
+    fn claim(
+        &mut self,
+        key: &str,
+        tool_name: &str,
+        params_hash: u64,
+        server: &str,
+    ) -> Result<Claim, ComputerError> {
+        if let Some(call) = self.entries.get(key) {
+            if call.tool_name != tool_name || call.params_hash != params_hash {
+                return Err(ComputerError::ValidationError(format!(
+                    "Idempotency key '{}' was already used for a different tool call",
+                    key
+                )));
+            }
+            if call.server == server && !call.is_abandoned() {
+                return Ok(Claim::Waiter(call.outcome.clone()));
+            }
+            self.order.retain(|k| k != key);
+        }
+
+        let (tx, rx) = watch::channel(None);
+        self.order.push_back(key.to_string());
+        self.entries.insert(
+            key.to_string(),
+            IdempotentCall {
+                tool_name: tool_name.to_string(),
+                params_hash,
+                server: server.to_string(),
+                outcome: rx,
+            },
+        );
+        while self.order.len() > MAX_COMPLETED_CALLS {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+        Ok(Claim::Owner(tx))
+    }
+
+    /// 移除失败的调用，使之后的重试重新执行 / Drop a failed call so later retries execute again
+    fn forget_failed(&mut self, key: &str) {
+        let failed = self
+            .entries
+            .get(key)
+            .is_some_and(|call| matches!(*call.outcome.borrow(), Some(Err(_))));
+        if failed {
+            self.entries.remove(key);
+            self.order.retain(|k| k != key);
+        }
+    }
+}
+
+/// 参数指纹，用于识别幂等键是否被复用于不同参数 / Parameters fingerprint detecting idempotency key reuse
+fn params_fingerprint(params: &Value) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    hasher.write(params.to_string().as_bytes());
+    hasher.finish()
+}
+
 /// MCP服务器管理器 / MCP server manager
 pub struct MCPServerManager {
     /// 服务器配置映射 / Server configuration mapping
     servers_config: Arc<RwLock<HashMap<ServerName, MCPServerConfig>>>,
+    /// 加载配置时编译的VRL程序 / VRL programs compiled when the config is loaded
+    vrl_programs: Arc<RwLock<HashMap<ServerName, StdArc<CompiledVrl>>>>,
     /// 活动客户端映射 / Active client mapping
     active_clients: Arc<RwLock<HashMap<ServerName, StdArc<dyn MCPClientProtocol>>>>,
     /// 工具到服务器的映射 / Tool to server mapping
@@ -38,12 +238,22 @@ pub struct MCPServerManager {
     alias_mapping: Arc<RwLock<HashMap<String, (ServerName, ToolName)>>>,
     /// 禁用工具集合 / Disabled tools set
     disabled_tools: Arc<RwLock<HashSet<ToolName>>>,
+    /// 服务器最近一次错误 / Last error per server
+    last_errors: Arc<RwLock<HashMap<ServerName, String>>>,
     /// 自动重连标志 / Auto reconnect flag
     auto_reconnect: Arc<RwLock<bool>>,
     /// 自动连接标志 / Auto connect flag
     auto_connect: Arc<RwLock<bool>>,
     /// 状态变化通知器 / State change notifier
     state_notifier: watch::Sender<ManagerState>,
+    /// 进行中与最近完成的调用，用于幂等重试 / In-flight and recently completed calls for idempotent retries
+    idempotency_cache: Arc<RwLock<IdempotencyCache>>,
+    /// 连接重试策略 / Connect retry policy
+    reconnect_policy: ReconnectPolicy,
+    /// 活动客户端的监督任务 / Supervisor tasks of active clients
+    supervisors: Arc<std::sync::Mutex<HashMap<ServerName, JoinHandle<()>>>>,
+    /// 管理器变更处理器 / Manager change handler
+    change_handler: Option<Arc<dyn ManagerChangeHandler>>,
 }
 
 /// 管理器状态 / Manager state
@@ -59,6 +269,21 @@ pub enum ManagerState {
     Error,
 }
 
+/// 管理器路由状态的只读快照 / Read-only snapshot of the manager's routing state
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManagerSnapshot {
+    /// 显示名称到服务器的映射 / Display tool name to server mapping
+    pub tools: HashMap<ToolName, ServerName>,
+    /// 别名到 (服务器, 原始工具名) 的映射 / Alias to (server, original tool name) mapping
+    pub aliases: HashMap<String, (ServerName, ToolName)>,
+    /// 被禁用的工具 / Disabled tools
+    pub disabled: HashSet<ToolName>,
+    /// 活动服务器名称（已排序）/ Active server names (sorted)
+    pub active: Vec<ServerName>,
+    /// 管理器状态 / Manager state
+    pub state: ManagerState,
+}
+
 impl MCPServerManager {
     /// 创建新的管理器 / Create new manager
     pub fn new() -> Self {
@@ -66,28 +291,147 @@ impl MCPServerManager {
 
         Self {
             servers_config: Arc::new(RwLock::new(HashMap::new())),
+            vrl_programs: Arc::new(RwLock::new(HashMap::new())),
             active_clients: Arc::new(RwLock::new(HashMap::new())),
             tool_mapping: Arc::new(RwLock::new(HashMap::new())),
             alias_mapping: Arc::new(RwLock::new(HashMap::new())),
             disabled_tools: Arc::new(RwLock::new(HashSet::new())),
+            last_errors: Arc::new(RwLock::new(HashMap::new())),
             auto_reconnect: Arc::new(RwLock::new(true)),
             auto_connect: Arc::new(RwLock::new(false)),
             state_notifier: state_tx,
+            idempotency_cache: Arc::new(RwLock::new(IdempotencyCache::default())),
+            reconnect_policy: ReconnectPolicy::default(),
+            supervisors: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            change_handler: None,
+        }
+    }
+
+    /// 设置管理器变更处理器 / Set the manager change handler
+    pub fn with_change_handler(mut self, handler: Arc<dyn ManagerChangeHandler>) -> Self {
+        self.change_handler = Some(handler);
+        self
+    }
+
+    /// 共享同一份状态的句柄，供后台任务使用 / Handle sharing the same state, for background tasks
+    fn shared(&self) -> Self {
+        Self {
+            servers_config: self.servers_config.clone(),
+            vrl_programs: self.vrl_programs.clone(),
+            active_clients: self.active_clients.clone(),
+            tool_mapping: self.tool_mapping.clone(),
+            alias_mapping: self.alias_mapping.clone(),
+            disabled_tools: self.disabled_tools.clone(),
+            last_errors: self.last_errors.clone(),
+            auto_reconnect: self.auto_reconnect.clone(),
+            auto_connect: self.auto_connect.clone(),
+            state_notifier: self.state_notifier.clone(),
+            idempotency_cache: self.idempotency_cache.clone(),
+            reconnect_policy: self.reconnect_policy,
+            supervisors: self.supervisors.clone(),
+            change_handler: self.change_handler.clone(),
         }
     }
 
+    /// 设置连接重试策略 / Set the connect retry policy
+    pub fn with_reconnect_policy(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect_policy = policy;
+        self
+    }
+
     /// 获取状态通知器 / Get state notifier
     pub fn get_state_notifier(&self) -> watch::Receiver<ManagerState> {
         self.state_notifier.subscribe()
     }
 
+    /// 获取路由状态的快照 / Get a snapshot of the routing state
+    pub async fn snapshot(&self) -> ManagerSnapshot {
+        let mut active: Vec<ServerName> =
+            self.active_clients.read().await.keys().cloned().collect();
+        active.sort();
+
+        ManagerSnapshot {
+            tools: self.tool_mapping.read().await.clone(),
+            aliases: self.alias_mapping.read().await.clone(),
+            disabled: self.disabled_tools.read().await.clone(),
+            active,
+            state: *self.state_notifier.borrow(),
+        }
+    }
+
     /// 更新管理器状态 / Update manager state
     async fn update_state(&self, state: ManagerState) {
-        let _ = self.state_notifier.send(state);
+        // 无订阅者时 send 不会更新值，快照需要始终保留当前状态
+        // send does not store the value without subscribers; snapshots need the current state
+        self.state_notifier.send_replace(state);
+    }
+
+    /// 编译服务器配置中的VRL脚本 / Compile the VRL script of a server config
+    fn compile_vrl(config: &MCPServerConfig) -> Result<Option<CompiledVrl>, ComputerError> {
+        config
+            .vrl()
+            .map(|script| {
+                CompiledVrl::compile(script).map_err(|e| {
+                    ComputerError::InvalidConfiguration(format!(
+                        "Invalid VRL script for server {}: {}",
+                        config.name(),
+                        e
+                    ))
+                })
+            })
+            .transpose()
+    }
+
+    /// 校验服务器配置中的返回值映射表 / Validate the ret_object_mapper specs of a server config
+    fn validate_ret_object_mappers(config: &MCPServerConfig) -> Result<(), ComputerError> {
+        let metas = config
+            .tool_meta()
+            .iter()
+            .map(|(tool_name, meta)| (tool_name.as_str(), meta))
+            .chain(config.default_tool_meta().map(|meta| ("<default>", meta)));
+        for (tool_name, meta) in metas {
+            if let Some(spec) = &meta.ret_object_mapper {
+                RetObjectMapper::parse(spec).map_err(|e| {
+                    ComputerError::InvalidConfiguration(format!(
+                        "Invalid ret_object_mapper for tool {} of server {}: {}",
+                        tool_name,
+                        config.name(),
+                        e
+                    ))
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 记录服务器的VRL程序，无脚本时移除 / Store a server's VRL program, removing it when there is no script
+    async fn set_vrl_program(&self, server_name: &str, program: Option<CompiledVrl>) {
+        let mut programs = self.vrl_programs.write().await;
+        match program {
+            Some(program) => {
+                programs.insert(server_name.to_string(), StdArc::new(program));
+            }
+            None => {
+                programs.remove(server_name);
+            }
+        }
     }
 
     /// 初始化管理器 / Initialize manager
+    ///
+    /// 任一服务器的VRL脚本无法编译或返回值映射表无效时返回 [`ComputerError::InvalidConfiguration`]，
+    /// 现有状态保持不变。
+    /// Returns [`ComputerError::InvalidConfiguration`] without touching the current state when any
+    /// server's VRL script fails to compile or its ret_object_mapper is invalid.
     pub async fn initialize(&self, servers: Vec<MCPServerConfig>) -> Result<(), ComputerError> {
+        let programs = servers
+            .iter()
+            .map(|server| {
+                Self::validate_ret_object_mappers(server)?;
+                Ok((server.name().to_string(), Self::compile_vrl(server)?))
+            })
+            .collect::<Result<Vec<_>, ComputerError>>()?;
+
         // 停止所有现有客户端 / Stop all existing clients
         self.stop_all().await?;
 
@@ -101,6 +445,9 @@ impl MCPServerManager {
                 configs.insert(server.name().to_string(), server);
             }
         }
+        for (server_name, program) in programs {
+            self.set_vrl_program(&server_name, program).await;
+        }
 
         // 刷新工具映射 / Refresh tool mapping
         self.refresh_tool_mapping().await?;
@@ -112,9 +459,12 @@ impl MCPServerManager {
         Ok(())
     }
 
-    /// 添加或更新服务器配置 / Add or update server configuration
+    /// 添加或更新服务器配置，内容未变化的已激活服务器不会重启
+    /// Add or update server configuration; an active server whose content is unchanged is not restarted
     pub async fn add_or_update_server(&self, config: MCPServerConfig) -> Result<(), ComputerError> {
         let server_name = config.name().to_string();
+        Self::validate_ret_object_mappers(&config)?;
+        let program = Self::compile_vrl(&config)?;
 
         // 检查是否已激活 / Check if already active
         let is_active = {
@@ -123,11 +473,18 @@ impl MCPServerManager {
         };
 
         if is_active {
-            let auto_reconnect = *self.auto_reconnect.read().await;
-            if auto_reconnect {
-                // 重启服务器 / Restart server
-                self.restart_server(&server_name).await?;
-            } else {
+            let unchanged = {
+                let configs = self.servers_config.read().await;
+                configs
+                    .get(&server_name)
+                    .is_some_and(|c| c.content_hash() == config.content_hash())
+            };
+            if unchanged {
+                debug!("Server {} config unchanged, skipping restart", server_name);
+                return Ok(());
+            }
+
+            if !*self.auto_reconnect.read().await {
                 return Err(ComputerError::InvalidConfiguration(format!(
                     "Server {} is active. Stop it before updating config",
                     server_name
@@ -136,14 +493,18 @@ impl MCPServerManager {
         }
 
         // 更新配置 / Update configuration
+        let disabled = config.disabled();
         {
             let mut configs = self.servers_config.write().await;
             configs.insert(server_name.clone(), config);
         }
+        self.set_vrl_program(&server_name, program).await;
 
-        // 检查是否需要自动连接 / Check if need auto connect
-        let auto_connect = *self.auto_connect.read().await;
-        if auto_connect && !is_active {
+        if is_active {
+            // 使用新配置重启服务器 / Restart server with the new config
+            self.restart_server(&server_name).await?;
+        } else if !disabled && *self.auto_connect.read().await {
+            // 检查是否需要自动连接 / Check if need auto connect
             self.start_client(&server_name).await?;
         }
 
@@ -153,6 +514,29 @@ impl MCPServerManager {
         Ok(())
     }
 
+    /// 以新的服务器集合重新加载配置：移除不再存在的服务器，只重启内容变化的服务器
+    /// Reload with a new set of servers: remove servers no longer present and restart only the changed ones
+    pub async fn reload_servers(&self, servers: Vec<MCPServerConfig>) -> Result<(), ComputerError> {
+        let keep: HashSet<String> = servers.iter().map(|s| s.name().to_string()).collect();
+        let removed: Vec<String> = {
+            let configs = self.servers_config.read().await;
+            configs
+                .keys()
+                .filter(|name| !keep.contains(*name))
+                .cloned()
+                .collect()
+        };
+
+        for name in removed {
+            self.remove_server(&name).await?;
+        }
+        for server in servers {
+            self.add_or_update_server(server).await?;
+        }
+
+        Ok(())
+    }
+
     /// 移除服务器配置 / Remove server configuration
     pub async fn remove_server(&self, server_name: &str) -> Result<(), ComputerError> {
         // 停止客户端 / Stop client
@@ -163,6 +547,7 @@ impl MCPServerManager {
             let mut configs = self.servers_config.write().await;
             configs.remove(server_name);
         }
+        self.set_vrl_program(server_name, None).await;
 
         // 刷新工具映射 / Refresh tool mapping
         self.refresh_tool_mapping().await?;
@@ -170,25 +555,54 @@ impl MCPServerManager {
         Ok(())
     }
 
-    /// 启动所有启用的服务器 / Start all enabled servers
+    /// 启动所有启用的服务器，任一失败即中止 / Start all enabled servers, aborting on the first failure
+    ///
+    /// 等同于 [`BootPolicy::AllOrNothing`] 的 [`start_all_with_policy`](Self::start_all_with_policy)。
+    /// Equivalent to [`start_all_with_policy`](Self::start_all_with_policy) with [`BootPolicy::AllOrNothing`].
     pub async fn start_all(&self) -> Result<(), ComputerError> {
-        let configs = self.servers_config.read().await;
-        let server_names: Vec<String> = configs
-            .iter()
-            .filter(|(_, config)| !config.disabled())
-            .map(|(name, _)| name.clone())
-            .collect();
+        self.start_all_with_policy(BootPolicy::AllOrNothing).await
+    }
 
-        drop(configs);
+    /// 按启动策略启动所有启用的服务器 / Start all enabled servers according to boot policy
+    pub async fn start_all_with_policy(&self, policy: BootPolicy) -> Result<(), ComputerError> {
+        let mut server_names: Vec<String> = {
+            let configs = self.servers_config.read().await;
+            configs
+                .iter()
+                .filter(|(_, config)| !config.disabled())
+                .map(|(name, _)| name.clone())
+                .collect()
+        };
+        server_names.sort();
 
+        let mut failed = Vec::new();
         for server_name in server_names {
-            self.start_client(&server_name).await?;
+            if let Err(e) = self.start_client(&server_name).await {
+                match policy {
+                    BootPolicy::AllOrNothing => {
+                        error!(
+                            "Server {} failed to start, aborting boot: {}",
+                            server_name, e
+                        );
+                        self.stop_all().await?;
+                        return Err(e);
+                    }
+                    BootPolicy::BestEffort => {
+                        warn!("Server {} failed to start, skipping: {}", server_name, e);
+                        failed.push(server_name);
+                    }
+                }
+            }
         }
 
         // 更新状态 / Update state
         self.update_state(ManagerState::Running).await;
 
-        info!("All servers started successfully");
+        if failed.is_empty() {
+            info!("All servers started successfully");
+        } else {
+            warn!("Servers started with failures: {:?}", failed);
+        }
         Ok(())
     }
 
@@ -219,19 +633,27 @@ impl MCPServerManager {
             }
         }
 
-        // 创建客户端 / Create client
-        let client = client_factory(config);
+        // 校验工作目录 / Validate working directory
+        if let Err(e) = validate_server_cwd(&config) {
+            self.last_errors
+                .write()
+                .await
+                .insert(server_name.to_string(), e.to_string());
+            return Err(e);
+        }
 
-        // 连接服务器 / Connect to server
-        client.connect().await.map_err(|e| {
-            ComputerError::ConnectionError(format!("Failed to connect to {}: {}", server_name, e))
-        })?;
+        // 创建客户端并连接，每次尝试使用新的客户端 / Create and connect a client, using a fresh one per attempt
+        let make_client: ClientFactory = StdArc::new(move || client_factory(config.clone()));
+        let client = self
+            .connect_with_retry(server_name, || make_client())
+            .await?;
 
         // 添加到活动客户端 / Add to active clients
         {
             let mut clients = self.active_clients.write().await;
-            clients.insert(server_name.to_string(), client);
+            clients.insert(server_name.to_string(), client.clone());
         }
+        self.spawn_supervisor(server_name, &client, make_client);
 
         // 刷新工具映射 / Refresh tool mapping
         self.refresh_tool_mapping().await?;
@@ -240,8 +662,245 @@ impl MCPServerManager {
         Ok(())
     }
 
+    /// 按重试策略连接，返回连接成功的客户端；全部失败时返回最后一次的连接错误
+    /// Connect following the retry policy and return the connected client; the last connection error is returned once all attempts fail
+    async fn connect_with_retry(
+        &self,
+        server_name: &str,
+        mut make_client: impl FnMut() -> StdArc<dyn MCPClientProtocol>,
+    ) -> Result<StdArc<dyn MCPClientProtocol>, ComputerError> {
+        let policy = self.reconnect_policy;
+        let mut retry = 0;
+        loop {
+            let client = make_client();
+            match client.connect().await {
+                Ok(()) => {
+                    self.last_errors.write().await.remove(server_name);
+                    return Ok(client);
+                }
+                Err(e) => {
+                    let message = format!("Failed to connect to {}: {}", server_name, e);
+                    self.last_errors
+                        .write()
+                        .await
+                        .insert(server_name.to_string(), message.clone());
+                    if retry >= policy.max_retries {
+                        return Err(ComputerError::ConnectionError(message));
+                    }
+                    let delay = policy.delay(retry);
+                    retry += 1;
+                    warn!(
+                        "{}; retrying in {:?} ({}/{})",
+                        message, delay, retry, policy.max_retries
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+            }
+        }
+    }
+
+    /// 监督活动客户端：连接中的客户端意外断开且开启自动重连时，按重试策略重连并刷新工具映射；
+    /// 收到工具列表变更通知时刷新工具映射并通知变更处理器
+    /// Supervise an active client: when a connected client drops unexpectedly and auto reconnect is on,
+    /// reconnect it following the retry policy and refresh the tool mapping; a tool list change
+    /// notification refreshes the tool mapping and notifies the change handler
+    fn spawn_supervisor(
+        &self,
+        server_name: &str,
+        client: &StdArc<dyn MCPClientProtocol>,
+        make_client: ClientFactory,
+    ) {
+        let Some(mut state_rx) = client.subscribe_state() else {
+            return;
+        };
+        // 在派生任务前读取当前状态，任务开始前发生的断开也会被察觉
+        // Read the current state before spawning so a drop before the task first runs is still seen
+        let mut last = *state_rx.borrow_and_update();
+        let mut notifications = client.subscribe_notifications();
+        let manager = self.shared();
+        let name = server_name.to_string();
+        let handle = tokio::spawn(async move {
+            loop {
+                let state = tokio::select! {
+                    changed = state_rx.changed() => {
+                        // 客户端被丢弃时结束监督 / Stop supervising once the client is dropped
+                        if changed.is_err() {
+                            return;
+                        }
+                        *state_rx.borrow_and_update()
+                    }
+                    message = next_notification(&mut notifications) => {
+                        match message.get("method").and_then(Value::as_str) {
+                            Some(TOOLS_LIST_CHANGED) => manager.handle_tool_list_changed(&name).await,
+                            Some(PROGRESS) => manager.handle_progress(&message).await,
+                            _ => {}
+                        }
+                        continue;
+                    }
+                };
+                let dropped = last == ClientState::Connected && state == ClientState::Disconnected;
+                last = state;
+                if !dropped {
+                    continue;
+                }
+                if !*manager.auto_reconnect.read().await {
+                    warn!(
+                        "Client {} disconnected unexpectedly, auto reconnect is disabled",
+                        name
+                    );
+                    return;
+                }
+
+                warn!("Client {} disconnected unexpectedly, reconnecting", name);
+                let client = match manager.connect_with_retry(&name, || make_client()).await {
+                    Ok(client) => client,
+                    Err(e) => {
+                        error!("Failed to reconnect {}: {}", name, e);
+                        return;
+                    }
+                };
+                let Some(rx) = client.subscribe_state() else {
+                    return;
+                };
+                state_rx = rx;
+                last = *state_rx.borrow_and_update();
+                notifications = client.subscribe_notifications();
+                manager
+                    .active_clients
+                    .write()
+                    .await
+                    .insert(name.clone(), client);
+                if let Err(e) = manager.refresh_tool_mapping().await {
+                    error!("Failed to refresh tools after reconnecting {}: {}", name, e);
+                }
+                info!("Client {} reconnected successfully", name);
+            }
+        });
+
+        if let Some(previous) = self
+            .supervisors
+            .lock()
+            .unwrap()
+            .insert(server_name.to_string(), handle)
+        {
+            previous.abort();
+        }
+    }
+
+    /// 服务器工具列表变更：刷新工具映射并通知变更处理器
+    /// A server's tool list changed: refresh the tool mapping and notify the change handler
+    async fn handle_tool_list_changed(&self, server_name: &str) {
+        debug!(
+            "Tool list of {} changed, refreshing tool mapping",
+            server_name
+        );
+        if let Err(e) = self.refresh_tool_mapping().await {
+            error!(
+                "Failed to refresh tools after {} changed: {}",
+                server_name, e
+            );
+            return;
+        }
+        if let Some(handler) = &self.change_handler {
+            if let Err(e) = handler
+                .on_change(ManagerChangeMessage::ToolListChanged)
+                .await
+            {
+                warn!(
+                    "Failed to handle tool list change of {}: {}",
+                    server_name, e
+                );
+            }
+        }
+    }
+
+    /// 服务器上报工具调用进度：转发给变更处理器
+    /// A server reported tool call progress: forward it to the change handler
+    async fn handle_progress(&self, message: &Value) {
+        let Some(handler) = &self.change_handler else {
+            return;
+        };
+        let params = &message["params"];
+        // 令牌由本端以字符串 req_id 下发 / Tokens are issued as string req_ids
+        let (Some(progress_token), Some(progress)) = (
+            params["progressToken"].as_str(),
+            params["progress"].as_f64(),
+        ) else {
+            debug!("Ignoring malformed progress notification: {}", message);
+            return;
+        };
+        let change = ManagerChangeMessage::ToolProgress {
+            progress_token: progress_token.to_string(),
+            progress,
+            total: params["total"].as_f64(),
+            message: params["message"].as_str().map(str::to_string),
+            // 部分结果随进度一同上报 / Partial content is reported along with the progress
+            content: params["content"].as_array().cloned().unwrap_or_default(),
+        };
+        if let Err(e) = handler.on_change(change).await {
+            warn!(
+                "Failed to forward progress of tool call {}: {}",
+                progress_token, e
+            );
+        }
+    }
+
+    /// 取消服务器的监督任务 / Cancel the supervisor of a server
+    fn cancel_supervisor(&self, server_name: &str) {
+        if let Some(handle) = self.supervisors.lock().unwrap().remove(server_name) {
+            handle.abort();
+        }
+    }
+
+    /// 挂载外部构造的客户端并刷新工具映射，工具名冲突时撤销挂载
+    /// Attach an externally constructed client and refresh the tool mapping; rolled back on a tool name conflict
+    #[cfg(any(test, feature = "test-util"))]
+    pub async fn attach_client(
+        &self,
+        config: MCPServerConfig,
+        client: StdArc<dyn MCPClientProtocol>,
+    ) -> Result<(), ComputerError> {
+        let server_name = config.name().to_string();
+        Self::validate_ret_object_mappers(&config)?;
+        let program = Self::compile_vrl(&config)?;
+
+        let client = self
+            .connect_with_retry(&server_name, || client.clone())
+            .await?;
+
+        self.servers_config
+            .write()
+            .await
+            .insert(server_name.clone(), config);
+        self.set_vrl_program(&server_name, program).await;
+        self.active_clients
+            .write()
+            .await
+            .insert(server_name.clone(), client.clone());
+
+        if let Err(e) = self.refresh_tool_mapping().await {
+            self.active_clients.write().await.remove(&server_name);
+            self.servers_config.write().await.remove(&server_name);
+            self.refresh_tool_mapping().await?;
+            return Err(e);
+        }
+
+        // 重连时复用同一个客户端 / Reconnects reuse the same client
+        let reconnect_client = client.clone();
+        self.spawn_supervisor(
+            &server_name,
+            &client,
+            StdArc::new(move || reconnect_client.clone()),
+        );
+        Ok(())
+    }
+
     /// 停止单个客户端 / Stop single client
     pub async fn stop_client(&self, server_name: &str) -> Result<(), ComputerError> {
+        // 先取消监督，避免主动断开被当作意外断开而重连
+        // Cancel supervision first so the intentional disconnect is not reconnected
+        self.cancel_supervisor(server_name);
+
         // 移除客户端 / Remove client
         let mut client = {
             let mut clients = self.active_clients.write().await;
@@ -303,13 +962,61 @@ impl MCPServerManager {
         Ok(())
     }
 
+    /// 在限定时间内停止所有服务器，超时的客户端被直接丢弃，返回被强制关闭的服务器
+    /// Stop all servers within the timeout; clients that time out are dropped. Returns the force-closed servers
+    pub async fn stop_all_with_timeout(
+        &self,
+        timeout: std::time::Duration,
+    ) -> Result<Vec<ServerName>, ComputerError> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        let server_names: Vec<String> = {
+            let clients = self.active_clients.read().await;
+            clients.keys().cloned().collect()
+        };
+
+        // 超时后仍会先轮询一次，能立即断开的客户端照常关闭
+        // Each stop is still polled once after the deadline, so clients that disconnect immediately close normally
+        let mut force_closed = Vec::new();
+        for server_name in server_names {
+            match tokio::time::timeout_at(deadline, self.stop_client(&server_name)).await {
+                Ok(result) => result?,
+                Err(_) => {
+                    warn!(
+                        "Timed out stopping {}, abandoning its connection",
+                        server_name
+                    );
+                    force_closed.push(server_name);
+                }
+            }
+        }
+
+        if !force_closed.is_empty() {
+            // 丢弃客户端并清理其工具映射 / Drop the clients and clear their tool mappings
+            {
+                let mut clients = self.active_clients.write().await;
+                for server_name in &force_closed {
+                    clients.remove(server_name);
+                }
+            }
+            self.refresh_tool_mapping().await?;
+        }
+
+        self.update_state(ManagerState::Initialized).await;
+        Ok(force_closed)
+    }
+
     /// 清空所有状态 / Clear all state
     async fn clear_all(&self) {
+        for (_, handle) in self.supervisors.lock().unwrap().drain() {
+            handle.abort();
+        }
         self.servers_config.write().await.clear();
+        self.vrl_programs.write().await.clear();
         self.active_clients.write().await.clear();
         self.tool_mapping.write().await.clear();
         self.alias_mapping.write().await.clear();
         self.disabled_tools.write().await.clear();
+        self.last_errors.write().await.clear();
     }
 
     /// 关闭管理器 / Close manager
@@ -427,26 +1134,83 @@ impl MCPServerManager {
             mapping.get(tool_name).cloned()
         };
 
-        let server_name = server_name.ok_or_else(|| {
-            ComputerError::InvalidConfiguration(format!(
+        let Some(server_name) = server_name else {
+            // 以原名调用被别名遮蔽的禁用工具同样拒绝
+            // A forbidden tool called by the original name hidden behind its alias is rejected too
+            let aliased = self
+                .alias_mapping
+                .read()
+                .await
+                .values()
+                .filter(|(_, original)| original == tool_name)
+                .map(|(server, _)| server.clone())
+                .collect::<Vec<_>>();
+            for server in aliased {
+                if self.is_forbidden(&server, tool_name).await {
+                    return Err(Self::forbidden_error(&server, tool_name));
+                }
+            }
+            return Err(ComputerError::InvalidConfiguration(format!(
                 "Tool '{}' not found in any active server",
                 tool_name
-            ))
-        })?;
-
-        // 检查是否为别名 / Check if it's an alias
-        let original_tool_name = {
-            let alias_map = self.alias_mapping.read().await;
-            if let Some((_, original)) = alias_map.get(tool_name) {
-                original.clone()
-            } else {
-                tool_name.to_string()
-            }
+            )));
         };
 
+        let original_tool_name = self.original_tool_name(tool_name).await;
+
+        // 按解析后的原始工具名再次检查，不依赖显示名 / Check the resolved original name, independent of the display name
+        if self.is_forbidden(&server_name, &original_tool_name).await {
+            return Err(Self::forbidden_error(&server_name, &original_tool_name));
+        }
+
         Ok((server_name, original_tool_name))
     }
 
+    /// 将别名解析为服务器上的原始工具名，非别名原样返回
+    /// Resolve an alias to the original tool name on its server; other names are returned as is
+    pub async fn original_tool_name(&self, tool_name: &str) -> String {
+        let alias_map = self.alias_mapping.read().await;
+        match alias_map.get(tool_name) {
+            Some((_, original)) => original.clone(),
+            None => tool_name.to_string(),
+        }
+    }
+
+    /// 工具是否在服务器的禁用列表中 / Whether a tool is in the server's forbidden list
+    async fn is_forbidden(&self, server_name: &str, original_tool_name: &str) -> bool {
+        self.servers_config
+            .read()
+            .await
+            .get(server_name)
+            .is_some_and(|config| {
+                config
+                    .forbidden_tools()
+                    .iter()
+                    .any(|forbidden| forbidden == original_tool_name)
+            })
+    }
+
+    fn forbidden_error(server_name: &str, original_tool_name: &str) -> ComputerError {
+        ComputerError::PermissionError(format!(
+            "Tool '{}' of server '{}' is forbidden by configuration",
+            original_tool_name, server_name
+        ))
+    }
+
+    /// 获取工具合并后的元数据（支持别名） / Get a tool's merged metadata (supports alias)
+    pub async fn get_tool_meta(&self, tool_name: &str) -> Option<ToolMeta> {
+        let server_name = self.tool_mapping.read().await.get(tool_name).cloned()?;
+        let original_tool_name = self
+            .alias_mapping
+            .read()
+            .await
+            .get(tool_name)
+            .map(|(_, original)| original.clone())
+            .unwrap_or_else(|| tool_name.to_string());
+        let configs = self.servers_config.read().await;
+        self.merged_tool_meta(configs.get(&server_name)?, &original_tool_name)
+    }
+
     /// 调用工具 / Call tool
     pub async fn call_tool(
         &self,
@@ -454,6 +1218,19 @@ impl MCPServerManager {
         tool_name: &str,
         parameters: serde_json::Value,
         timeout: Option<std::time::Duration>,
+    ) -> Result<CallToolResult, ComputerError> {
+        self.call_tool_inner(server_name, tool_name, parameters, timeout, None)
+            .await
+    }
+
+    /// 调用工具，携带令牌时请求服务器上报进度 / Call tool, requesting progress reports when a token is given
+    async fn call_tool_inner(
+        &self,
+        server_name: &str,
+        tool_name: &str,
+        parameters: serde_json::Value,
+        timeout: Option<std::time::Duration>,
+        progress_token: Option<&str>,
     ) -> Result<CallToolResult, ComputerError> {
         // 获取客户端引用 / Get client reference
         let client = {
@@ -470,16 +1247,25 @@ impl MCPServerManager {
         };
 
         // 执行工具调用 / Execute tool call
+        let call = async {
+            match progress_token {
+                Some(token) => {
+                    client
+                        .call_tool_with_progress(tool_name, parameters, token)
+                        .await
+                }
+                None => client.call_tool(tool_name, parameters).await,
+            }
+        };
         let result = if let Some(timeout) = timeout {
-            tokio::time::timeout(timeout, client.call_tool(tool_name, parameters))
+            tokio::time::timeout(timeout, call)
                 .await
                 .map_err(|_| ComputerError::TimeoutError("Tool execution timed out".to_string()))?
         } else {
-            client.call_tool(tool_name, parameters).await
+            call.await
         };
 
-        let mut result = result
-            .map_err(|e| ComputerError::ProtocolError(format!("Tool execution failed: {}", e)))?;
+        let mut result = result.map_err(ComputerError::from)?;
 
         // 添加工具元数据到结果 / Add tool metadata to result
         let config = {
@@ -487,21 +1273,42 @@ impl MCPServerManager {
             configs.get(server_name).cloned()
         };
 
-        if let Some(config) = config {
-            if let Some(tool_meta) = self.merged_tool_meta(&config, tool_name) {
-                if result.meta.is_none() {
-                    result.meta = Some(std::collections::HashMap::new());
-                }
-                if let Some(ref mut meta) = result.meta {
-                    meta.insert(
-                        A2C_TOOL_META.to_string(),
-                        serde_json::to_value(tool_meta).unwrap(),
-                    );
-                }
+        let tool_meta = config.and_then(|config| self.merged_tool_meta(&config, tool_name));
+        if let Some(ref tool_meta) = tool_meta {
+            if result.meta.is_none() {
+                result.meta = Some(std::collections::HashMap::new());
+            }
+            if let Some(ref mut meta) = result.meta {
+                meta.insert(
+                    A2C_TOOL_META.to_string(),
+                    serde_json::to_value(tool_meta).unwrap(),
+                );
             }
+        }
 
-            // VRL转换 / VRL transformation
-            if let Some(vrl_script) = config.vrl() {
+        // VRL转换 / VRL transformation
+        let program = self.vrl_programs.read().await.get(server_name).cloned();
+        if let Some(program) = program {
+            if let Some(structured) = result.structured_content.take() {
+                // 结构化内容替换为转换后的值，失败时保留原值
+                // Structured content is replaced by the transformed value, kept on failure
+                match program.run(structured.clone()) {
+                    Ok(transformed) => {
+                        result.structured_content = Some(transformed);
+                        debug!(
+                            "VRL转换成功 / VRL transformation succeeded for tool '{}'",
+                            tool_name
+                        );
+                    }
+                    Err(e) => {
+                        warn!(
+                            "VRL转换失败 / VRL transformation failed for tool '{}': {}. 原始结果将正常返回 / Original result will be returned normally.",
+                            tool_name, e
+                        );
+                        result.structured_content = Some(structured);
+                    }
+                }
+            } else {
                 // 获取原始参数用于VRL处理
                 // Note: 这里需要从调用栈获取原始参数，暂时使用空对象
                 let parameters = serde_json::json!({});
@@ -517,18 +1324,15 @@ impl MCPServerManager {
                 }
 
                 // 执行VRL转换
-                let mut runtime = VrlRuntime::new();
-                match runtime.run(vrl_script, event, "UTC") {
-                    Ok(vrl_result) => {
+                match program.run(event) {
+                    Ok(processed_event) => {
                         // 将转换后的结果存储到meta中
                         if result.meta.is_none() {
                             result.meta = Some(std::collections::HashMap::new());
                         }
                         if let Some(ref mut meta) = result.meta {
                             // 将转换后的结果序列化为JSON字符串
-                            if let Ok(transformed_json) =
-                                serde_json::to_string(&vrl_result.processed_event)
-                            {
+                            if let Ok(transformed_json) = serde_json::to_string(&processed_event) {
                                 meta.insert(
                                     A2C_VRL_TRANSFORMED.to_string(),
                                     Value::String(transformed_json),
@@ -550,6 +1354,20 @@ impl MCPServerManager {
             }
         }
 
+        // 返回值结构映射，在VRL转换之后执行 / Return object mapping, applied after the VRL transformation
+        let spec = tool_meta.and_then(|tool_meta| tool_meta.ret_object_mapper);
+        if let (Some(spec), Some(structured)) = (spec, result.structured_content.as_ref()) {
+            let mapped = RetObjectMapper::parse(&spec)
+                .and_then(|mapper| mapper.apply(structured))
+                .map_err(|e| {
+                    ComputerError::ValidationError(format!(
+                        "ret_object_mapper of tool '{}' failed: {}",
+                        tool_name, e
+                    ))
+                })?;
+            result.structured_content = Some(mapped);
+        }
+
         Ok(result)
     }
 
@@ -566,10 +1384,133 @@ impl MCPServerManager {
             .await
     }
 
+    /// 执行 Agent 的工具调用请求 / Execute an agent tool call request
+    ///
+    /// 幂等缓存以 `idempotency_key` 为键，缺省时使用 `req_id`；`req_id` 同时作为进度令牌。
+    /// The idempotency cache is keyed on `idempotency_key`, falling back to `req_id`; `req_id`
+    /// also serves as the progress token.
+    pub async fn execute_tool_req(
+        &self,
+        req: &smcp::ToolCallReq,
+    ) -> Result<(CallToolResult, smcp::CallInfo), ComputerError> {
+        self.execute_with_info(
+            req.dedup_key(),
+            &req.tool_name,
+            req.params.clone(),
+            Some(std::time::Duration::from_secs(req.timeout as u64)),
+            Some(req.base.req_id.as_str()),
+        )
+        .await
+    }
+
+    /// 执行工具调用并返回调用信息 / Execute tool call and return call info
+    ///
+    /// 调用先经过校验；相同 `cache_key`、工具与参数的重复调用返回首个调用的结果，`cache_hit` 为 true，
+    /// 首个调用仍在进行时等待其完成。同一 `cache_key` 用于不同工具或参数时返回
+    /// [`ComputerError::ValidationError`]。
+    /// Calls are validated first; repeats with the same `cache_key`, tool and parameters return the
+    /// first call's result with `cache_hit` set, waiting for it while it is still in flight. Reusing
+    /// a `cache_key` for a different tool or parameters yields [`ComputerError::ValidationError`].
+    pub async fn execute_tool_with_info(
+        &self,
+        cache_key: &str,
+        tool_name: &str,
+        parameters: serde_json::Value,
+        timeout: Option<std::time::Duration>,
+    ) -> Result<(CallToolResult, smcp::CallInfo), ComputerError> {
+        self.execute_with_info(cache_key, tool_name, parameters, timeout, None)
+            .await
+    }
+
+    async fn execute_with_info(
+        &self,
+        cache_key: &str,
+        tool_name: &str,
+        parameters: serde_json::Value,
+        timeout: Option<std::time::Duration>,
+        progress_token: Option<&str>,
+    ) -> Result<(CallToolResult, smcp::CallInfo), ComputerError> {
+        let started = std::time::Instant::now();
+
+        // 先校验，禁用或已移除的工具不会从缓存返回 / Validate first so forbidden or removed tools are never served from cache
+        let (server_name, original_tool_name) =
+            self.validate_tool_call(tool_name, &parameters).await?;
+        let params_hash = params_fingerprint(&parameters);
+
+        let claim = self.idempotency_cache.write().await.claim(
+            cache_key,
+            tool_name,
+            params_hash,
+            &server_name,
+        )?;
+        let tx = match claim {
+            Claim::Owner(tx) => tx,
+            Claim::Waiter(mut rx) => {
+                debug!("Tool call {} served from idempotency cache", cache_key);
+                let outcome = rx
+                    .wait_for(Option::is_some)
+                    .await
+                    .map(|outcome| outcome.clone());
+                let result = match outcome {
+                    Ok(Some(Ok(result))) => result,
+                    Ok(Some(Err(e))) => {
+                        return Err(ComputerError::RuntimeError(format!(
+                            "Original call for idempotency key '{}' failed: {}",
+                            cache_key, e
+                        )))
+                    }
+                    _ => {
+                        return Err(ComputerError::Cancelled(format!(
+                            "Original call for idempotency key '{}' was abandoned",
+                            cache_key
+                        )))
+                    }
+                };
+                let info = smcp::CallInfo {
+                    server: server_name,
+                    elapsed_ms: started.elapsed().as_millis() as u64,
+                    cache_hit: true,
+                };
+                return Ok((result, info));
+            }
+        };
+
+        let result = self
+            .call_tool_inner(
+                &server_name,
+                &original_tool_name,
+                parameters,
+                timeout,
+                progress_token,
+            )
+            .await;
+        let _ = tx.send(Some(match &result {
+            Ok(result) => Ok(result.clone()),
+            Err(e) => Err(e.to_string()),
+        }));
+        if result.is_err() {
+            self.idempotency_cache
+                .write()
+                .await
+                .forget_failed(cache_key);
+        }
+        let result = result?;
+
+        let info = smcp::CallInfo {
+            server: server_name,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+            cache_hit: false,
+        };
+        Ok((result, info))
+    }
+
     /// 获取服务器状态列表 / Get server status list
-    pub async fn get_server_status(&self) -> Vec<(String, bool, String)> {
+    ///
+    /// 返回 (名称, 是否激活, 状态, 最近错误) / Returns (name, is_active, state, last_error)
+    pub async fn get_server_status(&self) -> Vec<(String, bool, String, Option<String>)> {
         let configs = self.servers_config.read().await;
         let clients = self.active_clients.read().await;
+        let last_errors = self.last_errors.read().await;
 
         configs
             .keys()
@@ -580,14 +1521,27 @@ impl MCPServerManager {
                         .get(name)
                         .map(|c| c.state().to_string())
                         .unwrap_or_else(|| "unknown".to_string())
+                } else if last_errors.contains_key(name) {
+                    "error".to_string()
                 } else {
                     "pending".to_string()
                 };
-                (name.clone(), is_active, state)
+                (
+                    name.clone(),
+                    is_active,
+                    state,
+                    last_errors.get(name).cloned(),
+                )
             })
             .collect()
     }
 
+    /// 获取服务器信息（仅对已激活的服务器可用）/ Get server info (only available for active servers)
+    pub async fn get_server_info(&self, server_name: &str) -> Option<ServerInfo> {
+        let client = self.active_clients.read().await.get(server_name).cloned()?;
+        client.server_info().await
+    }
+
     /// 获取可用工具列表 / Get available tools list
     pub async fn list_available_tools(&self) -> Vec<Tool> {
         let mut tools = Vec::new();
@@ -622,6 +1576,211 @@ impl MCPServerManager {
         tools
     }
 
+    /// 以 SMCP 格式列出可用工具，附带合并后的工具元数据
+    /// List available tools in SMCP format, with the merged tool metadata attached
+    pub async fn list_available_smcp_tools(&self) -> Vec<smcp::SMCPTool> {
+        let mut smcp_tools = Vec::new();
+        for tool in self.list_available_tools().await {
+            let tool_meta = self.get_tool_meta(&tool.name).await;
+            smcp_tools.push(tool.into_smcp_tool(tool_meta.as_ref()));
+        }
+        smcp_tools
+    }
+
+    /// 收集所有活动服务器的提示词并解决重名冲突 / Collect prompts from active servers and resolve name conflicts
+    ///
+    /// 唯一的提示词名称保持原样；在多个服务器中重复的名称会以 `服务器名/提示词名` 的形式暴露。
+    /// Unique prompt names are kept as-is; names found in multiple servers are exposed as `server/prompt`.
+    async fn collect_prompts(&self) -> Vec<(String, ServerName, Prompt)> {
+        let clients: Vec<(ServerName, StdArc<dyn MCPClientProtocol>)> = {
+            let clients = self.active_clients.read().await;
+            clients
+                .iter()
+                .map(|(name, client)| (name.clone(), client.clone()))
+                .collect()
+        };
+
+        let mut collected: Vec<(ServerName, Prompt)> = Vec::new();
+        for (server_name, client) in clients {
+            match client.list_prompts().await {
+                Ok(prompts) => {
+                    collected.extend(prompts.into_iter().map(|p| (server_name.clone(), p)));
+                }
+                Err(e) => {
+                    warn!(
+                        "Failed to list prompts from server '{}': {}",
+                        server_name, e
+                    );
+                }
+            }
+        }
+        // 保证输出顺序稳定 / Keep output order stable
+        collected.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.name.cmp(&b.1.name)));
+
+        let mut name_servers: HashMap<String, Vec<ServerName>> = HashMap::new();
+        for (server_name, prompt) in &collected {
+            name_servers
+                .entry(prompt.name.clone())
+                .or_default()
+                .push(server_name.clone());
+        }
+
+        collected
+            .into_iter()
+            .map(|(server_name, prompt)| {
+                let servers = &name_servers[&prompt.name];
+                let display_name = if servers.len() > 1 {
+                    warn!(
+                        "Prompt '{}' exists in multiple servers: {:?}, exposing as '{}/{}'",
+                        prompt.name, servers, server_name, prompt.name
+                    );
+                    format!("{}/{}", server_name, prompt.name)
+                } else {
+                    prompt.name.clone()
+                };
+                (display_name, server_name, prompt)
+            })
+            .collect()
+    }
+
+    /// 获取可用提示词列表 / Get available prompts list
+    pub async fn list_available_prompts(&self) -> Vec<Prompt> {
+        self.collect_prompts()
+            .await
+            .into_iter()
+            .map(|(display_name, _, mut prompt)| {
+                prompt.name = display_name;
+                prompt
+            })
+            .collect()
+    }
+
+    /// 获取提示词内容（支持 `服务器名/提示词名` 形式） / Get prompt content (supports `server/prompt` form)
+    pub async fn get_prompt(
+        &self,
+        name: &str,
+        arguments: Option<HashMap<String, String>>,
+    ) -> Result<GetPromptResult, ComputerError> {
+        let (server_name, original_name) = self
+            .collect_prompts()
+            .await
+            .into_iter()
+            .find(|(display_name, _, _)| display_name == name)
+            .map(|(_, server_name, prompt)| (server_name, prompt.name))
+            .ok_or_else(|| {
+                ComputerError::InvalidConfiguration(format!("Prompt '{}' not found", name))
+            })?;
+
+        let client = {
+            let clients = self.active_clients.read().await;
+            clients.get(&server_name).cloned().ok_or_else(|| {
+                ComputerError::InvalidConfiguration(format!(
+                    "Server '{}' for prompt '{}' is not active",
+                    server_name, name
+                ))
+            })?
+        };
+
+        client
+            .get_prompt(&original_name, arguments)
+            .await
+            .map_err(|e| ComputerError::ProtocolError(format!("Get prompt failed: {}", e)))
+    }
+
+    /// 获取所有活动服务器的资源模板 / Get resource templates from all active servers
+    ///
+    /// 与提示词一致，唯一的模板名称保持原样；在多个服务器中重复的名称会以 `服务器名/模板名` 的形式暴露。
+    /// As with prompts, unique template names are kept as-is; names found in multiple servers are
+    /// exposed as `server/template`.
+    pub async fn list_available_resource_templates(&self) -> Vec<ResourceTemplate> {
+        let clients: Vec<(ServerName, StdArc<dyn MCPClientProtocol>)> = {
+            let clients = self.active_clients.read().await;
+            clients
+                .iter()
+                .map(|(name, client)| (name.clone(), client.clone()))
+                .collect()
+        };
+
+        let mut collected: Vec<(ServerName, ResourceTemplate)> = Vec::new();
+        for (server_name, client) in clients {
+            match client.list_resource_templates().await {
+                Ok(templates) => {
+                    collected.extend(templates.into_iter().map(|t| (server_name.clone(), t)));
+                }
+                Err(e) => {
+                    warn!(
+                        "Failed to list resource templates from server '{}': {}",
+                        server_name, e
+                    );
+                }
+            }
+        }
+        // 保证输出顺序稳定 / Keep output order stable
+        collected.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.name.cmp(&b.1.name)));
+
+        let mut name_counts: HashMap<String, usize> = HashMap::new();
+        for (_, template) in &collected {
+            *name_counts.entry(template.name.clone()).or_default() += 1;
+        }
+
+        collected
+            .into_iter()
+            .map(|(server_name, mut template)| {
+                if name_counts[&template.name] > 1 {
+                    warn!(
+                        "Resource template '{}' exists in multiple servers, exposing as '{}/{}'",
+                        template.name, server_name, template.name
+                    );
+                    template.name = format!("{}/{}", server_name, template.name);
+                }
+                template
+            })
+            .collect()
+    }
+
+    /// 收集所有活动服务器的窗口资源及其内容，`window` 指定时仅保留该窗口
+    /// Collect window resources and their contents from every active server; only `window` is kept when given
+    pub async fn list_windows(&self, window: Option<&str>) -> Vec<WindowInfo> {
+        let mut clients: Vec<(ServerName, StdArc<dyn MCPClientProtocol>)> = {
+            let clients = self.active_clients.read().await;
+            clients
+                .iter()
+                .map(|(name, client)| (name.clone(), client.clone()))
+                .collect()
+        };
+        // 保证输出顺序稳定 / Keep output order stable
+        clients.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut windows = Vec::new();
+        for (server_name, client) in clients {
+            let resources = match client.list_windows().await {
+                Ok(resources) => resources,
+                Err(e) => {
+                    warn!(
+                        "Failed to list windows from server '{}': {}",
+                        server_name, e
+                    );
+                    continue;
+                }
+            };
+            for resource in resources {
+                if !is_window_uri(&resource.uri) || window.is_some_and(|uri| uri != resource.uri) {
+                    continue;
+                }
+                match client.get_window_detail(resource.clone()).await {
+                    Ok(detail) => {
+                        windows.push(WindowInfo::new(server_name.clone(), resource, detail))
+                    }
+                    Err(e) => warn!(
+                        "Failed to read window '{}' from server '{}': {}",
+                        resource.uri, server_name, e
+                    ),
+                }
+            }
+        }
+        windows
+    }
+
     /// 合并工具元数据 / Merge tool metadata
     fn merged_tool_meta(&self, config: &MCPServerConfig, tool_name: &str) -> Option<ToolMeta> {
         let specific = config.tool_meta().get(tool_name);
@@ -672,6 +1831,23 @@ impl MCPServerManager {
     }
 }
 
+/// 等待下一条服务器消息；客户端不提供或通道关闭后永不返回
+/// Wait for the next server message; never returns when the client offers none or the channel closed
+async fn next_notification(notifications: &mut Option<broadcast::Receiver<Value>>) -> Value {
+    loop {
+        let Some(rx) = notifications else {
+            return std::future::pending().await;
+        };
+        match rx.recv().await {
+            Ok(message) => return message,
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                warn!("Skipped {} server messages", skipped);
+            }
+            Err(broadcast::error::RecvError::Closed) => *notifications = None,
+        }
+    }
+}
+
 impl Default for MCPServerManager {
     fn default() -> Self {
         Self::new()
@@ -681,6 +1857,7 @@ impl Default for MCPServerManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mcp_clients::testing::sh_reply;
     use std::collections::HashMap;
     use tokio::time::{sleep, Duration};
 
@@ -738,13 +1915,13 @@ mod tests {
         // 验证状态 / Verify status
         let stdio_status = status
             .iter()
-            .find(|(name, _, _)| name == "test_stdio")
+            .find(|(name, _, _, _)| name == "test_stdio")
             .unwrap();
         assert!(!stdio_status.1); // 未激活 / Not active
 
         let http_status = status
             .iter()
-            .find(|(name, _, _)| name == "test_http")
+            .find(|(name, _, _, _)| name == "test_http")
             .unwrap();
         assert!(!http_status.1); // 未激活 / Not active
     }
@@ -809,6 +1986,1154 @@ mod tests {
         assert!(status.is_empty());
     }
 
+    /// 仅提供提示词的测试客户端 / Test client that only serves prompts
+    struct PromptOnlyClient {
+        prompts: Vec<&'static str>,
+    }
+
+    #[async_trait::async_trait]
+    impl MCPClientProtocol for PromptOnlyClient {
+        fn state(&self) -> ClientState {
+            ClientState::Connected
+        }
+
+        async fn connect(&self) -> Result<(), MCPClientError> {
+            Ok(())
+        }
+
+        async fn disconnect(&self) -> Result<(), MCPClientError> {
+            Ok(())
+        }
+
+        async fn list_tools(&self) -> Result<Vec<Tool>, MCPClientError> {
+            Ok(vec![])
+        }
+
+        async fn call_tool(
+            &self,
+            _tool_name: &str,
+            _params: serde_json::Value,
+        ) -> Result<CallToolResult, MCPClientError> {
+            Err(MCPClientError::ProtocolError("unsupported".to_string()))
+        }
+
+        async fn list_windows(&self) -> Result<Vec<Resource>, MCPClientError> {
+            Ok(vec![])
+        }
+
+        async fn get_window_detail(
+            &self,
+            _resource: Resource,
+        ) -> Result<ReadResourceResult, MCPClientError> {
+            Err(MCPClientError::ProtocolError("unsupported".to_string()))
+        }
+
+        async fn subscribe_window(&self, _resource: Resource) -> Result<(), MCPClientError> {
+            Ok(())
+        }
+
+        async fn unsubscribe_window(&self, _resource: Resource) -> Result<(), MCPClientError> {
+            Ok(())
+        }
+
+        async fn list_prompts(&self) -> Result<Vec<Prompt>, MCPClientError> {
+            Ok(self
+                .prompts
+                .iter()
+                .map(|name| Prompt {
+                    name: name.to_string(),
+                    description: None,
+                    arguments: None,
+                })
+                .collect())
+        }
+
+        async fn get_prompt(
+            &self,
+            name: &str,
+            arguments: Option<HashMap<String, String>>,
+        ) -> Result<GetPromptResult, MCPClientError> {
+            Ok(GetPromptResult {
+                description: Some(name.to_string()),
+                messages: vec![PromptMessage {
+                    role: "user".to_string(),
+                    content: serde_json::to_value(arguments.unwrap_or_default()).unwrap(),
+                }],
+            })
+        }
+    }
+
+    /// 统计调用次数的工具测试客户端 / Test tool client counting its calls
+    struct CountingToolClient {
+        calls: StdArc<std::sync::atomic::AtomicUsize>,
+    }
+
+    #[async_trait::async_trait]
+    impl MCPClientProtocol for CountingToolClient {
+        fn state(&self) -> ClientState {
+            ClientState::Connected
+        }
+
+        async fn connect(&self) -> Result<(), MCPClientError> {
+            Ok(())
+        }
+
+        async fn disconnect(&self) -> Result<(), MCPClientError> {
+            Ok(())
+        }
+
+        async fn list_tools(&self) -> Result<Vec<Tool>, MCPClientError> {
+            Ok(vec![Tool {
+                name: "echo".to_string(),
+                description: "Echo".to_string(),
+                input_schema: serde_json::json!({"type": "object"}),
+                output_schema: None,
+                annotations: None,
+                meta: None,
+            }])
+        }
+
+        async fn call_tool(
+            &self,
+            _tool_name: &str,
+            params: serde_json::Value,
+        ) -> Result<CallToolResult, MCPClientError> {
+            self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(CallToolResult {
+                content: vec![Content::Text {
+                    text: params.to_string(),
+                }],
+                is_error: false,
+                structured_content: None,
+                meta: None,
+            })
+        }
+
+        async fn list_windows(&self) -> Result<Vec<Resource>, MCPClientError> {
+            Ok(vec![])
+        }
+
+        async fn get_window_detail(
+            &self,
+            _resource: Resource,
+        ) -> Result<ReadResourceResult, MCPClientError> {
+            Err(MCPClientError::ProtocolError("unsupported".to_string()))
+        }
+
+        async fn subscribe_window(&self, _resource: Resource) -> Result<(), MCPClientError> {
+            Ok(())
+        }
+
+        async fn unsubscribe_window(&self, _resource: Resource) -> Result<(), MCPClientError> {
+            Ok(())
+        }
+    }
+
+    /// 断开连接永远挂起的测试客户端 / Test client whose disconnect never completes
+    struct HangingDisconnectClient;
+
+    #[async_trait::async_trait]
+    impl MCPClientProtocol for HangingDisconnectClient {
+        fn state(&self) -> ClientState {
+            ClientState::Connected
+        }
+
+        async fn connect(&self) -> Result<(), MCPClientError> {
+            Ok(())
+        }
+
+        async fn disconnect(&self) -> Result<(), MCPClientError> {
+            std::future::pending().await
+        }
+
+        async fn list_tools(&self) -> Result<Vec<Tool>, MCPClientError> {
+            Ok(vec![])
+        }
+
+        async fn call_tool(
+            &self,
+            _tool_name: &str,
+            _params: serde_json::Value,
+        ) -> Result<CallToolResult, MCPClientError> {
+            Err(MCPClientError::ProtocolError("unsupported".to_string()))
+        }
+
+        async fn list_windows(&self) -> Result<Vec<Resource>, MCPClientError> {
+            Ok(vec![])
+        }
+
+        async fn get_window_detail(
+            &self,
+            _resource: Resource,
+        ) -> Result<ReadResourceResult, MCPClientError> {
+            Err(MCPClientError::ProtocolError("unsupported".to_string()))
+        }
+
+        async fn subscribe_window(&self, _resource: Resource) -> Result<(), MCPClientError> {
+            Ok(())
+        }
+
+        async fn unsubscribe_window(&self, _resource: Resource) -> Result<(), MCPClientError> {
+            Ok(())
+        }
+    }
+
+    /// 注册单个计数工具客户端的管理器 / Manager with a single counting tool client
+    async fn counting_manager(calls: StdArc<std::sync::atomic::AtomicUsize>) -> MCPServerManager {
+        let manager = MCPServerManager::new();
+        manager.servers_config.write().await.insert(
+            "echo_server".to_string(),
+            MCPServerConfig::Stdio(StdioServerConfig {
+                name: "echo_server".to_string(),
+                disabled: false,
+                forbidden_tools: vec![],
+                tool_meta: HashMap::new(),
+                default_tool_meta: None,
+                vrl: None,
+                server_parameters: StdioServerParameters {
+                    command: "echo".to_string(),
+                    args: vec![],
+                    env: HashMap::new(),
+                    cwd: None,
+                },
+            }),
+        );
+        manager.active_clients.write().await.insert(
+            "echo_server".to_string(),
+            StdArc::new(CountingToolClient { calls }),
+        );
+        manager.refresh_tool_mapping().await.unwrap();
+        manager
+    }
+
+    #[tokio::test]
+    async fn test_stop_all_with_timeout_abandons_hanging_client() {
+        let calls = StdArc::new(std::sync::atomic::AtomicUsize::new(0));
+        let manager = counting_manager(calls).await;
+        manager.active_clients.write().await.insert(
+            "stuck_server".to_string(),
+            StdArc::new(HangingDisconnectClient),
+        );
+
+        let force_closed = tokio::time::timeout(
+            Duration::from_secs(2),
+            manager.stop_all_with_timeout(Duration::from_millis(100)),
+        )
+        .await
+        .expect("stop_all_with_timeout should be bounded")
+        .unwrap();
+
+        assert_eq!(force_closed, vec!["stuck_server".to_string()]);
+        let snapshot = manager.snapshot().await;
+        assert!(snapshot.active.is_empty());
+        assert!(snapshot.tools.is_empty());
+        assert_eq!(snapshot.state, ManagerState::Initialized);
+    }
+
+    #[tokio::test]
+    async fn test_execute_tool_with_info_reports_server_and_cache_hit() {
+        let calls = StdArc::new(std::sync::atomic::AtomicUsize::new(0));
+        let manager = counting_manager(calls.clone()).await;
+
+        let params = serde_json::json!({"text": "hi"});
+        let (result, info) = manager
+            .execute_tool_with_info("req-1", "echo", params.clone(), None)
+            .await
+            .unwrap();
+        assert_eq!(info.server, "echo_server");
+        assert!(!info.cache_hit);
+        assert!(!result.is_error);
+
+        // 相同 req_id 的重试命中缓存，不再调用服务器 / Retry with the same req_id hits the cache
+        let (cached, info) = manager
+            .execute_tool_with_info("req-1", "echo", params.clone(), None)
+            .await
+            .unwrap();
+        assert!(info.cache_hit);
+        assert_eq!(info.server, "echo_server");
+        assert_eq!(cached, result);
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        let (_, info) = manager
+            .execute_tool_with_info("req-2", "echo", params, None)
+            .await
+            .unwrap();
+        assert!(!info.cache_hit);
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_cache_hit_requires_valid_matching_call() {
+        let calls = StdArc::new(std::sync::atomic::AtomicUsize::new(0));
+        let manager = counting_manager(calls.clone()).await;
+        let params = serde_json::json!({"text": "hi"});
+        manager
+            .execute_tool_with_info("key-1", "echo", params.clone(), None)
+            .await
+            .unwrap();
+
+        // 同一键用于不同参数或工具被拒绝 / Reusing the key for other params or tools is rejected
+        let err = manager
+            .execute_tool_with_info("key-1", "echo", serde_json::json!({"text": "bye"}), None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ComputerError::ValidationError(_)));
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        // 之后被禁用的工具不再从缓存返回 / A tool forbidden afterwards is not served from cache
+        if let Some(MCPServerConfig::Stdio(config)) =
+            manager.servers_config.write().await.get_mut("echo_server")
+        {
+            config.forbidden_tools = vec!["echo".to_string()];
+        }
+        let err = manager
+            .execute_tool_with_info("key-1", "echo", params.clone(), None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ComputerError::PermissionError(_)));
+
+        // 服务器移除后同样失败 / The same holds once the server is gone
+        manager.active_clients.write().await.clear();
+        manager.refresh_tool_mapping().await.unwrap();
+        assert!(manager
+            .execute_tool_with_info("key-1", "echo", params, None)
+            .await
+            .is_err());
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_retries_share_in_flight_call() {
+        use crate::mcp_clients::testing::{mock_server_config, MockMCPClient};
+
+        let manager = MCPServerManager::new();
+        let slow = MockMCPClient::builder()
+            .tool("slow")
+            .call_delay(Duration::from_millis(200))
+            .build();
+        manager
+            .attach_client(mock_server_config("slow"), StdArc::new(slow.clone()))
+            .await
+            .unwrap();
+
+        let request = |params: serde_json::Value| smcp::ToolCallReq {
+            base: smcp::AgentCallData {
+                agent: "agent".to_string(),
+                req_id: smcp::ReqId::new(),
+            },
+            computer: "computer".to_string(),
+            tool_name: "slow".to_string(),
+            params,
+            timeout: 5,
+            idempotency_key: Some("op-1".to_string()),
+        };
+        let first = request(serde_json::json!({}));
+        let retry = request(serde_json::json!({}));
+
+        let (a, b) = tokio::join!(
+            manager.execute_tool_req(&first),
+            manager.execute_tool_req(&retry)
+        );
+        let ((result_a, info_a), (result_b, info_b)) = (a.unwrap(), b.unwrap());
+        assert_eq!(slow.total_calls(), 1);
+        assert_eq!(result_a, result_b);
+        assert!(info_a.cache_hit != info_b.cache_hit);
+
+        // 同一键携带不同参数被拒绝 / The same key with different params is rejected
+        let err = manager
+            .execute_tool_req(&request(serde_json::json!({"other": true})))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ComputerError::ValidationError(_)));
+        assert_eq!(slow.total_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_key_dedupes_across_req_ids() {
+        let calls = StdArc::new(std::sync::atomic::AtomicUsize::new(0));
+        let manager = counting_manager(calls.clone()).await;
+
+        let request = |key: Option<&str>| smcp::ToolCallReq {
+            base: smcp::AgentCallData {
+                agent: "agent".to_string(),
+                req_id: smcp::ReqId::new(),
+            },
+            computer: "computer".to_string(),
+            tool_name: "echo".to_string(),
+            params: serde_json::json!({"text": "hi"}),
+            timeout: 5,
+            idempotency_key: key.map(|k| k.to_string()),
+        };
+
+        let (_, first) = manager
+            .execute_tool_req(&request(Some("op-1")))
+            .await
+            .unwrap();
+        let (_, retry) = manager
+            .execute_tool_req(&request(Some("op-1")))
+            .await
+            .unwrap();
+        assert!(!first.cache_hit);
+        assert!(retry.cache_hit);
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        // 没有幂等键时每个新的 req_id 都会执行 / Without a key every new req_id executes
+        manager.execute_tool_req(&request(None)).await.unwrap();
+        manager.execute_tool_req(&request(None)).await.unwrap();
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 3);
+    }
+
+    /// 列出给定工具的模拟 STDIO 服务器 / Mock STDIO server listing the given tools
+    fn mock_tool_server(name: &str, tools: &[&str]) -> StdioServerConfig {
+        let init = r#"{"jsonrpc":"2.0","id":1,"result":{"capabilities":{}}}"#;
+        let tools: Vec<serde_json::Value> = tools
+            .iter()
+            .map(|tool| {
+                serde_json::json!({
+                    "name": tool,
+                    "description": tool,
+                    "inputSchema": {"type": "object"}
+                })
+            })
+            .collect();
+        let list = serde_json::json!({"jsonrpc": "2.0", "id": 3, "result": {"tools": tools}});
+        let script = format!(
+            "read l; {}; read l; while read l; do {}; done",
+            sh_reply(init),
+            sh_reply(&list.to_string())
+        );
+
+        StdioServerConfig {
+            name: name.to_string(),
+            disabled: false,
+            forbidden_tools: vec![],
+            tool_meta: HashMap::new(),
+            default_tool_meta: None,
+            vrl: None,
+            server_parameters: StdioServerParameters {
+                command: "sh".to_string(),
+                args: vec!["-c".to_string(), script],
+                env: HashMap::new(),
+                cwd: None,
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_reflects_tool_routing() {
+        let manager = MCPServerManager::new();
+        let snapshot = manager.snapshot().await;
+        assert!(snapshot.tools.is_empty());
+        assert!(snapshot.active.is_empty());
+        assert_eq!(snapshot.state, ManagerState::Uninitialized);
+
+        let mut fs = mock_tool_server("fs", &["read_file", "delete_file"]);
+        fs.forbidden_tools = vec!["delete_file".to_string()];
+        let mut web = mock_tool_server("web", &["search"]);
+        let mut meta = ToolMeta::new();
+        meta.alias = Some("web_search".to_string());
+        web.tool_meta.insert("search".to_string(), meta);
+
+        manager
+            .initialize(vec![
+                MCPServerConfig::Stdio(fs),
+                MCPServerConfig::Stdio(web),
+            ])
+            .await
+            .unwrap();
+        manager.start_all().await.unwrap();
+
+        let snapshot = manager.snapshot().await;
+        assert_eq!(snapshot.state, ManagerState::Running);
+        assert_eq!(snapshot.active, vec!["fs".to_string(), "web".to_string()]);
+        assert_eq!(snapshot.tools.len(), 3);
+        assert_eq!(snapshot.tools["read_file"], "fs");
+        assert_eq!(snapshot.tools["delete_file"], "fs");
+        assert_eq!(snapshot.tools["web_search"], "web");
+        assert_eq!(
+            snapshot.aliases["web_search"],
+            ("web".to_string(), "search".to_string())
+        );
+        assert_eq!(
+            snapshot.disabled,
+            HashSet::from(["delete_file".to_string()])
+        );
+
+        manager.stop_all().await.unwrap();
+        assert!(manager.snapshot().await.active.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_forbidden_tool_rejected_by_alias_and_original_name() {
+        let manager = MCPServerManager::new();
+        let mut fs = mock_tool_server("fs", &["read_file", "delete_file"]);
+        fs.forbidden_tools = vec!["delete_file".to_string()];
+        let mut meta = ToolMeta::new();
+        meta.alias = Some("rm".to_string());
+        fs.tool_meta.insert("delete_file".to_string(), meta);
+
+        manager
+            .initialize(vec![MCPServerConfig::Stdio(fs)])
+            .await
+            .unwrap();
+        manager.start_all().await.unwrap();
+
+        let params = serde_json::json!({});
+        for name in ["rm", "delete_file"] {
+            let err = manager.validate_tool_call(name, &params).await.unwrap_err();
+            assert!(
+                matches!(err, ComputerError::PermissionError(_)),
+                "{} should be rejected, got {:?}",
+                name,
+                err
+            );
+        }
+        assert_eq!(
+            manager
+                .validate_tool_call("read_file", &params)
+                .await
+                .unwrap(),
+            ("fs".to_string(), "read_file".to_string())
+        );
+
+        manager.stop_all().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_get_tool_meta_resolves_alias() {
+        let manager = MCPServerManager::new();
+        let mut web = mock_tool_server("web", &["search", "fetch"]);
+        let mut meta = ToolMeta::new();
+        meta.alias = Some("web_search".to_string());
+        meta.auto_apply = Some(true);
+        web.tool_meta.insert("search".to_string(), meta);
+        let mut default_meta = ToolMeta::new();
+        default_meta.auto_apply = Some(false);
+        web.default_tool_meta = Some(default_meta);
+
+        manager
+            .initialize(vec![MCPServerConfig::Stdio(web)])
+            .await
+            .unwrap();
+        manager.start_all().await.unwrap();
+
+        let meta = manager.get_tool_meta("web_search").await.unwrap();
+        assert_eq!(meta.auto_apply, Some(true));
+        assert_eq!(
+            manager.get_tool_meta("fetch").await.unwrap().auto_apply,
+            Some(false)
+        );
+        // 别名生效后原名不再可路由 / The original name is not routable once aliased
+        assert!(manager.get_tool_meta("search").await.is_none());
+        assert!(manager.get_tool_meta("unknown").await.is_none());
+
+        manager.stop_all().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_list_available_smcp_tools_maps_schemas_and_meta() {
+        use crate::mcp_clients::testing::{mock_server_config, MockMCPClient};
+
+        let client = MockMCPClient::builder()
+            .tool_definition(Tool {
+                name: "query".to_string(),
+                description: "Query".to_string(),
+                input_schema: serde_json::json!({"type": "object", "required": ["sql"]}),
+                output_schema: Some(serde_json::json!({"type": "array"})),
+                annotations: None,
+                meta: Some(HashMap::from([(
+                    "origin".to_string(),
+                    serde_json::json!("db"),
+                )])),
+            })
+            .tool("ping")
+            .build();
+        let mut config = mock_server_config("db");
+        if let MCPServerConfig::Stdio(ref mut stdio) = config {
+            let mut meta = ToolMeta::new();
+            meta.tags = Some(vec!["sql".to_string()]);
+            stdio.tool_meta.insert("query".to_string(), meta);
+            let mut default_meta = ToolMeta::new();
+            default_meta.auto_apply = Some(true);
+            stdio.default_tool_meta = Some(default_meta);
+        }
+        let manager = MCPServerManager::new();
+        manager
+            .attach_client(config, StdArc::new(client))
+            .await
+            .unwrap();
+
+        let mut tools = manager.list_available_smcp_tools().await;
+        tools.sort_by(|a, b| a.name.cmp(&b.name));
+        assert_eq!(tools.len(), 2);
+
+        let ping = &tools[0];
+        assert_eq!(ping.params_schema, serde_json::json!({"type": "object"}));
+        assert!(ping.return_schema.is_none());
+        assert_eq!(
+            ping.meta.as_ref().unwrap()[A2C_TOOL_META],
+            serde_json::json!({"auto_apply": true})
+        );
+
+        let query = &tools[1];
+        assert_eq!(query.params_schema["required"], serde_json::json!(["sql"]));
+        assert_eq!(
+            query.return_schema,
+            Some(serde_json::json!({"type": "array"}))
+        );
+        let meta = query.meta.as_ref().unwrap();
+        assert_eq!(meta["origin"], "db");
+        assert_eq!(
+            meta[A2C_TOOL_META],
+            serde_json::json!({"auto_apply": true, "tags": ["sql"]})
+        );
+    }
+
+    #[cfg(feature = "vrl")]
+    #[tokio::test]
+    async fn test_vrl_transforms_structured_content() {
+        use crate::mcp_clients::testing::{mock_server_config, MockMCPClient};
+
+        let client = MockMCPClient::builder()
+            .tool("count")
+            .call_response(
+                "count",
+                CallToolResult {
+                    content: vec![],
+                    is_error: false,
+                    structured_content: Some(serde_json::json!({"count": 1})),
+                    meta: None,
+                },
+            )
+            .build();
+        let mut config = mock_server_config("stats");
+        if let MCPServerConfig::Stdio(ref mut stdio) = config {
+            stdio.vrl = Some(".total = del(.count)".to_string());
+        }
+        let manager = MCPServerManager::new();
+        manager
+            .attach_client(config, StdArc::new(client))
+            .await
+            .unwrap();
+
+        let result = manager
+            .call_tool("stats", "count", serde_json::json!({}), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            result.structured_content,
+            Some(serde_json::json!({"total": 1}))
+        );
+    }
+
+    #[cfg(feature = "vrl")]
+    #[tokio::test]
+    async fn test_invalid_vrl_rejected_at_config_load() {
+        let manager = MCPServerManager::new();
+        let mut config = crate::mcp_clients::testing::mock_server_config("broken");
+        if let MCPServerConfig::Stdio(ref mut stdio) = config {
+            stdio.disabled = true;
+            stdio.vrl = Some(".total = ".to_string());
+        }
+
+        let err = manager.add_or_update_server(config).await.unwrap_err();
+        assert!(matches!(err, ComputerError::InvalidConfiguration(_)));
+        assert!(manager.get_server_status().await.is_empty());
+    }
+
+    /// 构造带返回值映射的管理器 / Build a manager whose tool has a ret_object_mapper
+    async fn manager_with_ret_mapper(structured: serde_json::Value) -> MCPServerManager {
+        use crate::mcp_clients::testing::{mock_server_config, MockMCPClient};
+
+        let client = MockMCPClient::builder()
+            .tool("weather")
+            .call_response(
+                "weather",
+                CallToolResult {
+                    content: vec![],
+                    is_error: false,
+                    structured_content: Some(structured),
+                    meta: None,
+                },
+            )
+            .build();
+        let mut config = mock_server_config("weather");
+        if let MCPServerConfig::Stdio(ref mut stdio) = config {
+            let mut meta = ToolMeta::new();
+            meta.ret_object_mapper = Some(HashMap::from([(
+                "out.temp".to_string(),
+                "$.current.temperature".to_string(),
+            )]));
+            stdio.tool_meta.insert("weather".to_string(), meta);
+        }
+        let manager = MCPServerManager::new();
+        manager
+            .attach_client(config, StdArc::new(client))
+            .await
+            .unwrap();
+        manager
+    }
+
+    #[tokio::test]
+    async fn test_ret_object_mapper_reshapes_structured_content() {
+        let manager =
+            manager_with_ret_mapper(serde_json::json!({"current": {"temperature": 21}})).await;
+
+        let result = manager
+            .call_tool("weather", "weather", serde_json::json!({}), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            result.structured_content,
+            Some(serde_json::json!({"out": {"temp": 21}}))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ret_object_mapper_missing_source_path() {
+        let manager = manager_with_ret_mapper(serde_json::json!({"current": {}})).await;
+
+        let err = manager
+            .call_tool("weather", "weather", serde_json::json!({}), None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ComputerError::ValidationError(ref msg)
+            if msg.contains("$.current.temperature")));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_ret_object_mapper_rejected_at_config_load() {
+        let manager = MCPServerManager::new();
+        let mut config = crate::mcp_clients::testing::mock_server_config("broken");
+        if let MCPServerConfig::Stdio(ref mut stdio) = config {
+            stdio.disabled = true;
+            let mut meta = ToolMeta::new();
+            meta.ret_object_mapper =
+                Some(HashMap::from([("out".to_string(), "current".to_string())]));
+            stdio.default_tool_meta = Some(meta);
+        }
+
+        let err = manager.add_or_update_server(config).await.unwrap_err();
+        assert!(matches!(err, ComputerError::InvalidConfiguration(_)));
+    }
+
+    #[tokio::test]
+    async fn test_prompt_conflict_resolution() {
+        let manager = MCPServerManager::new();
+        {
+            let mut clients = manager.active_clients.write().await;
+            clients.insert(
+                "server_a".to_string(),
+                StdArc::new(PromptOnlyClient {
+                    prompts: vec!["review", "summarize"],
+                }),
+            );
+            clients.insert(
+                "server_b".to_string(),
+                StdArc::new(PromptOnlyClient {
+                    prompts: vec!["review"],
+                }),
+            );
+        }
+
+        let names: Vec<String> = manager
+            .list_available_prompts()
+            .await
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        assert_eq!(
+            names,
+            vec!["server_a/review", "summarize", "server_b/review"]
+        );
+
+        let mut arguments = HashMap::new();
+        arguments.insert("lang".to_string(), "rust".to_string());
+        let result = manager
+            .get_prompt("server_b/review", Some(arguments))
+            .await
+            .unwrap();
+        assert_eq!(result.description.as_deref(), Some("review"));
+        assert_eq!(result.messages[0].content["lang"], "rust");
+
+        let result = manager.get_prompt("summarize", None).await.unwrap();
+        assert_eq!(result.description.as_deref(), Some("summarize"));
+
+        // 冲突的原始名称不可直接使用 / Conflicting raw name cannot be used directly
+        assert!(manager.get_prompt("review", None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_resource_templates_aggregated_across_servers() {
+        use crate::mcp_clients::testing::{mock_server_config, MockMCPClient};
+
+        let manager = MCPServerManager::new();
+        let files = MockMCPClient::builder()
+            .resource_template("file:///{path}", "file")
+            .resource_template("file:///logs/{name}", "log")
+            .build();
+        let db = MockMCPClient::builder()
+            .resource_template("db://tables/{table}", "table")
+            .resource_template("db://logs/{name}", "log")
+            .build();
+        manager
+            .attach_client(mock_server_config("files"), Arc::new(files))
+            .await
+            .unwrap();
+        manager
+            .attach_client(mock_server_config("db"), Arc::new(db))
+            .await
+            .unwrap();
+
+        let templates = manager.list_available_resource_templates().await;
+        let names: Vec<(&str, &str)> = templates
+            .iter()
+            .map(|t| (t.name.as_str(), t.uri_template.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("db/log", "db://logs/{name}"),
+                ("table", "db://tables/{table}"),
+                ("file", "file:///{path}"),
+                ("files/log", "file:///logs/{name}"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reconnect_policy_delay_backoff() {
+        let policy = ReconnectPolicy {
+            max_retries: 5,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(350),
+            jitter: 0.0,
+        };
+        assert_eq!(policy.delay(0), Duration::from_millis(100));
+        assert_eq!(policy.delay(1), Duration::from_millis(200));
+        assert_eq!(policy.delay(2), Duration::from_millis(350));
+        assert_eq!(policy.delay(40), Duration::from_millis(350));
+
+        // 抖动只增加等待，且不超过比例 / Jitter only adds to the wait, within the fraction
+        let jittered = ReconnectPolicy {
+            jitter: 0.5,
+            ..policy
+        };
+        let delay = jittered.delay(0);
+        assert!(delay >= Duration::from_millis(100) && delay <= Duration::from_millis(150));
+    }
+
+    #[tokio::test]
+    async fn test_connect_retries_until_flaky_client_succeeds() {
+        use crate::mcp_clients::testing::{mock_server_config, MockMCPClient};
+
+        let policy = ReconnectPolicy {
+            max_retries: 3,
+            base_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(50),
+            jitter: 0.0,
+        };
+        let manager = MCPServerManager::new().with_reconnect_policy(policy);
+        let flaky = MockMCPClient::builder()
+            .tool("read")
+            .connect_failures(2)
+            .build();
+        manager
+            .attach_client(mock_server_config("flaky"), Arc::new(flaky.clone()))
+            .await
+            .unwrap();
+
+        assert_eq!(flaky.connect_count(), 3);
+        assert_eq!(manager.snapshot().await.active, vec!["flaky".to_string()]);
+        assert!(manager.get_server_status().await[0].3.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_connect_returns_last_error_after_retries() {
+        use crate::mcp_clients::testing::{mock_server_config, MockMCPClient};
+
+        let policy = ReconnectPolicy {
+            max_retries: 1,
+            base_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(10),
+            jitter: 0.0,
+        };
+        let manager = MCPServerManager::new().with_reconnect_policy(policy);
+        let flaky = MockMCPClient::builder().connect_failures(5).build();
+        let err = manager
+            .attach_client(mock_server_config("flaky"), Arc::new(flaky.clone()))
+            .await
+            .unwrap_err();
+
+        assert_eq!(flaky.connect_count(), 2);
+        assert!(matches!(
+            err,
+            ComputerError::ConnectionError(message) if message.contains("connect attempt 2 refused")
+        ));
+        assert!(manager.snapshot().await.active.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_supervisor_reconnects_after_unexpected_disconnect() {
+        use crate::mcp_clients::testing::{mock_server_config, MockMCPClient};
+
+        let manager = MCPServerManager::new();
+        let mock = MockMCPClient::builder().tool("read").build();
+        manager
+            .attach_client(mock_server_config("files"), Arc::new(mock.clone()))
+            .await
+            .unwrap();
+        assert_eq!(mock.connect_count(), 1);
+
+        mock.simulate_disconnect();
+        tokio::time::timeout(Duration::from_secs(2), async {
+            while mock.connect_count() < 2 || mock.state() != ClientState::Connected {
+                sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("supervisor should reconnect the client");
+
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(mock.list_tools_count(), 2);
+        let snapshot = manager.snapshot().await;
+        assert_eq!(snapshot.active, vec!["files".to_string()]);
+        assert_eq!(snapshot.tools.get("read"), Some(&"files".to_string()));
+
+        // 主动停止不会触发重连 / An intentional stop does not trigger a reconnect
+        manager.stop_client("files").await.unwrap();
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(mock.connect_count(), 2);
+        assert_eq!(mock.state(), ClientState::Disconnected);
+        assert!(manager.snapshot().await.active.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_supervisor_respects_disabled_auto_reconnect() {
+        use crate::mcp_clients::testing::{mock_server_config, MockMCPClient};
+
+        let manager = MCPServerManager::new();
+        manager.disable_auto_reconnect().await;
+        let mock = MockMCPClient::builder().tool("read").build();
+        manager
+            .attach_client(mock_server_config("files"), Arc::new(mock.clone()))
+            .await
+            .unwrap();
+
+        mock.simulate_disconnect();
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(mock.connect_count(), 1);
+        assert_eq!(mock.state(), ClientState::Disconnected);
+    }
+
+    #[tokio::test]
+    async fn test_tool_list_changed_notification_refreshes_and_notifies() {
+        use crate::computer::{ManagerChangeHandler, ManagerChangeMessage};
+        use crate::errors::ComputerResult;
+        use crate::mcp_clients::testing::{mock_server_config, MockMCPClient};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        struct CountingHandler(AtomicUsize);
+
+        #[async_trait::async_trait]
+        impl ManagerChangeHandler for CountingHandler {
+            async fn on_change(&self, message: ManagerChangeMessage) -> ComputerResult<()> {
+                if matches!(message, ManagerChangeMessage::ToolListChanged) {
+                    self.0.fetch_add(1, Ordering::SeqCst);
+                }
+                Ok(())
+            }
+        }
+
+        let handler = Arc::new(CountingHandler(AtomicUsize::new(0)));
+        let manager = MCPServerManager::new().with_change_handler(handler.clone());
+        let mock = MockMCPClient::builder().tool("read").build();
+        manager
+            .attach_client(mock_server_config("files"), Arc::new(mock.clone()))
+            .await
+            .unwrap();
+        assert_eq!(mock.list_tools_count(), 1);
+
+        // 其他通知被忽略 / Other notifications are ignored
+        mock.send_notification(serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/resources/list_changed"
+        }));
+        mock.send_notification(serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/tools/list_changed"
+        }));
+
+        tokio::time::timeout(Duration::from_secs(2), async {
+            while handler.0.load(Ordering::SeqCst) == 0 {
+                sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("change handler should run");
+        assert_eq!(handler.0.load(Ordering::SeqCst), 1);
+        assert_eq!(mock.list_tools_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_progress_notifications_forwarded_before_result() {
+        use crate::computer::{ManagerChangeHandler, ManagerChangeMessage};
+        use crate::errors::ComputerResult;
+        use crate::mcp_clients::testing::{mock_server_config, MockMCPClient};
+
+        struct RecordingHandler(std::sync::Mutex<Vec<(String, f64, Option<String>)>>);
+
+        #[async_trait::async_trait]
+        impl ManagerChangeHandler for RecordingHandler {
+            async fn on_change(&self, message: ManagerChangeMessage) -> ComputerResult<()> {
+                if let ManagerChangeMessage::ToolProgress {
+                    progress_token,
+                    progress,
+                    message,
+                    ..
+                } = message
+                {
+                    self.0
+                        .lock()
+                        .unwrap()
+                        .push((progress_token, progress, message));
+                }
+                Ok(())
+            }
+        }
+
+        let handler = Arc::new(RecordingHandler(std::sync::Mutex::new(vec![])));
+        let manager = MCPServerManager::new().with_change_handler(handler.clone());
+        let mock = MockMCPClient::builder()
+            .tool("build")
+            .call_progress("build", 1.0, Some(2.0), Some("compiling"))
+            .call_progress("build", 2.0, Some(2.0), Some("linking"))
+            .build();
+        manager
+            .attach_client(mock_server_config("files"), Arc::new(mock.clone()))
+            .await
+            .unwrap();
+
+        let req = smcp::ToolCallReq {
+            base: smcp::AgentCallData {
+                agent: "agent1".to_string(),
+                req_id: smcp::ReqId::from_string("req-progress".to_string()),
+            },
+            computer: "c".to_string(),
+            tool_name: "build".to_string(),
+            params: serde_json::json!({}),
+            timeout: 5,
+            idempotency_key: None,
+        };
+        let (result, _) = manager.execute_tool_req(&req).await.unwrap();
+        assert!(matches!(&result.content[0], Content::Text { text } if text == "mock:build"));
+
+        tokio::time::timeout(Duration::from_secs(2), async {
+            while handler.0.lock().unwrap().len() < 2 {
+                sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("progress should be forwarded");
+        let recorded = handler.0.lock().unwrap().clone();
+        assert_eq!(
+            recorded,
+            vec![
+                (
+                    "req-progress".to_string(),
+                    1.0,
+                    Some("compiling".to_string())
+                ),
+                ("req-progress".to_string(), 2.0, Some("linking".to_string())),
+            ]
+        );
+
+        // 未携带令牌的调用不产生进度 / Calls without a token produce no progress
+        manager
+            .execute_tool("build", serde_json::json!({}), None)
+            .await
+            .unwrap();
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(handler.0.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_content_hash_tracks_content() {
+        use crate::mcp_clients::testing::mock_server_config;
+
+        let with_env = |pairs: &[(&str, &str)]| {
+            let mut config = mock_server_config("a");
+            if let MCPServerConfig::Stdio(ref mut stdio) = config {
+                for (k, v) in pairs {
+                    stdio
+                        .server_parameters
+                        .env
+                        .insert(k.to_string(), v.to_string());
+                }
+            }
+            config
+        };
+
+        // 插入顺序不影响哈希 / Insertion order does not affect the hash
+        let forward = with_env(&[("A", "1"), ("B", "2"), ("C", "3")]);
+        let backward = with_env(&[("C", "3"), ("B", "2"), ("A", "1")]);
+        assert_eq!(forward.content_hash(), backward.content_hash());
+
+        let changed = with_env(&[("A", "1"), ("B", "2"), ("C", "4")]);
+        assert_ne!(forward.content_hash(), changed.content_hash());
+    }
+
+    #[tokio::test]
+    async fn test_reload_restarts_only_changed_servers() {
+        use crate::mcp_clients::testing::{mock_server_config, MockMCPClient};
+
+        let manager = MCPServerManager::new();
+        manager.enable_auto_reconnect().await;
+        let a = MockMCPClient::builder().tool("read").build();
+        let b = MockMCPClient::builder().tool("query").build();
+        manager
+            .attach_client(mock_server_config("a"), Arc::new(a.clone()))
+            .await
+            .unwrap();
+        manager
+            .attach_client(mock_server_config("b"), Arc::new(b.clone()))
+            .await
+            .unwrap();
+
+        // 相同配置不重启任何服务器 / Identical config restarts nothing
+        manager
+            .reload_servers(vec![mock_server_config("a"), mock_server_config("b")])
+            .await
+            .unwrap();
+        assert_eq!(a.disconnect_count(), 0);
+        assert_eq!(b.disconnect_count(), 0);
+
+        // 只修改 b 的参数，只有 b 以新配置重启 / Only b's args change, so only b restarts with the new config
+        let init = r#"{"jsonrpc":"2.0","id":1,"result":{"capabilities":{}}}"#;
+        let tools = r#"{"jsonrpc":"2.0","id":3,"result":{"tools":[]}}"#;
+        let script = format!(
+            "read l; {}; read l; while read l; do {}; done",
+            sh_reply(init),
+            sh_reply(tools)
+        );
+        let mut changed = mock_server_config("b");
+        if let MCPServerConfig::Stdio(ref mut stdio) = changed {
+            stdio.server_parameters.command = "sh".to_string();
+            stdio.server_parameters.args = vec!["-c".to_string(), script];
+        }
+        manager
+            .reload_servers(vec![mock_server_config("a"), changed.clone()])
+            .await
+            .unwrap();
+        assert_eq!(a.disconnect_count(), 0);
+        assert_eq!(b.disconnect_count(), 1);
+
+        let configs = manager.servers_config.read().await;
+        assert_eq!(configs.get("b"), Some(&changed));
+        drop(configs);
+        manager.stop_all().await.unwrap();
+    }
+
     #[tokio::test]
     async fn test_tool_conflict_detection() {
         let manager = MCPServerManager::new();
@@ -12,19 +12,26 @@ pub mod base_client;
 pub mod http_client;
 pub mod manager;
 pub mod model;
+pub mod object_mapper;
 pub mod render;
 pub mod resource_cache;
 pub mod sse_client;
 pub mod stdio_client;
 pub mod subscription_manager;
+#[cfg(any(test, feature = "test-util"))]
+pub mod testing;
 pub mod utils;
 pub mod vrl_runtime;
 
 // 重新导出核心类型 / Re-export core types
 pub use base_client::BaseMCPClient;
-pub use manager::{MCPServerManager, ToolNameDuplicatedError};
+pub use manager::{
+    BootPolicy, MCPServerManager, ManagerSnapshot, ManagerState, ReconnectPolicy,
+    ToolNameDuplicatedError,
+};
 pub use model::*;
-pub use render::{ConfigRender, RenderError};
+pub use object_mapper::{ObjectMapperError, RetObjectMapper};
+pub use render::{ConfigRender, RenderError, UnresolvedEnvPolicy};
 pub use resource_cache::{CachedResource, ResourceCache};
 pub use subscription_manager::{Subscription, SubscriptionManager};
 pub use utils::client_factory;
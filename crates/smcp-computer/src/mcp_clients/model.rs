@@ -124,6 +124,47 @@ impl MCPServerConfig {
             MCPServerConfig::Http(config) => config.vrl.as_deref(),
         }
     }
+
+    /// 配置内容的稳定哈希，内容相同则哈希相同，与映射的遍历顺序无关
+    /// Stable hash of the config content; equal content hashes equally regardless of map iteration order
+    pub fn content_hash(&self) -> u64 {
+        // FNV-1a，跨进程与编译器版本保持稳定 / FNV-1a, stable across processes and compiler versions
+        const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+        let value = serde_json::to_value(self).unwrap_or_default();
+        canonical_json(&value)
+            .bytes()
+            .fold(OFFSET_BASIS, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+            })
+    }
+}
+
+/// 按键排序输出 JSON，使内容相同的值得到相同的文本 / Render JSON with sorted keys so equal values produce equal text
+fn canonical_json(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            let fields: Vec<String> = entries
+                .into_iter()
+                .map(|(key, value)| {
+                    format!(
+                        "{}:{}",
+                        serde_json::Value::String(key.clone()),
+                        canonical_json(value)
+                    )
+                })
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        }
+        serde_json::Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(canonical_json).collect();
+            format!("[{}]", items.join(","))
+        }
+        other => other.to_string(),
+    }
 }
 
 /// STDIO服务器配置 / STDIO server configuration
@@ -239,100 +280,11 @@ pub struct HttpServerParameters {
     pub headers: HashMap<String, String>,
 }
 
-/// MCP服务器输入项基类 / Base MCP server input configuration
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-#[serde(tag = "type")]
-pub enum MCPServerInput {
-    /// 字符串输入 / String input
-    PromptString(PromptStringInput),
-    /// 选择输入 / Pick string input
-    PickString(PickStringInput),
-    /// 命令输入 / Command input
-    Command(CommandInput),
-}
-
-impl MCPServerInput {
-    /// 获取输入ID / Get input ID
-    pub fn id(&self) -> &str {
-        match self {
-            MCPServerInput::PromptString(input) => &input.id,
-            MCPServerInput::PickString(input) => &input.id,
-            MCPServerInput::Command(input) => &input.id,
-        }
-    }
-
-    /// 获取输入描述 / Get input description
-    pub fn description(&self) -> &str {
-        match self {
-            MCPServerInput::PromptString(input) => &input.description,
-            MCPServerInput::PickString(input) => &input.description,
-            MCPServerInput::Command(input) => &input.description,
-        }
-    }
-
-    /// 获取默认值 / Get default value
-    pub fn default(&self) -> Option<serde_json::Value> {
-        match self {
-            MCPServerInput::PromptString(input) => input
-                .default
-                .as_ref()
-                .map(|s| serde_json::Value::String(s.clone())),
-            MCPServerInput::PickString(input) => input
-                .default
-                .as_ref()
-                .map(|s| serde_json::Value::String(s.clone())),
-            MCPServerInput::Command(_input) => {
-                // Command 类型不支持默认值
-                // Command type doesn't support default values
-                None
-            }
-        }
-    }
-}
-
-/// 字符串输入类型 / String input type
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-pub struct PromptStringInput {
-    /// 输入ID / Input ID
-    pub id: String,
-    /// 描述 / Description
-    pub description: String,
-    /// 默认值 / Default value
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub default: Option<String>,
-    /// 是否为密码 / Whether password
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub password: Option<bool>,
-}
-
-/// 选择输入类型 / Pick string input type
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-pub struct PickStringInput {
-    /// 输入ID / Input ID
-    pub id: String,
-    /// 描述 / Description
-    pub description: String,
-    /// 选项 / Options
-    #[serde(default)]
-    pub options: Vec<String>,
-    /// 默认值 / Default value
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub default: Option<String>,
-}
-
-/// 命令输入类型 / Command input type
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-pub struct CommandInput {
-    /// 输入ID / Input ID
-    pub id: String,
-    /// 描述 / Description
-    pub description: String,
-    /// 命令 / Command
-    pub command: String,
-    /// 参数 / Arguments
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub args: Option<HashMap<String, String>>,
-}
+// 输入项类型定义在协议层，以便 Agent 解析 Computer 配置
+// Input types live in the protocol crate so agents can parse Computer configs
+pub use smcp::{
+    CommandInput, MCPServerInput, PickStringInput, PromptNumberInput, PromptStringInput,
+};
 
 /// MCP客户端协议trait / MCP client protocol trait
 #[async_trait::async_trait]
@@ -340,6 +292,24 @@ pub trait MCPClientProtocol: Send + Sync {
     /// 获取客户端状态 / Get client state
     fn state(&self) -> ClientState;
 
+    /// 订阅客户端状态变化 / Subscribe to client state changes
+    ///
+    /// 默认返回 None，此时 Manager 不会监督该客户端的意外断开
+    /// Returns None by default, in which case the manager does not supervise the client for unexpected disconnects
+    fn subscribe_state(&self) -> Option<tokio::sync::watch::Receiver<ClientState>> {
+        None
+    }
+
+    /// 订阅服务器主动发送的消息（通知与服务器请求） / Subscribe to messages initiated by the server (notifications and server requests)
+    ///
+    /// 默认返回 None，此时 Manager 不会响应服务器通知
+    /// Returns None by default, in which case the manager does not react to server notifications
+    fn subscribe_notifications(
+        &self,
+    ) -> Option<tokio::sync::broadcast::Receiver<serde_json::Value>> {
+        None
+    }
+
     /// 连接MCP服务器 / Connect to MCP server
     async fn connect(&self) -> Result<(), MCPClientError>;
 
@@ -356,6 +326,22 @@ pub trait MCPClientProtocol: Send + Sync {
         params: serde_json::Value,
     ) -> Result<CallToolResult, MCPClientError>;
 
+    /// 调用工具并请求进度通知 / Call tool requesting progress notifications
+    ///
+    /// 服务器据 `progress_token` 发送 `notifications/progress`，经 `subscribe_notifications` 送达。
+    /// 默认忽略令牌，不支持服务器通知的客户端无需实现
+    /// The server sends `notifications/progress` tagged with `progress_token`, delivered through
+    /// `subscribe_notifications`. The token is ignored by default, clients without server
+    /// notifications need not override
+    async fn call_tool_with_progress(
+        &self,
+        tool_name: &str,
+        params: serde_json::Value,
+        _progress_token: &str,
+    ) -> Result<CallToolResult, MCPClientError> {
+        self.call_tool(tool_name, params).await
+    }
+
     /// 列出窗口资源 / List window resources
     async fn list_windows(&self) -> Result<Vec<Resource>, MCPClientError>;
 
@@ -370,6 +356,41 @@ pub trait MCPClientProtocol: Send + Sync {
 
     /// 取消订阅窗口资源更新 / Unsubscribe from window resource updates
     async fn unsubscribe_window(&self, resource: Resource) -> Result<(), MCPClientError>;
+
+    /// 获取资源模板列表 / Get resource templates list
+    ///
+    /// 默认返回空列表，不支持资源模板的客户端无需实现
+    /// Returns an empty list by default, clients without resource template support need not override
+    async fn list_resource_templates(&self) -> Result<Vec<ResourceTemplate>, MCPClientError> {
+        Ok(vec![])
+    }
+
+    /// 获取可用提示词列表 / Get available prompts list
+    ///
+    /// 默认返回空列表，不支持 prompts 的客户端无需实现
+    /// Returns an empty list by default, clients without prompts support need not override
+    async fn list_prompts(&self) -> Result<Vec<Prompt>, MCPClientError> {
+        Ok(vec![])
+    }
+
+    /// 获取提示词内容 / Get prompt content
+    async fn get_prompt(
+        &self,
+        name: &str,
+        _arguments: Option<HashMap<String, String>>,
+    ) -> Result<GetPromptResult, MCPClientError> {
+        Err(MCPClientError::ProtocolError(format!(
+            "Prompt '{}' not supported by this client",
+            name
+        )))
+    }
+
+    /// 获取 initialize 时服务器上报的信息 / Get the info the server reported during initialize
+    ///
+    /// 未连接或客户端不支持时返回 None / Returns None when not connected or unsupported
+    async fn server_info(&self) -> Option<ServerInfo> {
+        None
+    }
 }
 
 /// 客户端状态 / Client state
@@ -429,6 +450,13 @@ pub struct Tool {
     /// 输入模式 / Input schema
     #[serde(rename = "inputSchema")]
     pub input_schema: serde_json::Value,
+    /// 输出模式 / Output schema
+    #[serde(
+        rename = "outputSchema",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub output_schema: Option<serde_json::Value>,
     /// 工具注解 / Tool annotations
     #[serde(skip_serializing_if = "Option::is_none")]
     pub annotations: Option<ToolAnnotations>,
@@ -437,6 +465,33 @@ pub struct Tool {
     pub meta: Option<HashMap<String, serde_json::Value>>,
 }
 
+impl Tool {
+    /// 转换为 SMCP 工具，合并后的工具元数据写入 `meta` 的 `a2c_tool_meta` 字段
+    /// Convert into an SMCP tool, writing the merged tool metadata to `a2c_tool_meta` in `meta`
+    pub fn into_smcp_tool(self, tool_meta: Option<&ToolMeta>) -> smcp::SMCPTool {
+        let mut meta = self.meta.unwrap_or_default();
+        if let Some(tool_meta) = tool_meta {
+            meta.insert(
+                A2C_TOOL_META.to_string(),
+                serde_json::to_value(tool_meta).unwrap_or_default(),
+            );
+        }
+        smcp::SMCPTool {
+            name: self.name,
+            description: self.description,
+            params_schema: self.input_schema,
+            return_schema: self.output_schema,
+            meta: (!meta.is_empty()).then(|| serde_json::Value::Object(meta.into_iter().collect())),
+        }
+    }
+}
+
+impl From<Tool> for smcp::SMCPTool {
+    fn from(tool: Tool) -> Self {
+        tool.into_smcp_tool(None)
+    }
+}
+
 /// 工具注解 / Tool annotations
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ToolAnnotations {
@@ -453,6 +508,80 @@ pub struct ToolAnnotations {
     pub open_world_hint: bool,
 }
 
+/// 提示词参数 / Prompt argument
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PromptArgument {
+    /// 参数名称 / Argument name
+    pub name: String,
+    /// 参数描述 / Argument description
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// 是否必填 / Whether required
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub required: Option<bool>,
+}
+
+/// MCP提示词定义 / MCP prompt definition
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Prompt {
+    /// 提示词名称 / Prompt name
+    pub name: String,
+    /// 提示词描述 / Prompt description
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// 参数列表 / Argument list
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<Vec<PromptArgument>>,
+}
+
+/// 提示词消息 / Prompt message
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PromptMessage {
+    /// 角色（user/assistant） / Role (user/assistant)
+    pub role: String,
+    /// 消息内容 / Message content
+    pub content: serde_json::Value,
+}
+
+/// 获取提示词结果 / Get prompt result
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GetPromptResult {
+    /// 描述 / Description
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// 消息列表 / Messages
+    pub messages: Vec<PromptMessage>,
+}
+
+/// MCP服务器信息（来自 initialize 响应）/ MCP server info (from the initialize response)
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ServerInfo {
+    /// 服务器名称（serverInfo.name）/ Server name (serverInfo.name)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// 服务器版本（serverInfo.version）/ Server version (serverInfo.version)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// 服务器使用说明 / Server usage instructions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
+}
+
+impl ServerInfo {
+    /// 从 initialize 结果中提取 / Extract from an initialize result
+    pub fn from_initialize_result(result: &serde_json::Value) -> Self {
+        let field = |value: Option<&serde_json::Value>| {
+            value.and_then(|v| v.as_str()).map(|s| s.to_string())
+        };
+        let server_info = result.get("serverInfo");
+        Self {
+            name: field(server_info.and_then(|info| info.get("name"))),
+            version: field(server_info.and_then(|info| info.get("version"))),
+            instructions: field(result.get("instructions")),
+        }
+    }
+}
+
 /// 资源定义 / Resource definition
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Resource {
@@ -468,14 +597,48 @@ pub struct Resource {
     pub mime_type: Option<String>,
 }
 
+/// 资源模板定义（RFC 6570 URI 模板） / Resource template definition (RFC 6570 URI template)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ResourceTemplate {
+    /// URI 模板 / URI template
+    #[serde(rename = "uriTemplate")]
+    pub uri_template: String,
+    /// 名称 / Name
+    pub name: String,
+    /// 描述 / Description
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// MIME类型 / MIME type
+    #[serde(rename = "mimeType", skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+}
+
+impl From<ResourceTemplate> for smcp::SMCPResourceTemplate {
+    fn from(template: ResourceTemplate) -> Self {
+        smcp::SMCPResourceTemplate {
+            uri_template: template.uri_template,
+            name: template.name,
+            description: template.description,
+            mime_type: template.mime_type,
+        }
+    }
+}
+
 /// 工具调用结果 / Tool call result
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct CallToolResult {
     /// 内容 / Content
     pub content: Vec<Content>,
     /// 是否为错误 / Is error
-    #[serde(default)]
+    #[serde(rename = "isError", default)]
     pub is_error: bool,
+    /// 结构化内容 / Structured content
+    #[serde(
+        rename = "structuredContent",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub structured_content: Option<serde_json::Value>,
     /// 元数据 / Metadata
     #[serde(skip_serializing_if = "Option::is_none")]
     pub meta: Option<HashMap<String, serde_json::Value>>,
@@ -497,6 +660,14 @@ pub enum Content {
         uri: String,
         mime_type: Option<String>,
     },
+    /// 资源链接，内容需通过 read_resource 获取 / Resource link, content is fetched via read_resource
+    #[serde(rename = "resource_link")]
+    ResourceLink {
+        uri: String,
+        name: String,
+        mime_type: Option<String>,
+        size: Option<u64>,
+    },
 }
 
 /// 读取资源结果 / Read resource result
@@ -0,0 +1,182 @@
+/**
+* 文件名: object_mapper
+* 作者: JQQ
+* 创建日期: 2025/12/16
+* 最后修改日期: 2025/12/16
+* 版权: 2023 JQQ. All rights reserved.
+* 依赖: serde_json
+* 描述: ToolMeta.ret_object_mapper 的解析与执行，将工具结构化结果重塑为Agent期望的结构
+*/
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ObjectMapperError {
+    #[error("Invalid target path: {0:?}")]
+    InvalidTarget(String),
+    #[error("Invalid source path: {0:?}, expected `$` or `$.a.b`")]
+    InvalidSource(String),
+    #[error("Target path {0:?} overlaps with target path {1:?}")]
+    TargetConflict(String, String),
+    #[error("Source path {0:?} not found in tool result")]
+    SourceNotFound(String),
+}
+
+/// 返回值映射器，格式为 `{ "out.field": "$.in.nested" }`
+///
+/// 键为输出对象中以 `.` 分隔的目标路径，值为以 `$` 开头的源路径，
+/// `$` 表示整个结果，数组元素可以用数字段访问（如 `$.items.0.id`）。
+/// 未出现在映射表中的字段不会被保留。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetObjectMapper {
+    /// (目标路径, 源路径原文, 源路径分段)，按目标路径排序
+    entries: Vec<(Vec<String>, String, Vec<String>)>,
+}
+
+impl RetObjectMapper {
+    /// 解析并校验映射表
+    pub fn parse(spec: &HashMap<String, String>) -> Result<Self, ObjectMapperError> {
+        let mut entries = spec
+            .iter()
+            .map(|(target, source)| {
+                Ok((
+                    Self::parse_target(target)?,
+                    source.clone(),
+                    Self::parse_source(source)?,
+                ))
+            })
+            .collect::<Result<Vec<_>, ObjectMapperError>>()?;
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+        // 排序后前缀关系的路径必然相邻（如 `a` 与 `a.b`）
+        for pair in entries.windows(2) {
+            let (prev, next) = (&pair[0].0, &pair[1].0);
+            if next.starts_with(prev) {
+                return Err(ObjectMapperError::TargetConflict(
+                    prev.join("."),
+                    next.join("."),
+                ));
+            }
+        }
+
+        Ok(Self { entries })
+    }
+
+    /// 按映射表从源值构造新的对象
+    pub fn apply(&self, source: &Value) -> Result<Value, ObjectMapperError> {
+        let mut output = Map::new();
+        for (target, raw_source, source_path) in &self.entries {
+            let value = Self::lookup(source, source_path)
+                .ok_or_else(|| ObjectMapperError::SourceNotFound(raw_source.clone()))?;
+            Self::insert(&mut output, target, value.clone());
+        }
+        Ok(Value::Object(output))
+    }
+
+    fn parse_target(target: &str) -> Result<Vec<String>, ObjectMapperError> {
+        let segments: Vec<String> = target.split('.').map(str::to_string).collect();
+        if target.starts_with('$') || segments.iter().any(String::is_empty) {
+            return Err(ObjectMapperError::InvalidTarget(target.to_string()));
+        }
+        Ok(segments)
+    }
+
+    fn parse_source(source: &str) -> Result<Vec<String>, ObjectMapperError> {
+        if source == "$" {
+            return Ok(vec![]);
+        }
+        let rest = source
+            .strip_prefix("$.")
+            .ok_or_else(|| ObjectMapperError::InvalidSource(source.to_string()))?;
+        let segments: Vec<String> = rest.split('.').map(str::to_string).collect();
+        if segments.iter().any(String::is_empty) {
+            return Err(ObjectMapperError::InvalidSource(source.to_string()));
+        }
+        Ok(segments)
+    }
+
+    fn lookup<'a>(value: &'a Value, path: &[String]) -> Option<&'a Value> {
+        path.iter()
+            .try_fold(value, |current, segment| match current {
+                Value::Object(map) => map.get(segment),
+                Value::Array(items) => segment.parse::<usize>().ok().and_then(|i| items.get(i)),
+                _ => None,
+            })
+    }
+
+    fn insert(output: &mut Map<String, Value>, path: &[String], value: Value) {
+        let (last, parents) = path.split_last().expect("target path is never empty");
+        let mut current = output;
+        for segment in parents {
+            // 目标路径互不重叠，中间节点只会是对象
+            current = match current
+                .entry(segment.clone())
+                .or_insert_with(|| Value::Object(Map::new()))
+            {
+                Value::Object(map) => map,
+                _ => unreachable!("target paths do not overlap"),
+            };
+        }
+        current.insert(last.clone(), value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn mapper(pairs: &[(&str, &str)]) -> Result<RetObjectMapper, ObjectMapperError> {
+        RetObjectMapper::parse(
+            &pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_basic_remap() {
+        let mapper = mapper(&[
+            ("out.field", "$.in.nested"),
+            ("out.first", "$.items.0.id"),
+            ("raw", "$"),
+        ])
+        .unwrap();
+        let source = json!({"in": {"nested": 42}, "items": [{"id": "a"}], "extra": true});
+
+        let result = mapper.apply(&source).unwrap();
+        assert_eq!(
+            result,
+            json!({"out": {"field": 42, "first": "a"}, "raw": source})
+        );
+    }
+
+    #[test]
+    fn test_missing_source_path() {
+        let mapper = mapper(&[("out", "$.in.missing")]).unwrap();
+
+        let err = mapper.apply(&json!({"in": {}})).unwrap_err();
+        assert_eq!(
+            err,
+            ObjectMapperError::SourceNotFound("$.in.missing".to_string())
+        );
+    }
+
+    #[test]
+    fn test_invalid_spec_rejected() {
+        assert!(matches!(
+            mapper(&[("out", "in.nested")]),
+            Err(ObjectMapperError::InvalidSource(_))
+        ));
+        assert!(matches!(
+            mapper(&[("out..field", "$.a")]),
+            Err(ObjectMapperError::InvalidTarget(_))
+        ));
+        assert!(matches!(
+            mapper(&[("out", "$.a"), ("out.field", "$.b")]),
+            Err(ObjectMapperError::TargetConflict(_, _))
+        ));
+    }
+}
@@ -16,15 +16,29 @@ use thiserror::Error;
 pub enum RenderError {
     #[error("Input not found: {0}")]
     InputNotFound(String),
+    #[error("Environment variable not found: {0}")]
+    EnvNotFound(String),
     #[error("Render depth exceeded")]
     DepthExceeded,
     #[error("Invalid placeholder format")]
     InvalidPlaceholder,
 }
 
+/// 未设置的 ${env:VAR} 的处理策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnresolvedEnvPolicy {
+    /// 替换为空字符串
+    #[default]
+    Empty,
+    /// 返回 `RenderError::EnvNotFound`
+    Error,
+}
+
 /// 配置渲染器，用于处理 ${input:xxx} 占位符
 pub struct ConfigRender {
     placeholder_regex: Regex,
+    env_regex: Regex,
+    env_policy: UnresolvedEnvPolicy,
     max_depth: usize,
 }
 
@@ -33,17 +47,87 @@ impl ConfigRender {
     pub fn new(max_depth: usize) -> Self {
         Self {
             placeholder_regex: Regex::new(r"\$\{input:([^}]+)}").unwrap(),
+            env_regex: Regex::new(r"\$\{env:([^}]+)}").unwrap(),
+            env_policy: UnresolvedEnvPolicy::default(),
             max_depth,
         }
     }
 
+    /// 设置未设置环境变量的处理策略
+    pub fn with_env_policy(mut self, policy: UnresolvedEnvPolicy) -> Self {
+        self.env_policy = policy;
+        self
+    }
+
+    /// 使用宿主进程环境展开字符串中的 ${env:VAR}
+    pub fn expand_env(&self, s: &str) -> Result<String, RenderError> {
+        let mut result = String::with_capacity(s.len());
+        let mut last = 0;
+        for caps in self.env_regex.captures_iter(s) {
+            let m = caps.get(0).unwrap();
+            let value = match std::env::var(&caps[1]) {
+                Ok(value) => value,
+                Err(_) => match self.env_policy {
+                    UnresolvedEnvPolicy::Empty => String::new(),
+                    UnresolvedEnvPolicy::Error => {
+                        return Err(RenderError::EnvNotFound(caps[1].to_string()))
+                    }
+                },
+            };
+            result.push_str(&s[last..m.start()]);
+            result.push_str(&value);
+            last = m.end();
+        }
+        result.push_str(&s[last..]);
+        Ok(result)
+    }
+
     /// 渲染配置值
     pub async fn render<F, Fut>(&self, data: Value, resolver: F) -> Result<Value, RenderError>
     where
         F: Fn(String) -> Fut + Copy + Send + Sync,
         Fut: std::future::Future<Output = Result<Value, RenderError>> + Send,
     {
-        self.render_with_depth(data, resolver, 0).await
+        self.render_with_depth(data, resolver, 0, false).await
+    }
+
+    /// 渲染字段均为字符串的配置值
+    ///
+    /// 与 [`render`](Self::render) 不同，整串占位符解析为数字或布尔值时也以字符串写回，
+    /// 保证渲染结果仍能反序列化为原配置类型（如 `PORT: "${input:port}"` 渲染为 `"42"`）。
+    pub async fn render_strings<F, Fut>(
+        &self,
+        data: Value,
+        resolver: F,
+    ) -> Result<Value, RenderError>
+    where
+        F: Fn(String) -> Fut + Copy + Send + Sync,
+        Fut: std::future::Future<Output = Result<Value, RenderError>> + Send,
+    {
+        self.render_with_depth(data, resolver, 0, true).await
+    }
+
+    /// 收集配置值中残留的占位符 input id（去重，按出现顺序）
+    pub fn find_placeholders(&self, data: &Value) -> Vec<String> {
+        let mut ids = Vec::new();
+        self.collect_placeholders(data, &mut ids);
+        ids
+    }
+
+    fn collect_placeholders(&self, data: &Value, ids: &mut Vec<String>) {
+        match data {
+            Value::String(s) => {
+                for caps in self.placeholder_regex.captures_iter(s) {
+                    let id = caps[1].to_string();
+                    if !ids.contains(&id) {
+                        ids.push(id);
+                    }
+                }
+            }
+            Value::Object(map) => map.values().for_each(|v| self.collect_placeholders(v, ids)),
+            Value::Array(arr) => arr.iter().for_each(|v| self.collect_placeholders(v, ids)),
+            _ => {}
+        }
     }
 
     #[async_recursion]
@@ -52,6 +136,7 @@ impl ConfigRender {
         data: Value,
         resolver: F,
         depth: usize,
+        stringify: bool,
     ) -> Result<Value, RenderError>
     where
         F: Fn(String) -> Fut + Copy + Send + Sync,
@@ -62,17 +147,28 @@ impl ConfigRender {
         }
 
         match data {
-            Value::String(s) => self.render_string(s, resolver, depth).await,
+            Value::String(s) => match self.render_string(s, resolver, depth).await? {
+                Value::String(s) => Ok(Value::String(s)),
+                other if stringify => Ok(Value::String(other.to_string())),
+                other => Ok(other),
+            },
             Value::Object(mut map) => {
                 for (k, v) in map.clone() {
-                    map.insert(k, self.render_with_depth(v, resolver, depth + 1).await?);
+                    map.insert(
+                        k,
+                        self.render_with_depth(v, resolver, depth + 1, stringify)
+                            .await?,
+                    );
                 }
                 Ok(Value::Object(map))
             }
             Value::Array(arr) => {
                 let mut new_arr = Vec::with_capacity(arr.len());
                 for item in arr {
-                    new_arr.push(self.render_with_depth(item, resolver, depth + 1).await?);
+                    new_arr.push(
+                        self.render_with_depth(item, resolver, depth + 1, stringify)
+                            .await?,
+                    );
                 }
                 Ok(Value::Array(new_arr))
             }
@@ -186,6 +282,67 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_find_placeholders() {
+        let render = ConfigRender::default();
+        let data = serde_json::json!({
+            "command": "${input:cmd}",
+            "args": ["--token=${input:token}", "${input:cmd}"],
+            "cwd": null
+        });
+        let mut ids = render.find_placeholders(&data);
+        ids.sort();
+        assert_eq!(ids, vec!["cmd".to_string(), "token".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_render_strings_keeps_string_positions() {
+        let render = ConfigRender::default();
+        let data = serde_json::json!({
+            "env": {
+                "PORT": "${input:number}",
+                "API_TOKEN": "Bearer ${input:token}"
+            },
+            "args": ["${input:number}"]
+        });
+
+        let rendered = render
+            .render_strings(data.clone(), mock_resolver)
+            .await
+            .unwrap();
+        assert_eq!(rendered["env"]["PORT"], "42");
+        assert_eq!(rendered["env"]["API_TOKEN"], "Bearer resolved_token");
+        assert_eq!(rendered["args"][0], "42");
+
+        // render 保留解析值的原始类型 / render keeps the resolved value's type
+        let raw = render.render(data, mock_resolver).await.unwrap();
+        assert_eq!(raw["env"]["PORT"], 42);
+    }
+
+    #[test]
+    fn test_expand_env_unresolved_policy() {
+        let home = std::env::var("HOME").unwrap();
+        let render = ConfigRender::default();
+        assert_eq!(
+            render.expand_env("${env:HOME}/data").unwrap(),
+            format!("{}/data", home)
+        );
+        assert_eq!(
+            render
+                .expand_env("${env:SMCP_TEST_SURELY_UNSET_VAR}:/opt/bin")
+                .unwrap(),
+            ":/opt/bin"
+        );
+
+        let err = ConfigRender::default()
+            .with_env_policy(UnresolvedEnvPolicy::Error)
+            .expand_env("${env:SMCP_TEST_SURELY_UNSET_VAR}:/opt/bin")
+            .unwrap_err();
+        assert!(
+            matches!(err, RenderError::EnvNotFound(name) if name == "SMCP_TEST_SURELY_UNSET_VAR")
+        );
+    }
+
     #[tokio::test]
     async fn test_missing_input() {
         let render = ConfigRender::default();
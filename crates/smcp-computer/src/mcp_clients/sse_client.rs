@@ -4,37 +4,317 @@
 * 创建日期: 2025/12/15
 * 最后修改日期: 2025/12/15
 * 版权: 2023 JQQ. All rights reserved.
-* 依赖: tokio, reqwest, eventsource-client, serde_json
+* 依赖: tokio, reqwest, serde_json, url
 * 描述: SSE类型的MCP客户端实现
 */
-use super::base_client::BaseMCPClient;
+use super::base_client::{cancelled_notification, BaseMCPClient, CancelOnDrop};
 use super::model::*;
 use super::{ResourceCache, SubscriptionManager};
 use crate::desktop::window_uri::{is_window_uri, WindowURI};
 use async_trait::async_trait;
-use es::Client as EsClient;
-use eventsource_client as es;
-use futures::stream::{Stream, StreamExt};
+use futures::StreamExt;
 use serde_json;
-use std::pin::Pin;
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::{mpsc, Mutex};
+use tokio::sync::{mpsc, oneshot, watch, Mutex};
+use tokio::task::JoinHandle;
 use tracing::{debug, error, info, warn};
 
+/// 单个请求等待响应的超时 / Timeout for a single request awaiting its response
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 断线后的默认重连间隔（服务器可通过 `retry` 字段覆盖）
+/// Default reconnect delay after the stream drops (the server may override it with `retry`)
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// 放弃前的最大连续重连次数 / Maximum consecutive reconnect attempts before giving up
+const MAX_RECONNECT_ATTEMPTS: u32 = 5;
+
+/// 等待响应的请求：请求ID -> 响应发送端 / Requests awaiting a response: request id -> response sender
+type PendingRequests = Arc<std::sync::Mutex<HashMap<u64, oneshot::Sender<serde_json::Value>>>>;
+
+/// 请求结束（完成、超时或被中止）时移除等待登记
+/// Removes the pending entry when a request ends (completed, timed out or aborted)
+struct PendingGuard<'a> {
+    pending: &'a PendingRequests,
+    id: u64,
+}
+
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        self.pending.lock().unwrap().remove(&self.id);
+    }
+}
+
+/// 一条 SSE 事件 / One SSE event
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct SseEvent {
+    /// 事件类型（`event` 字段） / Event type (`event` field)
+    pub(crate) event: Option<String>,
+    /// 事件数据，多行 `data` 以换行连接 / Event data, multiple `data` lines joined by newlines
+    pub(crate) data: String,
+    /// 事件ID（`id` 字段） / Event id (`id` field)
+    pub(crate) id: Option<String>,
+    /// 重连间隔毫秒数（`retry` 字段） / Reconnect delay in milliseconds (`retry` field)
+    pub(crate) retry: Option<u64>,
+}
+
+/// 增量 SSE 流解析器 / Incremental SSE stream parser
+#[derive(Debug, Default)]
+pub(crate) struct SseParser {
+    /// 未完成的行 / Incomplete line
+    buffer: Vec<u8>,
+    /// 正在累积的事件 / Event being accumulated
+    current: SseEvent,
+    /// 当前事件是否已有字段 / Whether the current event has any field yet
+    dirty: bool,
+}
+
+impl SseParser {
+    /// 输入一段字节，返回其中完整的事件 / Feed a chunk of bytes and return the complete events
+    pub(crate) fn feed(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buffer.extend_from_slice(chunk);
+        let mut events = Vec::new();
+        while let Some(pos) = self.buffer.iter().position(|b| *b == b'\n') {
+            let mut line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            line.pop();
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+            let line = String::from_utf8_lossy(&line);
+            if line.is_empty() {
+                // 空行结束一个事件 / A blank line terminates an event
+                if std::mem::take(&mut self.dirty) {
+                    events.push(std::mem::take(&mut self.current));
+                }
+                continue;
+            }
+            if line.starts_with(':') {
+                continue;
+            }
+            let (field, value) = match line.split_once(':') {
+                Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+                None => (line.as_ref(), ""),
+            };
+            match field {
+                "event" => self.current.event = Some(value.to_string()),
+                "data" => {
+                    if !self.current.data.is_empty() {
+                        self.current.data.push('\n');
+                    }
+                    self.current.data.push_str(value);
+                }
+                "id" => self.current.id = Some(value.to_string()),
+                "retry" => self.current.retry = value.parse().ok(),
+                _ => continue,
+            }
+            self.dirty = true;
+        }
+        events
+    }
+}
+
+/// SSE 读取任务使用的共享状态 / Shared state used by the SSE reader task
+#[derive(Clone)]
+struct StreamContext {
+    http_client: reqwest::Client,
+    url: String,
+    headers: HashMap<String, String>,
+    endpoint: Arc<watch::Sender<Option<String>>>,
+    pending: PendingRequests,
+    resource_cache: ResourceCache,
+    update_tx: Arc<Mutex<Option<mpsc::UnboundedSender<ResourceUpdate>>>>,
+}
+
+impl StreamContext {
+    /// 发起 SSE GET 请求，断线重连时携带 `Last-Event-ID`
+    /// Open the SSE GET request, sending `Last-Event-ID` when reconnecting
+    async fn open(&self, last_event_id: Option<&str>) -> Result<reqwest::Response, MCPClientError> {
+        let mut request = self
+            .http_client
+            .get(&self.url)
+            .header("Accept", "text/event-stream")
+            .header("Cache-Control", "no-cache");
+
+        // 添加headers / Add headers
+        for (key, value) in &self.headers {
+            request = request.header(key, value);
+        }
+        if let Some(id) = last_event_id {
+            request = request.header("Last-Event-ID", id);
+        }
+
+        let response = request.send().await.map_err(|e| {
+            MCPClientError::ConnectionError(format!("SSE connection failed: {}", e))
+        })?;
+        if !response.status().is_success() {
+            return Err(MCPClientError::ConnectionError(format!(
+                "SSE connection failed: {}",
+                response.status()
+            )));
+        }
+        Ok(response)
+    }
+
+    /// 读取事件直到流结束 / Read events until the stream ends
+    async fn consume(
+        &self,
+        response: reqwest::Response,
+        last_event_id: &mut Option<String>,
+        reconnect_delay: &mut Duration,
+    ) {
+        let mut parser = SseParser::default();
+        let mut stream = response.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(e) => {
+                    warn!("SSE stream error: {}", e);
+                    return;
+                }
+            };
+            for event in parser.feed(&chunk) {
+                if let Some(id) = &event.id {
+                    *last_event_id = Some(id.clone());
+                }
+                if let Some(retry) = event.retry {
+                    *reconnect_delay = Duration::from_millis(retry);
+                }
+                self.handle_event(event).await;
+            }
+        }
+        debug!("SSE stream closed by server");
+    }
+
+    /// 处理一条 SSE 事件 / Handle one SSE event
+    async fn handle_event(&self, event: SseEvent) {
+        match event.event.as_deref() {
+            Some("endpoint") => {
+                let resolved = url::Url::parse(&self.url).and_then(|base| base.join(&event.data));
+                match resolved {
+                    Ok(endpoint) => {
+                        debug!("SSE message endpoint: {}", endpoint);
+                        self.endpoint.send_replace(Some(endpoint.to_string()));
+                    }
+                    Err(e) => error!("Invalid SSE endpoint {:?}: {}", event.data, e),
+                }
+            }
+            None | Some("message") => self.dispatch_message(&event.data).await,
+            Some(other) => debug!("Ignoring SSE event of type {}", other),
+        }
+    }
+
+    /// 按响应 `id` 分发给对应的等待者，并处理资源更新通知
+    /// Dispatch responses to their waiters by `id` and handle resource update notifications
+    async fn dispatch_message(&self, data: &str) {
+        let message: serde_json::Value = match serde_json::from_str(data) {
+            Ok(message) => message,
+            Err(e) => {
+                warn!("Ignoring non JSON-RPC SSE message: {} ({})", data, e);
+                return;
+            }
+        };
+
+        // 区分消息类型 / Distinguish message types
+        if let Some(method) = message.get("method").and_then(|m| m.as_str()) {
+            // 资源更新通知通常包含 "method" = "resources/update"
+            if method == "resources/update" || method.contains("update") {
+                debug!("Received resource update notification");
+                let params = message.get("params");
+                let uri = params.and_then(|p| p.get("uri")).and_then(|u| u.as_str());
+                let data = params.and_then(|p| p.get("data"));
+                if let (Some(uri), Some(data)) = (uri, data) {
+                    // 刷新缓存
+                    let _ = self.resource_cache.refresh(uri, data.clone()).await;
+
+                    // 发送更新通知
+                    if let Some(tx) = self.update_tx.lock().await.as_ref() {
+                        let _ = tx.send(ResourceUpdate {
+                            uri: uri.to_string(),
+                            data: data.clone(),
+                            version: 1, // TODO: 从缓存获取版本号
+                        });
+                    }
+                }
+            } else {
+                debug!("Received server message: {}", message);
+            }
+            return;
+        }
+
+        let Some(id) = message.get("id").and_then(|id| id.as_u64()) else {
+            warn!("Ignoring response without id: {}", message);
+            return;
+        };
+        match self.pending.lock().unwrap().remove(&id) {
+            Some(waiter) => {
+                debug!("Received SSE response: {}", message);
+                let _ = waiter.send(message);
+            }
+            // 已取消或已超时请求的迟到响应 / Late response to a cancelled or timed out request
+            None => debug!("Discarded response for unknown request {}", id),
+        }
+    }
+
+    /// 读取事件流，断线后携带 `Last-Event-ID` 重连
+    /// Read the event stream and reconnect with `Last-Event-ID` when it drops
+    async fn run(self, mut response: reqwest::Response) {
+        let mut last_event_id = None;
+        let mut reconnect_delay = RECONNECT_DELAY;
+        'stream: loop {
+            self.consume(response, &mut last_event_id, &mut reconnect_delay)
+                .await;
+            // 旧端点随连接失效，等待重连后的新 endpoint 事件
+            // The old endpoint dies with the stream; wait for the endpoint event after reconnecting
+            self.endpoint.send_replace(None);
+
+            for attempt in 1..=MAX_RECONNECT_ATTEMPTS {
+                tokio::time::sleep(reconnect_delay).await;
+                info!(
+                    "Reconnecting SSE stream (attempt {}, Last-Event-ID: {:?})",
+                    attempt, last_event_id
+                );
+                match self.open(last_event_id.as_deref()).await {
+                    Ok(next) => {
+                        response = next;
+                        continue 'stream;
+                    }
+                    Err(e) => warn!("SSE reconnect attempt {} failed: {}", attempt, e),
+                }
+            }
+            break;
+        }
+        // 放弃重连后丢弃所有等待者，使其立即失败 / Drop every waiter after giving up so they fail fast
+        error!(
+            "SSE stream lost after {} reconnect attempts",
+            MAX_RECONNECT_ATTEMPTS
+        );
+        self.pending.lock().unwrap().clear();
+    }
+}
+
 /// SSE MCP客户端 / SSE MCP client
+///
+/// 通过 GET 打开事件流，从 `endpoint` 事件得到消息地址；请求以 POST 发往该地址，
+/// 响应经事件流返回并按 JSON-RPC `id` 匹配。
+/// Opens the event stream with GET and learns the message URL from the `endpoint` event;
+/// requests are POSTed there and their responses arrive over the stream, matched by JSON-RPC `id`.
 pub struct SseMCPClient {
     /// 基础客户端 / Base client
     base: BaseMCPClient<SseServerParameters>,
     /// HTTP客户端 / HTTP client
-    #[allow(dead_code)]
     http_client: reqwest::Client,
-    /// 请求发送器 / Request sender
-    request_tx: Arc<Mutex<Option<mpsc::UnboundedSender<serde_json::Value>>>>,
-    /// 响应接收器 / Response receiver
-    response_rx: Arc<Mutex<Option<mpsc::UnboundedReceiver<serde_json::Value>>>>,
+    /// 消息 POST 地址（来自 `endpoint` 事件） / Message POST URL (from the `endpoint` event)
+    endpoint: Arc<watch::Sender<Option<String>>>,
+    /// 等待响应的请求 / Requests awaiting a response
+    pending: PendingRequests,
+    /// 读取事件流的任务 / Task reading the event stream
+    reader: std::sync::Mutex<Option<JoinHandle<()>>>,
     /// 会话ID / Session ID
     session_id: Arc<Mutex<Option<String>>>,
+    /// 服务器信息 / Server info
+    server_info: Arc<Mutex<Option<ServerInfo>>>,
     /// 订阅管理器 / Subscription manager
     subscription_manager: SubscriptionManager,
     /// 资源缓存 / Resource cache
@@ -67,31 +347,116 @@ impl std::fmt::Debug for SseMCPClient {
 impl SseMCPClient {
     /// 创建新的SSE客户端 / Create new SSE client
     pub fn new(params: SseServerParameters) -> Self {
+        // 事件流是长连接，因此只限制建连时间，请求超时逐个设置
+        // The event stream is long-lived, so only connecting is bounded; requests set their own timeout
         let http_client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
+            .connect_timeout(Duration::from_secs(30))
             .build()
             .expect("Failed to create HTTP client");
 
         Self {
             base: BaseMCPClient::new(params),
             http_client,
-            request_tx: Arc::new(Mutex::new(None)),
-            response_rx: Arc::new(Mutex::new(None)),
+            endpoint: Arc::new(watch::channel(None).0),
+            pending: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            reader: std::sync::Mutex::new(None),
             session_id: Arc::new(Mutex::new(None)),
+            server_info: Arc::new(Mutex::new(None)),
             subscription_manager: SubscriptionManager::new(),
             resource_cache: ResourceCache::new(Duration::from_secs(60)), // 默认 60 秒 TTL
             update_tx: Arc::new(Mutex::new(None)),
         }
     }
 
+    /// 事件流读取任务是否在运行 / Whether the stream reader task is running
+    fn stream_running(&self) -> bool {
+        self.reader
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|handle| !handle.is_finished())
+    }
+
+    /// 获取消息地址，重连期间等待新的 `endpoint` 事件
+    /// Get the message URL, waiting for a fresh `endpoint` event while reconnecting
+    async fn endpoint_url(&self) -> Result<String, MCPClientError> {
+        let current = self.endpoint.borrow().clone();
+        if let Some(endpoint) = current {
+            return Ok(endpoint);
+        }
+        if !self.stream_running() {
+            return Err(MCPClientError::ConnectionError(
+                "SSE connection not established".to_string(),
+            ));
+        }
+        let mut rx = self.endpoint.subscribe();
+        let endpoint =
+            match tokio::time::timeout(REQUEST_TIMEOUT, rx.wait_for(Option::is_some)).await {
+                Ok(Ok(endpoint)) => endpoint.clone(),
+                Ok(Err(_)) => {
+                    return Err(MCPClientError::ConnectionError(
+                        "SSE connection closed".to_string(),
+                    ))
+                }
+                Err(_) => {
+                    return Err(MCPClientError::TimeoutError(
+                        "No endpoint event received from SSE server".to_string(),
+                    ))
+                }
+            };
+        Ok(endpoint.unwrap_or_default())
+    }
+
+    /// POST 一条 JSON-RPC 消息 / POST a JSON-RPC message
+    async fn post_json(
+        http_client: &reqwest::Client,
+        url: &str,
+        headers: &HashMap<String, String>,
+        body: &serde_json::Value,
+    ) -> Result<(), MCPClientError> {
+        let mut request = http_client.post(url).timeout(REQUEST_TIMEOUT);
+
+        // 添加headers / Add headers
+        for (key, value) in headers {
+            request = request.header(key, value);
+        }
+
+        let response =
+            request.json(body).send().await.map_err(|e| {
+                MCPClientError::ConnectionError(format!("HTTP request failed: {}", e))
+            })?;
+        if !response.status().is_success() {
+            return Err(MCPClientError::ConnectionError(format!(
+                "HTTP error: {}",
+                response.status()
+            )));
+        }
+        Ok(())
+    }
+
     /// 发送JSON-RPC请求 / Send JSON-RPC request
     async fn send_request(
         &self,
         method: &str,
         params: Option<serde_json::Value>,
     ) -> Result<serde_json::Value, MCPClientError> {
+        let request_id = self.base.next_request_id();
+        self.send_request_with_id(request_id, method, params).await
+    }
+
+    /// 使用指定ID发送JSON-RPC请求，并等待事件流中同 `id` 的响应
+    /// Send a JSON-RPC request with the given ID and await the response with the same `id` on the stream
+    async fn send_request_with_id(
+        &self,
+        request_id: u64,
+        method: &str,
+        params: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, MCPClientError> {
+        let endpoint = self.endpoint_url().await?;
+
         let mut request_body = serde_json::json!({
             "jsonrpc": "2.0",
+            "id": request_id,
             "method": method,
         });
 
@@ -99,167 +464,121 @@ impl SseMCPClient {
             request_body["params"] = p;
         }
 
-        // 添加请求ID / Add request ID
-        let request_id = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
-        request_body["id"] = serde_json::Value::Number(serde_json::Number::from(request_id));
+        debug!("Sending SSE request to {}: {}", endpoint, request_body);
 
-        debug!("Sending SSE request: {}", request_body);
-
-        // 通过SSE发送请求 / Send request via SSE
-        let tx = self.request_tx.lock().await;
-        if let Some(ref tx) = *tx {
-            tx.send(request_body.clone()).map_err(|e| {
-                MCPClientError::ConnectionError(format!("Failed to send request: {}", e))
-            })?;
-        } else {
-            return Err(MCPClientError::ConnectionError(
-                "SSE connection not established".to_string(),
-            ));
-        }
+        // 先登记再发送，避免响应早于登记到达 / Register before sending so the response cannot arrive first
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(request_id, tx);
+        let _guard = PendingGuard {
+            pending: &self.pending,
+            id: request_id,
+        };
 
-        // 等待响应 / Wait for response
-        let mut rx = self.response_rx.lock().await;
-        if let Some(ref mut receiver) = *rx {
-            match receiver.recv().await {
-                Some(response) => {
-                    debug!("Received SSE response: {}", response);
-                    Ok(response)
-                }
-                None => Err(MCPClientError::ConnectionError(
-                    "Response channel closed".to_string(),
-                )),
-            }
-        } else {
-            Err(MCPClientError::ConnectionError(
-                "Response channel not established".to_string(),
-            ))
+        Self::post_json(
+            &self.http_client,
+            &endpoint,
+            &self.base.params.headers,
+            &request_body,
+        )
+        .await?;
+
+        match tokio::time::timeout(REQUEST_TIMEOUT, rx).await {
+            Ok(Ok(response)) => Ok(response),
+            Ok(Err(_)) => Err(MCPClientError::ConnectionError(
+                "SSE connection closed".to_string(),
+            )),
+            Err(_) => Err(MCPClientError::TimeoutError(format!(
+                "No response received within timeout for request {}",
+                request_id
+            ))),
         }
     }
 
-    /// 启动SSE连接 / Start SSE connection
-    async fn start_sse_connection(&self) -> Result<(), MCPClientError> {
-        let url = &self.base.params.url;
-
-        // 构建SSE URL / Build SSE URL
-        let sse_url = if url.contains('?') {
-            format!("{}&events=true", url)
-        } else {
-            format!("{}?events=true", url)
-        };
+    /// 发送通知（不需要响应） / Send notification (no response expected)
+    async fn send_notification(
+        &self,
+        method: &str,
+        params: Option<serde_json::Value>,
+    ) -> Result<(), MCPClientError> {
+        let endpoint = self.endpoint_url().await?;
 
-        let mut builder = es::ClientBuilder::for_url(&sse_url)
-            .map_err(|e| MCPClientError::ConnectionError(format!("Invalid SSE URL: {:?}", e)))?;
+        let mut notification = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": method,
+        });
 
-        // 添加headers / Add headers
-        for (key, value) in &self.base.params.headers {
-            builder = builder.header(key, value).map_err(|e| {
-                MCPClientError::ConnectionError(format!("Failed to add header {}: {:?}", key, e))
-            })?;
+        if let Some(p) = params {
+            notification["params"] = p;
         }
 
-        let es_client = builder.build();
-
-        // 创建通信通道 / Create communication channels
-        let (request_tx, request_rx) = mpsc::unbounded_channel::<serde_json::Value>();
-        let (response_tx, response_rx) = mpsc::unbounded_channel::<serde_json::Value>();
-
-        *self.request_tx.lock().await = Some(request_tx);
-        *self.response_rx.lock().await = Some(response_rx);
-
-        // 克隆资源缓存和更新通知发送器，用于 SSE 事件处理
-        let resource_cache = self.resource_cache.clone();
-        let update_tx = self.update_tx.clone();
-
-        // 启动SSE事件处理任务 / Start SSE event handling task
-        let stream: Pin<Box<dyn Stream<Item = Result<es::SSE, es::Error>> + Send + Sync>> =
-            es_client.stream();
-
-        tokio::spawn(async move {
-            let mut stream = Box::pin(stream);
-            let mut request_rx = Box::pin(request_rx);
-
-            loop {
-                tokio::select! {
-                    // 处理SSE事件 / Handle SSE events
-                    Some(event_result) = stream.next() => {
-                        match event_result {
-                            Ok(event) => {
-                                debug!("Received SSE event: {:?}", event);
-
-                                // 尝试解析JSON-RPC响应 / Try to parse JSON-RPC response
-                                // Pattern match on SSE enum variants
-                                match event {
-                                    es::SSE::Event(event_data) => {
-                                        if let Ok(value) = serde_json::from_str::<serde_json::Value>(&event_data.data) {
-                                            // 区分消息类型 / Distinguish message types
-
-                                            // 检查是否是资源更新通知
-                                            // 资源更新通知通常包含 "method" = "resources/update"
-                                            if let Some(method) = value.get("method").and_then(|m| m.as_str()) {
-                                                if method == "resources/update" || method.contains("update") {
-                                                    debug!("Received resource update notification");
-
-                                                    // 提取 URI 和数据
-                                                    if let Some(params) = value.get("params") {
-                                                        if let Some(uri) = params.get("uri").and_then(|u| u.as_str()) {
-                                                            // 刷新缓存
-                                                            if let Some(data) = params.get("data") {
-                                                                let _ = resource_cache.refresh(uri, data.clone()).await;
-
-                                                                // 发送更新通知
-                                                                if let Some(tx) = update_tx.lock().await.as_ref() {
-                                                                    let _ = tx.send(ResourceUpdate {
-                                                                        uri: uri.to_string(),
-                                                                        data: data.clone(),
-                                                                        version: 1, // TODO: 从缓存获取版本号
-                                                                    });
-                                                                }
-                                                            }
-                                                        }
-                                                    }
-                                                } else {
-                                                    // 其他通知，也发送到 response channel
-                                                    let _ = response_tx.send(value);
-                                                }
-                                            } else {
-                                                // JSON-RPC 响应
-                                                let _ = response_tx.send(value);
-                                            }
-                                        }
-                                    }
-                                    es::SSE::Comment(_) => {
-                                        debug!("Received SSE comment");
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                error!("SSE event error: {:?}", e);
-                                break;
-                            }
-                        }
-                    }
+        Self::post_json(
+            &self.http_client,
+            &endpoint,
+            &self.base.params.headers,
+            &notification,
+        )
+        .await
+    }
 
-                    // 处理请求发送 / Handle request sending
-                    Some(request) = request_rx.recv() => {
-                        debug!("Sending request via SSE: {}", request);
-                        // 在实际实现中，这里需要通过HTTP POST发送请求
-                        // In actual implementation, this needs to send request via HTTP POST
-                        // 这里简化处理，实际需要根据SSE协议实现
-                        // This is simplified, actual implementation needed according to SSE protocol
-                    }
+    /// 请求被丢弃时通知服务器取消 / Tell the server to cancel a request when it is dropped
+    fn cancel_on_drop(&self, request_id: u64) -> CancelOnDrop {
+        let http_client = self.http_client.clone();
+        let endpoint = self.endpoint.clone();
+        let headers = self.base.params.headers.clone();
+        CancelOnDrop::new(move || {
+            let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+                return;
+            };
+            let Some(url) = endpoint.borrow().clone() else {
+                return;
+            };
+            runtime.spawn(async move {
+                let notification = cancelled_notification(request_id, "Tool call cancelled");
+                if let Err(e) = Self::post_json(&http_client, &url, &headers, &notification).await {
+                    warn!(
+                        "Failed to send cancellation for request {}: {}",
+                        request_id, e
+                    );
                 }
-            }
-        });
+            });
+        })
+    }
 
-        // Note: es_client is not stored since it's not object-safe
-        // The stream is managed within the task above
+    /// 启动SSE连接并等待 `endpoint` 事件 / Start the SSE connection and wait for the `endpoint` event
+    async fn start_sse_connection(&self) -> Result<(), MCPClientError> {
+        let context = StreamContext {
+            http_client: self.http_client.clone(),
+            url: self.base.params.url.clone(),
+            headers: self.base.params.headers.clone(),
+            endpoint: self.endpoint.clone(),
+            pending: self.pending.clone(),
+            resource_cache: self.resource_cache.clone(),
+            update_tx: self.update_tx.clone(),
+        };
 
+        let response = context.open(None).await?;
+        self.endpoint.send_replace(None);
+        let handle = tokio::spawn(context.run(response));
+        if let Some(previous) = self.reader.lock().unwrap().replace(handle) {
+            previous.abort();
+        }
+
+        if let Err(e) = self.endpoint_url().await {
+            self.stop_sse_connection();
+            return Err(e);
+        }
         Ok(())
     }
 
+    /// 关闭SSE连接 / Close the SSE connection
+    fn stop_sse_connection(&self) {
+        if let Some(handle) = self.reader.lock().unwrap().take() {
+            handle.abort();
+        }
+        self.endpoint.send_replace(None);
+        self.pending.lock().unwrap().clear();
+    }
+
     /// 初始化会话 / Initialize session
     async fn initialize_session(&self) -> Result<(), MCPClientError> {
         let params = serde_json::json!({
@@ -288,10 +607,12 @@ impl SseMCPClient {
             if let Some(session_id) = result.get("sessionId").and_then(|v| v.as_str()) {
                 *self.session_id.lock().await = Some(session_id.to_string());
             }
+            *self.server_info.lock().await = Some(ServerInfo::from_initialize_result(result));
         }
 
         // 发送initialized通知 / Send initialized notification
-        self.send_request("notifications/initialized", None).await?;
+        self.send_notification("notifications/initialized", None)
+            .await?;
 
         info!("SSE session initialized successfully");
         Ok(())
@@ -359,6 +680,10 @@ impl MCPClientProtocol for SseMCPClient {
         self.base.state()
     }
 
+    fn subscribe_state(&self) -> Option<watch::Receiver<ClientState>> {
+        Some(self.base.get_state_notifier())
+    }
+
     async fn connect(&self) -> Result<(), MCPClientError> {
         // 检查是否可以连接 / Check if can connect
         if !self.base.can_connect().await {
@@ -372,7 +697,10 @@ impl MCPClientProtocol for SseMCPClient {
         self.start_sse_connection().await?;
 
         // 初始化会话 / Initialize session
-        self.initialize_session().await?;
+        if let Err(e) = self.initialize_session().await {
+            self.stop_sse_connection();
+            return Err(e);
+        }
 
         // 更新状态 / Update state
         self.base.update_state(ClientState::Connected).await;
@@ -396,15 +724,16 @@ impl MCPClientProtocol for SseMCPClient {
         }
 
         // 发送exit通知 / Send exit notification
-        if let Err(e) = self.send_request("exit", None).await {
+        if let Err(e) = self.send_notification("exit", None).await {
             warn!("Failed to send exit notification: {}", e);
         }
 
         // 关闭SSE连接 / Close SSE connection
-        *self.request_tx.lock().await = None;
+        self.stop_sse_connection();
 
         // 清理会话ID / Clear session ID
         *self.session_id.lock().await = None;
+        *self.server_info.lock().await = None;
 
         // 更新状态 / Update state
         self.base.update_state(ClientState::Disconnected).await;
@@ -456,7 +785,15 @@ impl MCPClientProtocol for SseMCPClient {
             "arguments": params
         });
 
-        let response = self.send_request("tools/call", Some(call_params)).await?;
+        // 调用被中止时向服务器发送 notifications/cancelled
+        // Send notifications/cancelled to the server if the call is aborted
+        let request_id = self.base.next_request_id();
+        let cancel_guard = self.cancel_on_drop(request_id);
+        let response = self
+            .send_request_with_id(request_id, "tools/call", Some(call_params))
+            .await;
+        cancel_guard.disarm();
+        let response = response?;
 
         if let Some(error) = response.get("error") {
             return Err(MCPClientError::ProtocolError(format!(
@@ -640,13 +977,165 @@ impl MCPClientProtocol for SseMCPClient {
 
         Ok(())
     }
+
+    async fn server_info(&self) -> Option<ServerInfo> {
+        self.server_info.lock().await.clone()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use http_body_util::combinators::BoxBody;
+    use http_body_util::{BodyExt, Full, StreamBody};
+    use hyper::body::{Bytes, Frame, Incoming};
+    use hyper::server::conn::http1;
+    use hyper::service::service_fn;
+    use hyper::{Method, Request, Response, StatusCode};
+    use hyper_util::rt::TokioIo;
     use serde_json::json;
     use std::collections::HashMap;
+    use std::convert::Infallible;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use tokio::net::TcpListener;
+
+    /// 模拟 MCP SSE 服务器 / Mock MCP SSE server
+    struct MockSseServer {
+        url: String,
+        state: Arc<MockState>,
+        handle: JoinHandle<()>,
+    }
+
+    #[derive(Default)]
+    struct MockState {
+        /// 当前事件流 / Current event stream
+        stream: std::sync::Mutex<Option<mpsc::UnboundedSender<Bytes>>>,
+        /// 最后一个事件ID / Last event id
+        last_event_id: AtomicU64,
+        /// 每次 GET 的查询串与 Last-Event-ID / Query and Last-Event-ID of every GET
+        opened: std::sync::Mutex<Vec<(Option<String>, Option<String>)>>,
+    }
+
+    impl MockState {
+        fn push_event(&self, event: &str, data: &str) {
+            let id = self.last_event_id.fetch_add(1, Ordering::SeqCst) + 1;
+            let frame = format!(
+                "id: {}\nevent: {}\ndata: {}\nretry: 50\n\n",
+                id, event, data
+            );
+            if let Some(tx) = self.stream.lock().unwrap().as_ref() {
+                let _ = tx.send(Bytes::from(frame));
+            }
+        }
+    }
+
+    impl MockSseServer {
+        async fn start() -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("http://{}", listener.local_addr().unwrap());
+            let state = Arc::new(MockState::default());
+            let server_state = state.clone();
+            let handle = tokio::spawn(async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    let state = server_state.clone();
+                    tokio::spawn(async move {
+                        let service =
+                            service_fn(move |req| handle_mock_request(state.clone(), req));
+                        let _ = http1::Builder::new()
+                            .serve_connection(TokioIo::new(stream), service)
+                            .await;
+                    });
+                }
+            });
+            Self { url, state, handle }
+        }
+
+        fn client(&self) -> SseMCPClient {
+            SseMCPClient::new(SseServerParameters {
+                url: format!("{}/sse", self.url),
+                headers: HashMap::new(),
+            })
+        }
+    }
+
+    impl Drop for MockSseServer {
+        fn drop(&mut self) {
+            self.handle.abort();
+        }
+    }
+
+    fn mock_result(message: &serde_json::Value) -> serde_json::Value {
+        match message["method"].as_str().unwrap_or_default() {
+            "initialize" => json!({
+                "protocolVersion": "2024-11-05",
+                "capabilities": {"tools": {}},
+                "serverInfo": {"name": "mock-sse", "version": "0.1.0"}
+            }),
+            "tools/list" => json!({
+                "tools": [{
+                    "name": "echo",
+                    "description": "Echo the input text",
+                    "inputSchema": {"type": "object", "properties": {"text": {"type": "string"}}}
+                }]
+            }),
+            "tools/call" => json!({
+                "content": [{"type": "text", "text": message["params"]["arguments"]["text"]}],
+                "isError": false
+            }),
+            _ => json!({}),
+        }
+    }
+
+    async fn handle_mock_request(
+        state: Arc<MockState>,
+        req: Request<Incoming>,
+    ) -> Result<Response<BoxBody<Bytes, Infallible>>, Infallible> {
+        match (req.method().clone(), req.uri().path()) {
+            (Method::GET, "/sse") => {
+                let last_event_id = req
+                    .headers()
+                    .get("last-event-id")
+                    .and_then(|v| v.to_str().ok())
+                    .map(str::to_string);
+                let query = req.uri().query().map(str::to_string);
+                state.opened.lock().unwrap().push((query, last_event_id));
+
+                let (tx, rx) = mpsc::unbounded_channel::<Bytes>();
+                *state.stream.lock().unwrap() = Some(tx);
+                state.push_event("endpoint", "/messages?session_id=mock");
+
+                let body = StreamBody::new(futures::stream::unfold(rx, |mut rx| async move {
+                    rx.recv()
+                        .await
+                        .map(|chunk| (Ok::<_, Infallible>(Frame::data(chunk)), rx))
+                }));
+                Ok(Response::builder()
+                    .header("Content-Type", "text/event-stream")
+                    .body(BodyExt::boxed(body))
+                    .unwrap())
+            }
+            (Method::POST, "/messages") => {
+                let body = req.into_body().collect().await.unwrap().to_bytes();
+                let message: serde_json::Value = serde_json::from_slice(&body).unwrap();
+                if let Some(id) = message.get("id") {
+                    // 响应前插入一条通知，验证响应按 id 匹配 / Interleave a notification to check id matching
+                    let notification = json!({"jsonrpc": "2.0", "method": "notifications/message"});
+                    state.push_event("message", &notification.to_string());
+                    let response =
+                        json!({"jsonrpc": "2.0", "id": id, "result": mock_result(&message)});
+                    state.push_event("message", &response.to_string());
+                }
+                Ok(Response::builder()
+                    .status(StatusCode::ACCEPTED)
+                    .body(Full::new(Bytes::new()).boxed())
+                    .unwrap())
+            }
+            _ => Ok(Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .body(Full::new(Bytes::new()).boxed())
+                .unwrap()),
+        }
+    }
 
     #[tokio::test]
     async fn test_sse_client_creation() {
@@ -836,46 +1325,41 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_start_sse_connection_url_formatting() {
+    async fn test_start_sse_connection_unreachable_server() {
         let params = SseServerParameters {
-            url: "http://localhost:8081".to_string(),
+            url: "http://127.0.0.1:1/sse".to_string(),
             headers: HashMap::new(),
         };
 
         let client = SseMCPClient::new(params);
 
-        // start_sse_connection 会创建通道并返回 Ok，即使没有实际服务器
-        // 它只是启动了任务，实际的连接错误会在后续操作中体现
+        // 服务器不可达时应立即返回连接错误，且不留下读取任务
         let result = client.start_sse_connection().await;
-        assert!(result.is_ok());
-
-        // 验证通道已创建
-        let request_tx = client.request_tx.lock().await;
-        assert!(request_tx.is_some());
-
-        let response_rx = client.response_rx.lock().await;
-        assert!(response_rx.is_some());
+        assert!(matches!(result, Err(MCPClientError::ConnectionError(_))));
+        assert!(!client.stream_running());
     }
 
     #[tokio::test]
-    async fn test_start_sse_connection_url_formatting_with_query() {
+    async fn test_start_sse_connection_preserves_query() {
+        let server = MockSseServer::start().await;
         let params = SseServerParameters {
-            url: "http://localhost:8081?param=value".to_string(),
+            url: format!("{}/sse?token=abc", server.url),
             headers: HashMap::new(),
         };
 
         let client = SseMCPClient::new(params);
 
-        // 测试带查询参数的URL格式化
-        let result = client.start_sse_connection().await;
-        assert!(result.is_ok());
-
-        // 验证通道已创建
-        let request_tx = client.request_tx.lock().await;
-        assert!(request_tx.is_some());
-
-        let response_rx = client.response_rx.lock().await;
-        assert!(response_rx.is_some());
+        // 原样使用配置的 URL，并把相对 endpoint 解析为绝对地址
+        client.start_sse_connection().await.unwrap();
+        assert_eq!(
+            server.state.opened.lock().unwrap()[0].0.as_deref(),
+            Some("token=abc")
+        );
+        assert_eq!(
+            client.endpoint.borrow().as_deref(),
+            Some(format!("{}/messages?session_id=mock", server.url).as_str())
+        );
+        client.stop_sse_connection();
     }
 
     #[tokio::test]
@@ -905,7 +1389,7 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_request_response_channels() {
+    async fn test_endpoint_initially_unset() {
         let params = SseServerParameters {
             url: "http://localhost:8081".to_string(),
             headers: HashMap::new(),
@@ -913,13 +1397,9 @@ mod tests {
 
         let client = SseMCPClient::new(params);
 
-        // 初始状态下通道应该为空
-        let request_tx = client.request_tx.lock().await;
-        assert!(request_tx.is_none());
-        drop(request_tx);
-
-        let response_rx = client.response_rx.lock().await;
-        assert!(response_rx.is_none());
+        // 初始状态下没有消息地址，也没有读取任务
+        assert!(client.endpoint.borrow().is_none());
+        assert!(!client.stream_running());
     }
 
     #[tokio::test]
@@ -985,4 +1465,122 @@ mod tests {
         let debug_str = format!("{:?}", client);
         assert!(debug_str.contains("SseMCPClient"));
     }
+
+    #[test]
+    fn test_sse_parser_handles_split_chunks() {
+        let mut parser = SseParser::default();
+
+        // 事件可能被拆分到多个数据块中 / Events may be split across chunks
+        assert!(parser.feed(b"id: 7\r\nevent: endp").is_empty());
+        let events = parser.feed(b"oint\r\ndata: /messages\r\n\r\ndata: {}\n\n");
+        assert_eq!(
+            events,
+            vec![
+                SseEvent {
+                    event: Some("endpoint".to_string()),
+                    data: "/messages".to_string(),
+                    id: Some("7".to_string()),
+                    retry: None,
+                },
+                SseEvent {
+                    data: "{}".to_string(),
+                    ..Default::default()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sse_parser_multiline_data_and_comments() {
+        let mut parser = SseParser::default();
+
+        let events = parser.feed(b": keep-alive\n\ndata: line1\ndata:line2\nretry: 250\n\n");
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].data, "line1\nline2");
+        assert_eq!(events[0].retry, Some(250));
+    }
+
+    #[tokio::test]
+    async fn test_list_tools_over_sse() {
+        let server = MockSseServer::start().await;
+        let client = server.client();
+
+        client.connect().await.unwrap();
+        assert_eq!(client.state(), ClientState::Connected);
+        assert_eq!(
+            client.server_info().await.and_then(|info| info.name),
+            Some("mock-sse".to_string())
+        );
+
+        let tools = client.list_tools().await.unwrap();
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0].name, "echo");
+
+        client.disconnect().await.unwrap();
+        assert!(!client.stream_running());
+    }
+
+    #[tokio::test]
+    async fn test_call_tool_round_trip() {
+        let server = MockSseServer::start().await;
+        let client = server.client();
+        client.connect().await.unwrap();
+
+        // 并发调用的响应按 id 回到各自的调用方 / Concurrent calls each get their own response by id
+        let (first, second) = tokio::join!(
+            client.call_tool("echo", json!({"text": "first"})),
+            client.call_tool("echo", json!({"text": "second"})),
+        );
+        assert_eq!(
+            first.unwrap().content,
+            vec![Content::Text {
+                text: "first".to_string()
+            }]
+        );
+        assert_eq!(
+            second.unwrap().content,
+            vec![Content::Text {
+                text: "second".to_string()
+            }]
+        );
+        assert!(client.pending.lock().unwrap().is_empty());
+
+        client.disconnect().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_reconnects_with_last_event_id() {
+        let server = MockSseServer::start().await;
+        let client = server.client();
+        client.connect().await.unwrap();
+        client.list_tools().await.unwrap();
+
+        // 服务器关闭事件流 / The server drops the event stream
+        let last_event_id = server.state.last_event_id.load(Ordering::SeqCst);
+        server.state.stream.lock().unwrap().take();
+        let mut endpoint = client.endpoint.subscribe();
+        tokio::time::timeout(Duration::from_secs(5), endpoint.wait_for(Option::is_none))
+            .await
+            .unwrap()
+            .unwrap();
+
+        // 重连后请求照常完成 / Requests complete as usual after reconnecting
+        let result = client
+            .call_tool("echo", json!({"text": "after reconnect"}))
+            .await
+            .unwrap();
+        assert_eq!(
+            result.content,
+            vec![Content::Text {
+                text: "after reconnect".to_string()
+            }]
+        );
+
+        let opened = server.state.opened.lock().unwrap().clone();
+        assert_eq!(opened.len(), 2);
+        assert_eq!(opened[0].1, None);
+        assert_eq!(opened[1].1, Some(last_event_id.to_string()));
+
+        client.disconnect().await.unwrap();
+    }
 }
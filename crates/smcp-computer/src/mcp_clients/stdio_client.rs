@@ -7,20 +7,47 @@
 * 依赖: tokio, serde_json
 * 描述: STDIO类型的MCP客户端实现
 */
-use super::base_client::BaseMCPClient;
+use super::base_client::{cancelled_notification, BaseMCPClient, CancelOnDrop};
 use super::model::*;
 use super::{ResourceCache, SubscriptionManager};
 use crate::desktop::window_uri::{is_window_uri, WindowURI};
 use async_trait::async_trait;
 use serde_json;
+use std::collections::{HashMap, VecDeque};
 use std::process::Stdio;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::io::{AsyncBufReadExt, BufReader};
-use tokio::process::{Child, Command};
-use tokio::sync::Mutex;
+use tokio::process::{Child, ChildStderr, ChildStdout, Command};
+use tokio::sync::{broadcast, oneshot, Mutex};
+use tokio::task::JoinHandle;
 use tracing::{debug, error, info, warn};
 
+/// 单个请求等待响应的超时 / Timeout for a single request awaiting its response
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 服务器消息通道容量 / Capacity of the server message channel
+const NOTIFICATION_CAPACITY: usize = 64;
+
+/// 保留的 stderr 行数 / Number of stderr lines kept
+const STDERR_BUFFER_LINES: usize = 50;
+
+/// 等待响应的请求：请求ID -> 响应发送端 / Requests awaiting a response: request id -> response sender
+type PendingRequests = Arc<std::sync::Mutex<HashMap<u64, oneshot::Sender<serde_json::Value>>>>;
+
+/// 请求结束（完成、超时或被中止）时移除等待登记
+/// Removes the pending entry when a request ends (completed, timed out or aborted)
+struct PendingGuard<'a> {
+    pending: &'a PendingRequests,
+    id: u64,
+}
+
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        self.pending.lock().unwrap().remove(&self.id);
+    }
+}
+
 /// STDIO MCP客户端 / STDIO MCP client
 pub struct StdioMCPClient {
     /// 基础客户端 / Base client
@@ -29,10 +56,22 @@ pub struct StdioMCPClient {
     child_process: Arc<Mutex<Option<Child>>>,
     /// 会话ID / Session ID
     session_id: Arc<Mutex<Option<String>>>,
+    /// 服务器信息 / Server info
+    server_info: Arc<Mutex<Option<ServerInfo>>>,
     /// 订阅管理器 / Subscription manager
     subscription_manager: SubscriptionManager,
     /// 资源缓存 / Resource cache
     resource_cache: ResourceCache,
+    /// 等待响应的请求 / Requests awaiting a response
+    pending: PendingRequests,
+    /// 服务器主动发送的消息（通知与请求） / Messages initiated by the server (notifications and requests)
+    notifications: broadcast::Sender<serde_json::Value>,
+    /// 读取子进程输出并分发响应的任务 / Task reading the child's stdout and dispatching responses
+    reader: std::sync::Mutex<Option<JoinHandle<()>>>,
+    /// 日志中使用的服务器名称 / Server name used in logs
+    server_name: String,
+    /// 最近的 stderr 输出 / Most recent stderr output
+    stderr_lines: Arc<std::sync::Mutex<VecDeque<String>>>,
 }
 
 impl std::fmt::Debug for StdioMCPClient {
@@ -49,14 +88,38 @@ impl StdioMCPClient {
     /// 创建新的STDIO客户端 / Create new STDIO client
     pub fn new(params: StdioServerParameters) -> Self {
         Self {
-            base: BaseMCPClient::new(params),
             child_process: Arc::new(Mutex::new(None)),
             session_id: Arc::new(Mutex::new(None)),
+            server_info: Arc::new(Mutex::new(None)),
             subscription_manager: SubscriptionManager::new(),
             resource_cache: ResourceCache::new(Duration::from_secs(60)), // 默认 60 秒 TTL
+            pending: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            notifications: broadcast::channel(NOTIFICATION_CAPACITY).0,
+            reader: std::sync::Mutex::new(None),
+            server_name: params.command.clone(),
+            stderr_lines: Arc::new(std::sync::Mutex::new(VecDeque::new())),
+            base: BaseMCPClient::new(params),
         }
     }
 
+    /// 设置日志中使用的服务器名称，默认为启动命令 / Set the server name used in logs, defaults to the command
+    pub fn with_server_name(mut self, name: impl Into<String>) -> Self {
+        self.server_name = name.into();
+        self
+    }
+
+    /// 子进程最近输出的 stderr 行（最多保留 50 行），用于排查连接失败
+    /// Most recent stderr lines of the child (up to 50 kept), for debugging failed connects
+    pub fn last_stderr(&self) -> Vec<String> {
+        self.stderr_lines.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// 订阅服务器主动发送的消息（不带响应 ID 的通知及服务器请求）
+    /// Subscribe to messages initiated by the server (notifications and server requests)
+    pub fn notifications(&self) -> broadcast::Receiver<serde_json::Value> {
+        self.notifications.subscribe()
+    }
+
     /// 启动子进程 / Start child process
     async fn start_child_process(
         &self,
@@ -91,23 +154,114 @@ impl StdioMCPClient {
         Ok(child)
     }
 
-    /// 发送JSON-RPC请求 / Send JSON-RPC request
+    /// 启动读取任务：按响应的 `id` 分发给对应的等待者，服务器消息转发到通知通道
+    /// Start the reader task: dispatch responses to their waiters by `id` and forward server messages
+    fn spawn_reader(&self, stdout: ChildStdout) {
+        let pending = self.pending.clone();
+        let notifications = self.notifications.clone();
+        let handle = tokio::spawn(async move {
+            let mut lines = BufReader::new(stdout).lines();
+            loop {
+                match lines.next_line().await {
+                    Ok(Some(line)) => Self::dispatch_line(line.trim(), &pending, &notifications),
+                    Ok(None) => break,
+                    Err(e) => {
+                        warn!("Failed to read from MCP server stdout: {}", e);
+                        break;
+                    }
+                }
+            }
+            // 输出关闭后丢弃所有等待者，使其立即失败 / Drop every waiter once stdout closes so they fail fast
+            debug!("MCP server stdout closed");
+            pending.lock().unwrap().clear();
+        });
+        if let Some(previous) = self.reader.lock().unwrap().replace(handle) {
+            previous.abort();
+        }
+    }
+
+    /// 启动 stderr 读取任务：逐行记录到日志并保留最近的输出，流关闭时结束
+    /// Start the stderr reader: log each line and keep the most recent output, ending when the stream closes
+    fn spawn_stderr_reader(&self, stderr: ChildStderr) {
+        let server_name = self.server_name.clone();
+        let stderr_lines = self.stderr_lines.clone();
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(stderr).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                warn!(server = %server_name, "{}", line);
+                let mut buffer = stderr_lines.lock().unwrap();
+                if buffer.len() == STDERR_BUFFER_LINES {
+                    buffer.pop_front();
+                }
+                buffer.push_back(line);
+            }
+            debug!("MCP server {} stderr closed", server_name);
+        });
+    }
+
+    /// 分发一行服务器输出 / Dispatch one line of server output
+    fn dispatch_line(
+        line: &str,
+        pending: &PendingRequests,
+        notifications: &broadcast::Sender<serde_json::Value>,
+    ) {
+        if line.is_empty() {
+            return;
+        }
+        let message: serde_json::Value = match serde_json::from_str(line) {
+            Ok(message) => message,
+            Err(e) => {
+                warn!(
+                    "Ignoring non JSON-RPC output from MCP server: {} ({})",
+                    line, e
+                );
+                return;
+            }
+        };
+
+        if message.get("method").is_some() {
+            debug!("Received server message: {}", line);
+            let _ = notifications.send(message);
+            return;
+        }
+
+        let Some(id) = message.get("id").and_then(|id| id.as_u64()) else {
+            warn!("Ignoring response without id: {}", line);
+            return;
+        };
+        match pending.lock().unwrap().remove(&id) {
+            Some(waiter) => {
+                debug!("Received response: {}", line);
+                let _ = waiter.send(message);
+            }
+            // 已取消或已超时请求的迟到响应 / Late response to a cancelled or timed out request
+            None => debug!("Discarded response for unknown request {}", id),
+        }
+    }
+
     /// 发送通知（不需要响应） / Send notification (no response expected)
     async fn send_notification(
         &self,
         notification: &serde_json::Value,
     ) -> Result<(), MCPClientError> {
-        let mut child = self.child_process.lock().await;
+        Self::write_message(&self.child_process, notification).await
+    }
+
+    /// 向子进程写入一条消息 / Write one message to the child process
+    async fn write_message(
+        child_process: &Mutex<Option<Child>>,
+        message: &serde_json::Value,
+    ) -> Result<(), MCPClientError> {
+        let mut child = child_process.lock().await;
         if let Some(ref mut process) = *child {
             if let Some(stdin) = process.stdin.as_mut() {
-                let notification_str = serde_json::to_string(notification)?;
+                let message_str = serde_json::to_string(message)?;
                 use tokio::io::AsyncWriteExt;
-                stdin.write_all(notification_str.as_bytes()).await?;
+                stdin.write_all(message_str.as_bytes()).await?;
                 stdin.write_all(b"\n").await?;
                 stdin.flush().await?;
 
-                debug!("Sent notification: {}", notification_str);
-                info!("Sent notification to MCP server: {}", notification_str);
+                debug!("Sent message to MCP server: {}", message_str);
                 return Ok(());
             }
         }
@@ -116,75 +270,121 @@ impl StdioMCPClient {
         ))
     }
 
-    async fn send_request(
+    /// 请求被丢弃时通知服务器取消 / Tell the server to cancel a request when it is dropped
+    fn cancel_on_drop(&self, request_id: u64) -> CancelOnDrop {
+        let child_process = self.child_process.clone();
+        CancelOnDrop::new(move || {
+            let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+                return;
+            };
+            runtime.spawn(async move {
+                let notification = cancelled_notification(request_id, "Tool call cancelled");
+                if let Err(e) = Self::write_message(&child_process, &notification).await {
+                    warn!(
+                        "Failed to send cancellation for request {}: {}",
+                        request_id, e
+                    );
+                }
+            });
+        })
+    }
+
+    /// 发送 `tools/call` 请求，携带令牌时请求服务器上报进度
+    /// Send a `tools/call` request, asking the server to report progress when a token is given
+    async fn call_tool_request(
         &self,
-        request: &serde_json::Value,
-    ) -> Result<serde_json::Value, MCPClientError> {
-        let mut child = self.child_process.lock().await;
-        if let Some(ref mut process) = *child {
-            if let Some(stdin) = process.stdin.as_mut() {
-                let request_str = serde_json::to_string(request)?;
-                use tokio::io::AsyncWriteExt;
-                stdin.write_all(request_str.as_bytes()).await?;
-                stdin.write_all(b"\n").await?;
-                stdin.flush().await?;
+        tool_name: &str,
+        params: serde_json::Value,
+        progress_token: Option<&str>,
+    ) -> Result<CallToolResult, MCPClientError> {
+        if self.base.get_state().await != ClientState::Connected {
+            return Err(MCPClientError::ConnectionError("Not connected".to_string()));
+        }
 
-                debug!("Sent request: {}", request_str);
-                info!("Sent request to MCP server: {}", request_str);
+        let request_id = self.base.next_request_id();
+        let mut request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": request_id,
+            "method": "tools/call",
+            "params": {
+                "name": tool_name,
+                "arguments": params
+            }
+        });
+        if let Some(token) = progress_token {
+            request["params"]["_meta"] = serde_json::json!({ "progressToken": token });
+        }
 
-                // 读取响应 / Read response
-                if let Some(stdout) = process.stdout.as_mut() {
-                    let mut reader = BufReader::new(stdout);
-                    let mut line = String::new();
+        // 调用被中止时向服务器发送 notifications/cancelled
+        // Send notifications/cancelled to the server if the call is aborted
+        let cancel_guard = self.cancel_on_drop(request_id);
+        let response = self.send_request(&request).await;
+        cancel_guard.disarm();
+        let response = response?;
 
-                    info!("Waiting for response from MCP server...");
+        if let Some(error) = response.get("error") {
+            return Err(MCPClientError::ProtocolError(format!(
+                "Call tool error: {}",
+                error
+            )));
+        }
 
-                    // 添加超时以防止无限阻塞
-                    return match tokio::time::timeout(
-                        std::time::Duration::from_secs(30),
-                        reader.read_line(&mut line),
-                    )
-                    .await
-                    {
-                        Ok(Ok(0)) => {
-                            error!("Process closed stdout without response");
-                            Err(MCPClientError::ConnectionError(
-                                "Process closed stdout".to_string(),
-                            ))
-                        }
-                        Ok(Ok(_)) => {
-                            info!("Received raw response: {}", line.trim());
-                            debug!("Received response: {}", line.trim());
-                            let response: serde_json::Value = serde_json::from_str(line.trim())
-                                .map_err(|e| {
-                                    error!("Failed to parse JSON response: {}", e);
-                                    MCPClientError::ProtocolError(format!("Invalid JSON: {}", e))
-                                })?;
-                            info!("Parsed JSON response: {}", response);
-                            Ok(response)
-                        }
-                        Ok(Err(e)) => Err(MCPClientError::ConnectionError(format!(
-                            "Failed to read response: {}",
-                            e
-                        ))),
-                        Err(_) => Err(MCPClientError::TimeoutError(
-                            "No response received within timeout".to_string(),
-                        )),
-                    };
-                }
-            }
+        if let Some(result) = response.get("result") {
+            let call_result: CallToolResult = serde_json::from_value(result.clone())?;
+            return Ok(call_result);
         }
 
-        Err(MCPClientError::ConnectionError(
-            "Process not running".to_string(),
+        Err(MCPClientError::ProtocolError(
+            "Invalid response".to_string(),
         ))
     }
 
+    /// 发送JSON-RPC请求并等待同 `id` 的响应，多个请求可并发进行
+    /// Send a JSON-RPC request and await the response with the same `id`; requests may run concurrently
+    async fn send_request(
+        &self,
+        request: &serde_json::Value,
+    ) -> Result<serde_json::Value, MCPClientError> {
+        if self.child_process.lock().await.is_none() {
+            return Err(MCPClientError::ConnectionError(
+                "Process not running".to_string(),
+            ));
+        }
+        let id = request
+            .get("id")
+            .and_then(|id| id.as_u64())
+            .ok_or_else(|| MCPClientError::ProtocolError("Request without id".to_string()))?;
+
+        // 先登记再发送，避免响应早于登记到达 / Register before sending so the response cannot arrive first
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id, tx);
+        let _guard = PendingGuard {
+            pending: &self.pending,
+            id,
+        };
+
+        Self::write_message(&self.child_process, request).await?;
+
+        match tokio::time::timeout(REQUEST_TIMEOUT, rx).await {
+            Ok(Ok(response)) => Ok(response),
+            Ok(Err(_)) => {
+                error!("Process closed stdout without response to request {}", id);
+                Err(MCPClientError::ConnectionError(
+                    "Process closed stdout".to_string(),
+                ))
+            }
+            Err(_) => Err(MCPClientError::TimeoutError(format!(
+                "No response received within timeout for request {}",
+                id
+            ))),
+        }
+    }
+
     /// 初始化会话 / Initialize session
     async fn initialize_session(&self) -> Result<(), MCPClientError> {
         let init_request = serde_json::json!({
             "jsonrpc": "2.0",
-            "id": 1,
+            "id": self.base.next_request_id(),
             "method": "initialize",
             "params": {
                 "protocolVersion": "2024-11-05",
@@ -213,6 +413,7 @@ impl StdioMCPClient {
             if let Some(session_id) = result.get("sessionId").and_then(|v| v.as_str()) {
                 *self.session_id.lock().await = Some(session_id.to_string());
             }
+            *self.server_info.lock().await = Some(ServerInfo::from_initialize_result(result));
         }
 
         // 发送initialized通知 / Send initialized notification
@@ -284,6 +485,14 @@ impl MCPClientProtocol for StdioMCPClient {
         self.base.state()
     }
 
+    fn subscribe_state(&self) -> Option<tokio::sync::watch::Receiver<ClientState>> {
+        Some(self.base.get_state_notifier())
+    }
+
+    fn subscribe_notifications(&self) -> Option<broadcast::Receiver<serde_json::Value>> {
+        Some(self.notifications())
+    }
+
     async fn connect(&self) -> Result<(), MCPClientError> {
         // 检查是否可以连接 / Check if can connect
         if !self.base.can_connect().await {
@@ -297,7 +506,13 @@ impl MCPClientProtocol for StdioMCPClient {
         let params = self.base.params.clone();
 
         // 启动子进程 / Start child process
-        let child = self.start_child_process(&params).await?;
+        let mut child = self.start_child_process(&params).await?;
+        if let Some(stdout) = child.stdout.take() {
+            self.spawn_reader(stdout);
+        }
+        if let Some(stderr) = child.stderr.take() {
+            self.spawn_stderr_reader(stderr);
+        }
         *self.child_process.lock().await = Some(child);
 
         // 初始化会话 / Initialize session
@@ -325,7 +540,7 @@ impl MCPClientProtocol for StdioMCPClient {
             // 尝试优雅关闭 / Try graceful shutdown
             let shutdown_request = serde_json::json!({
                 "jsonrpc": "2.0",
-                "id": 2,
+                "id": self.base.next_request_id(),
                 "method": "shutdown"
             });
 
@@ -381,8 +596,15 @@ impl MCPClientProtocol for StdioMCPClient {
             drop(child);
         }
 
+        // 停止读取任务并让未完成的请求失败 / Stop the reader and fail outstanding requests
+        if let Some(reader) = self.reader.lock().unwrap().take() {
+            reader.abort();
+        }
+        self.pending.lock().unwrap().clear();
+
         // 清理会话ID / Clear session ID
         *self.session_id.lock().await = None;
+        *self.server_info.lock().await = None;
 
         // 更新状态 / Update state
         self.base.update_state(ClientState::Disconnected).await;
@@ -398,7 +620,7 @@ impl MCPClientProtocol for StdioMCPClient {
 
         let request = serde_json::json!({
             "jsonrpc": "2.0",
-            "id": 3,
+            "id": self.base.next_request_id(),
             "method": "tools/list"
         });
 
@@ -441,37 +663,17 @@ impl MCPClientProtocol for StdioMCPClient {
         tool_name: &str,
         params: serde_json::Value,
     ) -> Result<CallToolResult, MCPClientError> {
-        if self.base.get_state().await != ClientState::Connected {
-            return Err(MCPClientError::ConnectionError("Not connected".to_string()));
-        }
-
-        let request = serde_json::json!({
-            "jsonrpc": "2.0",
-            "id": 4,
-            "method": "tools/call",
-            "params": {
-                "name": tool_name,
-                "arguments": params
-            }
-        });
-
-        let response = self.send_request(&request).await?;
-
-        if let Some(error) = response.get("error") {
-            return Err(MCPClientError::ProtocolError(format!(
-                "Call tool error: {}",
-                error
-            )));
-        }
-
-        if let Some(result) = response.get("result") {
-            let call_result: CallToolResult = serde_json::from_value(result.clone())?;
-            return Ok(call_result);
-        }
+        self.call_tool_request(tool_name, params, None).await
+    }
 
-        Err(MCPClientError::ProtocolError(
-            "Invalid response".to_string(),
-        ))
+    async fn call_tool_with_progress(
+        &self,
+        tool_name: &str,
+        params: serde_json::Value,
+        progress_token: &str,
+    ) -> Result<CallToolResult, MCPClientError> {
+        self.call_tool_request(tool_name, params, Some(progress_token))
+            .await
     }
 
     async fn list_windows(&self) -> Result<Vec<Resource>, MCPClientError> {
@@ -486,7 +688,7 @@ impl MCPClientProtocol for StdioMCPClient {
         loop {
             let mut request = serde_json::json!({
                 "jsonrpc": "2.0",
-                "id": 5,
+                "id": self.base.next_request_id(),
                 "method": "resources/list"
             });
 
@@ -565,7 +767,7 @@ impl MCPClientProtocol for StdioMCPClient {
 
         let request = serde_json::json!({
             "jsonrpc": "2.0",
-            "id": 6,
+            "id": self.base.next_request_id(),
             "method": "resources/read",
             "params": {
                 "uri": resource.uri
@@ -598,7 +800,7 @@ impl MCPClientProtocol for StdioMCPClient {
 
         let request = serde_json::json!({
             "jsonrpc": "2.0",
-            "id": 7,
+            "id": self.base.next_request_id(),
             "method": "resources/subscribe",
             "params": {
                 "uri": resource.uri
@@ -649,7 +851,7 @@ impl MCPClientProtocol for StdioMCPClient {
 
         let request = serde_json::json!({
             "jsonrpc": "2.0",
-            "id": 8,
+            "id": self.base.next_request_id(),
             "method": "resources/unsubscribe",
             "params": {
                 "uri": resource.uri
@@ -677,11 +879,166 @@ impl MCPClientProtocol for StdioMCPClient {
 
         Ok(())
     }
+
+    async fn list_prompts(&self) -> Result<Vec<Prompt>, MCPClientError> {
+        if self.base.get_state().await != ClientState::Connected {
+            return Err(MCPClientError::ConnectionError("Not connected".to_string()));
+        }
+
+        // 支持分页获取提示词 / Support pagination for prompts
+        let mut all_prompts = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        loop {
+            let mut request = serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": self.base.next_request_id(),
+                "method": "prompts/list"
+            });
+
+            if let Some(ref c) = cursor {
+                request["params"] = serde_json::json!({ "cursor": c });
+            }
+
+            let response = self.send_request(&request).await?;
+
+            if let Some(error) = response.get("error") {
+                return Err(MCPClientError::ProtocolError(format!(
+                    "List prompts error: {}",
+                    error
+                )));
+            }
+
+            let Some(result) = response.get("result") else {
+                break;
+            };
+
+            if let Some(prompts) = result.get("prompts").and_then(|v| v.as_array()) {
+                for prompt in prompts {
+                    match serde_json::from_value::<Prompt>(prompt.clone()) {
+                        Ok(parsed) => all_prompts.push(parsed),
+                        Err(_) => warn!("Failed to parse prompt: {}", prompt),
+                    }
+                }
+            }
+
+            cursor = result
+                .get("nextCursor")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string());
+
+            if cursor.is_none() {
+                break;
+            }
+        }
+
+        Ok(all_prompts)
+    }
+
+    async fn list_resource_templates(&self) -> Result<Vec<ResourceTemplate>, MCPClientError> {
+        if self.base.get_state().await != ClientState::Connected {
+            return Err(MCPClientError::ConnectionError("Not connected".to_string()));
+        }
+
+        // 支持分页获取资源模板 / Support pagination for resource templates
+        let mut all_templates = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        loop {
+            let mut request = serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": self.base.next_request_id(),
+                "method": "resources/templates/list"
+            });
+
+            if let Some(ref c) = cursor {
+                request["params"] = serde_json::json!({ "cursor": c });
+            }
+
+            let response = self.send_request(&request).await?;
+
+            if let Some(error) = response.get("error") {
+                return Err(MCPClientError::ProtocolError(format!(
+                    "List resource templates error: {}",
+                    error
+                )));
+            }
+
+            let Some(result) = response.get("result") else {
+                break;
+            };
+
+            if let Some(templates) = result.get("resourceTemplates").and_then(|v| v.as_array()) {
+                for template in templates {
+                    match serde_json::from_value::<ResourceTemplate>(template.clone()) {
+                        Ok(parsed) => all_templates.push(parsed),
+                        Err(_) => warn!("Failed to parse resource template: {}", template),
+                    }
+                }
+            }
+
+            cursor = result
+                .get("nextCursor")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string());
+
+            if cursor.is_none() {
+                break;
+            }
+        }
+
+        Ok(all_templates)
+    }
+
+    async fn get_prompt(
+        &self,
+        name: &str,
+        arguments: Option<std::collections::HashMap<String, String>>,
+    ) -> Result<GetPromptResult, MCPClientError> {
+        if self.base.get_state().await != ClientState::Connected {
+            return Err(MCPClientError::ConnectionError("Not connected".to_string()));
+        }
+
+        let mut params = serde_json::json!({ "name": name });
+        if let Some(arguments) = arguments {
+            params["arguments"] = serde_json::to_value(arguments)?;
+        }
+
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": self.base.next_request_id(),
+            "method": "prompts/get",
+            "params": params
+        });
+
+        let response = self.send_request(&request).await?;
+
+        if let Some(error) = response.get("error") {
+            return Err(MCPClientError::ProtocolError(format!(
+                "Get prompt error: {}",
+                error
+            )));
+        }
+
+        if let Some(result) = response.get("result") {
+            let prompt_result: GetPromptResult = serde_json::from_value(result.clone())?;
+            return Ok(prompt_result);
+        }
+
+        Err(MCPClientError::ProtocolError(
+            "Invalid response".to_string(),
+        ))
+    }
+
+    async fn server_info(&self) -> Option<ServerInfo> {
+        self.server_info.lock().await.clone()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mcp_clients::testing::sh_reply;
     use serde_json::json;
     use std::collections::HashMap;
     use tokio::time::{sleep, Duration};
@@ -768,6 +1125,34 @@ mod tests {
         let _ = child.kill().await;
     }
 
+    #[tokio::test]
+    async fn test_stderr_captured_in_bounded_buffer() {
+        let params = StdioServerParameters {
+            command: "sh".to_string(),
+            args: vec![
+                "-c".to_string(),
+                r#"i=0; while [ $i -lt 60 ]; do echo "line $i" >&2; i=$((i+1)); done"#.to_string(),
+            ],
+            env: HashMap::new(),
+            cwd: None,
+        };
+
+        let client = StdioMCPClient::new(params).with_server_name("noisy");
+        assert!(client.connect().await.is_err());
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while client.last_stderr().last().map(String::as_str) != Some("line 59") {
+                sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("stderr should be captured");
+
+        let lines = client.last_stderr();
+        assert_eq!(lines.len(), STDERR_BUFFER_LINES);
+        assert_eq!(lines[0], "line 10");
+    }
+
     #[tokio::test]
     async fn test_start_child_process_with_invalid_command() {
         let params = StdioServerParameters {
@@ -1073,6 +1458,188 @@ mod tests {
         let _ = child.wait().await;
     }
 
+    /// 构造一个按顺序应答的 sh 模拟 MCP 服务器 / Build a sh mock MCP server answering in order
+    fn mock_prompt_server_params(responses: &[serde_json::Value]) -> StdioServerParameters {
+        // initialize 请求 -> 响应，initialized 通知无响应，之后每个请求一条响应
+        // initialize request -> response, initialized notification has no reply, then one reply per request
+        let init = json!({"jsonrpc": "2.0", "id": 1, "result": {"capabilities": {"prompts": {}}}});
+        let mut script = format!("read l; {}; read l; ", sh_reply(&init.to_string()));
+        for response in responses {
+            script.push_str(&format!("read l; {}; ", sh_reply(&response.to_string())));
+        }
+        script.push_str("cat > /dev/null");
+
+        StdioServerParameters {
+            command: "sh".to_string(),
+            args: vec!["-c".to_string(), script],
+            env: HashMap::new(),
+            cwd: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_prompts_from_mock_server() {
+        let params = mock_prompt_server_params(&[json!({
+            "jsonrpc": "2.0",
+            "id": 9,
+            "result": {
+                "prompts": [
+                    {
+                        "name": "code_review",
+                        "description": "Review a piece of code",
+                        "arguments": [{"name": "language", "required": true}]
+                    },
+                    {"name": "summarize"}
+                ]
+            }
+        })]);
+
+        let client = StdioMCPClient::new(params);
+        client.connect().await.unwrap();
+
+        let prompts = client.list_prompts().await.unwrap();
+        assert_eq!(prompts.len(), 2);
+        assert_eq!(prompts[0].name, "code_review");
+        let args = prompts[0].arguments.as_ref().unwrap();
+        assert_eq!(args[0].name, "language");
+        assert_eq!(args[0].required, Some(true));
+        assert_eq!(prompts[1].name, "summarize");
+        assert!(prompts[1].arguments.is_none());
+
+        let _ = client.disconnect().await;
+    }
+
+    #[tokio::test]
+    async fn test_list_resource_templates_from_mock_server() {
+        let params = mock_prompt_server_params(&[
+            json!({
+                "jsonrpc": "2.0",
+                "id": 9,
+                "result": {
+                    "resourceTemplates": [{
+                        "uriTemplate": "file:///{path}",
+                        "name": "project_file",
+                        "mimeType": "text/plain"
+                    }],
+                    "nextCursor": "page-2"
+                }
+            }),
+            json!({
+                "jsonrpc": "2.0",
+                "id": 10,
+                "result": {
+                    "resourceTemplates": [{
+                        "uriTemplate": "db://tables/{table}",
+                        "name": "table",
+                        "description": "A database table"
+                    }]
+                }
+            }),
+        ]);
+
+        let client = StdioMCPClient::new(params);
+        client.connect().await.unwrap();
+
+        let templates = client.list_resource_templates().await.unwrap();
+        assert_eq!(templates.len(), 2);
+        assert_eq!(templates[0].uri_template, "file:///{path}");
+        assert_eq!(templates[0].mime_type.as_deref(), Some("text/plain"));
+        assert_eq!(templates[1].name, "table");
+        assert_eq!(
+            templates[1].description.as_deref(),
+            Some("A database table")
+        );
+
+        let _ = client.disconnect().await;
+    }
+
+    #[tokio::test]
+    async fn test_get_prompt_with_arguments_from_mock_server() {
+        let params = mock_prompt_server_params(&[json!({
+            "jsonrpc": "2.0",
+            "id": 10,
+            "result": {
+                "description": "Code review prompt",
+                "messages": [
+                    {"role": "user", "content": {"type": "text", "text": "Review this rust code"}}
+                ]
+            }
+        })]);
+
+        let client = StdioMCPClient::new(params);
+        client.connect().await.unwrap();
+
+        let mut arguments = HashMap::new();
+        arguments.insert("language".to_string(), "rust".to_string());
+        let result = client
+            .get_prompt("code_review", Some(arguments))
+            .await
+            .unwrap();
+
+        assert_eq!(result.description.as_deref(), Some("Code review prompt"));
+        assert_eq!(result.messages.len(), 1);
+        assert_eq!(result.messages[0].role, "user");
+        assert_eq!(result.messages[0].content["text"], "Review this rust code");
+
+        let _ = client.disconnect().await;
+    }
+
+    #[tokio::test]
+    async fn test_server_info_captured_from_initialize() {
+        let init = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {
+                "capabilities": {},
+                "serverInfo": {"name": "mock-server", "version": "0.3.1"},
+                "instructions": "Call list_files before reading"
+            }
+        });
+        let params = StdioServerParameters {
+            command: "sh".to_string(),
+            args: vec![
+                "-c".to_string(),
+                format!("read l; {}; cat > /dev/null", sh_reply(&init.to_string())),
+            ],
+            env: HashMap::new(),
+            cwd: None,
+        };
+
+        let client = StdioMCPClient::new(params);
+        assert!(client.server_info().await.is_none());
+
+        client.connect().await.unwrap();
+        let info = client.server_info().await.unwrap();
+        assert_eq!(info.name.as_deref(), Some("mock-server"));
+        assert_eq!(info.version.as_deref(), Some("0.3.1"));
+        assert_eq!(
+            info.instructions.as_deref(),
+            Some("Call list_files before reading")
+        );
+
+        // 断开后信息被清除 / Info is cleared on disconnect
+        let _ = client.disconnect().await;
+        assert!(client.server_info().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_list_prompts_requires_connection() {
+        let params = StdioServerParameters {
+            command: "echo".to_string(),
+            args: vec!["test".to_string()],
+            env: HashMap::new(),
+            cwd: None,
+        };
+
+        let client = StdioMCPClient::new(params);
+
+        let result = client.list_prompts().await;
+        assert!(matches!(
+            result.unwrap_err(),
+            MCPClientError::ConnectionError(_)
+        ));
+    }
+
     #[tokio::test]
     async fn test_stdio_client_debug_format() {
         let params = StdioServerParameters {
@@ -1088,4 +1655,105 @@ mod tests {
         let debug_str = format!("{:?}", client);
         assert!(debug_str.contains("StdioMCPClient"));
     }
+
+    #[tokio::test]
+    async fn test_concurrent_requests_matched_by_id() {
+        let init = json!({"jsonrpc": "2.0", "id": 1, "result": {"capabilities": {}}});
+        let result = |text: &str| {
+            json!({"jsonrpc": "2.0", "id": 0, "result": {"content": [{"type": "text", "text": text}]}})
+                .to_string()
+        };
+        let reply = format!(
+            "case \"$l\" in *alpha*) {};; *) {};; esac",
+            sh_reply(&result("alpha")),
+            sh_reply(&result("beta"))
+        );
+        let notification = json!({"jsonrpc": "2.0", "method": "notifications/message", "params": {"data": "busy"}});
+        // 等两个请求都到达后先回复后到的那个，中间穿插一条通知
+        // Wait for both requests, answer the later one first, with a notification in between
+        let script = format!(
+            "read l; {}; read l; read l; first=$l; read l; echo '{}'; {}; l=$first; {}; cat > /dev/null",
+            sh_reply(&init.to_string()),
+            notification,
+            reply,
+            reply
+        );
+        let params = StdioServerParameters {
+            command: "sh".to_string(),
+            args: vec!["-c".to_string(), script],
+            env: HashMap::new(),
+            cwd: None,
+        };
+
+        let client = StdioMCPClient::new(params);
+        let mut notifications = client.notifications();
+        client.connect().await.unwrap();
+
+        let text = |result: CallToolResult| {
+            serde_json::to_value(result).unwrap()["content"][0]["text"]
+                .as_str()
+                .unwrap()
+                .to_string()
+        };
+        let (alpha, beta) = tokio::join!(
+            client.call_tool("alpha", json!({})),
+            client.call_tool("beta", json!({}))
+        );
+        assert_eq!(text(alpha.unwrap()), "alpha");
+        assert_eq!(text(beta.unwrap()), "beta");
+
+        let message = notifications.recv().await.unwrap();
+        assert_eq!(message["method"], "notifications/message");
+
+        client.disconnect().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_aborted_call_tool_sends_cancelled_notification() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("requests.log");
+        // 只响应 initialize，之后记录所有收到的消息且不再回复
+        // Only answer initialize, then record every message and never reply
+        let init = json!({"jsonrpc": "2.0", "id": 1, "result": {"capabilities": {}}});
+        let script = format!(
+            "read l; {}; read l; cat > '{}'",
+            sh_reply(&init.to_string()),
+            log.display()
+        );
+        let params = StdioServerParameters {
+            command: "sh".to_string(),
+            args: vec!["-c".to_string(), script],
+            env: HashMap::new(),
+            cwd: None,
+        };
+
+        let client = Arc::new(StdioMCPClient::new(params));
+        client.connect().await.unwrap();
+
+        let call_client = client.clone();
+        let call = tokio::spawn(async move { call_client.call_tool("slow", json!({})).await });
+        sleep(Duration::from_millis(200)).await;
+        call.abort();
+        let _ = call.await;
+        sleep(Duration::from_millis(200)).await;
+
+        let messages: Vec<serde_json::Value> = std::fs::read_to_string(&log)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let call_id = messages
+            .iter()
+            .find(|m| m["method"] == "tools/call")
+            .map(|m| m["id"].clone())
+            .unwrap();
+        let cancelled = messages
+            .iter()
+            .find(|m| m["method"] == "notifications/cancelled")
+            .unwrap();
+        assert_eq!(cancelled["params"]["requestId"], call_id);
+        assert!(cancelled.get("id").is_none());
+
+        client.disconnect().await.unwrap();
+    }
 }
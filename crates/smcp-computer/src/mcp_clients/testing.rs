@@ -0,0 +1,570 @@
+/**
+* 文件名: testing
+* 作者: JQQ
+* 创建日期: 2025/12/15
+* 最后修改日期: 2025/12/15
+* 版权: 2023 JQQ. All rights reserved.
+* 依赖: async-trait, serde_json
+* 描述: 测试辅助：可编排的 MCP 客户端 / Test support: a scriptable MCP client
+*/
+use super::model::*;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{broadcast, watch};
+
+/// 编排的进度：(progress, total, message, 部分结果) / A scripted progress step: (progress, total, message, partial content)
+type ProgressStep = (f64, Option<f64>, Option<String>, Vec<serde_json::Value>);
+
+/// 可编排的 MCP 客户端，用于在不启动子进程的情况下驱动 Manager 与 Computer
+/// Scriptable MCP client for driving the manager and Computer without subprocesses
+///
+/// 克隆共享同一份脚本与计数器，交给 Manager 后仍可通过克隆断言调用次数。
+/// Clones share the same script and counters, so a clone kept by the test can assert
+/// invocation counts after the client was handed to the manager.
+#[derive(Clone, Default)]
+pub struct MockMCPClient {
+    inner: Arc<MockInner>,
+}
+
+struct MockInner {
+    tools: Vec<Tool>,
+    resource_templates: Vec<ResourceTemplate>,
+    windows: Vec<(Resource, String)>,
+    responses: HashMap<String, CallToolResult>,
+    call_errors: HashMap<String, String>,
+    progress: HashMap<String, Vec<ProgressStep>>,
+    connect_error: Option<String>,
+    connect_failures: usize,
+    list_tools_error: Option<String>,
+    call_delay: Option<Duration>,
+    state: watch::Sender<ClientState>,
+    notifications: broadcast::Sender<serde_json::Value>,
+    calls: Mutex<Vec<(String, serde_json::Value)>>,
+    connect_count: Mutex<usize>,
+    disconnect_count: Mutex<usize>,
+    list_tools_count: Mutex<usize>,
+}
+
+impl Default for MockInner {
+    fn default() -> Self {
+        Self {
+            tools: vec![],
+            resource_templates: vec![],
+            windows: vec![],
+            responses: HashMap::new(),
+            call_errors: HashMap::new(),
+            progress: HashMap::new(),
+            connect_error: None,
+            connect_failures: 0,
+            list_tools_error: None,
+            call_delay: None,
+            state: watch::Sender::new(ClientState::Initialized),
+            notifications: broadcast::channel(16).0,
+            calls: Mutex::new(vec![]),
+            connect_count: Mutex::new(0),
+            disconnect_count: Mutex::new(0),
+            list_tools_count: Mutex::new(0),
+        }
+    }
+}
+
+impl std::fmt::Debug for MockMCPClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MockMCPClient")
+            .field("tools", &self.inner.tools.len())
+            .field("state", &self.state())
+            .finish()
+    }
+}
+
+impl MockMCPClient {
+    /// 创建构建器 / Create a builder
+    pub fn builder() -> MockMCPClientBuilder {
+        MockMCPClientBuilder::default()
+    }
+
+    /// 记录的工具调用（工具名与参数） / Recorded tool calls (tool name and params)
+    pub fn calls(&self) -> Vec<(String, serde_json::Value)> {
+        self.inner.calls.lock().unwrap().clone()
+    }
+
+    /// 指定工具的调用次数 / Number of calls to the given tool
+    pub fn call_count(&self, tool_name: &str) -> usize {
+        self.inner
+            .calls
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(name, _)| name == tool_name)
+            .count()
+    }
+
+    /// 所有工具调用次数 / Total number of tool calls
+    pub fn total_calls(&self) -> usize {
+        self.inner.calls.lock().unwrap().len()
+    }
+
+    /// `connect` 调用次数 / Number of `connect` calls
+    pub fn connect_count(&self) -> usize {
+        *self.inner.connect_count.lock().unwrap()
+    }
+
+    /// `disconnect` 调用次数 / Number of `disconnect` calls
+    pub fn disconnect_count(&self) -> usize {
+        *self.inner.disconnect_count.lock().unwrap()
+    }
+
+    /// `list_tools` 调用次数 / Number of `list_tools` calls
+    pub fn list_tools_count(&self) -> usize {
+        *self.inner.list_tools_count.lock().unwrap()
+    }
+
+    /// 模拟服务器意外断开（不计入 `disconnect` 调用） / Simulate an unexpected server drop (not counted as a `disconnect` call)
+    pub fn simulate_disconnect(&self) {
+        self.inner.state.send_replace(ClientState::Disconnected);
+    }
+
+    /// 模拟服务器主动发送一条消息 / Simulate a message initiated by the server
+    pub fn send_notification(&self, message: serde_json::Value) {
+        let _ = self.inner.notifications.send(message);
+    }
+}
+
+/// `MockMCPClient` 构建器 / Builder for `MockMCPClient`
+#[derive(Default)]
+pub struct MockMCPClientBuilder {
+    inner: MockInner,
+}
+
+impl MockMCPClientBuilder {
+    /// 添加一个工具 / Add a tool
+    pub fn tool(mut self, name: &str) -> Self {
+        self.inner.tools.push(Tool {
+            name: name.to_string(),
+            description: format!("Mock tool {}", name),
+            input_schema: serde_json::json!({"type": "object"}),
+            output_schema: None,
+            annotations: None,
+            meta: None,
+        });
+        self
+    }
+
+    /// 添加完整定义的工具 / Add a fully defined tool
+    pub fn tool_definition(mut self, tool: Tool) -> Self {
+        self.inner.tools.push(tool);
+        self
+    }
+
+    /// 添加一个资源模板 / Add a resource template
+    pub fn resource_template(mut self, uri_template: &str, name: &str) -> Self {
+        self.inner.resource_templates.push(ResourceTemplate {
+            uri_template: uri_template.to_string(),
+            name: name.to_string(),
+            description: None,
+            mime_type: None,
+        });
+        self
+    }
+
+    /// 添加一个资源及其文本内容，`window://` 资源即为窗口 / Add a resource with its text content; `window://` resources are windows
+    pub fn window(mut self, uri: &str, text: &str) -> Self {
+        self.inner.windows.push((
+            Resource {
+                uri: uri.to_string(),
+                name: uri.to_string(),
+                description: None,
+                mime_type: None,
+            },
+            text.to_string(),
+        ));
+        self
+    }
+
+    /// 设置工具调用的返回结果 / Set the result returned for a tool call
+    pub fn call_response(mut self, tool_name: &str, result: CallToolResult) -> Self {
+        self.inner.responses.insert(tool_name.to_string(), result);
+        self
+    }
+
+    /// 设置工具调用返回协议错误 / Make a tool call fail with a protocol error
+    pub fn call_error(mut self, tool_name: &str, message: impl Into<String>) -> Self {
+        self.inner
+            .call_errors
+            .insert(tool_name.to_string(), message.into());
+        self
+    }
+
+    /// 追加一条工具调用进度，携带进度令牌的调用在返回前依次发送
+    /// Append a progress step, sent in order before a call carrying a progress token returns
+    pub fn call_progress(
+        mut self,
+        tool_name: &str,
+        progress: f64,
+        total: Option<f64>,
+        message: Option<&str>,
+    ) -> Self {
+        self.inner
+            .progress
+            .entry(tool_name.to_string())
+            .or_default()
+            .push((progress, total, message.map(str::to_string), vec![]));
+        self
+    }
+
+    /// 追加一段随进度上报的部分文本结果，进度值按已编排的步数递增
+    /// Append a partial text result reported with a progress step; the progress counts the
+    /// scripted steps
+    pub fn call_chunk(mut self, tool_name: &str, text: &str) -> Self {
+        let steps = self
+            .inner
+            .progress
+            .entry(tool_name.to_string())
+            .or_default();
+        let progress = (steps.len() + 1) as f64;
+        steps.push((
+            progress,
+            None,
+            None,
+            vec![serde_json::json!({"type": "text", "text": text})],
+        ));
+        self
+    }
+
+    /// 设置 `connect` 返回连接错误 / Make `connect` fail with a connection error
+    pub fn connect_error(mut self, message: impl Into<String>) -> Self {
+        self.inner.connect_error = Some(message.into());
+        self
+    }
+
+    /// 前 `count` 次 `connect` 返回连接错误，之后成功 / Fail the first `count` `connect` calls, then succeed
+    pub fn connect_failures(mut self, count: usize) -> Self {
+        self.inner.connect_failures = count;
+        self
+    }
+
+    /// 设置 `list_tools` 返回协议错误 / Make `list_tools` fail with a protocol error
+    pub fn list_tools_error(mut self, message: impl Into<String>) -> Self {
+        self.inner.list_tools_error = Some(message.into());
+        self
+    }
+
+    /// 每次工具调用在返回前等待 `delay` / Make every tool call wait `delay` before returning
+    pub fn call_delay(mut self, delay: Duration) -> Self {
+        self.inner.call_delay = Some(delay);
+        self
+    }
+
+    /// 构建客户端 / Build the client
+    pub fn build(self) -> MockMCPClient {
+        MockMCPClient {
+            inner: Arc::new(self.inner),
+        }
+    }
+}
+
+/// 供 Mock 客户端使用的占位服务器配置 / Placeholder server config for a mock client
+pub fn mock_server_config(name: &str) -> MCPServerConfig {
+    MCPServerConfig::Stdio(StdioServerConfig {
+        name: name.to_string(),
+        disabled: false,
+        forbidden_tools: vec![],
+        tool_meta: HashMap::new(),
+        default_tool_meta: None,
+        vrl: None,
+        server_parameters: StdioServerParameters {
+            command: "mock".to_string(),
+            args: vec![],
+            env: HashMap::new(),
+            cwd: None,
+        },
+    })
+}
+
+/// 生成 sh 模拟服务器的应答片段：把 `response` 的 `id` 换成刚读入的请求行 `$l` 的 `id`
+/// Build a reply snippet for sh mock servers: `response` is echoed with the `id` of the request line just read into `$l`
+pub fn sh_reply(response: &str) -> String {
+    let start = response.find("\"id\":").expect("response must carry an id") + "\"id\":".len();
+    let end = response[start..]
+        .find(|c: char| !c.is_ascii_digit())
+        .map_or(response.len(), |n| start + n);
+    // 顶层 id 位于 params 对象之前 / The top-level id precedes the params object
+    format!(
+        r#"id=$(printf '%s' "$l" | sed -n 's/^{{[^{{]*"id":\([0-9][0-9]*\).*/\1/p'); echo '{}'"$id"'{}'"#,
+        &response[..start],
+        &response[end..]
+    )
+}
+
+#[async_trait]
+impl MCPClientProtocol for MockMCPClient {
+    fn state(&self) -> ClientState {
+        *self.inner.state.borrow()
+    }
+
+    fn subscribe_state(&self) -> Option<watch::Receiver<ClientState>> {
+        Some(self.inner.state.subscribe())
+    }
+
+    fn subscribe_notifications(&self) -> Option<broadcast::Receiver<serde_json::Value>> {
+        Some(self.inner.notifications.subscribe())
+    }
+
+    async fn connect(&self) -> Result<(), MCPClientError> {
+        let attempt = {
+            let mut count = self.inner.connect_count.lock().unwrap();
+            *count += 1;
+            *count
+        };
+        if let Some(message) = &self.inner.connect_error {
+            self.inner.state.send_replace(ClientState::Error);
+            return Err(MCPClientError::ConnectionError(message.clone()));
+        }
+        if attempt <= self.inner.connect_failures {
+            self.inner.state.send_replace(ClientState::Error);
+            return Err(MCPClientError::ConnectionError(format!(
+                "connect attempt {} refused",
+                attempt
+            )));
+        }
+        self.inner.state.send_replace(ClientState::Connected);
+        Ok(())
+    }
+
+    async fn disconnect(&self) -> Result<(), MCPClientError> {
+        *self.inner.disconnect_count.lock().unwrap() += 1;
+        self.inner.state.send_replace(ClientState::Disconnected);
+        Ok(())
+    }
+
+    async fn list_tools(&self) -> Result<Vec<Tool>, MCPClientError> {
+        *self.inner.list_tools_count.lock().unwrap() += 1;
+        if let Some(message) = &self.inner.list_tools_error {
+            return Err(MCPClientError::ProtocolError(message.clone()));
+        }
+        Ok(self.inner.tools.clone())
+    }
+
+    async fn call_tool(
+        &self,
+        tool_name: &str,
+        params: serde_json::Value,
+    ) -> Result<CallToolResult, MCPClientError> {
+        self.inner
+            .calls
+            .lock()
+            .unwrap()
+            .push((tool_name.to_string(), params));
+        if let Some(delay) = self.inner.call_delay {
+            tokio::time::sleep(delay).await;
+        }
+        if let Some(message) = self.inner.call_errors.get(tool_name) {
+            return Err(MCPClientError::ProtocolError(message.clone()));
+        }
+        // 未编排的工具返回包含工具名的文本 / Unscripted tools return a text naming the tool
+        Ok(self
+            .inner
+            .responses
+            .get(tool_name)
+            .cloned()
+            .unwrap_or_else(|| CallToolResult {
+                content: vec![Content::Text {
+                    text: format!("mock:{}", tool_name),
+                }],
+                is_error: false,
+                structured_content: None,
+                meta: None,
+            }))
+    }
+
+    async fn call_tool_with_progress(
+        &self,
+        tool_name: &str,
+        params: serde_json::Value,
+        progress_token: &str,
+    ) -> Result<CallToolResult, MCPClientError> {
+        for (progress, total, message, content) in
+            self.inner.progress.get(tool_name).into_iter().flatten()
+        {
+            self.send_notification(serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": "notifications/progress",
+                "params": {
+                    "progressToken": progress_token,
+                    "progress": progress,
+                    "total": total,
+                    "message": message,
+                    "content": content,
+                }
+            }));
+        }
+        self.call_tool(tool_name, params).await
+    }
+
+    async fn list_windows(&self) -> Result<Vec<Resource>, MCPClientError> {
+        Ok(self
+            .inner
+            .windows
+            .iter()
+            .map(|(resource, _)| resource.clone())
+            .collect())
+    }
+
+    async fn list_resource_templates(&self) -> Result<Vec<ResourceTemplate>, MCPClientError> {
+        Ok(self.inner.resource_templates.clone())
+    }
+
+    async fn get_window_detail(
+        &self,
+        resource: Resource,
+    ) -> Result<ReadResourceResult, MCPClientError> {
+        if let Some((_, text)) = self
+            .inner
+            .windows
+            .iter()
+            .find(|(window, _)| window.uri == resource.uri)
+        {
+            return Ok(ReadResourceResult {
+                contents: vec![TextResourceContents {
+                    uri: resource.uri,
+                    text: text.clone(),
+                    mime_type: None,
+                }],
+            });
+        }
+        Err(MCPClientError::ProtocolError(format!(
+            "Mock client has no window {}",
+            resource.uri
+        )))
+    }
+
+    async fn subscribe_window(&self, _resource: Resource) -> Result<(), MCPClientError> {
+        Ok(())
+    }
+
+    async fn unsubscribe_window(&self, _resource: Resource) -> Result<(), MCPClientError> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp_clients::manager::MCPServerManager;
+
+    #[tokio::test]
+    async fn test_mock_drives_manager_routing() {
+        let files = MockMCPClient::builder()
+            .tool("read_file")
+            .call_response(
+                "read_file",
+                CallToolResult {
+                    content: vec![Content::Text {
+                        text: "hello".to_string(),
+                    }],
+                    is_error: false,
+                    structured_content: None,
+                    meta: None,
+                },
+            )
+            .build();
+        let search = MockMCPClient::builder()
+            .tool("search")
+            .call_error("search", "index unavailable")
+            .build();
+
+        let manager = MCPServerManager::new();
+        manager
+            .attach_client(mock_server_config("files"), Arc::new(files.clone()))
+            .await
+            .unwrap();
+        manager
+            .attach_client(mock_server_config("search"), Arc::new(search.clone()))
+            .await
+            .unwrap();
+        assert_eq!(files.connect_count(), 1);
+
+        let result = manager
+            .execute_tool("read_file", serde_json::json!({"path": "a.txt"}), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            result.content,
+            vec![Content::Text {
+                text: "hello".to_string()
+            }]
+        );
+        assert_eq!(
+            files.calls(),
+            vec![(
+                "read_file".to_string(),
+                serde_json::json!({"path": "a.txt"})
+            )]
+        );
+        assert_eq!(search.total_calls(), 0);
+
+        let err = manager
+            .execute_tool("search", serde_json::json!({}), None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("index unavailable"));
+        assert_eq!(search.call_count("search"), 1);
+        assert_eq!(files.total_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_mock_detects_tool_name_conflict() {
+        let manager = MCPServerManager::new();
+        manager
+            .attach_client(
+                mock_server_config("first"),
+                Arc::new(MockMCPClient::builder().tool("echo").build()),
+            )
+            .await
+            .unwrap();
+
+        let err = manager
+            .attach_client(
+                mock_server_config("second"),
+                Arc::new(MockMCPClient::builder().tool("echo").build()),
+            )
+            .await
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Tool 'echo' exists in multiple servers"));
+
+        // 别名消除冲突后按别名路由 / An alias resolves the conflict and routes by alias
+        let aliased = MockMCPClient::builder().tool("echo").build();
+        let mut config = mock_server_config("second");
+        if let MCPServerConfig::Stdio(ref mut stdio) = config {
+            let mut meta = ToolMeta::new();
+            meta.alias = Some("second_echo".to_string());
+            stdio.tool_meta.insert("echo".to_string(), meta);
+        }
+        manager
+            .attach_client(config, Arc::new(aliased.clone()))
+            .await
+            .unwrap();
+        manager
+            .execute_tool("second_echo", serde_json::json!({}), None)
+            .await
+            .unwrap();
+        assert_eq!(aliased.call_count("echo"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_mock_connect_error() {
+        let mock = MockMCPClient::builder().connect_error("refused").build();
+        let manager = MCPServerManager::new();
+        let err = manager
+            .attach_client(mock_server_config("broken"), Arc::new(mock.clone()))
+            .await
+            .unwrap_err();
+        assert!(err.is_transient());
+        assert_eq!(mock.state(), ClientState::Error);
+        assert!(manager.list_available_tools().await.is_empty());
+    }
+}
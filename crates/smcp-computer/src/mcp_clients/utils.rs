@@ -17,7 +17,7 @@ use std::sync::Arc as StdArc;
 pub fn client_factory(config: MCPServerConfig) -> StdArc<dyn MCPClientProtocol> {
     match config {
         MCPServerConfig::Stdio(config) => {
-            StdArc::new(StdioMCPClient::new(config.server_parameters))
+            StdArc::new(StdioMCPClient::new(config.server_parameters).with_server_name(config.name))
         }
         MCPServerConfig::Sse(config) => StdArc::new(SseMCPClient::new(config.server_parameters)),
         MCPServerConfig::Http(config) => StdArc::new(HttpMCPClient::new(config.server_parameters)),
@@ -13,7 +13,7 @@ use thiserror::Error;
 use {
     serde_json::Value,
     vrl::{
-        compiler::{self, runtime::Runtime, TargetValue, TimeZone},
+        compiler::{self, runtime::Runtime, Program, TargetValue, TimeZone},
         stdlib,
         value::{Secrets, Value as VrlValue},
     },
@@ -66,19 +66,11 @@ impl VrlRuntime {
         timezone: &str,
     ) -> Result<VrlResult, VrlError> {
         // 编译VRL脚本
-        let compilation = compiler::compile(script, &stdlib::all())
-            .map_err(|e| VrlError::Compilation(format!("{:?}", e)))?;
+        let compiled = CompiledVrl::compile(script)?;
 
         // 转换JSON事件到VRL Value
         let vrl_value = self.json_to_vrl_value(event)?;
 
-        // 创建TargetValue作为执行目标
-        let mut target = TargetValue {
-            value: vrl_value,
-            metadata: VrlValue::Object(Default::default()),
-            secrets: Secrets::default(),
-        };
-
         // 解析时区
         let tz = if timezone == "UTC" {
             TimeZone::default()
@@ -88,12 +80,7 @@ impl VrlRuntime {
         };
 
         // 执行VRL程序
-        self.runtime
-            .resolve(&mut target, &compilation.program, &tz)
-            .map_err(|e| VrlError::Runtime(format!("{:?}", e)))?;
-
-        // 获取转换后的值
-        let processed = target.value;
+        let processed = compiled.resolve(&mut self.runtime, vrl_value, &tz)?;
 
         // 转换回JSON
         let processed_event = self.vrl_value_to_json(processed)?;
@@ -123,6 +110,63 @@ impl Default for VrlRuntime {
     }
 }
 
+/// 预编译的VRL程序，加载配置时编译一次，之后可重复执行
+#[cfg(feature = "vrl")]
+#[derive(Clone)]
+pub struct CompiledVrl {
+    program: Program,
+}
+
+#[cfg(feature = "vrl")]
+impl CompiledVrl {
+    /// 编译VRL脚本
+    pub fn compile(script: &str) -> Result<Self, VrlError> {
+        let compilation = compiler::compile(script, &stdlib::all())
+            .map_err(|e| VrlError::Compilation(format!("{:?}", e)))?;
+        Ok(Self {
+            program: compilation.program,
+        })
+    }
+
+    /// 以UTC时区对JSON事件执行程序，返回转换后的事件
+    pub fn run(&self, event: Value) -> Result<Value, VrlError> {
+        let processed = self.resolve(
+            &mut Runtime::default(),
+            VrlValue::from(event),
+            &TimeZone::default(),
+        )?;
+        processed
+            .try_into()
+            .map_err(|e| VrlError::Runtime(format!("Failed to convert VRL value: {:?}", e)))
+    }
+
+    /// 在给定运行时上执行程序
+    fn resolve(
+        &self,
+        runtime: &mut Runtime,
+        value: VrlValue,
+        tz: &TimeZone,
+    ) -> Result<VrlValue, VrlError> {
+        // 创建TargetValue作为执行目标
+        let mut target = TargetValue {
+            value,
+            metadata: VrlValue::Object(Default::default()),
+            secrets: Secrets::default(),
+        };
+        runtime
+            .resolve(&mut target, &self.program, tz)
+            .map_err(|e| VrlError::Runtime(format!("{:?}", e)))?;
+        Ok(target.value)
+    }
+}
+
+#[cfg(feature = "vrl")]
+impl std::fmt::Debug for CompiledVrl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CompiledVrl").finish_non_exhaustive()
+    }
+}
+
 // 当没有启用vrl feature时的占位实现
 #[cfg(not(feature = "vrl"))]
 #[derive(Error, Debug)]
@@ -170,6 +214,22 @@ impl Default for VrlRuntime {
     }
 }
 
+#[cfg(not(feature = "vrl"))]
+#[derive(Debug, Clone)]
+pub struct CompiledVrl;
+
+#[cfg(not(feature = "vrl"))]
+impl CompiledVrl {
+    pub fn compile(_script: &str) -> Result<Self, VrlError> {
+        Ok(Self)
+    }
+
+    pub fn run(&self, event: serde_json::Value) -> Result<serde_json::Value, VrlError> {
+        // 当VRL未启用时，直接返回原始事件
+        Ok(event)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -349,6 +409,23 @@ mod tests {
         assert!(debug_str.contains("processed_event"));
     }
 
+    #[cfg(feature = "vrl")]
+    #[test]
+    fn test_compiled_vrl_renames_field() {
+        let compiled = CompiledVrl::compile(".total = del(.count)").unwrap();
+
+        // 编译一次，可重复执行
+        for count in [1, 2] {
+            let result = compiled.run(json!({"count": count, "keep": "x"})).unwrap();
+            assert_eq!(result, json!({"total": count, "keep": "x"}));
+        }
+
+        assert!(matches!(
+            CompiledVrl::compile(".total ="),
+            Err(VrlError::Compilation(_))
+        ));
+    }
+
     #[cfg(feature = "vrl")]
     #[test]
     fn test_vrl_runtime_default() {
@@ -8,26 +8,146 @@
 * 描述: SMCP Computer的Socket.IO客户端实现 / Socket.IO client implementation for SMCP Computer
 */
 
+use crate::computer::{desktop_history, CallContext, ResultPipeline, ToolHistory};
+use crate::desktop::organize_desktop;
 use crate::errors::{ComputerError, ComputerResult};
 use crate::mcp_clients::manager::MCPServerManager;
+use crate::mcp_clients::model::{CallToolResult, Content, MCPServerConfig, MCPServerInput};
+use chrono::{DateTime, Utc};
 use futures_util::FutureExt;
 use rust_socketio::{
-    asynchronous::{Client, ClientBuilder},
+    asynchronous::{Client, ClientBuilder, ReconnectSettings},
     Event, Payload, TransportType,
 };
+use serde::{de::DeserializeOwned, Serialize};
 use serde_json::Value;
 use smcp::{
+    build_handshake_url, error_codes,
     events::{
-        CLIENT_GET_CONFIG, CLIENT_GET_DESKTOP, CLIENT_GET_TOOLS, CLIENT_TOOL_CALL,
-        SERVER_JOIN_OFFICE, SERVER_LEAVE_OFFICE, SERVER_UPDATE_CONFIG, SERVER_UPDATE_DESKTOP,
-        SERVER_UPDATE_TOOL_LIST,
+        CLIENT_GET_CONFIG, CLIENT_GET_DESKTOP, CLIENT_GET_PROMPT, CLIENT_GET_PROMPTS,
+        CLIENT_GET_RESOURCE_TEMPLATES, CLIENT_GET_TOOLS, CLIENT_READ_RESOURCE, CLIENT_TOOL_CALL,
+        NOTIFY_SERVER_HELLO, NOTIFY_TOOL_CALL_CANCEL, SERVER_JOIN_OFFICE, SERVER_LEAVE_OFFICE,
+        SERVER_TOOL_PROGRESS, SERVER_UPDATE_CONFIG, SERVER_UPDATE_DESKTOP, SERVER_UPDATE_TOOL_LIST,
     },
-    GetComputerConfigReq, GetComputerConfigRet, GetDesktopReq, GetDesktopRet, GetToolsReq,
-    GetToolsRet, ToolCallReq, SMCP_NAMESPACE,
+    extract_4008_from_error,
+    handshake::disconnect_reason_from_payload,
+    AgentCallData, DisconnectReason, ErrorPayload, GetComputerConfigReq, GetComputerConfigRet,
+    GetDesktopReq, GetDesktopRet, GetPromptReq, GetPromptRet, GetPromptsReq, GetPromptsRet,
+    GetResourceTemplatesReq, GetResourceTemplatesRet, GetToolsReq, GetToolsRet, ReadResourceReq,
+    ReadResourceRet, ReconnectState, ReconnectTracker, ReqId, ServerCapabilities, ToolCallReq,
+    ToolProgressNotification, DEFAULT_MAX_TIMEOUT_SECS, PROTOCOL_VERSION, SMCP_NAMESPACE,
 };
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::{debug, error, info};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
+
+/// 进行中的工具调用，按 req_id 索引其取消令牌
+/// In-flight tool calls, keyed by req_id to their cancellation token
+type PendingToolCalls = Arc<std::sync::Mutex<HashMap<String, CancellationToken>>>;
+
+/// 连接断开回调类型 / Disconnect callback type
+pub type DisconnectCallback = Arc<dyn Fn(&DisconnectReason) + Send + Sync>;
+
+/// 重连次数耗尽回调类型，参数为已尝试次数 / Reconnect exhausted callback type, receives the attempt count
+pub type ReconnectExhaustedCallback = Arc<dyn Fn(u32) + Send + Sync>;
+
+/// Socket.IO 连接信息，重连成功后更新 / Socket.IO connection info, updated after a successful reconnect
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConnectionInfo {
+    /// 服务器 URL / Server URL
+    pub url: String,
+    /// 命名空间 / Namespace
+    pub namespace: String,
+    /// 传输方式 / Transport
+    pub transport: String,
+    /// Computer 名称 / Computer name
+    pub computer_name: String,
+    /// 连接建立次数，每次重连成功加一 / Times the connection was established, incremented on each reconnect
+    pub connect_count: u32,
+    /// 最近一次建立连接的时间 / When the connection was last established
+    pub connected_at: DateTime<Utc>,
+}
+
+/// 重连选项 / Reconnect options
+#[derive(Clone, Default)]
+pub struct ReconnectOptions {
+    /// 最大重连次数，`None` 表示不限 / Maximum reconnect attempts, `None` for unlimited
+    pub max_attempts: Option<u32>,
+    /// 重连次数耗尽时的回调 / Callback fired when reconnect attempts are exhausted
+    pub on_exhausted: Option<ReconnectExhaustedCallback>,
+}
+
+/// Computer 身份（role/name）在握手中的位置 / Where the Computer identity (role/name) goes in the handshake
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IdentityPlacement {
+    /// 放在 `server:join_office` 事件中（默认）/ In the `server:join_office` event (default)
+    #[default]
+    JoinEvent,
+    /// 放在 Socket.IO 连接的 auth 载荷中，加入事件只携带 office_id
+    /// In the Socket.IO connect auth payload; the join event only carries office_id
+    ConnectAuth,
+}
+
+/// 握手配置，适配在连接阶段认证身份的服务器
+/// Handshake config for servers that authenticate the identity at connect
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HandshakeConfig {
+    /// 身份位置 / Identity placement
+    pub identity: IdentityPlacement,
+}
+
+impl HandshakeConfig {
+    /// 连接时发送的 auth 载荷，身份不在连接阶段发送时为 `None`
+    /// Auth payload sent at connect, `None` when the identity is not sent at connect
+    pub fn connect_auth(&self, computer_name: &str) -> Option<Value> {
+        match self.identity {
+            IdentityPlacement::JoinEvent => None,
+            IdentityPlacement::ConnectAuth => Some(serde_json::json!({
+                "role": "computer",
+                "name": computer_name
+            })),
+        }
+    }
+
+    /// `server:join_office` 事件载荷 / `server:join_office` event payload
+    pub fn join_office_payload(&self, office_id: &str, computer_name: &str) -> Value {
+        match self.identity {
+            IdentityPlacement::JoinEvent => serde_json::json!({
+                "office_id": office_id,
+                "role": "computer",
+                "name": computer_name
+            }),
+            IdentityPlacement::ConnectAuth => serde_json::json!({ "office_id": office_id }),
+        }
+    }
+}
+
+/// Computer 配置的共享视图，用于应答 `client:get_config`
+/// Shared view of the computer configuration, used to answer `client:get_config`
+#[derive(Clone, Default)]
+pub struct ComputerConfigSource {
+    /// 原始服务器配置 (名称 -> 配置) / Raw server configs (name -> config)
+    pub servers: Arc<RwLock<HashMap<String, MCPServerConfig>>>,
+    /// 输入定义 (id -> 输入) / Input definitions (id -> input)
+    pub inputs: Arc<RwLock<HashMap<String, MCPServerInput>>>,
+}
+
+impl ComputerConfigSource {
+    /// 当前配置快照；输入按 id 排序，密码输入的默认值在序列化时去除
+    /// Snapshot of the current config; inputs are sorted by id and password defaults are dropped on serialization
+    pub async fn snapshot(&self) -> ComputerResult<GetComputerConfigRet> {
+        let servers = serde_json::to_value(&*self.servers.read().await)?;
+        let mut inputs: Vec<MCPServerInput> = self.inputs.read().await.values().cloned().collect();
+        inputs.sort_by(|a, b| a.id().cmp(b.id()));
+        Ok(GetComputerConfigRet {
+            inputs: Some(inputs),
+            servers,
+        })
+    }
+}
 
 /// SMCP Computer Socket.IO客户端
 /// SMCP Computer Socket.IO client
@@ -38,6 +158,20 @@ pub struct SmcpComputerClient {
     computer_name: String,
     /// 当前所在的office ID / Current office ID
     office_id: Arc<RwLock<Option<String>>>,
+    /// 最近一次断开原因 / Last disconnect reason
+    disconnect_reason: Arc<RwLock<Option<DisconnectReason>>>,
+    /// 重连计数 / Reconnect attempt tracker
+    reconnect: Arc<std::sync::Mutex<ReconnectTracker>>,
+    /// 连接信息 / Connection info
+    connection: Arc<std::sync::RwLock<ConnectionInfo>>,
+    /// 握手配置 / Handshake config
+    handshake: HandshakeConfig,
+    /// 应答 get_config 的配置来源 / Config source answering get_config
+    config_source: Arc<RwLock<Option<ComputerConfigSource>>>,
+    /// 工具结果后处理 / Tool result post-processing
+    result_pipeline: Arc<RwLock<ResultPipeline>>,
+    /// 桌面排序使用的工具调用历史 / Tool call history used for desktop ordering
+    tool_history: Arc<RwLock<ToolHistory>>,
 }
 
 impl SmcpComputerClient {
@@ -47,17 +181,148 @@ impl SmcpComputerClient {
         url: &str,
         manager: Arc<RwLock<Option<MCPServerManager>>>,
         computer_name: String,
+    ) -> ComputerResult<Self> {
+        Self::new_with_disconnect_callback(url, manager, computer_name, None).await
+    }
+
+    /// 创建新的Socket.IO客户端，并在连接断开时回调
+    /// Create a new Socket.IO client with a disconnect callback
+    pub async fn new_with_disconnect_callback(
+        url: &str,
+        manager: Arc<RwLock<Option<MCPServerManager>>>,
+        computer_name: String,
+        on_disconnected: Option<DisconnectCallback>,
+    ) -> ComputerResult<Self> {
+        Self::new_with_options(
+            url,
+            manager,
+            computer_name,
+            on_disconnected,
+            ReconnectOptions::default(),
+            HandshakeConfig::default(),
+        )
+        .await
+    }
+
+    /// 创建新的Socket.IO客户端，可配置断开回调、重连上限与握手方式
+    /// Create a new Socket.IO client with a disconnect callback, reconnect limit and handshake
+    pub async fn new_with_options(
+        url: &str,
+        manager: Arc<RwLock<Option<MCPServerManager>>>,
+        computer_name: String,
+        on_disconnected: Option<DisconnectCallback>,
+        reconnect: ReconnectOptions,
+        handshake: HandshakeConfig,
     ) -> ComputerResult<Self> {
         let office_id = Arc::new(RwLock::new(None));
         let manager_clone = manager.clone();
         let computer_name_clone = computer_name.clone();
         let office_id_clone = office_id.clone();
+        let disconnect_reason = Arc::new(RwLock::new(None));
+        let disconnect_reason_clone = disconnect_reason.clone();
+        let tracker = Arc::new(std::sync::Mutex::new(ReconnectTracker::new(
+            reconnect.max_attempts,
+        )));
+        let connect_tracker = tracker.clone();
+        let connection = Arc::new(std::sync::RwLock::new(ConnectionInfo {
+            url: url.to_string(),
+            namespace: SMCP_NAMESPACE.to_string(),
+            transport: "websocket".to_string(),
+            computer_name: computer_name.clone(),
+            connect_count: 0,
+            connected_at: Utc::now(),
+        }));
+        let connect_info = connection.clone();
+        let rejoin_office_id = office_id.clone();
+        let rejoin_handshake = handshake.clone();
+        let rejoin_name = computer_name.clone();
+        let reconnect_tracker = tracker.clone();
+        let client_slot: Arc<std::sync::Mutex<Option<Client>>> =
+            Arc::new(std::sync::Mutex::new(None));
+        let reconnect_slot = client_slot.clone();
+        let on_exhausted = reconnect.on_exhausted.clone();
+        let pending_calls: PendingToolCalls = Arc::new(std::sync::Mutex::new(HashMap::new()));
+        let max_timeout_secs = Arc::new(AtomicU64::new(DEFAULT_MAX_TIMEOUT_SECS));
+        let config_source: Arc<RwLock<Option<ComputerConfigSource>>> = Arc::new(RwLock::new(None));
+        let config_source_clone = config_source.clone();
+        let result_pipeline = Arc::new(RwLock::new(ResultPipeline::new()));
+        let result_pipeline_clone = result_pipeline.clone();
+        let tool_history: Arc<RwLock<ToolHistory>> = Arc::new(RwLock::new(ToolHistory::default()));
+        let tool_history_clone = tool_history.clone();
 
         // 使用ClientBuilder注册事件处理器
         // Use ClientBuilder to register event handlers
-        let client = ClientBuilder::new(url)
+        // 命名空间由 Socket.IO 连接包指定，握手地址只追加协议版本
+        // The namespace travels in the Socket.IO connect packet; the handshake URL only adds the protocol version
+        let mut builder = ClientBuilder::new(build_handshake_url(url, "", PROTOCOL_VERSION, None))
             .namespace(SMCP_NAMESPACE)
-            .transport_type(TransportType::Websocket)
+            .transport_type(TransportType::Websocket);
+        if let Some(auth) = handshake.connect_auth(&computer_name) {
+            builder = builder.auth(auth);
+        }
+        // 底层上限多留一次，以便在第 max+1 次回调时确认前 max 次均已失败
+        // Allow one extra attempt underneath so the (max+1)th callback confirms the first max failed
+        if let Some(max) = reconnect.max_attempts {
+            builder =
+                builder.max_reconnect_attempts(max.saturating_add(1).min(u8::MAX as u32) as u8);
+        }
+        let client = builder
+            .on(Event::Connect, move |_payload, client| {
+                let mut tracker = connect_tracker.lock().unwrap();
+                // 首次连接由 connect() 返回处记录，这里只记录重连
+                // The first connect is recorded when connect() returns; only reconnects are recorded here
+                if matches!(tracker.state(), ReconnectState::Reconnecting { .. }) {
+                    let mut info = connect_info.write().unwrap();
+                    info.connect_count += 1;
+                    info.connected_at = Utc::now();
+
+                    // 在事件循环之外等待 ACK，避免阻塞回调
+                    // Wait for the ack outside the event loop so the callback does not block it
+                    tokio::spawn(Self::rejoin_office(
+                        client,
+                        rejoin_office_id.clone(),
+                        rejoin_handshake.clone(),
+                        rejoin_name.clone(),
+                    ));
+                }
+                tracker.on_connected();
+                async {}.boxed()
+            })
+            .on_reconnect(move || {
+                if Self::record_reconnect_attempt(&reconnect_tracker, &on_exhausted).is_some() {
+                    // 停止底层重连 / Stop the underlying reconnect loop
+                    if let Some(client) = reconnect_slot.lock().unwrap().clone() {
+                        tokio::spawn(async move {
+                            if let Err(e) = client.disconnect().await {
+                                error!("Failed to stop reconnecting: {}", e);
+                            }
+                        });
+                    }
+                }
+                async { ReconnectSettings::new() }.boxed()
+            })
+            .on(Event::Close, move |payload, client| {
+                let reason = disconnect_reason_from_payload(payload);
+                let disconnect_reason = disconnect_reason_clone.clone();
+                let on_disconnected = on_disconnected.clone();
+
+                async move {
+                    info!("Disconnected from server: {:?}", reason);
+                    if let Some(ref callback) = on_disconnected {
+                        callback(&reason);
+                    }
+                    let should_reconnect = reason.should_reconnect();
+                    *disconnect_reason.write().await = Some(reason);
+
+                    // 按关闭码策略停止自动重连 / Stop auto-reconnect per close code policy
+                    if !should_reconnect {
+                        if let Err(e) = client.disconnect().await {
+                            error!("Failed to stop reconnecting: {}", e);
+                        }
+                    }
+                }
+                .boxed()
+            })
             .on_any(move |event, payload, client| {
                 // 只处理自定义事件
                 // Only handle custom events
@@ -71,15 +336,23 @@ impl SmcpComputerClient {
                         let manager = manager_clone.clone();
                         let computer_name = computer_name_clone.clone();
                         let office_id = office_id_clone.clone();
+                        let pending_calls = pending_calls.clone();
+                        let max_timeout_secs = max_timeout_secs.load(Ordering::Relaxed);
+                        let result_pipeline = result_pipeline_clone.clone();
                         let client_clone = client.clone();
                         let payload_clone = payload.clone();
 
-                        async move {
+                        // 在独立任务中执行，以便调用期间仍能收到取消通知
+                        // Run in its own task so cancel notifications are received during the call
+                        tokio::spawn(async move {
                             match Self::handle_tool_call_with_ack(
                                 payload,
                                 manager,
                                 computer_name,
                                 office_id,
+                                pending_calls,
+                                max_timeout_secs,
+                                result_pipeline.clone(),
                                 client_clone,
                             )
                             .await
@@ -95,11 +368,14 @@ impl SmcpComputerClient {
                                     error!("Error handling tool call: {}", e);
                                     // 尝试返回错误响应 / Try to return error response
                                     if let Ok((Some(id), _)) = Self::extract_ack_id(payload_clone) {
+                                        // 按错误详细程度决定是否返回详情 / The error verbosity decides whether details are returned
+                                        let message =
+                                            result_pipeline.read().await.error_message(&e);
                                         let error_response = serde_json::json!({
                                             "isError": true,
                                             "content": [],
                                             "structuredContent": {
-                                                "error": e.to_string(),
+                                                "error": message,
                                                 "error_type": "ComputerError"
                                             }
                                         });
@@ -107,8 +383,16 @@ impl SmcpComputerClient {
                                     }
                                 }
                             }
-                        }
-                        .boxed()
+                        });
+                        async {}.boxed()
+                    }
+                    NOTIFY_TOOL_CALL_CANCEL => {
+                        Self::handle_tool_call_cancel(payload, &pending_calls);
+                        async {}.boxed()
+                    }
+                    NOTIFY_SERVER_HELLO => {
+                        Self::handle_server_hello(payload, &max_timeout_secs);
+                        async {}.boxed()
                     }
                     CLIENT_GET_TOOLS => {
                         let manager = manager_clone.clone();
@@ -141,18 +425,17 @@ impl SmcpComputerClient {
                         .boxed()
                     }
                     CLIENT_GET_CONFIG => {
-                        let manager = manager_clone.clone();
+                        let config_source = config_source_clone.clone();
                         let computer_name = computer_name_clone.clone();
                         let office_id = office_id_clone.clone();
-                        let client_clone = client.clone();
+                        let payload_clone = payload.clone();
 
                         async move {
                             match Self::handle_get_config_with_ack(
                                 payload,
-                                manager,
+                                config_source,
                                 computer_name,
                                 office_id,
-                                client_clone,
                             )
                             .await
                             {
@@ -165,6 +448,53 @@ impl SmcpComputerClient {
                                 }
                                 Err(e) => {
                                     error!("Error handling get config: {}", e);
+                                    // 以结构化错误应答 / Answer with a structured error
+                                    if let Ok((Some(id), _)) = Self::extract_ack_id(payload_clone) {
+                                        let error_response = serde_json::json!(ErrorPayload::new(
+                                            error_codes::INVALID_REQUEST,
+                                            "invalid_request",
+                                            e.to_string(),
+                                        ));
+                                        let _ = client.ack_with_id(id, error_response).await;
+                                    }
+                                }
+                            }
+                        }
+                        .boxed()
+                    }
+                    CLIENT_READ_RESOURCE => {
+                        let result_pipeline = result_pipeline_clone.clone();
+                        let computer_name = computer_name_clone.clone();
+                        let office_id = office_id_clone.clone();
+                        let payload_clone = payload.clone();
+
+                        async move {
+                            match Self::handle_read_resource_with_ack(
+                                payload,
+                                result_pipeline,
+                                computer_name,
+                                office_id,
+                            )
+                            .await
+                            {
+                                Ok((ack_id, response)) => {
+                                    if let Some(id) = ack_id {
+                                        if let Err(e) = client.ack_with_id(id, response).await {
+                                            error!("Failed to send ack: {}", e);
+                                        }
+                                    }
+                                }
+                                Err(e) => {
+                                    error!("Error handling read resource: {}", e);
+                                    // 以结构化错误应答 / Answer with a structured error
+                                    if let Ok((Some(id), _)) = Self::extract_ack_id(payload_clone) {
+                                        let error_response = serde_json::json!(ErrorPayload::new(
+                                            error_codes::INVALID_REQUEST,
+                                            "invalid_request",
+                                            e.to_string(),
+                                        ));
+                                        let _ = client.ack_with_id(id, error_response).await;
+                                    }
                                 }
                             }
                         }
@@ -174,6 +504,7 @@ impl SmcpComputerClient {
                         let manager = manager_clone.clone();
                         let computer_name = computer_name_clone.clone();
                         let office_id = office_id_clone.clone();
+                        let tool_history = tool_history_clone.clone();
                         let client_clone = client.clone();
 
                         async move {
@@ -182,6 +513,7 @@ impl SmcpComputerClient {
                                 manager,
                                 computer_name,
                                 office_id,
+                                tool_history,
                                 client_clone,
                             )
                             .await
@@ -200,6 +532,124 @@ impl SmcpComputerClient {
                         }
                         .boxed()
                     }
+                    CLIENT_GET_PROMPTS => {
+                        let manager = manager_clone.clone();
+                        let computer_name = computer_name_clone.clone();
+                        let office_id = office_id_clone.clone();
+                        let client_clone = client.clone();
+                        let payload_clone = payload.clone();
+
+                        async move {
+                            match Self::handle_get_prompts_with_ack(
+                                payload,
+                                manager,
+                                computer_name,
+                                office_id,
+                                client_clone,
+                            )
+                            .await
+                            {
+                                Ok((ack_id, response)) => {
+                                    if let Some(id) = ack_id {
+                                        if let Err(e) = client.ack_with_id(id, response).await {
+                                            error!("Failed to send ack: {}", e);
+                                        }
+                                    }
+                                }
+                                Err(e) => {
+                                    error!("Error handling get prompts: {}", e);
+                                    // 以结构化错误应答 / Answer with a structured error
+                                    if let Ok((Some(id), _)) = Self::extract_ack_id(payload_clone) {
+                                        let error_response = serde_json::json!(ErrorPayload::new(
+                                            error_codes::INVALID_REQUEST,
+                                            "invalid_request",
+                                            e.to_string(),
+                                        ));
+                                        let _ = client.ack_with_id(id, error_response).await;
+                                    }
+                                }
+                            }
+                        }
+                        .boxed()
+                    }
+                    CLIENT_GET_RESOURCE_TEMPLATES => {
+                        let manager = manager_clone.clone();
+                        let computer_name = computer_name_clone.clone();
+                        let office_id = office_id_clone.clone();
+                        let payload_clone = payload.clone();
+
+                        async move {
+                            match Self::handle_get_resource_templates_with_ack(
+                                payload,
+                                manager,
+                                computer_name,
+                                office_id,
+                            )
+                            .await
+                            {
+                                Ok((ack_id, response)) => {
+                                    if let Some(id) = ack_id {
+                                        if let Err(e) = client.ack_with_id(id, response).await {
+                                            error!("Failed to send ack: {}", e);
+                                        }
+                                    }
+                                }
+                                Err(e) => {
+                                    error!("Error handling get resource templates: {}", e);
+                                    // 以结构化错误应答 / Answer with a structured error
+                                    if let Ok((Some(id), _)) = Self::extract_ack_id(payload_clone) {
+                                        let error_response = serde_json::json!(ErrorPayload::new(
+                                            error_codes::INVALID_REQUEST,
+                                            "invalid_request",
+                                            e.to_string(),
+                                        ));
+                                        let _ = client.ack_with_id(id, error_response).await;
+                                    }
+                                }
+                            }
+                        }
+                        .boxed()
+                    }
+                    CLIENT_GET_PROMPT => {
+                        let manager = manager_clone.clone();
+                        let computer_name = computer_name_clone.clone();
+                        let office_id = office_id_clone.clone();
+                        let client_clone = client.clone();
+                        let payload_clone = payload.clone();
+
+                        async move {
+                            match Self::handle_get_prompt_with_ack(
+                                payload,
+                                manager,
+                                computer_name,
+                                office_id,
+                                client_clone,
+                            )
+                            .await
+                            {
+                                Ok((ack_id, response)) => {
+                                    if let Some(id) = ack_id {
+                                        if let Err(e) = client.ack_with_id(id, response).await {
+                                            error!("Failed to send ack: {}", e);
+                                        }
+                                    }
+                                }
+                                Err(e) => {
+                                    error!("Error handling get prompt: {}", e);
+                                    // 以结构化错误应答 / Answer with a structured error
+                                    if let Ok((Some(id), _)) = Self::extract_ack_id(payload_clone) {
+                                        let error_response = serde_json::json!(ErrorPayload::new(
+                                            error_codes::INVALID_REQUEST,
+                                            "invalid_request",
+                                            e.to_string(),
+                                        ));
+                                        let _ = client.ack_with_id(id, error_response).await;
+                                    }
+                                }
+                            }
+                        }
+                        .boxed()
+                    }
                     _ => {
                         debug!("Unhandled event: {}", event_str);
                         async {}.boxed()
@@ -208,7 +658,20 @@ impl SmcpComputerClient {
             })
             .connect()
             .await
-            .map_err(|e| ComputerError::SocketIoError(format!("Failed to connect: {}", e)))?;
+            .map_err(|e| match extract_4008_from_error(&e.to_string()) {
+                // 服务器因协议版本不兼容拒绝握手 / The server rejected the handshake for an incompatible protocol version
+                Some(payload) => ComputerError::ProtocolError(format!(
+                    "Protocol version mismatch ({}): {}",
+                    payload.code, payload.message
+                )),
+                None => ComputerError::ConnectionError(format!("Failed to connect: {}", e)),
+            })?;
+        *client_slot.lock().unwrap() = Some(client.clone());
+        {
+            let mut info = connection.write().unwrap();
+            info.connect_count += 1;
+            info.connected_at = Utc::now();
+        }
 
         info!(
             "Connected to SMCP server at {} with computer name: {}",
@@ -219,9 +682,110 @@ impl SmcpComputerClient {
             client,
             computer_name,
             office_id,
+            disconnect_reason,
+            reconnect: tracker,
+            connection,
+            handshake,
+            config_source,
+            result_pipeline,
+            tool_history,
         })
     }
 
+    /// 设置应答 `client:get_config` 的配置来源 / Set the config source answering `client:get_config`
+    pub async fn set_config_source(&self, source: ComputerConfigSource) {
+        *self.config_source.write().await = Some(source);
+    }
+
+    /// 设置工具调用结果的后处理 / Set the post-processing applied to tool call results
+    pub async fn set_result_pipeline(&self, pipeline: ResultPipeline) {
+        *self.result_pipeline.write().await = pipeline;
+    }
+
+    /// 设置桌面排序使用的工具调用历史 / Set the tool call history used for desktop ordering
+    pub async fn set_tool_history(&self, history: ToolHistory) {
+        *self.tool_history.write().await = history;
+    }
+
+    /// 记录一次重连尝试，首次耗尽时触发回调并返回已尝试次数
+    /// Record a reconnect attempt; on first exhaustion fire the callback and return the attempt count
+    fn record_reconnect_attempt(
+        tracker: &std::sync::Mutex<ReconnectTracker>,
+        on_exhausted: &Option<ReconnectExhaustedCallback>,
+    ) -> Option<u32> {
+        let mut tracker = tracker.lock().unwrap();
+        if tracker.state() == ReconnectState::Failed {
+            return None;
+        }
+        match tracker.on_attempt() {
+            ReconnectState::Failed => {
+                let attempts = tracker.failed_attempts();
+                error!("Gave up reconnecting after {} attempts", attempts);
+                if let Some(callback) = on_exhausted {
+                    callback(attempts);
+                }
+                Some(attempts)
+            }
+            state => {
+                info!("Reconnecting to server: {:?}", state);
+                None
+            }
+        }
+    }
+
+    /// 重连后重新加入之前所在的办公室并重新上报工具列表；显式离开后不会重新加入
+    /// Rejoin the previous office after a reconnect and re-announce the tool list; nothing is
+    /// rejoined after an explicit leave
+    async fn rejoin_office(
+        client: Client,
+        office_id: Arc<RwLock<Option<String>>>,
+        handshake: HandshakeConfig,
+        computer_name: String,
+    ) {
+        let Some(current) = office_id.read().await.clone() else {
+            return;
+        };
+        info!("Rejoining office {} after reconnect", current);
+
+        let payload = handshake.join_office_payload(&current, &computer_name);
+        let joined = Self::call_on(&client, SERVER_JOIN_OFFICE, payload, Some(10))
+            .await
+            .and_then(|values| {
+                Self::decode_ack::<(bool, Option<String>)>(SERVER_JOIN_OFFICE, values)
+            });
+        match joined {
+            Ok((true, _)) => {}
+            Ok((false, error_msg)) => {
+                warn!(
+                    "Failed to rejoin office {}: {}",
+                    current,
+                    error_msg.as_deref().unwrap_or("Unknown error")
+                );
+                *office_id.write().await = None;
+                return;
+            }
+            Err(e) => {
+                warn!("Failed to rejoin office {}: {}", current, e);
+                return;
+            }
+        }
+
+        let req_data = serde_json::json!({ "computer": computer_name });
+        if let Err(e) = client
+            .emit(SERVER_UPDATE_TOOL_LIST, Payload::Text(vec![req_data], None))
+            .await
+        {
+            warn!("Failed to emit tool list update after rejoin: {}", e);
+        }
+        info!("Rejoined office {} after reconnect", current);
+    }
+
+    /// 获取重连状态，耗尽后为 `Failed` 且不再改变
+    /// Get the reconnect state; stays `Failed` once attempts are exhausted
+    pub fn reconnect_state(&self) -> ReconnectState {
+        self.reconnect.lock().unwrap().state()
+    }
+
     /// 加入Office（Socket.IO Room）
     /// Join an Office (Socket.IO Room)
     pub async fn join_office(&self, office_id: &str) -> ComputerResult<()> {
@@ -231,62 +795,27 @@ impl SmcpComputerClient {
         // Set office_id first
         *self.office_id.write().await = Some(office_id.to_string());
 
-        let req_data = serde_json::json!({
-            "office_id": office_id,
-            "role": "computer",
-            "name": self.computer_name
-        });
-
-        // 使用call方法等待服务器响应
-        // Use call method to wait for server response
-        match self.call(SERVER_JOIN_OFFICE, req_data, Some(10)).await {
-            Ok(response) => {
-                // 服务器返回的是 (bool, Option<String>) 元组序列化后的数组
-                // Server returns serialized array of (bool, Option<String>) tuple
-                debug!("Join office response: {:?}", response);
-
-                // 检查响应是否包含嵌套数组
-                // Check if response contains nested array
-                let actual_response = if response.len() == 1 {
-                    if let Some(arr) = response.first().and_then(|v| v.as_array()) {
-                        arr.to_vec()
-                    } else {
-                        response
-                    }
-                } else {
-                    response
-                };
+        let req_data = self
+            .handshake
+            .join_office_payload(office_id, &self.computer_name);
 
-                if !actual_response.is_empty() {
-                    if let Some(success) = actual_response.first().and_then(|v| v.as_bool()) {
-                        if success {
-                            info!("Successfully joined office: {}", office_id);
-                            Ok(())
-                        } else {
-                            // 加入失败，重置office_id / Reset office_id on failure
-                            *self.office_id.write().await = None;
-                            let error_msg = actual_response
-                                .get(1)
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("Unknown error");
-                            Err(ComputerError::SocketIoError(format!(
-                                "Failed to join office: {}",
-                                error_msg
-                            )))
-                        }
-                    } else {
-                        *self.office_id.write().await = None;
-                        Err(ComputerError::SocketIoError(format!(
-                            "Invalid response format from server: {:?}",
-                            actual_response
-                        )))
-                    }
-                } else {
-                    *self.office_id.write().await = None;
-                    Err(ComputerError::SocketIoError(
-                        "Empty response from server".to_string(),
-                    ))
-                }
+        // 服务器返回 (bool, Option<String>) 元组
+        // Server returns a (bool, Option<String>) tuple
+        let response = self
+            .call_typed::<(bool, Option<String>)>(SERVER_JOIN_OFFICE, req_data, Some(10))
+            .await;
+        match response {
+            Ok((true, _)) => {
+                info!("Successfully joined office: {}", office_id);
+                Ok(())
+            }
+            Ok((false, error_msg)) => {
+                // 加入失败，重置office_id / Reset office_id on failure
+                *self.office_id.write().await = None;
+                Err(ComputerError::PermissionError(format!(
+                    "Failed to join office: {}",
+                    error_msg.as_deref().unwrap_or("Unknown error")
+                )))
             }
             Err(e) => {
                 *self.office_id.write().await = None;
@@ -309,14 +838,29 @@ impl SmcpComputerClient {
     /// 离开Office
     /// Leave an Office
     pub async fn leave_office(&self, office_id: &str) -> ComputerResult<()> {
-        debug!("Leaving office: {}", office_id);
+        self.leave_office_with_reason(office_id, None).await
+    }
 
-        let req_data = serde_json::json!({
+    /// 携带离开原因离开Office，原因随离开通知广播给办公室成员
+    /// Leave an Office with a reason that is broadcast to the office along with the leave notification
+    pub async fn leave_office_with_reason(
+        &self,
+        office_id: &str,
+        reason: Option<&str>,
+    ) -> ComputerResult<()> {
+        debug!("Leaving office: {} (reason: {:?})", office_id, reason);
+
+        let mut req_data = serde_json::json!({
             "office_id": office_id
         });
+        if let Some(reason) = reason {
+            req_data["reason"] = serde_json::Value::String(reason.to_string());
+        }
 
-        self.emit(SERVER_LEAVE_OFFICE, req_data).await?;
+        // 先清除office_id，离开请求发送失败时重连后也不会重新加入
+        // Clear office_id first so a reconnect never rejoins, even when the leave emit fails
         *self.office_id.write().await = None;
+        self.emit(SERVER_LEAVE_OFFICE, req_data).await?;
 
         info!("Left office: {}", office_id);
         Ok(())
@@ -350,6 +894,44 @@ impl SmcpComputerClient {
         Ok(())
     }
 
+    /// 上报工具调用进度，由服务器转发为 `notify:tool_progress`
+    /// Report tool call progress, relayed by the server as `notify:tool_progress`
+    ///
+    /// `content` 为工具已产生的部分结果，Agent 通过 `tool_call_stream` 按序逐段接收。
+    /// 配置了结果转换时不转发部分结果，以免绕过转换，Agent 仍会在最终结果中收到经转换的完整内容。
+    /// `content` holds partial results the tool has produced so far; the Agent receives them
+    /// in order through `tool_call_stream`. They are not forwarded when result transforms are
+    /// configured, so the transforms cannot be bypassed; the Agent still receives the
+    /// transformed full content in the final result.
+    pub async fn emit_tool_progress(
+        &self,
+        req_id: &str,
+        progress: f64,
+        total: Option<f64>,
+        message: Option<String>,
+        mut content: Vec<Value>,
+    ) -> ComputerResult<()> {
+        let office_id = self.office_id.read().await;
+        if office_id.is_some() {
+            if !content.is_empty() && self.result_pipeline.read().await.has_transforms() {
+                debug!("Dropping partial content of tool call {}", req_id);
+                content.clear();
+            }
+            let notification = ToolProgressNotification {
+                computer: self.computer_name.clone(),
+                req_id: ReqId::from_string(req_id.to_string()),
+                progress,
+                total,
+                message,
+                content,
+            };
+            self.emit(SERVER_TOOL_PROGRESS, serde_json::to_value(notification)?)
+                .await?;
+            debug!("Emitted progress {} for tool call {}", progress, req_id);
+        }
+        Ok(())
+    }
+
     /// 发送桌面更新通知
     /// Emit desktop update notification
     pub async fn emit_update_desktop(&self) -> ComputerResult<()> {
@@ -382,7 +964,7 @@ impl SmcpComputerClient {
         self.client
             .emit(event, Payload::Text(vec![data], None))
             .await
-            .map_err(|e| ComputerError::SocketIoError(format!("Failed to emit {}: {}", event, e)))
+            .map_err(|e| ComputerError::ConnectionError(format!("Failed to emit {}: {}", event, e)))
     }
 
     /// 发送事件并等待响应
@@ -403,6 +985,17 @@ impl SmcpComputerClient {
             ));
         }
 
+        Self::call_on(&self.client, event, data, timeout_secs).await
+    }
+
+    /// 在给定客户端上发送事件并等待响应
+    /// Emit event on the given client and wait for response
+    async fn call_on(
+        client: &Client,
+        event: &str,
+        data: Value,
+        timeout_secs: Option<u64>,
+    ) -> ComputerResult<Vec<Value>> {
         let timeout = std::time::Duration::from_secs(timeout_secs.unwrap_or(30));
         debug!("Calling event: {} with timeout {:?}", event, timeout);
 
@@ -416,11 +1009,11 @@ impl SmcpComputerClient {
             async {}.boxed()
         };
 
-        self.client
+        client
             .emit_with_ack(event, Payload::Text(vec![data], None), timeout, callback)
             .await
             .map_err(|e| {
-                ComputerError::SocketIoError(format!("Failed to call {}: {}", event, e))
+                ComputerError::ConnectionError(format!("Failed to call {}: {}", event, e))
             })?;
 
         // 使用 tokio::time::timeout 来确保 rx.await 不会无限期等待
@@ -438,31 +1031,62 @@ impl SmcpComputerClient {
                         // 尝试解析字符串为JSON数组
                         // Try to parse string as JSON array
                         let parsed: Vec<Value> = serde_json::from_str(&s).map_err(|e| {
-                            ComputerError::SocketIoError(format!("Failed to parse response: {}", e))
+                            ComputerError::ProtocolError(format!("Failed to parse response: {}", e))
                         })?;
                         debug!("Received parsed response: {:?}", parsed);
                         Ok(parsed)
                     }
-                    Payload::Binary(_, _) => Err(ComputerError::SocketIoError(
+                    Payload::Binary(_, _) => Err(ComputerError::ProtocolError(
                         "Binary response not supported".to_string(),
                     )),
                 }
             }
             Ok(Err(_)) => {
                 error!("Channel closed while calling event: {}", event);
-                Err(ComputerError::SocketIoError(
+                Err(ComputerError::ConnectionError(
                     "Channel closed while waiting for response".to_string(),
                 ))
             }
             Err(_) => {
                 error!("Timeout while calling event: {}", event);
-                Err(ComputerError::SocketIoError(
+                Err(ComputerError::TimeoutError(
                     "Timeout while waiting for response".to_string(),
                 ))
             }
         }
     }
 
+    /// 发送事件并将响应反序列化为指定类型
+    /// Emit event and deserialize the response into the given type
+    async fn call_typed<T: DeserializeOwned>(
+        &self,
+        event: &str,
+        data: Value,
+        timeout_secs: Option<u64>,
+    ) -> ComputerResult<T> {
+        let values = self.call(event, data, timeout_secs).await?;
+        debug!("{} response: {:?}", event, values);
+        Self::decode_ack(event, values)
+    }
+
+    /// 解析ACK参数：单个参数直接反序列化（含被包裹成嵌套数组的元组），
+    /// 否则将多个参数视为一个元组
+    /// Decode ACK arguments: a single argument is deserialized directly (including a tuple
+    /// wrapped as a nested array), otherwise multiple arguments are treated as one tuple
+    fn decode_ack<T: DeserializeOwned>(event: &str, values: Vec<Value>) -> ComputerResult<T> {
+        if let [value] = values.as_slice() {
+            if let Ok(decoded) = serde_json::from_value(value.clone()) {
+                return Ok(decoded);
+            }
+        }
+        serde_json::from_value(Value::Array(values.clone())).map_err(|e| {
+            ComputerError::ProtocolError(format!(
+                "Invalid response format for {}: {} ({:?})",
+                event, e, values
+            ))
+        })
+    }
+
     /// 处理工具调用事件（带ACK响应）
     /// Handle tool call event (with ACK response)
     async fn handle_tool_call_with_ack(
@@ -470,9 +1094,14 @@ impl SmcpComputerClient {
         manager: Arc<RwLock<Option<MCPServerManager>>>,
         computer_name: String,
         office_id: Arc<RwLock<Option<String>>>,
+        pending_calls: PendingToolCalls,
+        max_timeout_secs: u64,
+        result_pipeline: Arc<RwLock<ResultPipeline>>,
         _client: Client,
     ) -> ComputerResult<(Option<i32>, Value)> {
-        let (ack_id, req) = Self::extract_ack_and_parse::<ToolCallReq>(payload)?;
+        let (ack_id, mut req) = Self::extract_ack_and_parse::<ToolCallReq>(payload)?;
+        // 按服务器公布的上限截断超时 / Clamp the timeout to the server's advertised ceiling
+        req.timeout = i32::try_from(req.timeout_secs(max_timeout_secs)).unwrap_or(i32::MAX);
 
         // 验证office_id和computer_name
         // Validate office_id and computer_name
@@ -490,31 +1119,129 @@ impl SmcpComputerClient {
             )));
         }
 
-        // 执行工具调用 / Execute tool call
-        let result = {
-            let manager_guard = manager.read().await;
-            match manager_guard.as_ref() {
-                Some(mgr) => {
-                    mgr.execute_tool(
-                        &req.tool_name,
-                        req.params,
-                        Some(std::time::Duration::from_secs(req.timeout as u64)),
-                    )
-                    .await?
-                }
-                None => {
-                    return Err(ComputerError::InvalidState(
-                        "MCP Manager not initialized".to_string(),
-                    ));
-                }
-            }
+        let manager_guard = manager.read().await;
+        let mgr = manager_guard.as_ref().ok_or_else(|| {
+            ComputerError::InvalidState("MCP Manager not initialized".to_string())
+        })?;
+        let pipeline = result_pipeline.read().await.clone();
+        let result_value = Self::execute_cancellable(mgr, &req, &pending_calls, &pipeline).await?;
+        Ok((ack_id, result_value))
+    }
+
+    /// 执行工具调用，并按 req_id 登记取消令牌；收到取消通知时中止并返回 `isError` 结果。
+    /// 中止会丢弃 MCP 请求，从而通知 MCP 服务器取消。
+    /// Execute a tool call with its cancellation token registered under req_id; on a cancel
+    /// notification the call is aborted and an `isError` result is returned. Aborting drops the
+    /// MCP request, which notifies the MCP server to cancel it.
+    async fn execute_cancellable(
+        mgr: &MCPServerManager,
+        req: &ToolCallReq,
+        pending_calls: &PendingToolCalls,
+        pipeline: &ResultPipeline,
+    ) -> ComputerResult<Value> {
+        let req_id = req.base.req_id.0.clone();
+        let token = CancellationToken::new();
+        pending_calls
+            .lock()
+            .unwrap()
+            .insert(req_id.clone(), token.clone());
+        let outcome = tokio::select! {
+            result = mgr.execute_tool_req(req) => Some(result),
+            _ = token.cancelled() => None,
+        };
+        pending_calls.lock().unwrap().remove(&req_id);
+
+        let Some(result) = outcome else {
+            info!("Tool call {} cancelled", req_id);
+            let cancelled = CallToolResult {
+                content: vec![Content::Text {
+                    text: format!("Tool call {} cancelled", req_id),
+                }],
+                is_error: true,
+                structured_content: None,
+                meta: None,
+            };
+            return serde_json::to_value(cancelled).map_err(ComputerError::SerializationError);
+        };
+        let (result, call_info) = result?;
+        let ctx = CallContext {
+            req_id: req_id.clone(),
+            server: call_info.server.clone(),
+            tool: mgr.original_tool_name(&req.tool_name).await,
+            requested_tool: req.tool_name.clone(),
+            parameters: req.params.clone(),
         };
+        let result = pipeline.transform(&ctx, result).await;
+        let result = pipeline.finish(&req_id, result).await?;
 
-        let result_value =
+        let mut result_value =
             serde_json::to_value(result).map_err(ComputerError::SerializationError)?;
+        // 附加调用信息 / Attach call info
+        if let Value::Object(map) = &mut result_value {
+            map.insert(
+                "call_info".to_string(),
+                serde_json::to_value(&call_info).map_err(ComputerError::SerializationError)?,
+            );
+        }
 
-        info!("Tool call executed successfully: {}", req.tool_name);
-        Ok((ack_id, result_value))
+        info!(
+            "Tool call executed successfully: {} (server: {}, {}ms, cache_hit: {})",
+            req.tool_name, call_info.server, call_info.elapsed_ms, call_info.cache_hit
+        );
+        Ok(result_value)
+    }
+
+    /// 处理工具调用取消通知 / Handle tool call cancel notification
+    fn handle_tool_call_cancel(payload: Payload, pending_calls: &PendingToolCalls) {
+        let data = match payload {
+            Payload::Text(values, _) => values
+                .into_iter()
+                .next()
+                .and_then(|value| serde_json::from_value::<AgentCallData>(value).ok()),
+            _ => None,
+        };
+        let Some(data) = data else {
+            warn!("Ignoring malformed tool call cancel notification");
+            return;
+        };
+        if Self::cancel_pending_call(pending_calls, &data.req_id.0) {
+            info!(
+                "Cancelling tool call {} from agent {}",
+                data.req_id.0, data.agent
+            );
+        } else {
+            debug!("No running tool call for cancel {}", data.req_id.0);
+        }
+    }
+
+    /// 取消指定 req_id 的进行中调用，返回是否找到 / Cancel the in-flight call for req_id, returning whether it was found
+    fn cancel_pending_call(pending_calls: &PendingToolCalls, req_id: &str) -> bool {
+        match pending_calls.lock().unwrap().remove(req_id) {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 处理服务器能力通知，记录超时上限 / Handle the server hello, recording the timeout ceiling
+    fn handle_server_hello(payload: Payload, max_timeout_secs: &AtomicU64) {
+        let capabilities = match payload {
+            Payload::Text(values, _) => values
+                .into_iter()
+                .next()
+                .and_then(|value| serde_json::from_value::<ServerCapabilities>(value).ok()),
+            _ => None,
+        };
+        let Some(capabilities) = capabilities else {
+            warn!("Ignoring malformed server hello");
+            return;
+        };
+        if let Some(max) = capabilities.max_timeout_secs {
+            debug!("Server timeout ceiling: {}s", max);
+            max_timeout_secs.store(max, Ordering::Relaxed);
+        }
     }
 
     /// 处理获取工具列表事件（带ACK响应）
@@ -548,21 +1275,7 @@ impl SmcpComputerClient {
         let tools: Vec<smcp::SMCPTool> = {
             let manager_guard = manager.read().await;
             match manager_guard.as_ref() {
-                Some(mgr) => {
-                    // 转换Tool为SMCPTool
-                    // Convert Tool to SMCPTool
-                    let tool_list = mgr.list_available_tools().await;
-                    tool_list
-                        .into_iter()
-                        .map(|tool| smcp::SMCPTool {
-                            name: tool.name,
-                            description: tool.description,
-                            params_schema: tool.input_schema,
-                            return_schema: None,
-                            meta: None,
-                        })
-                        .collect()
-                }
+                Some(mgr) => mgr.list_available_smcp_tools().await,
                 None => {
                     return Err(ComputerError::InvalidState(
                         "MCP Manager not initialized".to_string(),
@@ -584,16 +1297,16 @@ impl SmcpComputerClient {
         Ok((ack_id, serde_json::to_value(response)?))
     }
 
-    /// 处理获取配置事件（带ACK响应）
-    /// Handle get config event (with ACK response)
-    async fn handle_get_config_with_ack(
+    /// 处理获取提示词列表事件（带ACK响应）
+    /// Handle get prompts event (with ACK response)
+    async fn handle_get_prompts_with_ack(
         payload: Payload,
         manager: Arc<RwLock<Option<MCPServerManager>>>,
         computer_name: String,
         office_id: Arc<RwLock<Option<String>>>,
         _client: Client,
     ) -> ComputerResult<(Option<i32>, Value)> {
-        let (ack_id, req) = Self::extract_ack_and_parse::<GetComputerConfigReq>(payload)?;
+        let (ack_id, req) = Self::extract_ack_and_parse::<GetPromptsReq>(payload)?;
 
         // 验证office_id和computer_name
         // Validate office_id and computer_name
@@ -611,16 +1324,138 @@ impl SmcpComputerClient {
             )));
         }
 
-        // 获取配置 / Get config
-        let servers = {
+        // 获取提示词列表 / Get prompts list
+        let prompts: Vec<smcp::SMCPPrompt> = {
+            let manager_guard = manager.read().await;
+            match manager_guard.as_ref() {
+                Some(mgr) => mgr
+                    .list_available_prompts()
+                    .await
+                    .into_iter()
+                    .map(|prompt| smcp::SMCPPrompt {
+                        name: prompt.name,
+                        description: prompt.description,
+                        arguments: prompt.arguments.map(|args| {
+                            args.into_iter()
+                                .map(|arg| smcp::SMCPPromptArgument {
+                                    name: arg.name,
+                                    description: arg.description,
+                                    required: arg.required,
+                                })
+                                .collect()
+                        }),
+                    })
+                    .collect(),
+                None => {
+                    return Err(ComputerError::InvalidState(
+                        "MCP Manager not initialized".to_string(),
+                    ));
+                }
+            }
+        };
+
+        info!(
+            "Returned {} prompts for agent {}",
+            prompts.len(),
+            req.base.agent
+        );
+
+        let response = GetPromptsRet {
+            prompts,
+            req_id: req.base.req_id,
+        };
+        Ok((ack_id, serde_json::to_value(response)?))
+    }
+
+    /// 处理获取资源模板列表事件（带ACK响应）
+    /// Handle get resource templates event (with ACK response)
+    async fn handle_get_resource_templates_with_ack(
+        payload: Payload,
+        manager: Arc<RwLock<Option<MCPServerManager>>>,
+        computer_name: String,
+        office_id: Arc<RwLock<Option<String>>>,
+    ) -> ComputerResult<(Option<i32>, Value)> {
+        let (ack_id, req) = Self::extract_ack_and_parse::<GetResourceTemplatesReq>(payload)?;
+
+        // 验证office_id和computer_name
+        // Validate office_id and computer_name
+        let current_office_id = office_id.read().await;
+        if current_office_id.as_ref() != Some(&req.base.agent) {
+            return Err(ComputerError::ValidationError(format!(
+                "Office ID mismatch: expected {:?}, got {}",
+                current_office_id, req.base.agent
+            )));
+        }
+        if computer_name != req.computer {
+            return Err(ComputerError::ValidationError(format!(
+                "Computer name mismatch: expected {}, got {}",
+                computer_name, req.computer
+            )));
+        }
+
+        // 获取资源模板列表 / Get resource templates list
+        let resource_templates: Vec<smcp::SMCPResourceTemplate> = {
             let manager_guard = manager.read().await;
             match manager_guard.as_ref() {
-                Some(mgr) => {
-                    // 获取服务器状态并转换为配置格式
-                    // Get server status and convert to config format
-                    let status = mgr.get_server_status().await;
-                    serde_json::json!(status)
+                Some(mgr) => mgr
+                    .list_available_resource_templates()
+                    .await
+                    .into_iter()
+                    .map(smcp::SMCPResourceTemplate::from)
+                    .collect(),
+                None => {
+                    return Err(ComputerError::InvalidState(
+                        "MCP Manager not initialized".to_string(),
+                    ));
                 }
+            }
+        };
+
+        info!(
+            "Returned {} resource templates for agent {}",
+            resource_templates.len(),
+            req.base.agent
+        );
+
+        let response = GetResourceTemplatesRet {
+            resource_templates,
+            req_id: req.base.req_id,
+        };
+        Ok((ack_id, serde_json::to_value(response)?))
+    }
+
+    /// 处理获取提示词内容事件（带ACK响应）
+    /// Handle get prompt event (with ACK response)
+    async fn handle_get_prompt_with_ack(
+        payload: Payload,
+        manager: Arc<RwLock<Option<MCPServerManager>>>,
+        computer_name: String,
+        office_id: Arc<RwLock<Option<String>>>,
+        _client: Client,
+    ) -> ComputerResult<(Option<i32>, Value)> {
+        let (ack_id, req) = Self::extract_ack_and_parse::<GetPromptReq>(payload)?;
+
+        // 验证office_id和computer_name
+        // Validate office_id and computer_name
+        let current_office_id = office_id.read().await;
+        if current_office_id.as_ref() != Some(&req.base.agent) {
+            return Err(ComputerError::ValidationError(format!(
+                "Office ID mismatch: expected {:?}, got {}",
+                current_office_id, req.base.agent
+            )));
+        }
+        if computer_name != req.computer {
+            return Err(ComputerError::ValidationError(format!(
+                "Computer name mismatch: expected {}, got {}",
+                computer_name, req.computer
+            )));
+        }
+
+        // 获取提示词内容 / Get prompt content
+        let result = {
+            let manager_guard = manager.read().await;
+            match manager_guard.as_ref() {
+                Some(mgr) => mgr.get_prompt(&req.name, req.arguments).await?,
                 None => {
                     return Err(ComputerError::InvalidState(
                         "MCP Manager not initialized".to_string(),
@@ -628,21 +1463,115 @@ impl SmcpComputerClient {
                 }
             }
         };
-        let inputs = None; // 暂时返回None / Return None for now
 
-        let response = GetComputerConfigRet { servers, inputs };
+        let messages = result
+            .messages
+            .into_iter()
+            .map(serde_json::to_value)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        info!(
+            "Returned prompt '{}' for agent {}",
+            req.name, req.base.agent
+        );
+
+        let response = GetPromptRet {
+            description: result.description,
+            messages,
+            req_id: req.base.req_id,
+        };
+        Ok((ack_id, serde_json::to_value(response)?))
+    }
+
+    /// 处理获取配置事件（带ACK响应）
+    /// Handle get config event (with ACK response)
+    async fn handle_get_config_with_ack(
+        payload: Payload,
+        config_source: Arc<RwLock<Option<ComputerConfigSource>>>,
+        computer_name: String,
+        office_id: Arc<RwLock<Option<String>>>,
+    ) -> ComputerResult<(Option<i32>, Value)> {
+        let (ack_id, req) = Self::extract_ack_and_parse::<GetComputerConfigReq>(payload)?;
+
+        // 验证office_id和computer_name
+        // Validate office_id and computer_name
+        let current_office_id = office_id.read().await;
+        if current_office_id.as_ref() != Some(&req.base.agent) {
+            return Err(ComputerError::ValidationError(format!(
+                "Office ID mismatch: expected {:?}, got {}",
+                current_office_id, req.base.agent
+            )));
+        }
+        if computer_name != req.computer {
+            return Err(ComputerError::ValidationError(format!(
+                "Computer name mismatch: expected {}, got {}",
+                computer_name, req.computer
+            )));
+        }
+
+        // 获取配置 / Get config
+        let response = match *config_source.read().await {
+            Some(ref source) => source.snapshot().await?,
+            None => {
+                return Err(ComputerError::InvalidState(
+                    "Computer config not attached".to_string(),
+                ))
+            }
+        };
 
         info!("Returned config for agent {}", req.base.agent);
         Ok((ack_id, serde_json::to_value(response)?))
     }
 
+    /// 处理读取资源事件（带ACK响应），用于取回大结果生成的资源链接
+    /// Handle read resource event (with ACK response), used to fetch resource links minted for large results
+    async fn handle_read_resource_with_ack(
+        payload: Payload,
+        result_pipeline: Arc<RwLock<ResultPipeline>>,
+        computer_name: String,
+        office_id: Arc<RwLock<Option<String>>>,
+    ) -> ComputerResult<(Option<i32>, Value)> {
+        let (ack_id, req) = Self::extract_ack_and_parse::<ReadResourceReq>(payload)?;
+
+        // 验证office_id和computer_name
+        // Validate office_id and computer_name
+        let current_office_id = office_id.read().await;
+        if current_office_id.as_ref() != Some(&req.base.agent) {
+            return Err(ComputerError::ValidationError(format!(
+                "Office ID mismatch: expected {:?}, got {}",
+                current_office_id, req.base.agent
+            )));
+        }
+        if computer_name != req.computer {
+            return Err(ComputerError::ValidationError(format!(
+                "Computer name mismatch: expected {}, got {}",
+                computer_name, req.computer
+            )));
+        }
+
+        let read = result_pipeline.read().await.read_resource(&req.uri).await?;
+        let contents = read
+            .contents
+            .iter()
+            .map(serde_json::to_value)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        info!("Returned resource {} for agent {}", req.uri, req.base.agent);
+        let response = ReadResourceRet {
+            contents,
+            req_id: req.base.req_id,
+        };
+        Ok((ack_id, serde_json::to_value(response)?))
+    }
+
     /// 处理获取桌面事件（带ACK响应）
     /// Handle get desktop event (with ACK response)
     async fn handle_get_desktop_with_ack(
         payload: Payload,
-        _manager: Arc<RwLock<Option<MCPServerManager>>>,
+        manager: Arc<RwLock<Option<MCPServerManager>>>,
         computer_name: String,
         office_id: Arc<RwLock<Option<String>>>,
+        tool_history: Arc<RwLock<ToolHistory>>,
         _client: Client,
     ) -> ComputerResult<(Option<i32>, Value)> {
         let (ack_id, req) = Self::extract_ack_and_parse::<GetDesktopReq>(payload)?;
@@ -663,10 +1592,15 @@ impl SmcpComputerClient {
             )));
         }
 
-        // 获取桌面 / Get desktop
-        // TODO: 实现实际的桌面捕获逻辑
-        // TODO: Implement actual desktop capture logic
-        let desktops = Vec::<String>::new(); // 暂时返回空列表 / Return empty list for now
+        // 组合桌面，按最近使用的服务器与窗口优先级排序并截断
+        // Compose the desktop, ordered by recently used servers and window priority, and truncated
+        let windows = match *manager.read().await {
+            Some(ref manager) => manager.list_windows(req.window.as_deref()).await,
+            None => Vec::new(),
+        };
+        let history = desktop_history(&*tool_history.read().await).await;
+        let size = req.desktop_size.map(|size| size.max(0) as usize);
+        let desktops = organize_desktop(windows, size, &history);
 
         let response = GetDesktopRet {
             desktops: Some(desktops),
@@ -697,7 +1631,7 @@ impl SmcpComputerClient {
                 let req = serde_json::from_str(&s).map_err(ComputerError::SerializationError)?;
                 Ok((ack_id, req))
             }
-            Payload::Binary(_, _) => Err(ComputerError::SocketIoError(
+            Payload::Binary(_, _) => Err(ComputerError::ProtocolError(
                 "Binary payload not supported".to_string(),
             )),
         }
@@ -721,7 +1655,7 @@ impl SmcpComputerClient {
         self.client
             .disconnect()
             .await
-            .map_err(|e| ComputerError::SocketIoError(format!("Failed to disconnect: {}", e)))?;
+            .map_err(|e| ComputerError::ConnectionError(format!("Failed to disconnect: {}", e)))?;
         info!("Disconnected from server");
         Ok(())
     }
@@ -732,19 +1666,201 @@ impl SmcpComputerClient {
         self.office_id.read().await.clone()
     }
 
+    /// 获取最近一次断开原因
+    /// Get the last disconnect reason
+    pub async fn last_disconnect_reason(&self) -> Option<DisconnectReason> {
+        self.disconnect_reason.read().await.clone()
+    }
+
+    /// 获取连接信息
+    /// Get connection info
+    pub fn connection_info(&self) -> ConnectionInfo {
+        self.connection.read().unwrap().clone()
+    }
+
     /// 获取连接的 URL
     /// Get connected URL
     pub fn get_url(&self) -> String {
-        // 由于 rust_socketio 的 Client 没有 uri() 方法，返回默认值
-        // Since rust_socketio Client doesn't have uri() method, return default
-        "unknown".to_string()
+        self.connection.read().unwrap().url.clone()
     }
 
     /// 获取连接的 namespace
     /// Get connected namespace
     pub fn get_namespace(&self) -> String {
-        // 从 client 中获取 namespace，如果无法获取则返回默认值
-        // Get namespace from client, return default if unable to get
-        "/smcp".to_string()
+        self.connection.read().unwrap().namespace.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cancel_in_flight_tool_call_by_req_id() {
+        use crate::mcp_clients::testing::{mock_server_config, MockMCPClient};
+        use std::time::Duration;
+
+        let manager = MCPServerManager::new();
+        let slow = MockMCPClient::builder()
+            .tool("slow")
+            .call_delay(Duration::from_secs(30))
+            .build();
+        manager
+            .attach_client(mock_server_config("slow"), Arc::new(slow.clone()))
+            .await
+            .unwrap();
+
+        let req: ToolCallReq = serde_json::from_value(serde_json::json!({
+            "agent": "office-1",
+            "req_id": "req-slow",
+            "computer": "c",
+            "tool_name": "slow",
+            "params": {},
+            "timeout": 60
+        }))
+        .unwrap();
+        let pending_calls: PendingToolCalls = Arc::new(std::sync::Mutex::new(HashMap::new()));
+
+        let pipeline = ResultPipeline::new();
+        let call =
+            SmcpComputerClient::execute_cancellable(&manager, &req, &pending_calls, &pipeline);
+        let cancel = async {
+            while slow.total_calls() == 0 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+            assert!(!SmcpComputerClient::cancel_pending_call(
+                &pending_calls,
+                "req-other"
+            ));
+            assert!(SmcpComputerClient::cancel_pending_call(
+                &pending_calls,
+                "req-slow"
+            ));
+        };
+        let (result, ()) =
+            tokio::time::timeout(Duration::from_secs(5), async { tokio::join!(call, cancel) })
+                .await
+                .expect("cancelled call should return promptly");
+
+        let result: CallToolResult = serde_json::from_value(result.unwrap()).unwrap();
+        assert!(result.is_error);
+        assert!(matches!(
+            &result.content[0],
+            Content::Text { text } if text.contains("cancelled")
+        ));
+        assert!(pending_calls.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_reconnect_exhausted_enters_failed_and_fires_callback() {
+        let tracker = std::sync::Mutex::new(ReconnectTracker::new(Some(2)));
+        let fired = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let fired_clone = fired.clone();
+        let on_exhausted: Option<ReconnectExhaustedCallback> = Some(Arc::new(move |attempts| {
+            fired_clone.lock().unwrap().push(attempts)
+        }));
+
+        for attempt in 1..=2 {
+            assert_eq!(
+                SmcpComputerClient::record_reconnect_attempt(&tracker, &on_exhausted),
+                None
+            );
+            assert_eq!(
+                tracker.lock().unwrap().state(),
+                ReconnectState::Reconnecting { attempt }
+            );
+        }
+
+        assert_eq!(
+            SmcpComputerClient::record_reconnect_attempt(&tracker, &on_exhausted),
+            Some(2)
+        );
+        assert_eq!(tracker.lock().unwrap().state(), ReconnectState::Failed);
+        assert_eq!(*fired.lock().unwrap(), vec![2]);
+
+        // 回调只触发一次 / The callback fires only once
+        assert_eq!(
+            SmcpComputerClient::record_reconnect_attempt(&tracker, &on_exhausted),
+            None
+        );
+        assert_eq!(fired.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_decode_ack_tuple_shapes() {
+        // 元组展开为多个参数 / Tuple spread across ACK arguments
+        let ok: (bool, Option<String>) = SmcpComputerClient::decode_ack(
+            SERVER_JOIN_OFFICE,
+            vec![serde_json::json!(true), Value::Null],
+        )
+        .unwrap();
+        assert_eq!(ok, (true, None));
+
+        // 元组被包裹为嵌套数组 / Tuple wrapped as a nested array
+        let failed: (bool, Option<String>) = SmcpComputerClient::decode_ack(
+            SERVER_JOIN_OFFICE,
+            vec![serde_json::json!([false, "Office is full"])],
+        )
+        .unwrap();
+        assert_eq!(failed, (false, Some("Office is full".to_string())));
+
+        // 单个对象参数 / Single object argument
+        let reason: DisconnectReason = SmcpComputerClient::decode_ack(
+            "server:example",
+            vec![serde_json::json!({"code": 4008, "message": "mismatch"})],
+        )
+        .unwrap();
+        assert_eq!(reason.code, Some(4008));
+
+        let err =
+            SmcpComputerClient::decode_ack::<(bool, Option<String>)>(SERVER_JOIN_OFFICE, vec![])
+                .unwrap_err();
+        assert!(err.to_string().contains(SERVER_JOIN_OFFICE));
+    }
+
+    #[test]
+    fn test_handshake_join_event_matches_default_payload() {
+        let handshake = HandshakeConfig::default();
+        assert_eq!(handshake.identity, IdentityPlacement::JoinEvent);
+        assert_eq!(handshake.connect_auth("c1"), None);
+        assert_eq!(
+            handshake.join_office_payload("office1", "c1"),
+            serde_json::json!({"office_id": "office1", "role": "computer", "name": "c1"})
+        );
+    }
+
+    #[test]
+    fn test_handshake_connect_auth_moves_identity_to_auth() {
+        let handshake = HandshakeConfig {
+            identity: IdentityPlacement::ConnectAuth,
+        };
+        assert_eq!(
+            handshake.connect_auth("c1"),
+            Some(serde_json::json!({"role": "computer", "name": "c1"}))
+        );
+        assert_eq!(
+            handshake.join_office_payload("office1", "c1"),
+            serde_json::json!({"office_id": "office1"})
+        );
+    }
+
+    #[test]
+    fn test_server_hello_sets_timeout_ceiling() {
+        let max_timeout_secs = AtomicU64::new(DEFAULT_MAX_TIMEOUT_SECS);
+        SmcpComputerClient::handle_server_hello(
+            Payload::Text(
+                vec![serde_json::json!({"protocol_version": "1.0", "max_timeout_secs": 5})],
+                None,
+            ),
+            &max_timeout_secs,
+        );
+        assert_eq!(max_timeout_secs.load(Ordering::Relaxed), 5);
+
+        // 旧版服务器不公布上限时保持原值 / Keep the value when an older server omits the ceiling
+        SmcpComputerClient::handle_server_hello(
+            Payload::Text(vec![serde_json::json!({"protocol_version": "1.0"})], None),
+            &max_timeout_secs,
+        );
+        assert_eq!(max_timeout_secs.load(Ordering::Relaxed), 5);
     }
 }
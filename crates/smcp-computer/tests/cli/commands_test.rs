@@ -13,6 +13,7 @@
 
 use std::time::Duration;
 use tokio::time::timeout;
+use smcp_computer::cli::commands::OutputFormat;
 use common::{
     create_command_handler, 
     create_uninitialized_command_handler,
@@ -171,7 +172,7 @@ async fn test_tools_listing() {
     let handler = create_command_handler().await;
     
     // 列出工具（可能为空） / List tools (might be empty)
-    let result = handler.list_tools().await;
+    let result = handler.list_tools(OutputFormat::Text).await;
     assert!(result.is_ok());
 }
 
@@ -211,14 +211,15 @@ async fn test_computer_multiple_boot_up() {
     // 第一次启动 / First boot up
     computer.boot_up().await.unwrap();
 
-    // 第二次启动应该成功（可能重置状态）
-    // Second boot up should succeed (might reset state)
+    // 第二次启动为空操作 / Second boot up is a no-op
     computer.boot_up().await.unwrap();
+    assert!(computer.is_booted().await);
 
     // 第三次启动 / Third boot up
     computer.boot_up().await.unwrap();
 
     computer.shutdown().await.unwrap();
+    assert!(!computer.is_booted().await);
 
     // 关闭后再次启动 / Boot up after shutdown
     computer.boot_up().await.unwrap();
@@ -308,6 +309,7 @@ async fn test_computer_manager_change_handler() {
     // 测试资源列表变更消息 / Test resource list change message
     let result = computer
         .on_change(ManagerChangeMessage::ResourceListChanged {
+            server: "test_server".to_string(),
             windows: vec!["window1".to_string(), "window2".to_string()],
         })
         .await;
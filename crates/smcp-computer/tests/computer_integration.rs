@@ -62,6 +62,7 @@ impl Session for TestSession {
                         .clone()
                         .unwrap_or_else(|| input.options.first().cloned().unwrap_or_default()),
                 )),
+                MCPServerInput::PromptNumber(input) => Ok(input.to_json(input.fallback())),
                 MCPServerInput::Command(_input) => Ok(serde_json::Value::Null),
             }
         }
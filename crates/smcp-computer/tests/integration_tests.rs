@@ -98,13 +98,13 @@ async fn test_complete_workflow() {
     let status = manager.get_server_status().await;
     let calc_status = status
         .iter()
-        .find(|(name, _, _)| name == "calculator_server")
+        .find(|(name, _, _, _)| name == "calculator_server")
         .unwrap();
     assert!(calc_status.1); // calculator_server 应该已激活 / calculator_server should be active
 
     let http_status = status
         .iter()
-        .find(|(name, _, _)| name == "http_server")
+        .find(|(name, _, _, _)| name == "http_server")
         .unwrap();
     assert!(!http_status.1); // http_server 应该未激活（被禁用）/ http_server should not be active (disabled)
 
@@ -114,7 +114,7 @@ async fn test_complete_workflow() {
 
     // 9. 检查最终状态 / Check final status
     let status = manager.get_server_status().await;
-    for (_, active, _) in status {
+    for (_, active, _, _) in status {
         assert!(!active); // 所有服务器都应该未激活 / All servers should be inactive
     }
 
@@ -0,0 +1,418 @@
+/*!
+* 文件名: socket_tool_call_test
+* 作者: JQQ
+* 创建日期: 2025/12/16
+* 最后修改日期: 2025/12/16
+* 版权: 2023 JQQ. All rights reserved.
+* 依赖: tokio, smcp-agent, smcp-server-core
+* 描述: Agent 经服务器调用 Computer 工具的集成测试 / Integration tests for Agent tool calls relayed to the Computer by the server
+*/
+
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use http_body_util::Full;
+use hyper::HeaderMap;
+use hyper_util::rt::TokioIo;
+use serde_json::{json, Value};
+use smcp_agent::{
+    AsyncSmcpAgent, DefaultAuthProvider, Result as AgentResult, SmcpAgentConfig, SmcpAgentError,
+    ToolCallStreamItem,
+};
+use smcp_computer::computer::{
+    CallContext, ErrorVerbosity, ManagerChangeHandler, ManagerChangeMessage, ResultPipeline,
+    ResultTransform, RESULT_BLOB_URI_PREFIX,
+};
+use smcp_computer::errors::ComputerResult;
+use smcp_computer::mcp_clients::manager::MCPServerManager;
+use smcp_computer::mcp_clients::model::{CallToolResult, Content};
+use smcp_computer::mcp_clients::testing::{mock_server_config, MockMCPClient};
+use smcp_computer::socketio_client::SmcpComputerClient;
+use smcp_server_core::auth::{AuthError, AuthenticationProvider, JwtClaims};
+use smcp_server_core::SmcpServerBuilder;
+use tokio::net::TcpListener;
+use tokio::sync::RwLock;
+use tokio::time::sleep;
+use tower::{Layer, Service};
+
+const OFFICE_ID: &str = "socket-tool-call-office";
+const COMPUTER_NAME: &str = "socket-computer";
+
+/// 不做认证检查的提供者 / Provider that performs no authentication checks
+#[derive(Debug)]
+struct NoOpAuthProvider;
+
+#[async_trait]
+impl AuthenticationProvider for NoOpAuthProvider {
+    async fn authenticate(
+        &self,
+        _headers: &HeaderMap,
+        _auth: Option<&Value>,
+    ) -> Result<Option<JwtClaims>, AuthError> {
+        Ok(None)
+    }
+}
+
+/// 启动测试服务器，返回 URL / Start the test server and return its URL
+async fn start_server() -> String {
+    let layer = SmcpServerBuilder::new()
+        .with_auth_provider(Arc::new(NoOpAuthProvider))
+        .build_layer()
+        .expect("failed to build SMCP server layer");
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let layer = layer.clone();
+            tokio::spawn(async move {
+                let svc = tower::service_fn(move |req| {
+                    let layer = layer.clone();
+                    async move {
+                        let inner = tower::service_fn(|_req| async {
+                            Ok::<_, std::convert::Infallible>(hyper::Response::new(Full::new(
+                                hyper::body::Bytes::new(),
+                            )))
+                        });
+                        layer.layer.layer(inner).call(req).await
+                    }
+                });
+                let _ = hyper::server::conn::http1::Builder::new()
+                    .serve_connection(
+                        TokioIo::new(stream),
+                        hyper_util::service::TowerToHyperService::new(svc),
+                    )
+                    .with_upgrades()
+                    .await;
+            });
+        }
+    });
+
+    format!("http://{}", addr)
+}
+
+/// 以给定 MCP 客户端与后处理连接 Computer 并加入办公室
+/// Connect a Computer backed by the given MCP client and pipeline, then join the office
+async fn joined_computer(
+    url: &str,
+    mock: MockMCPClient,
+    pipeline: ResultPipeline,
+) -> SmcpComputerClient {
+    let manager = MCPServerManager::new();
+    manager
+        .attach_client(mock_server_config("mock"), Arc::new(mock))
+        .await
+        .unwrap();
+    let client = SmcpComputerClient::new(
+        url,
+        Arc::new(RwLock::new(Some(manager))),
+        COMPUTER_NAME.to_string(),
+    )
+    .await
+    .expect("computer failed to connect");
+    client.set_result_pipeline(pipeline).await;
+    client.join_office(OFFICE_ID).await.unwrap();
+    client
+}
+
+async fn joined_agent(url: &str) -> AsyncSmcpAgent {
+    let auth = DefaultAuthProvider::new("socket-agent".to_string(), OFFICE_ID.to_string());
+    let mut agent = AsyncSmcpAgent::new(auth, SmcpAgentConfig::new());
+    agent.connect(url).await.expect("agent failed to connect");
+    agent.join_office("socket-agent").await.unwrap();
+    sleep(Duration::from_millis(200)).await;
+    agent
+}
+
+#[tokio::test]
+async fn test_sanitized_verbosity_hides_error_detail_over_socket() {
+    let url = start_server().await;
+    let mock = MockMCPClient::builder()
+        .tool("query_db")
+        .call_error("query_db", "connect postgres://admin@10.0.0.5/prod refused")
+        .build();
+    let computer = joined_computer(
+        &url,
+        mock,
+        ResultPipeline::new().with_error_verbosity(ErrorVerbosity::Sanitized),
+    )
+    .await;
+    let agent = joined_agent(&url).await;
+
+    let response = agent
+        .tool_call(COMPUTER_NAME, "query_db", json!({}))
+        .await
+        .unwrap();
+    assert_eq!(response["isError"], json!(true));
+    let error = response["structuredContent"]["error"].as_str().unwrap();
+    assert!(!error.contains("postgres"), "leaked detail: {}", error);
+    let correlation_id = error.rsplit(' ').next().unwrap();
+    assert!(uuid::Uuid::parse_str(correlation_id).is_ok());
+
+    let _ = computer.disconnect().await;
+}
+
+#[tokio::test]
+async fn test_full_verbosity_returns_error_detail_over_socket() {
+    let url = start_server().await;
+    let mock = MockMCPClient::builder()
+        .tool("query_db")
+        .call_error("query_db", "connect postgres://admin@10.0.0.5/prod refused")
+        .build();
+    let computer = joined_computer(&url, mock, ResultPipeline::new()).await;
+    let agent = joined_agent(&url).await;
+
+    let response = agent
+        .tool_call(COMPUTER_NAME, "query_db", json!({}))
+        .await
+        .unwrap();
+    assert_eq!(response["isError"], json!(true));
+    let error = response["structuredContent"]["error"].as_str().unwrap();
+    assert!(error.contains("postgres://admin@10.0.0.5/prod"));
+
+    let _ = computer.disconnect().await;
+}
+
+/// 将文本内容转为大写并标注来源的转换 / Transform upper-casing text content and tagging its origin
+struct UppercaseTransform;
+
+#[async_trait]
+impl ResultTransform for UppercaseTransform {
+    async fn transform(
+        &self,
+        ctx: &CallContext,
+        mut result: CallToolResult,
+    ) -> ComputerResult<CallToolResult> {
+        for content in &mut result.content {
+            if let Content::Text { text } = content {
+                *text = format!("{}@{}:{}", text.to_uppercase(), ctx.server, ctx.tool);
+            }
+        }
+        Ok(result)
+    }
+}
+
+#[tokio::test]
+async fn test_result_transforms_apply_over_socket() {
+    let url = start_server().await;
+    let mock = MockMCPClient::builder().tool("echo").tool("other").build();
+    let pipeline = ResultPipeline::new().with_tool_transform("echo", Arc::new(UppercaseTransform));
+    let computer = joined_computer(&url, mock, pipeline).await;
+    let agent = joined_agent(&url).await;
+
+    let transformed = agent
+        .tool_call(COMPUTER_NAME, "echo", json!({}))
+        .await
+        .unwrap();
+    assert_eq!(
+        transformed["content"][0]["text"],
+        json!("MOCK:ECHO@mock:echo")
+    );
+
+    // 未注册转换的工具保持原样 / Tools without a registered transform are left untouched
+    let untouched = agent
+        .tool_call(COMPUTER_NAME, "other", json!({}))
+        .await
+        .unwrap();
+    assert_eq!(untouched["content"][0]["text"], json!("mock:other"));
+
+    let _ = computer.disconnect().await;
+}
+
+#[tokio::test]
+async fn test_large_result_link_round_trip_over_socket() {
+    let url = start_server().await;
+    let large_text = "x".repeat(4096);
+    let mock = MockMCPClient::builder()
+        .tool("dump")
+        .call_response(
+            "dump",
+            CallToolResult {
+                content: vec![Content::Text {
+                    text: large_text.clone(),
+                }],
+                is_error: false,
+                structured_content: None,
+                meta: None,
+            },
+        )
+        .build();
+    let computer =
+        joined_computer(&url, mock, ResultPipeline::new().with_link_threshold(1024)).await;
+    let agent = joined_agent(&url).await;
+
+    let response = agent
+        .tool_call(COMPUTER_NAME, "dump", json!({}))
+        .await
+        .unwrap();
+    let link = &response["content"][0];
+    assert_eq!(link["type"], json!("resource_link"));
+    let uri = link["uri"].as_str().unwrap();
+    assert!(uri.starts_with(RESULT_BLOB_URI_PREFIX));
+
+    // Agent 通过资源读取事件取回完整结果 / The Agent fetches the full result through the read resource event
+    let read = agent.read_resource(COMPUTER_NAME, uri).await.unwrap();
+    assert_eq!(read.contents.len(), 1);
+    let full: CallToolResult =
+        serde_json::from_str(read.contents[0]["text"].as_str().unwrap()).unwrap();
+    assert!(matches!(&full.content[0], Content::Text { text } if *text == large_text));
+
+    assert!(agent
+        .read_resource(COMPUTER_NAME, "smcp-blob://results/unknown")
+        .await
+        .is_err());
+
+    let _ = computer.disconnect().await;
+}
+
+/// 在远低于转发超时的时间内等待 Agent 请求，确认 Computer 以错误应答而非不应答
+/// Await an Agent request well within the forward timeout, proving the Computer acked an error instead of staying silent
+async fn expect_acked_error<T: std::fmt::Debug>(
+    request: impl std::future::Future<Output = AgentResult<T>>,
+) {
+    let result = tokio::time::timeout(Duration::from_secs(5), request)
+        .await
+        .expect("computer did not ack the failed request");
+    let err = result.unwrap_err();
+    assert!(
+        !matches!(err, SmcpAgentError::Timeout),
+        "unexpected error: {}",
+        err
+    );
+}
+
+#[tokio::test]
+async fn test_prompt_errors_are_acked_over_socket() {
+    let url = start_server().await;
+    let computer = joined_computer(
+        &url,
+        MockMCPClient::builder().build(),
+        ResultPipeline::new(),
+    )
+    .await;
+    let agent = joined_agent(&url).await;
+
+    expect_acked_error(agent.get_prompt(COMPUTER_NAME, "missing", None)).await;
+
+    // 管理器未初始化时列表请求失败 / Listing requests fail while the manager is uninitialized
+    let bare = SmcpComputerClient::new(
+        &url,
+        Arc::new(RwLock::new(None)),
+        "bare-computer".to_string(),
+    )
+    .await
+    .expect("computer failed to connect");
+    bare.join_office(OFFICE_ID).await.unwrap();
+    sleep(Duration::from_millis(200)).await;
+
+    expect_acked_error(agent.get_prompts("bare-computer")).await;
+    expect_acked_error(agent.get_resource_templates("bare-computer")).await;
+
+    let _ = bare.disconnect().await;
+    let _ = computer.disconnect().await;
+}
+
+/// 将工具进度转发给 Socket.IO 客户端的处理器 / Handler relaying tool progress to the Socket.IO client
+#[derive(Default)]
+struct ProgressRelay {
+    client: std::sync::Mutex<Weak<SmcpComputerClient>>,
+}
+
+#[async_trait]
+impl ManagerChangeHandler for ProgressRelay {
+    async fn on_change(&self, message: ManagerChangeMessage) -> ComputerResult<()> {
+        if let ManagerChangeMessage::ToolProgress {
+            progress_token,
+            progress,
+            total,
+            message,
+            content,
+        } = message
+        {
+            let client = self.client.lock().unwrap().upgrade();
+            if let Some(client) = client {
+                client
+                    .emit_tool_progress(&progress_token, progress, total, message, content)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 流式调用一个先上报两段文本再返回的工具，返回流的全部输出项
+/// Stream a call to a tool reporting two text chunks before returning, collecting every item
+async fn stream_chunked_tool(pipeline: ResultPipeline) -> Vec<ToolCallStreamItem> {
+    let url = start_server().await;
+    let mock = MockMCPClient::builder()
+        .tool("generate")
+        .call_chunk("generate", "Hello, ")
+        .call_chunk("generate", "world")
+        .call_delay(Duration::from_millis(300))
+        .build();
+    let relay = Arc::new(ProgressRelay::default());
+    let manager = MCPServerManager::new().with_change_handler(relay.clone());
+    manager
+        .attach_client(mock_server_config("mock"), Arc::new(mock))
+        .await
+        .unwrap();
+    let computer = Arc::new(
+        SmcpComputerClient::new(
+            &url,
+            Arc::new(RwLock::new(Some(manager))),
+            COMPUTER_NAME.to_string(),
+        )
+        .await
+        .expect("computer failed to connect"),
+    );
+    *relay.client.lock().unwrap() = Arc::downgrade(&computer);
+    computer.set_result_pipeline(pipeline).await;
+    computer.join_office(OFFICE_ID).await.unwrap();
+    let agent = joined_agent(&url).await;
+
+    agent
+        .tool_call_stream(COMPUTER_NAME, "generate", json!({}))
+        .map(|item| item.unwrap())
+        .collect()
+        .await
+}
+
+#[tokio::test]
+async fn test_tool_call_stream_yields_partial_content_over_socket() {
+    let items = stream_chunked_tool(ResultPipeline::new()).await;
+
+    assert_eq!(items.len(), 3, "unexpected stream items: {:?}", items);
+    let texts: Vec<&Value> = items[..2]
+        .iter()
+        .map(|item| match item {
+            ToolCallStreamItem::Content(content) => &content[0]["text"],
+            other => panic!("expected partial content, got {:?}", other),
+        })
+        .collect();
+    assert_eq!(texts, vec![&json!("Hello, "), &json!("world")]);
+    match &items[2] {
+        ToolCallStreamItem::Done(result) => {
+            assert_eq!(result["content"][0]["text"], json!("mock:generate"))
+        }
+        other => panic!("expected final result, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_result_transforms_drop_partial_content_over_socket() {
+    let pipeline =
+        ResultPipeline::new().with_tool_transform("generate", Arc::new(UppercaseTransform));
+    let items = stream_chunked_tool(pipeline).await;
+
+    // 部分结果未经转换，不应转发 / Untransformed partial content must not be forwarded
+    assert_eq!(items.len(), 1, "unexpected stream items: {:?}", items);
+    match &items[0] {
+        ToolCallStreamItem::Done(result) => assert_eq!(
+            result["content"][0]["text"],
+            json!("MOCK:GENERATE@mock:generate")
+        ),
+        other => panic!("expected final result, got {:?}", other),
+    }
+}
@@ -14,11 +14,13 @@ mod tests {
     use http_body_util::Full;
     use hyper::body::Bytes;
     use hyper::HeaderMap;
+    use smcp::SMCP_NAMESPACE;
+    use smcp_computer::computer::{Computer, SilentSession};
     use smcp_computer::errors::ComputerResult;
     use smcp_computer::mcp_clients::manager::MCPServerManager;
     use smcp_computer::socketio_client::SmcpComputerClient;
-    use smcp_server_core::auth::{AuthError, AuthenticationProvider};
-    use smcp_server_core::SmcpServerBuilder;
+    use smcp_server_core::auth::{AuthError, AuthenticationProvider, JwtClaims};
+    use smcp_server_core::{SmcpServerBuilder, SmcpServerLayer};
     use std::net::SocketAddr;
     use std::sync::Arc;
     use tokio::sync::RwLock;
@@ -38,13 +40,19 @@ mod tests {
             &self,
             _headers: &HeaderMap,
             _auth: Option<&serde_json::Value>,
-        ) -> Result<(), AuthError> {
-            Ok(())
+        ) -> Result<Option<JwtClaims>, AuthError> {
+            Ok(None)
         }
     }
 
     /// 启动测试服务器
     async fn start_test_server() -> String {
+        start_test_server_with_layer().await.0
+    }
+
+    /// 启动测试服务器并返回其 SMCP layer，便于检查服务器状态
+    /// Start the test server and return its SMCP layer for inspecting server state
+    async fn start_test_server_with_layer() -> (String, SmcpServerLayer) {
         // 构建SMCP服务器层 - 使用无操作认证提供者以避免API key检查
         // Build SMCP server layer - use no-op auth provider to avoid API key checks
         let layer = SmcpServerBuilder::new()
@@ -131,7 +139,39 @@ mod tests {
         // Wait for server to be fully ready, avoiding race condition
         sleep(Duration::from_millis(100)).await;
 
-        format!("http://127.0.0.1:{}", port)
+        (format!("http://127.0.0.1:{}", port), layer)
+    }
+
+    /// 转发到服务器的 TCP 代理，发送到返回的 watch 通道即切断所有现有连接以模拟网络中断
+    /// TCP proxy in front of the server; sending on the returned watch channel drops every open
+    /// connection to simulate a network failure
+    async fn start_dropping_proxy(server_url: &str) -> (String, tokio::sync::watch::Sender<u32>) {
+        let target = server_url.trim_start_matches("http://").to_string();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Failed to bind proxy");
+        let port = listener.local_addr().unwrap().port();
+        let (drop_tx, drop_rx) = tokio::sync::watch::channel(0u32);
+
+        tokio::spawn(async move {
+            loop {
+                let (mut inbound, _) = listener.accept().await.expect("Failed to accept");
+                let target = target.clone();
+                let mut drop_rx = drop_rx.clone();
+                let _ = drop_rx.borrow_and_update();
+                tokio::spawn(async move {
+                    let Ok(mut outbound) = tokio::net::TcpStream::connect(target).await else {
+                        return;
+                    };
+                    tokio::select! {
+                        _ = tokio::io::copy_bidirectional(&mut inbound, &mut outbound) => {}
+                        _ = drop_rx.changed() => {}
+                    }
+                });
+            }
+        });
+
+        (format!("http://127.0.0.1:{}", port), drop_tx)
     }
 
     #[tokio::test]
@@ -377,4 +417,176 @@ mod tests {
 
         Ok(())
     }
+    #[tokio::test]
+    async fn test_connect_returns_connection_info() -> ComputerResult<()> {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let server_url = start_test_server().await;
+
+        // 通过 Computer 连接，返回的句柄携带连接信息
+        // Connect through the Computer; the returned handle carries the connection info
+        let computer = Computer::new(
+            "test_computer_info",
+            SilentSession::new("test"),
+            None,
+            None,
+            true,
+            true,
+        );
+        computer.boot_up().await?;
+        let client = computer
+            .connect_socketio(&server_url, SMCP_NAMESPACE, &None, &None)
+            .await?;
+
+        let info = client.connection_info();
+        assert_eq!(info.url, server_url);
+        assert_eq!(info.namespace, SMCP_NAMESPACE);
+        assert_eq!(info.transport, "websocket");
+        assert_eq!(info.computer_name, "test_computer_info");
+        assert_eq!(info.connect_count, 1);
+        assert_eq!(computer.connection_info().await, Some(info));
+
+        // 句柄释放后 Computer 不再持有连接
+        // Once the handle is dropped the Computer no longer holds the connection
+        drop(client);
+        assert!(computer.connection_info().await.is_none());
+
+        computer.shutdown().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_leaves_office_with_reason() -> ComputerResult<()> {
+        use futures_util::FutureExt;
+        use rust_socketio::asynchronous::ClientBuilder;
+        use rust_socketio::{Payload, TransportType};
+
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let server_url = start_test_server().await;
+        let office_id = "test_office_shutdown";
+
+        // Agent 监听离开通知 / The agent records leave notifications
+        let leaves = Arc::new(std::sync::Mutex::new(
+            Vec::<smcp::LeaveOfficeNotification>::new(),
+        ));
+        let leaves_clone = leaves.clone();
+        let agent = ClientBuilder::new(server_url.clone())
+            .transport_type(TransportType::Websocket)
+            .namespace(SMCP_NAMESPACE)
+            .on(
+                smcp::events::NOTIFY_LEAVE_OFFICE,
+                move |payload, _client| {
+                    let leaves = leaves_clone.clone();
+                    async move {
+                        if let Payload::Text(values, _) = payload {
+                            if let Ok(leave) = serde_json::from_value(values[0].clone()) {
+                                leaves.lock().unwrap().push(leave);
+                            }
+                        }
+                    }
+                    .boxed()
+                },
+            )
+            .connect()
+            .await
+            .expect("Connection failed");
+        agent
+            .emit(
+                smcp::events::SERVER_JOIN_OFFICE,
+                serde_json::json!({
+                    "office_id": office_id,
+                    "role": "agent",
+                    "name": "test_agent",
+                }),
+            )
+            .await
+            .expect("Failed to join office");
+
+        let computer = Computer::new(
+            "test_computer_shutdown",
+            SilentSession::new("test"),
+            None,
+            None,
+            true,
+            true,
+        );
+        computer.boot_up().await?;
+        let client = computer
+            .connect_socketio(&server_url, SMCP_NAMESPACE, &None, &None)
+            .await?;
+        client.join_office(office_id).await?;
+        sleep(Duration::from_millis(200)).await;
+
+        // 关闭时在断开连接前主动离开 / Shutdown leaves explicitly before the socket closes
+        computer.shutdown().await?;
+        sleep(Duration::from_millis(200)).await;
+
+        let leaves = leaves.lock().unwrap().clone();
+        let leave = leaves
+            .iter()
+            .find(|leave| leave.computer.as_deref() == Some("test_computer_shutdown"))
+            .expect("leave notification not received");
+        assert_eq!(leave.reason.as_deref(), Some(smcp::LEAVE_REASON_SHUTDOWN));
+
+        client.disconnect().await?;
+        agent.disconnect().await.expect("Failed to disconnect");
+        Ok(())
+    }
+
+    /// 等待条件成立，超时返回 false / Wait for a condition, returning false on timeout
+    async fn wait_until(mut condition: impl FnMut() -> bool) -> bool {
+        for _ in 0..100 {
+            if condition() {
+                return true;
+            }
+            sleep(Duration::from_millis(100)).await;
+        }
+        false
+    }
+
+    #[tokio::test]
+    async fn test_rejoin_office_after_reconnect() -> ComputerResult<()> {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let (server_url, layer) = start_test_server_with_layer().await;
+        let (proxy_url, drop_connections) = start_dropping_proxy(&server_url).await;
+        let sessions = layer.state.session_manager.clone();
+        let office_id = "test_office_rejoin".to_string();
+        let in_office = |sessions: &smcp_server_core::SessionManager| {
+            sessions.has_computer_in_office(&office_id, "test_computer_rejoin")
+        };
+
+        let manager = Arc::new(RwLock::new(Some(MCPServerManager::new())));
+        let client = SmcpComputerClient::new(
+            &proxy_url,
+            manager.clone(),
+            "test_computer_rejoin".to_string(),
+        )
+        .await?;
+        client.join_office(&office_id).await?;
+        assert!(in_office(&sessions));
+
+        // 断线后服务器移除会话，重连后客户端自动重新加入
+        // The server drops the session on disconnect; the client rejoins after reconnecting
+        drop_connections.send_modify(|n| *n += 1);
+        assert!(wait_until(|| !in_office(&sessions)).await);
+        assert!(
+            wait_until(|| in_office(&sessions)).await,
+            "computer did not rejoin the office after reconnecting"
+        );
+        assert_eq!(client.connection_info().connect_count, 2);
+        assert_eq!(client.get_office_id().await, Some(office_id.clone()));
+
+        // 显式离开后再次断线不会重新加入 / No rejoin after an explicit leave
+        client.leave_office(&office_id).await?;
+        drop_connections.send_modify(|n| *n += 1);
+        assert!(wait_until(|| client.connection_info().connect_count == 3).await);
+        sleep(Duration::from_millis(500)).await;
+        assert!(!in_office(&sessions));
+        assert!(client.get_office_id().await.is_none());
+
+        client.disconnect().await?;
+        Ok(())
+    }
 }
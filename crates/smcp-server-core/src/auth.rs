@@ -1,9 +1,14 @@
 //! 认证接口抽象定义 / Authentication interface abstract definition
 
 use async_trait::async_trait;
-use http::HeaderMap;
+use http::{header::AUTHORIZATION, HeaderMap};
+use jsonwebtoken::{errors::ErrorKind, Algorithm, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::session::SessionData;
+use smcp::SMCPTool;
+
 /// 认证错误类型
 #[derive(Error, Debug, serde::Serialize)]
 pub enum AuthError {
@@ -11,6 +16,12 @@ pub enum AuthError {
     MissingApiKey,
     #[error("Invalid API key")]
     InvalidApiKey,
+    #[error("Missing bearer token")]
+    MissingToken,
+    #[error("Bearer token expired")]
+    ExpiredToken,
+    #[error("Invalid bearer token: {0}")]
+    InvalidToken(String),
     #[error("Authentication failed: {0}")]
     Failed(String),
 }
@@ -27,14 +38,90 @@ pub trait AuthenticationProvider: Send + Sync + 'static + std::fmt::Debug {
     /// * `auth` - 原始认证数据 / Raw authentication data
     ///
     /// # Returns
-    /// 认证是否成功 / Whether authentication succeeded
+    /// 认证成功时返回令牌声明，不基于令牌的认证返回 `None`
+    /// Token claims on success, `None` for authentication not based on a token
     async fn authenticate(
         &self,
         headers: &HeaderMap,
         auth: Option<&serde_json::Value>,
-    ) -> Result<(), AuthError>;
+    ) -> Result<Option<JwtClaims>, AuthError>;
+
+    /// 是否允许该连接以系统角色加入办公室
+    /// Whether the connection may join offices with the system role
+    ///
+    /// 默认拒绝，系统会话需由提供者显式授权
+    /// Denied by default, system sessions must be granted by the provider
+    async fn authorize_system(
+        &self,
+        _headers: &HeaderMap,
+        _auth: Option<&serde_json::Value>,
+    ) -> bool {
+        false
+    }
+}
+
+/// 授权提供者抽象 trait，在认证通过后决定会话可访问的办公室
+/// Authorization provider abstract trait, deciding which offices an authenticated session may access
+#[async_trait]
+pub trait AuthorizationProvider: Send + Sync + 'static + std::fmt::Debug {
+    /// 判断会话能否加入指定办公室，默认允许
+    /// Decide whether the session may join the given office, allowed by default
+    ///
+    /// # Arguments
+    /// * `session` - 请求加入的会话 / Session requesting to join
+    /// * `claims` - 连接认证时验证的令牌声明，不基于令牌的认证为 `None`
+    ///   / Token claims verified when the connection authenticated, `None` without a token
+    /// * `office_id` - 目标办公室 ID / Target office ID
+    async fn can_join(
+        &self,
+        _session: &SessionData,
+        _claims: Option<&JwtClaims>,
+        _office_id: &str,
+    ) -> Result<(), AuthError> {
+        Ok(())
+    }
+}
+
+/// 默认授权提供者，允许加入任意办公室
+/// Default authorization provider, allows joining any office
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultAuthorizationProvider;
+
+#[async_trait]
+impl AuthorizationProvider for DefaultAuthorizationProvider {}
+
+/// 工具可见性策略，决定 Agent 能看到 Computer 的哪些工具
+/// Tool visibility policy, deciding which of a computer's tools an agent may see
+pub trait ToolPolicy: Send + Sync + 'static + std::fmt::Debug {
+    /// 过滤返回给 Agent 的工具列表，默认原样返回
+    /// Filter the tool list returned to the agent, returned unchanged by default
+    ///
+    /// # Arguments
+    /// * `agent` - Agent 名称 / Agent name
+    /// * `tools` - Computer 返回的工具列表 / Tools returned by the computer
+    fn filter(&self, _agent: &str, tools: Vec<SMCPTool>) -> Vec<SMCPTool> {
+        tools
+    }
+
+    /// 判断 Agent 能否调用指定工具，默认允许。在 `filter` 中隐藏工具的策略应同时拒绝调用
+    /// Decide whether the agent may call the given tool, allowed by default. Policies hiding
+    /// tools in `filter` should deny calling them as well
+    ///
+    /// # Arguments
+    /// * `agent` - Agent 名称 / Agent name
+    /// * `tool_name` - 要调用的工具名 / Name of the tool to call
+    fn can_call(&self, _agent: &str, _tool_name: &str) -> bool {
+        true
+    }
 }
 
+/// 默认工具策略，不过滤任何工具
+/// Default tool policy, filters nothing
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultToolPolicy;
+
+impl ToolPolicy for DefaultToolPolicy {}
+
 /// 默认认证提供者，提供基础的认证逻辑实现
 /// Default authentication provider, provides basic authentication logic implementation
 #[derive(Debug, Clone)]
@@ -43,6 +130,8 @@ pub struct DefaultAuthenticationProvider {
     admin_secret: Option<String>,
     /// API 密钥字段名 / API key field name
     api_key_name: String,
+    /// 系统会话密钥 / System session secret
+    system_secret: Option<String>,
 }
 
 impl DefaultAuthenticationProvider {
@@ -56,8 +145,24 @@ impl DefaultAuthenticationProvider {
         Self {
             admin_secret,
             api_key_name: api_key_name.unwrap_or_else(|| "x-api-key".to_string()),
+            system_secret: None,
         }
     }
+
+    /// 设置系统会话密钥，携带该密钥的连接可以系统角色加入
+    /// Set the system session secret; connections presenting it may join with the system role
+    pub fn with_system_secret(mut self, system_secret: String) -> Self {
+        self.system_secret = Some(system_secret);
+        self
+    }
+
+    /// 从请求头中提取 API 密钥
+    /// Extract API key from headers
+    fn api_key<'a>(&self, headers: &'a HeaderMap) -> Option<&'a str> {
+        headers
+            .get(self.api_key_name.as_str())
+            .and_then(|value| value.to_str().ok())
+    }
 }
 
 #[async_trait]
@@ -66,34 +171,233 @@ impl AuthenticationProvider for DefaultAuthenticationProvider {
         &self,
         headers: &HeaderMap,
         _auth: Option<&serde_json::Value>,
-    ) -> Result<(), AuthError> {
+    ) -> Result<Option<JwtClaims>, AuthError> {
         // 从 headers 中提取 API 密钥
         // Extract API key from headers
-        let api_key = headers
-            .get(self.api_key_name.as_str())
-            .and_then(|value| value.to_str().ok())
-            .map(|s| s.to_string());
-
-        let api_key = api_key.ok_or(AuthError::MissingApiKey)?;
+        let api_key = self.api_key(headers).ok_or(AuthError::MissingApiKey)?;
 
-        // 检查管理员权限：与配置的管理员密钥比较
-        // Check admin permission: compare with configured admin secret
-        if let Some(ref admin_secret) = self.admin_secret {
-            if api_key.as_str() == admin_secret {
-                return Ok(());
-            }
+        // 检查管理员权限：与配置的管理员密钥或系统会话密钥比较
+        // Check admin permission: compare with configured admin or system secret
+        if self.admin_secret.as_deref() == Some(api_key)
+            || self.system_secret.as_deref() == Some(api_key)
+        {
+            return Ok(None);
         }
 
         // 这里可以添加其他认证逻辑，如数据库验证等
         // Additional authentication logic can be added here, such as database validation
         Err(AuthError::InvalidApiKey)
     }
+
+    async fn authorize_system(
+        &self,
+        headers: &HeaderMap,
+        _auth: Option<&serde_json::Value>,
+    ) -> bool {
+        self.system_secret.is_some() && self.system_secret.as_deref() == self.api_key(headers)
+    }
+}
+
+/// JWT 声明，认证成功后供后续授权使用
+/// JWT claims, exposed after authentication for later authorization
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JwtClaims {
+    /// 令牌主体 / Token subject
+    pub sub: String,
+    /// 过期时间（Unix 秒） / Expiration time (Unix seconds)
+    pub exp: u64,
+    /// 允许加入的办公室列表，缺省表示不限制
+    /// Offices the token may join, absent means unrestricted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub office_allowlist: Option<Vec<String>>,
+    /// 其余自定义声明 / Remaining custom claims
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl JwtClaims {
+    /// 令牌是否允许加入指定办公室
+    /// Whether the token may join the given office
+    pub fn allows_office(&self, office_id: &str) -> bool {
+        self.office_allowlist
+            .as_ref()
+            .is_none_or(|offices| offices.iter().any(|office| office == office_id))
+    }
+}
+
+/// 基于 HS256 JWT Bearer 令牌的认证提供者
+/// Authentication provider validating HS256 JWT bearer tokens
+///
+/// 令牌优先从 `Authorization: Bearer <token>` 请求头读取，其次从 socket.io 认证数据的
+/// `token` 字段读取
+/// The token is read from the `Authorization: Bearer <token>` header first, then from the
+/// `token` field of the socket.io auth payload
+#[derive(Clone)]
+pub struct JwtAuthenticationProvider {
+    /// 解码密钥 / Decoding key
+    key: DecodingKey,
+    /// 校验规则 / Validation rules
+    validation: Validation,
+}
+
+impl std::fmt::Debug for JwtAuthenticationProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // 不输出密钥 / Never print the secret
+        f.debug_struct("JwtAuthenticationProvider")
+            .field("validation", &self.validation)
+            .finish_non_exhaustive()
+    }
+}
+
+impl JwtAuthenticationProvider {
+    /// 使用 HS256 共享密钥创建提供者
+    /// Create a provider with an HS256 shared secret
+    pub fn new(secret: impl AsRef<[u8]>) -> Self {
+        Self {
+            key: DecodingKey::from_secret(secret.as_ref()),
+            validation: Validation::new(Algorithm::HS256),
+        }
+    }
+
+    /// 设置过期校验的时钟容差（秒），默认 60 秒
+    /// Set the clock leeway (seconds) for expiry checks, defaults to 60 seconds
+    pub fn with_leeway(mut self, leeway: u64) -> Self {
+        self.validation.leeway = leeway;
+        self
+    }
+
+    /// 提取并校验令牌，返回其声明
+    /// Extract and verify the token, returning its claims
+    pub fn verify(
+        &self,
+        headers: &HeaderMap,
+        auth: Option<&serde_json::Value>,
+    ) -> Result<JwtClaims, AuthError> {
+        let token = Self::bearer_token(headers, auth).ok_or(AuthError::MissingToken)?;
+        jsonwebtoken::decode::<JwtClaims>(token, &self.key, &self.validation)
+            .map(|data| data.claims)
+            .map_err(|e| match e.kind() {
+                ErrorKind::ExpiredSignature => AuthError::ExpiredToken,
+                _ => AuthError::InvalidToken(e.to_string()),
+            })
+    }
+
+    /// 从请求头或认证数据中提取 Bearer 令牌
+    /// Extract the bearer token from headers or the auth payload
+    fn bearer_token<'a>(
+        headers: &'a HeaderMap,
+        auth: Option<&'a serde_json::Value>,
+    ) -> Option<&'a str> {
+        let from_header = headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok());
+        let from_auth = || {
+            auth.and_then(|auth| auth.get("token"))
+                .and_then(|token| token.as_str())
+        };
+        from_header.or_else(from_auth).map(|raw| {
+            let raw = raw.trim();
+            raw.strip_prefix("Bearer ").unwrap_or(raw).trim()
+        })
+    }
+}
+
+#[async_trait]
+impl AuthenticationProvider for JwtAuthenticationProvider {
+    async fn authenticate(
+        &self,
+        headers: &HeaderMap,
+        auth: Option<&serde_json::Value>,
+    ) -> Result<Option<JwtClaims>, AuthError> {
+        self.verify(headers, auth).map(Some)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use http::HeaderValue;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+
+    const JWT_SECRET: &str = "jwt-secret";
+
+    fn now() -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    fn sign(exp: u64) -> String {
+        let claims = serde_json::json!({
+            "sub": "agent-1",
+            "exp": exp,
+            "office_allowlist": ["office-a"],
+        });
+        encode(
+            &Header::new(Algorithm::HS256),
+            &claims,
+            &EncodingKey::from_secret(JWT_SECRET.as_bytes()),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_jwt_auth_valid_token() {
+        let auth = JwtAuthenticationProvider::new(JWT_SECRET);
+        let token = sign(now() + 3600);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {token}")).unwrap(),
+        );
+        let claims = auth.verify(&headers, None).unwrap();
+        assert_eq!(claims.sub, "agent-1");
+        assert!(claims.allows_office("office-a"));
+        assert!(!claims.allows_office("office-b"));
+        let authenticated = auth.authenticate(&headers, None).await.unwrap();
+        assert_eq!(authenticated, Some(claims));
+
+        // socket.io 认证数据中的令牌同样有效
+        let payload = serde_json::json!({ "token": token });
+        assert!(auth
+            .authenticate(&HeaderMap::new(), Some(&payload))
+            .await
+            .is_ok());
+        assert!(matches!(
+            auth.authenticate(&HeaderMap::new(), None).await,
+            Err(AuthError::MissingToken)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_jwt_auth_expired_token() {
+        let auth = JwtAuthenticationProvider::new(JWT_SECRET).with_leeway(0);
+        let payload = serde_json::json!({ "token": sign(now() - 3600) });
+
+        let result = auth.authenticate(&HeaderMap::new(), Some(&payload)).await;
+        assert!(matches!(result, Err(AuthError::ExpiredToken)));
+    }
+
+    #[tokio::test]
+    async fn test_jwt_auth_tampered_signature() {
+        let auth = JwtAuthenticationProvider::new(JWT_SECRET);
+        let token = sign(now() + 3600);
+        let (unsigned, signature) = token.rsplit_once('.').unwrap();
+        let flipped = if signature.starts_with('A') { 'B' } else { 'A' };
+        let tampered = format!("{unsigned}.{flipped}{}", &signature[1..]);
+
+        let payload = serde_json::json!({ "token": tampered });
+        let result = auth.authenticate(&HeaderMap::new(), Some(&payload)).await;
+        assert!(matches!(result, Err(AuthError::InvalidToken(_))));
+
+        // 使用其他密钥签发的令牌同样无效
+        let other = JwtAuthenticationProvider::new("other-secret");
+        let payload = serde_json::json!({ "token": token });
+        let result = other.authenticate(&HeaderMap::new(), Some(&payload)).await;
+        assert!(matches!(result, Err(AuthError::InvalidToken(_))));
+    }
 
     #[tokio::test]
     async fn test_default_auth_success() {
@@ -101,8 +405,9 @@ mod tests {
         let mut headers = HeaderMap::new();
         headers.insert("x-api-key", HeaderValue::from_static("secret123"));
 
+        // API 密钥认证不携带令牌声明
         let result = auth.authenticate(&headers, None).await;
-        assert!(result.is_ok());
+        assert!(matches!(result, Ok(None)));
     }
 
     #[tokio::test]
@@ -133,4 +438,18 @@ mod tests {
         let result = auth.authenticate(&headers, None).await;
         assert!(matches!(result, Err(AuthError::InvalidApiKey)));
     }
+
+    #[tokio::test]
+    async fn test_default_auth_system_secret() {
+        let auth = DefaultAuthenticationProvider::new(Some("secret123".to_string()), None)
+            .with_system_secret("system123".to_string());
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", HeaderValue::from_static("system123"));
+        assert!(auth.authenticate(&headers, None).await.is_ok());
+        assert!(auth.authorize_system(&headers, None).await);
+
+        headers.insert("x-api-key", HeaderValue::from_static("secret123"));
+        assert!(auth.authenticate(&headers, None).await.is_ok());
+        assert!(!auth.authorize_system(&headers, None).await);
+    }
 }
@@ -1,17 +1,23 @@
 //! SMCP 协议处理器 / SMCP protocol handler
 
-use crate::auth::{AuthError, AuthenticationProvider};
-use crate::session::{ClientRole, SessionData, SessionError, SessionManager};
+use crate::auth::{
+    AuthError, AuthenticationProvider, AuthorizationProvider, JwtClaims, ToolPolicy,
+};
+use crate::metrics::ServerMetrics;
+use crate::sequence::OfficeSequencer;
+use crate::server::SmcpServerConfig;
+use crate::session::{ClientRole, SessionData, SessionError, SessionId, SessionManager};
 use futures_util::StreamExt;
 use serde_json::Value;
 use smcp::*;
 use socketioxide::{
     extract::{AckSender, Data, SocketRef},
-    SocketIo,
+    AckError, SocketIo,
 };
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use thiserror::Error;
-use tracing::{error, info, warn};
+use tracing::{debug, error, info, warn};
 
 /// 处理器错误类型
 #[derive(Error, Debug)]
@@ -26,6 +32,79 @@ pub enum HandlerError {
     Timeout(String),
     #[error("Invalid request: {0}")]
     InvalidRequest(String),
+    #[error("Not found: {0}")]
+    NotFound(String),
+    #[error("Payload too large: {size} bytes exceeds the limit of {limit} bytes")]
+    PayloadTooLarge { size: usize, limit: usize },
+}
+
+/// 错误码，便于 Agent 区分错误类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    /// 请求本身不合法（如缺少必填字段）
+    InvalidRequest,
+    /// 请求的目标不存在
+    NotFound,
+    /// 认证失败
+    Unauthorized,
+    /// 等待响应超时
+    Timeout,
+    /// 消息超过协议允许的大小
+    PayloadTooLarge,
+}
+
+impl ErrorCode {
+    /// 协议错误码
+    pub fn as_u16(self) -> u16 {
+        match self {
+            ErrorCode::InvalidRequest => error_codes::INVALID_REQUEST,
+            ErrorCode::NotFound => error_codes::NOT_FOUND,
+            ErrorCode::Unauthorized => error_codes::UNAUTHORIZED,
+            ErrorCode::Timeout => error_codes::TIMEOUT,
+            ErrorCode::PayloadTooLarge => error_codes::PAYLOAD_TOO_LARGE,
+        }
+    }
+
+    /// 错误类别名称
+    pub fn category(self) -> &'static str {
+        match self {
+            ErrorCode::InvalidRequest => "invalid_request",
+            ErrorCode::NotFound => "not_found",
+            ErrorCode::Unauthorized => "unauthorized",
+            ErrorCode::Timeout => "timeout",
+            ErrorCode::PayloadTooLarge => "payload_too_large",
+        }
+    }
+
+    /// 稍后重试是否可能成功
+    pub fn is_retriable(self) -> bool {
+        matches!(self, ErrorCode::Timeout)
+    }
+}
+
+impl HandlerError {
+    /// 获取错误对应的错误码
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            HandlerError::Auth(_) => ErrorCode::Unauthorized,
+            HandlerError::Session(SessionError::NotFound(_)) => ErrorCode::NotFound,
+            HandlerError::Session(_) => ErrorCode::InvalidRequest,
+            HandlerError::Json(_) => ErrorCode::InvalidRequest,
+            HandlerError::Timeout(_) => ErrorCode::Timeout,
+            HandlerError::InvalidRequest(_) => ErrorCode::InvalidRequest,
+            HandlerError::NotFound(_) => ErrorCode::NotFound,
+            HandlerError::PayloadTooLarge { .. } => ErrorCode::PayloadTooLarge,
+        }
+    }
+}
+
+impl HandlerError {
+    /// 转换为下发给客户端的结构化错误信息
+    pub fn to_payload(&self) -> ErrorPayload {
+        let code = self.code();
+        ErrorPayload::new(code.as_u16(), code.category(), self.to_string())
+            .with_retriable(code.is_retriable())
+    }
 }
 
 impl serde::Serialize for HandlerError {
@@ -33,10 +112,54 @@ impl serde::Serialize for HandlerError {
     where
         S: serde::Serializer,
     {
-        serializer.serialize_str(&self.to_string())
+        self.to_payload().serialize(serializer)
+    }
+}
+
+/// 不携带超时的转发请求（获取工具、配置等）的默认等待秒数
+const DEFAULT_FORWARD_TIMEOUT_SECS: u64 = 30;
+
+/// 各转发事件等待 Computer 响应的默认时间，实际等待时间不超过服务器超时上限
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeoutConfig {
+    /// 工具调用，仅在请求未携带正超时时使用
+    pub tool_call: Duration,
+    /// 获取工具列表
+    pub get_tools: Duration,
+    /// 获取桌面
+    pub get_desktop: Duration,
+    /// 获取配置
+    pub get_config: Duration,
+    /// 其他转发请求（提示词、资源模板等）
+    pub other: Duration,
+}
+
+impl Default for TimeoutConfig {
+    fn default() -> Self {
+        let forward = Duration::from_secs(DEFAULT_FORWARD_TIMEOUT_SECS);
+        Self {
+            tool_call: Duration::from_secs(smcp::DEFAULT_MAX_TIMEOUT_SECS),
+            get_tools: forward,
+            get_desktop: forward,
+            get_config: forward,
+            other: forward,
+        }
     }
 }
 
+/// 健康检查结果
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct HealthStatus {
+    /// 进程存活即为 `ok`
+    pub status: &'static str,
+    /// 当前活动会话数
+    pub sessions: usize,
+    /// 运行时长（秒）
+    pub uptime_secs: u64,
+    /// SMCP 命名空间是否已注册
+    pub namespace_registered: bool,
+}
+
 /// 服务器状态
 #[derive(Clone, Debug)]
 pub struct ServerState {
@@ -44,18 +167,105 @@ pub struct ServerState {
     pub session_manager: Arc<SessionManager>,
     /// 认证提供者
     pub auth_provider: Arc<dyn AuthenticationProvider>,
+    /// 授权提供者，决定会话可以加入哪些办公室
+    pub authorization_provider: Arc<dyn AuthorizationProvider>,
+    /// 工具可见性策略，过滤返回给 Agent 的工具列表
+    pub tool_policy: Arc<dyn ToolPolicy>,
     /// SocketIo 实例引用，用于跨 socket 通信
     pub io: Arc<SocketIo>,
+    /// 服务器指标
+    pub metrics: Arc<ServerMetrics>,
+    /// 协议层允许转发的最大消息字节数，`None` 表示不限制
+    pub max_payload_bytes: Option<usize>,
+    /// 服务器对外公布的基础URL
+    pub public_url: Option<String>,
+    /// 办公室通知序号分配器，保证同一办公室的通知按发送顺序送达
+    pub office_sequencer: Arc<OfficeSequencer>,
+    /// 请求超时上限（秒），随服务器能力下发，更长的请求超时在转发前被截断
+    pub max_timeout_secs: u64,
+    /// 默认办公室，未携带办公室 ID 的加入/离开/列出请求使用该办公室
+    pub default_office: Option<String>,
+    /// 各转发事件的默认等待时间
+    pub timeout_config: TimeoutConfig,
+    /// 服务器启动时间，用于计算运行时长
+    pub started_at: Instant,
+    /// 服务器配置（命名空间、连接上限等）
+    pub config: SmcpServerConfig,
+}
+
+impl ServerState {
+    /// 当前服务器能力
+    pub fn capabilities(&self) -> ServerCapabilities {
+        ServerCapabilities {
+            protocol_version: smcp::PROTOCOL_VERSION.to_string(),
+            public_url: self.public_url.clone(),
+            max_timeout_secs: Some(self.max_timeout_secs),
+        }
+    }
+
+    /// 命名空间处理器是否已注册，注册后服务器才能接受 SMCP 连接
+    pub fn is_ready(&self) -> bool {
+        self.io.of(self.config.namespace.as_str()).is_some()
+    }
+
+    /// 当前健康状况，供存活探针使用
+    pub fn health(&self) -> HealthStatus {
+        HealthStatus {
+            status: "ok",
+            sessions: self.session_manager.get_stats().total,
+            uptime_secs: self.started_at.elapsed().as_secs(),
+            namespace_registered: self.is_ready(),
+        }
+    }
+
+    /// 解析请求中的办公室 ID，空值在配置了默认办公室时替换为默认办公室
+    pub fn resolve_office_id(&self, office_id: String) -> String {
+        match &self.default_office {
+            Some(default) if office_id.is_empty() => default.clone(),
+            _ => office_id,
+        }
+    }
+
+    /// 转发请求的等待时间，不超过超时上限
+    fn forward_timeout(&self, timeout: Duration) -> Duration {
+        timeout.min(Duration::from_secs(self.max_timeout_secs))
+    }
+
+    /// 工具调用的等待秒数：优先使用请求携带的正超时，否则使用配置的默认值，均不超过超时上限
+    pub fn tool_call_timeout_secs(&self, req: &ToolCallReq) -> u64 {
+        if req.timeout > 0 {
+            return req.timeout_secs(self.max_timeout_secs);
+        }
+        // 不足一秒的配置按一秒处理，避免向 Computer 下发 0
+        self.timeout_config
+            .tool_call
+            .as_secs()
+            .clamp(1, self.max_timeout_secs.max(1))
+    }
 }
 
 /// SMCP 事件处理器
 pub struct SmcpHandler;
 
 impl SmcpHandler {
-    /// 注册所有事件处理器
+    /// 在配置的命名空间（默认 [`smcp::SMCP_NAMESPACE`]）上注册所有事件处理器
     pub fn register_handlers(io: &SocketIo, state: ServerState) {
+        let namespace = state.config.namespace.clone();
+        Self::register_handlers_on(io, namespace, state);
+    }
+
+    /// 在指定命名空间上注册所有事件处理器，广播与 `of()` 调用均使用该命名空间
+    ///
+    /// 同一服务器可据此承载多个命名空间（如 `/smcp` 与 `/smcp-staging`）；各命名空间应使用
+    /// 独立的 [`SessionManager`]，否则会话与办公室状态会在命名空间之间共享。
+    pub fn register_handlers_on(
+        io: &SocketIo,
+        namespace: impl Into<String>,
+        mut state: ServerState,
+    ) {
+        state.config.namespace = namespace.into();
         // 注册命名空间和连接处理器
-        io.ns(SMCP_NAMESPACE, move |socket: SocketRef| {
+        io.ns(state.config.namespace.clone(), move |socket: SocketRef| {
             let state = state.clone();
             async move {
                 if let Err(e) = Self::on_connect(socket.clone(), &state).await {
@@ -63,6 +273,14 @@ impl SmcpHandler {
                     return;
                 }
 
+                // 向新连接下发服务器能力
+                if let Err(e) = state
+                    .metrics
+                    .track(socket.emit(smcp::events::NOTIFY_SERVER_HELLO, &state.capabilities()))
+                {
+                    warn!("Failed to send server hello to {}: {}", socket.id, e);
+                }
+
                 // 连接时注册所有事件处理器
                 Self::handle_connection(socket, state)
             }
@@ -122,11 +340,20 @@ impl SmcpHandler {
             },
         );
 
+        let state_tool_progress = state.clone();
+        socket.on(
+            smcp::events::SERVER_TOOL_PROGRESS,
+            move |socket: SocketRef, Data::<ToolProgressNotification>(data)| async move {
+                Self::on_server_tool_progress(socket, data, state_tool_progress.clone()).await
+            },
+        );
+
         let state_tool_call = state.clone();
         socket.on(
             smcp::events::CLIENT_TOOL_CALL,
             move |socket: SocketRef, Data::<ToolCallReq>(data), ack: AckSender| async move {
                 let result = Self::on_client_tool_call(socket, data, state_tool_call.clone()).await;
+                state_tool_call.metrics.record_tool_call(result.is_ok());
                 let _ = ack.send(&result);
             },
         );
@@ -159,6 +386,50 @@ impl SmcpHandler {
             },
         );
 
+        let state_get_prompts = state.clone();
+        socket.on(
+            smcp::events::CLIENT_GET_PROMPTS,
+            move |socket: SocketRef, Data::<GetPromptsReq>(data), ack: AckSender| async move {
+                let result =
+                    Self::on_client_get_prompts(socket, data, state_get_prompts.clone()).await;
+                let _ = ack.send(&result);
+            },
+        );
+
+        let state_get_prompt = state.clone();
+        socket.on(
+            smcp::events::CLIENT_GET_PROMPT,
+            move |socket: SocketRef, Data::<GetPromptReq>(data), ack: AckSender| async move {
+                let result =
+                    Self::on_client_get_prompt(socket, data, state_get_prompt.clone()).await;
+                let _ = ack.send(&result);
+            },
+        );
+
+        let state_get_resource_templates = state.clone();
+        socket.on(
+            smcp::events::CLIENT_GET_RESOURCE_TEMPLATES,
+            move |socket: SocketRef, Data::<GetResourceTemplatesReq>(data), ack: AckSender| async move {
+                let result = Self::on_client_get_resource_templates(
+                    socket,
+                    data,
+                    state_get_resource_templates.clone(),
+                )
+                .await;
+                let _ = ack.send(&result);
+            },
+        );
+
+        let state_read_resource = state.clone();
+        socket.on(
+            smcp::events::CLIENT_READ_RESOURCE,
+            move |socket: SocketRef, Data::<ReadResourceReq>(data), ack: AckSender| async move {
+                let result =
+                    Self::on_client_read_resource(socket, data, state_read_resource.clone()).await;
+                let _ = ack.send(&result);
+            },
+        );
+
         let state_update_desktop = state.clone();
         socket.on(
             smcp::events::SERVER_UPDATE_DESKTOP,
@@ -181,7 +452,8 @@ impl SmcpHandler {
     async fn on_connect(socket: SocketRef, state: &ServerState) -> Result<(), HandlerError> {
         info!(
             "SocketIO Client {} connecting to {}...",
-            socket.id, SMCP_NAMESPACE
+            socket.id,
+            socket.ns()
         );
 
         // 获取请求头进行认证
@@ -189,14 +461,34 @@ impl SmcpHandler {
         let auth_data = socket.req_parts().extensions.get::<Value>();
 
         // 认证
-        state
+        let claims = state
             .auth_provider
             .authenticate(&headers, auth_data)
             .await?;
 
+        // 连接数达到上限时拒绝并断开
+        if !state
+            .session_manager
+            .try_accept_connection(state.config.max_sessions)
+        {
+            let max = state.config.max_sessions.unwrap_or_default();
+            warn!(
+                "Rejecting SocketIO Client {}: session limit {} reached",
+                socket.id, max
+            );
+            let _ = socket.disconnect();
+            return Err(SessionError::LimitReached(max).into());
+        }
+
+        // 保存令牌声明，供加入办公室时授权使用
+        if let Some(claims) = claims {
+            socket.extensions.insert(claims);
+        }
+
         info!(
             "SocketIO Client {} connected successfully to {}",
-            socket.id, SMCP_NAMESPACE
+            socket.id,
+            socket.ns()
         );
         Ok(())
     }
@@ -205,90 +497,109 @@ impl SmcpHandler {
     async fn on_disconnect(socket: SocketRef, state: ServerState) {
         info!(
             "SocketIO Client {} disconnecting from {}...",
-            socket.id, SMCP_NAMESPACE
+            socket.id,
+            socket.ns()
         );
 
         // 清理会话
+        state.session_manager.release_connection();
         let sid = socket.id.to_string();
         if let Some(session) = state.session_manager.unregister_session(&sid) {
-            // 如果在房间内，广播离开消息
-            if let Some(office_id) = session.office_id {
+            // 如果在房间内，广播离开消息（系统会话不对外公告）
+            let office_id = session
+                .office_id
+                .filter(|_| session.role != ClientRole::System);
+            if let Some(office_id) = office_id {
                 let notification = if session.role == ClientRole::Computer {
                     LeaveOfficeNotification {
                         office_id: office_id.clone(),
                         computer: Some(session.name),
                         agent: None,
+                        reason: Some(smcp::LEAVE_REASON_DISCONNECT.to_string()),
                     }
                 } else {
                     LeaveOfficeNotification {
                         office_id: office_id.clone(),
                         computer: None,
                         agent: Some(session.name),
+                        reason: Some(smcp::LEAVE_REASON_DISCONNECT.to_string()),
                     }
                 };
 
-                let _ = socket
-                    .within(office_id)
-                    .emit(smcp::events::NOTIFY_LEAVE_OFFICE, &notification)
-                    .await;
+                let turn = state.office_sequencer.acquire(&office_id).await;
+                let _ = state.metrics.track(
+                    socket
+                        .within(office_id)
+                        .emit(smcp::events::NOTIFY_LEAVE_OFFICE, &turn.wrap(&notification))
+                        .await,
+                );
+                state.metrics.record_leave();
             }
         }
 
         info!(
             "SocketIO Client {} disconnected from {}",
-            socket.id, SMCP_NAMESPACE
+            socket.id,
+            socket.ns()
         );
     }
 
+    /// 校验客户端协议版本，主版本号不一致时拒绝；未携带版本的旧版客户端放行
+    fn check_protocol_version(data: &EnterOfficeReq) -> Result<(), String> {
+        match data.a2c_version.as_deref() {
+            Some(version) if !smcp::is_protocol_compatible(version) => Err(format!(
+                "version mismatch: client protocol {} is incompatible with server protocol {}",
+                version,
+                smcp::PROTOCOL_VERSION
+            )),
+            _ => Ok(()),
+        }
+    }
+
     /// 处理加入办公室事件
     async fn on_server_join_office(
         socket: SocketRef,
-        data: EnterOfficeReq,
+        mut data: EnterOfficeReq,
         state: ServerState,
     ) -> (bool, Option<String>) {
         info!("on_server_join_office called with data: {:?}", data);
+        if let Err(e) = Self::check_protocol_version(&data) {
+            warn!("Rejecting join from {}: {}", socket.id, e);
+            return (false, Some(e));
+        }
+        data.office_id = state.resolve_office_id(data.office_id);
 
         let sid = socket.id.to_string();
         let requested_role = ClientRole::from(data.role.clone());
         let requested_name = data.name.clone();
 
-        // 获取或创建会话
-        let session = match state.session_manager.get_session(&sid) {
-            Some(s) => {
-                // 检查角色/状态一致性
-                if s.role != requested_role {
-                    return (
-                        false,
-                        Some(format!(
-                            "Role mismatch: existing session has role {:?}, but requested {:?}",
-                            s.role, requested_role
-                        )),
-                    );
-                }
-
-                if s.name != requested_name {
-                    return (
-                        false,
-                        Some(format!(
-                            "Name mismatch: existing session has name '{}', but requested '{}'",
-                            s.name, requested_name
-                        )),
-                    );
-                }
-
-                s
-            }
-            None => {
-                // 创建新会话
-                let new_session = SessionData::new(sid.clone(), requested_name, requested_role);
+        // 系统角色需由认证提供者授权
+        if requested_role == ClientRole::System && !Self::authorize_system(&socket, &state).await {
+            warn!("System role not authorized for sid={}", sid);
+            return (false, Some("System role not authorized".to_string()));
+        }
 
-                if let Err(e) = state.session_manager.register_session(new_session.clone()) {
-                    return (false, Some(format!("Failed to register session: {}", e)));
-                }
-                new_session
-            }
+        // 获取或创建会话
+        let session = match Self::resolve_join_session(&sid, requested_role, requested_name, &state)
+        {
+            Ok(session) => session,
+            Err(e) => return (false, Some(e)),
         };
 
+        // 由授权提供者结合连接时验证的令牌声明，决定会话能否进入该办公室
+        let claims = socket.extensions.get::<JwtClaims>();
+        if let Err(e) = state
+            .authorization_provider
+            .can_join(&session, claims.as_ref(), &data.office_id)
+            .await
+        {
+            warn!(
+                "Join to office '{}' not authorized for sid={}: {}",
+                data.office_id, sid, e
+            );
+            return (false, Some(e.to_string()));
+        }
+
         // 检查并加入房间
         if let Err(e) =
             Self::handle_join_room(socket.clone(), &session, &data.office_id, &state).await
@@ -305,6 +616,25 @@ impl SmcpHandler {
             return (false, Some(format!("Failed to update office_id: {}", e)));
         }
 
+        // 首次加入时登记办公室元数据，已有元数据时忽略
+        if let Some(meta) = data.meta {
+            if !state
+                .session_manager
+                .init_office_meta(&data.office_id, meta)
+            {
+                debug!(
+                    "Office '{}' already has metadata, ignoring join-time meta",
+                    data.office_id
+                );
+            }
+        }
+
+        // 系统会话只观察，不广播进入通知
+        if session.role == ClientRole::System {
+            return (true, None);
+        }
+        state.metrics.record_join();
+
         // 构建通知数据
         let session_name = session.name.clone();
         let notification_data = if session.role == ClientRole::Computer {
@@ -321,10 +651,17 @@ impl SmcpHandler {
             }
         };
 
-        let result = socket
-            .to(data.office_id.clone())
-            .emit(smcp::events::NOTIFY_ENTER_OFFICE, &notification_data)
-            .await;
+        let turn = state.office_sequencer.acquire(&data.office_id).await;
+        let result = state.metrics.track(
+            socket
+                .to(data.office_id.clone())
+                .emit(
+                    smcp::events::NOTIFY_ENTER_OFFICE,
+                    &turn.wrap(&notification_data),
+                )
+                .await,
+        );
+        drop(turn);
 
         if let Err(e) = result {
             warn!("Failed to broadcast NOTIFY_ENTER_OFFICE: {}", e);
@@ -336,9 +673,10 @@ impl SmcpHandler {
     /// 处理离开办公室事件
     async fn on_server_leave_office(
         socket: SocketRef,
-        data: LeaveOfficeReq,
+        mut data: LeaveOfficeReq,
         state: ServerState,
     ) -> (bool, Option<String>) {
+        data.office_id = state.resolve_office_id(data.office_id);
         let sid = socket.id.to_string();
 
         // 获取会话
@@ -347,26 +685,34 @@ impl SmcpHandler {
             None => return (false, Some(format!("Session not found: {}", sid))),
         };
 
-        // 构建离开通知
-        let notification = if session.role == ClientRole::Computer {
-            LeaveOfficeNotification {
-                office_id: data.office_id.clone(),
-                computer: Some(session.name),
-                agent: None,
-            }
-        } else {
-            LeaveOfficeNotification {
-                office_id: data.office_id.clone(),
-                computer: None,
-                agent: Some(session.name),
-            }
-        };
+        // 广播离开消息（系统会话不对外公告）
+        if session.role != ClientRole::System {
+            let notification = if session.role == ClientRole::Computer {
+                LeaveOfficeNotification {
+                    office_id: data.office_id.clone(),
+                    computer: Some(session.name),
+                    agent: None,
+                    reason: data.reason.clone(),
+                }
+            } else {
+                LeaveOfficeNotification {
+                    office_id: data.office_id.clone(),
+                    computer: None,
+                    agent: Some(session.name),
+                    reason: data.reason.clone(),
+                }
+            };
 
-        // 广播离开消息
-        let _ = socket
-            .within(data.office_id.clone())
-            .emit(smcp::events::NOTIFY_LEAVE_OFFICE, &notification)
-            .await;
+            let turn = state.office_sequencer.acquire(&data.office_id).await;
+            let _ = state.metrics.track(
+                socket
+                    .within(data.office_id.clone())
+                    .emit(smcp::events::NOTIFY_LEAVE_OFFICE, &turn.wrap(&notification))
+                    .await,
+            );
+            drop(turn);
+            state.metrics.record_leave();
+        }
 
         // 更新会话
         if let Err(e) = state.session_manager.update_office_id(&sid, None) {
@@ -415,11 +761,13 @@ impl SmcpHandler {
             }
         };
 
-        if let Err(e) = socket
-            .to(office_id)
-            .emit(smcp::events::NOTIFY_TOOL_CALL_CANCEL, &data)
-            .await
-        {
+        let turn = state.office_sequencer.acquire(&office_id).await;
+        if let Err(e) = state.metrics.track(
+            socket
+                .to(office_id)
+                .emit(smcp::events::NOTIFY_TOOL_CALL_CANCEL, &turn.wrap(&data))
+                .await,
+        ) {
             warn!("Failed to broadcast NOTIFY_TOOL_CALL_CANCEL: {}", e);
         }
     }
@@ -471,11 +819,16 @@ impl SmcpHandler {
             office_id_clone, computer_clone, sid
         );
 
-        if let Err(e) = socket
-            .to(office_id.clone())
-            .emit(smcp::events::NOTIFY_UPDATE_CONFIG, &notification)
-            .await
-        {
+        let turn = state.office_sequencer.acquire(&office_id).await;
+        if let Err(e) = state.metrics.track(
+            socket
+                .to(office_id.clone())
+                .emit(
+                    smcp::events::NOTIFY_UPDATE_CONFIG,
+                    &turn.wrap(&notification),
+                )
+                .await,
+        ) {
             warn!("Failed to broadcast NOTIFY_UPDATE_CONFIG: {}", e);
         } else {
             info!(
@@ -525,21 +878,76 @@ impl SmcpHandler {
             computer: data.computer,
         };
 
-        if let Err(e) = socket
-            .to(office_id)
-            .emit(smcp::events::NOTIFY_UPDATE_TOOL_LIST, &notification)
-            .await
-        {
+        let turn = state.office_sequencer.acquire(&office_id).await;
+        if let Err(e) = state.metrics.track(
+            socket
+                .to(office_id)
+                .emit(
+                    smcp::events::NOTIFY_UPDATE_TOOL_LIST,
+                    &turn.wrap(&notification),
+                )
+                .await,
+        ) {
             warn!("Failed to broadcast NOTIFY_UPDATE_TOOL_LIST: {}", e);
         }
     }
 
+    /// 处理工具调用进度事件，转发给办公室内的其他成员
+    async fn on_server_tool_progress(
+        socket: SocketRef,
+        data: ToolProgressNotification,
+        state: ServerState,
+    ) {
+        let sid = socket.id.to_string();
+        let session = match state.session_manager.get_session(&sid) {
+            Some(s) => s,
+            None => {
+                warn!("SERVER_TOOL_PROGRESS from unknown session sid={}", sid);
+                return;
+            }
+        };
+
+        // 角色断言：进度只能由执行工具的 Computer 上报
+        if session.role != ClientRole::Computer {
+            warn!(
+                "SERVER_TOOL_PROGRESS role mismatch: expected Computer, got {:?}, sid={}",
+                session.role, sid
+            );
+            return;
+        }
+
+        let office_id = match session.office_id {
+            Some(ref office_id) => office_id.clone(),
+            None => {
+                warn!(
+                    "SERVER_TOOL_PROGRESS but session not in office, sid={}",
+                    sid
+                );
+                return;
+            }
+        };
+
+        // 同一次调用的部分结果需按上报顺序送达，复用办公室通知序号
+        let turn = state.office_sequencer.acquire(&office_id).await;
+        if let Err(e) = state.metrics.track(
+            socket
+                .to(office_id)
+                .emit(smcp::events::NOTIFY_TOOL_PROGRESS, &turn.wrap(&data))
+                .await,
+        ) {
+            warn!("Failed to broadcast NOTIFY_TOOL_PROGRESS: {}", e);
+        }
+    }
+
     /// 处理客户端工具调用事件
     async fn on_client_tool_call(
         socket: SocketRef,
         data: ToolCallReq,
         state: ServerState,
     ) -> Result<Value, HandlerError> {
+        // 在查找 Computer 之前先校验必填字段
+        Self::validate_tool_call_req(&data)?;
+
         // 获取 Agent 的会话信息
         let sid = socket.id.to_string();
         let session = state
@@ -561,62 +969,68 @@ impl SmcpHandler {
             )
         })?;
 
+        // 工具策略拒绝的调用不转发给 Computer
+        Self::check_tool_policy(&state, &session.name, &data.tool_name)?;
+
         // 查找目标 Computer 的 sid
         let computer_sid = state
             .session_manager
             .get_computer_sid_in_office(&office_id, &data.computer)
             .ok_or_else(|| {
-                HandlerError::InvalidRequest(format!(
-                    "Computer '{}' not found in office",
-                    data.computer
-                ))
+                HandlerError::NotFound(format!("Computer '{}' not found in office", data.computer))
             })?;
 
         // 获取目标 socket
         let target_socket = state
             .io
-            .of(SMCP_NAMESPACE)
+            .of(&state.config.namespace)
             .and_then(|op| op.get_socket(computer_sid.parse().unwrap()))
             .ok_or_else(|| {
                 HandlerError::InvalidRequest("Target computer socket not found".to_string())
             })?;
 
-        // 转发请求并等待响应
-        let timeout = tokio::time::Duration::from_secs(30);
-        let ack_result = target_socket.emit_with_ack(smcp::events::CLIENT_TOOL_CALL, &data);
+        // 未携带超时时使用配置的默认值，并以服务器上限为准截断，Computer 收到的是最终值
+        let timeout_secs = state.tool_call_timeout_secs(&data);
+        let data = ToolCallReq {
+            timeout: i32::try_from(timeout_secs).unwrap_or(i32::MAX),
+            ..data
+        };
 
-        match tokio::time::timeout(timeout, async move {
-            match ack_result {
-                Ok(stream) => {
-                    let mut pinned = Box::pin(stream);
-                    match pinned.next().await {
-                        Some((_, response)) => response,
-                        None => Ok(serde_json::Value::Null),
-                    }
-                }
-                Err(_) => Ok(serde_json::Value::Null),
-            }
-        })
-        .await
-        {
-            Ok(Ok(response)) => {
-                // 解析响应
-                match response {
-                    serde_json::Value::Object(mut map) => {
-                        // 提取 result 字段
-                        let result = map.remove("result").unwrap_or(serde_json::Value::Null);
-                        Ok(result)
-                    }
-                    _ => Ok(response),
-                }
+        // 转发请求并等待响应
+        let response = Self::forward_with_ack(
+            &state,
+            &target_socket,
+            smcp::events::CLIENT_TOOL_CALL,
+            &data,
+            "Tool call",
+            Duration::from_secs(timeout_secs),
+        )
+        .await?;
+
+        // 解析响应
+        match response {
+            serde_json::Value::Object(mut map) => {
+                // 提取 result 字段
+                let result = map.remove("result").unwrap_or(serde_json::Value::Null);
+                Ok(result)
             }
-            Ok(Err(e)) => Err(HandlerError::Timeout(format!(
-                "Failed to get response from computer: {}",
-                e
-            ))),
-            Err(_) => Err(HandlerError::Timeout(
-                "Tool call timed out after 30 seconds".to_string(),
-            )),
+            _ => Ok(response),
+        }
+    }
+
+    /// 按工具策略检查 Agent 能否调用该工具
+    fn check_tool_policy(
+        state: &ServerState,
+        agent: &str,
+        tool_name: &str,
+    ) -> Result<(), HandlerError> {
+        if state.tool_policy.can_call(agent, tool_name) {
+            Ok(())
+        } else {
+            Err(HandlerError::Auth(AuthError::Failed(format!(
+                "Tool '{}' is not allowed for agent '{}'",
+                tool_name, agent
+            ))))
         }
     }
 
@@ -650,61 +1064,51 @@ impl SmcpHandler {
             .session_manager
             .get_computer_sid_in_office(&office_id, &data.computer)
             .ok_or_else(|| {
-                HandlerError::InvalidRequest(format!(
-                    "Computer '{}' not found in office",
-                    data.computer
-                ))
+                HandlerError::NotFound(format!("Computer '{}' not found in office", data.computer))
             })?;
 
         // 获取目标 socket
         let target_socket = state
             .io
-            .of(SMCP_NAMESPACE)
+            .of(&state.config.namespace)
             .and_then(|op| op.get_socket(computer_sid.parse().unwrap()))
             .ok_or_else(|| {
                 HandlerError::InvalidRequest("Target computer socket not found".to_string())
             })?;
 
         // 转发请求并等待响应
-        let timeout = tokio::time::Duration::from_secs(30);
-        let ack_result = target_socket.emit_with_ack(smcp::events::CLIENT_GET_TOOLS, &data);
+        let response = Self::forward_with_ack(
+            &state,
+            &target_socket,
+            smcp::events::CLIENT_GET_TOOLS,
+            &data,
+            "Get tools",
+            state.forward_timeout(state.timeout_config.get_tools),
+        )
+        .await?;
 
-        match tokio::time::timeout(timeout, async move {
-            match ack_result {
-                Ok(stream) => {
-                    let mut pinned = Box::pin(stream);
-                    match pinned.next().await {
-                        Some((_, response)) => response,
-                        None => Ok(serde_json::Value::Null),
-                    }
-                }
-                Err(_) => Ok(serde_json::Value::Null),
-            }
-        })
-        .await
-        {
-            Ok(Ok(response)) => {
-                // 解析响应
-                serde_json::from_value(response).map_err(|e| {
-                    HandlerError::InvalidRequest(format!("Failed to parse response: {}", e))
-                })
-            }
-            Ok(Err(e)) => Err(HandlerError::Timeout(format!(
-                "Failed to get response from computer: {}",
-                e
-            ))),
-            Err(_) => Err(HandlerError::Timeout(
-                "Get tools timed out after 30 seconds".to_string(),
-            )),
-        }
+        Self::filter_tools_response(&state, &session.name, response)
     }
 
-    /// 处理获取桌面信息事件
-    async fn on_client_get_desktop(
+    /// 解析 Computer 返回的工具列表，并按工具策略过滤
+    fn filter_tools_response(
+        state: &ServerState,
+        agent: &str,
+        response: serde_json::Value,
+    ) -> Result<GetToolsRet, HandlerError> {
+        let mut ret: GetToolsRet = serde_json::from_value(response).map_err(|e| {
+            HandlerError::InvalidRequest(format!("Failed to parse response: {}", e))
+        })?;
+        ret.tools = state.tool_policy.filter(agent, ret.tools);
+        Ok(ret)
+    }
+
+    /// 处理获取提示词列表事件
+    async fn on_client_get_prompts(
         socket: SocketRef,
-        data: GetDesktopReq,
+        data: GetPromptsReq,
         state: ServerState,
-    ) -> Result<GetDesktopRet, HandlerError> {
+    ) -> Result<GetPromptsRet, HandlerError> {
         // 获取 Agent 的会话信息
         let sid = socket.id.to_string();
         let session = state
@@ -715,13 +1119,13 @@ impl SmcpHandler {
         // 验证角色必须是 Agent
         if session.role != ClientRole::Agent {
             return Err(HandlerError::InvalidRequest(
-                "Only agents can get desktop".to_string(),
+                "Only agents can get prompts".to_string(),
             ));
         }
 
         // 验证 Agent 在某个办公室内
         let office_id = session.office_id.ok_or_else(|| {
-            HandlerError::InvalidRequest("Agent must be in an office to get desktop".to_string())
+            HandlerError::InvalidRequest("Agent must be in an office to get prompts".to_string())
         })?;
 
         // 查找目标 Computer 的 sid
@@ -729,59 +1133,272 @@ impl SmcpHandler {
             .session_manager
             .get_computer_sid_in_office(&office_id, &data.computer)
             .ok_or_else(|| {
-                HandlerError::InvalidRequest(format!(
-                    "Computer '{}' not found in office",
-                    data.computer
-                ))
+                HandlerError::NotFound(format!("Computer '{}' not found in office", data.computer))
             })?;
 
         // 获取目标 socket
         let target_socket = state
             .io
-            .of(SMCP_NAMESPACE)
+            .of(&state.config.namespace)
             .and_then(|op| op.get_socket(computer_sid.parse().unwrap()))
             .ok_or_else(|| {
                 HandlerError::InvalidRequest("Target computer socket not found".to_string())
             })?;
 
         // 转发请求并等待响应
-        let timeout = tokio::time::Duration::from_secs(30);
-        let ack_result = target_socket.emit_with_ack(smcp::events::CLIENT_GET_DESKTOP, &data);
+        let response = Self::forward_with_ack(
+            &state,
+            &target_socket,
+            smcp::events::CLIENT_GET_PROMPTS,
+            &data,
+            "Get prompts",
+            state.forward_timeout(state.timeout_config.other),
+        )
+        .await?;
 
-        match tokio::time::timeout(timeout, async move {
-            match ack_result {
-                Ok(stream) => {
-                    let mut pinned = Box::pin(stream);
-                    match pinned.next().await {
-                        Some((_, response)) => response,
-                        None => Ok(serde_json::Value::Null),
-                    }
-                }
-                Err(_) => Ok(serde_json::Value::Null),
-            }
-        })
-        .await
-        {
-            Ok(Ok(response)) => {
-                // 解析响应
-                serde_json::from_value(response).map_err(|e| {
-                    HandlerError::InvalidRequest(format!("Failed to parse response: {}", e))
-                })
-            }
-            Ok(Err(e)) => Err(HandlerError::Timeout(format!(
-                "Failed to get response from computer: {}",
-                e
-            ))),
-            Err(_) => Err(HandlerError::Timeout(
-                "Get desktop timed out after 30 seconds".to_string(),
-            )),
-        }
+        // 解析响应
+        serde_json::from_value(response)
+            .map_err(|e| HandlerError::InvalidRequest(format!("Failed to parse response: {}", e)))
     }
 
-    /// 处理获取计算机配置事件
-    async fn on_client_get_config(
+    /// 处理获取资源模板列表事件
+    async fn on_client_get_resource_templates(
         socket: SocketRef,
-        data: GetComputerConfigReq,
+        data: GetResourceTemplatesReq,
+        state: ServerState,
+    ) -> Result<GetResourceTemplatesRet, HandlerError> {
+        // 获取 Agent 的会话信息
+        let sid = socket.id.to_string();
+        let session = state
+            .session_manager
+            .get_session(&sid)
+            .ok_or_else(|| HandlerError::Session(SessionError::NotFound(sid.clone())))?;
+
+        // 验证角色必须是 Agent
+        if session.role != ClientRole::Agent {
+            return Err(HandlerError::InvalidRequest(
+                "Only agents can get resource templates".to_string(),
+            ));
+        }
+
+        // 验证 Agent 在某个办公室内
+        let office_id = session.office_id.ok_or_else(|| {
+            HandlerError::InvalidRequest(
+                "Agent must be in an office to get resource templates".to_string(),
+            )
+        })?;
+
+        // 查找目标 Computer 的 sid
+        let computer_sid = state
+            .session_manager
+            .get_computer_sid_in_office(&office_id, &data.computer)
+            .ok_or_else(|| {
+                HandlerError::NotFound(format!("Computer '{}' not found in office", data.computer))
+            })?;
+
+        // 获取目标 socket
+        let target_socket = state
+            .io
+            .of(&state.config.namespace)
+            .and_then(|op| op.get_socket(computer_sid.parse().unwrap()))
+            .ok_or_else(|| {
+                HandlerError::InvalidRequest("Target computer socket not found".to_string())
+            })?;
+
+        // 转发请求并等待响应
+        let response = Self::forward_with_ack(
+            &state,
+            &target_socket,
+            smcp::events::CLIENT_GET_RESOURCE_TEMPLATES,
+            &data,
+            "Get resource templates",
+            state.forward_timeout(state.timeout_config.other),
+        )
+        .await?;
+
+        // 解析响应
+        serde_json::from_value(response)
+            .map_err(|e| HandlerError::InvalidRequest(format!("Failed to parse response: {}", e)))
+    }
+
+    /// 处理获取提示词内容事件
+    async fn on_client_get_prompt(
+        socket: SocketRef,
+        data: GetPromptReq,
+        state: ServerState,
+    ) -> Result<GetPromptRet, HandlerError> {
+        // 获取 Agent 的会话信息
+        let sid = socket.id.to_string();
+        let session = state
+            .session_manager
+            .get_session(&sid)
+            .ok_or_else(|| HandlerError::Session(SessionError::NotFound(sid.clone())))?;
+
+        // 验证角色必须是 Agent
+        if session.role != ClientRole::Agent {
+            return Err(HandlerError::InvalidRequest(
+                "Only agents can get prompt".to_string(),
+            ));
+        }
+
+        // 验证 Agent 在某个办公室内
+        let office_id = session.office_id.ok_or_else(|| {
+            HandlerError::InvalidRequest("Agent must be in an office to get prompt".to_string())
+        })?;
+
+        // 查找目标 Computer 的 sid
+        let computer_sid = state
+            .session_manager
+            .get_computer_sid_in_office(&office_id, &data.computer)
+            .ok_or_else(|| {
+                HandlerError::NotFound(format!("Computer '{}' not found in office", data.computer))
+            })?;
+
+        // 获取目标 socket
+        let target_socket = state
+            .io
+            .of(&state.config.namespace)
+            .and_then(|op| op.get_socket(computer_sid.parse().unwrap()))
+            .ok_or_else(|| {
+                HandlerError::InvalidRequest("Target computer socket not found".to_string())
+            })?;
+
+        // 转发请求并等待响应
+        let response = Self::forward_with_ack(
+            &state,
+            &target_socket,
+            smcp::events::CLIENT_GET_PROMPT,
+            &data,
+            "Get prompt",
+            state.forward_timeout(state.timeout_config.other),
+        )
+        .await?;
+
+        // 解析响应
+        serde_json::from_value(response)
+            .map_err(|e| HandlerError::InvalidRequest(format!("Failed to parse response: {}", e)))
+    }
+
+    /// 处理读取资源事件
+    async fn on_client_read_resource(
+        socket: SocketRef,
+        data: ReadResourceReq,
+        state: ServerState,
+    ) -> Result<ReadResourceRet, HandlerError> {
+        // 获取 Agent 的会话信息
+        let sid = socket.id.to_string();
+        let session = state
+            .session_manager
+            .get_session(&sid)
+            .ok_or_else(|| HandlerError::Session(SessionError::NotFound(sid.clone())))?;
+
+        // 验证角色必须是 Agent
+        if session.role != ClientRole::Agent {
+            return Err(HandlerError::InvalidRequest(
+                "Only agents can read resources".to_string(),
+            ));
+        }
+
+        // 验证 Agent 在某个办公室内
+        let office_id = session.office_id.ok_or_else(|| {
+            HandlerError::InvalidRequest("Agent must be in an office to read resources".to_string())
+        })?;
+
+        // 查找目标 Computer 的 sid
+        let computer_sid = state
+            .session_manager
+            .get_computer_sid_in_office(&office_id, &data.computer)
+            .ok_or_else(|| {
+                HandlerError::NotFound(format!("Computer '{}' not found in office", data.computer))
+            })?;
+
+        // 获取目标 socket
+        let target_socket = state
+            .io
+            .of(&state.config.namespace)
+            .and_then(|op| op.get_socket(computer_sid.parse().unwrap()))
+            .ok_or_else(|| {
+                HandlerError::InvalidRequest("Target computer socket not found".to_string())
+            })?;
+
+        // 转发请求并等待响应
+        let response = Self::forward_with_ack(
+            &state,
+            &target_socket,
+            smcp::events::CLIENT_READ_RESOURCE,
+            &data,
+            "Read resource",
+            state.forward_timeout(state.timeout_config.other),
+        )
+        .await?;
+
+        // 解析响应
+        serde_json::from_value(response)
+            .map_err(|e| HandlerError::InvalidRequest(format!("Failed to parse response: {}", e)))
+    }
+
+    /// 处理获取桌面信息事件
+    async fn on_client_get_desktop(
+        socket: SocketRef,
+        data: GetDesktopReq,
+        state: ServerState,
+    ) -> Result<GetDesktopRet, HandlerError> {
+        // 获取 Agent 的会话信息
+        let sid = socket.id.to_string();
+        let session = state
+            .session_manager
+            .get_session(&sid)
+            .ok_or_else(|| HandlerError::Session(SessionError::NotFound(sid.clone())))?;
+
+        // 验证角色必须是 Agent
+        if session.role != ClientRole::Agent {
+            return Err(HandlerError::InvalidRequest(
+                "Only agents can get desktop".to_string(),
+            ));
+        }
+
+        // 验证 Agent 在某个办公室内
+        let office_id = session.office_id.ok_or_else(|| {
+            HandlerError::InvalidRequest("Agent must be in an office to get desktop".to_string())
+        })?;
+
+        // 查找目标 Computer 的 sid
+        let computer_sid = state
+            .session_manager
+            .get_computer_sid_in_office(&office_id, &data.computer)
+            .ok_or_else(|| {
+                HandlerError::NotFound(format!("Computer '{}' not found in office", data.computer))
+            })?;
+
+        // 获取目标 socket
+        let target_socket = state
+            .io
+            .of(&state.config.namespace)
+            .and_then(|op| op.get_socket(computer_sid.parse().unwrap()))
+            .ok_or_else(|| {
+                HandlerError::InvalidRequest("Target computer socket not found".to_string())
+            })?;
+
+        // 转发请求并等待响应
+        let response = Self::forward_with_ack(
+            &state,
+            &target_socket,
+            smcp::events::CLIENT_GET_DESKTOP,
+            &data,
+            "Get desktop",
+            state.forward_timeout(state.timeout_config.get_desktop),
+        )
+        .await?;
+
+        // 解析响应
+        serde_json::from_value(response)
+            .map_err(|e| HandlerError::InvalidRequest(format!("Failed to parse response: {}", e)))
+    }
+
+    /// 处理获取计算机配置事件
+    async fn on_client_get_config(
+        socket: SocketRef,
+        data: GetComputerConfigReq,
         state: ServerState,
     ) -> Result<GetComputerConfigRet, HandlerError> {
         // 获取 Agent 的会话信息
@@ -808,53 +1425,32 @@ impl SmcpHandler {
             .session_manager
             .get_computer_sid_in_office(&office_id, &data.computer)
             .ok_or_else(|| {
-                HandlerError::InvalidRequest(format!(
-                    "Computer '{}' not found in office",
-                    data.computer
-                ))
+                HandlerError::NotFound(format!("Computer '{}' not found in office", data.computer))
             })?;
 
         // 获取目标 socket
         let target_socket = state
             .io
-            .of(SMCP_NAMESPACE)
+            .of(&state.config.namespace)
             .and_then(|op| op.get_socket(computer_sid.parse().unwrap()))
             .ok_or_else(|| {
                 HandlerError::InvalidRequest("Target computer socket not found".to_string())
             })?;
 
         // 转发请求并等待响应
-        let timeout = tokio::time::Duration::from_secs(30);
-        let ack_result = target_socket.emit_with_ack(smcp::events::CLIENT_GET_CONFIG, &data);
+        let response = Self::forward_with_ack(
+            &state,
+            &target_socket,
+            smcp::events::CLIENT_GET_CONFIG,
+            &data,
+            "Get config",
+            state.forward_timeout(state.timeout_config.get_config),
+        )
+        .await?;
 
-        match tokio::time::timeout(timeout, async move {
-            match ack_result {
-                Ok(stream) => {
-                    let mut pinned = Box::pin(stream);
-                    match pinned.next().await {
-                        Some((_, response)) => response,
-                        None => Ok(serde_json::Value::Null),
-                    }
-                }
-                Err(_) => Ok(serde_json::Value::Null),
-            }
-        })
-        .await
-        {
-            Ok(Ok(response)) => {
-                // 解析响应
-                serde_json::from_value(response).map_err(|e| {
-                    HandlerError::InvalidRequest(format!("Failed to parse response: {}", e))
-                })
-            }
-            Ok(Err(e)) => Err(HandlerError::Timeout(format!(
-                "Failed to get response from computer: {}",
-                e
-            ))),
-            Err(_) => Err(HandlerError::Timeout(
-                "Get config timed out after 30 seconds".to_string(),
-            )),
-        }
+        // 解析响应
+        serde_json::from_value(response)
+            .map_err(|e| HandlerError::InvalidRequest(format!("Failed to parse response: {}", e)))
     }
 
     /// 处理桌面更新事件
@@ -897,11 +1493,16 @@ impl SmcpHandler {
             computer: data.computer,
         };
 
-        if let Err(e) = socket
-            .to(office_id)
-            .emit(smcp::events::NOTIFY_UPDATE_DESKTOP, &notification)
-            .await
-        {
+        let turn = state.office_sequencer.acquire(&office_id).await;
+        if let Err(e) = state.metrics.track(
+            socket
+                .to(office_id)
+                .emit(
+                    smcp::events::NOTIFY_UPDATE_DESKTOP,
+                    &turn.wrap(&notification),
+                )
+                .await,
+        ) {
             warn!("Failed to broadcast NOTIFY_UPDATE_DESKTOP: {}", e);
         }
     }
@@ -909,9 +1510,10 @@ impl SmcpHandler {
     /// 处理列出房间事件
     async fn on_server_list_room(
         socket: SocketRef,
-        data: ListRoomReq,
+        mut data: ListRoomReq,
         state: ServerState,
     ) -> ListRoomRet {
+        data.office_id = state.resolve_office_id(data.office_id);
         // 获取发起者会话信息
         let sid = socket.id.to_string();
         let session = match state.session_manager.get_session(&sid) {
@@ -921,41 +1523,68 @@ impl SmcpHandler {
                 return ListRoomRet {
                     sessions: vec![],
                     req_id: data.base.req_id,
+                    total: 0,
+                    office_meta: None,
                 };
             }
         };
 
-        // 权限校验：只能查询自己所在的办公室
-        let session_office_id = match session.office_id {
-            Some(id) => id,
-            None => {
-                warn!("Session {} not in any office", sid);
+        // 权限校验：只能查询自己所在的办公室，系统会话可查询任意办公室
+        if session.role != ClientRole::System {
+            let session_office_id = match session.office_id {
+                Some(id) => id,
+                None => {
+                    warn!("Session {} not in any office", sid);
+                    return ListRoomRet {
+                        sessions: vec![],
+                        req_id: data.base.req_id,
+                        total: 0,
+                        office_meta: None,
+                    };
+                }
+            };
+
+            if session_office_id != data.office_id {
+                warn!(
+                    "Session {} trying to list room {} but in office {}",
+                    sid, data.office_id, session_office_id
+                );
                 return ListRoomRet {
                     sessions: vec![],
                     req_id: data.base.req_id,
+                    total: 0,
+                    office_meta: None,
                 };
             }
-        };
-
-        if session_office_id != data.office_id {
-            warn!(
-                "Session {} trying to list room {} but in office {}",
-                sid, data.office_id, session_office_id
-            );
-            return ListRoomRet {
-                sessions: vec![],
-                req_id: data.base.req_id,
-            };
         }
 
-        // 获取指定办公室的所有会话
-        let sessions = state
-            .session_manager
-            .get_sessions_in_office(&data.office_id);
+        // 通过办公室成员索引按角色取会话，再分页
+        let role = data.role.clone().map(ClientRole::from);
+        let sessions = state.session_manager.list_in_office(&data.office_id, role);
+        let (session_infos, total) = Self::paginate_sessions(sessions, &data);
 
-        // 转换为 SessionInfo 列表
-        let session_infos: Vec<SessionInfo> = sessions
+        ListRoomRet {
+            sessions: session_infos,
+            req_id: data.base.req_id,
+            total,
+            office_meta: state.session_manager.get_office_meta(&data.office_id),
+        }
+    }
+
+    /// 截取 `offset`/`limit` 窗口，返回当前页与会话总数
+    ///
+    /// 会话按 sid 排序，保证连续分页的结果稳定。
+    fn paginate_sessions(
+        mut sessions: Vec<SessionData>,
+        req: &ListRoomReq,
+    ) -> (Vec<SessionInfo>, usize) {
+        sessions.sort_by(|a, b| a.sid.cmp(&b.sid));
+        let total = sessions.len();
+
+        let page = sessions
             .into_iter()
+            .skip(req.offset.unwrap_or(0))
+            .take(req.limit.unwrap_or(usize::MAX))
             .map(|s| SessionInfo {
                 sid: s.sid,
                 name: s.name,
@@ -963,11 +1592,7 @@ impl SmcpHandler {
                 office_id: s.office_id.unwrap_or_default(),
             })
             .collect();
-
-        ListRoomRet {
-            sessions: session_infos,
-            req_id: data.base.req_id,
-        }
+        (page, total)
     }
 
     /// 处理加入房间的逻辑
@@ -998,26 +1623,37 @@ impl SmcpHandler {
                     leave_office, office_id, socket.id
                 );
 
-                // 构建离开通知（Python语义：切换房间前需要通知旧房间）
-                let leave_notification = if session.role == ClientRole::Computer {
-                    LeaveOfficeNotification {
-                        office_id: leave_office.clone(),
-                        computer: Some(session.name.clone()),
-                        agent: None,
-                    }
-                } else {
-                    LeaveOfficeNotification {
-                        office_id: leave_office.clone(),
-                        computer: None,
-                        agent: Some(session.name.clone()),
-                    }
-                };
-
-                // 向旧房间广播离开消息
-                let _ = socket
-                    .within(leave_office.clone())
-                    .emit(smcp::events::NOTIFY_LEAVE_OFFICE, &leave_notification)
-                    .await;
+                // 构建离开通知（Python语义：切换房间前需要通知旧房间），系统会话不公告
+                if session.role != ClientRole::System {
+                    let leave_notification = if session.role == ClientRole::Computer {
+                        LeaveOfficeNotification {
+                            office_id: leave_office.clone(),
+                            computer: Some(session.name.clone()),
+                            agent: None,
+                            reason: None,
+                        }
+                    } else {
+                        LeaveOfficeNotification {
+                            office_id: leave_office.clone(),
+                            computer: None,
+                            agent: Some(session.name.clone()),
+                            reason: None,
+                        }
+                    };
+
+                    // 向旧房间广播离开消息
+                    let turn = state.office_sequencer.acquire(&leave_office).await;
+                    let _ = state.metrics.track(
+                        socket
+                            .within(leave_office.clone())
+                            .emit(
+                                smcp::events::NOTIFY_LEAVE_OFFICE,
+                                &turn.wrap(&leave_notification),
+                            )
+                            .await,
+                    );
+                    drop(turn);
+                }
 
                 socket.leave(leave_office);
                 socket.join(office_id.to_string());
@@ -1026,6 +1662,148 @@ impl SmcpHandler {
         }
     }
 
+    /// 向 Computer 转发请求并等待 ACK，同时记录发送指标
+    async fn forward_with_ack<T: serde::Serialize + ?Sized>(
+        state: &ServerState,
+        target_socket: &SocketRef,
+        event: &str,
+        data: &T,
+        action: &str,
+        timeout: Duration,
+    ) -> Result<Value, HandlerError> {
+        Self::check_payload_size(state, data)?;
+
+        let started = Instant::now();
+        let ack_result = state
+            .metrics
+            .track(target_socket.emit_with_ack::<_, Value>(event, data));
+
+        match tokio::time::timeout(timeout, async move {
+            match ack_result {
+                Ok(stream) => {
+                    let mut pinned = Box::pin(stream);
+                    match pinned.next().await {
+                        Some((_, response)) => response,
+                        None => Ok(serde_json::Value::Null),
+                    }
+                }
+                Err(_) => Ok(serde_json::Value::Null),
+            }
+        })
+        .await
+        {
+            Ok(Ok(response)) => {
+                state.metrics.observe_forward_latency(started.elapsed());
+                Self::check_payload_size(state, &response)?;
+                Ok(response)
+            }
+            Ok(Err(e)) => {
+                if matches!(e, AckError::Timeout) {
+                    state.metrics.record_ack_timeout();
+                }
+                Err(HandlerError::Timeout(format!(
+                    "Failed to get response from computer: {}",
+                    e
+                )))
+            }
+            Err(_) => {
+                state.metrics.record_ack_timeout();
+                Err(HandlerError::Timeout(format!(
+                    "{} timed out after {} seconds",
+                    action,
+                    timeout.as_secs()
+                )))
+            }
+        }
+    }
+
+    /// 校验消息大小不超过协议限制，超限时返回明确的错误而不是交给传输层丢弃
+    fn check_payload_size<T: serde::Serialize + ?Sized>(
+        state: &ServerState,
+        data: &T,
+    ) -> Result<(), HandlerError> {
+        let Some(limit) = state.max_payload_bytes else {
+            return Ok(());
+        };
+        let size = serde_json::to_vec(data)?.len();
+        if size > limit {
+            return Err(HandlerError::PayloadTooLarge { size, limit });
+        }
+        Ok(())
+    }
+
+    /// 校验工具调用请求的必填字段
+    fn validate_tool_call_req(data: &ToolCallReq) -> Result<(), HandlerError> {
+        if data.computer.trim().is_empty() {
+            return Err(HandlerError::InvalidRequest(
+                "Tool call must specify a target computer".to_string(),
+            ));
+        }
+        if data.tool_name.trim().is_empty() {
+            return Err(HandlerError::InvalidRequest(
+                "Tool call must specify a tool_name".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// 向认证提供者确认连接是否可使用系统角色
+    async fn authorize_system(socket: &SocketRef, state: &ServerState) -> bool {
+        let headers = socket.req_parts().headers.clone();
+        let auth_data = socket.req_parts().extensions.get::<Value>();
+        state
+            .auth_provider
+            .authorize_system(&headers, auth_data)
+            .await
+    }
+
+    /// 取得加入办公室所用的会话：已有会话视为更新（要求角色与名称一致），
+    /// 否则注册新会话。注册时若并发的加入已占用该 sid，同样按更新处理。
+    fn resolve_join_session(
+        sid: &SessionId,
+        requested_role: ClientRole,
+        requested_name: String,
+        state: &ServerState,
+    ) -> Result<SessionData, String> {
+        let session = match state.session_manager.get_session(sid) {
+            Some(s) => s,
+            None => {
+                // 创建新会话
+                let new_session =
+                    SessionData::new(sid.clone(), requested_name.clone(), requested_role.clone());
+                match state.session_manager.register_session(new_session.clone()) {
+                    Ok(()) => return Ok(new_session),
+                    Err(SessionError::SidExists(_)) => {
+                        debug!(
+                            "Session {} registered concurrently, treating join as update",
+                            sid
+                        );
+                        state
+                            .session_manager
+                            .get_session(sid)
+                            .ok_or_else(|| format!("Session not found: {}", sid))?
+                    }
+                    Err(e) => return Err(format!("Failed to register session: {}", e)),
+                }
+            }
+        };
+
+        // 检查角色/状态一致性
+        if session.role != requested_role {
+            return Err(format!(
+                "Role mismatch: existing session has role {:?}, but requested {:?}",
+                session.role, requested_role
+            ));
+        }
+        if session.name != requested_name {
+            return Err(format!(
+                "Name mismatch: existing session has name '{}', but requested '{}'",
+                session.name, requested_name
+            ));
+        }
+        Ok(session)
+    }
+
     fn validate_join_room(
         session: &SessionData,
         office_id: &str,
@@ -1088,6 +1866,17 @@ impl SmcpHandler {
                     )));
                 }
             }
+            ClientRole::System => {
+                // 系统会话可随意切换办公室，不受 Agent/Computer 唯一性限制
+                if let Some(current_office) = &session.office_id {
+                    if current_office == office_id {
+                        return Ok(JoinRoomDecision::Noop);
+                    }
+                    return Ok(JoinRoomDecision::LeaveAndJoin {
+                        leave_office: current_office.clone(),
+                    });
+                }
+            }
         }
 
         Ok(JoinRoomDecision::Join)
@@ -1104,7 +1893,9 @@ enum JoinRoomDecision {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::auth::DefaultAuthenticationProvider;
+    use crate::auth::{
+        DefaultAuthenticationProvider, DefaultAuthorizationProvider, DefaultToolPolicy,
+    };
     use serde_json;
 
     fn create_test_state() -> ServerState {
@@ -1115,10 +1906,34 @@ mod tests {
                 Some("test_secret".to_string()),
                 None,
             )),
+            authorization_provider: Arc::new(DefaultAuthorizationProvider),
+            tool_policy: Arc::new(DefaultToolPolicy),
             io: Arc::new(io),
+            metrics: Arc::new(ServerMetrics::new()),
+            max_payload_bytes: None,
+            public_url: None,
+            office_sequencer: Arc::new(OfficeSequencer::new()),
+            max_timeout_secs: smcp::DEFAULT_MAX_TIMEOUT_SECS,
+            default_office: None,
+            timeout_config: TimeoutConfig::default(),
+            started_at: Instant::now(),
+            config: SmcpServerConfig::default(),
         }
     }
 
+    #[test]
+    fn test_ready_after_handlers_registered() {
+        let state = create_test_state();
+        assert!(!state.is_ready());
+        let health = serde_json::to_value(state.health()).unwrap();
+        assert_eq!(health["sessions"], 0);
+        assert_eq!(health["namespace_registered"], false);
+
+        SmcpHandler::register_handlers(&state.io, state.clone());
+        assert!(state.is_ready());
+        assert!(state.health().namespace_registered);
+    }
+
     #[tokio::test]
     async fn test_agent_join_office() {
         let (_layer, io) = SocketIo::builder().build_layer();
@@ -1128,7 +1943,18 @@ mod tests {
                 Some("test_secret".to_string()),
                 None,
             )),
+            authorization_provider: Arc::new(DefaultAuthorizationProvider),
+            tool_policy: Arc::new(DefaultToolPolicy),
             io: Arc::new(io.clone()),
+            metrics: Arc::new(ServerMetrics::new()),
+            max_payload_bytes: None,
+            public_url: None,
+            office_sequencer: Arc::new(OfficeSequencer::new()),
+            max_timeout_secs: smcp::DEFAULT_MAX_TIMEOUT_SECS,
+            default_office: None,
+            timeout_config: TimeoutConfig::default(),
+            started_at: Instant::now(),
+            config: SmcpServerConfig::default(),
         };
 
         // 注册处理器
@@ -1147,6 +1973,234 @@ mod tests {
         assert!(json.contains("bad"));
     }
 
+    fn tool_call_req(computer: &str, tool_name: &str) -> ToolCallReq {
+        ToolCallReq {
+            base: AgentCallData {
+                agent: "agent1".to_string(),
+                req_id: ReqId::new(),
+            },
+            computer: computer.to_string(),
+            tool_name: tool_name.to_string(),
+            params: serde_json::json!({}),
+            timeout: 30,
+            idempotency_key: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_tool_call_req_empty_computer() {
+        let err = SmcpHandler::validate_tool_call_req(&tool_call_req("  ", "echo")).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::InvalidRequest);
+        assert!(err.to_string().contains("target computer"));
+    }
+
+    #[test]
+    fn test_validate_tool_call_req_empty_tool_name() {
+        let err = SmcpHandler::validate_tool_call_req(&tool_call_req("c1", "")).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::InvalidRequest);
+        assert!(err.to_string().contains("tool_name"));
+        assert!(!err.to_string().contains("target computer"));
+    }
+
+    #[test]
+    fn test_validate_tool_call_req_ok() {
+        assert!(SmcpHandler::validate_tool_call_req(&tool_call_req("c1", "echo")).is_ok());
+    }
+
+    #[test]
+    fn test_check_payload_size() {
+        let mut state = create_test_state();
+        let small = tool_call_req("c1", "echo");
+        let mut large = tool_call_req("c1", "echo");
+        large.params = serde_json::json!({ "text": "x".repeat(2048) });
+
+        // 未设置限制时不做检查
+        assert!(SmcpHandler::check_payload_size(&state, &large).is_ok());
+
+        state.max_payload_bytes = Some(1024);
+        assert!(SmcpHandler::check_payload_size(&state, &small).is_ok());
+        let err = SmcpHandler::check_payload_size(&state, &large).unwrap_err();
+        assert!(matches!(
+            err,
+            HandlerError::PayloadTooLarge { size, limit: 1024 } if size > 2048
+        ));
+        assert_eq!(err.code(), ErrorCode::PayloadTooLarge);
+        assert!(err.to_string().contains("exceeds the limit of 1024 bytes"));
+    }
+
+    #[test]
+    fn test_handler_error_code() {
+        let missing = HandlerError::NotFound("Computer 'c1' not found in office".to_string());
+        assert_eq!(missing.code(), ErrorCode::NotFound);
+        assert_eq!(
+            HandlerError::Session(SessionError::NotFound("sid".to_string())).code(),
+            ErrorCode::NotFound
+        );
+        assert_eq!(
+            HandlerError::Timeout("t".to_string()).code(),
+            ErrorCode::Timeout
+        );
+    }
+
+    #[test]
+    fn test_handler_error_serializes_to_payload() {
+        let cases = [
+            (
+                HandlerError::Auth(AuthError::InvalidApiKey),
+                4010,
+                "unauthorized",
+                false,
+            ),
+            (
+                HandlerError::Session(SessionError::NotFound("sid".to_string())),
+                4040,
+                "not_found",
+                false,
+            ),
+            (
+                HandlerError::Session(SessionError::AgentAlreadyExists),
+                4000,
+                "invalid_request",
+                false,
+            ),
+            (
+                HandlerError::Json(serde_json::from_str::<Value>("{").unwrap_err()),
+                4000,
+                "invalid_request",
+                false,
+            ),
+            (
+                HandlerError::Timeout("t".to_string()),
+                4080,
+                "timeout",
+                true,
+            ),
+            (
+                HandlerError::InvalidRequest("bad".to_string()),
+                4000,
+                "invalid_request",
+                false,
+            ),
+            (
+                HandlerError::NotFound("c1".to_string()),
+                4040,
+                "not_found",
+                false,
+            ),
+            (
+                HandlerError::PayloadTooLarge {
+                    size: 2048,
+                    limit: 1024,
+                },
+                4130,
+                "payload_too_large",
+                false,
+            ),
+        ];
+
+        for (err, code, category, retriable) in cases {
+            assert_eq!(
+                serde_json::to_value(&err).unwrap(),
+                serde_json::json!({
+                    "code": code,
+                    "category": category,
+                    "message": err.to_string(),
+                    "retriable": retriable,
+                }),
+                "unexpected payload for {:?}",
+                err
+            );
+        }
+
+        // 作为 ACK 的 Result 下发时错误位于 Err 字段中
+        let result: Result<Value, HandlerError> = Err(HandlerError::NotFound("c1".to_string()));
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["Err"]["code"], error_codes::NOT_FOUND);
+        assert_eq!(json["Err"]["message"], "Not found: c1");
+    }
+
+    fn list_room_req(
+        limit: Option<usize>,
+        offset: Option<usize>,
+        role: Option<Role>,
+    ) -> ListRoomReq {
+        ListRoomReq {
+            base: AgentCallData {
+                agent: "agent1".to_string(),
+                req_id: ReqId::new(),
+            },
+            office_id: "office1".to_string(),
+            limit,
+            offset,
+            role,
+        }
+    }
+
+    fn office_sessions() -> Vec<SessionData> {
+        ["c3", "a1", "c1", "c2"]
+            .iter()
+            .map(|sid| {
+                let role = if sid.starts_with('a') {
+                    ClientRole::Agent
+                } else {
+                    ClientRole::Computer
+                };
+                SessionData::new(sid.to_string(), format!("name-{}", sid), role)
+                    .with_office_id("office1".to_string())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_paginate_sessions_window() {
+        let sids = |page: Vec<SessionInfo>| page.into_iter().map(|s| s.sid).collect::<Vec<_>>();
+
+        let (page, total) =
+            SmcpHandler::paginate_sessions(office_sessions(), &list_room_req(None, None, None));
+        assert_eq!(total, 4);
+        assert_eq!(sids(page), vec!["a1", "c1", "c2", "c3"]);
+
+        let (page, total) = SmcpHandler::paginate_sessions(
+            office_sessions(),
+            &list_room_req(Some(2), Some(1), None),
+        );
+        assert_eq!(total, 4);
+        assert_eq!(sids(page), vec!["c1", "c2"]);
+
+        let (page, total) = SmcpHandler::paginate_sessions(
+            office_sessions(),
+            &list_room_req(Some(10), Some(5), None),
+        );
+        assert_eq!(total, 4);
+        assert!(page.is_empty());
+    }
+
+    #[test]
+    fn test_paginate_sessions_role_filter() {
+        let manager = SessionManager::new();
+        for session in office_sessions() {
+            manager.register_session(session).unwrap();
+        }
+        let office_id = "office1".to_string();
+
+        let (page, total) = SmcpHandler::paginate_sessions(
+            manager.list_in_office(&office_id, Some(ClientRole::Computer)),
+            &list_room_req(Some(2), None, Some(Role::Computer)),
+        );
+        assert_eq!(total, 3);
+        assert_eq!(page.len(), 2);
+        assert!(page.iter().all(|s| s.role == Role::Computer));
+
+        let (page, total) = SmcpHandler::paginate_sessions(
+            manager.list_in_office(&office_id, Some(ClientRole::Agent)),
+            &list_room_req(None, None, Some(Role::Agent)),
+        );
+        assert_eq!(total, 1);
+        assert_eq!(page[0].sid, "a1");
+
+        assert_eq!(manager.list_in_office(&office_id, None).len(), 4);
+    }
+
     #[test]
     fn test_validate_join_room_agent_already_in_other_room() {
         let state = create_test_state();
@@ -1160,6 +2214,38 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_resolve_join_session_registers_then_updates() {
+        let state = create_test_state();
+        let sid = "sid_c".to_string();
+
+        let created =
+            SmcpHandler::resolve_join_session(&sid, ClientRole::Computer, "c1".to_string(), &state)
+                .unwrap();
+        assert_eq!(created.office_id, None);
+        state
+            .session_manager
+            .update_office_id(&sid, Some("office1".to_string()))
+            .unwrap();
+
+        // 同一连接再次加入时复用现有会话，而不是报 sid 冲突
+        let rejoined =
+            SmcpHandler::resolve_join_session(&sid, ClientRole::Computer, "c1".to_string(), &state)
+                .unwrap();
+        assert_eq!(rejoined.sid, sid);
+        assert_eq!(rejoined.office_id.as_deref(), Some("office1"));
+        assert_eq!(state.session_manager.get_stats().total, 1);
+
+        let err =
+            SmcpHandler::resolve_join_session(&sid, ClientRole::Computer, "c2".to_string(), &state)
+                .unwrap_err();
+        assert!(err.contains("Name mismatch"));
+        let err =
+            SmcpHandler::resolve_join_session(&sid, ClientRole::Agent, "c1".to_string(), &state)
+                .unwrap_err();
+        assert!(err.contains("Role mismatch"));
+    }
+
     #[test]
     fn test_validate_join_room_agent_already_exists() {
         let state = create_test_state();
@@ -1263,6 +2349,34 @@ mod tests {
         assert_eq!(decision, JoinRoomDecision::Join);
     }
 
+    #[test]
+    fn test_validate_join_room_system_not_counted_as_agent() {
+        let state = create_test_state();
+        let system = SessionData::new("sid_sys".to_string(), "sys".to_string(), ClientRole::System)
+            .with_office_id("office1".to_string());
+        state.session_manager.register_session(system).unwrap();
+
+        // 系统会话不占用办公室的 Agent 名额
+        let agent = SessionData::new("sid_agent".to_string(), "a".to_string(), ClientRole::Agent);
+        let decision = SmcpHandler::validate_join_room(&agent, "office1", &state).unwrap();
+        assert_eq!(decision, JoinRoomDecision::Join);
+
+        // 已有 Agent 的办公室系统会话仍可加入或切换
+        let agent = agent.with_office_id("office2".to_string());
+        state.session_manager.register_session(agent).unwrap();
+        let system = state
+            .session_manager
+            .get_session(&"sid_sys".to_string())
+            .unwrap();
+        let decision = SmcpHandler::validate_join_room(&system, "office2", &state).unwrap();
+        assert_eq!(
+            decision,
+            JoinRoomDecision::LeaveAndJoin {
+                leave_office: "office1".to_string()
+            }
+        );
+    }
+
     #[test]
     fn test_enter_office_notification_computer() {
         let computer_name = "computer1".to_string();
@@ -1304,6 +2418,7 @@ mod tests {
             office_id: office_id.clone(),
             computer: Some(computer_name.clone()),
             agent: None,
+            reason: None,
         };
 
         assert_eq!(notification.office_id, office_id);
@@ -1350,4 +2465,95 @@ mod tests {
         let json = serde_json::to_string(&mcp_config_notification).unwrap();
         assert!(json.contains("\"computer\":\"computer1\""));
     }
+
+    #[test]
+    fn test_check_protocol_version() {
+        let req = |a2c_version: Option<&str>| EnterOfficeReq {
+            role: Role::Computer,
+            name: "computer1".to_string(),
+            office_id: "office1".to_string(),
+            meta: None,
+            a2c_version: a2c_version.map(str::to_string),
+        };
+
+        // 旧版客户端与同主版本客户端放行
+        assert!(SmcpHandler::check_protocol_version(&req(None)).is_ok());
+        assert!(SmcpHandler::check_protocol_version(&req(Some(smcp::PROTOCOL_VERSION))).is_ok());
+
+        let err = SmcpHandler::check_protocol_version(&req(Some("99.0.0"))).unwrap_err();
+        assert!(err.starts_with("version mismatch"));
+        assert!(err.contains("99.0.0"));
+        assert!(err.contains(smcp::PROTOCOL_VERSION));
+    }
+
+    #[test]
+    fn test_tool_policy_filters_get_tools_response() {
+        /// 对 agent1 隐藏 get_time 工具
+        #[derive(Debug)]
+        struct HideGetTime;
+
+        impl ToolPolicy for HideGetTime {
+            fn filter(&self, agent: &str, tools: Vec<SMCPTool>) -> Vec<SMCPTool> {
+                tools
+                    .into_iter()
+                    .filter(|tool| agent != "agent1" || tool.name != "get_time")
+                    .collect()
+            }
+        }
+
+        let response = serde_json::json!({
+            "tools": [
+                {"name": "echo", "description": "Echo", "params_schema": {}, "return_schema": null},
+                {"name": "get_time", "description": "Time", "params_schema": {}, "return_schema": null}
+            ],
+            "req_id": "req1"
+        });
+
+        // 默认策略原样返回
+        let state = create_test_state();
+        let ret = SmcpHandler::filter_tools_response(&state, "agent1", response.clone()).unwrap();
+        assert_eq!(ret.tools.len(), 2);
+
+        let state = ServerState {
+            tool_policy: Arc::new(HideGetTime),
+            ..create_test_state()
+        };
+        let ret = SmcpHandler::filter_tools_response(&state, "agent1", response.clone()).unwrap();
+        let names: Vec<_> = ret.tools.iter().map(|tool| tool.name.as_str()).collect();
+        assert_eq!(names, vec!["echo"]);
+        assert_eq!(ret.req_id.0, "req1");
+
+        let ret = SmcpHandler::filter_tools_response(&state, "agent2", response).unwrap();
+        assert_eq!(ret.tools.len(), 2);
+    }
+
+    #[test]
+    fn test_tool_policy_denies_tool_call() {
+        /// 禁止 agent1 调用 get_time 工具
+        #[derive(Debug)]
+        struct DenyGetTime;
+
+        impl ToolPolicy for DenyGetTime {
+            fn can_call(&self, agent: &str, tool_name: &str) -> bool {
+                agent != "agent1" || tool_name != "get_time"
+            }
+        }
+
+        // 默认策略允许任意调用
+        let state = create_test_state();
+        assert!(SmcpHandler::check_tool_policy(&state, "agent1", "get_time").is_ok());
+
+        let state = ServerState {
+            tool_policy: Arc::new(DenyGetTime),
+            ..create_test_state()
+        };
+        let err = SmcpHandler::check_tool_policy(&state, "agent1", "get_time").unwrap_err();
+        assert_eq!(err.code(), ErrorCode::Unauthorized);
+        let payload = err.to_payload();
+        assert_eq!(payload.code, error_codes::UNAUTHORIZED);
+        assert!(payload.message.contains("get_time"));
+
+        assert!(SmcpHandler::check_tool_policy(&state, "agent1", "echo").is_ok());
+        assert!(SmcpHandler::check_tool_policy(&state, "agent2", "get_time").is_ok());
+    }
 }
@@ -5,13 +5,21 @@
 
 pub mod auth;
 pub mod handler;
+pub mod metrics;
+pub mod sequence;
 pub mod server;
 pub mod session;
 
 // 重新导出主要类型
-pub use auth::{AuthError, AuthenticationProvider, DefaultAuthenticationProvider};
-pub use handler::{HandlerError, ServerState, SmcpHandler};
-pub use server::{SmcpServerBuilder, SmcpServerLayer};
+pub use auth::{
+    AuthError, AuthenticationProvider, AuthorizationProvider, DefaultAuthenticationProvider,
+    DefaultAuthorizationProvider, DefaultToolPolicy, JwtAuthenticationProvider, JwtClaims,
+    ToolPolicy,
+};
+pub use handler::{ErrorCode, HandlerError, HealthStatus, ServerState, SmcpHandler, TimeoutConfig};
+pub use metrics::ServerMetrics;
+pub use sequence::{OfficeSequencer, OfficeTurn};
+pub use server::{SmcpServerBuilder, SmcpServerConfig, SmcpServerLayer};
 pub use session::{ClientRole, SessionData, SessionError, SessionManager, SessionStats};
 
 /// SMCP 服务器预lude
@@ -19,6 +27,8 @@ pub use session::{ClientRole, SessionData, SessionError, SessionManager, Session
 pub mod prelude {
     pub use crate::auth::*;
     pub use crate::handler::*;
+    pub use crate::metrics::*;
+    pub use crate::sequence::*;
     pub use crate::server::*;
     pub use crate::session::*;
 }
@@ -0,0 +1,280 @@
+//! 服务器指标模块 / Server metrics module
+
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// 转发延迟直方图的桶上界（秒）
+const LATENCY_BUCKETS: [f64; 12] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0,
+];
+
+/// 服务器指标注册表
+/// Server metrics registry
+#[derive(Debug, Default)]
+pub struct ServerMetrics {
+    /// 发送尝试次数（含广播与带 ACK 的发送）
+    emit_attempts: AtomicU64,
+    /// 发送失败次数
+    emit_failures: AtomicU64,
+    /// 等待 ACK 超时次数
+    ack_timeouts: AtomicU64,
+    /// 加入办公室次数
+    joins: AtomicU64,
+    /// 离开办公室次数（含断开连接）
+    leaves: AtomicU64,
+    /// 工具调用次数
+    tool_calls: AtomicU64,
+    /// 失败的工具调用次数
+    tool_call_errors: AtomicU64,
+    /// 转发延迟直方图
+    forward_latency: LatencyHistogram,
+}
+
+/// 固定桶的延迟直方图
+#[derive(Debug, Default)]
+struct LatencyHistogram {
+    /// 各桶的非累计计数，最后一个为 +Inf 桶
+    buckets: [AtomicU64; LATENCY_BUCKETS.len() + 1],
+    /// 观测值总和（微秒）
+    sum_micros: AtomicU64,
+    /// 观测次数
+    count: AtomicU64,
+}
+
+impl LatencyHistogram {
+    fn observe(&self, latency: Duration) {
+        let secs = latency.as_secs_f64();
+        let idx = LATENCY_BUCKETS
+            .iter()
+            .position(|bound| secs <= *bound)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        self.buckets[idx].fetch_add(1, Ordering::Relaxed);
+        self.sum_micros.fetch_add(
+            u64::try_from(latency.as_micros()).unwrap_or(u64::MAX),
+            Ordering::Relaxed,
+        );
+        self.count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn render(&self, out: &mut String, name: &str, help: &str) {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} histogram", name);
+        let mut cumulative = 0;
+        for (bound, bucket) in LATENCY_BUCKETS.iter().zip(&self.buckets) {
+            cumulative += bucket.load(Ordering::Relaxed);
+            let _ = writeln!(out, "{}_bucket{{le=\"{}\"}} {}", name, bound, cumulative);
+        }
+        let count = self.count.load(Ordering::Relaxed);
+        let _ = writeln!(out, "{}_bucket{{le=\"+Inf\"}} {}", name, count);
+        let sum = self.sum_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0;
+        let _ = writeln!(out, "{}_sum {}", name, sum);
+        let _ = writeln!(out, "{}_count {}", name, count);
+    }
+}
+
+impl ServerMetrics {
+    /// 创建新的指标注册表
+    /// Create new metrics registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录一次发送结果，原样返回结果
+    /// Record an emit result and return it unchanged
+    pub fn track<T, E>(&self, result: Result<T, E>) -> Result<T, E> {
+        self.record_emit_attempt();
+        if result.is_err() {
+            self.record_emit_failure();
+        }
+        result
+    }
+
+    /// 记录一次发送尝试
+    pub fn record_emit_attempt(&self) {
+        self.emit_attempts.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 记录一次发送失败
+    pub fn record_emit_failure(&self) {
+        self.emit_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 记录一次 ACK 超时
+    pub fn record_ack_timeout(&self) {
+        self.ack_timeouts.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 记录一次加入办公室
+    pub fn record_join(&self) {
+        self.joins.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 记录一次离开办公室
+    pub fn record_leave(&self) {
+        self.leaves.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 记录一次工具调用及其是否成功
+    pub fn record_tool_call(&self, success: bool) {
+        self.tool_calls.fetch_add(1, Ordering::Relaxed);
+        if !success {
+            self.tool_call_errors.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// 记录一次向 Computer 转发并收到应答的耗时
+    pub fn observe_forward_latency(&self, latency: Duration) {
+        self.forward_latency.observe(latency);
+    }
+
+    /// 发送尝试次数
+    pub fn emit_attempts(&self) -> u64 {
+        self.emit_attempts.load(Ordering::Relaxed)
+    }
+
+    /// 发送失败次数
+    pub fn emit_failures(&self) -> u64 {
+        self.emit_failures.load(Ordering::Relaxed)
+    }
+
+    /// ACK 超时次数
+    pub fn ack_timeouts(&self) -> u64 {
+        self.ack_timeouts.load(Ordering::Relaxed)
+    }
+
+    /// 加入办公室次数
+    pub fn joins(&self) -> u64 {
+        self.joins.load(Ordering::Relaxed)
+    }
+
+    /// 离开办公室次数
+    pub fn leaves(&self) -> u64 {
+        self.leaves.load(Ordering::Relaxed)
+    }
+
+    /// 工具调用次数
+    pub fn tool_calls(&self) -> u64 {
+        self.tool_calls.load(Ordering::Relaxed)
+    }
+
+    /// 失败的工具调用次数
+    pub fn tool_call_errors(&self) -> u64 {
+        self.tool_call_errors.load(Ordering::Relaxed)
+    }
+
+    /// 以 Prometheus 文本格式输出指标
+    /// Render metrics in Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let counters = [
+            (
+                "smcp_emit_attempts_total",
+                "Total number of Socket.IO emit attempts",
+                self.emit_attempts(),
+            ),
+            (
+                "smcp_emit_failures_total",
+                "Total number of failed Socket.IO emits",
+                self.emit_failures(),
+            ),
+            (
+                "smcp_ack_timeouts_total",
+                "Total number of acknowledgements that timed out",
+                self.ack_timeouts(),
+            ),
+            (
+                "smcp_office_joins_total",
+                "Total number of office joins",
+                self.joins(),
+            ),
+            (
+                "smcp_office_leaves_total",
+                "Total number of office leaves, including disconnects",
+                self.leaves(),
+            ),
+            (
+                "smcp_tool_calls_total",
+                "Total number of tool calls forwarded to computers",
+                self.tool_calls(),
+            ),
+            (
+                "smcp_tool_call_errors_total",
+                "Total number of tool calls that failed",
+                self.tool_call_errors(),
+            ),
+        ];
+        for (name, help, value) in counters {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} counter", name);
+            let _ = writeln!(out, "{} {}", name, value);
+        }
+        self.forward_latency.render(
+            &mut out,
+            "smcp_forward_latency_seconds",
+            "Latency of requests forwarded to computers until their acknowledgement",
+        );
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_track_successful_emit() {
+        let metrics = ServerMetrics::new();
+        assert!(metrics.track(Ok::<(), &str>(())).is_ok());
+
+        assert_eq!(metrics.emit_attempts(), 1);
+        assert_eq!(metrics.emit_failures(), 0);
+    }
+
+    #[test]
+    fn test_track_failed_emit_increments_failures() {
+        let metrics = ServerMetrics::new();
+        let result = metrics.track(Err::<(), _>("socket closed"));
+
+        assert_eq!(result.unwrap_err(), "socket closed");
+        assert_eq!(metrics.emit_attempts(), 1);
+        assert_eq!(metrics.emit_failures(), 1);
+    }
+
+    #[test]
+    fn test_render_prometheus_format() {
+        let metrics = ServerMetrics::new();
+        metrics.record_emit_attempt();
+        metrics.record_ack_timeout();
+
+        let text = metrics.render();
+        assert!(text.contains("# TYPE smcp_emit_attempts_total counter"));
+        assert!(text.contains("smcp_emit_attempts_total 1"));
+        assert!(text.contains("smcp_emit_failures_total 0"));
+        assert!(text.contains("smcp_ack_timeouts_total 1"));
+    }
+
+    #[test]
+    fn test_render_tool_calls_and_latency_histogram() {
+        let metrics = ServerMetrics::new();
+        metrics.record_join();
+        metrics.record_tool_call(true);
+        metrics.record_tool_call(false);
+        metrics.observe_forward_latency(Duration::from_millis(20));
+        metrics.observe_forward_latency(Duration::from_secs(60));
+
+        let text = metrics.render();
+        assert!(text.contains("smcp_office_joins_total 1"));
+        assert!(text.contains("smcp_office_leaves_total 0"));
+        assert!(text.contains("smcp_tool_calls_total 2"));
+        assert!(text.contains("smcp_tool_call_errors_total 1"));
+        assert!(text.contains("# TYPE smcp_forward_latency_seconds histogram"));
+        // 桶计数是累计的，超出所有上界的观测只计入 +Inf
+        assert!(text.contains("smcp_forward_latency_seconds_bucket{le=\"0.01\"} 0"));
+        assert!(text.contains("smcp_forward_latency_seconds_bucket{le=\"0.025\"} 1"));
+        assert!(text.contains("smcp_forward_latency_seconds_bucket{le=\"30\"} 1"));
+        assert!(text.contains("smcp_forward_latency_seconds_bucket{le=\"+Inf\"} 2"));
+        assert!(text.contains("smcp_forward_latency_seconds_sum 60.02"));
+        assert!(text.contains("smcp_forward_latency_seconds_count 2"));
+    }
+}
@@ -0,0 +1,96 @@
+//! 办公室通知序号 / Per-office notification sequencing
+
+use dashmap::DashMap;
+use smcp::Sequenced;
+use std::sync::Arc;
+use tokio::sync::{Mutex, OwnedMutexGuard};
+
+/// 办公室通知序号分配器
+/// Per-office notification sequencer
+///
+/// 每个办公室持有一把锁，分配序号与广播都在锁内完成，
+/// 因此同一办公室的通知按 `seq` 递增的顺序进入各连接的发送队列。
+/// 序号在办公室清空后保留，重连的客户端不会看到序号回退。
+#[derive(Debug, Default)]
+pub struct OfficeSequencer {
+    offices: DashMap<String, Arc<Mutex<u64>>>,
+}
+
+impl OfficeSequencer {
+    /// 创建新的序号分配器
+    /// Create new sequencer
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 为办公室分配下一个序号，返回的 [`OfficeTurn`] 被丢弃前其他通知需等待
+    /// Allocate the next seq for an office; other notifications wait until the turn is dropped
+    pub async fn acquire(&self, office_id: &str) -> OfficeTurn {
+        let lock = self
+            .offices
+            .entry(office_id.to_string())
+            .or_default()
+            .clone();
+        let mut guard = lock.lock_owned().await;
+        *guard += 1;
+        OfficeTurn {
+            seq: *guard,
+            _guard: guard,
+        }
+    }
+}
+
+/// 办公室内的一次广播机会，持有期间独占该办公室的发送顺序
+/// A broadcast turn in an office, holding the office's emit order while alive
+#[derive(Debug)]
+pub struct OfficeTurn {
+    seq: u64,
+    _guard: OwnedMutexGuard<u64>,
+}
+
+impl OfficeTurn {
+    /// 分配到的序号
+    pub fn seq(&self) -> u64 {
+        self.seq
+    }
+
+    /// 为通知附加序号
+    /// Attach the seq to a notification
+    pub fn wrap<'a, T>(&self, data: &'a T) -> Sequenced<&'a T> {
+        Sequenced {
+            seq: self.seq,
+            data,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_seq_increases_per_office() {
+        let sequencer = OfficeSequencer::new();
+        assert_eq!(sequencer.acquire("office1").await.seq(), 1);
+        assert_eq!(sequencer.acquire("office1").await.seq(), 2);
+        assert_eq!(sequencer.acquire("office2").await.seq(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_turn_serializes_office() {
+        let sequencer = Arc::new(OfficeSequencer::new());
+        let turn = sequencer.acquire("office1").await;
+
+        let waiting = {
+            let sequencer = sequencer.clone();
+            tokio::spawn(async move { sequencer.acquire("office1").await.seq() })
+        };
+        tokio::task::yield_now().await;
+        assert!(!waiting.is_finished());
+        // 其他办公室不受影响
+        assert_eq!(sequencer.acquire("office2").await.seq(), 1);
+
+        drop(turn);
+        assert_eq!(waiting.await.unwrap(), 2);
+    }
+}
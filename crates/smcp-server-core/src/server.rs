@@ -1,12 +1,62 @@
 //! SMCP 服务器构建器 / SMCP server builder
 
-use crate::auth::{AuthenticationProvider, DefaultAuthenticationProvider};
-use crate::handler::{ServerState, SmcpHandler};
+use crate::auth::{
+    AuthenticationProvider, AuthorizationProvider, DefaultAuthenticationProvider,
+    DefaultAuthorizationProvider, DefaultToolPolicy, ToolPolicy,
+};
+use crate::handler::{ServerState, SmcpHandler, TimeoutConfig};
+use crate::metrics::ServerMetrics;
+use crate::sequence::OfficeSequencer;
 use crate::session::SessionManager;
 use socketioxide::layer::SocketIoLayer;
 use socketioxide::SocketIo;
 use std::sync::Arc;
-use tracing::info;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// SMCP 服务器配置
+/// SMCP server configuration
+#[derive(Clone, PartialEq, Eq)]
+pub struct SmcpServerConfig {
+    /// 管理员密钥，未设置认证提供者时用于默认 API Key 认证；默认不设置
+    pub auth_secret: Option<String>,
+    /// Socket.IO 命名空间，默认 [`smcp::SMCP_NAMESPACE`]
+    pub namespace: String,
+    /// 最大并发连接数，超出的连接在连接时被拒绝；默认不限制
+    pub max_sessions: Option<usize>,
+    /// 心跳间隔，未设置时使用 socketioxide 默认值
+    pub ping_interval: Option<Duration>,
+    /// 心跳超时，未设置时使用 socketioxide 默认值
+    pub ping_timeout: Option<Duration>,
+}
+
+impl Default for SmcpServerConfig {
+    fn default() -> Self {
+        Self {
+            auth_secret: None,
+            namespace: smcp::SMCP_NAMESPACE.to_string(),
+            max_sessions: None,
+            ping_interval: None,
+            ping_timeout: None,
+        }
+    }
+}
+
+impl std::fmt::Debug for SmcpServerConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // 不输出密钥内容
+        f.debug_struct("SmcpServerConfig")
+            .field(
+                "auth_secret",
+                &self.auth_secret.as_ref().map(|_| "<redacted>"),
+            )
+            .field("namespace", &self.namespace)
+            .field("max_sessions", &self.max_sessions)
+            .field("ping_interval", &self.ping_interval)
+            .field("ping_timeout", &self.ping_timeout)
+            .finish()
+    }
+}
 
 /// SMCP 服务器构建器
 /// SMCP server builder
@@ -14,8 +64,26 @@ use tracing::info;
 pub struct SmcpServerBuilder {
     /// 认证提供者
     auth_provider: Option<Arc<dyn AuthenticationProvider>>,
+    /// 授权提供者
+    authorization_provider: Option<Arc<dyn AuthorizationProvider>>,
+    /// 工具可见性策略
+    tool_policy: Option<Arc<dyn ToolPolicy>>,
     /// 会话管理器
     session_manager: Option<Arc<SessionManager>>,
+    /// 服务器配置
+    config: SmcpServerConfig,
+    /// Socket.IO 传输层允许的最大消息字节数，未设置时使用 socketioxide 默认值
+    max_payload: Option<u64>,
+    /// 协议层允许转发的最大消息字节数，未设置时与传输层限制一致
+    max_payload_bytes: Option<usize>,
+    /// 服务器对外公布的基础URL
+    public_url: Option<String>,
+    /// 请求超时上限
+    max_timeout: Option<Duration>,
+    /// 默认办公室
+    default_office: Option<String>,
+    /// 各转发事件的默认等待时间
+    timeout_config: TimeoutConfig,
 }
 
 impl Default for SmcpServerBuilder {
@@ -30,7 +98,16 @@ impl SmcpServerBuilder {
     pub fn new() -> Self {
         Self {
             auth_provider: None,
+            authorization_provider: None,
+            tool_policy: None,
             session_manager: None,
+            config: SmcpServerConfig::default(),
+            max_payload: None,
+            max_payload_bytes: None,
+            public_url: None,
+            max_timeout: None,
+            default_office: None,
+            timeout_config: TimeoutConfig::default(),
         }
     }
 
@@ -41,6 +118,20 @@ impl SmcpServerBuilder {
         self
     }
 
+    /// 设置授权提供者，用于限制会话可加入的办公室
+    /// Set authorization provider restricting which offices a session may join
+    pub fn with_authorization_provider(mut self, provider: Arc<dyn AuthorizationProvider>) -> Self {
+        self.authorization_provider = Some(provider);
+        self
+    }
+
+    /// 设置工具可见性策略，过滤返回给 Agent 的工具列表
+    /// Set the tool visibility policy filtering the tool list returned to agents
+    pub fn with_tool_policy(mut self, policy: Arc<dyn ToolPolicy>) -> Self {
+        self.tool_policy = Some(policy);
+        self
+    }
+
     /// 设置默认认证提供者（基于 API Key）
     /// Set default authentication provider (API Key based)
     pub fn with_default_auth(
@@ -56,6 +147,34 @@ impl SmcpServerBuilder {
         self
     }
 
+    /// 设置管理员密钥，未设置认证提供者时据此启用默认 API Key 认证
+    /// Set the admin secret used by the default API key authentication when no provider is set
+    pub fn with_auth_secret(mut self, secret: impl Into<String>) -> Self {
+        self.config.auth_secret = Some(secret.into());
+        self
+    }
+
+    /// 设置 Socket.IO 命名空间
+    /// Set the Socket.IO namespace
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.config.namespace = namespace.into();
+        self
+    }
+
+    /// 设置最大并发连接数，超出的连接在连接时被拒绝
+    /// Set the maximum number of concurrent connections; connections past the cap are rejected
+    pub fn with_max_sessions(mut self, max: usize) -> Self {
+        self.config.max_sessions = Some(max);
+        self
+    }
+
+    /// 一次性设置服务器配置
+    /// Set the whole server configuration
+    pub fn with_config(mut self, config: SmcpServerConfig) -> Self {
+        self.config = config;
+        self
+    }
+
     /// 设置会话管理器
     /// Set session manager
     pub fn with_session_manager(mut self, manager: Arc<SessionManager>) -> Self {
@@ -63,25 +182,131 @@ impl SmcpServerBuilder {
         self
     }
 
+    /// 设置心跳间隔
+    /// Set heartbeat ping interval
+    pub fn with_ping_interval(mut self, interval: Duration) -> Self {
+        self.config.ping_interval = Some(interval);
+        self
+    }
+
+    /// 设置心跳超时
+    /// Set heartbeat ping timeout
+    pub fn with_ping_timeout(mut self, timeout: Duration) -> Self {
+        self.config.ping_timeout = Some(timeout);
+        self
+    }
+
+    /// 设置 Socket.IO 传输层的最大消息字节数
+    /// Set the Socket.IO transport max payload in bytes
+    pub fn with_max_payload(mut self, bytes: u64) -> Self {
+        self.max_payload = Some(bytes);
+        self
+    }
+
+    /// 设置协议层的最大消息字节数，超限的请求或结果会以明确的错误拒绝
+    /// Set the protocol max payload in bytes; oversized requests or results are rejected with a clear error
+    pub fn with_max_payload_bytes(mut self, bytes: usize) -> Self {
+        self.max_payload_bytes = Some(bytes);
+        self
+    }
+
+    /// 设置服务器对外公布的基础URL，随服务器能力下发给客户端
+    /// Set the public base URL announced to clients in the server capabilities
+    pub fn with_public_url(mut self, url: impl Into<String>) -> Self {
+        self.public_url = Some(url.into());
+        self
+    }
+
+    /// 设置请求超时上限，随服务器能力下发；Agent 请求更长的超时会被截断到该值
+    /// Set the request timeout ceiling, announced in the server capabilities; longer agent timeouts are clamped to it
+    pub fn with_max_timeout(mut self, timeout: Duration) -> Self {
+        self.max_timeout = Some(timeout);
+        self
+    }
+
+    /// 设置默认办公室，未携带办公室 ID 的请求落入该办公室；多办公室部署保持不设置
+    /// Set the default office used by requests without an office id; leave unset for multi-office deployments
+    pub fn with_default_office(mut self, office_id: impl Into<String>) -> Self {
+        self.default_office = Some(office_id.into());
+        self
+    }
+
+    /// 设置各转发事件的默认等待时间，实际等待时间不超过超时上限
+    /// Set the default wait per forwarded event; the actual wait never exceeds the timeout ceiling
+    pub fn with_timeout_config(mut self, config: TimeoutConfig) -> Self {
+        self.timeout_config = config;
+        self
+    }
+
     /// 构建 Socket.IO Layer
     /// Build Socket.IO layer
     pub fn build_layer(self) -> Result<SmcpServerLayer, crate::handler::HandlerError> {
-        // 使用默认值
-        let auth_provider = self
-            .auth_provider
-            .unwrap_or_else(|| Arc::new(DefaultAuthenticationProvider::new(None, None)));
+        // 使用默认值，显式设置的认证提供者优先于管理员密钥
+        let auth_provider = self.auth_provider.unwrap_or_else(|| {
+            Arc::new(DefaultAuthenticationProvider::new(
+                self.config.auth_secret.clone(),
+                None,
+            ))
+        });
+        let authorization_provider = self
+            .authorization_provider
+            .unwrap_or_else(|| Arc::new(DefaultAuthorizationProvider));
+        let tool_policy = self
+            .tool_policy
+            .unwrap_or_else(|| Arc::new(DefaultToolPolicy));
         let session_manager = self
             .session_manager
             .unwrap_or_else(|| Arc::new(SessionManager::new()));
 
         // 创建 Socket.IO
-        let (layer, io) = SocketIo::builder().build_layer();
+        let mut io_builder = SocketIo::builder();
+        if let Some(interval) = self.config.ping_interval {
+            io_builder = io_builder.ping_interval(interval);
+        }
+        if let Some(timeout) = self.config.ping_timeout {
+            io_builder = io_builder.ping_timeout(timeout);
+        }
+        if let Some(max_payload) = self.max_payload {
+            io_builder = io_builder.max_payload(max_payload);
+        }
+        let (layer, io) = io_builder.build_layer();
+
+        // 协议层限制默认与传输层一致；大于传输层时超限消息会被传输层静默丢弃
+        let transport_limit =
+            usize::try_from(io.config().engine_config.max_payload).unwrap_or(usize::MAX);
+        let max_payload_bytes = match self.max_payload_bytes {
+            Some(protocol_limit) => {
+                if protocol_limit > transport_limit {
+                    warn!(
+                        "Protocol max_payload_bytes ({}) exceeds the Socket.IO max payload ({}); \
+                         oversized messages will be dropped by the transport instead of rejected",
+                        protocol_limit, transport_limit
+                    );
+                }
+                Some(protocol_limit)
+            }
+            None => self.max_payload.map(|_| transport_limit),
+        };
 
         // 更新状态中的 io 引用
         let state = ServerState {
             session_manager,
             auth_provider,
+            authorization_provider,
+            tool_policy,
             io: Arc::new(io.clone()),
+            metrics: Arc::new(ServerMetrics::new()),
+            max_payload_bytes,
+            public_url: self.public_url,
+            office_sequencer: Arc::new(OfficeSequencer::new()),
+            // 上限至少一秒，避免所有请求立即超时
+            max_timeout_secs: self
+                .max_timeout
+                .map_or(smcp::DEFAULT_MAX_TIMEOUT_SECS, |t| t.as_secs().max(1)),
+            default_office: self.default_office,
+            timeout_config: self.timeout_config,
+            started_at: Instant::now(),
+            config: self.config,
         };
 
         // 注册处理器
@@ -151,6 +376,155 @@ mod tests {
         assert!(Arc::ptr_eq(&layer.state.session_manager, &manager));
     }
 
+    #[test]
+    fn test_server_builder_ping_config() {
+        let layer = SmcpServerBuilder::new()
+            .with_ping_interval(Duration::from_secs(10))
+            .with_ping_timeout(Duration::from_secs(5))
+            .build_layer()
+            .unwrap();
+
+        let engine_config = &layer.io.config().engine_config;
+        assert_eq!(engine_config.ping_interval, Duration::from_secs(10));
+        assert_eq!(engine_config.ping_timeout, Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_server_builder_ping_config_defaults() {
+        let layer = SmcpServerBuilder::new().build_layer().unwrap();
+        let (_, default_io) = SocketIo::builder().build_layer();
+
+        let engine_config = &layer.io.config().engine_config;
+        let default_config = &default_io.config().engine_config;
+        assert_eq!(engine_config.ping_interval, default_config.ping_interval);
+        assert_eq!(engine_config.ping_timeout, default_config.ping_timeout);
+    }
+
+    #[test]
+    fn test_server_builder_max_payload_feeds_transport_and_protocol() {
+        let layer = SmcpServerBuilder::new()
+            .with_max_payload(4096)
+            .build_layer()
+            .unwrap();
+
+        assert_eq!(layer.io.config().engine_config.max_payload, 4096);
+        assert_eq!(layer.state.max_payload_bytes, Some(4096));
+    }
+
+    #[test]
+    fn test_server_builder_max_payload_defaults_to_unlimited_protocol() {
+        let layer = SmcpServerBuilder::new().build_layer().unwrap();
+        let (_, default_io) = SocketIo::builder().build_layer();
+
+        assert_eq!(
+            layer.io.config().engine_config.max_payload,
+            default_io.config().engine_config.max_payload
+        );
+        assert_eq!(layer.state.max_payload_bytes, None);
+    }
+
+    #[test]
+    fn test_server_builder_public_url_in_capabilities() {
+        let layer = SmcpServerBuilder::new()
+            .with_public_url("https://smcp.example.com")
+            .build_layer()
+            .unwrap();
+
+        let capabilities = layer.state.capabilities();
+        assert_eq!(
+            capabilities.public_url.as_deref(),
+            Some("https://smcp.example.com")
+        );
+        assert_eq!(capabilities.protocol_version, smcp::PROTOCOL_VERSION);
+
+        let default_layer = SmcpServerBuilder::new().build_layer().unwrap();
+        assert_eq!(default_layer.state.capabilities().public_url, None);
+    }
+
+    #[test]
+    fn test_server_builder_max_timeout_in_capabilities() {
+        let layer = SmcpServerBuilder::new()
+            .with_max_timeout(Duration::from_secs(5))
+            .build_layer()
+            .unwrap();
+        assert_eq!(layer.state.capabilities().max_timeout_secs, Some(5));
+
+        let default_layer = SmcpServerBuilder::new().build_layer().unwrap();
+        assert_eq!(
+            default_layer.state.capabilities().max_timeout_secs,
+            Some(smcp::DEFAULT_MAX_TIMEOUT_SECS)
+        );
+    }
+
+    #[test]
+    fn test_server_builder_default_office() {
+        let layer = SmcpServerBuilder::new()
+            .with_default_office("main")
+            .build_layer()
+            .unwrap();
+        assert_eq!(layer.state.resolve_office_id(String::new()), "main");
+        assert_eq!(layer.state.resolve_office_id("other".to_string()), "other");
+
+        let default_layer = SmcpServerBuilder::new().build_layer().unwrap();
+        assert_eq!(default_layer.state.resolve_office_id(String::new()), "");
+    }
+
+    #[test]
+    fn test_server_builder_timeout_config() {
+        let layer = SmcpServerBuilder::new()
+            .with_max_timeout(Duration::from_secs(10))
+            .with_timeout_config(TimeoutConfig {
+                tool_call: Duration::from_secs(1),
+                ..Default::default()
+            })
+            .build_layer()
+            .unwrap();
+        let req = |timeout| smcp::ToolCallReq {
+            base: smcp::AgentCallData {
+                agent: "agent1".to_string(),
+                req_id: smcp::ReqId::new(),
+            },
+            computer: "computer1".to_string(),
+            tool_name: "tool".to_string(),
+            params: serde_json::json!({}),
+            timeout,
+            idempotency_key: None,
+        };
+
+        // 未携带超时使用配置值，正超时优先且不超过上限
+        assert_eq!(layer.state.tool_call_timeout_secs(&req(0)), 1);
+        assert_eq!(layer.state.tool_call_timeout_secs(&req(5)), 5);
+        assert_eq!(layer.state.tool_call_timeout_secs(&req(60)), 10);
+        assert_eq!(
+            layer.state.timeout_config.get_tools,
+            Duration::from_secs(30)
+        );
+    }
+
+    #[test]
+    fn test_server_builder_config() {
+        let default_layer = SmcpServerBuilder::new().build_layer().unwrap();
+        assert_eq!(default_layer.state.config, SmcpServerConfig::default());
+        assert_eq!(default_layer.state.config.namespace, smcp::SMCP_NAMESPACE);
+
+        let layer = SmcpServerBuilder::new()
+            .with_auth_secret("secret")
+            .with_namespace("/custom")
+            .with_max_sessions(3)
+            .with_ping_timeout(Duration::from_secs(5))
+            .build_layer()
+            .unwrap();
+        let config = &layer.state.config;
+        assert_eq!(config.auth_secret.as_deref(), Some("secret"));
+        assert_eq!(config.namespace, "/custom");
+        assert_eq!(config.max_sessions, Some(3));
+        assert_eq!(
+            layer.io.config().engine_config.ping_timeout,
+            Duration::from_secs(5)
+        );
+        assert!(!format!("{:?}", config).contains("secret\""));
+    }
+
     #[test]
     fn test_socket_io_accessor_returns_inner() {
         let layer = SmcpServerBuilder::new().build_layer().unwrap();
@@ -2,7 +2,11 @@
 
 use dashmap::DashMap;
 use serde::{Deserialize, Serialize};
+use smcp::OfficeMeta;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 use thiserror::Error;
 
 // 类型别名
@@ -16,6 +20,8 @@ pub enum SessionError {
     NotFound(String),
     #[error("Name already registered: {0}")]
     NameAlreadyRegistered(String),
+    #[error("Session id already registered: {0}")]
+    SidExists(SessionId),
     #[error("Agent already in room: {0}")]
     AgentAlreadyInRoom(OfficeId),
     #[error("Agent already exists in room")]
@@ -24,6 +30,8 @@ pub enum SessionError {
     ComputerAlreadyExists(String, OfficeId),
     #[error("Invalid session state: {0}")]
     InvalidState(String),
+    #[error("Session limit reached: {0}")]
+    LimitReached(usize),
 }
 
 /// 客户端角色
@@ -31,6 +39,8 @@ pub enum SessionError {
 pub enum ClientRole {
     Agent,
     Computer,
+    /// 系统会话：可查看任意办公室，不参与单 Agent 限制
+    System,
 }
 
 impl From<smcp::Role> for ClientRole {
@@ -38,6 +48,7 @@ impl From<smcp::Role> for ClientRole {
         match role {
             smcp::Role::Agent => ClientRole::Agent,
             smcp::Role::Computer => ClientRole::Computer,
+            smcp::Role::System => ClientRole::System,
         }
     }
 }
@@ -47,6 +58,7 @@ impl From<ClientRole> for smcp::Role {
         match role {
             ClientRole::Agent => smcp::Role::Agent,
             ClientRole::Computer => smcp::Role::Computer,
+            ClientRole::System => smcp::Role::System,
         }
     }
 }
@@ -56,12 +68,13 @@ impl std::fmt::Display for ClientRole {
         match self {
             ClientRole::Agent => write!(f, "agent"),
             ClientRole::Computer => write!(f, "computer"),
+            ClientRole::System => write!(f, "system"),
         }
     }
 }
 
 /// 会话数据
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SessionData {
     /// 会话 ID
     pub sid: SessionId,
@@ -107,6 +120,12 @@ pub struct SessionManager {
     sessions: Arc<DashMap<SessionId, SessionData>>,
     /// name -> sid 映射（用于通过 name 查找 session）
     name_to_sid: Arc<DashMap<String, SessionId>>,
+    /// office_id -> (sid -> 角色) 成员索引，用于按办公室计数与筛选
+    office_members: Arc<DashMap<OfficeId, HashMap<SessionId, ClientRole>>>,
+    /// office_id -> 办公室元数据
+    office_meta: Arc<DashMap<OfficeId, OfficeMeta>>,
+    /// 已接受的连接数
+    connections: Arc<AtomicUsize>,
 }
 
 impl SessionManager {
@@ -117,6 +136,7 @@ impl SessionManager {
                 Some(office_id) => format!("computer:{}:{}", office_id, name),
                 None => format!("computer::{}", name),
             },
+            ClientRole::System => format!("system:{}", name),
         }
     }
 
@@ -125,25 +145,61 @@ impl SessionManager {
         Self {
             sessions: Arc::new(DashMap::new()),
             name_to_sid: Arc::new(DashMap::new()),
+            office_members: Arc::new(DashMap::new()),
+            office_meta: Arc::new(DashMap::new()),
+            connections: Arc::new(AtomicUsize::new(0)),
         }
     }
 
+    /// 在连接数未达到上限时接受新连接，`None` 表示不限制
+    pub fn try_accept_connection(&self, max: Option<usize>) -> bool {
+        self.connections
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |count| match max {
+                Some(max) if count >= max => None,
+                _ => Some(count + 1),
+            })
+            .is_ok()
+    }
+
+    /// 释放一个已接受的连接
+    pub fn release_connection(&self) {
+        let _ = self
+            .connections
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |count| {
+                count.checked_sub(1)
+            });
+    }
+
+    /// 当前已接受的连接数
+    pub fn connection_count(&self) -> usize {
+        self.connections.load(Ordering::Acquire)
+    }
+
     /// 注册新会话
+    ///
+    /// 只负责创建：sid 已存在时返回 [`SessionError::SidExists`]，名称已被其他 sid
+    /// 占用时返回 [`SessionError::NameAlreadyRegistered`]。已注册连接的重复加入
+    /// 应先通过 [`get_session`](Self::get_session) 取得现有会话再更新，而不是重新注册。
     pub fn register_session(&self, session: SessionData) -> Result<(), SessionError> {
         let key = Self::name_key(&session.role, session.office_id.as_ref(), &session.name);
-        // 检查 name 是否已被其他 sid 使用
-        if let Some(existing_sid) = self.name_to_sid.get(&key) {
-            if *existing_sid != session.sid {
-                return Err(SessionError::NameAlreadyRegistered(session.name));
+        // 持有 sid 条目期间完成检查与插入，避免并发注册同一 sid
+        let entry = match self.sessions.entry(session.sid.clone()) {
+            dashmap::mapref::entry::Entry::Occupied(_) => {
+                return Err(SessionError::SidExists(session.sid));
             }
-            // 如果是同一个 sid 重新注册，允许（幂等操作）
-            tracing::debug!("Name '{}' re-registered by same sid", session.name);
-            return Ok(());
+            dashmap::mapref::entry::Entry::Vacant(entry) => entry,
+        };
+        // 检查 name 是否已被其他 sid 使用
+        if self.name_to_sid.contains_key(&key) {
+            return Err(SessionError::NameAlreadyRegistered(session.name));
         }
 
         // 注册映射
-        self.sessions.insert(session.sid.clone(), session.clone());
         self.name_to_sid.insert(key, session.sid.clone());
+        if let Some(office_id) = &session.office_id {
+            self.add_office_member(office_id, &session.sid, &session.role);
+        }
+        entry.insert(session.clone());
 
         tracing::debug!("Registered session: {} -> {}", session.name, session.sid);
         Ok(())
@@ -160,6 +216,9 @@ impl SessionManager {
             &session.1.name,
         );
         self.name_to_sid.remove(&key);
+        if let Some(office_id) = &session.1.office_id {
+            self.remove_office_member(office_id, sid);
+        }
 
         tracing::debug!("Unregistered session: {} -> {}", session.1.name, sid);
         Some(session.1)
@@ -205,10 +264,78 @@ impl SessionManager {
             self.name_to_sid.insert(new_key, sid.clone());
         }
 
+        if old_office_id != office_id {
+            if let Some(old) = &old_office_id {
+                self.remove_office_member(old, sid);
+            }
+            if let Some(new) = &office_id {
+                self.add_office_member(new, sid, &role);
+            }
+        }
+
         session.office_id = office_id;
         Ok(())
     }
 
+    fn add_office_member(&self, office_id: &OfficeId, sid: &SessionId, role: &ClientRole) {
+        self.office_members
+            .entry(office_id.clone())
+            .or_default()
+            .insert(sid.clone(), role.clone());
+    }
+
+    fn remove_office_member(&self, office_id: &OfficeId, sid: &SessionId) {
+        if let Some(mut members) = self.office_members.get_mut(office_id) {
+            members.remove(sid);
+        }
+        self.office_members
+            .remove_if(office_id, |_, members| members.is_empty());
+    }
+
+    /// 统计办公室内的会话数，`role` 为 `None` 时统计全部角色
+    ///
+    /// 基于成员索引计数，不扫描全部会话。
+    pub fn count_in_office(&self, office_id: &OfficeId, role: Option<ClientRole>) -> usize {
+        self.office_members
+            .get(office_id)
+            .map(|members| match role {
+                Some(role) => members.values().filter(|r| **r == role).count(),
+                None => members.len(),
+            })
+            .unwrap_or(0)
+    }
+
+    /// 列出办公室内的会话，`role` 为 `None` 时列出全部角色
+    ///
+    /// 基于成员索引查找，不扫描全部会话。
+    pub fn list_in_office(
+        &self,
+        office_id: &OfficeId,
+        role: Option<ClientRole>,
+    ) -> Vec<SessionData> {
+        // 先复制成员 sid 再释放索引锁，避免与持有会话锁的写路径交叉加锁
+        let sids: Vec<SessionId> = match self.office_members.get(office_id) {
+            Some(members) => members
+                .iter()
+                .filter(|(_, r)| role.as_ref().is_none_or(|role| *r == role))
+                .map(|(sid, _)| sid.clone())
+                .collect(),
+            None => return Vec::new(),
+        };
+        sids.iter()
+            .filter_map(|sid| self.sessions.get(sid).map(|s| s.clone()))
+            .collect()
+    }
+
+    /// 列出办公室内指定角色的会话
+    pub fn list_in_office_by_role(
+        &self,
+        office_id: &OfficeId,
+        role: ClientRole,
+    ) -> Vec<SessionData> {
+        self.list_in_office(office_id, Some(role))
+    }
+
     /// 获取指定办公室内的所有会话
     pub fn get_sessions_in_office(&self, office_id: &OfficeId) -> Vec<SessionData> {
         self.sessions
@@ -220,9 +347,7 @@ impl SessionManager {
 
     /// 检查房间内是否已有 Agent
     pub fn has_agent_in_office(&self, office_id: &OfficeId) -> bool {
-        self.sessions
-            .iter()
-            .any(|s| s.office_id.as_ref() == Some(office_id) && s.role == ClientRole::Agent)
+        self.count_in_office(office_id, Some(ClientRole::Agent)) > 0
     }
 
     /// 检查房间内是否有指定名称的 Computer
@@ -257,6 +382,67 @@ impl SessionManager {
         self.sessions.iter().map(|s| s.clone()).collect()
     }
 
+    /// 导出全部会话的快照，供嵌入方持久化（如写入 Redis 或磁盘）
+    pub fn snapshot(&self) -> Vec<SessionData> {
+        let mut sessions = self.get_all_sessions();
+        sessions.sort_by(|a, b| a.sid.cmp(&b.sid));
+        sessions
+    }
+
+    /// 用快照替换当前全部会话，并重建名称与办公室成员索引
+    ///
+    /// 与其他会话冲突（sid 或名称重复）的条目会被跳过并记录警告。重启后快照中的 socket ID
+    /// 已失效，调用方应在客户端重连时按名称对账，用新会话替换旧条目；办公室元数据与连接计数
+    /// 不在快照范围内。
+    pub fn restore(&self, sessions: Vec<SessionData>) {
+        self.sessions.clear();
+        self.name_to_sid.clear();
+        self.office_members.clear();
+
+        for session in sessions {
+            let sid = session.sid.clone();
+            if let Err(e) = self.register_session(session) {
+                tracing::warn!("Skipping session {} while restoring snapshot: {}", sid, e);
+            }
+        }
+    }
+
+    /// 设置办公室元数据，覆盖已有的值；未提供创建时间时沿用已有值或取当前时间
+    pub fn set_office_meta(&self, office_id: &OfficeId, mut meta: OfficeMeta) {
+        let existing = self.office_meta.get(office_id).and_then(|m| m.created_at);
+        meta.created_at = meta.created_at.or(existing).or_else(Self::now_secs);
+        self.office_meta.insert(office_id.clone(), meta);
+    }
+
+    /// 仅在办公室尚无元数据时登记，返回是否登记成功
+    pub fn init_office_meta(&self, office_id: &OfficeId, mut meta: OfficeMeta) -> bool {
+        match self.office_meta.entry(office_id.clone()) {
+            dashmap::mapref::entry::Entry::Occupied(_) => false,
+            dashmap::mapref::entry::Entry::Vacant(entry) => {
+                meta.created_at = meta.created_at.or_else(Self::now_secs);
+                entry.insert(meta);
+                true
+            }
+        }
+    }
+
+    /// 获取办公室元数据
+    pub fn get_office_meta(&self, office_id: &OfficeId) -> Option<OfficeMeta> {
+        self.office_meta.get(office_id).map(|m| m.clone())
+    }
+
+    /// 移除办公室元数据
+    pub fn remove_office_meta(&self, office_id: &OfficeId) -> Option<OfficeMeta> {
+        self.office_meta.remove(office_id).map(|(_, meta)| meta)
+    }
+
+    fn now_secs() -> Option<u64> {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .map(|d| d.as_secs())
+    }
+
     /// 获取会话统计信息
     pub fn get_stats(&self) -> SessionStats {
         let total = self.sessions.len();
@@ -270,11 +456,17 @@ impl SessionManager {
             .iter()
             .filter(|s| s.role == ClientRole::Computer)
             .count();
+        let systems = self
+            .sessions
+            .iter()
+            .filter(|s| s.role == ClientRole::System)
+            .count();
 
         SessionStats {
             total,
             agents,
             computers,
+            systems,
         }
     }
 }
@@ -288,6 +480,8 @@ pub struct SessionStats {
     pub agents: usize,
     /// Computer 数量
     pub computers: usize,
+    /// 系统会话数量
+    pub systems: usize,
 }
 
 impl Default for SessionManager {
@@ -302,6 +496,104 @@ mod tests {
     use serde_json::json;
     use uuid::Uuid;
 
+    #[test]
+    fn test_snapshot_restore_round_trip() {
+        let source = SessionManager::new();
+        source
+            .register_session(
+                SessionData::new("sid-a".to_string(), "agent1".to_string(), ClientRole::Agent)
+                    .with_office_id("office1".to_string()),
+            )
+            .unwrap();
+        source
+            .register_session(
+                SessionData::new(
+                    "sid-c1".to_string(),
+                    "computer1".to_string(),
+                    ClientRole::Computer,
+                )
+                .with_office_id("office1".to_string())
+                .with_extra(json!({"tools": 3})),
+            )
+            .unwrap();
+        source
+            .register_session(
+                SessionData::new(
+                    "sid-c2".to_string(),
+                    "computer2".to_string(),
+                    ClientRole::Computer,
+                )
+                .with_office_id("office2".to_string()),
+            )
+            .unwrap();
+
+        let persisted = serde_json::to_string(&source.snapshot()).unwrap();
+        let restored = SessionManager::new();
+        // 恢复会替换已有会话
+        restored
+            .register_session(SessionData::new(
+                "stale".to_string(),
+                "old".to_string(),
+                ClientRole::Agent,
+            ))
+            .unwrap();
+        restored.restore(serde_json::from_str(&persisted).unwrap());
+
+        assert_eq!(restored.snapshot(), source.snapshot());
+        assert!(restored.get_session(&"stale".to_string()).is_none());
+        assert_eq!(restored.get_sid_by_name("agent1").as_deref(), Some("sid-a"));
+        assert!(restored.get_sid_by_name("old").is_none());
+
+        let office1 = "office1".to_string();
+        assert_eq!(restored.count_in_office(&office1, None), 2);
+        assert!(restored.has_agent_in_office(&office1));
+        assert_eq!(
+            restored
+                .get_computer_sid_in_office(&office1, "computer1")
+                .as_deref(),
+            Some("sid-c1")
+        );
+        assert_eq!(
+            restored.count_in_office(&"office2".to_string(), Some(ClientRole::Computer)),
+            1
+        );
+
+        // 恢复后索引可继续正常更新
+        restored
+            .update_office_id(&"sid-c1".to_string(), Some("office2".to_string()))
+            .unwrap();
+        assert_eq!(restored.count_in_office(&office1, None), 1);
+        assert_eq!(restored.count_in_office(&"office2".to_string(), None), 2);
+    }
+
+    #[test]
+    fn test_restore_skips_conflicting_sessions() {
+        let manager = SessionManager::new();
+        manager.restore(vec![
+            SessionData::new("sid-1".to_string(), "agent1".to_string(), ClientRole::Agent),
+            SessionData::new("sid-2".to_string(), "agent1".to_string(), ClientRole::Agent),
+        ]);
+
+        assert_eq!(manager.get_stats().total, 1);
+        assert_eq!(manager.get_sid_by_name("agent1").as_deref(), Some("sid-1"));
+    }
+
+    #[test]
+    fn test_connection_cap() {
+        let manager = SessionManager::new();
+
+        assert!(manager.try_accept_connection(Some(2)));
+        assert!(manager.try_accept_connection(Some(2)));
+        assert!(!manager.try_accept_connection(Some(2)));
+        assert_eq!(manager.connection_count(), 2);
+
+        // 释放后可再次接受，不限制时总是接受
+        manager.release_connection();
+        assert!(manager.try_accept_connection(Some(2)));
+        assert!(manager.try_accept_connection(None));
+        assert_eq!(manager.connection_count(), 3);
+    }
+
     #[test]
     fn test_session_registration() {
         let manager = SessionManager::new();
@@ -362,7 +654,10 @@ mod tests {
 
         // Computer 名称按 office 唯一：同 office 冲突
         assert!(manager.register_session(session3.clone()).is_ok());
-        assert!(manager.register_session(session3).is_ok());
+        assert!(matches!(
+            manager.register_session(session3),
+            Err(SessionError::SidExists(_))
+        ));
 
         let dup_same_office = SessionData::new(
             Uuid::new_v4().to_string(),
@@ -402,6 +697,76 @@ mod tests {
         assert!(manager.has_computer_in_office(&office_id, "test_computer"));
     }
 
+    #[test]
+    fn test_office_counts_and_role_listing() {
+        let manager = SessionManager::new();
+        let office_a = "office_a".to_string();
+        let office_b = "office_b".to_string();
+        let register = |name: &str, role: ClientRole, office: &OfficeId| {
+            let sid = Uuid::new_v4().to_string();
+            manager
+                .register_session(
+                    SessionData::new(sid.clone(), name.to_string(), role)
+                        .with_office_id(office.clone()),
+                )
+                .unwrap();
+            sid
+        };
+
+        register("agent_a", ClientRole::Agent, &office_a);
+        let c1 = register("c1", ClientRole::Computer, &office_a);
+        register("c2", ClientRole::Computer, &office_a);
+        register("c3", ClientRole::Computer, &office_b);
+        register("monitor", ClientRole::System, &office_a);
+
+        assert_eq!(manager.count_in_office(&office_a, None), 4);
+        assert_eq!(
+            manager.count_in_office(&office_a, Some(ClientRole::Computer)),
+            2
+        );
+        assert_eq!(
+            manager.count_in_office(&office_a, Some(ClientRole::Agent)),
+            1
+        );
+        assert_eq!(
+            manager.count_in_office(&office_b, Some(ClientRole::Agent)),
+            0
+        );
+        assert_eq!(manager.count_in_office(&"missing".to_string(), None), 0);
+
+        let mut computers: Vec<String> = manager
+            .list_in_office_by_role(&office_a, ClientRole::Computer)
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        computers.sort();
+        assert_eq!(computers, vec!["c1", "c2"]);
+        let agents = manager.list_in_office_by_role(&office_a, ClientRole::Agent);
+        assert_eq!(agents.len(), 1);
+        assert_eq!(agents[0].name, "agent_a");
+
+        // 离开办公室与注销后计数同步更新
+        manager
+            .update_office_id(&c1, Some(office_b.clone()))
+            .unwrap();
+        assert_eq!(
+            manager.count_in_office(&office_a, Some(ClientRole::Computer)),
+            1
+        );
+        assert_eq!(
+            manager.count_in_office(&office_b, Some(ClientRole::Computer)),
+            2
+        );
+        manager.unregister_session(&c1);
+        assert_eq!(manager.count_in_office(&office_b, None), 1);
+        assert_eq!(
+            manager
+                .list_in_office_by_role(&office_b, ClientRole::Computer)
+                .len(),
+            1
+        );
+    }
+
     #[test]
     fn test_session_unregistration() {
         let manager = SessionManager::new();
@@ -451,17 +816,27 @@ mod tests {
     }
 
     #[test]
-    fn test_register_session_idempotent_same_sid() {
+    fn test_register_session_existing_sid_rejected() {
         let manager = SessionManager::new();
         let sid = Uuid::new_v4().to_string();
         let session1 = SessionData::new(sid.clone(), "same_name".to_string(), ClientRole::Agent);
         let session2 = SessionData::new(sid.clone(), "same_name".to_string(), ClientRole::Agent);
+        let renamed = SessionData::new(sid.clone(), "other_name".to_string(), ClientRole::Agent);
 
         assert!(manager.register_session(session1).is_ok());
-        assert!(manager.register_session(session2).is_ok());
+        assert!(matches!(
+            manager.register_session(session2),
+            Err(SessionError::SidExists(s)) if s == sid
+        ));
+        // 不同名称的同 sid 注册也不能覆盖原会话
+        assert!(matches!(
+            manager.register_session(renamed),
+            Err(SessionError::SidExists(_))
+        ));
 
         let retrieved = manager.get_session(&sid).unwrap();
         assert_eq!(retrieved.name, "same_name");
+        assert!(manager.get_sid_by_name("other_name").is_none());
     }
 
     #[test]
@@ -527,4 +902,40 @@ mod tests {
             SessionData::new(sid, "n".to_string(), ClientRole::Computer).with_extra(extra.clone());
         assert_eq!(session.extra, extra);
     }
+
+    #[test]
+    fn test_office_meta_registry() {
+        let manager = SessionManager::new();
+        let office = "office1".to_string();
+        assert!(manager.get_office_meta(&office).is_none());
+
+        let meta = OfficeMeta {
+            label: Some("Team A".to_string()),
+            ..Default::default()
+        };
+        assert!(manager.init_office_meta(&office, meta));
+        let stored = manager.get_office_meta(&office).unwrap();
+        assert_eq!(stored.label.as_deref(), Some("Team A"));
+        let created_at = stored.created_at.expect("created_at should be filled");
+
+        // 首次登记后再次初始化不会覆盖
+        let other = OfficeMeta {
+            label: Some("Team B".to_string()),
+            ..Default::default()
+        };
+        assert!(!manager.init_office_meta(&office, other.clone()));
+        assert_eq!(
+            manager.get_office_meta(&office).unwrap().label.as_deref(),
+            Some("Team A")
+        );
+
+        // 显式设置会覆盖，但保留创建时间
+        manager.set_office_meta(&office, other);
+        let stored = manager.get_office_meta(&office).unwrap();
+        assert_eq!(stored.label.as_deref(), Some("Team B"));
+        assert_eq!(stored.created_at, Some(created_at));
+
+        assert!(manager.remove_office_meta(&office).is_some());
+        assert!(manager.get_office_meta(&office).is_none());
+    }
 }
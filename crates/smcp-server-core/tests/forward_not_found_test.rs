@@ -0,0 +1,86 @@
+//! 测试转发事件在目标 Computer 不在办公室时统一返回 NotFound
+
+#[path = "test_utils.rs"]
+mod test_utils;
+
+use std::time::Duration;
+
+use rust_socketio::Payload;
+use serde_json::json;
+use tokio::sync::oneshot;
+use tokio::time::sleep;
+
+use smcp::events::*;
+use smcp::*;
+use test_utils::*;
+
+#[tokio::test]
+async fn test_forward_events_report_missing_computer_as_not_found() {
+    let _ = tracing_subscriber::fmt().with_env_filter("info").try_init();
+
+    let server = SmcpTestServer::start().await;
+    let server_url = server.url();
+
+    // 创建Agent客户端并加入办公室
+    let agent_client = create_test_client(&server_url, "smcp").await;
+    sleep(Duration::from_millis(200)).await;
+    join_office(&agent_client, Role::Agent, "office1", "agent1").await;
+
+    // 同时满足所有转发请求结构的载荷
+    let req = json!({
+        "agent": "agent1",
+        "req_id": "req-not-found",
+        "computer": "nonexistent",
+        "tool_name": "echo",
+        "params": {},
+        "timeout": 5,
+        "name": "greeting",
+        "uri": "file:///missing"
+    });
+
+    for event in [
+        CLIENT_TOOL_CALL,
+        CLIENT_GET_TOOLS,
+        CLIENT_GET_CONFIG,
+        CLIENT_GET_DESKTOP,
+        CLIENT_GET_PROMPTS,
+        CLIENT_GET_PROMPT,
+        CLIENT_GET_RESOURCE_TEMPLATES,
+        CLIENT_READ_RESOURCE,
+    ] {
+        let (result_tx, result_rx) = oneshot::channel::<serde_json::Value>();
+        agent_client
+            .emit_with_ack(
+                event,
+                req.clone(),
+                Duration::from_secs(5),
+                ack_to_sender(result_tx, |p| match p {
+                    Payload::Text(mut values, _) => values.pop().unwrap_or(serde_json::Value::Null),
+                    _ => serde_json::Value::Null,
+                }),
+            )
+            .await
+            .expect("emit_with_ack failed");
+
+        let ack = tokio::time::timeout(Duration::from_secs(5), result_rx)
+            .await
+            .expect("ack timeout")
+            .unwrap();
+
+        // ack 可能是 [ {"Err": {...}} ] 的数组包裹形式
+        let ack = match ack {
+            serde_json::Value::Array(mut a) if a.len() == 1 => a.pop().unwrap(),
+            v => v,
+        };
+        assert_eq!(
+            ack["Err"]["code"],
+            json!(error_codes::NOT_FOUND),
+            "unexpected ack for {}: {}",
+            event,
+            ack
+        );
+    }
+
+    agent_client.disconnect().await.unwrap();
+    server.shutdown();
+}
@@ -177,8 +177,10 @@ async fn test_get_config_complete_flow() {
     // Computer加入办公室
     let computer_join_req = EnterOfficeReq {
         office_id: "office1".to_string(),
+        meta: None,
         role: Role::Computer,
         name: "computer1".to_string(),
+        a2c_version: None,
     };
     computer_client
         .emit("server:join_office", json!(computer_join_req))
@@ -200,8 +202,10 @@ async fn test_get_config_complete_flow() {
     // Agent加入办公室
     let agent_join_req = EnterOfficeReq {
         office_id: "office1".to_string(),
+        meta: None,
         role: Role::Agent,
         name: "agent1".to_string(),
+        a2c_version: None,
     };
     agent_client
         .emit("server:join_office", json!(agent_join_req))
@@ -277,8 +281,10 @@ async fn test_get_config_computer_not_found() {
     // Agent加入办公室
     let agent_join_req = EnterOfficeReq {
         office_id: "office1".to_string(),
+        meta: None,
         role: Role::Agent,
         name: "agent1".to_string(),
+        a2c_version: None,
     };
     agent_client
         .emit("server:join_office", json!(agent_join_req))
@@ -330,7 +336,7 @@ async fn test_get_config_computer_not_found() {
             arr.first()
                 .map(|v| {
                     // 如果元素是包含Err字段的对象
-                    if let Some(err) = v.get("Err").and_then(|e| e.as_str()) {
+                    if let Some(err) = v.get("Err").and_then(|e| e["message"].as_str()) {
                         err.to_string()
                     } else if let Some(s) = v.as_str() {
                         s.to_string()
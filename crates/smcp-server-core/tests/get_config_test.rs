@@ -17,10 +17,12 @@ use tower::Layer;
 use tower::Service;
 
 use smcp_server_core::{
-    auth::DefaultAuthenticationProvider,
-    handler::SmcpHandler,
+    auth::{DefaultAuthenticationProvider, DefaultAuthorizationProvider, DefaultToolPolicy},
+    handler::{SmcpHandler, TimeoutConfig},
+    metrics::ServerMetrics,
+    sequence::OfficeSequencer,
     session::{ClientRole, SessionData, SessionManager},
-    ServerState, SmcpServerBuilder,
+    ServerState, SmcpServerBuilder, SmcpServerConfig,
 };
 use socketioxide::{adapter::LocalAdapter, SocketIo};
 
@@ -39,7 +41,18 @@ async fn test_get_config_event_registered() {
     let state = ServerState {
         session_manager: session_manager.clone(),
         auth_provider,
+        authorization_provider: Arc::new(DefaultAuthorizationProvider),
+        tool_policy: Arc::new(DefaultToolPolicy),
         io: Arc::new(io.clone()),
+        metrics: Arc::new(ServerMetrics::new()),
+        max_payload_bytes: None,
+        public_url: None,
+        office_sequencer: Arc::new(OfficeSequencer::new()),
+        max_timeout_secs: smcp::DEFAULT_MAX_TIMEOUT_SECS,
+        default_office: None,
+        timeout_config: TimeoutConfig::default(),
+        started_at: std::time::Instant::now(),
+        config: SmcpServerConfig::default(),
     };
     SmcpHandler::register_handlers(&io, state);
 }
@@ -57,7 +70,18 @@ async fn test_get_config_unauthorized_role() {
     let state = ServerState {
         session_manager: session_manager.clone(),
         auth_provider,
+        authorization_provider: Arc::new(DefaultAuthorizationProvider),
+        tool_policy: Arc::new(DefaultToolPolicy),
         io: Arc::new(io.clone()),
+        metrics: Arc::new(ServerMetrics::new()),
+        max_payload_bytes: None,
+        public_url: None,
+        office_sequencer: Arc::new(OfficeSequencer::new()),
+        max_timeout_secs: smcp::DEFAULT_MAX_TIMEOUT_SECS,
+        default_office: None,
+        timeout_config: TimeoutConfig::default(),
+        started_at: std::time::Instant::now(),
+        config: SmcpServerConfig::default(),
     };
     SmcpHandler::register_handlers(&io, state);
 
@@ -85,7 +109,18 @@ async fn test_get_config_success() {
     let state = ServerState {
         session_manager: session_manager.clone(),
         auth_provider,
+        authorization_provider: Arc::new(DefaultAuthorizationProvider),
+        tool_policy: Arc::new(DefaultToolPolicy),
         io: Arc::new(io.clone()),
+        metrics: Arc::new(ServerMetrics::new()),
+        max_payload_bytes: None,
+        public_url: None,
+        office_sequencer: Arc::new(OfficeSequencer::new()),
+        max_timeout_secs: smcp::DEFAULT_MAX_TIMEOUT_SECS,
+        default_office: None,
+        timeout_config: TimeoutConfig::default(),
+        started_at: std::time::Instant::now(),
+        config: SmcpServerConfig::default(),
     };
     SmcpHandler::register_handlers(&io, state);
 
@@ -133,10 +168,14 @@ async fn test_get_config_success() {
 
     // 模拟返回的配置数据
     let mock_config = GetComputerConfigRet {
-        inputs: Some(vec![json!({
-            "name": "test_input",
-            "type": "stdio"
-        })]),
+        inputs: Some(vec![smcp::MCPServerInput::PromptString(
+            smcp::PromptStringInput {
+                id: "test_input".to_string(),
+                description: "Test input".to_string(),
+                default: None,
+                password: None,
+            },
+        )]),
         servers: json!({
             "test_server": {
                 "name": "test_server",
@@ -164,7 +203,18 @@ async fn test_get_config_computer_not_found() {
     let state = ServerState {
         session_manager: session_manager.clone(),
         auth_provider,
+        authorization_provider: Arc::new(DefaultAuthorizationProvider),
+        tool_policy: Arc::new(DefaultToolPolicy),
         io: Arc::new(io.clone()),
+        metrics: Arc::new(ServerMetrics::new()),
+        max_payload_bytes: None,
+        public_url: None,
+        office_sequencer: Arc::new(OfficeSequencer::new()),
+        max_timeout_secs: smcp::DEFAULT_MAX_TIMEOUT_SECS,
+        default_office: None,
+        timeout_config: TimeoutConfig::default(),
+        started_at: std::time::Instant::now(),
+        config: SmcpServerConfig::default(),
     };
     SmcpHandler::register_handlers(&io, state);
 
@@ -201,7 +251,18 @@ async fn test_get_config_agent_not_in_office() {
     let state = ServerState {
         session_manager: session_manager.clone(),
         auth_provider,
+        authorization_provider: Arc::new(DefaultAuthorizationProvider),
+        tool_policy: Arc::new(DefaultToolPolicy),
         io: Arc::new(io.clone()),
+        metrics: Arc::new(ServerMetrics::new()),
+        max_payload_bytes: None,
+        public_url: None,
+        office_sequencer: Arc::new(OfficeSequencer::new()),
+        max_timeout_secs: smcp::DEFAULT_MAX_TIMEOUT_SECS,
+        default_office: None,
+        timeout_config: TimeoutConfig::default(),
+        started_at: std::time::Instant::now(),
+        config: SmcpServerConfig::default(),
     };
     SmcpHandler::register_handlers(&io, state);
 
@@ -336,8 +397,10 @@ async fn test_get_config_event_forwarded() {
     // Computer加入office
     let computer_join_req = EnterOfficeReq {
         office_id: "office1".to_string(),
+        meta: None,
         role: Role::Computer,
         name: "computer1".to_string(),
+        a2c_version: None,
     };
 
     computer_client
@@ -360,8 +423,10 @@ async fn test_get_config_event_forwarded() {
     // Agent加入同一个office
     let agent_join_req = EnterOfficeReq {
         office_id: "office1".to_string(),
+        meta: None,
         role: Role::Agent,
         name: "agent1".to_string(),
+        a2c_version: None,
     };
 
     agent_client
@@ -419,8 +484,10 @@ async fn test_get_config_computer_not_found_integration() {
     // Agent加入office
     let agent_join_req = EnterOfficeReq {
         office_id: "office1".to_string(),
+        meta: None,
         role: Role::Agent,
         name: "agent1".to_string(),
+        a2c_version: None,
     };
 
     agent_client
@@ -475,8 +542,10 @@ async fn test_get_config_unauthorized_role_integration() {
     // Computer加入office
     let computer_join_req = EnterOfficeReq {
         office_id: "office1".to_string(),
+        meta: None,
         role: Role::Computer,
         name: "computer1".to_string(),
+        a2c_version: None,
     };
 
     computer_client
@@ -230,7 +230,7 @@ async fn test_get_tools_computer_not_found() {
         serde_json::Value::Array(arr) => arr
             .first()
             .map(|v| {
-                if let Some(err) = v.get("Err").and_then(|e| e.as_str()) {
+                if let Some(err) = v.get("Err").and_then(|e| e["message"].as_str()) {
                     err.to_string()
                 } else if let Some(s) = v.as_str() {
                     s.to_string()
@@ -311,7 +311,7 @@ async fn test_get_tools_cross_office_permission_denied() {
         serde_json::Value::Array(arr) => arr
             .first()
             .map(|v| {
-                if let Some(err) = v.get("Err").and_then(|e| e.as_str()) {
+                if let Some(err) = v.get("Err").and_then(|e| e["message"].as_str()) {
                     err.to_string()
                 } else if let Some(s) = v.as_str() {
                     s.to_string()
@@ -477,7 +477,7 @@ async fn test_get_tools_computer_not_in_office() {
         serde_json::Value::Array(arr) => arr
             .first()
             .map(|v| {
-                if let Some(err) = v.get("Err").and_then(|e| e.as_str()) {
+                if let Some(err) = v.get("Err").and_then(|e| e["message"].as_str()) {
                     err.to_string()
                 } else if let Some(s) = v.as_str() {
                     s.to_string()
@@ -1,10 +1,12 @@
 //! 测试消息转发功能
 
 use smcp_server_core::{
-    auth::DefaultAuthenticationProvider,
-    handler::SmcpHandler,
+    auth::{DefaultAuthenticationProvider, DefaultAuthorizationProvider, DefaultToolPolicy},
+    handler::{SmcpHandler, TimeoutConfig},
+    metrics::ServerMetrics,
+    sequence::OfficeSequencer,
     session::{ClientRole, SessionData, SessionManager},
-    ServerState,
+    ServerState, SmcpServerConfig,
 };
 use socketioxide::SocketIo;
 use std::sync::Arc;
@@ -22,7 +24,18 @@ async fn test_message_forwarding() {
     let state = ServerState {
         session_manager: session_manager.clone(),
         auth_provider,
+        authorization_provider: Arc::new(DefaultAuthorizationProvider),
+        tool_policy: Arc::new(DefaultToolPolicy),
         io: Arc::new(io.clone()),
+        metrics: Arc::new(ServerMetrics::new()),
+        max_payload_bytes: None,
+        public_url: None,
+        office_sequencer: Arc::new(OfficeSequencer::new()),
+        max_timeout_secs: smcp::DEFAULT_MAX_TIMEOUT_SECS,
+        default_office: None,
+        timeout_config: TimeoutConfig::default(),
+        started_at: std::time::Instant::now(),
+        config: SmcpServerConfig::default(),
     };
     SmcpHandler::register_handlers(&io, state);
 
@@ -116,7 +129,18 @@ async fn test_error_handling() {
     let state = ServerState {
         session_manager,
         auth_provider,
+        authorization_provider: Arc::new(DefaultAuthorizationProvider),
+        tool_policy: Arc::new(DefaultToolPolicy),
         io: Arc::new(io),
+        metrics: Arc::new(ServerMetrics::new()),
+        max_payload_bytes: None,
+        public_url: None,
+        office_sequencer: Arc::new(OfficeSequencer::new()),
+        max_timeout_secs: smcp::DEFAULT_MAX_TIMEOUT_SECS,
+        default_office: None,
+        timeout_config: TimeoutConfig::default(),
+        started_at: std::time::Instant::now(),
+        config: SmcpServerConfig::default(),
     };
 
     // 创建一个不在办公室的 Agent 会话
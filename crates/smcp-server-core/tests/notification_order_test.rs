@@ -0,0 +1,106 @@
+//! Test per-office notification ordering
+
+#[path = "test_utils.rs"]
+mod test_utils;
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures_util::FutureExt;
+use rust_socketio::asynchronous::ClientBuilder;
+use rust_socketio::{Payload, TransportType};
+use serde_json::json;
+use tokio::time::sleep;
+
+use smcp::events::{NOTIFY_UPDATE_CONFIG, NOTIFY_UPDATE_TOOL_LIST};
+use smcp::*;
+use test_utils::*;
+
+type Received = Arc<Mutex<Vec<(&'static str, u64)>>>;
+
+fn record(
+    event: &'static str,
+    received: Received,
+) -> impl FnMut(
+    Payload,
+    rust_socketio::asynchronous::Client,
+) -> std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>>
+       + Send
+       + Sync {
+    move |payload: Payload, _client| {
+        let received = received.clone();
+        async move {
+            if let Payload::Text(values, _) = payload {
+                if let Some(seq) = values
+                    .into_iter()
+                    .next()
+                    .and_then(|v| {
+                        serde_json::from_value::<Sequenced<UpdateToolListNotification>>(v).ok()
+                    })
+                    .map(|n| n.seq)
+                {
+                    received.lock().unwrap().push((event, seq));
+                }
+            }
+        }
+        .boxed()
+    }
+}
+
+#[tokio::test]
+async fn test_rapid_updates_arrive_in_seq_order() {
+    let _ = tracing_subscriber::fmt().with_env_filter("info").try_init();
+
+    let server = SmcpTestServer::start().await;
+    let server_url = server.url();
+
+    let received: Received = Arc::new(Mutex::new(Vec::new()));
+    let agent_client = ClientBuilder::new(server_url.clone())
+        .transport_type(TransportType::Websocket)
+        .namespace(SMCP_NAMESPACE)
+        .opening_header("x-api-key", "test_secret")
+        .on(NOTIFY_UPDATE_CONFIG, record("config", received.clone()))
+        .on(
+            NOTIFY_UPDATE_TOOL_LIST,
+            record("tool_list", received.clone()),
+        )
+        .connect()
+        .await
+        .expect("Failed to connect agent");
+    join_office(&agent_client, Role::Agent, "office1", "agent1").await;
+
+    let computer_client = create_test_client(&server_url, SMCP_NAMESPACE).await;
+    join_office(&computer_client, Role::Computer, "office1", "computer1").await;
+
+    const ROUNDS: usize = 20;
+    for _ in 0..ROUNDS {
+        computer_client
+            .emit("server:update_config", json!({"computer": "computer1"}))
+            .await
+            .unwrap();
+        computer_client
+            .emit("server:update_tool_list", json!({"computer": "computer1"}))
+            .await
+            .unwrap();
+    }
+    sleep(Duration::from_millis(500)).await;
+
+    let received = received.lock().unwrap().clone();
+    assert_eq!(received.len(), ROUNDS * 2);
+    assert_eq!(
+        received
+            .iter()
+            .filter(|(event, _)| *event == "config")
+            .count(),
+        ROUNDS
+    );
+    assert!(
+        received.windows(2).all(|pair| pair[0].1 < pair[1].1),
+        "notifications out of order: {:?}",
+        received
+    );
+
+    computer_client.disconnect().await.unwrap();
+    agent_client.disconnect().await.unwrap();
+    server.shutdown();
+}
@@ -0,0 +1,183 @@
+//! Test per-office authorization on join
+
+#[path = "test_utils.rs"]
+mod test_utils;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+use rust_socketio::asynchronous::{Client, ClientBuilder};
+use rust_socketio::{Payload, TransportType};
+use serde_json::json;
+use tokio::sync::oneshot;
+
+use smcp::*;
+use smcp_server_core::session::SessionData;
+use smcp_server_core::{AuthError, AuthorizationProvider, JwtAuthenticationProvider, JwtClaims};
+use test_utils::*;
+
+/// 只允许进入指定办公室的授权提供者
+#[derive(Debug)]
+struct SingleOfficeAuthorization {
+    office_id: String,
+}
+
+#[async_trait]
+impl AuthorizationProvider for SingleOfficeAuthorization {
+    async fn can_join(
+        &self,
+        session: &SessionData,
+        _claims: Option<&JwtClaims>,
+        office_id: &str,
+    ) -> Result<(), AuthError> {
+        if office_id == self.office_id {
+            Ok(())
+        } else {
+            Err(AuthError::Failed(format!(
+                "{} may not join office {}",
+                session.name, office_id
+            )))
+        }
+    }
+}
+
+async fn start_server() -> SmcpTestServer {
+    SmcpTestServer::start_with(|builder| {
+        builder.with_authorization_provider(Arc::new(SingleOfficeAuthorization {
+            office_id: "allowed".to_string(),
+        }))
+    })
+    .await
+}
+
+/// 发送事件并返回 ACK 的第一个值
+async fn emit_ack(client: &Client, event: &str, data: serde_json::Value) -> serde_json::Value {
+    let (result_tx, result_rx) = oneshot::channel::<serde_json::Value>();
+    client
+        .emit_with_ack(
+            event,
+            data,
+            Duration::from_secs(5),
+            ack_to_sender(result_tx, |p| match p {
+                Payload::Text(mut values, _) => values.pop().unwrap_or(serde_json::Value::Null),
+                _ => serde_json::Value::Null,
+            }),
+        )
+        .await
+        .expect("emit_with_ack failed");
+    tokio::time::timeout(Duration::from_secs(5), result_rx)
+        .await
+        .expect("ack timeout")
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_join_permitted_office() {
+    let server = start_server().await;
+    let server_url = server.url();
+
+    let client = create_test_client(&server_url, SMCP_NAMESPACE).await;
+    join_office(&client, Role::Agent, "allowed", "agent1").await;
+
+    client.disconnect().await.unwrap();
+    server.shutdown();
+}
+
+#[tokio::test]
+async fn test_join_forbidden_office_rejected() {
+    let server = start_server().await;
+    let server_url = server.url();
+
+    let client = create_test_client(&server_url, SMCP_NAMESPACE).await;
+    let result = emit_ack(
+        &client,
+        "server:join_office",
+        json!({"role": "agent", "office_id": "forbidden", "name": "agent1"}),
+    )
+    .await;
+    assert_eq!(result[0], false);
+    assert!(result[1]
+        .as_str()
+        .unwrap_or_default()
+        .contains("may not join office forbidden"));
+
+    // 被拒绝后仍可加入允许的办公室
+    join_office(&client, Role::Agent, "allowed", "agent1").await;
+
+    client.disconnect().await.unwrap();
+    server.shutdown();
+}
+
+const JWT_SECRET: &str = "jwt-secret";
+
+/// 按令牌声明中的办公室白名单授权的提供者
+#[derive(Debug)]
+struct ClaimsAuthorization;
+
+#[async_trait]
+impl AuthorizationProvider for ClaimsAuthorization {
+    async fn can_join(
+        &self,
+        session: &SessionData,
+        claims: Option<&JwtClaims>,
+        office_id: &str,
+    ) -> Result<(), AuthError> {
+        match claims {
+            Some(claims) if claims.allows_office(office_id) => Ok(()),
+            _ => Err(AuthError::Failed(format!(
+                "{} may not join office {}",
+                session.name, office_id
+            ))),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_join_denied_by_token_claims() {
+    let server = SmcpTestServer::start_with(|builder| {
+        builder
+            .with_auth_provider(Arc::new(JwtAuthenticationProvider::new(JWT_SECRET)))
+            .with_authorization_provider(Arc::new(ClaimsAuthorization))
+    })
+    .await;
+
+    let exp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+        + 3600;
+    let token = encode(
+        &Header::new(Algorithm::HS256),
+        &json!({"sub": "agent1", "exp": exp, "office_allowlist": ["allowed"]}),
+        &EncodingKey::from_secret(JWT_SECRET.as_bytes()),
+    )
+    .unwrap();
+    let bearer = format!("Bearer {token}");
+    let client = ClientBuilder::new(server.url())
+        .transport_type(TransportType::Websocket)
+        .namespace(SMCP_NAMESPACE)
+        .opening_header("authorization", bearer.as_str())
+        .connect()
+        .await
+        .expect("Failed to connect client");
+
+    // 令牌声明未列出的办公室被拒绝
+    let result = emit_ack(
+        &client,
+        "server:join_office",
+        json!({"role": "agent", "office_id": "forbidden", "name": "agent1"}),
+    )
+    .await;
+    assert_eq!(result[0], false);
+    assert!(result[1]
+        .as_str()
+        .unwrap_or_default()
+        .contains("may not join office forbidden"));
+
+    join_office(&client, Role::Agent, "allowed", "agent1").await;
+
+    client.disconnect().await.unwrap();
+    server.shutdown();
+}
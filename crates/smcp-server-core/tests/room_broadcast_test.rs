@@ -153,8 +153,10 @@ async fn test_computer_switch_room_broadcasts_leave_notification() {
     // 让client1加入office1
     let join_req1 = EnterOfficeReq {
         office_id: "office1".to_string(),
+        meta: None,
         role: Role::Computer,
         name: "computer1".to_string(),
+        a2c_version: None,
     };
 
     client1
@@ -177,8 +179,10 @@ async fn test_computer_switch_room_broadcasts_leave_notification() {
     // 让client2加入office1作为agent
     let join_req2 = EnterOfficeReq {
         office_id: "office1".to_string(),
+        meta: None,
         role: Role::Agent,
         name: "agent1".to_string(),
+        a2c_version: None,
     };
 
     client2
@@ -190,8 +194,10 @@ async fn test_computer_switch_room_broadcasts_leave_notification() {
     // 现在，computer1从office1切换到office2
     let join_req3 = EnterOfficeReq {
         office_id: "office2".to_string(),
+        meta: None,
         role: Role::Computer,
         name: "computer1".to_string(),
+        a2c_version: None,
     };
 
     client1
@@ -212,6 +218,101 @@ async fn test_computer_switch_room_broadcasts_leave_notification() {
     server.shutdown();
 }
 
+#[tokio::test]
+async fn test_leave_notification_carries_reason() {
+    let _ = tracing_subscriber::fmt().with_env_filter("info").try_init();
+
+    let server = SmcpTestServer::start().await;
+    let server_url = server.url();
+
+    // agent 在 office1 中收集离开通知
+    let leaves = Arc::new(std::sync::Mutex::new(Vec::<LeaveOfficeNotification>::new()));
+    let leaves_clone = leaves.clone();
+    let agent = ClientBuilder::new(server_url.clone())
+        .transport_type(TransportType::Websocket)
+        .namespace("smcp")
+        .opening_header("x-api-key", "test_secret")
+        .on("notify:leave_office", move |payload: Payload, _client| {
+            let leaves = leaves_clone.clone();
+            async move {
+                if let Payload::Text(values, _) = payload {
+                    if let Ok(leave) = serde_json::from_value(values[0].clone()) {
+                        leaves.lock().unwrap().push(leave);
+                    }
+                }
+            }
+            .boxed()
+        })
+        .connect()
+        .await
+        .expect("Connection failed");
+    sleep(Duration::from_millis(100)).await;
+
+    let join = |role: Role, name: &str| {
+        json!(EnterOfficeReq {
+            office_id: "office1".to_string(),
+            meta: None,
+            role,
+            name: name.to_string(),
+            a2c_version: None,
+        })
+    };
+    agent
+        .emit("server:join_office", join(Role::Agent, "agent1"))
+        .await
+        .unwrap();
+
+    let mut computers = Vec::new();
+    for name in ["computer1", "computer2"] {
+        let computer = ClientBuilder::new(server_url.clone())
+            .transport_type(TransportType::Websocket)
+            .namespace("smcp")
+            .opening_header("x-api-key", "test_secret")
+            .connect()
+            .await
+            .expect("Connection failed");
+        computer
+            .emit("server:join_office", join(Role::Computer, name))
+            .await
+            .unwrap();
+        computers.push(computer);
+    }
+    sleep(Duration::from_millis(200)).await;
+
+    // computer1 主动离开并说明原因，computer2 直接断开
+    let leave_req = LeaveOfficeReq {
+        office_id: "office1".to_string(),
+        reason: Some(LEAVE_REASON_SHUTDOWN.to_string()),
+    };
+    computers[0]
+        .emit("server:leave_office", json!(leave_req))
+        .await
+        .unwrap();
+    sleep(Duration::from_millis(200)).await;
+    computers[1].disconnect().await.unwrap();
+    sleep(Duration::from_millis(300)).await;
+
+    let leaves = leaves.lock().unwrap().clone();
+    let reason_of = |name: &str| {
+        leaves
+            .iter()
+            .find(|leave| leave.computer.as_deref() == Some(name))
+            .map(|leave| leave.reason.clone())
+    };
+    assert_eq!(
+        reason_of("computer1"),
+        Some(Some(LEAVE_REASON_SHUTDOWN.to_string()))
+    );
+    assert_eq!(
+        reason_of("computer2"),
+        Some(Some(LEAVE_REASON_DISCONNECT.to_string()))
+    );
+
+    computers[0].disconnect().await.unwrap();
+    agent.disconnect().await.unwrap();
+    server.shutdown();
+}
+
 #[tokio::test]
 async fn test_join_office_broadcasts_only_to_room() {
     let _ = tracing_subscriber::fmt().with_env_filter("info").try_init();
@@ -303,8 +404,10 @@ async fn test_join_office_broadcasts_only_to_room() {
     // client1加入office1
     let join_req1 = EnterOfficeReq {
         office_id: "office1".to_string(),
+        meta: None,
         role: Role::Agent,
         name: "agent1".to_string(),
+        a2c_version: None,
     };
 
     client1
@@ -316,8 +419,10 @@ async fn test_join_office_broadcasts_only_to_room() {
     // client2加入office2
     let join_req2 = EnterOfficeReq {
         office_id: "office2".to_string(),
+        meta: None,
         role: Role::Agent,
         name: "agent2".to_string(),
+        a2c_version: None,
     };
 
     client2
@@ -329,8 +434,10 @@ async fn test_join_office_broadcasts_only_to_room() {
     // 现在，computer1加入office1
     let computer_join_req = EnterOfficeReq {
         office_id: "office1".to_string(),
+        meta: None,
         role: Role::Computer,
         name: "computer1".to_string(),
+        a2c_version: None,
     };
 
     // 使用新的客户端连接来发送join请求
@@ -40,6 +40,9 @@ async fn test_list_room_success() {
             req_id: ReqId("req1".to_string()),
         },
         office_id: "office1".to_string(),
+        limit: None,
+        offset: None,
+        role: None,
     };
 
     // 创建channel接收响应
@@ -154,6 +157,9 @@ async fn test_list_room_empty_office() {
             req_id: ReqId("req2".to_string()),
         },
         office_id: "office_empty".to_string(),
+        limit: None,
+        offset: None,
+        role: None,
     };
 
     // 创建channel接收响应
@@ -325,6 +331,9 @@ async fn test_list_room_cross_office_access_denied() {
             req_id: ReqId("req4".to_string()),
         },
         office_id: "office2".to_string(), // 不同的办公室
+        limit: None,
+        offset: None,
+        role: None,
     };
 
     // 创建channel接收响应
@@ -411,6 +420,9 @@ async fn test_list_room_multiple_offices() {
             req_id: ReqId("req5".to_string()),
         },
         office_id: "office1".to_string(),
+        limit: None,
+        offset: None,
+        role: None,
     };
 
     // Agent2列出office2的会话
@@ -420,6 +432,9 @@ async fn test_list_room_multiple_offices() {
             req_id: ReqId("req6".to_string()),
         },
         office_id: "office2".to_string(),
+        limit: None,
+        offset: None,
+        role: None,
     };
 
     // 分别发送请求
@@ -527,8 +542,10 @@ async fn test_computer_duplicate_name_rejected() {
     // 第二个Computer尝试使用相同名称加入同一办公室
     let join_req = EnterOfficeReq {
         office_id: "office1".to_string(),
+        meta: None,
         role: Role::Computer,
         name: "duplicate_comp".to_string(),
+        a2c_version: None,
     };
 
     println!("Sending join_office request from second computer...");
@@ -615,8 +632,10 @@ async fn test_computer_different_name_allowed() {
     // 第二个Computer使用不同名称加入同一办公室
     let join_req = EnterOfficeReq {
         office_id: "office1".to_string(),
+        meta: None,
         role: Role::Computer,
         name: "comp2".to_string(),
+        a2c_version: None,
     };
 
     // 创建channel接收响应
@@ -693,8 +712,10 @@ async fn test_computer_switch_room_with_same_name_allowed() {
     // Computer切换到第二个房间（使用相同名称）
     let join_req = EnterOfficeReq {
         office_id: "office2".to_string(),
+        meta: None,
         role: Role::Computer,
         name: "switching_comp".to_string(),
+        a2c_version: None,
     };
 
     // 创建channel接收响应
@@ -744,3 +765,241 @@ async fn test_computer_switch_room_with_same_name_allowed() {
     computer_client.disconnect().await.unwrap();
     server.shutdown();
 }
+
+#[tokio::test]
+async fn test_list_room_includes_office_meta() {
+    let _ = tracing_subscriber::fmt().with_env_filter("info").try_init();
+
+    let server = SmcpTestServer::start().await;
+    let server_url = server.url();
+
+    let agent_client = create_test_client(&server_url, SMCP_NAMESPACE).await;
+    let computer_client = create_test_client(&server_url, SMCP_NAMESPACE).await;
+
+    // 首次加入时携带元数据
+    let join_with_meta =
+        |client: &rust_socketio::asynchronous::Client, role: Role, name: &str, label: &str| {
+            let join_req = EnterOfficeReq {
+                role,
+                name: name.to_string(),
+                office_id: "office_meta".to_string(),
+                meta: Some(OfficeMeta {
+                    label: Some(label.to_string()),
+                    owner: Some("ops".to_string()),
+                    ..Default::default()
+                }),
+                a2c_version: None,
+            };
+            let client = client.clone();
+            async move {
+                let (result_tx, result_rx) = oneshot::channel::<serde_json::Value>();
+                client
+                    .emit_with_ack(
+                        "server:join_office",
+                        json!(join_req),
+                        Duration::from_secs(5),
+                        ack_to_sender(result_tx, |p| match p {
+                            Payload::Text(mut values, _) => {
+                                values.pop().unwrap_or(serde_json::Value::Null)
+                            }
+                            _ => serde_json::Value::Null,
+                        }),
+                    )
+                    .await
+                    .expect("join_office emit_with_ack failed");
+                tokio::time::timeout(Duration::from_secs(5), result_rx)
+                    .await
+                    .expect("join_office ack timeout")
+                    .unwrap()
+            }
+        };
+
+    join_with_meta(&agent_client, Role::Agent, "agent1", "Team A").await;
+    // 后加入者的元数据不会覆盖首次登记的值
+    join_with_meta(&computer_client, Role::Computer, "computer1", "Team B").await;
+    sleep(Duration::from_millis(300)).await;
+
+    let list_room_req = ListRoomReq {
+        base: AgentCallData {
+            agent: "agent1".to_string(),
+            req_id: ReqId("req_meta".to_string()),
+        },
+        office_id: "office_meta".to_string(),
+        limit: None,
+        offset: None,
+        role: None,
+    };
+
+    let (result_tx, result_rx) = oneshot::channel::<serde_json::Value>();
+    agent_client
+        .emit_with_ack(
+            "server:list_room",
+            json!(list_room_req),
+            Duration::from_secs(5),
+            ack_to_sender(result_tx, |p| match p {
+                Payload::Text(mut values, _) => values.pop().unwrap_or(serde_json::Value::Null),
+                _ => serde_json::Value::Null,
+            }),
+        )
+        .await
+        .expect("list_room emit_with_ack failed");
+
+    let result = tokio::time::timeout(Duration::from_secs(5), result_rx)
+        .await
+        .expect("list_room ack timeout")
+        .unwrap();
+    let response_data = match result.as_array() {
+        Some(arr) => arr.first().cloned().unwrap_or(serde_json::Value::Null),
+        None => result,
+    };
+
+    let ret: ListRoomRet = serde_json::from_value(response_data).expect("invalid ListRoomRet");
+    assert_eq!(ret.sessions.len(), 2);
+    let meta = ret.office_meta.expect("office_meta should be present");
+    assert_eq!(meta.label.as_deref(), Some("Team A"));
+    assert_eq!(meta.owner.as_deref(), Some("ops"));
+    assert!(meta.created_at.is_some());
+
+    agent_client.disconnect().await.unwrap();
+    computer_client.disconnect().await.unwrap();
+    server.shutdown();
+}
+
+#[tokio::test]
+async fn test_join_without_office_id_uses_default_office() {
+    let server = SmcpTestServer::start_with(|builder| builder.with_default_office("main")).await;
+    let server_url = server.url();
+
+    let agent_client = create_test_client(&server_url, SMCP_NAMESPACE).await;
+    join_office(&agent_client, Role::Agent, "main", "agent1").await;
+
+    // Computer 加入时不携带办公室 ID
+    let computer_client = create_test_client(&server_url, SMCP_NAMESPACE).await;
+    let (result_tx, result_rx) = oneshot::channel::<serde_json::Value>();
+    computer_client
+        .emit_with_ack(
+            "server:join_office",
+            json!({"role": "computer", "name": "computer1"}),
+            Duration::from_secs(5),
+            ack_to_sender(result_tx, |p| match p {
+                Payload::Text(mut values, _) => values.pop().unwrap_or(serde_json::Value::Null),
+                _ => serde_json::Value::Null,
+            }),
+        )
+        .await
+        .expect("join_office emit_with_ack failed");
+    let result = tokio::time::timeout(Duration::from_secs(5), result_rx)
+        .await
+        .expect("join_office ack timeout")
+        .unwrap();
+    assert_eq!(result[0], true, "join without office id failed: {}", result);
+
+    let (result_tx, result_rx) = oneshot::channel::<serde_json::Value>();
+    agent_client
+        .emit_with_ack(
+            "server:list_room",
+            json!({"agent": "agent1", "req_id": "req_default", "office_id": "main"}),
+            Duration::from_secs(5),
+            ack_to_sender(result_tx, |p| match p {
+                Payload::Text(mut values, _) => values.pop().unwrap_or(serde_json::Value::Null),
+                _ => serde_json::Value::Null,
+            }),
+        )
+        .await
+        .expect("list_room emit_with_ack failed");
+    let result = tokio::time::timeout(Duration::from_secs(5), result_rx)
+        .await
+        .expect("list_room ack timeout")
+        .unwrap();
+    let sessions = result[0]["sessions"].as_array().expect("sessions array");
+    assert!(sessions
+        .iter()
+        .any(|s| s["name"] == "computer1" && s["office_id"] == "main"));
+
+    computer_client.disconnect().await.unwrap();
+    agent_client.disconnect().await.unwrap();
+    server.shutdown();
+}
+
+#[tokio::test]
+async fn test_list_room_pagination_and_role_filter() {
+    let server = SmcpTestServer::start().await;
+    let server_url = server.url();
+
+    let agent_client = create_test_client(&server_url, SMCP_NAMESPACE).await;
+    join_office(&agent_client, Role::Agent, "office_page", "agent1").await;
+    let mut computers = Vec::new();
+    for i in 0..3 {
+        let client = create_test_client(&server_url, SMCP_NAMESPACE).await;
+        join_office(
+            &client,
+            Role::Computer,
+            "office_page",
+            &format!("computer{}", i),
+        )
+        .await;
+        computers.push(client);
+    }
+    sleep(Duration::from_millis(300)).await;
+
+    let list = |req: serde_json::Value| {
+        let agent_client = agent_client.clone();
+        async move {
+            let (result_tx, result_rx) = oneshot::channel::<serde_json::Value>();
+            agent_client
+                .emit_with_ack(
+                    "server:list_room",
+                    req,
+                    Duration::from_secs(5),
+                    ack_to_sender(result_tx, |p| match p {
+                        Payload::Text(mut values, _) => {
+                            values.pop().unwrap_or(serde_json::Value::Null)
+                        }
+                        _ => serde_json::Value::Null,
+                    }),
+                )
+                .await
+                .expect("list_room emit_with_ack failed");
+            tokio::time::timeout(Duration::from_secs(5), result_rx)
+                .await
+                .expect("list_room ack timeout")
+                .unwrap()
+        }
+    };
+
+    // 分页窗口：总数不受 limit/offset 影响
+    let first = list(json!({
+        "agent": "agent1", "req_id": "page1", "office_id": "office_page",
+        "limit": 2, "offset": 0
+    }))
+    .await;
+    let second = list(json!({
+        "agent": "agent1", "req_id": "page2", "office_id": "office_page",
+        "limit": 2, "offset": 2
+    }))
+    .await;
+    assert_eq!(first[0]["total"], 4);
+    assert_eq!(second[0]["total"], 4);
+    let first_page = first[0]["sessions"].as_array().unwrap();
+    let second_page = second[0]["sessions"].as_array().unwrap();
+    assert_eq!(first_page.len(), 2);
+    assert_eq!(second_page.len(), 2);
+    assert!(first_page.iter().all(|s| !second_page.contains(s)));
+
+    // 角色过滤
+    let computers_only = list(json!({
+        "agent": "agent1", "req_id": "role", "office_id": "office_page",
+        "role": "computer"
+    }))
+    .await;
+    assert_eq!(computers_only[0]["total"], 3);
+    let sessions = computers_only[0]["sessions"].as_array().unwrap();
+    assert_eq!(sessions.len(), 3);
+    assert!(sessions.iter().all(|s| s["role"] == "computer"));
+
+    for client in computers {
+        client.disconnect().await.unwrap();
+    }
+    agent_client.disconnect().await.unwrap();
+    server.shutdown();
+}
@@ -1,10 +1,12 @@
 //! 测试安全修复功能
 
 use smcp_server_core::{
-    auth::DefaultAuthenticationProvider,
-    handler::SmcpHandler,
-    session::{ClientRole, SessionData, SessionManager},
-    ServerState,
+    auth::{DefaultAuthenticationProvider, DefaultAuthorizationProvider, DefaultToolPolicy},
+    handler::{SmcpHandler, TimeoutConfig},
+    metrics::ServerMetrics,
+    sequence::OfficeSequencer,
+    session::{ClientRole, SessionData, SessionError, SessionManager},
+    ServerState, SmcpServerConfig,
 };
 use socketioxide::SocketIo;
 use std::sync::Arc;
@@ -22,7 +24,18 @@ async fn test_list_room_permission_validation() {
     let state = ServerState {
         session_manager: session_manager.clone(),
         auth_provider,
+        authorization_provider: Arc::new(DefaultAuthorizationProvider),
+        tool_policy: Arc::new(DefaultToolPolicy),
         io: Arc::new(io.clone()),
+        metrics: Arc::new(ServerMetrics::new()),
+        max_payload_bytes: None,
+        public_url: None,
+        office_sequencer: Arc::new(OfficeSequencer::new()),
+        max_timeout_secs: smcp::DEFAULT_MAX_TIMEOUT_SECS,
+        default_office: None,
+        timeout_config: TimeoutConfig::default(),
+        started_at: std::time::Instant::now(),
+        config: SmcpServerConfig::default(),
     };
     SmcpHandler::register_handlers(&io, state.clone());
 
@@ -69,7 +82,18 @@ async fn test_join_office_consistency_check() {
     let _state = ServerState {
         session_manager: session_manager.clone(),
         auth_provider,
+        authorization_provider: Arc::new(DefaultAuthorizationProvider),
+        tool_policy: Arc::new(DefaultToolPolicy),
         io: Arc::new(io),
+        metrics: Arc::new(ServerMetrics::new()),
+        max_payload_bytes: None,
+        public_url: None,
+        office_sequencer: Arc::new(OfficeSequencer::new()),
+        max_timeout_secs: smcp::DEFAULT_MAX_TIMEOUT_SECS,
+        default_office: None,
+        timeout_config: TimeoutConfig::default(),
+        started_at: std::time::Instant::now(),
+        config: SmcpServerConfig::default(),
     };
 
     // 测试1: 新会话可以正常加入
@@ -82,9 +106,12 @@ async fn test_join_office_consistency_check() {
     assert_eq!(retrieved.name, "test_agent");
     assert_eq!(retrieved.role, ClientRole::Agent);
 
-    // 测试2: 相同的 role 和 name 应该允许（幂等操作）
+    // 测试2: 已注册的 sid 不能重复注册（重复加入由 handler 按更新处理）
     let same_session = SessionData::new(sid1.clone(), "test_agent".to_string(), ClientRole::Agent);
-    assert!(session_manager.register_session(same_session).is_ok());
+    assert!(matches!(
+        session_manager.register_session(same_session),
+        Err(SessionError::SidExists(_))
+    ));
 
     // 测试3: 不同的 role 应该被拒绝
     let _diff_role_session =
@@ -0,0 +1,133 @@
+//! Test SmcpServerConfig options: session cap and namespace override
+
+#[path = "test_utils.rs"]
+mod test_utils;
+
+use std::time::Duration;
+
+use rust_socketio::asynchronous::Client;
+use rust_socketio::Payload;
+use serde_json::json;
+use tokio::sync::oneshot;
+use tokio::time::sleep;
+
+use smcp::*;
+use smcp_server_core::{
+    DefaultAuthenticationProvider, ServerState, SessionManager, SmcpHandler, SmcpServerBuilder,
+};
+use std::sync::Arc;
+use test_utils::*;
+
+/// 发送加入办公室请求，返回 ACK；连接被拒绝时在超时内收不到 ACK
+async fn try_join(client: &Client, name: &str) -> Option<serde_json::Value> {
+    let (result_tx, result_rx) = oneshot::channel::<serde_json::Value>();
+    let emitted = client
+        .emit_with_ack(
+            "server:join_office",
+            json!({"role": "computer", "office_id": "office1", "name": name}),
+            Duration::from_secs(1),
+            ack_to_sender(result_tx, |p| match p {
+                Payload::Text(mut values, _) => values.pop().unwrap_or(serde_json::Value::Null),
+                _ => serde_json::Value::Null,
+            }),
+        )
+        .await;
+    if emitted.is_err() {
+        return None;
+    }
+    tokio::time::timeout(Duration::from_secs(2), result_rx)
+        .await
+        .ok()
+        .and_then(Result::ok)
+}
+
+#[tokio::test]
+async fn test_max_sessions_rejects_connection_past_cap() {
+    let server = SmcpTestServer::start_with(|builder| builder.with_max_sessions(1)).await;
+    let server_url = server.url();
+
+    let first = create_test_client(&server_url, SMCP_NAMESPACE).await;
+    join_office(&first, Role::Computer, "office1", "computer1").await;
+
+    // 超出上限的连接被服务器断开，不会处理任何事件
+    let second = create_test_client(&server_url, SMCP_NAMESPACE).await;
+    sleep(Duration::from_millis(200)).await;
+    assert!(try_join(&second, "computer2").await.is_none());
+    let _ = second.disconnect().await;
+
+    // 释放连接后可以再次接入
+    first.disconnect().await.unwrap();
+    sleep(Duration::from_millis(300)).await;
+    let third = create_test_client(&server_url, SMCP_NAMESPACE).await;
+    let result = try_join(&third, "computer3").await.expect("join ack");
+    assert_eq!(result[0], true);
+
+    third.disconnect().await.unwrap();
+    server.shutdown();
+}
+
+#[tokio::test]
+async fn test_namespace_override() {
+    let server = SmcpTestServer::start_with(|builder| builder.with_namespace("/custom")).await;
+    let server_url = server.url();
+
+    let client = create_test_client(&server_url, "/custom").await;
+    let result = try_join(&client, "computer1").await.expect("join ack");
+    assert_eq!(result[0], true);
+
+    // 默认命名空间不再提供 SMCP 事件
+    let default_client = create_test_client(&server_url, SMCP_NAMESPACE).await;
+    assert!(try_join(&default_client, "computer2").await.is_none());
+
+    client.disconnect().await.unwrap();
+    let _ = default_client.disconnect().await;
+    server.shutdown();
+}
+
+#[tokio::test]
+async fn test_two_namespaces_keep_sessions_isolated() {
+    let layer = SmcpServerBuilder::new()
+        .with_auth_provider(Arc::new(DefaultAuthenticationProvider::new(
+            Some("test_secret".to_string()),
+            None,
+        )))
+        .build_layer()
+        .expect("failed to build SMCP server layer");
+    let staging_state = ServerState {
+        session_manager: Arc::new(SessionManager::new()),
+        ..layer.state.clone()
+    };
+    SmcpHandler::register_handlers_on(&layer.io, "/smcp-staging", staging_state.clone());
+    let prod_sessions = layer.state.session_manager.clone();
+    let server = SmcpTestServer::start_with_layer(layer).await;
+    let server_url = server.url();
+
+    // 同名 Computer 可分别加入两个命名空间的同名办公室
+    let prod = create_test_client(&server_url, SMCP_NAMESPACE).await;
+    let result = try_join(&prod, "computer1").await.expect("join ack");
+    assert_eq!(result[0], true);
+    let staging = create_test_client(&server_url, "/smcp-staging").await;
+    let result = try_join(&staging, "computer1").await.expect("join ack");
+    assert_eq!(result[0], true);
+
+    let office = "office1".to_string();
+    assert_eq!(prod_sessions.get_stats().total, 1);
+    assert_eq!(staging_state.session_manager.get_stats().total, 1);
+    assert_eq!(prod_sessions.get_sessions_in_office(&office).len(), 1);
+    assert_eq!(
+        staging_state
+            .session_manager
+            .get_sessions_in_office(&office)
+            .len(),
+        1
+    );
+
+    // 断开一侧不影响另一侧
+    staging.disconnect().await.unwrap();
+    sleep(Duration::from_millis(300)).await;
+    assert_eq!(staging_state.session_manager.get_stats().total, 0);
+    assert_eq!(prod_sessions.get_stats().total, 1);
+
+    prod.disconnect().await.unwrap();
+    server.shutdown();
+}
@@ -0,0 +1,72 @@
+//! Test server hello capabilities
+
+#[path = "test_utils.rs"]
+mod test_utils;
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rust_socketio::Payload;
+use tokio::time::sleep;
+
+use smcp::{events::NOTIFY_SERVER_HELLO, ServerCapabilities, SMCP_NAMESPACE};
+use test_utils::*;
+
+async fn receive_hello(server_url: &str) -> Option<ServerCapabilities> {
+    let received: Arc<Mutex<Option<ServerCapabilities>>> = Arc::new(Mutex::new(None));
+    let received_clone = received.clone();
+
+    let client = create_client_with_handler(
+        server_url,
+        SMCP_NAMESPACE,
+        NOTIFY_SERVER_HELLO,
+        move |payload, _client| {
+            let received = received_clone.clone();
+            Box::pin(async move {
+                if let Payload::Text(values, _) = payload {
+                    if let Some(value) = values.into_iter().next() {
+                        *received.lock().unwrap() = serde_json::from_value(value).ok();
+                    }
+                }
+            })
+        },
+    )
+    .await;
+
+    sleep(Duration::from_millis(300)).await;
+    let _ = client.disconnect().await;
+    let hello = received.lock().unwrap().clone();
+    hello
+}
+
+#[tokio::test]
+async fn test_server_hello_announces_public_url() {
+    let _ = tracing_subscriber::fmt().with_env_filter("info").try_init();
+
+    let server =
+        SmcpTestServer::start_with(|builder| builder.with_public_url("https://smcp.example.com"))
+            .await;
+
+    let hello = receive_hello(&server.url())
+        .await
+        .expect("server hello not received");
+    assert_eq!(
+        hello.public_url.as_deref(),
+        Some("https://smcp.example.com")
+    );
+    assert_eq!(hello.protocol_version, smcp::PROTOCOL_VERSION);
+
+    server.shutdown();
+}
+
+#[tokio::test]
+async fn test_server_hello_without_public_url() {
+    let server = SmcpTestServer::start().await;
+
+    let hello = receive_hello(&server.url())
+        .await
+        .expect("server hello not received");
+    assert_eq!(hello.public_url, None);
+
+    server.shutdown();
+}
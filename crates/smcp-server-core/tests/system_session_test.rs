@@ -0,0 +1,125 @@
+//! Test system (observer) sessions
+
+#[path = "test_utils.rs"]
+mod test_utils;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use rust_socketio::asynchronous::{Client, ClientBuilder};
+use rust_socketio::{Payload, TransportType};
+use serde_json::json;
+use tokio::sync::oneshot;
+
+use smcp::*;
+use smcp_server_core::DefaultAuthenticationProvider;
+use test_utils::*;
+
+const SYSTEM_SECRET: &str = "system_secret";
+
+async fn start_server() -> SmcpTestServer {
+    SmcpTestServer::start_with(|builder| {
+        builder.with_auth_provider(Arc::new(
+            DefaultAuthenticationProvider::new(Some("test_secret".to_string()), None)
+                .with_system_secret(SYSTEM_SECRET.to_string()),
+        ))
+    })
+    .await
+}
+
+async fn create_system_client(server_url: &str) -> Client {
+    ClientBuilder::new(server_url)
+        .transport_type(TransportType::Websocket)
+        .namespace(SMCP_NAMESPACE)
+        .opening_header("x-api-key", SYSTEM_SECRET)
+        .connect()
+        .await
+        .expect("Failed to connect system client")
+}
+
+/// 发送事件并返回 ACK 的第一个值
+async fn emit_ack(client: &Client, event: &str, data: serde_json::Value) -> serde_json::Value {
+    let (result_tx, result_rx) = oneshot::channel::<serde_json::Value>();
+    client
+        .emit_with_ack(
+            event,
+            data,
+            Duration::from_secs(5),
+            ack_to_sender(result_tx, |p| match p {
+                Payload::Text(mut values, _) => values.pop().unwrap_or(serde_json::Value::Null),
+                _ => serde_json::Value::Null,
+            }),
+        )
+        .await
+        .expect("emit_with_ack failed");
+    tokio::time::timeout(Duration::from_secs(5), result_rx)
+        .await
+        .expect("ack timeout")
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_system_session_lists_office_it_did_not_join() {
+    let server = start_server().await;
+    let server_url = server.url();
+
+    let computer_client = create_test_client(&server_url, SMCP_NAMESPACE).await;
+    join_office(&computer_client, Role::Computer, "office1", "computer1").await;
+
+    let system_client = create_system_client(&server_url).await;
+    join_office(&system_client, Role::System, "monitor", "scraper").await;
+
+    let result = emit_ack(
+        &system_client,
+        "server:list_room",
+        json!({"agent": "scraper", "req_id": "req1", "office_id": "office1"}),
+    )
+    .await;
+    let sessions = result[0]["sessions"].as_array().expect("sessions array");
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0]["name"], "computer1");
+    assert_eq!(sessions[0]["role"], "computer");
+
+    computer_client.disconnect().await.unwrap();
+    system_client.disconnect().await.unwrap();
+    server.shutdown();
+}
+
+#[tokio::test]
+async fn test_system_session_does_not_block_agent_join() {
+    let server = start_server().await;
+    let server_url = server.url();
+
+    let system_client = create_system_client(&server_url).await;
+    join_office(&system_client, Role::System, "office1", "scraper").await;
+
+    // 系统会话不计入单 Agent 限制
+    let agent_client = create_test_client(&server_url, SMCP_NAMESPACE).await;
+    join_office(&agent_client, Role::Agent, "office1", "agent1").await;
+
+    agent_client.disconnect().await.unwrap();
+    system_client.disconnect().await.unwrap();
+    server.shutdown();
+}
+
+#[tokio::test]
+async fn test_system_role_requires_authorization() {
+    let server = start_server().await;
+    let server_url = server.url();
+
+    let client = create_test_client(&server_url, SMCP_NAMESPACE).await;
+    let result = emit_ack(
+        &client,
+        "server:join_office",
+        json!({"role": "system", "office_id": "office1", "name": "intruder"}),
+    )
+    .await;
+    assert_eq!(result[0], false);
+    assert!(result[1]
+        .as_str()
+        .unwrap_or_default()
+        .contains("not authorized"));
+
+    client.disconnect().await.unwrap();
+    server.shutdown();
+}
@@ -25,7 +25,7 @@ use tokio::sync::oneshot;
 use tokio::time::sleep;
 use tower::{Layer, Service};
 
-use smcp_server_core::{DefaultAuthenticationProvider, SmcpServerBuilder};
+use smcp_server_core::{DefaultAuthenticationProvider, SmcpServerBuilder, SmcpServerLayer};
 
 /// 测试用的SMCP服务器
 pub struct SmcpTestServer {
@@ -35,17 +35,28 @@ pub struct SmcpTestServer {
 
 impl SmcpTestServer {
     /// 启动测试服务器
+    #[allow(dead_code)]
     pub async fn start() -> Self {
-        let port = find_available_port().await;
-        let addr: SocketAddr = format!("127.0.0.1:{}", port).parse().unwrap();
+        Self::start_with(|builder| builder).await
+    }
 
-        let layer = SmcpServerBuilder::new()
-            .with_auth_provider(Arc::new(DefaultAuthenticationProvider::new(
-                Some("test_secret".to_string()),
-                None,
-            )))
+    /// 使用自定义构建器配置启动测试服务器
+    pub async fn start_with(
+        configure: impl FnOnce(SmcpServerBuilder) -> SmcpServerBuilder,
+    ) -> Self {
+        let builder = SmcpServerBuilder::new().with_auth_provider(Arc::new(
+            DefaultAuthenticationProvider::new(Some("test_secret".to_string()), None),
+        ));
+        let layer = configure(builder)
             .build_layer()
             .expect("failed to build SMCP server layer");
+        Self::start_with_layer(layer).await
+    }
+
+    /// 使用已构建的 Layer 启动测试服务器
+    pub async fn start_with_layer(layer: SmcpServerLayer) -> Self {
+        let port = find_available_port().await;
+        let addr: SocketAddr = format!("127.0.0.1:{}", port).parse().unwrap();
 
         let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
         let listener = TcpListener::bind(addr).await.unwrap();
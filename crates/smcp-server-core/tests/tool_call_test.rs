@@ -16,6 +16,7 @@ use tokio::sync::oneshot;
 use tokio::time::sleep;
 
 use smcp::*;
+use smcp_server_core::TimeoutConfig;
 use test_utils::*;
 
 #[tokio::test]
@@ -76,6 +77,7 @@ async fn test_tool_call_roundtrip() {
         tool_name: "echo".to_string(),
         params: json!({"text": "hello world"}),
         timeout: 5,
+        idempotency_key: None,
     };
 
     // 创建channel接收响应
@@ -117,7 +119,7 @@ async fn test_tool_call_roundtrip() {
                 if let Some(first) = arr.first() {
                     first
                         .get("Err")
-                        .and_then(|e| e.as_str())
+                        .and_then(|e| e["message"].as_str())
                         .unwrap_or("No error field found")
                 } else {
                     "No response found"
@@ -176,6 +178,7 @@ async fn test_tool_call_computer_not_found() {
         tool_name: "echo".to_string(),
         params: json!({"text": "test"}),
         timeout: 5,
+        idempotency_key: None,
     };
 
     // 创建channel接收响应
@@ -207,7 +210,7 @@ async fn test_tool_call_computer_not_found() {
         serde_json::Value::Array(arr) => arr
             .first()
             .map(|v| {
-                if let Some(err) = v.get("Err").and_then(|e| e.as_str()) {
+                if let Some(err) = v.get("Err").and_then(|e| e["message"].as_str()) {
                     err.to_string()
                 } else if let Some(s) = v.as_str() {
                     s.to_string()
@@ -259,6 +262,7 @@ async fn test_tool_call_cross_office_permission_denied() {
         tool_name: "echo".to_string(),
         params: json!({"text": "test"}),
         timeout: 5,
+        idempotency_key: None,
     };
 
     // 创建channel接收响应
@@ -290,7 +294,7 @@ async fn test_tool_call_cross_office_permission_denied() {
         serde_json::Value::Array(arr) => arr
             .first()
             .map(|v| {
-                if let Some(err) = v.get("Err").and_then(|e| e.as_str()) {
+                if let Some(err) = v.get("Err").and_then(|e| e["message"].as_str()) {
                     err.to_string()
                 } else if let Some(s) = v.as_str() {
                     s.to_string()
@@ -357,6 +361,7 @@ async fn test_tool_call_timeout_handling() {
         tool_name: "slow_tool".to_string(),
         params: json!({"delay": 10}),
         timeout: 1, // 1秒超时
+        idempotency_key: None,
     };
 
     // 创建channel接收响应
@@ -411,3 +416,191 @@ async fn test_tool_call_timeout_handling() {
     agent_client.disconnect().await.unwrap();
     server.shutdown();
 }
+
+#[tokio::test]
+async fn test_tool_call_timeout_clamped_to_server_ceiling() {
+    let _ = tracing_subscriber::fmt().with_env_filter("info").try_init();
+
+    let server =
+        SmcpTestServer::start_with(|builder| builder.with_max_timeout(Duration::from_secs(1)))
+            .await;
+    let server_url = server.url();
+
+    // 记录Computer收到的超时值，且不回复ACK
+    let received_timeout = Arc::new(std::sync::Mutex::new(None));
+    let received_timeout_clone = received_timeout.clone();
+    let computer_client = ClientBuilder::new(server_url.clone())
+        .transport_type(TransportType::Websocket)
+        .namespace("smcp")
+        .opening_header("x-api-key", "test_secret")
+        .on("client:tool_call", move |payload: Payload, _client| {
+            let received_timeout = received_timeout_clone.clone();
+            async move {
+                if let Payload::Text(values, _) = payload {
+                    if let Some(req) = values
+                        .into_iter()
+                        .next()
+                        .and_then(|v| serde_json::from_value::<ToolCallReq>(v).ok())
+                    {
+                        *received_timeout.lock().unwrap() = Some(req.timeout);
+                    }
+                }
+            }
+            .boxed()
+        })
+        .connect()
+        .await
+        .expect("Failed to connect computer");
+    join_office(&computer_client, Role::Computer, "office1", "computer1").await;
+
+    let agent_client = create_test_client(&server_url, "smcp").await;
+    join_office(&agent_client, Role::Agent, "office1", "agent1").await;
+    sleep(Duration::from_millis(200)).await;
+
+    // 请求远超服务器上限的超时
+    let tool_call_req = ToolCallReq {
+        base: AgentCallData {
+            agent: "agent1".to_string(),
+            req_id: ReqId("req_clamped".to_string()),
+        },
+        computer: "computer1".to_string(),
+        tool_name: "slow".to_string(),
+        params: json!({}),
+        timeout: 3600,
+        idempotency_key: None,
+    };
+
+    let (result_tx, result_rx) = oneshot::channel::<serde_json::Value>();
+    let started = std::time::Instant::now();
+    agent_client
+        .emit_with_ack(
+            "client:tool_call",
+            json!(tool_call_req),
+            Duration::from_secs(10),
+            ack_to_sender(result_tx, |p| match p {
+                Payload::Text(mut values, _) => values.pop().unwrap_or(serde_json::Value::Null),
+                _ => serde_json::Value::Null,
+            }),
+        )
+        .await
+        .expect("tool_call emit_with_ack failed");
+
+    let response = tokio::time::timeout(Duration::from_secs(5), result_rx)
+        .await
+        .expect("server should time out at its ceiling, not the requested timeout")
+        .unwrap();
+    let elapsed = started.elapsed();
+
+    assert!(
+        elapsed >= Duration::from_millis(900) && elapsed < Duration::from_secs(3),
+        "expected a timeout at the 1s ceiling, took {:?}",
+        elapsed
+    );
+    assert!(
+        response.to_string().contains("timed out after 1 seconds"),
+        "unexpected response: {}",
+        response
+    );
+    // Computer 收到的是截断后的超时
+    assert_eq!(*received_timeout.lock().unwrap(), Some(1));
+
+    computer_client.disconnect().await.unwrap();
+    agent_client.disconnect().await.unwrap();
+    server.shutdown();
+}
+
+#[tokio::test]
+async fn test_tool_call_without_timeout_uses_configured_default() {
+    let _ = tracing_subscriber::fmt().with_env_filter("info").try_init();
+
+    let server = SmcpTestServer::start_with(|builder| {
+        builder.with_timeout_config(TimeoutConfig {
+            tool_call: Duration::from_secs(1),
+            ..Default::default()
+        })
+    })
+    .await;
+    let server_url = server.url();
+
+    // 记录Computer收到的超时值，且不回复ACK
+    let received_timeout = Arc::new(std::sync::Mutex::new(None));
+    let received_timeout_clone = received_timeout.clone();
+    let computer_client = ClientBuilder::new(server_url.clone())
+        .transport_type(TransportType::Websocket)
+        .namespace("smcp")
+        .opening_header("x-api-key", "test_secret")
+        .on("client:tool_call", move |payload: Payload, _client| {
+            let received_timeout = received_timeout_clone.clone();
+            async move {
+                if let Payload::Text(values, _) = payload {
+                    if let Some(req) = values
+                        .into_iter()
+                        .next()
+                        .and_then(|v| serde_json::from_value::<ToolCallReq>(v).ok())
+                    {
+                        *received_timeout.lock().unwrap() = Some(req.timeout);
+                    }
+                }
+            }
+            .boxed()
+        })
+        .connect()
+        .await
+        .expect("Failed to connect computer");
+    join_office(&computer_client, Role::Computer, "office1", "computer1").await;
+
+    let agent_client = create_test_client(&server_url, "smcp").await;
+    join_office(&agent_client, Role::Agent, "office1", "agent1").await;
+    sleep(Duration::from_millis(200)).await;
+
+    // 未携带超时，使用服务器配置的默认值
+    let tool_call_req = ToolCallReq {
+        base: AgentCallData {
+            agent: "agent1".to_string(),
+            req_id: ReqId("req_default_timeout".to_string()),
+        },
+        computer: "computer1".to_string(),
+        tool_name: "slow".to_string(),
+        params: json!({}),
+        timeout: 0,
+        idempotency_key: None,
+    };
+
+    let (result_tx, result_rx) = oneshot::channel::<serde_json::Value>();
+    let started = std::time::Instant::now();
+    agent_client
+        .emit_with_ack(
+            "client:tool_call",
+            json!(tool_call_req),
+            Duration::from_secs(10),
+            ack_to_sender(result_tx, |p| match p {
+                Payload::Text(mut values, _) => values.pop().unwrap_or(serde_json::Value::Null),
+                _ => serde_json::Value::Null,
+            }),
+        )
+        .await
+        .expect("tool_call emit_with_ack failed");
+
+    let response = tokio::time::timeout(Duration::from_secs(5), result_rx)
+        .await
+        .expect("server should time out at the configured default")
+        .unwrap();
+    let elapsed = started.elapsed();
+
+    assert!(
+        elapsed >= Duration::from_millis(900) && elapsed < Duration::from_secs(3),
+        "expected a timeout at the configured 1s, took {:?}",
+        elapsed
+    );
+    assert!(
+        response.to_string().contains("timed out after 1 seconds"),
+        "unexpected response: {}",
+        response
+    );
+    // Computer 收到的是配置的默认超时
+    assert_eq!(*received_timeout.lock().unwrap(), Some(1));
+
+    computer_client.disconnect().await.unwrap();
+    agent_client.disconnect().await.unwrap();
+    server.shutdown();
+}
@@ -3,25 +3,407 @@
 //! This crate provides a Hyper-based HTTP server implementation for the SMCP protocol.
 //! It exposes both programmatic API and a standalone binary.
 
+use std::collections::HashMap;
 use std::convert::Infallible;
-use std::net::SocketAddr;
+use std::fs::File;
+use std::future::Future;
+use std::io::BufReader;
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-use http_body_util::Full;
-use hyper::body::Bytes;
-use hyper::service::service_fn;
+use http_body_util::combinators::UnsyncBoxBody;
+use http_body_util::{BodyExt, Full, LengthLimitError, Limited};
+use hyper::body::{Body, Bytes, Incoming};
+use hyper::header::{self, HeaderValue};
+use hyper::service::{service_fn, Service};
 use hyper::{Method, Request, Response, StatusCode};
 use hyper_util::rt::TokioIo;
-use socketioxide::SocketIo;
-use tokio::net::TcpListener;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{watch, OwnedSemaphorePermit, Semaphore};
+use tokio::task::JoinSet;
+use tokio_rustls::rustls;
+use tokio_rustls::TlsAcceptor;
 use tower::ServiceBuilder;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
-use smcp_server_core::SmcpServerLayer;
+use smcp_server_core::{ServerState, SmcpServerLayer};
+
+/// Default time in-flight connections get to finish after a shutdown signal
+pub const DEFAULT_SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(30);
+
+/// Default maximum request body size in bytes, matching Socket.IO's `maxHttpBufferSize`
+pub const DEFAULT_MAX_BODY_SIZE: usize = 1_000_000;
+
+/// Default time a client gets to send the whole request body
+pub const DEFAULT_BODY_READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+type ServerError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Errors loading the TLS certificate and key
+#[derive(Debug, thiserror::Error)]
+pub enum TlsError {
+    #[error("Failed to read {path}: {source}")]
+    Read {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("No certificate found in {0}")]
+    NoCertificate(PathBuf),
+    #[error("No private key found in {0}")]
+    NoPrivateKey(PathBuf),
+    #[error("Invalid TLS configuration: {0}")]
+    Config(#[from] rustls::Error),
+}
+
+/// Build a TLS acceptor from PEM encoded certificate chain and private key files
+pub fn load_tls_acceptor(cert_path: &Path, key_path: &Path) -> Result<TlsAcceptor, TlsError> {
+    let open = |path: &Path| {
+        File::open(path)
+            .map(BufReader::new)
+            .map_err(|source| TlsError::Read {
+                path: path.to_path_buf(),
+                source,
+            })
+    };
+
+    let certs = rustls_pemfile::certs(&mut open(cert_path)?)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|source| TlsError::Read {
+            path: cert_path.to_path_buf(),
+            source,
+        })?;
+    if certs.is_empty() {
+        return Err(TlsError::NoCertificate(cert_path.to_path_buf()));
+    }
+
+    let key = rustls_pemfile::private_key(&mut open(key_path)?)
+        .map_err(|source| TlsError::Read {
+            path: key_path.to_path_buf(),
+            source,
+        })?
+        .ok_or_else(|| TlsError::NoPrivateKey(key_path.to_path_buf()))?;
+
+    let mut config = rustls::ServerConfig::builder_with_provider(Arc::new(
+        rustls::crypto::ring::default_provider(),
+    ))
+    .with_safe_default_protocol_versions()?
+    .with_no_client_auth()
+    .with_single_cert(certs, key)?;
+    // Connections are served over HTTP/1 only
+    config.alpn_protocols = vec![b"http/1.1".to_vec()];
+
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+/// A plaintext or TLS stream handed to hyper
+trait ConnectionIo: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> ConnectionIo for T {}
+
+/// What to do with new connections once `max_connections` is reached
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BacklogPolicy {
+    /// Stop accepting until a connection slot frees up; pending connections queue in the OS backlog
+    #[default]
+    Wait,
+    /// Accept and immediately close connections beyond the limit
+    Reject,
+}
+
+/// Connection limits applied by [`HyperServer`]
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionLimits {
+    /// Maximum number of concurrently served connections, `None` means unlimited
+    pub max_connections: Option<usize>,
+    /// Maximum number of concurrent connections from one IP, `None` means unlimited.
+    /// Connections over this cap are always closed.
+    pub max_connections_per_ip: Option<usize>,
+    /// Behaviour once `max_connections` is reached
+    pub backlog_policy: BacklogPolicy,
+}
+
+/// Request body limits applied by [`HyperServer`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BodyLimits {
+    /// Maximum request body size in bytes, larger bodies are answered with 413
+    pub max_body_size: usize,
+    /// Time allowed to receive the whole body, slower bodies are answered with 408
+    pub read_timeout: Duration,
+}
+
+impl Default for BodyLimits {
+    fn default() -> Self {
+        Self {
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+            read_timeout: DEFAULT_BODY_READ_TIMEOUT,
+        }
+    }
+}
+
+/// Tower layer buffering request bodies within [`BodyLimits`]
+#[derive(Debug, Clone)]
+pub struct BodyLimitLayer {
+    limits: BodyLimits,
+}
+
+impl BodyLimitLayer {
+    /// Create a layer enforcing `limits`
+    pub fn new(limits: BodyLimits) -> Self {
+        Self { limits }
+    }
+}
+
+impl<S> tower::Layer<S> for BodyLimitLayer {
+    type Service = BodyLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        BodyLimitService {
+            inner,
+            limits: self.limits,
+        }
+    }
+}
+
+/// Service produced by [`BodyLimitLayer`]
+///
+/// The body is read fully before `inner` sees the request, so `inner` never waits on a
+/// slow or oversized upload.
+#[derive(Debug, Clone)]
+pub struct BodyLimitService<S> {
+    inner: S,
+    limits: BodyLimits,
+}
+
+impl<S, B> Service<Request<Incoming>> for BodyLimitService<S>
+where
+    S: Service<Request<Full<Bytes>>, Response = Response<B>> + Clone + Send + 'static,
+    S::Future: Send,
+    B: Body<Data = Bytes> + Send + 'static,
+    B::Error: Into<ServerError>,
+{
+    type Response = Response<ResponseBody>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn call(&self, req: Request<Incoming>) -> Self::Future {
+        let inner = self.inner.clone();
+        let limits = self.limits;
+        Box::pin(async move {
+            let (parts, body) = req.into_parts();
+            let body = match read_body(body, limits).await {
+                Ok(body) => body,
+                Err(status) => {
+                    warn!("Rejecting {} {}: {}", parts.method, parts.uri, status);
+                    return Ok(Response::builder()
+                        .status(status)
+                        .body(box_body(Full::new(Bytes::from(
+                            status.canonical_reason().unwrap_or_default(),
+                        ))))
+                        .unwrap());
+                }
+            };
+            inner
+                .call(Request::from_parts(parts, Full::new(body)))
+                .await
+                .map(|res| res.map(box_body))
+        })
+    }
+}
+
+/// Read a request body within `limits`, or return the status to answer with
+async fn read_body(body: Incoming, limits: BodyLimits) -> Result<Bytes, StatusCode> {
+    // A declared Content-Length over the limit is rejected without reading
+    if body.size_hint().lower() > limits.max_body_size as u64 {
+        return Err(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+    let collect = Limited::new(body, limits.max_body_size).collect();
+    match tokio::time::timeout(limits.read_timeout, collect).await {
+        Ok(Ok(collected)) => Ok(collected.to_bytes()),
+        Ok(Err(err)) if err.downcast_ref::<LengthLimitError>().is_some() => {
+            Err(StatusCode::PAYLOAD_TOO_LARGE)
+        }
+        Ok(Err(_)) => Err(StatusCode::BAD_REQUEST),
+        Err(_) => Err(StatusCode::REQUEST_TIMEOUT),
+    }
+}
+
+/// Live connection count per remote IP
+#[derive(Debug, Default)]
+struct PerIpConnections {
+    counts: Mutex<HashMap<IpAddr, usize>>,
+}
+
+impl PerIpConnections {
+    /// Register a connection from `ip`, or return `None` if `ip` is at `cap`
+    fn try_register(self: &Arc<Self>, ip: IpAddr, cap: usize) -> Option<PerIpGuard> {
+        let mut counts = self.counts.lock().unwrap();
+        let count = counts.entry(ip).or_insert(0);
+        if *count >= cap {
+            return None;
+        }
+        *count += 1;
+        Some(PerIpGuard {
+            connections: self.clone(),
+            ip,
+        })
+    }
+}
+
+/// Releases a per-IP slot when the connection ends
+struct PerIpGuard {
+    connections: Arc<PerIpConnections>,
+    ip: IpAddr,
+}
+
+impl Drop for PerIpGuard {
+    fn drop(&mut self) {
+        let mut counts = self.connections.counts.lock().unwrap();
+        if let Some(count) = counts.get_mut(&self.ip) {
+            *count -= 1;
+            if *count == 0 {
+                counts.remove(&self.ip);
+            }
+        }
+    }
+}
+
+/// Cross-origin settings for browser-based clients
+///
+/// Requests whose `Origin` is allowed get `Access-Control-Allow-Origin` echoed back, and
+/// `OPTIONS` preflights from them are answered directly, including on `/socket.io/`.
+#[derive(Debug, Clone)]
+pub struct CorsConfig {
+    /// Origins allowed to make cross-origin requests, `*` allows any origin
+    pub allowed_origins: Vec<String>,
+    /// Methods advertised to preflight requests
+    pub allowed_methods: Vec<String>,
+    /// Request headers advertised to preflight requests
+    pub allowed_headers: Vec<String>,
+}
+
+impl CorsConfig {
+    /// Allow the given origins with the default methods and headers
+    pub fn new<I, T>(allowed_origins: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        Self {
+            allowed_origins: allowed_origins.into_iter().map(Into::into).collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Whether `origin` may make cross-origin requests
+    fn allows(&self, origin: &HeaderValue) -> bool {
+        self.allowed_origins
+            .iter()
+            .any(|allowed| allowed == "*" || origin == allowed)
+    }
+
+    /// Attach the allowed origin to a response
+    fn decorate<B>(&self, response: &mut Response<B>, origin: HeaderValue) {
+        let headers = response.headers_mut();
+        headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+        headers.append(header::VARY, HeaderValue::from_static("origin"));
+    }
+
+    /// Answer a preflight request from an allowed origin
+    fn preflight(&self, origin: HeaderValue) -> Response<Full<Bytes>> {
+        let mut response = Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .body(Full::new(Bytes::new()))
+            .unwrap();
+        let headers = response.headers_mut();
+        for (name, values) in [
+            (header::ACCESS_CONTROL_ALLOW_METHODS, &self.allowed_methods),
+            (header::ACCESS_CONTROL_ALLOW_HEADERS, &self.allowed_headers),
+        ] {
+            match HeaderValue::from_str(&values.join(", ")) {
+                Ok(value) => {
+                    headers.insert(name, value);
+                }
+                Err(err) => warn!("Ignoring invalid CORS {} value: {}", name, err),
+            }
+        }
+        self.decorate(&mut response, origin);
+        response
+    }
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: Vec::new(),
+            allowed_methods: vec!["GET".into(), "POST".into(), "OPTIONS".into()],
+            allowed_headers: vec!["content-type".into()],
+        }
+    }
+}
+
+/// Response body shared by SMCP routes and Socket.IO responses
+type ResponseBody = UnsyncBoxBody<Bytes, ServerError>;
+
+fn box_body<B>(body: B) -> ResponseBody
+where
+    B: Body<Data = Bytes> + Send + 'static,
+    B::Error: Into<ServerError>,
+{
+    body.map_err(Into::into).boxed_unsync()
+}
+
+/// Route a request through `inner`, applying `cors` when configured
+async fn serve_with_cors<S, B>(
+    cors: Option<&CorsConfig>,
+    req: Request<Incoming>,
+    inner: S,
+) -> Result<Response<ResponseBody>, S::Error>
+where
+    S: Service<Request<Incoming>, Response = Response<B>>,
+    B: Body<Data = Bytes> + Send + 'static,
+    B::Error: Into<ServerError>,
+{
+    let origin = cors.and_then(|cors| {
+        req.headers()
+            .get(header::ORIGIN)
+            .filter(|origin| cors.allows(origin))
+            .cloned()
+    });
+    let (Some(cors), Some(origin)) = (cors, origin) else {
+        return inner.call(req).await.map(|res| res.map(box_body));
+    };
+
+    if req.method() == Method::OPTIONS
+        && req
+            .headers()
+            .contains_key(header::ACCESS_CONTROL_REQUEST_METHOD)
+    {
+        return Ok(cors.preflight(origin).map(box_body));
+    }
+
+    let mut response = inner.call(req).await?.map(box_body);
+    cors.decorate(&mut response, origin);
+    Ok(response)
+}
 
 /// A Hyper-based SMCP server
 pub struct HyperServer {
     pub layer: Option<SmcpServerLayer>,
     pub addr: SocketAddr,
+    pub limits: ConnectionLimits,
+    /// How long in-flight connections may run after a shutdown signal before they are aborted
+    pub shutdown_grace_period: Duration,
+    /// Terminates TLS on accepted connections, `None` serves plaintext
+    pub tls: Option<TlsAcceptor>,
+    /// Cross-origin settings, `None` sends no CORS headers
+    pub cors: Option<CorsConfig>,
+    /// Request body size and read time limits
+    pub body_limits: BodyLimits,
 }
 
 impl HyperServer {
@@ -30,6 +412,11 @@ impl HyperServer {
         Self {
             layer: None,
             addr: "127.0.0.1:0".parse().unwrap(),
+            limits: ConnectionLimits::default(),
+            shutdown_grace_period: DEFAULT_SHUTDOWN_GRACE_PERIOD,
+            tls: None,
+            cors: None,
+            body_limits: BodyLimits::default(),
         }
     }
 
@@ -39,48 +426,219 @@ impl HyperServer {
         self
     }
 
+    /// Set the request body limits
+    pub fn with_body_limits(mut self, body_limits: BodyLimits) -> Self {
+        self.body_limits = body_limits;
+        self
+    }
+
+    /// Answer cross-origin requests from browser clients
+    pub fn with_cors(mut self, cors: CorsConfig) -> Self {
+        self.cors = Some(cors);
+        self
+    }
+
+    /// Serve connections over TLS using the given acceptor
+    pub fn with_tls_acceptor(mut self, acceptor: TlsAcceptor) -> Self {
+        self.tls = Some(acceptor);
+        self
+    }
+
+    /// Set the connection limits
+    pub fn with_limits(mut self, limits: ConnectionLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Set how long in-flight connections may run after a shutdown signal
+    pub fn with_shutdown_grace_period(mut self, grace_period: Duration) -> Self {
+        self.shutdown_grace_period = grace_period;
+        self
+    }
+
     /// Run the server on the given address
-    pub async fn run(
+    pub async fn run(self, addr: SocketAddr) -> Result<(), ServerError> {
+        self.run_with_shutdown(addr, std::future::pending()).await
+    }
+
+    /// Run the server on the given address until `shutdown` resolves.
+    ///
+    /// Once signalled the server stops accepting, asks open connections to close and
+    /// waits up to the grace period for them before returning.
+    pub async fn run_with_shutdown(
         self,
         addr: SocketAddr,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let layer = self.layer.ok_or("SMCP layer not configured")?;
+        shutdown: impl Future<Output = ()>,
+    ) -> Result<(), ServerError> {
+        if self.layer.is_none() {
+            return Err("SMCP layer not configured".into());
+        }
 
         info!("Starting SMCP server on {}", addr);
 
         // Create a TCP listener
         let listener = TcpListener::bind(addr).await?;
+        self.serve_with_shutdown(listener, shutdown).await
+    }
+
+    /// Serve connections from an already bound listener
+    pub async fn serve(self, listener: TcpListener) -> Result<(), ServerError> {
+        self.serve_with_shutdown(listener, std::future::pending())
+            .await
+    }
+
+    /// Serve connections from an already bound listener until `shutdown` resolves
+    pub async fn serve_with_shutdown(
+        self,
+        listener: TcpListener,
+        shutdown: impl Future<Output = ()>,
+    ) -> Result<(), ServerError> {
+        let layer = self.layer.ok_or("SMCP layer not configured")?;
+        let limits = self.limits;
+        let grace_period = self.shutdown_grace_period;
+        let tls = self.tls;
+        let cors = self.cors.map(Arc::new);
+        let body_limits = self.body_limits;
+
         let local_addr = listener.local_addr()?;
         info!("Server listening on {}", local_addr);
 
         // Build the service stack
-        let service = ServiceBuilder::new()
+        let smcp_service = ServiceBuilder::new()
+            .layer(BodyLimitLayer::new(body_limits))
             .layer(layer.layer)
-            .service(service_fn(move |req| {
-                let io = layer.io.clone();
-                async move { handle_request(req, &io).await }
+            .service(service_fn(move |req: Request<Full<Bytes>>| {
+                let state = layer.state.clone();
+                async move { handle_request(req, &state).await }
             }));
+        // CORS wraps the Socket.IO layer so polling responses carry the headers too
+        let service = service_fn(move |req: Request<Incoming>| {
+            let inner = smcp_service.clone();
+            let cors = cors.clone();
+            async move { serve_with_cors(cors.as_deref(), req, inner).await }
+        });
+
+        let slots = limits
+            .max_connections
+            .map(|max| Arc::new(Semaphore::new(max)));
+        let per_ip = Arc::new(PerIpConnections::default());
+
+        let (closing_tx, closing_rx) = watch::channel(false);
+        let mut connections = JoinSet::new();
+        tokio::pin!(shutdown);
 
         // Serve connections
         loop {
-            let (stream, remote_addr) = listener.accept().await?;
+            let (stream, remote_addr, mut permit) = tokio::select! {
+                _ = &mut shutdown => break,
+                // Reap finished connection tasks so the set does not grow unbounded
+                Some(_) = connections.join_next(), if !connections.is_empty() => continue,
+                accepted = accept(&listener, &slots, limits.backlog_policy) => accepted?,
+            };
+
+            if let (Some(slots), BacklogPolicy::Reject) = (&slots, limits.backlog_policy) {
+                match slots.clone().try_acquire_owned() {
+                    Ok(acquired) => permit = Some(acquired),
+                    Err(_) => {
+                        warn!(
+                            "Rejecting connection from {}: connection limit reached",
+                            remote_addr
+                        );
+                        continue;
+                    }
+                }
+            }
+
+            let ip_guard = match limits.max_connections_per_ip {
+                Some(cap) => match per_ip.try_register(remote_addr.ip(), cap) {
+                    Some(guard) => Some(guard),
+                    None => {
+                        warn!(
+                            "Rejecting connection from {}: per-IP connection limit reached",
+                            remote_addr
+                        );
+                        continue;
+                    }
+                },
+                None => None,
+            };
+
             info!("New connection from: {}", remote_addr);
 
             let service = service.clone();
-            tokio::spawn(async move {
+            let tls = tls.clone();
+            let mut closing = closing_rx.clone();
+            connections.spawn(async move {
+                // Slots are released when the connection ends
+                let _permit = permit;
+                let _ip_guard = ip_guard;
+                let stream: Box<dyn ConnectionIo> = match tls {
+                    Some(acceptor) => match acceptor.accept(stream).await {
+                        Ok(stream) => Box::new(stream),
+                        Err(err) => {
+                            warn!("TLS handshake with {} failed: {}", remote_addr, err);
+                            return;
+                        }
+                    },
+                    None => Box::new(stream),
+                };
                 let io = TokioIo::new(stream);
-                if let Err(err) = hyper::server::conn::http1::Builder::new()
+                let conn = hyper::server::conn::http1::Builder::new()
                     .serve_connection(io, service)
-                    .with_upgrades()
-                    .await
-                {
+                    .with_upgrades();
+                tokio::pin!(conn);
+                let result = tokio::select! {
+                    result = conn.as_mut() => result,
+                    _ = closing.changed() => {
+                        conn.as_mut().graceful_shutdown();
+                        conn.await
+                    }
+                };
+                if let Err(err) = result {
                     error!("Failed to serve connection: {}", err);
                 }
             });
         }
+
+        // Stop accepting and let in-flight connections drain
+        drop(listener);
+        info!(
+            "Shutting down, waiting for {} connection(s)",
+            connections.len()
+        );
+        let _ = closing_tx.send(true);
+        let drained = tokio::time::timeout(grace_period, async {
+            while connections.join_next().await.is_some() {}
+        })
+        .await;
+        if drained.is_err() {
+            warn!(
+                "Aborting {} connection(s) still open after {:?}",
+                connections.len(),
+                grace_period
+            );
+            connections.shutdown().await;
+        }
+
+        info!("Server on {} stopped", local_addr);
+        Ok(())
     }
 }
 
+/// Accept the next connection; under the Wait policy a free slot gates `accept`
+async fn accept(
+    listener: &TcpListener,
+    slots: &Option<Arc<Semaphore>>,
+    policy: BacklogPolicy,
+) -> Result<(TcpStream, SocketAddr, Option<OwnedSemaphorePermit>), ServerError> {
+    let permit = match (slots, policy) {
+        (Some(slots), BacklogPolicy::Wait) => Some(slots.clone().acquire_owned().await?),
+        _ => None,
+    };
+    let (stream, remote_addr) = listener.accept().await?;
+    Ok((stream, remote_addr, permit))
+}
+
 impl Default for HyperServer {
     fn default() -> Self {
         Self::new()
@@ -88,9 +646,13 @@ impl Default for HyperServer {
 }
 
 /// Handle HTTP requests
-pub async fn handle_request(
-    req: Request<hyper::body::Incoming>,
-    _io: &SocketIo,
+///
+/// `GET /health` reports liveness details, `GET /ready` answers 503 until the SMCP
+/// namespace is registered. CORS preflights and headers are handled by the server
+/// around this handler, see [`CorsConfig`].
+pub async fn handle_request<B>(
+    req: Request<B>,
+    state: &ServerState,
 ) -> Result<Response<Full<Bytes>>, Infallible> {
     let response = match (req.method(), req.uri().path()) {
         (&Method::GET, "/") => Response::builder()
@@ -99,7 +661,28 @@ pub async fn handle_request(
             .unwrap(),
         (&Method::GET, "/health") => Response::builder()
             .status(StatusCode::OK)
-            .body(Full::new(Bytes::from("{\"status\":\"ok\"}")))
+            .header("content-type", "application/json")
+            .body(Full::new(Bytes::from(
+                serde_json::to_vec(&state.health()).unwrap(),
+            )))
+            .unwrap(),
+        (&Method::GET, "/ready") => {
+            let (status, body) = if state.is_ready() {
+                (StatusCode::OK, "{\"ready\":true}")
+            } else {
+                (StatusCode::SERVICE_UNAVAILABLE, "{\"ready\":false}")
+            };
+            Response::builder()
+                .status(status)
+                .header("content-type", "application/json")
+                .body(Full::new(Bytes::from(body)))
+                .unwrap()
+        }
+        #[cfg(feature = "metrics")]
+        (&Method::GET, "/metrics") => Response::builder()
+            .status(StatusCode::OK)
+            .header("content-type", "text/plain; version=0.0.4")
+            .body(Full::new(Bytes::from(state.metrics.render())))
             .unwrap(),
         (&Method::GET, "/socket.io/") => {
             // Socket.IO will handle these requests through the layer
@@ -121,6 +704,11 @@ pub async fn handle_request(
 pub struct HyperServerBuilder {
     layer: Option<SmcpServerLayer>,
     addr: Option<SocketAddr>,
+    limits: ConnectionLimits,
+    shutdown_grace_period: Duration,
+    tls: Option<(PathBuf, PathBuf)>,
+    cors: Option<CorsConfig>,
+    body_limits: BodyLimits,
 }
 
 impl HyperServerBuilder {
@@ -129,6 +717,11 @@ impl HyperServerBuilder {
         Self {
             layer: None,
             addr: None,
+            limits: ConnectionLimits::default(),
+            shutdown_grace_period: DEFAULT_SHUTDOWN_GRACE_PERIOD,
+            tls: None,
+            cors: None,
+            body_limits: BodyLimits::default(),
         }
     }
 
@@ -138,22 +731,79 @@ impl HyperServerBuilder {
         self
     }
 
+    /// Set the maximum request body size in bytes
+    pub fn with_max_body_size(mut self, max: usize) -> Self {
+        self.body_limits.max_body_size = max;
+        self
+    }
+
+    /// Set how long a client gets to send the whole request body
+    pub fn with_body_read_timeout(mut self, timeout: Duration) -> Self {
+        self.body_limits.read_timeout = timeout;
+        self
+    }
+
+    /// Answer cross-origin requests from the given origins
+    pub fn with_cors(mut self, cors: CorsConfig) -> Self {
+        self.cors = Some(cors);
+        self
+    }
+
+    /// Serve HTTPS with the PEM encoded certificate chain and private key, loaded by `build`
+    pub fn with_tls(mut self, cert_path: impl Into<PathBuf>, key_path: impl Into<PathBuf>) -> Self {
+        self.tls = Some((cert_path.into(), key_path.into()));
+        self
+    }
+
     /// Set the server address
     pub fn with_addr(mut self, addr: SocketAddr) -> Self {
         self.addr = Some(addr);
         self
     }
 
-    /// Build the HyperServer
-    pub fn build(self) -> HyperServer {
-        let mut server = HyperServer::new();
+    /// Set the maximum number of concurrently served connections
+    pub fn with_max_connections(mut self, max: usize) -> Self {
+        self.limits.max_connections = Some(max);
+        self
+    }
+
+    /// Set the maximum number of concurrent connections from one IP
+    pub fn with_max_connections_per_ip(mut self, max: usize) -> Self {
+        self.limits.max_connections_per_ip = Some(max);
+        self
+    }
+
+    /// Set what happens to connections beyond `max_connections`
+    pub fn with_backlog_policy(mut self, policy: BacklogPolicy) -> Self {
+        self.limits.backlog_policy = policy;
+        self
+    }
+
+    /// Set how long in-flight connections may run after a shutdown signal
+    pub fn with_shutdown_grace_period(mut self, grace_period: Duration) -> Self {
+        self.shutdown_grace_period = grace_period;
+        self
+    }
+
+    /// Build the HyperServer, loading the TLS certificate and key if configured
+    pub fn build(self) -> Result<HyperServer, TlsError> {
+        let mut server = HyperServer::new()
+            .with_limits(self.limits)
+            .with_body_limits(self.body_limits)
+            .with_shutdown_grace_period(self.shutdown_grace_period);
         if let Some(layer) = self.layer {
             server = server.with_layer(layer);
         }
         if let Some(addr) = self.addr {
             server.addr = addr;
         }
-        server
+        if let Some(cors) = self.cors {
+            server = server.with_cors(cors);
+        }
+        if let Some((cert_path, key_path)) = self.tls {
+            server = server.with_tls_acceptor(load_tls_acceptor(&cert_path, &key_path)?);
+        }
+        Ok(server)
     }
 }
 
@@ -164,7 +814,7 @@ impl Default for HyperServerBuilder {
 }
 
 /// Convenience function to quickly run a server with default configuration
-pub async fn run_server(addr: SocketAddr) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+pub async fn run_server(addr: SocketAddr) -> Result<(), ServerError> {
     // Build SMCP layer with default configuration
     let layer = smcp_server_core::SmcpServerBuilder::new()
         .build_layer()
@@ -173,7 +823,7 @@ pub async fn run_server(addr: SocketAddr) -> Result<(), Box<dyn std::error::Erro
     let server = HyperServerBuilder::new()
         .with_layer(layer)
         .with_addr(addr)
-        .build();
+        .build()?;
 
     server.run(addr).await
 }
@@ -193,5 +843,33 @@ mod tests {
         let builder = HyperServerBuilder::new();
         assert!(builder.layer.is_none());
         assert!(builder.addr.is_none());
+        assert!(builder.limits.max_connections.is_none());
+        assert!(builder.tls.is_none());
+        assert!(builder.cors.is_none());
+    }
+
+    #[test]
+    fn test_cors_origin_matching() {
+        let cors = CorsConfig::new(["https://app.example.com"]);
+        assert!(cors.allows(&HeaderValue::from_static("https://app.example.com")));
+        assert!(!cors.allows(&HeaderValue::from_static("https://evil.example.com")));
+        assert!(CorsConfig::new(["*"]).allows(&HeaderValue::from_static("https://any.example")));
+        assert!(!CorsConfig::default().allows(&HeaderValue::from_static("https://any.example")));
+    }
+
+    #[test]
+    fn test_per_ip_connections_released_on_drop() {
+        let connections = Arc::new(PerIpConnections::default());
+        let ip: IpAddr = "127.0.0.1".parse().unwrap();
+
+        let first = connections.try_register(ip, 1).unwrap();
+        assert!(connections.try_register(ip, 1).is_none());
+        assert!(connections
+            .try_register("10.0.0.1".parse().unwrap(), 1)
+            .is_some());
+
+        drop(first);
+        assert!(connections.try_register(ip, 1).is_some());
+        assert!(connections.counts.lock().unwrap().is_empty());
     }
 }
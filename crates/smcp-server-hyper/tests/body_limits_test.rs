@@ -0,0 +1,89 @@
+//! Request body size and read timeout tests for HyperServer
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use smcp_server_core::SmcpServerBuilder;
+use smcp_server_hyper::HyperServerBuilder;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+async fn serve(max_body_size: usize, read_timeout: Duration) -> SocketAddr {
+    let layer = SmcpServerBuilder::new()
+        .build_layer()
+        .expect("failed to build SMCP layer");
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = HyperServerBuilder::new()
+        .with_layer(layer)
+        .with_max_body_size(max_body_size)
+        .with_body_read_timeout(read_timeout)
+        .build()
+        .expect("failed to build HyperServer");
+    tokio::spawn(server.serve(listener));
+    addr
+}
+
+/// Write raw request bytes and return the response status line
+async fn send(addr: SocketAddr, request: &[u8]) -> String {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream.write_all(request).await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    response.lines().next().unwrap_or_default().to_string()
+}
+
+fn post(path: &str, extra_headers: &str, body: &str) -> Vec<u8> {
+    format!(
+        "POST {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n{}\r\n{}",
+        path, extra_headers, body
+    )
+    .into_bytes()
+}
+
+#[tokio::test]
+async fn test_oversized_body_rejected_with_413() {
+    let addr = serve(16, Duration::from_secs(5)).await;
+    let body = "x".repeat(64);
+
+    let status = send(
+        addr,
+        &post("/", &format!("Content-Length: {}\r\n", body.len()), &body),
+    )
+    .await;
+    assert_eq!(status, "HTTP/1.1 413 Payload Too Large");
+}
+
+#[tokio::test]
+async fn test_oversized_chunked_body_rejected_with_413() {
+    let addr = serve(16, Duration::from_secs(5)).await;
+    let chunk = "x".repeat(32);
+    let body = format!("{:x}\r\n{}\r\n0\r\n\r\n", chunk.len(), chunk);
+
+    let status = send(addr, &post("/", "Transfer-Encoding: chunked\r\n", &body)).await;
+    assert_eq!(status, "HTTP/1.1 413 Payload Too Large");
+}
+
+#[tokio::test]
+async fn test_body_within_limit_is_served() {
+    let addr = serve(16, Duration::from_secs(5)).await;
+
+    let status = send(addr, &post("/health", "Content-Length: 2\r\n", "{}")).await;
+    assert_eq!(status, "HTTP/1.1 404 Not Found");
+
+    let status = send(
+        addr,
+        b"GET /health HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+    )
+    .await;
+    assert_eq!(status, "HTTP/1.1 200 OK");
+}
+
+#[tokio::test]
+async fn test_slow_body_times_out_with_408() {
+    let addr = serve(1024, Duration::from_millis(100)).await;
+
+    // Declare ten bytes but only send two, then stall
+    let status = send(addr, &post("/", "Content-Length: 10\r\n", "ab")).await;
+    assert_eq!(status, "HTTP/1.1 408 Request Timeout");
+}
@@ -0,0 +1,88 @@
+//! Connection limit tests for HyperServer
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use smcp_server_core::SmcpServerBuilder;
+use smcp_server_hyper::{BacklogPolicy, HyperServerBuilder};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::timeout;
+
+const HEALTH_REQUEST: &[u8] = b"GET /health HTTP/1.1\r\nHost: localhost\r\n\r\n";
+
+async fn start_server(max_connections: usize, policy: BacklogPolicy) -> SocketAddr {
+    let layer = SmcpServerBuilder::new()
+        .build_layer()
+        .expect("failed to build SMCP layer");
+    let server = HyperServerBuilder::new()
+        .with_layer(layer)
+        .with_max_connections(max_connections)
+        .with_backlog_policy(policy)
+        .build()
+        .expect("failed to build HyperServer");
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(server.serve(listener));
+    addr
+}
+
+/// Send a health request on a keep-alive connection and read the response
+async fn health(stream: &mut TcpStream) -> std::io::Result<String> {
+    stream.write_all(HEALTH_REQUEST).await?;
+    let mut buf = vec![0u8; 1024];
+    let n = stream.read(&mut buf).await?;
+    Ok(String::from_utf8_lossy(&buf[..n]).to_string())
+}
+
+#[tokio::test]
+async fn test_connection_over_limit_is_rejected() {
+    let addr = start_server(1, BacklogPolicy::Reject).await;
+
+    let mut first = TcpStream::connect(addr).await.unwrap();
+    assert!(health(&mut first).await.unwrap().contains("200 OK"));
+
+    // The second connection is accepted by the OS but closed by the server
+    let mut second = TcpStream::connect(addr).await.unwrap();
+    let response = timeout(Duration::from_secs(2), health(&mut second))
+        .await
+        .expect("rejected connection should be closed promptly");
+    assert!(response.map(|r| r.is_empty()).unwrap_or(true));
+
+    // The existing connection is still served
+    assert!(health(&mut first).await.unwrap().contains("200 OK"));
+
+    // Once the first connection ends a new one is served
+    drop(first);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let mut third = TcpStream::connect(addr).await.unwrap();
+    assert!(health(&mut third).await.unwrap().contains("200 OK"));
+}
+
+#[tokio::test]
+async fn test_connection_over_limit_waits_for_slot() {
+    let addr = start_server(1, BacklogPolicy::Wait).await;
+
+    let mut first = TcpStream::connect(addr).await.unwrap();
+    assert!(health(&mut first).await.unwrap().contains("200 OK"));
+
+    // The second connection is deferred while the first holds the only slot
+    let mut second = TcpStream::connect(addr).await.unwrap();
+    second.write_all(HEALTH_REQUEST).await.unwrap();
+    let mut buf = vec![0u8; 1024];
+    assert!(
+        timeout(Duration::from_millis(300), second.read(&mut buf))
+            .await
+            .is_err(),
+        "deferred connection must not be served yet"
+    );
+    assert!(health(&mut first).await.unwrap().contains("200 OK"));
+
+    drop(first);
+    let n = timeout(Duration::from_secs(2), second.read(&mut buf))
+        .await
+        .expect("deferred connection should be served after a slot frees")
+        .unwrap();
+    assert!(String::from_utf8_lossy(&buf[..n]).contains("200 OK"));
+}
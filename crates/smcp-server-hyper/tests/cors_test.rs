@@ -0,0 +1,96 @@
+//! CORS tests for HyperServer
+
+use std::net::SocketAddr;
+
+use smcp_server_core::SmcpServerBuilder;
+use smcp_server_hyper::{CorsConfig, HyperServerBuilder};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+const ORIGIN: &str = "https://app.example.com";
+
+/// Send a request and return the status line and lowercased response headers
+async fn request(
+    addr: SocketAddr,
+    method: &str,
+    path: &str,
+    headers: &[(&str, &str)],
+) -> (String, String) {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let mut request = format!(
+        "{} {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n",
+        method, path
+    );
+    for (name, value) in headers {
+        request.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    let (head, _) = response.split_once("\r\n\r\n").unwrap();
+    let (status, headers) = head.split_once("\r\n").unwrap_or((head, ""));
+    (status.to_string(), headers.to_lowercase())
+}
+
+async fn serve(cors: Option<CorsConfig>) -> SocketAddr {
+    let layer = SmcpServerBuilder::new()
+        .build_layer()
+        .expect("failed to build SMCP layer");
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let mut builder = HyperServerBuilder::new().with_layer(layer);
+    if let Some(cors) = cors {
+        builder = builder.with_cors(cors);
+    }
+    let server = builder.build().expect("failed to build HyperServer");
+    tokio::spawn(server.serve(listener));
+    addr
+}
+
+#[tokio::test]
+async fn test_preflight_answered_for_allowed_origin() {
+    let addr = serve(Some(CorsConfig::new([ORIGIN]))).await;
+
+    let (status, headers) = request(
+        addr,
+        "OPTIONS",
+        "/socket.io/?EIO=4&transport=polling",
+        &[
+            ("Origin", ORIGIN),
+            ("Access-Control-Request-Method", "POST"),
+        ],
+    )
+    .await;
+    assert_eq!(status, "HTTP/1.1 204 No Content");
+    assert!(headers.contains(&format!("access-control-allow-origin: {}", ORIGIN)));
+    assert!(headers.contains("access-control-allow-methods: get, post, options"));
+    assert!(headers.contains("access-control-allow-headers: content-type"));
+}
+
+#[tokio::test]
+async fn test_get_with_configured_origin_carries_header() {
+    let addr = serve(Some(CorsConfig::new([ORIGIN]))).await;
+
+    let (status, headers) = request(addr, "GET", "/health", &[("Origin", ORIGIN)]).await;
+    assert_eq!(status, "HTTP/1.1 200 OK");
+    assert!(headers.contains(&format!("access-control-allow-origin: {}", ORIGIN)));
+
+    let (_, headers) = request(
+        addr,
+        "GET",
+        "/health",
+        &[("Origin", "https://other.example.com")],
+    )
+    .await;
+    assert!(!headers.contains("access-control-allow-origin"));
+}
+
+#[tokio::test]
+async fn test_no_cors_headers_by_default() {
+    let addr = serve(None).await;
+
+    let (status, headers) = request(addr, "GET", "/", &[("Origin", ORIGIN)]).await;
+    assert_eq!(status, "HTTP/1.1 200 OK");
+    assert!(!headers.contains("access-control-allow-origin"));
+}
@@ -0,0 +1,79 @@
+//! Health and readiness endpoint tests for HyperServer
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use serde_json::Value;
+use smcp_server_core::{ServerState, SmcpHandler, SmcpServerBuilder, SmcpServerLayer};
+use smcp_server_hyper::HyperServerBuilder;
+use socketioxide::SocketIo;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Send a GET request and return the status line and body
+async fn get(addr: SocketAddr, path: &str) -> (String, String) {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+        path
+    );
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    (head.lines().next().unwrap().to_string(), body.to_string())
+}
+
+async fn serve(layer: SmcpServerLayer) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = HyperServerBuilder::new()
+        .with_layer(layer)
+        .build()
+        .expect("failed to build HyperServer");
+    tokio::spawn(server.serve(listener));
+    addr
+}
+
+#[tokio::test]
+async fn test_health_reports_sessions_and_uptime() {
+    let layer = SmcpServerBuilder::new()
+        .build_layer()
+        .expect("failed to build SMCP layer");
+    let addr = serve(layer).await;
+
+    let (status, body) = get(addr, "/health").await;
+    assert_eq!(status, "HTTP/1.1 200 OK");
+    let health: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(health["status"], "ok");
+    assert_eq!(health["sessions"].as_u64(), Some(0));
+    assert!(health["uptime_secs"].is_u64());
+    assert_eq!(health["namespace_registered"], true);
+}
+
+#[tokio::test]
+async fn test_ready_flips_after_init() {
+    let built = SmcpServerBuilder::new()
+        .build_layer()
+        .expect("failed to build SMCP layer");
+    // A fresh Socket.IO instance without the SMCP namespace stands in for an uninitialized layer
+    let (layer, io) = SocketIo::builder().build_layer();
+    let state = ServerState {
+        io: Arc::new(io.clone()),
+        ..built.state
+    };
+    let addr = serve(SmcpServerLayer {
+        io: io.clone(),
+        layer,
+        state: state.clone(),
+    })
+    .await;
+
+    let (status, _) = get(addr, "/ready").await;
+    assert_eq!(status, "HTTP/1.1 503 Service Unavailable");
+
+    SmcpHandler::register_handlers(&io, state);
+    let (status, body) = get(addr, "/ready").await;
+    assert_eq!(status, "HTTP/1.1 200 OK");
+    assert_eq!(body, "{\"ready\":true}");
+}
@@ -22,7 +22,7 @@ use tokio::sync::Mutex;
 use tokio::time::sleep;
 
 use smcp::{events, SMCP_NAMESPACE};
-use smcp_server_core::auth::{AuthError, AuthenticationProvider};
+use smcp_server_core::auth::{AuthError, AuthenticationProvider, JwtClaims};
 
 // 导入测试工具函数
 // ack_to_sender 函数定义在下面，因为无法从其他测试模块导入
@@ -38,8 +38,8 @@ impl AuthenticationProvider for NoAuthProvider {
         &self,
         _headers: &HeaderMap,
         _auth: Option<&serde_json::Value>,
-    ) -> Result<(), AuthError> {
-        Ok(())
+    ) -> Result<Option<JwtClaims>, AuthError> {
+        Ok(None)
     }
 }
 
@@ -70,7 +70,8 @@ impl TestServer {
         let server = HyperServerBuilder::new()
             .with_layer(layer)
             .with_addr(addr)
-            .build();
+            .build()
+            .expect("failed to build HyperServer");
 
         // 创建关闭信号通道
         let (shutdown_tx, mut shutdown_rx) = tokio::sync::oneshot::channel();
@@ -0,0 +1,43 @@
+//! Prometheus metrics endpoint tests for HyperServer
+
+#![cfg(feature = "metrics")]
+
+use std::time::Duration;
+
+use smcp_server_core::SmcpServerBuilder;
+use smcp_server_hyper::HyperServerBuilder;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+#[tokio::test]
+async fn test_metrics_endpoint_reports_tool_calls() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let layer = SmcpServerBuilder::new()
+        .build_layer()
+        .expect("failed to build SMCP layer");
+
+    // Simulate one forwarded tool call as the handler records it
+    let metrics = layer.state.metrics.clone();
+    metrics.record_tool_call(true);
+    metrics.observe_forward_latency(Duration::from_millis(12));
+
+    let server = HyperServerBuilder::new()
+        .with_layer(layer)
+        .build()
+        .expect("failed to build HyperServer");
+    tokio::spawn(server.serve(listener));
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream
+        .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .await
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    assert!(response.contains("# TYPE smcp_tool_calls_total counter"));
+    assert!(response.contains("smcp_tool_calls_total 1"));
+    assert!(response.contains("smcp_forward_latency_seconds_count 1"));
+}
@@ -0,0 +1,83 @@
+//! Graceful shutdown tests for HyperServer
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use smcp_server_core::SmcpServerBuilder;
+use smcp_server_hyper::HyperServerBuilder;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, timeout};
+
+type RunResult = Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+/// Start a server with `run_with_shutdown`, returning its address, shutdown trigger and task
+async fn start_server(
+    grace_period: Duration,
+) -> (SocketAddr, oneshot::Sender<()>, JoinHandle<RunResult>) {
+    let addr = TcpListener::bind("127.0.0.1:0")
+        .await
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let layer = SmcpServerBuilder::new()
+        .build_layer()
+        .expect("failed to build SMCP layer");
+    let server = HyperServerBuilder::new()
+        .with_layer(layer)
+        .with_shutdown_grace_period(grace_period)
+        .build()
+        .expect("failed to build HyperServer");
+
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+    let handle = tokio::spawn(server.run_with_shutdown(addr, async {
+        let _ = shutdown_rx.await;
+    }));
+    sleep(Duration::from_millis(100)).await;
+    (addr, shutdown_tx, handle)
+}
+
+#[tokio::test]
+async fn test_run_with_shutdown_resolves_after_signal() {
+    let (addr, shutdown_tx, handle) = start_server(Duration::from_secs(5)).await;
+
+    // An idle keep-alive connection must not hold up shutdown
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream
+        .write_all(b"GET /health HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .await
+        .unwrap();
+    let mut buf = vec![0u8; 1024];
+    let n = stream.read(&mut buf).await.unwrap();
+    assert!(String::from_utf8_lossy(&buf[..n]).contains("200 OK"));
+
+    shutdown_tx.send(()).unwrap();
+    timeout(Duration::from_secs(2), handle)
+        .await
+        .expect("run_with_shutdown should resolve once drained")
+        .unwrap()
+        .expect("run_with_shutdown should return Ok");
+
+    // The connection was closed and no new ones are accepted
+    assert_eq!(stream.read(&mut buf).await.unwrap_or(0), 0);
+    assert!(TcpStream::connect(addr).await.is_err());
+}
+
+#[tokio::test]
+async fn test_run_with_shutdown_bounded_by_grace_period() {
+    let (addr, shutdown_tx, handle) = start_server(Duration::from_millis(300)).await;
+
+    // A request that never completes keeps its connection busy
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream.write_all(b"GET /health HTTP/1.1\r\n").await.unwrap();
+    sleep(Duration::from_millis(100)).await;
+
+    shutdown_tx.send(()).unwrap();
+    timeout(Duration::from_secs(2), handle)
+        .await
+        .expect("shutdown should not wait past the grace period")
+        .unwrap()
+        .expect("run_with_shutdown should return Ok");
+}
@@ -0,0 +1,113 @@
+//! TLS tests for HyperServer
+
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use smcp_server_core::SmcpServerBuilder;
+use smcp_server_hyper::{HyperServerBuilder, TlsError};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::rustls::{self, pki_types::ServerName};
+use tokio_rustls::TlsConnector;
+
+/// Write a self-signed certificate for `localhost` into `dir`
+fn write_self_signed(dir: &Path) -> (PathBuf, PathBuf, rcgen::CertifiedKey) {
+    let certified = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+    let cert_path = dir.join("cert.pem");
+    let key_path = dir.join("key.pem");
+    std::fs::write(&cert_path, certified.cert.pem()).unwrap();
+    std::fs::write(&key_path, certified.key_pair.serialize_pem()).unwrap();
+    (cert_path, key_path, certified)
+}
+
+fn builder() -> HyperServerBuilder {
+    let layer = SmcpServerBuilder::new()
+        .build_layer()
+        .expect("failed to build SMCP layer");
+    HyperServerBuilder::new().with_layer(layer)
+}
+
+/// Client that trusts only the given self-signed certificate
+fn connector(certified: &rcgen::CertifiedKey) -> TlsConnector {
+    let mut roots = rustls::RootCertStore::empty();
+    roots.add(certified.cert.der().clone()).unwrap();
+    let config = rustls::ClientConfig::builder_with_provider(Arc::new(
+        rustls::crypto::ring::default_provider(),
+    ))
+    .with_safe_default_protocol_versions()
+    .unwrap()
+    .with_root_certificates(roots)
+    .with_no_client_auth();
+    TlsConnector::from(Arc::new(config))
+}
+
+async fn start_server(server: smcp_server_hyper::HyperServer) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(server.serve(listener));
+    addr
+}
+
+#[tokio::test]
+async fn test_https_health() {
+    let dir = tempfile::tempdir().unwrap();
+    let (cert_path, key_path, certified) = write_self_signed(dir.path());
+    let server = builder()
+        .with_tls(cert_path, key_path)
+        .build()
+        .expect("TLS configuration should load");
+    let addr = start_server(server).await;
+
+    let tcp = TcpStream::connect(addr).await.unwrap();
+    let mut tls = connector(&certified)
+        .connect(ServerName::try_from("localhost").unwrap(), tcp)
+        .await
+        .expect("TLS handshake should succeed");
+    tls.write_all(b"GET /health HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .await
+        .unwrap();
+    let mut response = String::new();
+    tls.read_to_string(&mut response).await.unwrap();
+
+    assert!(response.starts_with("HTTP/1.1 200 OK"), "{}", response);
+    assert!(response.contains("{\"status\":\"ok\"}"));
+}
+
+#[tokio::test]
+async fn test_plaintext_client_rejected_by_tls_server() {
+    let dir = tempfile::tempdir().unwrap();
+    let (cert_path, key_path, _) = write_self_signed(dir.path());
+    let server = builder().with_tls(cert_path, key_path).build().unwrap();
+    let addr = start_server(server).await;
+
+    let mut tcp = TcpStream::connect(addr).await.unwrap();
+    tcp.write_all(b"GET /health HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .await
+        .unwrap();
+    let mut response = Vec::new();
+    let _ = tcp.read_to_end(&mut response).await;
+    assert!(!String::from_utf8_lossy(&response).contains("200 OK"));
+}
+
+#[test]
+fn test_tls_load_errors_are_typed() {
+    let dir = tempfile::tempdir().unwrap();
+    let (cert_path, key_path, _) = write_self_signed(dir.path());
+
+    let missing = dir.path().join("missing.pem");
+    assert!(matches!(
+        builder().with_tls(&missing, &key_path).build(),
+        Err(TlsError::Read { path, .. }) if path == missing
+    ));
+
+    // A certificate file holds no private key
+    assert!(matches!(
+        builder().with_tls(&cert_path, &cert_path).build(),
+        Err(TlsError::NoPrivateKey(_))
+    ));
+    assert!(matches!(
+        builder().with_tls(&key_path, &key_path).build(),
+        Err(TlsError::NoCertificate(_))
+    ));
+}
@@ -52,7 +52,8 @@ async fn test_websocket_upgrade() {
     let server = HyperServerBuilder::new()
         .with_layer(layer)
         .with_addr(addr)
-        .build();
+        .build()
+        .expect("failed to build HyperServer");
 
     // 创建TcpListener来获取可用端口
     let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
@@ -139,7 +140,8 @@ async fn test_websocket_upgrade_with_invalid_role() {
     let server = HyperServerBuilder::new()
         .with_layer(layer)
         .with_addr(addr)
-        .build();
+        .build()
+        .expect("failed to build HyperServer");
 
     // 创建TcpListener来获取可用端口
     let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
@@ -237,7 +239,8 @@ async fn test_websocket_upgrade_with_polling_fallback() {
     let server = HyperServerBuilder::new()
         .with_layer(layer)
         .with_addr(server_addr)
-        .build();
+        .build()
+        .expect("failed to build HyperServer");
 
     // 在后台启动服务器
     let server_handle = tokio::spawn(async move { server.run(server_addr).await });
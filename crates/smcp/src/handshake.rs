@@ -0,0 +1,452 @@
+use crate::ErrorPayload;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// SMCP协议版本
+pub const PROTOCOL_VERSION: &str = "0.1.0";
+
+/// 协议版本的主版本号，无法解析时返回 None
+fn major_version(version: &str) -> Option<u64> {
+    version
+        .trim()
+        .trim_start_matches('v')
+        .split('.')
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// 对端协议版本是否与本端 [`PROTOCOL_VERSION`] 兼容，主版本号一致即兼容
+pub fn is_protocol_compatible(version: &str) -> bool {
+    match (major_version(version), major_version(PROTOCOL_VERSION)) {
+        (Some(peer), Some(local)) => peer == local,
+        _ => false,
+    }
+}
+
+/// 握手URL中携带协议版本的查询参数名
+pub const VERSION_QUERY_KEY: &str = "smcp_version";
+
+/// 握手URL中携带认证信息的查询参数名
+pub const AUTH_QUERY_KEY: &str = "auth";
+
+/// 握手失败时携带错误信息的响应头
+pub const ERROR_HEADER: &str = "x-smcp-error";
+
+/// 协议版本不兼容的握手错误码
+pub const PROTOCOL_MISMATCH_CODE: u16 = 4008;
+
+/// 服务器要求客户端改用轮询传输的关闭码，收到后不应自动重连
+pub const CLOSE_CODE_USE_POLLING: u16 = 4900;
+
+/// 连接断开原因
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DisconnectReason {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl DisconnectReason {
+    /// 从关闭事件的负载中解析断开原因
+    ///
+    /// 支持 `{"code": 4900, "reason": "..."}`（或 `message` 字段）、
+    /// `"4900 reason"` 形式的字符串以及普通字符串。
+    pub fn from_close_payload(value: &serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Object(map) => Self {
+                code: map
+                    .get("code")
+                    .and_then(|c| c.as_u64())
+                    .and_then(|c| u16::try_from(c).ok()),
+                message: map
+                    .get("reason")
+                    .or_else(|| map.get("message"))
+                    .and_then(|m| m.as_str())
+                    .map(|m| m.to_string()),
+            },
+            serde_json::Value::String(text) => {
+                let text = text.trim();
+                let (head, rest) = text.split_once(' ').unwrap_or((text, ""));
+                match head.trim_end_matches(':').parse::<u16>() {
+                    Ok(code) => Self {
+                        code: Some(code),
+                        message: (!rest.trim().is_empty()).then(|| rest.trim().to_string()),
+                    },
+                    Err(_) => Self {
+                        code: None,
+                        message: (!text.is_empty()).then(|| text.to_string()),
+                    },
+                }
+            }
+            serde_json::Value::Number(n) => Self {
+                code: n.as_u64().and_then(|c| u16::try_from(c).ok()),
+                message: None,
+            },
+            _ => Self::default(),
+        }
+    }
+
+    /// 按关闭码判断是否允许自动重连
+    ///
+    /// 协议不兼容（4008）与要求改用轮询（4900）时不应自动重连。
+    pub fn should_reconnect(&self) -> bool {
+        !matches!(
+            self.code,
+            Some(PROTOCOL_MISMATCH_CODE) | Some(CLOSE_CODE_USE_POLLING)
+        )
+    }
+}
+
+/// 从 Socket.IO 关闭事件负载解析断开原因
+#[cfg(feature = "socketio")]
+pub fn disconnect_reason_from_payload(payload: rust_socketio::Payload) -> DisconnectReason {
+    match payload {
+        rust_socketio::Payload::Text(values, _) => values
+            .first()
+            .map(DisconnectReason::from_close_payload)
+            .unwrap_or_default(),
+        #[allow(deprecated)]
+        rust_socketio::Payload::String(s, _) => {
+            DisconnectReason::from_close_payload(&serde_json::Value::String(s))
+        }
+        rust_socketio::Payload::Binary(_, _) => DisconnectReason::default(),
+    }
+}
+
+/// 重连状态
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReconnectState {
+    /// 已连接（或尚未开始重连）
+    #[default]
+    Connected,
+    /// 正在进行第 `attempt` 次重连
+    Reconnecting { attempt: u32 },
+    /// 重连次数耗尽，不再重连
+    Failed,
+}
+
+/// 重连尝试计数器
+///
+/// 每次重连尝试前调用 `on_attempt`，超过上限后进入终止的 `Failed` 状态；
+/// 连接成功后调用 `on_connected` 清零。`max_attempts` 为 `None` 时不限次数。
+#[derive(Debug, Clone, Default)]
+pub struct ReconnectTracker {
+    max_attempts: Option<u32>,
+    attempts: u32,
+    state: ReconnectState,
+}
+
+impl ReconnectTracker {
+    /// 创建计数器
+    pub fn new(max_attempts: Option<u32>) -> Self {
+        Self {
+            max_attempts,
+            ..Default::default()
+        }
+    }
+
+    /// 记录一次重连尝试并返回新状态
+    ///
+    /// 已失败时保持 `Failed`。
+    pub fn on_attempt(&mut self) -> ReconnectState {
+        if self.state == ReconnectState::Failed {
+            return self.state;
+        }
+        self.attempts += 1;
+        self.state = match self.max_attempts {
+            Some(max) if self.attempts > max => ReconnectState::Failed,
+            _ => ReconnectState::Reconnecting {
+                attempt: self.attempts,
+            },
+        };
+        self.state
+    }
+
+    /// 连接成功，清零计数；已失败时忽略
+    pub fn on_connected(&mut self) {
+        if self.state != ReconnectState::Failed {
+            self.attempts = 0;
+            self.state = ReconnectState::Connected;
+        }
+    }
+
+    /// 当前状态
+    pub fn state(&self) -> ReconnectState {
+        self.state
+    }
+
+    /// 失败前已进行的重连次数
+    pub fn failed_attempts(&self) -> u32 {
+        match self.state {
+            ReconnectState::Failed => self.attempts - 1,
+            _ => self.attempts,
+        }
+    }
+}
+
+/// 构建握手URL
+///
+/// 将命名空间追加到 `base` 的路径上，并附加协议版本与可选的认证查询参数，
+/// `base` 中已有的查询参数会被保留。
+pub fn build_handshake_url(
+    base: &str,
+    namespace: &str,
+    version: &str,
+    auth: Option<&str>,
+) -> String {
+    let (base, fragment) = match base.split_once('#') {
+        Some((base, fragment)) => (base, Some(fragment)),
+        None => (base, None),
+    };
+    let (path, query) = match base.split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (base, None),
+    };
+
+    let namespace = namespace.trim_matches('/');
+    let mut url = path.trim_end_matches('/').to_string();
+    if !namespace.is_empty() {
+        url.push('/');
+        url.push_str(namespace);
+    }
+
+    let mut params: Vec<String> = query
+        .into_iter()
+        .flat_map(|q| q.split('&'))
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| pair.to_string())
+        .collect();
+    params.push(format!(
+        "{}={}",
+        VERSION_QUERY_KEY,
+        encode_component(version)
+    ));
+    if let Some(auth) = auth {
+        params.push(format!("{}={}", AUTH_QUERY_KEY, encode_component(auth)));
+    }
+
+    url.push('?');
+    url.push_str(&params.join("&"));
+    if let Some(fragment) = fragment {
+        url.push('#');
+        url.push_str(fragment);
+    }
+    url
+}
+
+/// 从握手失败的响应中提取 4008 错误
+///
+/// 优先读取 `x-smcp-error` 响应头，其次解析响应体，响应体可以是扁平的
+/// `{"code": 4008, "message": ...}` 或嵌套的 `{"error": {...}}`。
+/// 成功状态码或非 4008 错误返回 `None`。
+pub fn extract_4008_payload(
+    status: u16,
+    headers: &HashMap<String, String>,
+    body: &str,
+) -> Option<ErrorPayload> {
+    if (200..300).contains(&status) {
+        return None;
+    }
+
+    let from_header = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(ERROR_HEADER))
+        .and_then(|(_, value)| serde_json::from_str::<ErrorPayload>(value).ok());
+
+    let payload = from_header.or_else(|| {
+        let value: serde_json::Value = serde_json::from_str(body).ok()?;
+        let value = match value.get("error") {
+            Some(nested) if nested.is_object() => nested.clone(),
+            _ => value,
+        };
+        serde_json::from_value::<ErrorPayload>(value).ok()
+    })?;
+
+    (payload.code == PROTOCOL_MISMATCH_CODE).then_some(payload)
+}
+
+/// 从连接失败的错误信息中提取 4008 错误
+///
+/// Socket.IO 客户端只以文本形式暴露握手失败的原因，其中的 JSON 响应体按
+/// [`extract_4008_payload`] 的规则解析；能走到这里的握手均已失败。
+pub fn extract_4008_from_error(error: &str) -> Option<ErrorPayload> {
+    let body = &error[error.find('{')?..];
+    extract_4008_payload(400, &HashMap::new(), body)
+}
+
+/// 按 RFC 3986 对查询参数值进行百分号编码
+fn encode_component(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_protocol_compatible() {
+        assert!(is_protocol_compatible(PROTOCOL_VERSION));
+        assert!(is_protocol_compatible("0.9.3"));
+        assert!(is_protocol_compatible("v0.2"));
+        assert!(!is_protocol_compatible("1.0.0"));
+        assert!(!is_protocol_compatible("not-a-version"));
+    }
+
+    #[test]
+    fn test_build_handshake_url_without_query() {
+        let url = build_handshake_url("http://localhost:8000/", "/smcp", "0.1.0", None);
+        assert_eq!(url, "http://localhost:8000/smcp?smcp_version=0.1.0");
+    }
+
+    #[test]
+    fn test_build_handshake_url_keeps_existing_query() {
+        let url = build_handshake_url(
+            "https://example.com/base?tenant=a&region=eu",
+            "/smcp",
+            PROTOCOL_VERSION,
+            Some("token with/slash"),
+        );
+        assert_eq!(
+            url,
+            "https://example.com/base/smcp?tenant=a&region=eu&smcp_version=0.1.0&auth=token%20with%2Fslash"
+        );
+    }
+
+    #[test]
+    fn test_extract_4008_from_header() {
+        let mut headers = HashMap::new();
+        headers.insert(
+            "X-SMCP-Error".to_string(),
+            r#"{"code":4008,"message":"unsupported protocol version","data":{"supported":["0.1.0"]}}"#
+                .to_string(),
+        );
+
+        let payload = extract_4008_payload(400, &headers, "").unwrap();
+        assert_eq!(payload.code, PROTOCOL_MISMATCH_CODE);
+        assert_eq!(payload.message, "unsupported protocol version");
+        assert_eq!(payload.data.unwrap()["supported"][0], "0.1.0");
+    }
+
+    #[test]
+    fn test_extract_4008_from_flat_body() {
+        let body = r#"{"code":4008,"message":"unsupported protocol version"}"#;
+        let payload = extract_4008_payload(400, &HashMap::new(), body).unwrap();
+        assert_eq!(payload.code, 4008);
+        assert!(payload.data.is_none());
+
+        let nested = r#"{"error":{"code":4008,"message":"unsupported protocol version"}}"#;
+        assert!(extract_4008_payload(400, &HashMap::new(), nested).is_some());
+    }
+
+    #[test]
+    fn test_extract_4008_from_error_text() {
+        let error = r#"EngineIO Error: handshake rejected: {"code":4008,"message":"unsupported protocol version"}"#;
+        let payload = extract_4008_from_error(error).unwrap();
+        assert_eq!(payload.code, PROTOCOL_MISMATCH_CODE);
+        assert_eq!(payload.message, "unsupported protocol version");
+
+        assert!(extract_4008_from_error("Connection refused (os error 111)").is_none());
+    }
+
+    #[test]
+    fn test_disconnect_reason_from_close_payload() {
+        let reason = DisconnectReason::from_close_payload(&serde_json::json!({
+            "code": 4900,
+            "reason": "websocket disabled, use polling"
+        }));
+        assert_eq!(reason.code, Some(CLOSE_CODE_USE_POLLING));
+        assert_eq!(
+            reason.message.as_deref(),
+            Some("websocket disabled, use polling")
+        );
+        assert!(!reason.should_reconnect());
+
+        let reason = DisconnectReason::from_close_payload(&serde_json::json!("4900: use polling"));
+        assert_eq!(reason.code, Some(4900));
+        assert_eq!(reason.message.as_deref(), Some("use polling"));
+
+        let reason = DisconnectReason::from_close_payload(&serde_json::json!("transport close"));
+        assert_eq!(reason.code, None);
+        assert_eq!(reason.message.as_deref(), Some("transport close"));
+        assert!(reason.should_reconnect());
+    }
+
+    #[cfg(feature = "socketio")]
+    #[test]
+    fn test_disconnect_reason_from_socketio_payload() {
+        let payload = rust_socketio::Payload::Text(
+            vec![serde_json::json!({"code": 4900, "reason": "use polling"})],
+            None,
+        );
+        let reason = disconnect_reason_from_payload(payload);
+        assert_eq!(reason.code, Some(4900));
+        assert_eq!(reason.message.as_deref(), Some("use polling"));
+        assert!(!reason.should_reconnect());
+
+        let reason = disconnect_reason_from_payload(rust_socketio::Payload::Text(vec![], None));
+        assert_eq!(reason, DisconnectReason::default());
+        assert!(reason.should_reconnect());
+    }
+
+    #[test]
+    fn test_reconnect_tracker_fails_after_max_attempts() {
+        let mut tracker = ReconnectTracker::new(Some(2));
+        assert_eq!(
+            tracker.on_attempt(),
+            ReconnectState::Reconnecting { attempt: 1 }
+        );
+        assert_eq!(
+            tracker.on_attempt(),
+            ReconnectState::Reconnecting { attempt: 2 }
+        );
+        assert_eq!(tracker.on_attempt(), ReconnectState::Failed);
+        assert_eq!(tracker.failed_attempts(), 2);
+
+        // 失败是终止状态
+        tracker.on_connected();
+        assert_eq!(tracker.state(), ReconnectState::Failed);
+        assert_eq!(tracker.on_attempt(), ReconnectState::Failed);
+    }
+
+    #[test]
+    fn test_reconnect_tracker_resets_on_connect() {
+        let mut tracker = ReconnectTracker::new(Some(1));
+        tracker.on_attempt();
+        tracker.on_connected();
+        assert_eq!(tracker.state(), ReconnectState::Connected);
+        assert_eq!(
+            tracker.on_attempt(),
+            ReconnectState::Reconnecting { attempt: 1 }
+        );
+
+        let mut unlimited = ReconnectTracker::new(None);
+        for _ in 0..100 {
+            unlimited.on_attempt();
+        }
+        assert_eq!(
+            unlimited.state(),
+            ReconnectState::Reconnecting { attempt: 100 }
+        );
+    }
+
+    #[test]
+    fn test_extract_4008_ignores_other_responses() {
+        let body = r#"{"code":4008,"message":"unsupported protocol version"}"#;
+        assert!(extract_4008_payload(200, &HashMap::new(), body).is_none());
+
+        let other = r#"{"code":4001,"message":"unauthorized"}"#;
+        assert!(extract_4008_payload(401, &HashMap::new(), other).is_none());
+        assert!(extract_4008_payload(500, &HashMap::new(), "not json").is_none());
+    }
+}
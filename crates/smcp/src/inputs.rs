@@ -0,0 +1,199 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+
+/// MCP服务器输入项，通过 `type` 字段区分
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type")]
+pub enum MCPServerInput {
+    /// 字符串输入
+    PromptString(PromptStringInput),
+    /// 选择输入
+    PickString(PickStringInput),
+    /// 数字输入
+    ///
+    /// 线上标签固定为 `prompt_number`（与其他变体的 PascalCase 标签不同），这是协议约定的
+    /// 名称，修改会破坏已有配置与对端实现。
+    #[serde(rename = "prompt_number")]
+    PromptNumber(PromptNumberInput),
+    /// 命令输入
+    Command(CommandInput),
+}
+
+impl MCPServerInput {
+    /// 获取输入ID
+    pub fn id(&self) -> &str {
+        match self {
+            MCPServerInput::PromptString(input) => &input.id,
+            MCPServerInput::PickString(input) => &input.id,
+            MCPServerInput::PromptNumber(input) => &input.id,
+            MCPServerInput::Command(input) => &input.id,
+        }
+    }
+
+    /// 获取输入描述
+    pub fn description(&self) -> &str {
+        match self {
+            MCPServerInput::PromptString(input) => &input.description,
+            MCPServerInput::PickString(input) => &input.description,
+            MCPServerInput::PromptNumber(input) => &input.description,
+            MCPServerInput::Command(input) => &input.description,
+        }
+    }
+
+    /// 获取默认值
+    pub fn default(&self) -> Option<serde_json::Value> {
+        match self {
+            MCPServerInput::PromptString(input) => input
+                .default
+                .as_ref()
+                .map(|s| serde_json::Value::String(s.clone())),
+            MCPServerInput::PickString(input) => input
+                .default
+                .as_ref()
+                .map(|s| serde_json::Value::String(s.clone())),
+            MCPServerInput::PromptNumber(input) => input.default.map(|v| input.to_json(v)),
+            // Command 类型不支持默认值
+            MCPServerInput::Command(_input) => None,
+        }
+    }
+
+    /// 是否为密码输入
+    pub fn is_password(&self) -> bool {
+        matches!(
+            self,
+            MCPServerInput::PromptString(PromptStringInput {
+                password: Some(true),
+                ..
+            })
+        )
+    }
+
+    /// 返回去除密码默认值后的副本，用于发送给 Agent
+    pub fn redacted(&self) -> Self {
+        match self {
+            MCPServerInput::PromptString(input) if self.is_password() => {
+                MCPServerInput::PromptString(PromptStringInput {
+                    default: None,
+                    ..input.clone()
+                })
+            }
+            other => other.clone(),
+        }
+    }
+}
+
+/// 字符串输入类型
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PromptStringInput {
+    /// 输入ID
+    pub id: String,
+    /// 描述
+    pub description: String,
+    /// 默认值
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+    /// 是否为密码
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<bool>,
+}
+
+/// 选择输入类型
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PickStringInput {
+    /// 输入ID
+    pub id: String,
+    /// 描述
+    pub description: String,
+    /// 选项
+    #[serde(default)]
+    pub options: Vec<String>,
+    /// 默认值
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+}
+
+/// 数字输入类型
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PromptNumberInput {
+    /// 输入ID
+    pub id: String,
+    /// 描述
+    pub description: String,
+    /// 默认值
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<f64>,
+    /// 最小值（含）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    /// 最大值（含）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+    /// 是否只接受整数
+    #[serde(default)]
+    pub integer: bool,
+}
+
+impl PromptNumberInput {
+    /// 无法交互时使用的值：默认值，其次最小值，否则为 0
+    pub fn fallback(&self) -> f64 {
+        self.default.or(self.min).unwrap_or(0.0)
+    }
+
+    /// 转换为 JSON 数值，`integer` 为真时输出整数
+    pub fn to_json(&self, value: f64) -> serde_json::Value {
+        if self.integer {
+            serde_json::Value::from(value as i64)
+        } else {
+            serde_json::Value::from(value)
+        }
+    }
+}
+
+/// 命令输入类型
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CommandInput {
+    /// 输入ID
+    pub id: String,
+    /// 描述
+    pub description: String,
+    /// 命令
+    pub command: String,
+    /// 参数
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub args: Option<HashMap<String, String>>,
+}
+
+/// 序列化输入列表，密码输入的默认值不会被发送
+pub(crate) fn serialize_redacted<S>(
+    inputs: &Option<Vec<MCPServerInput>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    inputs
+        .as_ref()
+        .map(|inputs| {
+            inputs
+                .iter()
+                .map(MCPServerInput::redacted)
+                .collect::<Vec<_>>()
+        })
+        .serialize(serializer)
+}
+
+/// 反序列化输入列表，兼容旧版的任意 JSON 数组：无法识别的条目会被忽略
+pub(crate) fn deserialize_lenient<'de, D>(
+    deserializer: D,
+) -> Result<Option<Vec<MCPServerInput>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let values = Option::<Vec<serde_json::Value>>::deserialize(deserializer)?;
+    Ok(values.map(|values| {
+        values
+            .into_iter()
+            .filter_map(|value| serde_json::from_value(value).ok())
+            .collect()
+    }))
+}
@@ -1,9 +1,90 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use uuid::Uuid;
 
+pub mod handshake;
+pub mod inputs;
+
+pub use handshake::{
+    build_handshake_url, extract_4008_from_error, is_protocol_compatible, DisconnectReason,
+    ReconnectState, ReconnectTracker, PROTOCOL_VERSION,
+};
+pub use inputs::{
+    CommandInput, MCPServerInput, PickStringInput, PromptNumberInput, PromptStringInput,
+};
+
 /// SMCP协议的命名空间
 pub const SMCP_NAMESPACE: &str = "/smcp";
 
+/// 服务器未公布上限时使用的请求超时上限（秒）
+pub const DEFAULT_MAX_TIMEOUT_SECS: u64 = 300;
+
+/// 客户端主动退出时的离开原因
+pub const LEAVE_REASON_SHUTDOWN: &str = "shutdown";
+
+/// 连接意外断开、由服务器代发离开通知时的离开原因
+pub const LEAVE_REASON_DISCONNECT: &str = "disconnect";
+
+/// 错误码常量定义
+pub mod error_codes {
+    /// 请求不合法
+    pub const INVALID_REQUEST: u16 = 4000;
+
+    /// 协议版本不兼容
+    pub const VERSION_MISMATCH: u16 = crate::handshake::PROTOCOL_MISMATCH_CODE;
+
+    /// 认证失败
+    pub const UNAUTHORIZED: u16 = 4010;
+
+    /// 输入解析失败
+    pub const RESOLVER: u16 = 4013;
+
+    /// 请求的目标不存在
+    pub const NOT_FOUND: u16 = 4040;
+
+    /// 等待响应超时
+    pub const TIMEOUT: u16 = 4080;
+
+    /// 消息超过协议允许的大小
+    pub const PAYLOAD_TOO_LARGE: u16 = 4130;
+}
+
+/// 结构化错误信息，供客户端按错误码或类别分支处理
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ErrorPayload {
+    /// 错误码，见 [`error_codes`]
+    pub code: u16,
+    /// 错误类别，如 `not_found`、`timeout`
+    #[serde(default)]
+    pub category: String,
+    pub message: String,
+    /// 稍后重试是否可能成功
+    #[serde(default)]
+    pub retriable: bool,
+    /// 附加数据
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+}
+
+impl ErrorPayload {
+    /// 创建不可重试、不带附加数据的错误信息
+    pub fn new(code: u16, category: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            category: category.into(),
+            message: message.into(),
+            retriable: false,
+            data: None,
+        }
+    }
+
+    /// 设置是否可重试
+    pub fn with_retriable(mut self, retriable: bool) -> Self {
+        self.retriable = retriable;
+        self
+    }
+}
+
 /// SMCP事件常量定义
 pub mod events {
     /// 客户端请求获取工具列表
@@ -14,6 +95,14 @@ pub mod events {
     pub const CLIENT_GET_DESKTOP: &str = "client:get_desktop";
     /// 客户端工具调用请求
     pub const CLIENT_TOOL_CALL: &str = "client:tool_call";
+    /// 客户端请求获取提示词列表
+    pub const CLIENT_GET_PROMPTS: &str = "client:get_prompts";
+    /// 客户端请求获取单个提示词
+    pub const CLIENT_GET_PROMPT: &str = "client:get_prompt";
+    /// 客户端请求获取资源模板列表
+    pub const CLIENT_GET_RESOURCE_TEMPLATES: &str = "client:get_resource_templates";
+    /// 客户端请求读取资源（如大结果生成的资源链接）
+    pub const CLIENT_READ_RESOURCE: &str = "client:read_resource";
 
     /// 服务器加入办公室请求
     pub const SERVER_JOIN_OFFICE: &str = "server:join_office";
@@ -29,6 +118,8 @@ pub mod events {
     pub const SERVER_TOOL_CALL_CANCEL: &str = "server:tool_call_cancel";
     /// 服务器列出房间请求
     pub const SERVER_LIST_ROOM: &str = "server:list_room";
+    /// 服务器工具调用进度上报
+    pub const SERVER_TOOL_PROGRESS: &str = "server:tool_progress";
 
     /// 通知取消工具调用
     pub const NOTIFY_TOOL_CALL_CANCEL: &str = "notify:tool_call_cancel";
@@ -42,6 +133,11 @@ pub mod events {
     pub const NOTIFY_UPDATE_TOOL_LIST: &str = "notify:update_tool_list";
     /// 通知更新桌面
     pub const NOTIFY_UPDATE_DESKTOP: &str = "notify:update_desktop";
+    /// 通知工具调用进度
+    pub const NOTIFY_TOOL_PROGRESS: &str = "notify:tool_progress";
+
+    /// 通知服务器能力（连接建立后发送给该连接）
+    pub const NOTIFY_SERVER_HELLO: &str = "notify:server_hello";
 
     /// 通用通知前缀
     pub const NOTIFY_PREFIX: &str = "notify:";
@@ -80,6 +176,8 @@ impl Default for ReqId {
 pub enum Role {
     Agent,
     Computer,
+    /// 系统会话（管理工具、监控采集等观察者），需服务器授权，不计入 Agent
+    System,
 }
 
 impl std::fmt::Display for Role {
@@ -87,6 +185,7 @@ impl std::fmt::Display for Role {
         match self {
             Role::Agent => write!(f, "agent"),
             Role::Computer => write!(f, "computer"),
+            Role::System => write!(f, "system"),
         }
     }
 }
@@ -107,6 +206,28 @@ pub struct ToolCallReq {
     pub tool_name: String,
     pub params: serde_json::Value,
     pub timeout: i32,
+    /// 幂等键，重试同一逻辑操作时保持不变；缺省时使用 req_id
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
+}
+
+impl ToolCallReq {
+    /// Computer 幂等缓存使用的键：优先 idempotency_key，否则为 req_id
+    ///
+    /// 同一键只对应一次工具调用：Computer 会拒绝携带相同键但工具名或参数不同的请求。
+    pub fn dedup_key(&self) -> &str {
+        self.idempotency_key
+            .as_deref()
+            .unwrap_or(self.base.req_id.as_str())
+    }
+
+    /// 限制在 `max_secs` 以内的超时秒数，非正值按上限处理
+    pub fn timeout_secs(&self, max_secs: u64) -> u64 {
+        match u64::try_from(self.timeout) {
+            Ok(secs) if secs > 0 => secs.min(max_secs),
+            _ => max_secs,
+        }
+    }
 }
 
 /// 获取计算机配置请求
@@ -126,8 +247,14 @@ pub struct UpdateComputerConfigReq {
 /// 获取计算机配置返回
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GetComputerConfigRet {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub inputs: Option<Vec<serde_json::Value>>,
+    /// 服务器所需的输入项，序列化时会去除密码输入的默认值
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "inputs::serialize_redacted",
+        deserialize_with = "inputs::deserialize_lenient"
+    )]
+    pub inputs: Option<Vec<MCPServerInput>>,
     pub servers: serde_json::Value,
 }
 
@@ -140,6 +267,21 @@ pub struct ToolCallRet {
     pub is_error: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub req_id: Option<ReqId>,
+    /// 调用附加信息，由 Computer 填充，可省略
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub call_info: Option<CallInfo>,
+}
+
+/// 工具调用附加信息
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CallInfo {
+    /// 处理本次调用的 MCP 服务器
+    pub server: String,
+    /// 调用耗时（毫秒）
+    pub elapsed_ms: u64,
+    /// 是否命中幂等缓存（相同幂等键的重复调用）
+    #[serde(default)]
+    pub cache_hit: bool,
 }
 
 /// 获取工具请求
@@ -168,6 +310,103 @@ pub struct GetToolsRet {
     pub req_id: ReqId,
 }
 
+/// 获取提示词列表请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetPromptsReq {
+    #[serde(flatten)]
+    pub base: AgentCallData,
+    pub computer: String,
+}
+
+/// 提示词参数定义
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SMCPPromptArgument {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub required: Option<bool>,
+}
+
+/// SMCP提示词定义
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SMCPPrompt {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<Vec<SMCPPromptArgument>>,
+}
+
+/// 获取提示词列表返回
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetPromptsRet {
+    pub prompts: Vec<SMCPPrompt>,
+    pub req_id: ReqId,
+}
+
+/// 获取单个提示词请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetPromptReq {
+    #[serde(flatten)]
+    pub base: AgentCallData,
+    pub computer: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<std::collections::HashMap<String, String>>,
+}
+
+/// 获取单个提示词返回（符合 MCP GetPromptResult 标准）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetPromptRet {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub messages: Vec<serde_json::Value>,
+    pub req_id: ReqId,
+}
+
+/// 获取资源模板列表请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetResourceTemplatesReq {
+    #[serde(flatten)]
+    pub base: AgentCallData,
+    pub computer: String,
+}
+
+/// SMCP资源模板定义（对应 MCP ResourceTemplate，`uri_template` 为 RFC 6570 URI 模板）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SMCPResourceTemplate {
+    pub uri_template: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+}
+
+/// 获取资源模板列表返回
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetResourceTemplatesRet {
+    pub resource_templates: Vec<SMCPResourceTemplate>,
+    pub req_id: ReqId,
+}
+
+/// 读取资源请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadResourceReq {
+    #[serde(flatten)]
+    pub base: AgentCallData,
+    pub computer: String,
+    pub uri: String,
+}
+
+/// 读取资源返回（符合 MCP ReadResourceResult 标准）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadResourceRet {
+    pub contents: Vec<serde_json::Value>,
+    pub req_id: ReqId,
+}
+
 /// 代理调用数据（基类）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentCallData {
@@ -180,13 +419,41 @@ pub struct AgentCallData {
 pub struct EnterOfficeReq {
     pub role: Role,
     pub name: String,
+    /// 办公室 ID，留空时由服务器使用其默认办公室（若已配置）
+    #[serde(default)]
     pub office_id: String,
+    /// 办公室元数据，仅在办公室尚无元数据时生效
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<OfficeMeta>,
+    /// 客户端使用的协议版本，缺省视为旧版客户端并放行
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub a2c_version: Option<String>,
+}
+
+/// 办公室元数据
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OfficeMeta {
+    /// 便于展示的名称
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// 所有者
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    /// 创建时间（Unix 秒），未提供时由服务器在登记时填充
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<u64>,
+    /// 自定义标签
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub labels: HashMap<String, String>,
 }
 
 /// 离开办公室请求
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LeaveOfficeReq {
     pub office_id: String,
+    /// 离开原因，如 `shutdown`，随离开通知一起广播
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
 }
 
 /// 获取桌面请求
@@ -218,6 +485,15 @@ pub struct ListRoomReq {
     #[serde(flatten)]
     pub base: AgentCallData,
     pub office_id: String,
+    /// 最多返回的会话数，省略时返回全部
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    /// 跳过的会话数，省略时为 0
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<usize>,
+    /// 只返回指定角色的会话
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<Role>,
 }
 
 /// 会话信息
@@ -234,6 +510,25 @@ pub struct SessionInfo {
 pub struct ListRoomRet {
     pub sessions: Vec<SessionInfo>,
     pub req_id: ReqId,
+    /// 按角色过滤后、分页前的会话总数
+    #[serde(default)]
+    pub total: usize,
+    /// 办公室元数据，未登记时省略
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub office_meta: Option<OfficeMeta>,
+}
+
+/// 服务器能力，连接建立后通过 `notify:server_hello` 下发
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ServerCapabilities {
+    /// 服务器使用的协议版本
+    pub protocol_version: String,
+    /// 服务器对外公布的基础URL，未配置时省略
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_url: Option<String>,
+    /// 服务器允许的请求超时上限（秒），更长的超时会被截断；旧版服务器不提供
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_timeout_secs: Option<u64>,
 }
 
 /// 进入办公室通知
@@ -254,6 +549,9 @@ pub struct LeaveOfficeNotification {
     pub computer: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub agent: Option<String>,
+    /// 离开原因：主动退出时为 [`LEAVE_REASON_SHUTDOWN`]，连接意外断开时为 [`LEAVE_REASON_DISCONNECT`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
 }
 
 /// 更新MCP配置通知
@@ -268,6 +566,36 @@ pub struct UpdateToolListNotification {
     pub computer: String,
 }
 
+/// 工具调用进度通知，可携带工具已产生的部分结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolProgressNotification {
+    pub computer: String,
+    /// 对应工具调用请求的 `req_id`
+    pub req_id: ReqId,
+    /// 当前进度，随调用推进单调递增
+    pub progress: f64,
+    /// 总量，未知时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// 本次进度携带的部分结果（MCP content 块），按到达顺序拼接即为工具的增量输出
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub content: Vec<serde_json::Value>,
+}
+
+/// 带办公室内序号的通知
+///
+/// 同一办公室的通知按 `seq` 递增的顺序送达；序号字段与通知字段平铺在同一个对象中，
+/// 不关心序号的客户端可直接按原通知类型反序列化。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sequenced<T> {
+    /// 办公室内单调递增的序号，从 1 开始
+    pub seq: u64,
+    #[serde(flatten)]
+    pub data: T,
+}
+
 /// 通知类型枚举
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
@@ -278,6 +606,7 @@ pub enum Notification {
     UpdateMCPConfig(UpdateMCPConfigNotification),
     UpdateToolList(UpdateToolListNotification),
     UpdateDesktop,
+    ToolProgress(ToolProgressNotification),
 }
 
 #[cfg(test)]
@@ -303,6 +632,9 @@ mod tests {
 
         let de: Role = serde_json::from_str("\"computer\"").unwrap();
         assert!(matches!(de, Role::Computer));
+
+        let de: Role = serde_json::from_str("\"system\"").unwrap();
+        assert_eq!(de, Role::System);
     }
 
     #[test]
@@ -325,6 +657,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_leave_office_reason_backward_compatible() {
+        // 旧版本消息没有 reason 字段
+        let legacy: LeaveOfficeNotification =
+            serde_json::from_str(r#"{"office_id":"office1","computer":"c1"}"#).unwrap();
+        assert!(legacy.reason.is_none());
+        let json = serde_json::to_value(&legacy).unwrap();
+        assert!(json.get("reason").is_none());
+
+        let req: LeaveOfficeReq = serde_json::from_str(r#"{"office_id":"office1"}"#).unwrap();
+        assert!(req.reason.is_none());
+
+        let req = LeaveOfficeReq {
+            office_id: "office1".to_string(),
+            reason: Some(LEAVE_REASON_SHUTDOWN.to_string()),
+        };
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(json["reason"], "shutdown");
+    }
+
     #[test]
     fn test_tool_call_ret_mcp_format() {
         // 测试成功的工具调用返回（MCP CallToolResult 格式）
@@ -335,6 +687,7 @@ mod tests {
             })]),
             is_error: Some(false),
             req_id: Some(ReqId::from_string("test123".to_string())),
+            call_info: None,
         };
 
         let json = serde_json::to_string(&success_ret).unwrap();
@@ -366,6 +719,7 @@ mod tests {
             })]),
             is_error: Some(true),
             req_id: None,
+            call_info: None,
         };
 
         let json = serde_json::to_string(&error_ret).unwrap();
@@ -390,6 +744,7 @@ mod tests {
             content: None,
             is_error: None,
             req_id: None,
+            call_info: None,
         };
 
         let json = serde_json::to_string(&minimal_ret).unwrap();
@@ -399,6 +754,48 @@ mod tests {
         assert_eq!(parsed, serde_json::json!({}));
     }
 
+    #[test]
+    fn test_tool_call_ret_call_info() {
+        let ret = ToolCallRet {
+            content: Some(vec![]),
+            is_error: Some(false),
+            req_id: None,
+            call_info: Some(CallInfo {
+                server: "fs".to_string(),
+                elapsed_ms: 12,
+                cache_hit: false,
+            }),
+        };
+        let parsed = serde_json::to_value(&ret).unwrap();
+        assert_eq!(parsed["call_info"]["server"], "fs");
+        assert_eq!(parsed["call_info"]["elapsed_ms"], 12);
+        assert_eq!(parsed["call_info"]["cache_hit"], false);
+
+        // 未携带 call_info 的旧响应仍可解析
+        let legacy: ToolCallRet =
+            serde_json::from_str(r#"{"content":[],"isError":false}"#).unwrap();
+        assert!(legacy.call_info.is_none());
+    }
+
+    #[test]
+    fn test_tool_call_req_dedup_key() {
+        let json = r#"{"agent":"a1","req_id":"r1","computer":"c1","tool_name":"t","params":{},"timeout":5}"#;
+        let mut req: ToolCallReq = serde_json::from_str(json).unwrap();
+        assert!(req.idempotency_key.is_none());
+        assert_eq!(req.dedup_key(), "r1");
+        assert!(serde_json::to_value(&req)
+            .unwrap()
+            .get("idempotency_key")
+            .is_none());
+
+        req.idempotency_key = Some("op-1".to_string());
+        assert_eq!(req.dedup_key(), "op-1");
+        assert_eq!(
+            serde_json::to_value(&req).unwrap()["idempotency_key"],
+            "op-1"
+        );
+    }
+
     #[test]
     fn test_tool_call_ret_roundtrip() {
         // 测试序列化和反序列化的往返一致性
@@ -409,6 +806,7 @@ mod tests {
             })]),
             is_error: Some(false),
             req_id: Some(ReqId::new()),
+            call_info: None,
         };
 
         let json = serde_json::to_string(&original).unwrap();
@@ -418,4 +816,289 @@ mod tests {
         assert_eq!(original.is_error, deserialized.is_error);
         assert_eq!(original.req_id, deserialized.req_id);
     }
+
+    #[test]
+    fn test_error_payload_shape() {
+        let payload =
+            ErrorPayload::new(error_codes::TIMEOUT, "timeout", "timed out").with_retriable(true);
+        assert_eq!(
+            serde_json::to_value(&payload).unwrap(),
+            serde_json::json!({
+                "code": 4080,
+                "category": "timeout",
+                "message": "timed out",
+                "retriable": true
+            })
+        );
+
+        // 握手错误只携带 code/message
+        let handshake: ErrorPayload =
+            serde_json::from_str(r#"{"code":4008,"message":"unsupported"}"#).unwrap();
+        assert_eq!(handshake.code, error_codes::VERSION_MISMATCH);
+        assert!(handshake.category.is_empty());
+        assert!(!handshake.retriable);
+    }
+
+    #[test]
+    fn test_enter_office_req_version_roundtrip() {
+        let req = EnterOfficeReq {
+            role: Role::Agent,
+            name: "agent1".to_string(),
+            office_id: "office1".to_string(),
+            meta: None,
+            a2c_version: Some(PROTOCOL_VERSION.to_string()),
+        };
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(json["a2c_version"], PROTOCOL_VERSION);
+        let de: EnterOfficeReq = serde_json::from_value(json).unwrap();
+        assert_eq!(de.a2c_version.as_deref(), Some(PROTOCOL_VERSION));
+
+        // 旧版客户端不携带版本字段
+        let legacy: EnterOfficeReq = serde_json::from_value(serde_json::json!({
+            "role": "computer",
+            "name": "c1",
+            "office_id": "office1"
+        }))
+        .unwrap();
+        assert!(legacy.a2c_version.is_none());
+        assert!(serde_json::to_value(&legacy)
+            .unwrap()
+            .get("a2c_version")
+            .is_none());
+    }
+
+    fn sample_inputs() -> Vec<MCPServerInput> {
+        vec![
+            MCPServerInput::PromptString(PromptStringInput {
+                id: "api_key".to_string(),
+                description: "API Key".to_string(),
+                default: Some("secret".to_string()),
+                password: Some(true),
+            }),
+            MCPServerInput::PromptString(PromptStringInput {
+                id: "user".to_string(),
+                description: "User name".to_string(),
+                default: Some("alice".to_string()),
+                password: None,
+            }),
+            MCPServerInput::PickString(PickStringInput {
+                id: "region".to_string(),
+                description: "Region".to_string(),
+                options: vec!["us".to_string(), "eu".to_string()],
+                default: Some("eu".to_string()),
+            }),
+            MCPServerInput::Command(CommandInput {
+                id: "token".to_string(),
+                description: "Token command".to_string(),
+                command: "get-token".to_string(),
+                args: Some(std::collections::HashMap::from([(
+                    "scope".to_string(),
+                    "read".to_string(),
+                )])),
+            }),
+        ]
+    }
+
+    #[test]
+    fn test_get_computer_config_ret_inputs_roundtrip() {
+        let ret = GetComputerConfigRet {
+            inputs: Some(sample_inputs()),
+            servers: serde_json::json!({}),
+        };
+
+        let json = serde_json::to_value(&ret).unwrap();
+        assert_eq!(json["inputs"][2]["type"], "PickString");
+        assert_eq!(json["inputs"][3]["type"], "Command");
+
+        let parsed: GetComputerConfigRet = serde_json::from_value(json).unwrap();
+        let inputs = parsed.inputs.unwrap();
+        assert_eq!(inputs.len(), 4);
+        assert_eq!(inputs[1..], sample_inputs()[1..]);
+    }
+
+    #[test]
+    fn test_prompt_number_input_serde() {
+        let input: MCPServerInput = serde_json::from_value(serde_json::json!({
+            "type": "prompt_number",
+            "id": "port",
+            "description": "Port",
+            "default": 8080,
+            "min": 1,
+            "max": 65535,
+            "integer": true
+        }))
+        .unwrap();
+
+        assert_eq!(input.id(), "port");
+        assert_eq!(input.default(), Some(serde_json::json!(8080)));
+        let MCPServerInput::PromptNumber(ref number) = input else {
+            panic!("expected PromptNumber");
+        };
+        assert_eq!(number.min, Some(1.0));
+        assert_eq!(number.max, Some(65535.0));
+
+        let json = serde_json::to_value(&input).unwrap();
+        assert_eq!(json["type"], "prompt_number");
+        assert_eq!(
+            serde_json::from_value::<MCPServerInput>(json).unwrap(),
+            input
+        );
+
+        let minimal: MCPServerInput = serde_json::from_value(serde_json::json!({
+            "type": "prompt_number",
+            "id": "ratio",
+            "description": "Ratio"
+        }))
+        .unwrap();
+        let json = serde_json::to_value(&minimal).unwrap();
+        assert!(json.get("default").is_none());
+        assert_eq!(json["integer"], false);
+    }
+
+    #[test]
+    fn test_prompt_number_wire_tag_is_snake_case() {
+        let input = MCPServerInput::PromptNumber(PromptNumberInput {
+            id: "port".to_string(),
+            description: "Port".to_string(),
+            default: Some(8080.0),
+            min: None,
+            max: None,
+            integer: true,
+        });
+
+        // 协议约定的标签为 prompt_number，而非变体名 PromptNumber
+        let text = serde_json::to_string(&input).unwrap();
+        assert!(text.contains(r#""type":"prompt_number""#));
+        assert_eq!(
+            serde_json::from_str::<MCPServerInput>(&text).unwrap(),
+            input
+        );
+        assert!(serde_json::from_value::<MCPServerInput>(serde_json::json!({
+            "type": "PromptNumber",
+            "id": "port",
+            "description": "Port"
+        }))
+        .is_err());
+    }
+
+    #[test]
+    fn test_get_computer_config_ret_redacts_password_defaults() {
+        let ret = GetComputerConfigRet {
+            inputs: Some(sample_inputs()),
+            servers: serde_json::json!({}),
+        };
+
+        let json = serde_json::to_value(&ret).unwrap();
+        let password = &json["inputs"][0];
+        assert_eq!(password["id"], "api_key");
+        assert_eq!(password["password"], true);
+        assert!(password.get("default").is_none());
+        // 非密码输入保留默认值
+        assert_eq!(json["inputs"][1]["default"], "alice");
+        // 原始数据不受影响
+        assert!(ret.inputs.unwrap()[0].default().is_some());
+    }
+
+    #[test]
+    fn test_get_computer_config_ret_accepts_legacy_inputs() {
+        let parsed: GetComputerConfigRet = serde_json::from_value(serde_json::json!({
+            "inputs": [
+                {"type": "PromptString", "id": "user", "description": "User name"},
+                {"name": "test_input", "type": "stdio"}
+            ],
+            "servers": {}
+        }))
+        .unwrap();
+        let inputs = parsed.inputs.unwrap();
+        assert_eq!(inputs.len(), 1);
+        assert_eq!(inputs[0].id(), "user");
+
+        let parsed: GetComputerConfigRet =
+            serde_json::from_value(serde_json::json!({"servers": {}})).unwrap();
+        assert!(parsed.inputs.is_none());
+    }
+
+    #[test]
+    fn test_sequenced_notification_is_flat() {
+        let notification = Sequenced {
+            seq: 7,
+            data: UpdateToolListNotification {
+                computer: "c1".to_string(),
+            },
+        };
+        let value = serde_json::to_value(&notification).unwrap();
+        assert_eq!(value, serde_json::json!({"seq": 7, "computer": "c1"}));
+
+        let plain: UpdateToolListNotification = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(plain.computer, "c1");
+        let de: Sequenced<UpdateToolListNotification> = serde_json::from_value(value).unwrap();
+        assert_eq!(de.seq, 7);
+        assert_eq!(de.data.computer, "c1");
+    }
+
+    #[test]
+    fn test_tool_progress_notification_serde() {
+        let notification = ToolProgressNotification {
+            computer: "c1".to_string(),
+            req_id: ReqId::from_string("req1".to_string()),
+            progress: 1.0,
+            total: None,
+            message: None,
+            content: vec![],
+        };
+        let value = serde_json::to_value(&notification).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({"computer": "c1", "req_id": "req1", "progress": 1.0})
+        );
+
+        let de: Sequenced<ToolProgressNotification> = serde_json::from_value(serde_json::json!({
+            "seq": 3,
+            "computer": "c1",
+            "req_id": "req1",
+            "progress": 2.0,
+            "total": 4.0,
+            "message": "halfway"
+        }))
+        .unwrap();
+        assert_eq!(de.data.total, Some(4.0));
+        assert_eq!(de.data.message.as_deref(), Some("halfway"));
+        assert!(de.data.content.is_empty());
+
+        let chunk: ToolProgressNotification = serde_json::from_value(serde_json::json!({
+            "computer": "c1",
+            "req_id": "req1",
+            "progress": 1.0,
+            "content": [{"type": "text", "text": "partial"}]
+        }))
+        .unwrap();
+        assert_eq!(
+            chunk.content,
+            vec![serde_json::json!({"type": "text", "text": "partial"})]
+        );
+    }
+
+    #[test]
+    fn test_tool_call_timeout_clamped() {
+        let mut req = ToolCallReq {
+            base: AgentCallData {
+                agent: "office1".to_string(),
+                req_id: ReqId::new(),
+            },
+            computer: "c1".to_string(),
+            tool_name: "echo".to_string(),
+            params: serde_json::json!({}),
+            timeout: 30,
+            idempotency_key: None,
+        };
+        assert_eq!(req.timeout_secs(60), 30);
+
+        req.timeout = i32::MAX;
+        assert_eq!(req.timeout_secs(60), 60);
+
+        req.timeout = 0;
+        assert_eq!(req.timeout_secs(60), 60);
+        req.timeout = -1;
+        assert_eq!(req.timeout_secs(60), 60);
+    }
 }
@@ -32,6 +32,7 @@ fn test_tool_call_req() {
         tool_name: "test_tool".to_string(),
         params: serde_json::json!({"arg1": "value1", "arg2": 42}),
         timeout: 30,
+        idempotency_key: None,
     };
 
     let json = serde_json::to_string(&req).unwrap();
@@ -67,6 +68,48 @@ fn test_smcp_tool() {
     assert!(deserialized.meta.is_none());
 }
 
+#[test]
+fn test_get_prompt_req() {
+    let mut arguments = std::collections::HashMap::new();
+    arguments.insert("language".to_string(), "rust".to_string());
+    let req = GetPromptReq {
+        base: AgentCallData {
+            agent: "agent1".to_string(),
+            req_id: ReqId::from_string("test-req-456".to_string()),
+        },
+        computer: "computer1".to_string(),
+        name: "code_review".to_string(),
+        arguments: Some(arguments),
+    };
+
+    let value = serde_json::to_value(&req).unwrap();
+    assert_eq!(value["agent"], "agent1");
+    assert_eq!(value["req_id"], "test-req-456");
+    assert_eq!(value["arguments"]["language"], "rust");
+
+    let deserialized: GetPromptReq = serde_json::from_value(value).unwrap();
+    assert_eq!(req.name, deserialized.name);
+    assert_eq!(req.arguments, deserialized.arguments);
+}
+
+#[test]
+fn test_smcp_prompt() {
+    let json = serde_json::json!({
+        "name": "code_review",
+        "arguments": [{"name": "language", "required": true}]
+    });
+
+    let prompt: SMCPPrompt = serde_json::from_value(json).unwrap();
+    assert_eq!(prompt.name, "code_review");
+    assert!(prompt.description.is_none());
+    let args = prompt.arguments.as_ref().unwrap();
+    assert_eq!(args[0].name, "language");
+    assert_eq!(args[0].required, Some(true));
+
+    let value = serde_json::to_value(&prompt).unwrap();
+    assert!(value.get("description").is_none());
+}
+
 #[test]
 fn test_enter_office_notification() {
     let notification = EnterOfficeNotification {
@@ -89,6 +132,13 @@ fn test_all_events_constants() {
     assert_eq!(events::CLIENT_GET_CONFIG, "client:get_config");
     assert_eq!(events::CLIENT_GET_DESKTOP, "client:get_desktop");
     assert_eq!(events::CLIENT_TOOL_CALL, "client:tool_call");
+    assert_eq!(events::CLIENT_GET_PROMPTS, "client:get_prompts");
+    assert_eq!(events::CLIENT_GET_PROMPT, "client:get_prompt");
+    assert_eq!(
+        events::CLIENT_GET_RESOURCE_TEMPLATES,
+        "client:get_resource_templates"
+    );
+    assert_eq!(events::CLIENT_READ_RESOURCE, "client:read_resource");
 
     assert_eq!(events::SERVER_JOIN_OFFICE, "server:join_office");
     assert_eq!(events::SERVER_LEAVE_OFFICE, "server:leave_office");